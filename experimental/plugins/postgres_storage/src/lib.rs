@@ -37,14 +37,14 @@ use libindy::ErrorCode;
 use utils::sequence::SequenceUtils;
 use utils::crypto::base64 as util_base64;
 use utils::ctypes;
-use wql::storage::{WalletStorage, StorageRecord, StorageIterator, Tag, TagName, EncryptedValue};
+use wql::storage::{WalletStorage, StorageRecord, Tag, TagName, EncryptedValue};
 use wql::language;
 use errors::wallet::WalletStorageError;
 use postgres_storage::WalletStorageType;
 
 use self::libc::c_char;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::ffi::CString;
 use std::sync::Mutex;
 use std::str;
@@ -60,7 +60,7 @@ pub extern fn postgresstorage_init() -> libindy::ErrorCode {
         return err;
     }
 
-    libindy::wallet::register_wallet_storage(
+    let err = libindy::wallet::register_wallet_storage(
         postgres_storage_name.as_ptr(),
         PostgresWallet::create,
         PostgresWallet::open,
@@ -86,6 +86,27 @@ pub extern fn postgresstorage_init() -> libindy::ErrorCode {
         PostgresWallet::get_search_total_count,
         PostgresWallet::fetch_search_next_record,
         PostgresWallet::free_search,
+    );
+
+    if err != libindy::ErrorCode::Success {
+        return err;
+    }
+
+    let err = libindy::wallet::register_wallet_storage_aggregation(
+        postgres_storage_name.as_ptr(),
+        PostgresWallet::aggregate_records,
+        PostgresWallet::free_aggregation,
+    );
+
+    if err != libindy::ErrorCode::Success {
+        return err;
+    }
+
+    libindy::wallet::register_wallet_storage_transaction_hooks(
+        postgres_storage_name.as_ptr(),
+        PostgresWallet::begin_transaction,
+        PostgresWallet::commit_transaction,
+        PostgresWallet::rollback_transaction,
     )
 }
 
@@ -112,12 +133,27 @@ struct PostgresWalletRecord {
 }
 
 #[derive(Debug, Clone)]
-struct PostgresWalletRecordSet {
-    idx: usize,
-    records: Vec<PostgresWalletRecord>,
+struct PostgresWalletSearch {
+    query: Option<PostgresWalletSearchQuery>, // None for search of all records
+    after_id: i64,                            // id of the last fetched item
+    skip: i64,                                // records to skip before the first fetched one
+    sorted: bool,                             // sorted searches are paged by offset instead of item id
+    remaining: Option<i64>,                   // records left to fetch if the search is limited
+    records: VecDeque<PostgresWalletRecord>,  // fetched page of records
+    finished: bool,                           // all pages are fetched
     count: usize
 }
 
+#[derive(Debug, Clone)]
+struct PostgresWalletSearchQuery {
+    type_: String,
+    query: language::Operator,
+    options: String
+}
+
+// number of records fetched from the database at once
+const SEARCH_PAGE_SIZE: i64 = 100;
+
 lazy_static! {
     // store a PostgresStorage object (contains a connection) 
     static ref POSTGRES_OPEN_WALLETS: Mutex<HashMap<i32, PostgresStorageContext>> = Default::default();
@@ -134,10 +170,13 @@ lazy_static! {
 }
 
 lazy_static! {
-    // cache of active Postgres searches
-    // TODO figure out  athread-safe PostgresStorageIterator
-    // static ref POSTGRES_ACTIVE_SEARCHES: Mutex<HashMap<i32, Box<::postgres_storage::PostgresStorageIterator>>> = Default::default();
-    static ref POSTGRES_ACTIVE_SEARCHES: Mutex<HashMap<i32, PostgresWalletRecordSet>> = Default::default();
+    // active Postgres searches, records are fetched page by page on demand
+    static ref POSTGRES_ACTIVE_SEARCHES: Mutex<HashMap<i32, PostgresWalletSearch>> = Default::default();
+}
+
+lazy_static! {
+    // aggregation results until they are freed by libindy
+    static ref POSTGRES_ACTIVE_AGGREGATIONS: Mutex<HashMap<i32, CString>> = Default::default();
 }
 
 pub struct PostgresWallet {}
@@ -722,49 +761,53 @@ impl PostgresWallet {
             return ErrorCode::CommonInvalidState;
         }
 
-        let query = language::parse_from_json_encrypted(&query_json).unwrap();
+        let query = match language::parse_from_json_encrypted(&query_json) {
+            Ok(query) => query,
+            Err(_err) => return ErrorCode::WalletQueryError
+        };
+        let search_options: ::postgres_storage::SearchOptions = match serde_json::from_str(&options_json) {
+            Ok(search_options) => search_options,
+            Err(_err) => return ErrorCode::CommonInvalidStructure
+        };
+        if let Some(ref sort_by) = search_options.sort_by {
+            if sort_by.iter().any(|sort| sort.plain_tag_name().is_err()) {
+                return ErrorCode::WalletQueryError;
+            }
+        }
         let wallet_context = handles.get(&xhandle).unwrap();
         let wallet_box = &wallet_context.phandle;
         let storage = &*wallet_box;
 
-        let res = storage.search(&type_.as_bytes(), &query, Some(&options_json));
-
-        match res {
-            Ok(iter) => {
-                // iter: Box<StorageIterator>
-                let total_count = iter.get_total_count();
-                let search_records = _iterator_to_record_set(iter).unwrap();
-                let total_count = match total_count {
-                    Ok(count) => {
-                        match count {
-                            Some(ct) => ct,
-                            None => 0
-                        }
-                    },
-                    _ => search_records.len()
-                };
-                let search_set = PostgresWalletRecordSet {
-                    idx: 0,
-                    records: search_records,
-                    count: total_count
-                };
-
-                let search_handle = SequenceUtils::get_next_id();
-
-                let mut searches = POSTGRES_ACTIVE_SEARCHES.lock().unwrap();
-
-                // TODO store the iterator rather than the fetched records
-                // searches.insert(search_handle, iter);
-                searches.insert(search_handle, search_set);
-
-                unsafe { *handle = search_handle };
-                return ErrorCode::Success
-            },
-            Err(_err) => {
-                // err: WalletStorageError
-                return ErrorCode::WalletStorageError
+        // only the total count is queried here, records are fetched page by page in fetch_search_next_record
+        let total_count = if search_options.retrieve_total_count {
+            match storage.search_total_count(&type_.as_bytes(), &query) {
+                Ok(count) => count.unwrap_or(0),
+                Err(_err) => return ErrorCode::WalletStorageError
             }
-        }
+        } else { 0 };
+
+        let search = PostgresWalletSearch {
+            query: Some(PostgresWalletSearchQuery {
+                type_,
+                query,
+                options: options_json
+            }),
+            after_id: 0,
+            skip: search_options.skip.unwrap_or(0) as i64,
+            sorted: search_options.sort_by.is_some(),
+            remaining: search_options.limit.map(|limit| limit as i64),
+            records: VecDeque::new(),
+            finished: !search_options.retrieve_records || search_options.limit == Some(0),
+            count: total_count
+        };
+
+        let search_handle = SequenceUtils::get_next_id();
+
+        let mut searches = POSTGRES_ACTIVE_SEARCHES.lock().unwrap();
+        searches.insert(search_handle, search);
+
+        unsafe { *handle = search_handle };
+        ErrorCode::Success
     }
 
 
@@ -775,48 +818,24 @@ impl PostgresWallet {
             return ErrorCode::CommonInvalidState;
         }
 
-        let wallet_context = handles.get(&xhandle).unwrap();
-        let wallet_box = &wallet_context.phandle;
-        let storage = &*wallet_box;
+        let search = PostgresWalletSearch {
+            query: None,
+            after_id: 0,
+            skip: 0,
+            sorted: false,
+            remaining: None,
+            records: VecDeque::new(),
+            finished: false,
+            count: 0
+        };
 
-        let res = storage.get_all();
+        let search_handle = SequenceUtils::get_next_id();
 
-        match res {
-            Ok(iter) => {
-                // iter: Box<StorageIterator>
-                let total_count = iter.get_total_count();
-                let search_records = _iterator_to_record_set(iter).unwrap();
-                let total_count = match total_count {
-                    Ok(count) => {
-                        match count {
-                            Some(ct) => ct,
-                            None => 0
-                        }
-                    },
-                    _ => search_records.len()
-                };
-                let search_set = PostgresWalletRecordSet {
-                    idx: 0,
-                    records: search_records,
-                    count: total_count
-                };
-
-                let search_handle = SequenceUtils::get_next_id();
-
-                let mut searches = POSTGRES_ACTIVE_SEARCHES.lock().unwrap();
-
-                // TODO store the iterator rather than the fetched records
-                // searches.insert(search_handle, iter);
-                searches.insert(search_handle, search_set);
-
-                unsafe { *handle = search_handle };
-                return ErrorCode::Success
-            },
-            Err(_err) => {
-                // err: WalletStorageError
-                return ErrorCode::WalletStorageError
-            }
-        }
+        let mut searches = POSTGRES_ACTIVE_SEARCHES.lock().unwrap();
+        searches.insert(search_handle, search);
+
+        unsafe { *handle = search_handle };
+        ErrorCode::Success
     }
 
 
@@ -849,22 +868,32 @@ impl PostgresWallet {
 
         let mut searches = POSTGRES_ACTIVE_SEARCHES.lock().unwrap();
 
-        match searches.get_mut(&search_handle) {
-            Some(records) => {
-                if records.idx < records.records.len() {
-                    let handle = SequenceUtils::get_next_id();
+        let search = match searches.get_mut(&search_handle) {
+            Some(search) => search,
+            None => return ErrorCode::CommonInvalidState
+        };
 
-                    let mut handles = POSTGRES_ACTIVE_RECORDS.lock().unwrap();
-                    handles.insert(handle, records.records.get(records.idx).unwrap().clone());
-                    records.idx = records.idx + 1;
+        if search.records.is_empty() && !search.finished {
+            let storage = match handles.get(&xhandle) {
+                Some(wallet_context) => &*wallet_context.phandle,
+                None => return ErrorCode::WalletInvalidHandle
+            };
+            if let Err(err) = _fetch_search_page(storage, search) {
+                return err;
+            }
+        }
 
-                    unsafe { *record_handle = handle };
-                    ErrorCode::Success
-                } else {
-                    ErrorCode::WalletItemNotFound
-                }
+        match search.records.pop_front() {
+            Some(record) => {
+                let handle = SequenceUtils::get_next_id();
+
+                let mut handles = POSTGRES_ACTIVE_RECORDS.lock().unwrap();
+                handles.insert(handle, record);
+
+                unsafe { *record_handle = handle };
+                ErrorCode::Success
             },
-            None => ErrorCode::CommonInvalidState
+            None => ErrorCode::WalletItemNotFound
         }
     }
 
@@ -887,6 +916,144 @@ impl PostgresWallet {
     }
 
 
+    pub extern fn aggregate_records(xhandle: i32,
+                                    type_: *const c_char,
+                                    query_json: *const c_char,
+                                    group_by_json: *const c_char,
+                                    result_json_p: *mut *const c_char,
+                                    result_handle_p: *mut i32) -> ErrorCode {
+        check_useful_c_str!(type_, ErrorCode::CommonInvalidState);
+        check_useful_c_str!(query_json, ErrorCode::CommonInvalidState);
+        check_useful_c_str!(group_by_json, ErrorCode::CommonInvalidState);
+
+        let handles = POSTGRES_OPEN_WALLETS.lock().unwrap();
+
+        let storage = match handles.get(&xhandle) {
+            Some(wallet_context) => &*wallet_context.phandle,
+            None => return ErrorCode::CommonInvalidState
+        };
+
+        let query = match language::parse_from_json_encrypted(&query_json) {
+            Ok(query) => query,
+            Err(_err) => return ErrorCode::WalletQueryError
+        };
+
+        // plain tag names are passed as "~<base64 encoded encrypted tag name>"
+        let group_by: Vec<String> = match serde_json::from_str(&group_by_json) {
+            Ok(group_by) => group_by,
+            Err(_err) => return ErrorCode::CommonInvalidStructure
+        };
+
+        let group_by: Option<Vec<Vec<u8>>> = group_by.iter()
+            .map(|tag_name| if tag_name.starts_with("~") { util_base64::decode(&tag_name[1..]).ok() } else { None })
+            .collect();
+
+        let group_by = match group_by {
+            Some(group_by) => group_by,
+            None => return ErrorCode::WalletQueryError
+        };
+
+        let groups = match storage.aggregate(&type_.as_bytes(), &query, &group_by) {
+            Ok(groups) => groups,
+            Err(err) => {
+                error!("Error aggregating records. Error details: {:?}", err);
+                return ErrorCode::WalletStorageError;
+            }
+        };
+
+        let result = match serde_json::to_string(&groups) {
+            Ok(result) => result,
+            Err(_err) => return ErrorCode::CommonInvalidState
+        };
+
+        let result = match CString::new(result) {
+            Ok(result) => result,
+            Err(_err) => return ErrorCode::CommonInvalidState
+        };
+        let handle = SequenceUtils::get_next_id();
+
+        unsafe {
+            *result_json_p = result.as_ptr();
+            *result_handle_p = handle;
+        }
+
+        let mut aggregations = POSTGRES_ACTIVE_AGGREGATIONS.lock().unwrap();
+        aggregations.insert(handle, result);
+
+        ErrorCode::Success
+    }
+
+
+    pub extern fn free_aggregation(xhandle: i32, result_handle: i32) -> ErrorCode {
+        let handles = POSTGRES_OPEN_WALLETS.lock().unwrap();
+
+        if !handles.contains_key(&xhandle) {
+            return ErrorCode::CommonInvalidState;
+        }
+
+        let mut aggregations = POSTGRES_ACTIVE_AGGREGATIONS.lock().unwrap();
+
+        match aggregations.remove(&result_handle) {
+            Some(_) => ErrorCode::Success,
+            None => ErrorCode::CommonInvalidState
+        }
+    }
+
+
+    pub extern fn begin_transaction(xhandle: i32) -> ErrorCode {
+        let handles = POSTGRES_OPEN_WALLETS.lock().unwrap();
+
+        let storage = match handles.get(&xhandle) {
+            Some(wallet_context) => &*wallet_context.phandle,
+            None => return ErrorCode::CommonInvalidState
+        };
+
+        match storage.begin_transaction() {
+            Ok(_) => ErrorCode::Success,
+            Err(err) => {
+                error!("Error beginning transaction. Error details: {:?}", err);
+                ErrorCode::WalletStorageError
+            }
+        }
+    }
+
+
+    pub extern fn commit_transaction(xhandle: i32) -> ErrorCode {
+        let handles = POSTGRES_OPEN_WALLETS.lock().unwrap();
+
+        let storage = match handles.get(&xhandle) {
+            Some(wallet_context) => &*wallet_context.phandle,
+            None => return ErrorCode::CommonInvalidState
+        };
+
+        match storage.commit_transaction() {
+            Ok(_) => ErrorCode::Success,
+            Err(err) => {
+                error!("Error committing transaction. Error details: {:?}", err);
+                ErrorCode::WalletStorageError
+            }
+        }
+    }
+
+
+    pub extern fn rollback_transaction(xhandle: i32) -> ErrorCode {
+        let handles = POSTGRES_OPEN_WALLETS.lock().unwrap();
+
+        let storage = match handles.get(&xhandle) {
+            Some(wallet_context) => &*wallet_context.phandle,
+            None => return ErrorCode::CommonInvalidState
+        };
+
+        match storage.rollback_transaction() {
+            Ok(_) => ErrorCode::Success,
+            Err(err) => {
+                error!("Error rolling back transaction. Error details: {:?}", err);
+                ErrorCode::WalletStorageError
+            }
+        }
+    }
+
+
     pub extern fn close(xhandle: i32) -> ErrorCode {
         let mut handles = POSTGRES_OPEN_WALLETS.lock().unwrap();
 
@@ -945,29 +1112,50 @@ fn _storagerecord_to_postgresrecord(in_rec: &StorageRecord) -> Result<PostgresWa
     Ok(out_rec)
 }
 
-fn _iterator_to_record_set(mut iter: Box<dyn StorageIterator>) -> Result<Vec<PostgresWalletRecord>, ErrorCode> {
-    let mut search_continue: bool = true;
-    let mut search_records = Vec::new();
-    while search_continue {
-        let rec = iter.next();
-        match rec {
-            Ok(record) => {
-                match record {
-                    Some(record) => {
-                        // record: StorageRecord
-                        search_records.push(_storagerecord_to_postgresrecord(&record).unwrap());
-                    },
-                    None => {
-                        search_continue = false;
-                    }
-                };
-            },
-            Err(_err) => {
-                return Err(ErrorCode::WalletStorageError);
-            }
+fn _fetch_search_page(storage: &::postgres_storage::PostgresStorage, search: &mut PostgresWalletSearch) -> Result<(), ErrorCode> {
+    let page_size = match search.remaining {
+        Some(remaining) => ::std::cmp::min(remaining, SEARCH_PAGE_SIZE),
+        None => SEARCH_PAGE_SIZE
+    };
+
+    let res = match search.query {
+        Some(ref query) => storage.search_page(&query.type_.as_bytes(), &query.query, Some(&query.options), search.after_id, search.skip, page_size),
+        None => storage.get_all_page(search.after_id, page_size)
+    };
+
+    let (records, last_id) = match res {
+        Ok(page) => page,
+        Err(WalletStorageError::IOError(_)) => return Err(ErrorCode::CommonIOError),
+        Err(_err) => return Err(ErrorCode::WalletStorageError)
+    };
+
+    // skipped records precede the first page only, following pages continue after its last id
+    if search.sorted {
+        search.skip += records.len() as i64;
+    } else {
+        search.skip = 0;
+    }
+    if let Some(ref mut remaining) = search.remaining {
+        *remaining -= records.len() as i64;
+        if *remaining <= 0 {
+            search.finished = true;
+        }
+    }
+    if (records.len() as i64) < page_size {
+        search.finished = true;
+    }
+    match last_id {
+        Some(last_id) => search.after_id = last_id,
+        None => search.finished = true
+    };
+
+    for record in records {
+        match _storagerecord_to_postgresrecord(&record) {
+            Ok(record) => search.records.push_back(record),
+            Err(_err) => return Err(ErrorCode::WalletStorageError)
         };
     }
-    Ok(search_records)
+    Ok(())
 }
 
 fn _tags_to_json(tags: &[Tag]) -> Result<String, WalletStorageError> {
@@ -1689,6 +1877,167 @@ mod tests {
         _close_and_delete_wallet(handle);
     }
 */
+
+    #[test]
+    fn postgres_wallet_transaction_works() {
+        _cleanup();
+
+        let handle = _create_and_open_wallet();
+
+        let type1_ = _type1();
+        let tags1 = _tags_json(&_tags());
+        let get_options = _fetch_options(true, true, true);
+
+        for (id, value, commit) in [(_id1(), _value1(), true), (_id2(), _value2(), false)].iter() {
+            let err = PostgresWallet::begin_transaction(handle);
+            assert_match!(ErrorCode::Success, err);
+
+            let joined_value = value.to_bytes();
+            let err = PostgresWallet::add_record(handle,
+                                    type1_.as_ptr(),
+                                    id.as_ptr(),
+                                    joined_value.as_ptr(),
+                                    joined_value.len(),
+                                    tags1.as_ptr());
+            assert_match!(ErrorCode::Success, err);
+
+            let err = if *commit {
+                PostgresWallet::commit_transaction(handle)
+            } else {
+                PostgresWallet::rollback_transaction(handle)
+            };
+            assert_match!(ErrorCode::Success, err);
+
+            let mut rec_handle: i32 = -1;
+            let err = PostgresWallet::get_record(handle,
+                                    type1_.as_ptr(),
+                                    id.as_ptr(),
+                                    get_options.as_ptr(),
+                                    &mut rec_handle);
+            if *commit {
+                assert_match!(ErrorCode::Success, err);
+            } else {
+                assert_match!(ErrorCode::WalletItemNotFound, err);
+            }
+        }
+
+        let err = PostgresWallet::commit_transaction(handle);
+        assert_match!(ErrorCode::WalletStorageError, err);
+
+        _close_and_delete_wallet(handle);
+    }
+
+    #[test]
+    fn postgres_wallet_aggregate_records_works() {
+        _cleanup();
+
+        let handle = _create_and_open_wallet();
+
+        let type1_ = _type1();
+        let tags1 = _tags_json(&_tags());
+
+        for (id, value) in [(_id1(), _value1()), (_id2(), _value2())].iter() {
+            let joined_value = value.to_bytes();
+            let err = PostgresWallet::add_record(handle,
+                                    type1_.as_ptr(),
+                                    id.as_ptr(),
+                                    joined_value.as_ptr(),
+                                    joined_value.len(),
+                                    tags1.as_ptr());
+            assert_match!(ErrorCode::Success, err);
+        }
+
+        let query_json = CString::new("{}").unwrap();
+        let group_by_json = CString::new(json!([format!("~{}", util_base64::encode(&[1, 5, 8, 1]))]).to_string()).unwrap();
+
+        let mut result_ptr: *const c_char = ptr::null_mut();
+        let mut result_handle: i32 = -1;
+        let err = PostgresWallet::aggregate_records(handle,
+                                type1_.as_ptr(),
+                                query_json.as_ptr(),
+                                group_by_json.as_ptr(),
+                                &mut result_ptr,
+                                &mut result_handle);
+        assert_match!(ErrorCode::Success, err);
+
+        let result = unsafe { CStr::from_ptr(result_ptr).to_str().unwrap() };
+        let groups: Vec<(Vec<Option<String>>, usize)> = serde_json::from_str(result).unwrap();
+        assert_eq!(groups, vec![(vec![Some("Plain value 1".to_string())], 2)]);
+
+        let err = PostgresWallet::free_aggregation(handle, result_handle);
+        assert_match!(ErrorCode::Success, err);
+
+        let err = PostgresWallet::free_aggregation(handle, result_handle);
+        assert_match!(ErrorCode::CommonInvalidState, err);
+
+        _close_and_delete_wallet(handle);
+    }
+
+    #[test]
+    fn postgres_wallet_search_records_works_for_sort_by() {
+        _cleanup();
+
+        let handle = _create_and_open_wallet();
+
+        let type1_ = _type1();
+        let tag_name = format!("~{}", util_base64::encode(&[1, 5, 8, 1]));
+
+        for (i, tag_value) in [(1, "b"), (2, "c"), (3, "a")].iter() {
+            let joined_value = _value(*i).to_bytes();
+            let tags = CString::new(json!({tag_name.clone(): tag_value}).to_string()).unwrap();
+            let err = PostgresWallet::add_record(handle,
+                                    type1_.as_ptr(),
+                                    _id(*i).as_ptr(),
+                                    joined_value.as_ptr(),
+                                    joined_value.len(),
+                                    tags.as_ptr());
+            assert_match!(ErrorCode::Success, err);
+        }
+
+        let query_json = CString::new("{}").unwrap();
+        let options_asc = CString::new(json!({
+            "sortBy": [{"tagName": tag_name}],
+        }).to_string()).unwrap();
+        let options_desc = CString::new(json!({
+            "sortBy": [{"tagName": tag_name, "order": "desc"}],
+            "skip": 1,
+            "limit": 2,
+        }).to_string()).unwrap();
+
+        for (options_json, expected_ids) in [(options_asc, vec![_id(3), _id(1), _id(2)]), (options_desc, vec![_id(1), _id(3)])].iter() {
+            let mut search_handle: i32 = -1;
+            let err = PostgresWallet::search_records(handle,
+                                    type1_.as_ptr(),
+                                    query_json.as_ptr(),
+                                    options_json.as_ptr(),
+                                    &mut search_handle);
+            assert_match!(ErrorCode::Success, err);
+
+            let mut ids: Vec<CString> = Vec::new();
+            loop {
+                let mut rec_handle = -1;
+                let err = PostgresWallet::fetch_search_next_record(handle, search_handle, &mut rec_handle);
+                if err == ErrorCode::WalletItemNotFound {
+                    break;
+                }
+                assert_match!(ErrorCode::Success, err);
+
+                let mut id_ptr: *const c_char = ptr::null_mut();
+                let err = PostgresWallet::get_record_id(handle, rec_handle, &mut id_ptr);
+                assert_match!(ErrorCode::Success, err);
+                ids.push(unsafe { CStr::from_ptr(id_ptr) }.to_owned());
+
+                let err = PostgresWallet::free_record(handle, rec_handle);
+                assert_match!(ErrorCode::Success, err);
+            }
+            assert_eq!(expected_ids, &ids);
+
+            let err = PostgresWallet::free_search(handle, search_handle);
+            assert_match!(ErrorCode::Success, err);
+        }
+
+        _close_and_delete_wallet(handle);
+    }
     fn _create_and_open_wallet() -> i32 {
         let id = _wallet_id();
         let config = _wallet_config();
@@ -1763,6 +2112,9 @@ mod tests {
                 if scheme == "MultiWalletSingleTable" {
                     return _wallet_config_multi();
                 }
+                if scheme == "MultiWalletMultiTable" {
+                    return _wallet_config_multi_table();
+                }
             },
             Err(_) => ()
         };
@@ -1782,6 +2134,15 @@ mod tests {
             .map_or(Ok(None), |r| r.map(Some)).unwrap()
     }
 
+    fn _wallet_config_multi_table() -> Option<CString> {
+        let config = Some(json!({
+            "url": "localhost:5432".to_owned(),
+            "wallet_scheme": "MultiWalletMultiTable".to_owned()
+        }).to_string());
+        config.map(CString::new)
+            .map_or(Ok(None), |r| r.map(Some)).unwrap()
+    }
+
     fn _wallet_credentials() -> Option<CString> {
         let creds = Some(json!({
             "account": "postgres".to_owned(),
@@ -324,6 +324,52 @@ pub type WalletFetchSearchNextRecord = extern fn(storage_handle: IndyHandle,
 pub type WalletFreeSearch = extern fn(storage_handle: IndyHandle,
                                       search_handle: IndyHandle) -> ErrorCode;
 
+/// Count wallet storage records matching query grouped by values of plaintext tags
+///
+/// #Params
+/// storage_handle: opened storage handle (See open handler)
+/// type_: allows to separate different record types collections
+/// query_json: MongoDB style query to wallet record tags (the same as for search_records handler)
+/// group_by_json: list of plaintext tag names to group records by:
+///   ["~<base64 encoded encrypted tag name>", ...]
+/// result_json_p: pointer to store groups json: [[[<tag value or null>, ...], <count>], ...]
+/// result_handle_p: pointer to store handle of result json to free by free_aggregation handler
+pub type WalletAggregateRecords = extern fn(storage_handle: IndyHandle,
+                                            type_: *const c_char,
+                                            query_json: *const c_char,
+                                            group_by_json: *const c_char,
+                                            result_json_p: *mut *const c_char,
+                                            result_handle_p: *mut IndyHandle) -> ErrorCode;
+
+/// Free aggregation result json returned by aggregate records handler
+///
+/// #Params
+/// storage_handle: opened storage handle (See open handler)
+/// result_handle: handle of result json (See aggregate_records handler)
+pub type WalletFreeAggregation = extern fn(storage_handle: IndyHandle,
+                                           result_handle: IndyHandle) -> ErrorCode;
+
+/// Begin wallet storage transaction
+///
+/// All storage modifications made after this call and before commit or rollback
+/// must be applied atomically.
+///
+/// #Params
+/// storage_handle: opened storage handle (See open handler)
+pub type WalletBeginTransaction = extern fn(storage_handle: IndyHandle) -> ErrorCode;
+
+/// Commit wallet storage transaction started by begin transaction handler
+///
+/// #Params
+/// storage_handle: opened storage handle (See open handler)
+pub type WalletCommitTransaction = extern fn(storage_handle: IndyHandle) -> ErrorCode;
+
+/// Rollback wallet storage transaction started by begin transaction handler
+///
+/// #Params
+/// storage_handle: opened storage handle (See open handler)
+pub type WalletRollbackTransaction = extern fn(storage_handle: IndyHandle) -> ErrorCode;
+
 pub fn register_wallet_storage(
     wallet_storage_name: *const c_char,
     create: WalletCreate,
@@ -394,6 +440,68 @@ pub fn register_wallet_storage(
     receiver.recv().unwrap()
 }
 
+pub fn register_wallet_storage_aggregation(
+    wallet_storage_name: *const c_char,
+    aggregate_records: WalletAggregateRecords,
+    free_aggregation: WalletFreeAggregation,
+) -> ErrorCode {
+    let (sender, receiver) = channel();
+
+    let closure: Box<dyn FnMut(ErrorCode) + Send> = Box::new(move |err| {
+        sender.send(err).unwrap();
+    });
+
+    let (cmd_handle, cb) = callbacks::closure_to_cb_ec(closure);
+
+    let err = unsafe {
+        indy_register_wallet_storage_aggregation(
+            cmd_handle,
+            wallet_storage_name,
+            Some(aggregate_records),
+            Some(free_aggregation),
+            cb,
+        )
+    };
+
+    if err != ErrorCode::Success {
+        return err;
+    }
+
+    receiver.recv().unwrap()
+}
+
+pub fn register_wallet_storage_transaction_hooks(
+    wallet_storage_name: *const c_char,
+    begin_transaction: WalletBeginTransaction,
+    commit_transaction: WalletCommitTransaction,
+    rollback_transaction: WalletRollbackTransaction,
+) -> ErrorCode {
+    let (sender, receiver) = channel();
+
+    let closure: Box<dyn FnMut(ErrorCode) + Send> = Box::new(move |err| {
+        sender.send(err).unwrap();
+    });
+
+    let (cmd_handle, cb) = callbacks::closure_to_cb_ec(closure);
+
+    let err = unsafe {
+        indy_register_wallet_storage_transaction_hooks(
+            cmd_handle,
+            wallet_storage_name,
+            Some(begin_transaction),
+            Some(commit_transaction),
+            Some(rollback_transaction),
+            cb,
+        )
+    };
+
+    if err != ErrorCode::Success {
+        return err;
+    }
+
+    receiver.recv().unwrap()
+}
+
 extern {
     #[no_mangle]
     pub fn indy_register_wallet_storage(command_handle: IndyHandle,
@@ -424,6 +532,21 @@ extern {
                                             free_search: Option<WalletFreeSearch>,
                                             cb: Option<extern fn(command_handle_: IndyHandle,
                                                                     err: ErrorCode)>) -> ErrorCode;
-}
 
+    #[no_mangle]
+    pub fn indy_register_wallet_storage_aggregation(command_handle: IndyHandle,
+                                                    type_: *const c_char,
+                                                    aggregate_records: Option<WalletAggregateRecords>,
+                                                    free_aggregation: Option<WalletFreeAggregation>,
+                                                    cb: Option<extern fn(command_handle_: IndyHandle,
+                                                                         err: ErrorCode)>) -> ErrorCode;
 
+    #[no_mangle]
+    pub fn indy_register_wallet_storage_transaction_hooks(command_handle: IndyHandle,
+                                                          type_: *const c_char,
+                                                          begin_transaction: Option<WalletBeginTransaction>,
+                                                          commit_transaction: Option<WalletCommitTransaction>,
+                                                          rollback_transaction: Option<WalletRollbackTransaction>,
+                                                          cb: Option<extern fn(command_handle_: IndyHandle,
+                                                                               err: ErrorCode)>) -> ErrorCode;
+}
@@ -8,11 +8,14 @@ use self::r2d2_postgres::{TlsMode, PostgresConnectionManager};
 use serde_json;
 
 use self::owning_ref::OwningHandle;
+use std::ops::Deref;
 use std::rc::Rc;
+use std::sync::{Mutex, MutexGuard};
 use std::time::Duration;
 
-use errors::wallet::WalletStorageError;
+use errors::wallet::{WalletStorageError, WalletQueryError};
 use errors::common::CommonError;
+use utils::crypto::base64;
 use wql::language;
 use wql::query;
 use wql::transaction;
@@ -70,15 +73,25 @@ impl Default for RecordOptions {
 #[serde(rename_all = "camelCase")]
 pub struct SearchOptions {
     #[serde(default = "default_true")]
-    retrieve_records: bool,
+    pub retrieve_records: bool,
     #[serde(default = "default_false")]
-    retrieve_total_count: bool,
+    pub retrieve_total_count: bool,
     #[serde(default = "default_false")]
     retrieve_type: bool,
     #[serde(default = "default_true")]
     retrieve_value: bool,
     #[serde(default = "default_false")]
-    retrieve_tags: bool
+    retrieve_tags: bool,
+    // sorted searches are paged by offset as keyset pagination goes over item ids only
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_by: Option<Vec<SortBy>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip: Option<u32>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>
 }
 
 impl SearchOptions {
@@ -88,7 +101,10 @@ impl SearchOptions {
             retrieve_total_count: true,
             retrieve_type: true,
             retrieve_value: true,
-            retrieve_tags: false
+            retrieve_tags: false,
+            sort_by: None,
+            skip: None,
+            limit: None
         };
 
         serde_json::to_string(&options).unwrap()
@@ -103,15 +119,48 @@ impl Default for SearchOptions {
             retrieve_type: false,
             retrieve_value: true,
             retrieve_tags: false,
+            sort_by: None,
+            skip: None,
+            limit: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SortBy {
+    pub tag_name: String,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+impl SortBy {
+    // only plain tags can be sorted by, their names are passed as "~<base64 encoded encrypted tag name>"
+    pub fn plain_tag_name(&self) -> Result<Vec<u8>, WalletQueryError> {
+        if !self.tag_name.starts_with('~') {
+            return Err(WalletQueryError::StructureErr(format!("Sorting is supported only by plaintext tags: {}", self.tag_name)));
         }
+        base64::decode(&self.tag_name[1..])
+            .map_err(|_| WalletQueryError::StructureErr(format!("Invalid tag name for sorting: {}", self.tag_name)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl Default for SortOrder {
+    fn default() -> SortOrder {
+        SortOrder::Asc
     }
 }
 
 
 const _POSTGRES_DB: &str = "postgres";
 const _WALLETS_DB: &str = "wallets";
-const _PLAIN_TAGS_QUERY: &str = "SELECT name, value from tags_plaintext where item_id = $1";
-const _ENCRYPTED_TAGS_QUERY: &str = "SELECT name, value from tags_encrypted where item_id = $1";
 const _PLAIN_TAGS_QUERY_MULTI: &str = "SELECT name, value from tags_plaintext where item_id = $1 and wallet_id = $2";
 const _ENCRYPTED_TAGS_QUERY_MULTI: &str = "SELECT name, value from tags_encrypted where item_id = $1 and wallet_id = $2";
 const _CREATE_WALLET_DATABASE: &str = "CREATE DATABASE \"$1\"";
@@ -219,6 +268,54 @@ const _DELETE_WALLET_MULTI: [&str; 4] = [
     "DELETE FROM items WHERE wallet_id = $1",
     "DELETE FROM metadata WHERE wallet_id = $1"
     ];
+// Note: postgres truncates identifiers to 63 bytes, so wallet id length is limited by the longest table name
+const _MAX_IDENTIFIER_LEN: usize = 63;
+const _CREATE_SCHEMA_MULTI_TABLE: [&str; 11] = [
+    "CREATE TABLE \"metadata_$1\" (
+        id BIGSERIAL PRIMARY KEY,
+        value BYTEA NOT NULL
+    )",
+    "CREATE TABLE \"items_$1\" (
+        id BIGSERIAL PRIMARY KEY,
+        type BYTEA NOT NULL,
+        name BYTEA NOT NULL,
+        value BYTEA NOT NULL,
+        key BYTEA NOT NULL
+    )",
+    "CREATE UNIQUE INDEX ON \"items_$1\"(type, name)",
+    "CREATE TABLE \"tags_encrypted_$1\" (
+        name BYTEA NOT NULL,
+        value BYTEA NOT NULL,
+        item_id BIGINT NOT NULL,
+        PRIMARY KEY(name, item_id),
+        FOREIGN KEY(item_id)
+            REFERENCES \"items_$1\"(id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+    )",
+    "CREATE INDEX ON \"tags_encrypted_$1\"(name)",
+    "CREATE INDEX ON \"tags_encrypted_$1\"(value)",
+    "CREATE INDEX ON \"tags_encrypted_$1\"(item_id)",
+    "CREATE TABLE \"tags_plaintext_$1\" (
+        name BYTEA NOT NULL,
+        value TEXT NOT NULL,
+        item_id BIGINT NOT NULL,
+        PRIMARY KEY(name, item_id),
+        FOREIGN KEY(item_id)
+            REFERENCES \"items_$1\"(id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+    )",
+    "CREATE INDEX ON \"tags_plaintext_$1\"(name)",
+    "CREATE INDEX ON \"tags_plaintext_$1\"(value)",
+    "CREATE INDEX ON \"tags_plaintext_$1\"(item_id)"
+    ];
+const _DROP_SCHEMA_MULTI_TABLE: [&str; 4] = [
+    "DROP TABLE \"tags_plaintext_$1\"",
+    "DROP TABLE \"tags_encrypted_$1\"",
+    "DROP TABLE \"items_$1\"",
+    "DROP TABLE \"metadata_$1\""
+    ];
 
 
 #[derive(Debug)]
@@ -231,14 +328,15 @@ struct TagRetriever<'a> {
 type TagRetrieverOwned = OwningHandle<Rc<r2d2::PooledConnection<PostgresConnectionManager>>, Box<TagRetriever<'static>>>;
 
 impl<'a> TagRetriever<'a> {
-    fn new_owned(conn: Rc<r2d2::PooledConnection<PostgresConnectionManager>>, wallet_id: Option<String>) -> Result<TagRetrieverOwned, WalletStorageError> {
+    fn new_owned(conn: Rc<r2d2::PooledConnection<PostgresConnectionManager>>, wallet_id: Option<String>,
+                 plain_tags_table: &str, encrypted_tags_table: &str) -> Result<TagRetrieverOwned, WalletStorageError> {
         OwningHandle::try_new(conn.clone(), |conn| -> Result<_, postgres::Error> {
             let (plain_tags_stmt, encrypted_tags_stmt) = unsafe {
                 match wallet_id {
                     Some(_) => ((*conn).prepare(_PLAIN_TAGS_QUERY_MULTI)?,
                                 (*conn).prepare(_ENCRYPTED_TAGS_QUERY_MULTI)?),
-                    None => ((*conn).prepare(_PLAIN_TAGS_QUERY)?,
-                                (*conn).prepare(_ENCRYPTED_TAGS_QUERY)?)
+                    None => ((*conn).prepare(&format!("SELECT name, value from {} where item_id = $1", plain_tags_table))?,
+                                (*conn).prepare(&format!("SELECT name, value from {} where item_id = $1", encrypted_tags_table))?)
                 }
             };
             let tr = TagRetriever {
@@ -288,6 +386,7 @@ struct PostgresStorageIterator {
     options: RecordOptions,
     total_count: Option<usize>,
     iter_count: usize,
+    last_id: Option<i64>,
 }
 
 impl PostgresStorageIterator {
@@ -301,7 +400,8 @@ impl PostgresStorageIterator {
             tag_retriever,
             options,
             total_count,
-            iter_count: 0
+            iter_count: 0,
+            last_id: None
         };
 
         if let Some(stmt) = stmt {
@@ -329,6 +429,7 @@ impl StorageIterator for PostgresStorageIterator {
         match self.rows.as_mut().unwrap().iter().nth(self.iter_count) {
             Some(row) => {
                 self.iter_count = self.iter_count + 1;
+                self.last_id = Some(row.get(0));
                 let name = row.get(1);
                 let value = if self.options.retrieve_value {
                     Some(EncryptedValue::new(row.get(2), row.get(3)))
@@ -438,7 +539,36 @@ pub struct PostgresCredentials {
 #[derive(Debug)]
 pub struct PostgresStorage {
     pool: r2d2::Pool<PostgresConnectionManager>,
-    wallet_id: String
+    wallet_id: String,
+    transaction: Mutex<Option<r2d2::PooledConnection<PostgresConnectionManager>>> // connection of transaction started by begin_transaction
+}
+
+// Connection used by record operations: connection of active transaction or a new one from the pool
+enum StorageConnection<'a> {
+    Pooled(r2d2::PooledConnection<PostgresConnectionManager>),
+    Transaction(MutexGuard<'a, Option<r2d2::PooledConnection<PostgresConnectionManager>>>),
+}
+
+impl<'a> StorageConnection<'a> {
+    // Operations that need several statements run in a savepoint when transaction is active,
+    // so they don't commit or rollback the whole transaction
+    fn begin(&self) -> postgres::Result<transaction::Transaction> {
+        match *self {
+            StorageConnection::Pooled(_) => transaction::Transaction::new(self),
+            StorageConnection::Transaction(_) => transaction::Transaction::savepoint(self),
+        }
+    }
+}
+
+impl<'a> Deref for StorageConnection<'a> {
+    type Target = postgres::Connection;
+
+    fn deref(&self) -> &postgres::Connection {
+        match *self {
+            StorageConnection::Pooled(ref conn) => &**conn,
+            StorageConnection::Transaction(ref conn) => &**conn.as_ref().unwrap(),
+        }
+    }
 }
 
 pub trait WalletStorageType {
@@ -569,7 +699,8 @@ impl WalletStrategy for DatabasePerWalletStrategy {
 
         Ok(Box::new(PostgresStorage { 
             pool: pool,
-            wallet_id: id.to_string()
+            wallet_id: id.to_string(),
+            transaction: Mutex::new(None)
         }))
     }
     // delete a single wallet based on wallet storage strategy
@@ -729,7 +860,8 @@ impl WalletStrategy for MultiWalletSingleTableStrategy {
 
         Ok(Box::new(PostgresStorage { 
             pool: pool,
-            wallet_id: id.to_string()
+            wallet_id: id.to_string(),
+            transaction: Mutex::new(None)
         }))
     }
     // delete a single wallet based on wallet storage strategy
@@ -773,37 +905,155 @@ impl WalletStrategy for MultiWalletSingleTableStrategy {
     }
 }
 
+impl MultiWalletMultiTableStrategy {
+    fn _schema_sql(sql: &str, id: &str) -> String {
+        str::replace(sql, "$1", &id.replace("\"", "\"\""))
+    }
+
+    fn _check_wallet_id(id: &str) -> Result<(), WalletStorageError> {
+        if "tags_encrypted_".len() + id.replace("\"", "\"\"").len() > _MAX_IDENTIFIER_LEN {
+            return Err(WalletStorageError::CommonError(
+                CommonError::InvalidStructure(format!("Wallet id is too long for MultiWalletMultiTable scheme: {}", id))
+            ));
+        }
+        Ok(())
+    }
+
+    fn _create_tables(&self, conn: &postgres::Connection, id: &str, metadata: &[u8]) -> Result<(), WalletStorageError> {
+        let tx = conn.transaction()?;
+        for sql in &_CREATE_SCHEMA_MULTI_TABLE {
+            if let Err(error) = tx.execute(&MultiWalletMultiTableStrategy::_schema_sql(sql, id), &[]) {
+                if error.code() == Some(&postgres::error::DUPLICATE_TABLE) {
+                    return Err(WalletStorageError::AlreadyExists);
+                } else {
+                    return Err(WalletStorageError::IOError(format!("Error occurred while creating wallet schema: {}", error)));
+                }
+            }
+        }
+        let insert_sql = format!("INSERT INTO {}(value) VALUES($1)", self.table_name(id, "metadata"));
+        if let Err(error) = tx.execute(&insert_sql, &[&metadata]) {
+            return Err(WalletStorageError::IOError(format!("Error occurred while inserting the keys: {}", error)));
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn _drop_tables(conn: &postgres::Connection, id: &str) -> Result<(), WalletStorageError> {
+        let tx = conn.transaction()?;
+        for sql in &_DROP_SCHEMA_MULTI_TABLE {
+            if let Err(error) = tx.execute(&MultiWalletMultiTableStrategy::_schema_sql(sql, id), &[]) {
+                if error.code() == Some(&postgres::error::UNDEFINED_TABLE) {
+                    return Err(WalletStorageError::NotFound);
+                } else {
+                    return Err(WalletStorageError::IOError(format!("Error occurred while deleting wallet: {}", error)));
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
 impl WalletStrategy for MultiWalletMultiTableStrategy {
     // initialize storage based on wallet storage strategy
-    fn init_storage(&self, _config: &PostgresConfig, _credentials: &PostgresCredentials) -> Result<(), WalletStorageError> {
-        // create database for storage
-        // TODO
-        Ok(())
+    fn init_storage(&self, config: &PostgresConfig, credentials: &PostgresCredentials) -> Result<(), WalletStorageError> {
+        // create database for storage, tables are created per wallet
+        // if admin user and password aren't provided then bail
+        debug!("Initializing storage strategy MultiWalletMultiTableStrategy.");
+        if credentials.admin_account == None || credentials.admin_password == None {
+            return Ok(())
+        }
+
+        let url_base = PostgresStorageType::_admin_postgres_url(&config, &credentials);
+
+        let conn = postgres::Connection::connect(&url_base[..], config.tls())?;
+
+        let ret = match conn.execute(&_CREATE_WALLETS_DATABASE, &[]) {
+            Ok(_) => Ok(()),
+            Err(ref error) if error.code() == Some(&postgres::error::DUPLICATE_DATABASE) => Ok(()),
+            Err(error) => Err(WalletStorageError::IOError(format!("Error occurred while creating the database: {}", error)))
+        };
+        conn.finish()?;
+        ret
     }
     // initialize a single wallet based on wallet storage strategy
-    fn create_wallet(&self, _id: &str, _config: &PostgresConfig, _credentials: &PostgresCredentials, _metadata: &[u8]) -> Result<(), WalletStorageError> {
-        // create tables for wallet storage
-        // TODO
-        Ok(())
+    fn create_wallet(&self, id: &str, config: &PostgresConfig, credentials: &PostgresCredentials, metadata: &[u8]) -> Result<(), WalletStorageError> {
+        // create tables for wallet storage and insert metadata
+        MultiWalletMultiTableStrategy::_check_wallet_id(id)?;
+
+        let url = PostgresStorageType::_postgres_url(_WALLETS_DB, &config, &credentials);
+
+        let conn = match postgres::Connection::connect(&url[..], config.tls()) {
+            Ok(conn) => conn,
+            Err(error) => {
+                return Err(WalletStorageError::IOError(format!("Error occurred while connecting to wallet schema: {}", error)));
+            }
+        };
+
+        let ret = self._create_tables(&conn, id, metadata);
+        conn.finish()?;
+        ret
     }
     // open a wallet based on wallet storage strategy
-    fn open_wallet(&self, _id: &str, _config: &PostgresConfig, _credentials: &PostgresCredentials) -> Result<Box<PostgresStorage>, WalletStorageError> {
-        // TODO
-        Err(WalletStorageError::NotFound)
+    fn open_wallet(&self, id: &str, config: &PostgresConfig, credentials: &PostgresCredentials) -> Result<Box<PostgresStorage>, WalletStorageError> {
+        let url = PostgresStorageType::_postgres_url(_WALLETS_DB, &config, &credentials);
+
+        let conn = match postgres::Connection::connect(&url[..], config.tls()) {
+            Ok(conn) => conn,
+            Err(_) => return Err(WalletStorageError::NotFound)
+        };
+
+        // select metadata of this wallet to ensure its tables exist
+        let exists = match conn.query(&format!("SELECT value FROM {}", self.table_name(id, "metadata")), &[]) {
+            Ok(rows) => !rows.is_empty(),
+            Err(_) => false
+        };
+        conn.finish()?;
+
+        if !exists {
+            return Err(WalletStorageError::NotFound);
+        }
+
+        let manager = match PostgresConnectionManager::new(&url[..], config.r2d2_tls()) {
+            Ok(manager) => manager,
+            Err(_) => return Err(WalletStorageError::NotFound)
+        };
+        let pool = match r2d2::Pool::builder()
+            .min_idle(Some(config.min_idle_count()))
+            .max_size(config.max_connections())
+            .idle_timeout(Some(Duration::new(config.connection_timeout(), 0)))
+            .build(manager) {
+            Ok(pool) => pool,
+            Err(_) => return Err(WalletStorageError::NotFound)
+        };
+
+        Ok(Box::new(PostgresStorage {
+            pool: pool,
+            wallet_id: id.to_string(),
+            transaction: Mutex::new(None)
+        }))
     }
     // delete a single wallet based on wallet storage strategy
-    fn delete_wallet(&self, _id: &str, _config: &PostgresConfig, _credentials: &PostgresCredentials) -> Result<(), WalletStorageError> {
-        // TODO
-        Ok(())
+    fn delete_wallet(&self, id: &str, config: &PostgresConfig, credentials: &PostgresCredentials) -> Result<(), WalletStorageError> {
+        let url = PostgresStorageType::_postgres_url(_WALLETS_DB, &config, &credentials);
+
+        let conn = match postgres::Connection::connect(&url[..], config.tls()) {
+            Ok(conn) => conn,
+            Err(error) => {
+                return Err(WalletStorageError::IOError(format!("Error occurred while connecting to wallet schema: {}", error)));
+            }
+        };
+
+        let ret = MultiWalletMultiTableStrategy::_drop_tables(&conn, id);
+        conn.finish()?;
+        ret
     }
     // determine phyisical table name based on wallet strategy
-    fn table_name(&self, _id: &str, base_name: &str) -> String {
-        // TODO
-        base_name.to_owned()
+    fn table_name(&self, id: &str, base_name: &str) -> String {
+        format!("\"{}_{}\"", base_name, id.replace("\"", "\"\""))
     }
     // determine additional query parameters based on wallet strategy
     fn query_qualifier(&self) -> Option<String> {
-        // TODO
         None
     }
 }
@@ -885,8 +1135,7 @@ impl WalletStorage for PostgresStorage {
         } else {
             serde_json::from_str(options)?
         };
-        let pool = self.pool.clone();
-        let conn = pool.get().unwrap();
+        let conn = self._connection()?;
         let query_qualifier = unsafe {
             SELECTED_STRATEGY.query_qualifier()
         };
@@ -896,7 +1145,7 @@ impl WalletStorage for PostgresStorage {
                     "SELECT id, value, key FROM items where type = $1 AND name = $2 AND wallet_id = $3",
                     &[&type_.to_vec(), &id.to_vec(), &self.wallet_id]),
                 None => conn.query(
-                    "SELECT id, value, key FROM items where type = $1 AND name = $2",
+                    &format!("SELECT id, value, key FROM {} where type = $1 AND name = $2", self._table("items")),
                     &[&type_.to_vec(), &id.to_vec()])
             };
             match rows.as_mut().unwrap().iter().next() {
@@ -922,7 +1171,7 @@ impl WalletStorage for PostgresStorage {
                     stmt.query(&[&item.0, &self.wallet_id])?
                 },
                 None => {
-                    let stmt = conn.prepare_cached("SELECT name, value FROM tags_encrypted WHERE item_id = $1")?;
+                    let stmt = conn.prepare_cached(&format!("SELECT name, value FROM {} WHERE item_id = $1", self._table("tags_encrypted")))?;
                     stmt.query(&[&item.0])?
                 }
             };
@@ -942,7 +1191,7 @@ impl WalletStorage for PostgresStorage {
                     stmt.query(&[&item.0, &self.wallet_id])?
                 },
                 None => {
-                    let stmt = conn.prepare_cached("SELECT name, value FROM tags_plaintext WHERE item_id = $1")?;
+                    let stmt = conn.prepare_cached(&format!("SELECT name, value FROM {} WHERE item_id = $1", self._table("tags_plaintext")))?;
                     stmt.query(&[&item.0])?
                 }
             };
@@ -989,16 +1238,15 @@ impl WalletStorage for PostgresStorage {
     ///  * `IOError("IO error during storage operation:...")` - Failed connection or SQL query
     ///
     fn add(&self, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> Result<(), WalletStorageError> {
-        let pool = self.pool.clone();
-        let conn = pool.get().unwrap();
+        let conn = self._connection()?;
         let query_qualifier = unsafe {
             SELECTED_STRATEGY.query_qualifier()
         };
-        let tx: transaction::Transaction = transaction::Transaction::new(&conn)?;
+        let tx: transaction::Transaction = conn.begin()?;
         let res = match query_qualifier {
             Some(_) => tx.prepare_cached("INSERT INTO items (type, name, value, key, wallet_id) VALUES ($1, $2, $3, $4, $5) RETURNING id")?
                 .query(&[&type_.to_vec(), &id.to_vec(), &value.data, &value.key, &self.wallet_id]),
-            None => tx.prepare_cached("INSERT INTO items (type, name, value, key) VALUES ($1, $2, $3, $4) RETURNING id")?
+            None => tx.prepare_cached(&format!("INSERT INTO {} (type, name, value, key) VALUES ($1, $2, $3, $4) RETURNING id", self._table("items")))?
                 .query(&[&type_.to_vec(), &id.to_vec(), &value.data, &value.key])
         };
 
@@ -1030,11 +1278,11 @@ impl WalletStorage for PostgresStorage {
         if !tags.is_empty() {
             let stmt_e = match query_qualifier {
                 Some(_) => tx.prepare_cached("INSERT INTO tags_encrypted (item_id, name, value, wallet_id) VALUES ($1, $2, $3, $4)")?,
-                None => tx.prepare_cached("INSERT INTO tags_encrypted (item_id, name, value) VALUES ($1, $2, $3)")?
+                None => tx.prepare_cached(&format!("INSERT INTO {} (item_id, name, value) VALUES ($1, $2, $3)", self._table("tags_encrypted")))?
             };
             let stmt_p = match query_qualifier {
                 Some(_) => tx.prepare_cached("INSERT INTO tags_plaintext (item_id, name, value, wallet_id) VALUES ($1, $2, $3, $4)")?,
-                None => tx.prepare_cached("INSERT INTO tags_plaintext (item_id, name, value) VALUES ($1, $2, $3)")?
+                None => tx.prepare_cached(&format!("INSERT INTO {} (item_id, name, value) VALUES ($1, $2, $3)", self._table("tags_plaintext")))?
             };
 
             for tag in tags {
@@ -1083,15 +1331,14 @@ impl WalletStorage for PostgresStorage {
     }
 
     fn update(&self, type_: &[u8], id: &[u8], value: &EncryptedValue) -> Result<(), WalletStorageError> {
-        let pool = self.pool.clone();
-        let conn = pool.get().unwrap();
+        let conn = self._connection()?;
         let query_qualifier = unsafe {
             SELECTED_STRATEGY.query_qualifier()
         };
         let res = match query_qualifier {
             Some(_) => conn.prepare_cached("UPDATE items SET value = $1, key = $2 WHERE type = $3 AND name = $4 AND wallet_id = $5")?
                 .execute(&[&value.data, &value.key, &type_.to_vec(), &id.to_vec(), &self.wallet_id]),
-            None => conn.prepare_cached("UPDATE items SET value = $1, key = $2 WHERE type = $3 AND name = $4")?
+            None => conn.prepare_cached(&format!("UPDATE {} SET value = $1, key = $2 WHERE type = $3 AND name = $4", self._table("items")))?
                 .execute(&[&value.data, &value.key, &type_.to_vec(), &id.to_vec()])
         };
 
@@ -1104,16 +1351,15 @@ impl WalletStorage for PostgresStorage {
     }
 
     fn add_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> Result<(), WalletStorageError> {
-        let pool = self.pool.clone();
-        let conn = pool.get().unwrap();
+        let conn = self._connection()?;
         let query_qualifier = unsafe {
             SELECTED_STRATEGY.query_qualifier()
         };
-        let tx: transaction::Transaction = transaction::Transaction::new(&conn)?;
+        let tx: transaction::Transaction = conn.begin()?;
 
         let res = match query_qualifier {
             Some(_) => {
-                let mut rows = tx.prepare_cached("SELECT id FROM items WHERE type = $1 AND name = $2")?
+                let mut rows = tx.prepare_cached(&format!("SELECT id FROM {} WHERE type = $1 AND name = $2", self._table("items")))?
                     .query(&[&type_.to_vec(), &id.to_vec()]);
                 match rows.as_mut().unwrap().iter().next() {
                     Some(row) => Ok(row.get(0)),
@@ -1121,7 +1367,7 @@ impl WalletStorage for PostgresStorage {
                 }
             },
             None => {
-                let mut rows = tx.prepare_cached("SELECT id FROM items WHERE type = $1 AND name = $2")?
+                let mut rows = tx.prepare_cached(&format!("SELECT id FROM {} WHERE type = $1 AND name = $2", self._table("items")))?
                     .query(&[&type_.to_vec(), &id.to_vec()]);
                 match rows.as_mut().unwrap().iter().next() {
                     Some(row) => Ok(row.get(0)),
@@ -1140,14 +1386,14 @@ impl WalletStorage for PostgresStorage {
             let enc_tag_insert_stmt = match query_qualifier {
                 Some(_) => tx.prepare_cached("INSERT INTO tags_encrypted (item_id, name, value, wallet_id) VALUES ($1, $2, $3, $4)
                                         ON CONFLICT (name, item_id, wallet_id) DO UPDATE SET value = excluded.value")?,
-                None => tx.prepare_cached("INSERT INTO tags_encrypted (item_id, name, value) VALUES ($1, $2, $3)
-                                        ON CONFLICT (name, item_id) DO UPDATE SET value = excluded.value")?
+                None => tx.prepare_cached(&format!("INSERT INTO {} (item_id, name, value) VALUES ($1, $2, $3)
+                                        ON CONFLICT (name, item_id) DO UPDATE SET value = excluded.value", self._table("tags_encrypted")))?
             };
             let plain_tag_insert_stmt = match query_qualifier {
                 Some(_) => tx.prepare_cached("INSERT INTO tags_plaintext (item_id, name, value, wallet_id) VALUES ($1, $2, $3, $4)
                                             ON CONFLICT (name, item_id, wallet_id) DO UPDATE SET value = excluded.value")?,
-                None => tx.prepare_cached("INSERT INTO tags_plaintext (item_id, name, value) VALUES ($1, $2, $3)
-                                            ON CONFLICT (name, item_id) DO UPDATE SET value = excluded.value")?
+                None => tx.prepare_cached(&format!("INSERT INTO {} (item_id, name, value) VALUES ($1, $2, $3)
+                                            ON CONFLICT (name, item_id) DO UPDATE SET value = excluded.value", self._table("tags_plaintext")))?
             };
 
             for tag in tags {
@@ -1195,12 +1441,11 @@ impl WalletStorage for PostgresStorage {
     }
 
     fn update_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> Result<(), WalletStorageError> {
-        let pool = self.pool.clone();
-        let conn = pool.get().unwrap();
+        let conn = self._connection()?;
         let query_qualifier = unsafe {
             SELECTED_STRATEGY.query_qualifier()
         };
-        let tx: transaction::Transaction = transaction::Transaction::new(&conn)?;
+        let tx: transaction::Transaction = conn.begin()?;
 
         let res = match query_qualifier {
             Some(_) => {
@@ -1212,7 +1457,7 @@ impl WalletStorage for PostgresStorage {
                 }
             },
             None => {
-                let mut rows = tx.prepare_cached("SELECT id FROM items WHERE type = $1 AND name = $2")?
+                let mut rows = tx.prepare_cached(&format!("SELECT id FROM {} WHERE type = $1 AND name = $2", self._table("items")))?
                     .query(&[&type_.to_vec(), &id.to_vec()]);
                 match rows.as_mut().unwrap().iter().next() {
                     Some(row) => Ok(row.get(0)),
@@ -1233,19 +1478,19 @@ impl WalletStorage for PostgresStorage {
                 tx.execute("DELETE FROM tags_plaintext WHERE item_id = $1 AND wallet_id = $2", &[&item_id, &self.wallet_id])?;
             },
             None => {
-                tx.execute("DELETE FROM tags_encrypted WHERE item_id = $1", &[&item_id])?;
-                tx.execute("DELETE FROM tags_plaintext WHERE item_id = $1", &[&item_id])?;
+                tx.execute(&format!("DELETE FROM {} WHERE item_id = $1", self._table("tags_encrypted")), &[&item_id])?;
+                tx.execute(&format!("DELETE FROM {} WHERE item_id = $1", self._table("tags_plaintext")), &[&item_id])?;
             }
         };
 
         if !tags.is_empty() {
             let enc_tag_insert_stmt = match query_qualifier {
                 Some(_) => tx.prepare_cached("INSERT INTO tags_encrypted (item_id, name, value, wallet_id) VALUES ($1, $2, $3, $4)")?,
-                None => tx.prepare_cached("INSERT INTO tags_encrypted (item_id, name, value) VALUES ($1, $2, $3)")?
+                None => tx.prepare_cached(&format!("INSERT INTO {} (item_id, name, value) VALUES ($1, $2, $3)", self._table("tags_encrypted")))?
             };
             let plain_tag_insert_stmt = match query_qualifier {
                 Some(_) => tx.prepare_cached("INSERT INTO tags_plaintext (item_id, name, value, wallet_id) VALUES ($1, $2, $3, $4)")?,
-                None => tx.prepare_cached("INSERT INTO tags_plaintext (item_id, name, value) VALUES ($1, $2, $3)")?
+                None => tx.prepare_cached(&format!("INSERT INTO {} (item_id, name, value) VALUES ($1, $2, $3)", self._table("tags_plaintext")))?
             };
 
             for tag in tags {
@@ -1271,8 +1516,7 @@ impl WalletStorage for PostgresStorage {
     }
 
     fn delete_tags(&self, type_: &[u8], id: &[u8], tag_names: &[TagName]) -> Result<(), WalletStorageError> {
-        let pool = self.pool.clone();
-        let conn = pool.get().unwrap();
+        let conn = self._connection()?;
         let query_qualifier = unsafe {
             SELECTED_STRATEGY.query_qualifier()
         };
@@ -1286,7 +1530,7 @@ impl WalletStorage for PostgresStorage {
                 }
             },
                 None => {
-                let mut rows = conn.prepare_cached("SELECT id FROM items WHERE type =$1 AND name = $2")?
+                let mut rows = conn.prepare_cached(&format!("SELECT id FROM {} WHERE type =$1 AND name = $2", self._table("items")))?
                     .query(&[&type_.to_vec(), &id.to_vec()]);
                 match rows.as_mut().unwrap().iter().next() {
                     Some(row) => Ok(row.get(0)),
@@ -1301,15 +1545,15 @@ impl WalletStorage for PostgresStorage {
             Ok(id) => id
         };
 
-        let tx: transaction::Transaction = transaction::Transaction::new(&conn)?;
+        let tx: transaction::Transaction = conn.begin()?;
         {
             let enc_tag_delete_stmt = match query_qualifier {
                 Some(_) => tx.prepare_cached("DELETE FROM tags_encrypted WHERE item_id = $1 AND name = $2 AND wallet_id = $3")?,
-                None => tx.prepare_cached("DELETE FROM tags_encrypted WHERE item_id = $1 AND name = $2")?
+                None => tx.prepare_cached(&format!("DELETE FROM {} WHERE item_id = $1 AND name = $2", self._table("tags_encrypted")))?
             };
             let plain_tag_delete_stmt = match query_qualifier {
                 Some(_) => tx.prepare_cached("DELETE FROM tags_plaintext WHERE item_id = $1 AND name = $2 AND wallet_id = $3")?,
-                None => tx.prepare_cached("DELETE FROM tags_plaintext WHERE item_id = $1 AND name = $2")?
+                None => tx.prepare_cached(&format!("DELETE FROM {} WHERE item_id = $1 AND name = $2", self._table("tags_plaintext")))?
             };
 
             for tag_name in tag_names {
@@ -1359,8 +1603,7 @@ impl WalletStorage for PostgresStorage {
     ///  * `IOError("IO error during storage operation:...")` - Failed connection or SQL query
     ///
     fn delete(&self, type_: &[u8], id: &[u8]) -> Result<(), WalletStorageError> {
-        let pool = self.pool.clone();
-        let conn = pool.get().unwrap();
+        let conn = self._connection()?;
         let query_qualifier = unsafe {
             SELECTED_STRATEGY.query_qualifier()
         };
@@ -1370,7 +1613,7 @@ impl WalletStorage for PostgresStorage {
                 &[&type_.to_vec(), &id.to_vec(), &self.wallet_id]
             )?,
             None => conn.execute(
-                "DELETE FROM items where type = $1 AND name = $2",
+                &format!("DELETE FROM {} where type = $1 AND name = $2", self._table("items")),
                 &[&type_.to_vec(), &id.to_vec()]
             )?
         };
@@ -1382,8 +1625,7 @@ impl WalletStorage for PostgresStorage {
     }
 
     fn get_storage_metadata(&self) -> Result<Vec<u8>, WalletStorageError> {
-        let pool = self.pool.clone();
-        let conn = pool.get().unwrap();
+        let conn = self._connection()?;
         let query_qualifier = unsafe {
             SELECTED_STRATEGY.query_qualifier()
         };
@@ -1393,7 +1635,7 @@ impl WalletStorage for PostgresStorage {
                     "SELECT value FROM metadata WHERE wallet_id = $1",
                     &[&self.wallet_id]),
                 None => conn.query(
-                    "SELECT value FROM metadata",
+                    &format!("SELECT value FROM {}", self._table("metadata")),
                     &[])
             };
             match rows.as_mut().unwrap().iter().next() {
@@ -1410,14 +1652,13 @@ impl WalletStorage for PostgresStorage {
     }
 
     fn set_storage_metadata(&self, metadata: &[u8]) -> Result<(), WalletStorageError> {
-        let pool = self.pool.clone();
-        let conn = pool.get().unwrap();
+        let conn = self._connection()?;
         let query_qualifier = unsafe {
             SELECTED_STRATEGY.query_qualifier()
         };
         let res = match query_qualifier {
             Some(_) => conn.execute("UPDATE metadata SET value = $1 WHERE wallet_id = $2", &[&metadata.to_vec(), &self.wallet_id]),
-            None => conn.execute("UPDATE metadata SET value = $1", &[&metadata.to_vec()])
+            None => conn.execute(&format!("UPDATE {} SET value = $1", self._table("metadata")), &[&metadata.to_vec()])
         };
         match res {
             Ok(_) => Ok(()),
@@ -1433,18 +1674,14 @@ impl WalletStorage for PostgresStorage {
         };
         let statement = match query_qualifier {
             Some(_) => self._prepare_statement("SELECT id, name, value, key, type FROM items WHERE wallet_id = $1")?,
-            None => self._prepare_statement("SELECT id, name, value, key, type FROM items")?
+            None => self._prepare_statement(&format!("SELECT id, name, value, key, type FROM {}", self._table("items")))?
         };
         let fetch_options = RecordOptions {
             retrieve_type: true,
             retrieve_value: true,
             retrieve_tags: true,
         };
-        let pool = self.pool.clone();
-        let tag_retriever = match query_qualifier {
-            Some(_) => Some(TagRetriever::new_owned(Rc::new(pool.get().unwrap()).clone(), Some(self.wallet_id.clone()))?),
-            None => Some(TagRetriever::new_owned(Rc::new(pool.get().unwrap()).clone(), None)?)
-        };
+        let tag_retriever = Some(self._tag_retriever()?);
 
         let storage_iterator = match query_qualifier {
             Some(_) => PostgresStorageIterator::new(Some(statement), &[&self.wallet_id], fetch_options, tag_retriever, None)?,
@@ -1461,51 +1698,8 @@ impl WalletStorage for PostgresStorage {
             Some(option_str) => serde_json::from_str(option_str)?
         };
 
-        let pool = self.pool.clone();
-        let conn = pool.get().unwrap();
-        let query_qualifier = unsafe {
-            SELECTED_STRATEGY.query_qualifier()
-        };
-        let wallet_id_arg = self.wallet_id.to_owned();
         let total_count: Option<usize> = if search_options.retrieve_total_count {
-            let (query_string, query_arguments) = match query_qualifier {
-                Some(_) => {
-                    let (mut query_string, mut query_arguments) = query::wql_to_sql_count(&type_, query)?;
-                    query_arguments.push(&wallet_id_arg);
-                    let arg_str = format!(" AND i.wallet_id = ${}", query_arguments.len());
-                    query_string.push_str(&arg_str);
-                    let mut with_clause = false;
-                    if query_string.contains("tags_plaintext") {
-                        query_arguments.push(&wallet_id_arg);
-                        query_string = format!("tags_plaintext as (select * from tags_plaintext where wallet_id = ${}) {}", query_arguments.len(), query_string);
-                        with_clause = true;
-                    }
-                    if query_string.contains("tags_encrypted") {
-                        if with_clause {
-                            query_string = format!(", {}", query_string);
-                        }
-                        query_arguments.push(&wallet_id_arg);
-                        query_string = format!("tags_encrypted as (select * from tags_encrypted where wallet_id = ${}) {}", query_arguments.len(), query_string);
-                        with_clause = true;
-                    }
-                    if with_clause {
-                        query_string = format!("WITH {}", query_string);
-                    }
-                    (query_string, query_arguments)
-                },
-                None => query::wql_to_sql_count(&type_, query)?
-            };
-
-            let mut rows = conn.query(
-                &query_string,
-                &query_arguments[..]);
-            match rows.as_mut().unwrap().iter().next() {
-                Some(row) => {
-                    let x: i64 = row.get(0);
-                    Some(x as usize)
-                },
-                None => None
-            }
+            self.search_total_count(&type_, query)?
         } else { None };
 
         if search_options.retrieve_records {
@@ -1515,41 +1709,21 @@ impl WalletStorage for PostgresStorage {
                 retrieve_type: search_options.retrieve_type,
             };
 
-            let (query_string, query_arguments) = match query_qualifier {
-                Some(_) => {
-                    let (mut query_string, mut query_arguments) = query::wql_to_sql(&type_, query, options)?;
-                    query_arguments.push(&wallet_id_arg);
-                    let arg_str = format!(" AND i.wallet_id = ${}", query_arguments.len());
-                    query_string.push_str(&arg_str);
-                    let mut with_clause = false;
-                    if query_string.contains("tags_plaintext") {
-                        query_arguments.push(&wallet_id_arg);
-                        query_string = format!("tags_plaintext as (select * from tags_plaintext where wallet_id = ${}) {}", query_arguments.len(), query_string);
-                        with_clause = true;
-                    }
-                    if query_string.contains("tags_encrypted") {
-                        if with_clause {
-                            query_string = format!(", {}", query_string);
-                        }
-                        query_arguments.push(&wallet_id_arg);
-                        query_string = format!("tags_encrypted as (select * from tags_encrypted where wallet_id = ${}) {}", query_arguments.len(), query_string);
-                        with_clause = true;
-                    }
-                    if with_clause {
-                        query_string = format!("WITH {}", query_string);
-                    }
-                    (query_string, query_arguments)
-                },
-                None => query::wql_to_sql(&type_, query, options)?
-            };
+            let wallet_id_arg = self.wallet_id.to_owned();
+            let (mut query_string, query_arguments) = self._search_query(&type_, query, options, &wallet_id_arg, false)?;
+            if let Some(ref sort_by) = search_options.sort_by {
+                query_string.push_str(&self._order_by(sort_by)?);
+            }
+            if let Some(limit) = search_options.limit {
+                query_string.push_str(&format!(" LIMIT {}", limit));
+            }
+            if let Some(skip) = search_options.skip {
+                query_string.push_str(&format!(" OFFSET {}", skip));
+            }
 
             let statement = self._prepare_statement(&query_string)?;
             let tag_retriever = if fetch_options.retrieve_tags {
-                let pool = self.pool.clone();
-                match query_qualifier {
-                    Some(_) => Some(TagRetriever::new_owned(Rc::new(pool.get().unwrap()).clone(), Some(self.wallet_id.clone()))?),
-                    None => Some(TagRetriever::new_owned(Rc::new(pool.get().unwrap()).clone(), None)?)
-                }
+                Some(self._tag_retriever()?)
             } else {
                 None
             };
@@ -1562,17 +1736,283 @@ impl WalletStorage for PostgresStorage {
     }
 
     fn close(&mut self) -> Result<(), WalletStorageError> {
+        // don't return connection with unfinished transaction to the pool
+        if let Some(conn) = self.transaction.lock().unwrap().take() {
+            conn.batch_execute("ROLLBACK")?;
+        }
         // TODO throws a borrow error if we try to close the connection here; temporary workaround is to rely on idle connection timeout
         Ok(())
     }
 }
 
 impl PostgresStorage {
+    ///
+    /// Starts transaction. Record operations are made on the connection of transaction
+    /// until it is committed or rolled back.
+    ///
+    pub fn begin_transaction(&self) -> Result<(), WalletStorageError> {
+        let mut transaction = self.transaction.lock().unwrap();
+
+        if transaction.is_some() {
+            return Err(WalletStorageError::CommonError(CommonError::InvalidState("Transaction is already started".to_string())));
+        }
+
+        let conn = self._pooled_connection()?;
+        conn.batch_execute("START TRANSACTION")?;
+        *transaction = Some(conn);
+
+        Ok(())
+    }
+
+    ///
+    /// Commits transaction started by begin_transaction.
+    ///
+    pub fn commit_transaction(&self) -> Result<(), WalletStorageError> {
+        self._finish_transaction("COMMIT")
+    }
+
+    ///
+    /// Rolls back transaction started by begin_transaction.
+    ///
+    pub fn rollback_transaction(&self) -> Result<(), WalletStorageError> {
+        self._finish_transaction("ROLLBACK")
+    }
+
+    fn _finish_transaction(&self, query: &str) -> Result<(), WalletStorageError> {
+        let conn = match self.transaction.lock().unwrap().take() {
+            Some(conn) => conn,
+            None => return Err(WalletStorageError::CommonError(CommonError::InvalidState("Transaction is not started".to_string())))
+        };
+
+        conn.batch_execute(query)?;
+
+        Ok(())
+    }
+
+    ///
+    /// Counts records of the given type matching the query.
+    ///
+    pub fn search_total_count(&self, type_: &[u8], query: &language::Operator) -> Result<Option<usize>, WalletStorageError> {
+        let type_ = type_.to_vec();
+        let wallet_id_arg = self.wallet_id.to_owned();
+        let (query_string, query_arguments) = self._search_query(&type_, query, None, &wallet_id_arg, true)?;
+
+        let conn = self._connection()?;
+        let rows = conn.query(&query_string, &query_arguments[..])?;
+        let total_count = match rows.iter().next() {
+            Some(row) => {
+                let x: i64 = row.get(0);
+                Some(x as usize)
+            },
+            None => None
+        };
+        Ok(total_count)
+    }
+
+    ///
+    /// Counts records of the given type matching the query grouped by values of plain tags
+    /// with the given names. Records without a tag are grouped under None value.
+    ///
+    pub fn aggregate(&self, type_: &[u8], query: &language::Operator, group_by: &[Vec<u8>]) -> Result<Vec<(Vec<Option<String>>, usize)>, WalletStorageError> {
+        let type_ = type_.to_vec();
+        let wallet_id_arg = self.wallet_id.to_owned();
+        let (query_string, query_arguments) = query::wql_to_sql_aggregate(&type_, query, group_by)?;
+        let (mut query_string, query_arguments) = self._qualify_query(query_string, query_arguments, &wallet_id_arg);
+        query_string.push_str(&query::group_by_to_sql(group_by.len()));
+
+        let conn = self._connection()?;
+        let rows = conn.query(&query_string, &query_arguments[..])?;
+
+        let groups = rows.iter()
+            .map(|row| {
+                let values: Vec<Option<String>> = (0..group_by.len()).map(|index| row.get(index)).collect();
+                let count: i64 = row.get(group_by.len());
+                (values, count as usize)
+            })
+            .collect();
+        Ok(groups)
+    }
+
+    ///
+    /// Fetches at most `limit` records matching the query with item ids greater than `after_id`,
+    /// skipping the first `offset` of them. Returns fetched records and id of the last fetched item to continue the search from.
+    /// If options contain `sortBy`, records are ordered by values of the plain tags and then by id,
+    /// `after_id` is ignored and following pages must be requested by `offset`.
+    ///
+    pub fn search_page(&self, type_: &[u8], query: &language::Operator, options: Option<&str>, after_id: i64, offset: i64, limit: i64)
+                       -> Result<(Vec<StorageRecord>, Option<i64>), WalletStorageError> {
+        let type_ = type_.to_vec();
+
+        let search_options = match options {
+            None => SearchOptions::default(),
+            Some(option_str) => serde_json::from_str(option_str)?
+        };
+
+        if !search_options.retrieve_records {
+            return Ok((Vec::new(), None));
+        }
+
+        let fetch_options = RecordOptions {
+            retrieve_value: search_options.retrieve_value,
+            retrieve_tags: search_options.retrieve_tags,
+            retrieve_type: search_options.retrieve_type,
+        };
+
+        let order_by = match search_options.sort_by {
+            Some(ref sort_by) => Some(self._order_by(sort_by)?),
+            None => None
+        };
+
+        let wallet_id_arg = self.wallet_id.to_owned();
+        let (mut query_string, mut query_arguments) = self._search_query(&type_, query, options, &wallet_id_arg, false)?;
+        match order_by {
+            Some(ref order_by) => query_string.push_str(order_by),
+            None => {
+                query_arguments.push(&after_id);
+                query_string.push_str(&format!(" AND i.id > ${} ORDER BY i.id", query_arguments.len()));
+            }
+        }
+        query_arguments.push(&limit);
+        query_string.push_str(&format!(" LIMIT ${}", query_arguments.len()));
+        query_arguments.push(&offset);
+        query_string.push_str(&format!(" OFFSET ${}", query_arguments.len()));
+
+        self._fetch_page(&query_string, &query_arguments[..], fetch_options)
+    }
+
+    ///
+    /// Fetches at most `limit` records of any type with item ids greater than `after_id`.
+    /// Returns fetched records and id of the last fetched item to continue from.
+    ///
+    pub fn get_all_page(&self, after_id: i64, limit: i64) -> Result<(Vec<StorageRecord>, Option<i64>), WalletStorageError> {
+        let query_qualifier = unsafe {
+            SELECTED_STRATEGY.query_qualifier()
+        };
+        let fetch_options = RecordOptions {
+            retrieve_type: true,
+            retrieve_value: true,
+            retrieve_tags: true,
+        };
+        match query_qualifier {
+            Some(_) => self._fetch_page("SELECT id, name, value, key, type FROM items WHERE wallet_id = $1 AND id > $2 ORDER BY id LIMIT $3",
+                                        &[&self.wallet_id, &after_id, &limit], fetch_options),
+            None => self._fetch_page(&format!("SELECT id, name, value, key, type FROM {} WHERE id > $1 ORDER BY id LIMIT $2", self._table("items")),
+                                     &[&after_id, &limit], fetch_options)
+        }
+    }
+
+    fn _fetch_page(&self, sql: &str, args: &[&dyn postgres::types::ToSql], fetch_options: RecordOptions)
+                   -> Result<(Vec<StorageRecord>, Option<i64>), WalletStorageError> {
+        let statement = self._prepare_statement(sql)?;
+        let tag_retriever = if fetch_options.retrieve_tags {
+            Some(self._tag_retriever()?)
+        } else {
+            None
+        };
+
+        let mut iter = PostgresStorageIterator::new(Some(statement), args, fetch_options, tag_retriever, None)?;
+        let mut records = Vec::new();
+        while let Some(record) = iter.next()? {
+            records.push(record);
+        }
+        Ok((records, iter.last_id))
+    }
+
+    fn _search_query<'a>(&self, type_: &'a Vec<u8>, query: &'a language::Operator, options: Option<&str>, wallet_id_arg: &'a String, count: bool)
+                         -> Result<(String, Vec<&'a dyn postgres::types::ToSql>), WalletStorageError> {
+        let (query_string, query_arguments) = if count {
+            query::wql_to_sql_count(type_, query)?
+        } else {
+            query::wql_to_sql(type_, query, options)?
+        };
+        Ok(self._qualify_query(query_string, query_arguments, wallet_id_arg))
+    }
+
+    // Tag values are selected by item ids which are unique across wallets,
+    // so only physical table names have to be applied to ORDER BY clause
+    fn _order_by(&self, sort_by: &[SortBy]) -> Result<String, WalletStorageError> {
+        let sort_by = sort_by.iter()
+            .map(|sort| Ok((sort.plain_tag_name()?, sort.order == SortOrder::Desc)))
+            .collect::<Result<Vec<(Vec<u8>, bool)>, WalletQueryError>>()?;
+        let order_by = query::order_by_to_sql(&sort_by);
+
+        let query_qualifier = unsafe {
+            SELECTED_STRATEGY.query_qualifier()
+        };
+        match query_qualifier {
+            Some(_) => Ok(order_by),
+            None => Ok(query::with_tables(&order_by, |base_name| self._table(base_name)))
+        }
+    }
+
+    // Restricts query to tables of the wallet according to selected strategy
+    fn _qualify_query<'a>(&self, mut query_string: String, mut query_arguments: Vec<&'a dyn postgres::types::ToSql>, wallet_id_arg: &'a String)
+                          -> (String, Vec<&'a dyn postgres::types::ToSql>) {
+        let query_qualifier = unsafe {
+            SELECTED_STRATEGY.query_qualifier()
+        };
+        match query_qualifier {
+            Some(_) => {
+                query_arguments.push(wallet_id_arg);
+                let arg_str = format!(" AND i.wallet_id = ${}", query_arguments.len());
+                query_string.push_str(&arg_str);
+                let mut with_clause = false;
+                if query_string.contains("tags_plaintext") {
+                    query_arguments.push(wallet_id_arg);
+                    query_string = format!("tags_plaintext as (select * from tags_plaintext where wallet_id = ${}) {}", query_arguments.len(), query_string);
+                    with_clause = true;
+                }
+                if query_string.contains("tags_encrypted") {
+                    if with_clause {
+                        query_string = format!(", {}", query_string);
+                    }
+                    query_arguments.push(wallet_id_arg);
+                    query_string = format!("tags_encrypted as (select * from tags_encrypted where wallet_id = ${}) {}", query_arguments.len(), query_string);
+                    with_clause = true;
+                }
+                if with_clause {
+                    query_string = format!("WITH {}", query_string);
+                }
+            },
+            None => query_string = query::with_tables(&query_string, |base_name| self._table(base_name))
+        };
+        (query_string, query_arguments)
+    }
+
+    fn _tag_retriever(&self) -> Result<TagRetrieverOwned, WalletStorageError> {
+        let query_qualifier = unsafe {
+            SELECTED_STRATEGY.query_qualifier()
+        };
+        let wallet_id = query_qualifier.map(|_| self.wallet_id.clone());
+        TagRetriever::new_owned(Rc::new(self._pooled_connection()?), wallet_id, &self._table("tags_plaintext"), &self._table("tags_encrypted"))
+    }
+
+    fn _connection(&self) -> Result<StorageConnection, WalletStorageError> {
+        let transaction = self.transaction.lock().unwrap();
+
+        if transaction.is_some() {
+            return Ok(StorageConnection::Transaction(transaction));
+        }
+
+        self._pooled_connection().map(StorageConnection::Pooled)
+    }
+
+    // Searches keep own connection while iterated, so they don't see changes of active transaction
+    fn _pooled_connection(&self) -> Result<r2d2::PooledConnection<PostgresConnectionManager>, WalletStorageError> {
+        self.pool.get()
+            .map_err(|err| WalletStorageError::IOError(format!("Can't get connection from the pool: {}", err)))
+    }
+
+    fn _table(&self, base_name: &str) -> String {
+        unsafe {
+            SELECTED_STRATEGY.table_name(&self.wallet_id, base_name)
+        }
+    }
+
     fn _prepare_statement(&self, sql: &str) -> Result<
         OwningHandle<Rc<r2d2::PooledConnection<PostgresConnectionManager>>, Box<postgres::stmt::Statement<'static>>>,
         WalletStorageError> {
-            let pool = self.pool.clone();
-            OwningHandle::try_new(Rc::new(pool.get().unwrap()).clone(), |conn| {
+            OwningHandle::try_new(Rc::new(self._pooled_connection()?), |conn| {
                 unsafe { (*conn).prepare(sql) }.map(Box::new).map_err(WalletStorageError::from)
         })
     }
@@ -1821,6 +2261,23 @@ mod tests {
         assert_match!(Err(WalletStorageError::AlreadyExists), res);
     }
 
+    #[test]
+    fn postgres_storage_multi_table_name_works() {
+        let strategy = MultiWalletMultiTableStrategy {};
+        assert_eq!("\"items_walle1\"", strategy.table_name("walle1", "items"));
+        assert_eq!("\"tags_plaintext_wal\"\"le1\"", strategy.table_name("wal\"le1", "tags_plaintext"));
+        assert_eq!("CREATE INDEX ON \"tags_encrypted_walle1\"(name)",
+                   MultiWalletMultiTableStrategy::_schema_sql("CREATE INDEX ON \"tags_encrypted_$1\"(name)", "walle1"));
+    }
+
+    #[test]
+    fn postgres_storage_multi_table_check_wallet_id_works() {
+        MultiWalletMultiTableStrategy::_check_wallet_id(&"w".repeat(48)).unwrap();
+
+        let res = MultiWalletMultiTableStrategy::_check_wallet_id(&"w".repeat(49));
+        assert_match!(Err(WalletStorageError::CommonError(CommonError::InvalidStructure(_))), res);
+    }
+
     #[test]
     fn postgres_storage_get_storage_metadata_works() {
         _cleanup();
@@ -2014,6 +2471,78 @@ mod tests {
         assert!(record.is_none());
     }
 
+    #[test]
+    fn postgres_storage_get_all_page_works() {
+        _cleanup();
+
+        let storage = _postgres_storage();
+        storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+        storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+        let (records, last_id) = storage.get_all_page(0, 1).unwrap();
+        assert_eq!(1, records.len());
+        assert_eq!(records[0].type_.clone().unwrap(), _type1());
+        assert_eq!(_sort(records[0].tags.clone().unwrap()), _sort(_tags()));
+
+        let (records, last_id) = storage.get_all_page(last_id.unwrap(), 1).unwrap();
+        assert_eq!(1, records.len());
+        assert_eq!(records[0].type_.clone().unwrap(), _type2());
+
+        let (records, last_id) = storage.get_all_page(last_id.unwrap(), 1).unwrap();
+        assert!(records.is_empty());
+        assert!(last_id.is_none());
+    }
+
+    #[test]
+    fn postgres_storage_search_page_works() {
+        _cleanup();
+
+        let storage = _postgres_storage();
+        storage.add(&_type1(), &_id(1), &_value(1), &_tags()).unwrap();
+        storage.add(&_type2(), &_id(2), &_value(2), &_tags()).unwrap();
+        storage.add(&_type1(), &_id(3), &_value(3), &_tags()).unwrap();
+        storage.add(&_type1(), &_id(4), &_value(4), &_tags()).unwrap();
+
+        let query = language::parse_from_json("{}").unwrap();
+
+        let (records, last_id) = storage.search_page(&_type1(), &query, None, 0, 0, 2).unwrap();
+        assert_eq!(vec![_id(1), _id(3)], records.into_iter().map(|record| record.id).collect::<Vec<Vec<u8>>>());
+
+        let (records, last_id) = storage.search_page(&_type1(), &query, None, last_id.unwrap(), 0, 2).unwrap();
+        assert_eq!(vec![_id(4)], records.into_iter().map(|record| record.id).collect::<Vec<Vec<u8>>>());
+
+        let (records, last_id) = storage.search_page(&_type1(), &query, None, last_id.unwrap(), 0, 2).unwrap();
+        assert!(records.is_empty());
+        assert!(last_id.is_none());
+
+        let (records, _) = storage.search_page(&_type1(), &query, None, 0, 1, 2).unwrap();
+        assert_eq!(vec![_id(3), _id(4)], records.into_iter().map(|record| record.id).collect::<Vec<Vec<u8>>>());
+
+        assert_eq!(Some(3), storage.search_total_count(&_type1(), &query).unwrap());
+    }
+
+    #[test]
+    fn postgres_storage_aggregate_works() {
+        _cleanup();
+
+        let storage = _postgres_storage();
+        storage.add(&_type1(), &_id(1), &_value(1), &[Tag::PlainText(vec![1], "a".to_string())]).unwrap();
+        storage.add(&_type1(), &_id(2), &_value(2), &[Tag::PlainText(vec![1], "a".to_string()), Tag::PlainText(vec![2], "x".to_string())]).unwrap();
+        storage.add(&_type1(), &_id(3), &_value(3), &[Tag::PlainText(vec![1], "b".to_string())]).unwrap();
+        storage.add(&_type2(), &_id(4), &_value(4), &[Tag::PlainText(vec![1], "a".to_string())]).unwrap();
+
+        let query = language::parse_from_json("{}").unwrap();
+
+        let groups = storage.aggregate(&_type1(), &query, &[vec![1]]).unwrap();
+        assert_eq!(vec![(vec![Some("a".to_string())], 2), (vec![Some("b".to_string())], 1)], groups);
+
+        let groups = storage.aggregate(&_type1(), &query, &[vec![2]]).unwrap();
+        assert_eq!(vec![(vec![Some("x".to_string())], 1), (vec![None], 2)], groups);
+
+        let groups = storage.aggregate(&_type1(), &query, &[]).unwrap();
+        assert_eq!(vec![(vec![], 3)], groups);
+    }
+
     #[test]
     fn postgres_storage_get_all_works_for_empty() {
         _cleanup();
@@ -2286,6 +2815,12 @@ mod tests {
         res
     }
 
+    fn _postgres_storage() -> Box<PostgresStorage> {
+        let storage_type = PostgresStorageType::new();
+        storage_type.create_storage(_wallet_id(), Some(&_wallet_config()[..]), Some(&_wallet_credentials()[..]), &_metadata()).unwrap();
+        storage_type.open_storage(_wallet_id(), Some(&_wallet_config()[..]), Some(&_wallet_credentials()[..])).unwrap()
+    }
+
     fn _storage_db_pool() -> Box<WalletStorage> {
         let storage_type = PostgresStorageType::new();
         storage_type.create_storage(_wallet_id(), Some(&_wallet_config_db_pool()[..]), Some(&_wallet_credentials()[..]), &_metadata()).unwrap();
@@ -2300,6 +2835,9 @@ mod tests {
                 if scheme == "MultiWalletSingleTable" {
                     return _wallet_config_multi();
                 }
+                if scheme == "MultiWalletMultiTable" {
+                    return _wallet_config_multi_table();
+                }
             },
             Err(_) => ()
         };
@@ -2317,6 +2855,14 @@ mod tests {
         config
     }
 
+    fn _wallet_config_multi_table() -> String {
+        let config = json!({
+            "url": "localhost:5432".to_owned(),
+            "wallet_scheme": "MultiWalletMultiTable".to_owned()
+        }).to_string();
+        config
+    }
+
     fn _wallet_config_db_pool() -> String {
         let config = json!({
             "url": "localhost:5432".to_owned(),
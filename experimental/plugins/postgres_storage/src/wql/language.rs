@@ -54,7 +54,7 @@ impl From<String> for TargetValue {
 impl string::ToString for TargetValue {
     fn to_string(&self) -> String {
         match *self {
-            TargetValue::Unencrypted(ref s) => format!(r#""{}""#, s),
+            TargetValue::Unencrypted(ref s) => serde_json::Value::String(s.clone()).to_string(),
             TargetValue::Encrypted(ref v) => format!(r#""{}""#, base64::encode(v)),
         }
     }
@@ -74,6 +74,9 @@ pub enum Operator {
     Lt(TagName, TargetValue),
     Lte(TagName, TargetValue),
     Like(TagName, TargetValue),
+    ILike(TagName, TargetValue),
+    Regex(TagName, TargetValue),
+    Exists(TagName, bool),
     In(TagName, Vec<TargetValue>),
 }
 
@@ -137,6 +140,9 @@ impl string::ToString for Operator {
             Operator::Lt(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$lt":{}}}}}"#, tag_name.to_string(), tag_value.to_string()),
             Operator::Lte(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$lte":{}}}}}"#, tag_name.to_string(), tag_value.to_string()),
             Operator::Like(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$like":{}}}}}"#, tag_name.to_string(), tag_value.to_string()),
+            Operator::ILike(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$ilike":{}}}}}"#, tag_name.to_string(), tag_value.to_string()),
+            Operator::Regex(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$regex":{}}}}}"#, tag_name.to_string(), tag_value.to_string()),
+            Operator::Exists(ref tag_name, exists) => format!(r#"{{{}:{{"$exists":{}}}}}"#, tag_name.to_string(), exists),
             Operator::In(ref tag_name, ref tag_values) => {
                 format!(
                     r#"{{{}:{{"$in":[{}]}}}}"#,
@@ -311,6 +317,33 @@ fn parse_single_operator(operator_name: String, key: String, value: serde_json::
             }
         }
         ("$like", _) => Err(WalletQueryError::ValueErr("$like must be used with string".to_string())),
+        ("$ilike", serde_json::Value::String(s)) => {
+            let target_name = _build_target_name(&key, encrypted);
+            match target_name {
+                TagName::PlainTagName(_) => {
+                    let target_value = _build_target_value(&target_name, &s, encrypted);
+                    Ok(Operator::ILike(target_name, target_value))
+                },
+                TagName::EncryptedTagName(_) => Err(WalletQueryError::StructureErr("$ilike must be used only for nonencrypted tag".to_string()))
+            }
+        }
+        ("$ilike", _) => Err(WalletQueryError::ValueErr("$ilike must be used with string".to_string())),
+        ("$regex", serde_json::Value::String(s)) => {
+            let target_name = _build_target_name(&key, encrypted);
+            match target_name {
+                TagName::PlainTagName(_) => {
+                    let target_value = _build_target_value(&target_name, &s, encrypted);
+                    Ok(Operator::Regex(target_name, target_value))
+                },
+                TagName::EncryptedTagName(_) => Err(WalletQueryError::StructureErr("$regex must be used only for nonencrypted tag".to_string()))
+            }
+        }
+        ("$regex", _) => Err(WalletQueryError::ValueErr("$regex must be used with string".to_string())),
+        ("$exists", serde_json::Value::Bool(exists)) => {
+            let target_name = _build_target_name(&key, encrypted);
+            Ok(Operator::Exists(target_name, exists))
+        }
+        ("$exists", _) => Err(WalletQueryError::ValueErr("$exists must be used with bool".to_string())),
         ("$in", serde_json::Value::Array(values)) => {
             let mut target_values: Vec<TargetValue> = Vec::new();
 
@@ -398,9 +431,14 @@ mod tests {
                 | (Operator::Gte(name, value), Operator::Gte(other_name, other_value))
                 | (Operator::Lt(name, value), Operator::Lt(other_name, other_value))
                 | (Operator::Lte(name, value), Operator::Lte(other_name, other_value))
-                | (Operator::Like(name, value), Operator::Like(other_name, other_value)) => {
+                | (Operator::Like(name, value), Operator::Like(other_name, other_value))
+                | (Operator::ILike(name, value), Operator::ILike(other_name, other_value))
+                | (Operator::Regex(name, value), Operator::Regex(other_name, other_value)) => {
                     name == other_name && value == other_value
                 },
+                (Operator::Exists(name, exists), Operator::Exists(other_name, other_exists)) => {
+                    name == other_name && exists == other_exists
+                },
                 (Operator::In(name, values), Operator::In(other_name, other_values)) => {
                     name == other_name && vec_to_set(values) == vec_to_set(other_values)
                 },
@@ -653,6 +691,61 @@ mod tests {
         assert_eq!(query, expected);
     }
 
+    #[test]
+    fn test_simple_operator_ilike_plaintext_parse() {
+        let name1 = _random_string(10);
+        let value1 = _random_string(10);
+
+        let json = format!(r#"{{"~{}":{{"$ilike":"{}"}}}}"#, name1, value1);
+
+        let query = parse_from_json(&json).unwrap();
+
+        let expected = Operator::ILike(
+            TagName::PlainTagName(name1.to_vec()),
+            TargetValue::Unencrypted(value1.clone())
+        );
+
+        assert_eq!(query, expected);
+    }
+
+    #[test]
+    fn test_simple_operator_regex_plaintext_parse() {
+        let name1 = _random_string(10);
+
+        let json = format!(r#"{{"~{}":{{"$regex":"^\\d+$"}}}}"#, name1);
+
+        let query = parse_from_json(&json).unwrap();
+
+        let expected = Operator::Regex(
+            TagName::PlainTagName(name1.to_vec()),
+            TargetValue::Unencrypted(r"^\d+$".to_string())
+        );
+
+        assert_eq!(query, expected);
+    }
+
+    #[test]
+    fn test_simple_operator_regex_encrypted_parse() {
+        let name1 = _random_string(10);
+
+        let json = format!(r#"{{"{}":{{"$regex":"^a"}}}}"#, name1);
+
+        assert!(parse_from_json(&json).is_err());
+    }
+
+    #[test]
+    fn test_simple_operator_exists_parse() {
+        let name1 = _random_string(10);
+
+        let json = format!(r#"{{"{}":{{"$exists":true}}}}"#, name1);
+
+        let query = parse_from_json(&json).unwrap();
+
+        let expected = Operator::Exists(TagName::EncryptedTagName(name1.to_vec()), true);
+
+        assert_eq!(query, expected);
+    }
+
     #[test]
     fn test_simple_operator_in_plaintext_parse() {
         let name1 = _random_string(10);
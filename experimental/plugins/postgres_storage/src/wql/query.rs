@@ -32,6 +32,74 @@ pub fn wql_to_sql_count<'a>(class: &'a Vec<u8>, op: &'a Operator) -> Result<(Str
     Ok((convert_query_to_psql_args(&query_string), arguments))
 }
 
+// Values of plain tags used for grouping are selected by subqueries with tag names inlined
+// as hex literals, so arguments keep the order of WQL clause. GROUP BY clause is returned
+// separately as wallet qualifier must be added to WHERE clause before it
+pub fn wql_to_sql_aggregate<'a>(class: &'a Vec<u8>, op: &'a Operator, group_by: &[Vec<u8>]) -> Result<(String, Vec<&'a dyn ToSql>), WalletQueryError> {
+    let mut arguments: Vec<&dyn ToSql> = Vec::new();
+    arguments.push(class);
+    let clause_string = operator_to_sql(op, &mut arguments)?;
+    let mut columns: Vec<String> = group_by.iter()
+        .map(|name| format!("(SELECT value FROM tags_plaintext WHERE item_id = i.id AND name = decode('{}', 'hex'))", to_hex(name)))
+        .collect();
+    columns.push("count(*)".to_string());
+    let mut query_string = format!("SELECT {} FROM items as i WHERE i.type = $$", columns.join(", "));
+    if !clause_string.is_empty() {
+        query_string.push_str(" AND ");
+        query_string.push_str(&clause_string);
+    }
+    Ok((convert_query_to_psql_args(&query_string), arguments))
+}
+
+
+pub fn group_by_to_sql(columns_count: usize) -> String {
+    if columns_count == 0 {
+        return String::new();
+    }
+    let positions: Vec<String> = (1..=columns_count).map(|position| position.to_string()).collect();
+    format!(" GROUP BY {} ORDER BY {}", positions.join(", "), positions.join(", "))
+}
+
+
+// Orders by values of plain tags selected by subqueries with tag names inlined as hex literals,
+// records without the tag come first in ascending order as in SQLite. Item id makes order stable
+pub fn order_by_to_sql(sort_by: &[(Vec<u8>, bool)]) -> String {
+    let mut clauses: Vec<String> = sort_by.iter()
+        .map(|&(ref name, desc)| format!("(SELECT value FROM tags_plaintext WHERE item_id = i.id AND name = decode('{}', 'hex')) {}",
+                                          to_hex(name), if desc { "DESC NULLS LAST" } else { "ASC NULLS FIRST" }))
+        .collect();
+    clauses.push("i.id".to_string());
+    format!(" ORDER BY {}", clauses.join(", "))
+}
+
+
+// Replaces base table names in a generated query with physical tables of the wallet.
+// Table names only follow FROM and all user supplied values are passed as arguments,
+// so the replacement can't touch anything else. Query is scanned once, so physical
+// table names are never replaced again
+pub fn with_tables<F>(query: &str, table: F) -> String where F: Fn(&str) -> String {
+    let mut parts = query.split("FROM ");
+    let mut result = parts.next().unwrap_or("").to_string();
+    for part in parts {
+        result.push_str("FROM ");
+        let base_name = ["items", "tags_plaintext", "tags_encrypted"].iter()
+            .find(|base_name| part.starts_with(&format!("{} ", base_name)));
+        match base_name {
+            Some(base_name) => {
+                result.push_str(&table(base_name));
+                result.push_str(&part[base_name.len()..]);
+            }
+            None => result.push_str(part)
+        }
+    }
+    result
+}
+
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
 fn convert_query_to_psql_args(query: &str) -> String {
     let mut index = 1;
     let mut s: String = query.to_owned();
@@ -52,6 +120,9 @@ fn operator_to_sql<'a>(op: &'a Operator, arguments: &mut Vec<&'a dyn ToSql>) ->
         Operator::Lt(ref tag_name, ref target_value) => lt_to_sql(tag_name, target_value, arguments),
         Operator::Lte(ref tag_name, ref target_value) => lte_to_sql(tag_name, target_value, arguments),
         Operator::Like(ref tag_name, ref target_value) => like_to_sql(tag_name, target_value, arguments),
+        Operator::ILike(ref tag_name, ref target_value) => ilike_to_sql(tag_name, target_value, arguments),
+        Operator::Regex(ref tag_name, ref target_value) => regex_to_sql(tag_name, target_value, arguments),
+        Operator::Exists(ref tag_name, exists) => Ok(exists_to_sql(tag_name, exists, arguments)),
         Operator::In(ref tag_name, ref target_values) => in_to_sql(tag_name, target_values, arguments),
         Operator::And(ref suboperators) => and_to_sql(suboperators, arguments),
         Operator::Or(ref suboperators) => or_to_sql(suboperators, arguments),
@@ -154,6 +225,44 @@ fn like_to_sql<'a>(name: &'a TagName, value: &'a TargetValue, arguments: &mut Ve
 }
 
 
+fn ilike_to_sql<'a>(name: &'a TagName, value: &'a TargetValue, arguments: &mut Vec<&'a dyn ToSql>) -> Result<String, WalletQueryError> {
+    match (name, value) {
+        (&TagName::PlainTagName(ref queried_name), &TargetValue::Unencrypted(ref queried_value)) => {
+            arguments.push(queried_name);
+            arguments.push(queried_value);
+            Ok("(i.id in (SELECT item_id FROM tags_plaintext WHERE name = $$ AND value ILIKE $$))".to_string())
+        },
+        _ => Err(WalletQueryError::StructureErr("$ilike operator can be used only with plaintext tags".to_string()))
+    }
+}
+
+
+// Pattern is POSIX regular expression evaluated by postgres
+fn regex_to_sql<'a>(name: &'a TagName, value: &'a TargetValue, arguments: &mut Vec<&'a dyn ToSql>) -> Result<String, WalletQueryError> {
+    match (name, value) {
+        (&TagName::PlainTagName(ref queried_name), &TargetValue::Unencrypted(ref queried_value)) => {
+            arguments.push(queried_name);
+            arguments.push(queried_value);
+            Ok("(i.id in (SELECT item_id FROM tags_plaintext WHERE name = $$ AND value ~ $$))".to_string())
+        },
+        _ => Err(WalletQueryError::StructureErr("$regex operator can be used only with plaintext tags".to_string()))
+    }
+}
+
+
+fn exists_to_sql<'a>(name: &'a TagName, exists: bool, arguments: &mut Vec<&'a dyn ToSql>) -> String {
+    let (queried_name, table) = match *name {
+        TagName::PlainTagName(ref queried_name) => (queried_name, "tags_plaintext"),
+        TagName::EncryptedTagName(ref queried_name) => (queried_name, "tags_encrypted"),
+    };
+
+    arguments.push(queried_name);
+
+    let in_ = if exists { "in" } else { "not in" };
+    format!("(i.id {} (SELECT item_id FROM {} WHERE name = $$))", in_, table)
+}
+
+
 fn in_to_sql<'a>(name: &'a TagName, values: &'a Vec<TargetValue>, arguments: &mut Vec<&'a dyn ToSql>) -> Result<String, WalletQueryError> {
     let mut in_string = String::new();
     match name {
@@ -256,4 +365,44 @@ mod tests {
         let (query, _arguments) = wql_to_sql(&class, &query, None).unwrap();
         assert_eq!(query, "SELECT i.id, i.name, i.value, i.key, i.type FROM items as i WHERE i.type = $1 AND (((i.id in (SELECT item_id FROM tags_encrypted WHERE name = $2 AND value = $3)) AND (i.id in (SELECT item_id FROM tags_plaintext WHERE name = $4 AND value = $5))) OR ((i.id in (SELECT item_id FROM tags_encrypted WHERE name = $6 AND value = $7)) AND NOT ((i.id in (SELECT item_id FROM tags_plaintext WHERE name = $8 AND value = $9)))))")
     }
+
+    #[test]
+    fn exists_ilike_and_regex() {
+        let query = Operator::And(vec![
+            Operator::Exists(TagName::EncryptedTagName(vec![1,2,3]), false),
+            Operator::ILike(TagName::PlainTagName(vec![4,5,6]), TargetValue::Unencrypted("Spam%".to_string())),
+            Operator::Regex(TagName::PlainTagName(vec![7,8,9]), TargetValue::Unencrypted("^eggs$".to_string())),
+        ]);
+        let class = vec![100,100,100];
+        let (query, arguments) = wql_to_sql_count(&class, &query).unwrap();
+        assert_eq!(query, "SELECT count(*) FROM items as i WHERE i.type = $1 AND ((i.id not in (SELECT item_id FROM tags_encrypted WHERE name = $2)) AND (i.id in (SELECT item_id FROM tags_plaintext WHERE name = $3 AND value ILIKE $4)) AND (i.id in (SELECT item_id FROM tags_plaintext WHERE name = $5 AND value ~ $6)))");
+        assert_eq!(arguments.len(), 6);
+    }
+
+    #[test]
+    fn aggregate_by_plain_tags() {
+        let query = Operator::Eq(TagName::PlainTagName(vec![1,2,3]), TargetValue::Unencrypted("spam".to_string()));
+        let class = vec![100,100,100];
+        let (query, arguments) = wql_to_sql_aggregate(&class, &query, &[vec![4,5,6], vec![7,8,255]]).unwrap();
+        assert_eq!(query, "SELECT (SELECT value FROM tags_plaintext WHERE item_id = i.id AND name = decode('040506', 'hex')), (SELECT value FROM tags_plaintext WHERE item_id = i.id AND name = decode('0708ff', 'hex')), count(*) FROM items as i WHERE i.type = $1 AND (i.id in (SELECT item_id FROM tags_plaintext WHERE name = $2 AND value = $3))");
+        assert_eq!(arguments.len(), 3);
+        assert_eq!(group_by_to_sql(2), " GROUP BY 1, 2 ORDER BY 1, 2");
+        assert_eq!(group_by_to_sql(0), "");
+    }
+
+    #[test]
+    fn with_tables_works() {
+        let query = Operator::Exists(TagName::PlainTagName(vec![1,2,3]), true);
+        let class = vec![100,100,100];
+        let (query, _arguments) = wql_to_sql(&class, &query, None).unwrap();
+        let query = with_tables(&query, |base_name| format!("\"{}_wallet\"", base_name));
+        assert_eq!(query, "SELECT i.id, i.name, i.value, i.key, i.type FROM \"items_wallet\" as i WHERE i.type = $1 AND (i.id in (SELECT item_id FROM \"tags_plaintext_wallet\" WHERE name = $2))");
+    }
+
+    #[test]
+    fn ilike_for_encrypted_tag_fails() {
+        let query = Operator::ILike(TagName::EncryptedTagName(vec![1,2,3]), TargetValue::Encrypted(vec![4,5,6]));
+        let class = vec![100,100,100];
+        assert!(wql_to_sql(&class, &query, None).is_err());
+    }
 }
@@ -4,6 +4,7 @@ use std::ops::Deref;
 pub struct Transaction<'conn> {
     conn: &'conn Connection,
     committed: bool,
+    savepoint: bool,
 }
 
 impl<'conn> Transaction<'conn> {
@@ -15,6 +16,21 @@ impl<'conn> Transaction<'conn> {
                 Transaction {
                     conn,
                     committed: false,
+                    savepoint: false,
+                }
+            })
+    }
+
+    /// Begin a savepoint inside of transaction that is already started on the connection.
+    /// Commit releases the savepoint and rollback reverts changes made after it.
+    pub fn savepoint(conn: &Connection) -> Result<Transaction> {
+        let query = "SAVEPOINT wallet_operation";
+        conn.batch_execute(query)
+            .map(move |_| {
+                Transaction {
+                    conn,
+                    committed: false,
+                    savepoint: true,
                 }
             })
     }
@@ -26,7 +42,11 @@ impl<'conn> Transaction<'conn> {
 
     fn commit_(&mut self) -> Result<()> {
         self.committed = true;
-        self.conn.batch_execute("COMMIT")
+        if self.savepoint {
+            self.conn.batch_execute("RELEASE SAVEPOINT wallet_operation")
+        } else {
+            self.conn.batch_execute("COMMIT")
+        }
     }
 
     /// A convenience method which consumes and rolls back a transaction.
@@ -37,7 +57,11 @@ impl<'conn> Transaction<'conn> {
 
     fn rollback_(&mut self) -> Result<()> {
         self.committed = true;
-        self.conn.batch_execute("ROLLBACK")
+        if self.savepoint {
+            self.conn.batch_execute("ROLLBACK TO SAVEPOINT wallet_operation; RELEASE SAVEPOINT wallet_operation")
+        } else {
+            self.conn.batch_execute("ROLLBACK")
+        }
     }
 
     /// Consumes the transaction, committing or rolling back according to the current setting
@@ -88,6 +88,54 @@ extern "C" {
                                                               const char*   cred_def_json)
                                          );
 
+    /// Resolves DID to W3C DID Document built from ledger data.
+    /// If data is present inside of cache, cached data is returned.
+    /// Otherwise data is fetched from the ledger and stored inside of cache for future use.
+    ///
+    /// DID Document contains NYM verkey as Ed25519VerificationKey2018 verification method
+    /// and X25519 key agreement key derived from it, services built from `endpoint` ATTRIB
+    /// and content of `diddocContent` ATTRIB merged in.
+    /// Ledger replies are verified by state proof or consensus of the pool nodes.
+    ///
+    /// EXPERIMENTAL
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// pool_handle: pool handle (created by open_pool_ledger).
+    /// wallet_handle: wallet handle (created by open_wallet).
+    /// did: DID to resolve.
+    /// options_json:
+    ///  {
+    ///    noCache: (bool, optional, false by default) Skip usage of cache,
+    ///    noUpdate: (bool, optional, false by default) Use only cached data, do not try to update.
+    ///    noStore: (bool, optional, false by default) Skip storing fresh data if updated,
+    ///    minFresh: (int, optional, 3600 by default) Return cached data if not older than this many seconds. -1 means do not check age.
+    ///  }
+    ///
+    /// #Returns
+    /// DID resolution result json:
+    /// {
+    ///     didDocument: DID Document json,
+    ///     didDocumentMetadata: {
+    ///         seqNo: (optional) sequence number of NYM transaction,
+    ///         txnTime: (optional) time of NYM transaction,
+    ///         role: (optional) role of DID,
+    ///     },
+    ///     didResolutionMetadata: {
+    ///         contentType: "application/did+ld+json",
+    ///         lastTxnTime: (optional) time of ledger state NYM reply was verified against,
+    ///     }
+    /// }
+    extern indy_error_t indy_resolve_did(indy_handle_t command_handle,
+                                         indy_handle_t pool_handle,
+                                         indy_handle_t wallet_handle,
+                                         const char *  did,
+                                         const char *  options_json,
+                                         void          (*cb)(indy_handle_t command_handle_,
+                                                             indy_error_t  err,
+                                                             const char*   did_resolution_result_json)
+                                        );
+
     /// Purge schema cache.
     ///
     /// EXPERIMENTAL
@@ -123,6 +171,24 @@ extern "C" {
                                                   void          (*cb)(indy_handle_t command_handle_,
                                                                       indy_error_t  err)
                                                  );
+
+    /// Purge DID Document cache.
+    ///
+    /// EXPERIMENTAL
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// wallet_handle: wallet handle (created by open_wallet).
+    /// options_json:
+    ///  {
+    ///    maxAge: (int, optional, -1 by default) Purge cached data if older than this many seconds. -1 means purge all.
+    ///  }
+    extern indy_error_t indy_purge_did_doc_cache(indy_handle_t command_handle,
+                                                 indy_handle_t wallet_handle,
+                                                 const char *  options_json,
+                                                 void          (*cb)(indy_handle_t command_handle_,
+                                                                     indy_error_t  err)
+                                                );
 #ifdef __cplusplus
 }
 #endif
@@ -16,7 +16,13 @@ extern "C" {
     /// {
     ///     "seed": string, (optional) Seed that allows deterministic key creation (if not set random one will be created).
     ///                                Can be UTF-8, base64 or hex string.
-    ///     "crypto_type": string, // Optional (if not set then ed25519 curve is used); Currently only 'ed25519' value is supported for this field.
+    ///     "crypto_type": string, // Optional (if not set then ed25519 curve is used); Supported values are 'ed25519', 'secp256k1' and 'p256'. 'secp256k1' and 'p256' keys can be used only for signing.
+    ///     "custody": string, // Optional name of registered key custody that keeps secret key instead of the wallet.
+    ///     "mnemonic": { // Optional BIP-39 mnemonic to derive ed25519 key seed from instead of "seed" (see indy_generate_mnemonic).
+    ///         "phrase": string, // Mnemonic words separated by spaces.
+    ///         "passphrase": string, // Optional BIP-39 passphrase.
+    ///         "derivation_path": string, // SLIP-0010 path with hardened indexes only, for example "m/0'/1'".
+    ///     }
     /// }
     /// cb: Callback that takes command result as parameter.
     ///
@@ -40,6 +46,36 @@ extern "C" {
                                                                 const char *const vk)
                                        );
 
+    /// Generates BIP-39 mnemonic for deterministic creation of keys and DIDs.
+    ///
+    /// Mnemonic is not stored in the wallet. The same keys and DIDs can be created again in any wallet
+    /// by passing mnemonic with the same derivation path to indy_create_key or indy_create_and_store_my_did.
+    ///
+    /// #Params
+    /// command_handle: Command handle to map callback to caller context.
+    /// config_json: Mnemonic config as json. Example:
+    /// {
+    ///     "word_count": int, // Optional number of words (if not set then 24 is used); Supported values are 12, 15, 18, 21 and 24.
+    /// }
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Error Code
+    /// cb:
+    /// - command_handle_: command handle to map callback to caller context.
+    /// - err: Error code.
+    /// - mnemonic: Mnemonic words of BIP-39 English wordlist separated by spaces.
+    ///
+    /// #Errors
+    /// Common*
+    extern indy_error_t indy_generate_mnemonic(indy_handle_t     command_handle,
+                                               const char *const config_json,
+
+                                               void              (*cb)(indy_handle_t     command_handle,
+                                                                       indy_error_t      err,
+                                                                       const char *const mnemonic)
+                                              );
+
     /// Saves/replaces the meta information for the giving key in the wallet.
     ///
     /// #Params
@@ -165,6 +201,81 @@ extern "C" {
                                                                 indy_bool_t   valid )
                                           );
 
+    /// Signs a payload with a key and serializes the result as JWS (RFC 7515).
+    ///
+    /// Algorithm is defined by the key crypto type: EdDSA for ed25519, ES256K for secp256k1 and ES256 for p256 keys.
+    ///
+    /// Note to use DID keys with this function you can call indy_key_for_did to get key id (verkey)
+    /// for specific DID.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// signer_vk: id (verkey) of my key. The key must be created by calling indy_create_key or indy_create_and_store_my_did
+    /// payload_raw: a pointer to first byte of payload to be signed
+    /// payload_len: a payload length
+    /// header_json: (optional) additional protected header parameters as json object.
+    ///              "alg" is set from the key and "kid" defaults to signer_vk.
+    ///              Example: {"typ": "JWT", "kid": "did:example:123#key-1"}
+    /// detached: whether payload must be omitted from the result (RFC 7515 Appendix F)
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// jws_compact: JWS in compact serialization: <protected>.<payload>.<signature> (payload is empty if detached)
+    /// jws_json: JWS in flattened JSON serialization:
+    ///   {
+    ///     "protected": <b64URLencode(header)>,
+    ///     "payload": <b64URLencode(payload)>, // omitted if detached
+    ///     "signature": <b64URLencode(signature)>
+    ///   }
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Crypto*
+    extern indy_error_t indy_sign_jws(indy_handle_t      command_handle,
+                                      indy_handle_t      wallet_handle,
+                                      const char *       signer_vk,
+                                      const indy_u8_t *  payload_raw,
+                                      indy_u32_t         payload_len,
+                                      const char *       header_json,
+                                      indy_bool_t        detached,
+
+                                      void           (*cb)(indy_handle_t command_handle_,
+                                                           indy_error_t  err,
+                                                           const char *  jws_compact,
+                                                           const char *  jws_json)
+                                     );
+
+    /// Verifies a JWS (RFC 7515) created by indy_sign_jws or by any other EdDSA, ES256K or ES256 signer.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// jws: JWS in compact or JSON (flattened or general) serialization.
+    /// signer_vk: verkey of the expected signer. "kid" header parameter isn't trusted as it is set by the signer.
+    ///            For general JSON serialization JWS is valid if any of signatures is made by signer_vk.
+    /// detached_payload_raw: (optional) a pointer to first byte of payload for JWS with detached content
+    /// detached_payload_len: a detached payload length
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// valid: true - if signature is valid, false - otherwise
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Crypto*
+    extern indy_error_t indy_verify_jws(indy_handle_t      command_handle,
+                                        const char *       jws,
+                                        const char *       signer_vk,
+                                        const indy_u8_t *  detached_payload_raw,
+                                        indy_u32_t         detached_payload_len,
+
+                                        void           (*cb)(indy_handle_t command_handle_,
+                                                             indy_error_t  err,
+                                                             indy_bool_t   valid )
+                                       );
+
     /// **** THIS FUNCTION WILL BE DEPRECATED USE indy_pack_message() INSTEAD ****
     /// Encrypt a message by authenticated-encryption scheme.
     ///
@@ -403,9 +514,82 @@ extern "C" {
                                                                indy_u32_t        jwe_msg_len)
                                           );
 
+    /// Packs a message by encrypting the message and serializes it in the selected envelope format (Experimental)
+    ///
+    /// Note to use DID keys with this function you can call indy_key_for_did to get key id (verkey)
+    /// for specific DID.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handle (created by open_wallet).
+    /// message: a pointer to the first byte of the message to be packed
+    /// message_len: the length of the message
+    /// receivers: a string in the format of a json list which will contain the list of receiver's keys
+    ///                the message is being encrypted for.
+    ///                Example:
+    ///                "[<receiver edge_agent_1 verkey>, <receiver edge_agent_2 verkey>]"
+    /// sender: the sender's verkey as a string When null pointer is used in this parameter, anoncrypt is used
+    /// options_json: pack options as json:
+    ///   {
+    ///     format: (optional, string) envelope format. One of:
+    ///             "legacy" - JWE-like format produced by indy_pack_message (default)
+    ///             "didcomm_v2" - standard JWE defined by DIDComm Messaging v2.
+    ///                            ECDH-1PU+A256KW is used if sender is set and ECDH-ES+A256KW otherwise.
+    ///                            Key agreement is done over X25519 keys converted from ed25519 verkeys.
+    ///                            Keys are identified by did:key DID URLs: did:key:<multikey>#<multikey>.
+    ///   }
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// a JWE in legacy format described for indy_pack_message or a JWE in DIDComm v2 format defined below:
+    /// {
+    ///     "protected": "b64URLencoded({
+    ///        "typ": "application/didcomm-encrypted+json",
+    ///        "alg": "ECDH-1PU+A256KW" or "ECDH-ES+A256KW",
+    ///        "enc": "A256CBC-HS512",
+    ///        "skid": <sender key id> (authcrypt only),
+    ///        "apu": b64URLencode(<sender key id>) (authcrypt only),
+    ///        "apv": b64URLencode(sha256(<sorted recipient key ids joined with '.'>)),
+    ///        "epk": {"kty": "OKP", "crv": "X25519", "x": b64URLencode(<ephemeral public key>)}
+    ///     })",
+    ///     "recipients": [
+    ///         {
+    ///             "encrypted_key": b64URLencode(A256KW(kek, cek)),
+    ///             "header": {
+    ///                 "kid": <recipient key id>
+    ///             }
+    ///         },
+    ///     ],
+    ///     "iv": b64URLencode(iv),
+    ///     "ciphertext": b64URLencode(ciphertext),
+    ///     "tag": b64URLencode(tag)
+    /// }
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Ledger*
+    /// Crypto*
+    extern indy_error_t indy_pack_message_with_options(indy_handle_t      command_handle,
+                                                       indy_handle_t      wallet_handle,
+                                                       const indy_u8_t*   message,
+                                                       indy_u32_t         message_len,
+                                                       const char *       receiver_keys,
+                                                       const char *       sender,
+                                                       const char *       options_json,
+
+                                                       void           (*cb)(indy_handle_t     command_handle_,
+                                                                            indy_error_t      err,
+                                                                            const indy_u8_t*  jwe_msg_raw,
+                                                                            indy_u32_t        jwe_msg_len)
+                                                       );
+
 
     /// Unpacks a JWE-like formatted message outputted by indy_pack_message (Experimental)
     ///
+    /// Envelope format is detected automatically, so DIDComm v2 JWE produced by
+    /// indy_pack_message_with_options can be unpacked as well.
+    ///
     /// #Params
     /// command_handle: command handle to map callback to user context.
     /// wallet_handle: wallet handle (created by open_wallet).
@@ -35,6 +35,34 @@ extern "C" {
                                                                     indy_error_t err)
                                               );
 
+    /// Create a new non-secret record in the wallet with additional options
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context
+    /// wallet_handle: wallet handle (created by open_wallet)
+    /// type_: allows to separate different record types collections
+    /// id: the id of record
+    /// value: the value of record
+    /// tags_json: (optional) the record tags used for search and storing meta information as json.
+    ///   Tags have the same format as in indy_add_wallet_record.
+    /// options_json: record options as json:
+    ///   {
+    ///     expiresIn: (optional, int) number of seconds after which the record expires.
+    ///                Expired records are not returned by get and search calls and are deleted lazily.
+    ///                Record never expires if not set
+    ///   }
+
+    extern indy_error_t indy_add_wallet_record_with_options(indy_handle_t  command_handle,
+                                                            indy_handle_t  wallet_handle,
+                                                            const char*    type_,
+                                                            const char*    id,
+                                                            const char*    value,
+                                                            const char*    tags_json,
+                                                            const char*    options_json,
+                                                            void           (*fn)(indy_handle_t command_handle_,
+                                                                                 indy_error_t err)
+                                                           );
+
     /// Update a non-secret wallet record value
     ///
     /// #Params
@@ -53,6 +81,30 @@ extern "C" {
                                                                              indy_error_t err)
                                                        );
 
+    /// Update a non-secret wallet record value with additional options
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context
+    /// wallet_handle: wallet handle (created by open_wallet)
+    /// type_: allows to separate different record types collections
+    /// id: the id of record
+    /// value: the new value of record
+    /// options_json: record options as json:
+    ///   {
+    ///     expiresIn: (optional, int or null) number of seconds from now after which the record expires.
+    ///                Null makes the record never expire. Current expiry is kept if not set
+    ///   }
+
+    extern indy_error_t indy_update_wallet_record_value_with_options(indy_handle_t  command_handle,
+                                                                     indy_handle_t  wallet_handle,
+                                                                     const char*    type_,
+                                                                     const char*    id,
+                                                                     const char*    value,
+                                                                     const char*    options_json,
+                                                                     void           (*fn)(indy_handle_t command_handle_,
+                                                                                          indy_error_t err)
+                                                                    );
+
 /// Update a non-secret wallet record tags
 ///
 /// #Params
@@ -144,6 +196,33 @@ extern "C" {
                                                                        indy_error_t err)
                                                  );
 
+    /// Execute a batch of wallet record operations atomically:
+    /// either all operations are applied or none of them.
+    ///
+    /// Note that plugged storages support batches only if transaction hooks
+    /// were registered for them with indy_register_wallet_storage_transaction_hooks.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context
+    /// wallet_handle: wallet handle (created by open_wallet)
+    /// operations_json: list of operations to execute in the given order
+    ///   [
+    ///     {"operation": "add", "type": <record type>, "id": <record id>, "value": <record value>, "tags": <(optional) tags json>},
+    ///     {"operation": "updateValue", "type": <record type>, "id": <record id>, "value": <new record value>},
+    ///     {"operation": "addTags", "type": <record type>, "id": <record id>, "tags": <tags json>},
+    ///     {"operation": "updateTags", "type": <record type>, "id": <record id>, "tags": <tags json>},
+    ///     {"operation": "deleteTags", "type": <record type>, "id": <record id>, "tagNames": <list of tag names>},
+    ///     {"operation": "delete", "type": <record type>, "id": <record id>},
+    ///   ]
+    ///   Tags have the same format as in indy_add_wallet_record.
+
+    extern indy_error_t indy_wallet_batch_execute(indy_handle_t  command_handle,
+                                                  indy_handle_t  wallet_handle,
+                                                  const char*    operations_json,
+                                                  void           (*fn)(indy_handle_t command_handle_,
+                                                                       indy_error_t err)
+                                                 );
+
     /// Get an wallet record by id
     ///
     /// #Params
@@ -200,6 +279,11 @@ extern "C" {
     ///    retrieveType: (optional, false by default) Retrieve record type,
     ///    retrieveValue: (optional, true by default) Retrieve record value,
     ///    retrieveTags: (optional, true by default) Retrieve record tags,
+    ///    sortBy: (optional) List of plaintext (prefixed with "~") tag names to sort records by:
+    ///      [{"tagName": "~tagName", "order": (optional, "asc" by default) "asc" or "desc"}],
+    ///      records without a tag go first in ascending order,
+    ///    skip: (optional) Count of records to skip,
+    ///    limit: (optional) Max count of records to return,
     ///  }
     /// #Returns
     /// search_handle: Wallet search handle that can be used later
@@ -215,6 +299,30 @@ extern "C" {
                                                                      indy_handle_t search_handle)
                                                );
 
+    /// Open a search over all records of the wallet regardless of their type.
+    ///
+    /// Note that unlike indy_open_wallet_search this call also returns records
+    /// created by libindy itself (types with "Indy::" prefix) including secrets.
+    /// Returned records always contain type, value and tags.
+    ///
+    /// Records can be fetched by small batches with indy_fetch_wallet_search_next_records
+    /// and search must be closed by indy_close_wallet_search.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context
+    /// wallet_handle: wallet handle (created by open_wallet)
+    ///
+    /// #Returns
+    /// search_handle: Wallet search handle that can be used later
+    ///   to fetch records by small batches (with indy_fetch_wallet_search_next_records)
+
+    extern indy_error_t indy_open_wallet_all_records_search(indy_handle_t  command_handle,
+                                                            indy_handle_t  wallet_handle,
+                                                            void           (*fn)(indy_handle_t command_handle_,
+                                                                                 indy_error_t err,
+                                                                                 indy_handle_t search_handle)
+                                                           );
+
     /// Get an wallet record by id
     ///
     /// #Params
@@ -257,6 +365,46 @@ extern "C" {
                                                                       indy_error_t  err)
                                                 );
 
+    /// Count wallet records matching the query grouped by values of plaintext tags.
+    ///
+    /// Records are counted by wallet storage without fetching and decrypting them,
+    /// so only plaintext (prefixed with "~") tags can be used for grouping.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context
+    /// wallet_handle: wallet handle (created by open_wallet)
+    /// type_: allows to separate different record types collections
+    /// query_json: MongoDB style query to wallet record tags (the same as for indy_open_wallet_search)
+    /// aggregation_json:
+    ///  {
+    ///    groupBy: (optional) List of plaintext (prefixed with "~") tag names to group records by.
+    ///      Only total count is calculated if it is omitted or empty
+    ///  }
+    ///
+    /// #Returns
+    /// aggregation result json:
+    /// {
+    ///   count: <int>, // total count of records matching the query
+    ///   groups: [{ // distinct combinations of grouped tag values ordered by these values
+    ///       tags: {"~tagName": "tagValue", ...}, // null value if records don't have the tag
+    ///       count: <int>, // count of records in group
+    ///   }],
+    /// }
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_wallet_aggregate(indy_handle_t  command_handle,
+                                              indy_handle_t  wallet_handle,
+                                              const char*    type_,
+                                              const char*    query_json,
+                                              const char*    aggregation_json,
+                                              void           (*fn)(indy_handle_t command_handle_,
+                                                                   indy_error_t  err,
+                                                                   const char*   aggregation_result_json)
+                                             );
+
 
 #ifdef __cplusplus
 }
@@ -9,6 +9,12 @@ extern "C" {
     ///
     /// It allows library user to provide custom wallet implementation.
     ///
+    /// Names of built-in storage types ("default" and "inmem") can't be used for custom storages,
+    /// registration fails with WalletTypeAlreadyRegisteredError.
+    ///
+    /// libindy doesn't migrate schema of custom storages. Storage config with "migration" options
+    /// is passed to open handler untouched and storage is expected to migrate its own data there.
+    ///
     /// #Params
     /// command_handle: Command handle to map callback to caller context.
     /// type_: Wallet type name.
@@ -144,6 +150,62 @@ extern "C" {
                                                   void         (*fn)(indy_handle_t command_handle_, indy_error_t err)
                                                   );
 
+    /// Registers transaction handlers for custom wallet storage implementation.
+    ///
+    /// Registered handlers allow indy_wallet_batch_execute to be used with wallets
+    /// of this storage type. Handlers must be registered before the wallet is opened.
+    ///
+    /// #Params
+    /// command_handle: Command handle to map callback to caller context.
+    /// type_: Wallet type name (previously registered with indy_register_wallet_type).
+    /// begin_transaction: WalletType begin transaction operation handler
+    /// commit_transaction: WalletType commit transaction operation handler
+    /// rollback_transaction: WalletType rollback transaction operation handler
+    ///
+    /// #Returns
+    /// Error code
+
+    extern indy_error_t indy_register_wallet_storage_transaction_hooks(indy_handle_t  command_handle,
+                                                                       const char*    type_,
+                                                                       indy_error_t (*beginTransactionFn)(indy_handle_t handle),
+
+                                                                       indy_error_t (*commitTransactionFn)(indy_handle_t handle),
+
+                                                                       indy_error_t (*rollbackTransactionFn)(indy_handle_t handle),
+
+                                                                       void         (*fn)(indy_handle_t command_handle_, indy_error_t err)
+                                                                       );
+
+    /// Registers aggregation handlers for custom wallet storage implementation.
+    ///
+    /// Registered handlers allow indy_wallet_aggregate to be computed by the storage natively.
+    /// Without them records matching the query are fetched and counted one by one.
+    /// Handlers must be registered before the wallet is opened.
+    ///
+    /// #Params
+    /// command_handle: Command handle to map callback to caller context.
+    /// type_: Wallet type name (previously registered with indy_register_wallet_type).
+    /// aggregate_records: WalletType aggregate records operation handler
+    /// free_aggregation: WalletType free aggregation result operation handler
+    ///
+    /// #Returns
+    /// Error code
+
+    extern indy_error_t indy_register_wallet_storage_aggregation(indy_handle_t  command_handle,
+                                                                 const char*    type_,
+                                                                 indy_error_t (*aggregateRecordsFn)(indy_handle_t handle,
+                                                                                                    const char* type_,
+                                                                                                    const char* query_json,
+                                                                                                    const char* group_by_json,
+                                                                                                    const char** result_json_p,
+                                                                                                    indy_handle_t* result_handle_p),
+
+                                                                 indy_error_t (*freeAggregationFn)(indy_handle_t handle,
+                                                                                                   indy_handle_t result_handle),
+
+                                                                 void         (*fn)(indy_handle_t command_handle_, indy_error_t err)
+                                                                 );
+
     /// Create a new secure wallet.
     ///
     /// #Params
@@ -153,6 +215,7 @@ extern "C" {
     ///         Configured storage uses this identifier to lookup exact wallet data placement.
     ///   "storage_type": optional<string>, Type of the wallet storage. Defaults to 'default'.
     ///                  'Default' storage type allows to store wallet data in the local file.
+    ///                  'Inmem' storage type keeps wallet data in memory of the process until it is deleted.
     ///                  Custom storage types can be registered with indy_register_wallet_storage call.
     ///   "storage_config": optional<object>, Storage configuration json. Storage type defines set of supported keys.
     ///                     Can be optional if storage supports default configuration.
@@ -161,6 +224,13 @@ extern "C" {
     ///     "path": optional<string>, Path to the directory with wallet files.
     ///             Defaults to $HOME/.indy_client/wallet.
     ///             Wallet will be stored in the file {path}/{id}/sqlite.db
+    ///     "migration": optional<object>, Schema migration options applied on wallet opening:
+    ///     {
+    ///       "dry_run": optional<bool>, Apply pending migration steps and roll them back. Opening of wallet
+    ///                  that requires migration fails. Defaults to false.
+    ///       "backup": optional<bool>, Copy wallet file to {path}/{id}/sqlite.db.v<version>.bak before
+    ///                 migration. Defaults to false.
+    ///     }
     ///   }
     /// }
     /// credentials: Wallet credentials json
@@ -201,6 +271,7 @@ extern "C" {
     ///             Configured storage uses this identifier to lookup exact wallet data placement.
     ///       "storage_type": optional<string>, Type of the wallet storage. Defaults to 'default'.
     ///                       'Default' storage type allows to store wallet data in the local file.
+    ///                       'Inmem' storage type keeps wallet data in memory of the process until it is deleted.
     ///                       Custom storage types can be registered with indy_register_wallet_storage call.
     ///       "storage_config": optional<object>, Storage configuration json. Storage type defines set of supported keys.
     ///                         Can be optional if storage supports default configuration.
@@ -209,6 +280,13 @@ extern "C" {
     ///              "path": optional<string>, Path to the directory with wallet files.
     ///                      Defaults to $HOME/.indy_client/wallet.
     ///                      Wallet will be stored in the file {path}/{id}/sqlite.db
+    ///              "migration": optional<object>, Schema migration options applied on wallet opening:
+    ///              {
+    ///                "dry_run": optional<bool>, Apply pending migration steps and roll them back. Opening of wallet
+    ///                           that requires migration fails. Defaults to false.
+    ///                "backup": optional<bool>, Copy wallet file to {path}/{id}/sqlite.db.v<version>.bak before
+    ///                          migration. Defaults to false.
+    ///              }
     ///           }
     ///
     ///   }
@@ -246,6 +324,50 @@ extern "C" {
                                          void           (*fn)(indy_handle_t command_handle_, indy_error_t err, indy_handle_t handle)
                                         );
 
+    /// Changes the key of opened wallet.
+    ///
+    /// Wallet keys stored in the wallet metadata are re-encrypted with the master key
+    /// derived from new credentials. Values of wallet records are re-encrypted with new random
+    /// value keys in batches before that. Wallet stays usable while the new master key is derived
+    /// and doesn't need to be reopened.
+    /// After the call completes wallet must be opened with the new credentials.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// wallet_handle: wallet handle returned by indy_open_wallet
+    /// new_credentials: New wallet credentials json
+    ///   {
+    ///     "key": <string>, New key or passphrase used for wallet key derivation.
+    ///                     Look to key_derivation_method param for information about supported key derivation methods.
+    ///     "key_derivation_method": optional<string> Algorithm to use for wallet key derivation:
+    ///                              ARGON2I_MOD - derive secured wallet master key (used by default)
+    ///                              ARGON2I_INT - derive secured wallet master key (less secured but faster)
+    ///                              RAW - raw wallet key master provided (skip derivation).
+    ///                                RAW keys can be generated with indy_generate_wallet_key call
+    ///   }
+    /// progress_cb: (optional) Callback that is called before the first and after each re-encrypted batch
+    ///   of records with the number of processed records and the total number of records.
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_rekey_wallet(indy_handle_t  command_handle,
+                                          indy_handle_t  wallet_handle,
+                                          const char*    new_credentials,
+
+                                          void           (*progress_fn)(indy_handle_t command_handle_,
+                                                                        indy_u32_t    completed_steps,
+                                                                        indy_u32_t    total_steps),
+
+                                          void           (*fn)(indy_handle_t command_handle_,
+                                                               indy_error_t  err)
+                                          );
+
     /// Exports opened wallet
     ///
     /// #Params:
@@ -260,6 +382,9 @@ extern "C" {
     ///                              ARGON2I_INT - derive secured export key (less secured but faster)
     ///                              RAW - raw export key provided (skip derivation).
     ///                                RAW keys can be generated with indy_generate_wallet_key call
+    ///     "types": optional<array<string>>, Export only records of the given types (all records are exported by default)
+    ///     "query": optional<object>, Export only records matching the given WQL query (see indy_open_wallet_search).
+    ///              Requires "types" to be specified. The query is applied to each of the given types.
     ///   }
     ///
     /// #Returns
@@ -275,6 +400,45 @@ extern "C" {
                                            void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
                                            );
 
+    /// Exports opened wallet to the caller-provided stream
+    ///
+    /// Exported data has the same format as indy_export_wallet produces
+    /// and is passed to write_cb chunk by chunk instead of being written to a file.
+    ///
+    /// #Params:
+    /// command_handle: Command handle to map callbacks to caller context.
+    /// wallet_handle: wallet handle returned by indy_open_wallet
+    /// export_config: JSON containing settings for input operation.
+    ///   {
+    ///     "key": <string>, Key or passphrase used for wallet export key derivation.
+    ///                     Look to key_derivation_method param for information about supported key derivation methods.
+    ///     "key_derivation_method": optional<string> Algorithm to use for export key derivation:
+    ///                              ARGON2I_MOD - derive secured export key (used by default)
+    ///                              ARGON2I_INT - derive secured export key (less secured but faster)
+    ///                              RAW - raw export key provided (skip derivation).
+    ///                                RAW keys can be generated with indy_generate_wallet_key call
+    ///     "types": optional<array<string>>, Export only records of the given types (all records are exported by default)
+    ///     "query": optional<object>, Export only records matching the given WQL query (see indy_open_wallet_search).
+    ///              Requires "types" to be specified. The query is applied to each of the given types.
+    ///   }
+    /// write_cb: handler that consumes next chunk of exported data.
+    ///           Called with the same command_handle before fn. Must return Success
+    ///           if the whole chunk was consumed, any other code aborts export.
+    ///
+    /// #Returns
+    /// Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_export_wallet_to_stream(indy_handle_t  command_handle,
+                                                     indy_handle_t  wallet_handle,
+                                                     const char*    export_config_json,
+                                                     indy_error_t   (*write_cb)(indy_handle_t command_handle_, const indy_u8_t* data, indy_u32_t data_len),
+                                                     void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
+                                                     );
+
 
     /// Creates a new secure wallet and then imports its content
     /// according to fields provided in import_config
@@ -287,6 +451,7 @@ extern "C" {
     ///         Configured storage uses this identifier to lookup exact wallet data placement.
     ///   "storage_type": optional<string>, Type of the wallet storage. Defaults to 'default'.
     ///                  'Default' storage type allows to store wallet data in the local file.
+    ///                  'Inmem' storage type keeps wallet data in memory of the process until it is deleted.
     ///                  Custom storage types can be registered with indy_register_wallet_storage call.
     ///   "storage_config": optional<object>, Storage configuration json. Storage type defines set of supported keys.
     ///                     Can be optional if storage supports default configuration.
@@ -295,6 +460,13 @@ extern "C" {
     ///     "path": optional<string>, Path to the directory with wallet files.
     ///             Defaults to $HOME/.indy_client/wallet.
     ///             Wallet will be stored in the file {path}/{id}/sqlite.db
+    ///     "migration": optional<object>, Schema migration options applied on wallet opening:
+    ///     {
+    ///       "dry_run": optional<bool>, Apply pending migration steps and roll them back. Opening of wallet
+    ///                  that requires migration fails. Defaults to false.
+    ///       "backup": optional<bool>, Copy wallet file to {path}/{id}/sqlite.db.v<version>.bak before
+    ///                 migration. Defaults to false.
+    ///     }
     ///   }
     /// }
     /// credentials: Wallet credentials json
@@ -330,6 +502,256 @@ extern "C" {
                                            void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
                                            );
 
+    /// Creates a new secure wallet and then imports its content
+    /// from the caller-provided stream.
+    /// This can be seen as an indy_import_wallet call that reads exported data
+    /// through read_cb instead of a file.
+    ///
+    /// #Params
+    /// command_handle: Command handle to map callbacks to caller context.
+    /// config: Wallet configuration json (see indy_import_wallet).
+    /// credentials: Wallet credentials json (see indy_import_wallet).
+    /// import_config: Import settings json.
+    /// {
+    ///   "key": <string>, key used for export of the wallet
+    /// }
+    /// read_cb: handler that fills the given buffer with next chunk of exported data
+    ///          and stores the number of written bytes to read_len_p (0 means end of stream).
+    ///          Called with the same command_handle before fn. Any code other than Success aborts import.
+    ///
+    /// #Returns
+    /// Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_import_wallet_from_stream(indy_handle_t  command_handle,
+                                                       const char*    config,
+                                                       const char*    credentials,
+                                                       const char*    import_config_json,
+                                                       indy_error_t   (*read_cb)(indy_handle_t command_handle_, indy_u8_t* buffer, indy_u32_t buffer_len, indy_u32_t* read_len_p),
+                                                       void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
+                                                       );
+
+    /// Imports content of exported wallet into opened wallet.
+    ///
+    /// Unlike indy_import_wallet this call doesn't create a new wallet and merges
+    /// imported records with the records that wallet already contains.
+    /// If any imported record has the same type and id as an existing one, import fails
+    /// with WalletItemAlreadyExists error and no records are imported.
+    ///
+    /// #Params
+    /// wallet_handle: wallet handle returned by indy_open_wallet.
+    /// import_config: Import settings json.
+    /// {
+    ///   "path": <string>, path of the file that contains exported wallet content
+    ///   "key": <string>, key used for export of the wallet
+    /// }
+    ///
+    /// #Returns
+    /// Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_import_into_wallet(indy_handle_t  command_handle,
+                                                indy_handle_t  wallet_handle,
+                                                const char*    import_config_json,
+                                                void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
+                                                );
+
+    /// Verifies exported wallet file without importing it.
+    ///
+    /// Parses export header, decrypts and checks every encrypted chunk, header hash and
+    /// every record, so successful result means that export can be imported with the given key.
+    ///
+    /// #Params
+    /// export_config: Export file settings json.
+    /// {
+    ///   "path": <string>, path of the file that contains exported wallet content
+    ///   "key": <string>, key used for export of the wallet
+    /// }
+    ///
+    /// #Returns
+    /// Error code
+    /// summary_json: Export summary json.
+    /// {
+    ///   "version": <int>, version of export format
+    ///   "time": <int>, export time in seconds from UNIX Epoch
+    ///   "encryption_method": <string>, method of records stream encryption
+    ///   "key_derivation_method": <string>, method of export key derivation (ARGON2I_MOD, ARGON2I_INT or RAW)
+    ///   "types": <object>, number of exported records per record type {"type": <int>, ...}
+    ///   "records_count": <int>, total number of exported records
+    ///   "size": <int>, size of export file in bytes
+    /// }
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_inspect_wallet_export(indy_handle_t  command_handle,
+                                                   const char*    export_config_json,
+                                                   void           (*fn)(indy_handle_t command_handle_, indy_error_t err, const char* summary_json)
+                                                   );
+
+    /// Checks internal consistency of opened wallet.
+    ///
+    /// Walks through all wallet records and checks that their types, ids, values and tags can be decrypted.
+    /// Storage specific problems like tags without item or duplicated items are reported by storage.
+    ///
+    /// #Params
+    /// wallet_handle: wallet handle returned by indy_open_wallet.
+    /// options_json: Check options json.
+    /// {
+    ///   "repair": optional<bool>, fix storage specific problems and delete records that cannot be decrypted.
+    ///             Defaults to false.
+    /// }
+    ///
+    /// #Returns
+    /// Error code
+    /// report_json: Check report json.
+    /// {
+    ///   "records_count": <int>, number of checked records
+    ///   "issues": [{
+    ///       "kind": <string>, one of "invalid_type", "invalid_id", "invalid_value", "invalid_tags",
+    ///               "orphaned_tags", "duplicate_item"
+    ///       "type": optional<string>, record type if it can be decrypted
+    ///       "id": optional<string>, record id if it can be decrypted
+    ///       "details": <string>, description of the problem
+    ///       "repaired": <bool>, problem was fixed by repair
+    ///   }, ...]
+    /// }
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_check_wallet(indy_handle_t  command_handle,
+                                          indy_handle_t  wallet_handle,
+                                          const char*    options_json,
+                                          void           (*fn)(indy_handle_t command_handle_, indy_error_t err, const char* report_json)
+                                         );
+
+    /// Creates a new wallet that is a replica of opened wallet.
+    ///
+    /// Replica is created empty in its own storage but shares encryption keys with the source wallet,
+    /// so records can be copied between them by indy_sync_wallet without decryption.
+    /// Replica is opened with the same key as the source wallet.
+    ///
+    /// #Params
+    /// wallet_handle: handle of the source wallet returned by indy_open_wallet.
+    /// config: Replica configuration json. Same as config of indy_create_wallet.
+    /// credentials: Replica credentials json. Same as credentials of indy_create_wallet.
+    ///              Only "storage_credentials" are used, wallet key is inherited from the source wallet.
+    ///
+    /// #Returns
+    /// err: Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_create_wallet_replica(indy_handle_t  command_handle,
+                                                   indy_handle_t  wallet_handle,
+                                                   const char*    config,
+                                                   const char*    credentials,
+                                                   void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
+                                                  );
+
+    /// Copies encrypted records from one opened wallet to another without decryption.
+    ///
+    /// Both wallets must be encrypted with the same keys, so the target wallet must be created by
+    /// indy_create_wallet_replica. Existing records of the target wallet are overwritten.
+    /// Sync is incremental if marker returned by the previous sync is passed in options:
+    /// only records added or modified after it are copied. Records that equal target ones are
+    /// not rewritten. Target records missing in the source wallet are deleted.
+    ///
+    /// #Params
+    /// source_wallet_handle: handle of the wallet to copy records from.
+    /// target_wallet_handle: handle of the wallet to copy records to.
+    /// options_json: Sync options json.
+    /// {
+    ///   "since": optional<int>, marker returned by the previous sync. All records are copied if not set.
+    /// }
+    ///
+    /// #Returns
+    /// Error code
+    /// result_json: Sync result json.
+    /// {
+    ///   "records_count": <int>, number of copied records
+    ///   "deleted_count": <int>, number of target records deleted as missing in the source wallet
+    ///   "marker": <int>, marker of source wallet to pass to the next sync
+    /// }
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_sync_wallet(indy_handle_t  command_handle,
+                                         indy_handle_t  source_wallet_handle,
+                                         indy_handle_t  target_wallet_handle,
+                                         const char*    options_json,
+                                         void           (*fn)(indy_handle_t command_handle_, indy_error_t err, const char* result_json)
+                                        );
+
+    /// Subscribes to changes of wallet records of the given type matching WQL query.
+    ///
+    /// notify_cb is called for every record added, updated or deleted through libindy calls
+    /// (non_secrets, anoncreds, did, crypto etc.), including wallet import into opened wallet,
+    /// indy_sync_wallet, indy_check_wallet repair and deletion of expired records.
+    /// Updated record that doesn't match the query anymore is reported as removed.
+    /// notify_cb is called from libindy thread before the callback of the changing call,
+    /// so it should not block. Subscription is removed when the wallet is closed.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// wallet_handle: wallet handle (created by open_wallet).
+    /// type_: type of records to watch.
+    /// query_json: WQL query to filter watched records (see indy_open_wallet_search).
+    ///   Only tags are evaluated, "{}" matches all records of the type.
+    /// notify_cb: handler that is called on each change of matching record.
+    ///            Called with the same command_handle as passed to this call.
+    ///            operation is one of "add", "update", "delete" or "remove".
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// err: Error code
+    /// subscription_handle: Handle of subscription to use in indy_wallet_unsubscribe.
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_wallet_subscribe(indy_handle_t  command_handle,
+                                              indy_handle_t  wallet_handle,
+                                              const char*    type_,
+                                              const char*    query_json,
+                                              void           (*notify_cb)(indy_handle_t command_handle_, indy_handle_t subscription_handle, const char* operation, const char* id),
+                                              void           (*fn)(indy_handle_t command_handle_, indy_error_t err, indy_handle_t subscription_handle)
+                                             );
+
+    /// Removes subscription created by indy_wallet_subscribe.
+    /// notify_cb of the subscription is not called after this call completes.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// subscription_handle: subscription handle (created by indy_wallet_subscribe).
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern indy_error_t indy_wallet_unsubscribe(indy_handle_t  command_handle,
+                                                indy_handle_t  subscription_handle,
+                                                void           (*fn)(indy_handle_t command_handle_, indy_error_t err)
+                                               );
+
     /// Closes opened wallet and frees allocated resources.
     ///
     /// #Params
@@ -356,6 +778,7 @@ extern "C" {
     ///         Configured storage uses this identifier to lookup exact wallet data placement.
     ///   "storage_type": optional<string>, Type of the wallet storage. Defaults to 'default'.
     ///                  'Default' storage type allows to store wallet data in the local file.
+    ///                  'Inmem' storage type keeps wallet data in memory of the process until it is deleted.
     ///                  Custom storage types can be registered with indy_register_wallet_storage call.
     ///   "storage_config": optional<object>, Storage configuration json. Storage type defines set of supported keys.
     ///                     Can be optional if storage supports default configuration.
@@ -364,6 +787,13 @@ extern "C" {
     ///     "path": optional<string>, Path to the directory with wallet files.
     ///             Defaults to $HOME/.indy_client/wallet.
     ///             Wallet will be stored in the file {path}/{id}/sqlite.db
+    ///     "migration": optional<object>, Schema migration options applied on wallet opening:
+    ///     {
+    ///       "dry_run": optional<bool>, Apply pending migration steps and roll them back. Opening of wallet
+    ///                  that requires migration fails. Defaults to false.
+    ///       "backup": optional<bool>, Copy wallet file to {path}/{id}/sqlite.db.v<version>.bak before
+    ///                 migration. Defaults to false.
+    ///     }
     ///   }
     /// }
     /// credentials: Wallet credentials json
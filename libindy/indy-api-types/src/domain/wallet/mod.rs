@@ -38,9 +38,154 @@ pub struct ExportConfig {
     pub key: String,
     pub path: String,
     #[serde(default = "default_key_derivation_method")]
+    pub key_derivation_method: KeyDerivationMethod,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub types: Option<Vec<String>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StreamExportConfig {
+    pub key: String,
+    #[serde(default = "default_key_derivation_method")]
+    pub key_derivation_method: KeyDerivationMethod,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub types: Option<Vec<String>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RekeyCredentials {
+    pub key: String,
+    #[serde(default = "default_key_derivation_method")]
     pub key_derivation_method: KeyDerivationMethod
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportSummary {
+    // Version of export format
+    pub version: u32,
+    // Export time in seconds from UNIX Epoch
+    pub time: u64,
+    // Method of encryption used for records stream
+    pub encryption_method: String,
+    // Method of export key derivation
+    pub key_derivation_method: KeyDerivationMethod,
+    // Number of exported records per record type
+    pub types: HashMap<String, usize>,
+    // Total number of exported records
+    pub records_count: usize,
+    // Total size of export in bytes
+    pub size: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct CheckWalletOptions {
+    // Fix storage specific problems and delete records that can't be decrypted
+    #[serde(default)]
+    pub repair: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletCheckReport {
+    // Total number of checked records
+    pub records_count: usize,
+    // Problems found in wallet
+    pub issues: Vec<WalletCheckIssue>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct WalletCheckIssue {
+    pub kind: WalletCheckIssueKind,
+    // Record type if it can be decrypted
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    // Record id if it can be decrypted
+    pub id: Option<String>,
+    pub details: String,
+    // Problem was fixed by repair
+    pub repaired: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WalletCheckIssueKind {
+    InvalidType,
+    InvalidId,
+    InvalidValue,
+    InvalidTags,
+    OrphanedTags,
+    DuplicateItem,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct WalletSyncOptions {
+    // Marker returned by previous sync. All records are copied if it is not set
+    #[serde(default)]
+    pub since: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletSyncResult {
+    // Number of records copied to target wallet
+    pub records_count: usize,
+    // Number of target wallet records deleted as missing in source wallet
+    pub deleted_count: usize,
+    // Marker of source wallet to pass to next sync
+    pub marker: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletAggregation {
+    // Plaintext tag names to group records by. Only total count is returned if it is empty
+    #[serde(default)]
+    pub group_by: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletAggregationResult {
+    // Total number of records matching query
+    pub count: usize,
+    // Distinct combinations of grouped tag values with number of records
+    pub groups: Vec<WalletAggregationGroup>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct WalletAggregationGroup {
+    // Value of every grouped tag, null if record doesn't have the tag
+    pub tags: HashMap<String, Option<String>>,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AddRecordOptions {
+    // Number of seconds after which record expires. Record never expires if not set
+    pub expires_in: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRecordOptions {
+    // New expiry of record in seconds from now. Current expiry is kept if not set,
+    // explicit null makes record persistent
+    #[serde(default, deserialize_with = "deserialize_explicit_null")]
+    pub expires_in: Option<Option<u64>>,
+}
+
+// Lets missing field and null value be distinguished: the latter is deserialized as Some(None)
+fn deserialize_explicit_null<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+    where D: serde::Deserializer<'de>, T: serde::Deserialize<'de> {
+    <Option<T> as serde::Deserialize>::deserialize(deserializer).map(Some)
+}
+
 #[derive(Debug, Deserialize)]
 pub struct KeyConfig {
     pub seed: Option<String>
@@ -61,6 +206,44 @@ pub struct Record {
 
 pub type Tags = HashMap<String, String>;
 
+fn validate_export_filter(types: &Option<Vec<String>>, query: &Option<Value>) -> Result<(), String> {
+    if let Some(ref types) = types {
+        if types.is_empty() {
+            return Err("Export types list is empty".to_string());
+        }
+    }
+    if query.is_some() && types.is_none() {
+        return Err("Export query requires record types to be specified".to_string());
+    }
+    Ok(())
+}
+
+impl Validatable for ExportConfig {
+    fn validate(&self) -> Result<(), String> {
+        validate_export_filter(&self.types, &self.query)
+    }
+}
+
+impl Validatable for StreamExportConfig {
+    fn validate(&self) -> Result<(), String> {
+        validate_export_filter(&self.types, &self.query)
+    }
+}
+
+impl Validatable for WalletAggregation {
+    fn validate(&self) -> Result<(), String> {
+        for (index, tag_name) in self.group_by.iter().enumerate() {
+            if !tag_name.starts_with('~') {
+                return Err(format!("Grouping is supported only by plaintext tags: {}", tag_name));
+            }
+            if self.group_by[..index].contains(tag_name) {
+                return Err(format!("Tag is used for grouping more than once: {}", tag_name));
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Validatable for Config {
     fn validate(&self) -> Result<(), String> {
         if self.id.is_empty() {
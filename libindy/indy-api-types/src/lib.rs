@@ -29,6 +29,9 @@ pub const INVALID_COMMAND_HANDLE : CommandHandle = 0;
 
 pub type StorageHandle = i32;
 
+pub type SubscriptionHandle = i32;
+pub const INVALID_SUBSCRIPTION_HANDLE : SubscriptionHandle = 0;
+
 #[repr(transparent)]
 #[derive(Debug, Hash, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
 pub struct SearchHandle(pub i32);
@@ -538,6 +541,10 @@ pub mod wallet {
     ///    retrieveType: (optional, false by default) Retrieve record type,
     ///    retrieveValue: (optional, true by default) Retrieve record value,
     ///    retrieveTags: (optional, true by default) Retrieve record tags,
+    ///    sortBy: (optional) List of plaintext tags to sort records by:
+    ///      [{"tagName": "~<base64 encoded encrypted tag name>", "order": (optional, "asc" by default) "asc" or "desc"}],
+    ///    skip: (optional) Count of records to skip,
+    ///    limit: (optional) Max count of records to return,
     ///  }
     /// search_handle_p: pointer to store wallet search handle
     pub type WalletSearchRecords = extern fn(storage_handle: StorageHandle,
@@ -586,4 +593,91 @@ pub mod wallet {
     pub type WalletFreeSearch = extern fn(storage_handle: StorageHandle,
                                           search_handle: i32) -> ErrorCode;
 
+    /// Begin wallet storage transaction (optional, see indy_register_wallet_storage_transaction_hooks)
+    ///
+    /// All storage modifications made after this call and before commit or rollback
+    /// must be applied atomically.
+    ///
+    /// #Params
+    /// storage_handle: opened storage handle (See open handler)
+    pub type WalletBeginTransaction = extern fn(storage_handle: StorageHandle) -> ErrorCode;
+
+    /// Commit wallet storage transaction started by begin transaction handler
+    ///
+    /// #Params
+    /// storage_handle: opened storage handle (See open handler)
+    pub type WalletCommitTransaction = extern fn(storage_handle: StorageHandle) -> ErrorCode;
+
+    /// Rollback wallet storage transaction started by begin transaction handler
+    ///
+    /// #Params
+    /// storage_handle: opened storage handle (See open handler)
+    pub type WalletRollbackTransaction = extern fn(storage_handle: StorageHandle) -> ErrorCode;
+
+    /// Count wallet storage records matching query grouped by values of plaintext tags
+    /// (optional, see indy_register_wallet_storage_aggregation)
+    ///
+    /// #Params
+    /// storage_handle: opened storage handle (See open handler)
+    /// type_: allows to separate different record types collections
+    /// query_json: MongoDB style query to wallet record tags (the same as for search_records handler)
+    /// group_by_json: list of plaintext tag names to group records by:
+    ///   ["~<base64 encoded encrypted tag name>", ...]
+    /// result_json_p: pointer to store groups json:
+    ///   [[[<tag value or null>, ...], <count>], ...]
+    ///   Tag values go in the order of group_by_json. Records without a tag fall into group with null value.
+    ///   Single group with total count is expected for empty group_by_json
+    /// result_handle_p: pointer to store handle of result json to free by free_aggregation handler
+    pub type WalletAggregateRecords = extern fn(storage_handle: StorageHandle,
+                                                type_: *const c_char,
+                                                query_json: *const c_char,
+                                                group_by_json: *const c_char,
+                                                result_json_p: *mut *const c_char,
+                                                result_handle_p: *mut i32) -> ErrorCode;
+
+    /// Free aggregation result json returned by aggregate records handler
+    ///
+    /// #Params
+    /// storage_handle: opened storage handle (See open handler)
+    /// result_handle: handle of result json (See aggregate_records handler)
+    pub type WalletFreeAggregation = extern fn(storage_handle: StorageHandle,
+                                               result_handle: i32) -> ErrorCode;
+
+    /// Write next chunk of exported wallet data to the caller-provided stream
+    /// (See indy_export_wallet_to_stream)
+    ///
+    /// #Params
+    /// command_handle: command handle passed to indy_export_wallet_to_stream
+    /// data: pointer to the chunk bytes
+    /// data_len: chunk length
+    pub type WalletExportWrite = extern fn(command_handle: CommandHandle,
+                                           data: *const u8,
+                                           data_len: u32) -> ErrorCode;
+
+    /// Read next chunk of exported wallet data from the caller-provided stream
+    /// (See indy_import_wallet_from_stream)
+    ///
+    /// #Params
+    /// command_handle: command handle passed to indy_import_wallet_from_stream
+    /// buffer: pointer to the buffer to fill
+    /// buffer_len: buffer capacity
+    /// read_len_p: pointer to store the number of bytes written to buffer (0 means end of stream)
+    pub type WalletImportRead = extern fn(command_handle: CommandHandle,
+                                          buffer: *mut u8,
+                                          buffer_len: u32,
+                                          read_len_p: *mut u32) -> ErrorCode;
+
+    /// Notify about change of wallet record matching subscription
+    /// (See indy_wallet_subscribe)
+    ///
+    /// #Params
+    /// command_handle: command handle passed to indy_wallet_subscribe
+    /// subscription_handle: handle of subscription returned by indy_wallet_subscribe
+    /// operation: kind of change: "add", "update" or "delete"
+    /// id: id of changed record
+    pub type WalletRecordChanged = extern fn(command_handle: CommandHandle,
+                                             subscription_handle: SubscriptionHandle,
+                                             operation: *const c_char,
+                                             id: *const c_char);
+
 }
\ No newline at end of file
@@ -0,0 +1,236 @@
+extern crate openssl;
+
+use indy_api_types::errors::prelude::*;
+use self::openssl::bn::{BigNum, BigNumContext, BigNumRef};
+use self::openssl::ec::{EcGroup, EcKey, EcPoint, PointConversionForm};
+use self::openssl::ecdsa::EcdsaSig;
+use self::openssl::hash::{hash, MessageDigest};
+use self::openssl::nid::Nid;
+use std::ops::Deref;
+use zeroize::Zeroize;
+
+pub const SEEDBYTES: usize = 32;
+pub const SECRETKEYBYTES: usize = 32;
+// Public keys are in compressed SEC1 form
+pub const PUBLICKEYBYTES: usize = 33;
+// Signatures are in fixed size r || s form
+pub const SIGNATUREBYTES: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Curve {
+    Secp256k1,
+    P256,
+}
+
+// Secret key is big-endian private scalar. It is zeroized on drop
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct SecretKey([u8; SECRETKEYBYTES]);
+
+impl SecretKey {
+    pub fn from_slice(bytes: &[u8]) -> Result<SecretKey, IndyError> {
+        if bytes.len() != SECRETKEYBYTES {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Invalid secret key length: {}", bytes.len())));
+        }
+
+        let mut secret_key = SecretKey([0; SECRETKEYBYTES]);
+        secret_key.0.copy_from_slice(bytes);
+        Ok(secret_key)
+    }
+}
+
+impl Deref for SecretKey {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+// Private scalar is erased when it isn't needed anymore
+struct Scalar(BigNum);
+
+impl Drop for Scalar {
+    fn drop(&mut self) {
+        self.0.clear();
+    }
+}
+
+impl Curve {
+    fn group(self) -> Result<EcGroup, IndyError> {
+        let nid = match self {
+            Curve::Secp256k1 => Nid::SECP256K1,
+            Curve::P256 => Nid::X9_62_PRIME256V1,
+        };
+
+        Ok(EcGroup::from_curve_name(nid)?)
+    }
+}
+
+// Seed is used as private scalar as is. Seed out of [1, n - 1] range is rejected, not reduced
+pub fn create_key_pair(curve: Curve, seed: Option<&[u8]>) -> Result<(Vec<u8>, SecretKey), IndyError> {
+    let group = curve.group()?;
+    let mut ctx = BigNumContext::new()?;
+
+    let key = match seed {
+        Some(seed) => {
+            if seed.len() != SEEDBYTES {
+                return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Invalid seed length: {}", seed.len())));
+            }
+
+            _private_key(curve, &group, seed, &mut ctx)?
+        }
+        None => EcKey::generate(&group)?
+    };
+
+    let vk = key.public_key().to_bytes(&group, PointConversionForm::COMPRESSED, &mut ctx)?;
+
+    let mut sk = _to_padded_vec(key.private_key(), SECRETKEYBYTES);
+    let secret_key = SecretKey::from_slice(&sk);
+    sk.zeroize();
+
+    Ok((vk, secret_key?))
+}
+
+pub fn sign(curve: Curve, secret_key: &SecretKey, doc: &[u8]) -> Result<Vec<u8>, IndyError> {
+    let group = curve.group()?;
+    let mut ctx = BigNumContext::new()?;
+
+    let key = _private_key(curve, &group, secret_key, &mut ctx)?;
+
+    let signature = EcdsaSig::sign(&hash(MessageDigest::sha256(), doc)?, &key)?;
+
+    // Normalize to low S form as most of secp256k1 verifiers reject high S signatures
+    let mut order = BigNum::new()?;
+    group.order(&mut order, &mut ctx)?;
+    let mut half_order = BigNum::new()?;
+    half_order.rshift1(&order)?;
+
+    let mut s = signature.s().to_owned()?;
+    if *s > *half_order {
+        let mut low_s = BigNum::new()?;
+        low_s.checked_sub(&order, &s)?;
+        s = low_s;
+    }
+
+    let mut res = _to_padded_vec(signature.r(), SIGNATUREBYTES / 2);
+    res.extend(_to_padded_vec(&s, SIGNATUREBYTES / 2));
+
+    Ok(res)
+}
+
+pub fn verify(curve: Curve, public_key: &[u8], doc: &[u8], signature: &[u8]) -> Result<bool, IndyError> {
+    if signature.len() != SIGNATUREBYTES {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Invalid {:?} signature length: {}", curve, signature.len())));
+    }
+
+    let key = _public_key(curve, public_key)?;
+
+    let r = BigNum::from_slice(&signature[..SIGNATUREBYTES / 2])?;
+    let s = BigNum::from_slice(&signature[SIGNATUREBYTES / 2..])?;
+    let signature = EcdsaSig::from_private_components(r, s)?;
+
+    Ok(signature.verify(&hash(MessageDigest::sha256(), doc)?, &key)?)
+}
+
+pub fn validate_key(curve: Curve, public_key: &[u8]) -> Result<(), IndyError> {
+    _public_key(curve, public_key).map(|_| ())
+}
+
+// Private scalar must be in [1, n - 1] range
+fn _private_key(curve: Curve, group: &EcGroup, bytes: &[u8], ctx: &mut BigNumContext) -> Result<EcKey<self::openssl::pkey::Private>, IndyError> {
+    let private_key = Scalar(BigNum::from_slice(bytes)?);
+    let mut order = BigNum::new()?;
+    group.order(&mut order, ctx)?;
+
+    if private_key.0.num_bits() == 0 || private_key.0 >= order {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Secret key is out of {:?} curve order", curve)));
+    }
+
+    let mut public_key = EcPoint::new(group)?;
+    public_key.mul_generator(group, &private_key.0, ctx)?;
+
+    Ok(EcKey::from_private_components(group, &private_key.0, &public_key)?)
+}
+
+fn _public_key(curve: Curve, public_key: &[u8]) -> Result<EcKey<self::openssl::pkey::Public>, IndyError> {
+    let group = curve.group()?;
+    let mut ctx = BigNumContext::new()?;
+
+    if public_key.len() != PUBLICKEYBYTES {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Invalid {:?} public key length: {}", curve, public_key.len())));
+    }
+
+    let point = EcPoint::from_bytes(&group, public_key, &mut ctx)
+        .to_indy(IndyErrorKind::InvalidStructure, format!("Invalid {:?} public key", curve))?;
+    let key = EcKey::from_public_key(&group, &point)?;
+
+    key.check_key()
+        .to_indy(IndyErrorKind::InvalidStructure, format!("Invalid {:?} public key", curve))?;
+
+    Ok(key)
+}
+
+// Intermediate bytes are zeroized as number can be private scalar
+fn _to_padded_vec(num: &BigNumRef, len: usize) -> Vec<u8> {
+    let mut bytes = num.to_vec();
+    let mut res = vec![0; len.saturating_sub(bytes.len())];
+    res.extend_from_slice(&bytes);
+    bytes.zeroize();
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::randombytes::randombytes;
+
+    #[test]
+    fn sign_verify_works() {
+        for curve in &[Curve::Secp256k1, Curve::P256] {
+            let text = randombytes(16);
+
+            let (public_key, secret_key) = create_key_pair(*curve, None).unwrap();
+            assert_eq!(PUBLICKEYBYTES, public_key.len());
+            assert_eq!(SECRETKEYBYTES, secret_key[..].len());
+
+            let signature = sign(*curve, &secret_key, &text).unwrap();
+            assert_eq!(SIGNATUREBYTES, signature.len());
+            assert!(verify(*curve, &public_key, &text, &signature).unwrap());
+            assert!(!verify(*curve, &public_key, &randombytes(16), &signature).unwrap());
+        }
+    }
+
+    #[test]
+    fn create_key_pair_works_for_seed() {
+        let seed = vec![1u8; SEEDBYTES];
+
+        let (public_key, secret_key) = create_key_pair(Curve::Secp256k1, Some(seed.as_slice())).unwrap();
+        assert_eq!(seed, secret_key[..].to_vec());
+        assert_eq!(create_key_pair(Curve::Secp256k1, Some(seed.as_slice())).unwrap().0, public_key);
+
+        let (p256_public_key, _) = create_key_pair(Curve::P256, Some(seed.as_slice())).unwrap();
+        assert_ne!(public_key, p256_public_key);
+    }
+
+    #[test]
+    fn create_key_pair_not_works_for_zero_seed() {
+        let res = create_key_pair(Curve::P256, Some(&[0u8; SEEDBYTES][..]));
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+    }
+
+    #[test]
+    fn sign_not_works_for_out_of_order_secret_key() {
+        let res = sign(Curve::Secp256k1, &SecretKey::from_slice(&[0u8; SECRETKEYBYTES]).unwrap(), b"text");
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+
+        let res = sign(Curve::P256, &SecretKey::from_slice(&[0xffu8; SECRETKEYBYTES]).unwrap(), b"text");
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+    }
+
+    #[test]
+    fn validate_key_not_works_for_invalid_encoding() {
+        let res = validate_key(Curve::Secp256k1, &[5u8; PUBLICKEYBYTES]);
+        assert!(res.is_err());
+    }
+}
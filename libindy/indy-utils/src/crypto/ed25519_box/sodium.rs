@@ -1,17 +1,28 @@
 extern crate sodiumoxide;
 
 use indy_api_types::errors::prelude::*;
+use libc::c_int;
 use self::sodiumoxide::crypto::box_;
 
 
 pub const NONCEBYTES: usize = box_::curve25519xsalsa20poly1305::NONCEBYTES;
 pub const PUBLICKEYBYTES: usize = box_::curve25519xsalsa20poly1305::PUBLICKEYBYTES;
 pub const SECRETKEYBYTES: usize = box_::curve25519xsalsa20poly1305::SECRETKEYBYTES;
+pub const SHAREDSECRETBYTES: usize = 32;
 
 sodium_type!(Nonce, box_::Nonce, NONCEBYTES);
 sodium_type!(PublicKey, box_::PublicKey, PUBLICKEYBYTES);
 sodium_type!(SecretKey, box_::SecretKey, SECRETKEYBYTES);
 
+extern {
+    // this function isn't included to sodiumoxide rust wrappers,
+    // local binding is used to call libsodium-sys function
+    fn crypto_scalarmult_curve25519(
+        q: *mut [u8; SHAREDSECRETBYTES],
+        n: *const [u8; SECRETKEYBYTES],
+        p: *const [u8; PUBLICKEYBYTES]) -> c_int;
+}
+
 pub fn encrypt(secret_key: &SecretKey, public_key: &PublicKey, doc: &[u8], nonce: &Nonce) -> Result<Vec<u8>, IndyError> {
     Ok(box_::seal(
         doc,
@@ -35,6 +46,26 @@ pub fn gen_nonce() -> Nonce {
     Nonce(box_::gen_nonce())
 }
 
+pub fn gen_keypair() -> (PublicKey, SecretKey) {
+    let (public_key, secret_key) = box_::gen_keypair();
+    (PublicKey(public_key), SecretKey(secret_key))
+}
+
+/// Computes raw X25519 shared secret. Fails for low order public keys.
+pub fn scalarmult(secret_key: &SecretKey, public_key: &PublicKey) -> Result<Vec<u8>, IndyError> {
+    let mut shared_secret: [u8; SHAREDSECRETBYTES] = [0; SHAREDSECRETBYTES];
+
+    let res = unsafe {
+        crypto_scalarmult_curve25519(&mut shared_secret, &(secret_key.0).0, &(public_key.0).0)
+    };
+
+    if res != 0 {
+        return Err(IndyError::from_msg(IndyErrorKind::InvalidStructure, "Unable to compute X25519 shared secret"));
+    }
+
+    Ok(shared_secret.to_vec())
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -66,4 +97,23 @@ mod tests {
         assert!(alice_decrypted_text.is_ok());
         assert_eq!(text, alice_decrypted_text.unwrap());
     }
+
+    #[test]
+    fn scalarmult_works() {
+        let (alice_pk, alice_sk) = gen_keypair();
+        let (bob_pk, bob_sk) = gen_keypair();
+
+        let alice_shared = scalarmult(&alice_sk, &bob_pk).unwrap();
+        let bob_shared = scalarmult(&bob_sk, &alice_pk).unwrap();
+
+        assert_eq!(SHAREDSECRETBYTES, alice_shared.len());
+        assert_eq!(alice_shared, bob_shared);
+    }
+
+    #[test]
+    fn scalarmult_not_works_for_low_order_key() {
+        let (_, sk) = gen_keypair();
+        let res = scalarmult(&sk, &PublicKey::new([0; PUBLICKEYBYTES]));
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+    }
 }
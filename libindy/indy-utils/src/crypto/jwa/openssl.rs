@@ -0,0 +1,273 @@
+extern crate openssl;
+
+use indy_api_types::errors::prelude::*;
+use self::openssl::aes::{AesKey, unwrap_key, wrap_key};
+use self::openssl::hash::{Hasher, MessageDigest};
+use self::openssl::memcmp;
+use self::openssl::pkey::PKey;
+use self::openssl::rand::rand_bytes;
+use self::openssl::sign::Signer;
+use self::openssl::symm::{Cipher, decrypt, encrypt};
+
+// A256KW key encryption key
+pub const KEKBYTES: usize = 32;
+// A256CBC-HS512 content encryption key is MAC key || ENC key
+pub const CEKBYTES: usize = 64;
+pub const IVBYTES: usize = 16;
+pub const TAGBYTES: usize = 32;
+// A256KW adds 64 bit integrity check value to the wrapped key
+const WRAPPINGBYTES: usize = 8;
+
+pub fn gen_cek() -> Result<Vec<u8>, IndyError> {
+    let mut cek = vec![0u8; CEKBYTES];
+    rand_bytes(&mut cek)?;
+    Ok(cek)
+}
+
+/// Concat KDF defined in NIST SP 800-56A and RFC 7518 section 4.6.2 with SHA-256.
+///
+/// `cc_tag` is the content encryption tag that ECDH-1PU key agreement appends to SuppPubInfo.
+pub fn concat_kdf(z: &[u8], alg: &str, apu: &[u8], apv: &[u8], cc_tag: Option<&[u8]>) -> Result<Vec<u8>, IndyError> {
+    _concat_kdf(z, alg, apu, apv, KEKBYTES, cc_tag)
+}
+
+// Single round is enough for keys not longer than SHA-256 output
+fn _concat_kdf(z: &[u8], alg: &str, apu: &[u8], apv: &[u8], key_len: usize, cc_tag: Option<&[u8]>) -> Result<Vec<u8>, IndyError> {
+    let mut hasher = Hasher::new(MessageDigest::sha256())?;
+
+    hasher.update(&1u32.to_be_bytes())?;
+    hasher.update(z)?;
+    hasher.update(&_len_prefixed(alg.as_bytes()))?;
+    hasher.update(&_len_prefixed(apu))?;
+    hasher.update(&_len_prefixed(apv))?;
+    hasher.update(&((key_len * 8) as u32).to_be_bytes())?;
+
+    if let Some(cc_tag) = cc_tag {
+        hasher.update(&_len_prefixed(cc_tag))?;
+    }
+
+    let mut key = hasher.finish()?.to_vec();
+    key.truncate(key_len);
+
+    Ok(key)
+}
+
+/// AES-256 key wrap defined in RFC 3394.
+pub fn wrap(kek: &[u8], cek: &[u8]) -> Result<Vec<u8>, IndyError> {
+    if cek.len() < 2 * WRAPPINGBYTES || cek.len() % WRAPPINGBYTES != 0 {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Invalid length of key to wrap: {}", cek.len())));
+    }
+
+    let kek = AesKey::new_encrypt(kek)
+        .map_err(|_| err_msg(IndyErrorKind::InvalidStructure, "Invalid key encryption key"))?;
+
+    let mut res = vec![0u8; cek.len() + WRAPPINGBYTES];
+
+    wrap_key(&kek, None, &mut res, cek)
+        .map_err(|_| err_msg(IndyErrorKind::InvalidStructure, "Unable to wrap key"))?;
+
+    Ok(res)
+}
+
+pub fn unwrap(kek: &[u8], wrapped_cek: &[u8]) -> Result<Vec<u8>, IndyError> {
+    if wrapped_cek.len() < 3 * WRAPPINGBYTES || wrapped_cek.len() % WRAPPINGBYTES != 0 {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Invalid length of wrapped key: {}", wrapped_cek.len())));
+    }
+
+    let kek = AesKey::new_decrypt(kek)
+        .map_err(|_| err_msg(IndyErrorKind::InvalidStructure, "Invalid key encryption key"))?;
+
+    let mut res = vec![0u8; wrapped_cek.len() - WRAPPINGBYTES];
+
+    unwrap_key(&kek, None, &mut res, wrapped_cek)
+        .map_err(|_| err_msg(IndyErrorKind::UnknownCrypto, "Unable to unwrap key"))?;
+
+    Ok(res)
+}
+
+/// AES_256_CBC_HMAC_SHA_512 authenticated encryption defined in RFC 7518 section 5.2.
+/// Returns ciphertext, iv and tag.
+pub fn encrypt_a256cbc_hs512(cek: &[u8], plaintext: &[u8], aad: &[u8]) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>), IndyError> {
+    let mut iv = vec![0u8; IVBYTES];
+    rand_bytes(&mut iv)?;
+
+    let (ciphertext, tag) = _encrypt_a256cbc_hs512(cek, plaintext, aad, &iv)?;
+
+    Ok((ciphertext, iv, tag))
+}
+
+fn _encrypt_a256cbc_hs512(cek: &[u8], plaintext: &[u8], aad: &[u8], iv: &[u8]) -> Result<(Vec<u8>, Vec<u8>), IndyError> {
+    let (mac_key, enc_key) = _split_cek(cek)?;
+
+    let ciphertext = encrypt(Cipher::aes_256_cbc(), enc_key, Some(iv), plaintext)?;
+    let tag = _tag(mac_key, aad, iv, &ciphertext)?;
+
+    Ok((ciphertext, tag))
+}
+
+pub fn decrypt_a256cbc_hs512(cek: &[u8], ciphertext: &[u8], aad: &[u8], iv: &[u8], tag: &[u8]) -> Result<Vec<u8>, IndyError> {
+    let (mac_key, enc_key) = _split_cek(cek)?;
+
+    if iv.len() != IVBYTES {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Invalid IV length: {}", iv.len())));
+    }
+
+    let expected_tag = _tag(mac_key, aad, iv, ciphertext)?;
+
+    if tag.len() != TAGBYTES || !memcmp::eq(&expected_tag, tag) {
+        return Err(err_msg(IndyErrorKind::UnknownCrypto, "Failed to decrypt ciphertext: authentication tag mismatch"));
+    }
+
+    decrypt(Cipher::aes_256_cbc(), enc_key, Some(iv), ciphertext)
+        .to_indy(IndyErrorKind::UnknownCrypto, "Failed to decrypt ciphertext")
+}
+
+fn _split_cek(cek: &[u8]) -> Result<(&[u8], &[u8]), IndyError> {
+    if cek.len() != CEKBYTES {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Invalid content encryption key length: {}", cek.len())));
+    }
+
+    Ok(cek.split_at(CEKBYTES / 2))
+}
+
+fn _tag(mac_key: &[u8], aad: &[u8], iv: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, IndyError> {
+    let key = PKey::hmac(mac_key)?;
+    let mut signer = Signer::new(MessageDigest::sha512(), &key)?;
+
+    signer.update(aad)?;
+    signer.update(iv)?;
+    signer.update(ciphertext)?;
+    signer.update(&((aad.len() * 8) as u64).to_be_bytes())?;
+
+    let mut tag = signer.sign_to_vec()?;
+    tag.truncate(TAGBYTES);
+
+    Ok(tag)
+}
+
+fn _len_prefixed(data: &[u8]) -> Vec<u8> {
+    let mut res = (data.len() as u32).to_be_bytes().to_vec();
+    res.extend_from_slice(data);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_unwrap_works() {
+        let kek = vec![7u8; KEKBYTES];
+        let cek = gen_cek().unwrap();
+
+        let wrapped = wrap(&kek, &cek).unwrap();
+        assert_eq!(CEKBYTES + WRAPPINGBYTES, wrapped.len());
+        assert_eq!(cek, unwrap(&kek, &wrapped).unwrap());
+    }
+
+    #[test]
+    fn unwrap_not_works_for_other_kek() {
+        let cek = gen_cek().unwrap();
+        let wrapped = wrap(&vec![7u8; KEKBYTES], &cek).unwrap();
+
+        let res = unwrap(&vec![8u8; KEKBYTES], &wrapped);
+        assert_eq!(IndyErrorKind::UnknownCrypto, res.unwrap_err().kind());
+    }
+
+    #[test]
+    fn wrap_works_for_rfc3394_test_vector() {
+        // RFC 3394 section 4.6: wrap 256 bits of key data with a 256-bit KEK
+        let kek: Vec<u8> = (0u8..32).collect();
+        let key_data = [0x00u8, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF,
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F];
+        let expected = [0x28u8, 0xC9, 0xF4, 0x04, 0xC4, 0xB8, 0x10, 0xF4, 0xCB, 0xCC, 0xB3, 0x5C, 0xFB, 0x87, 0xF8, 0x26,
+            0x3F, 0x57, 0x86, 0xE2, 0xD8, 0x0E, 0xD3, 0x26, 0xCB, 0xC7, 0xF0, 0xE7, 0x1A, 0x99, 0xF4, 0x3B,
+            0xFB, 0x98, 0x8B, 0x9B, 0x7A, 0x02, 0xDD, 0x21];
+
+        assert_eq!(expected.to_vec(), wrap(&kek, &key_data).unwrap());
+    }
+
+    #[test]
+    fn encrypt_decrypt_a256cbc_hs512_works() {
+        let cek = gen_cek().unwrap();
+        let plaintext = b"Hello World";
+        let aad = b"protected";
+
+        let (ciphertext, iv, tag) = encrypt_a256cbc_hs512(&cek, plaintext, aad).unwrap();
+        assert_eq!(IVBYTES, iv.len());
+        assert_eq!(TAGBYTES, tag.len());
+
+        let res = decrypt_a256cbc_hs512(&cek, &ciphertext, aad, &iv, &tag).unwrap();
+        assert_eq!(plaintext.to_vec(), res);
+    }
+
+    #[test]
+    fn decrypt_a256cbc_hs512_not_works_for_other_aad() {
+        let cek = gen_cek().unwrap();
+
+        let (ciphertext, iv, tag) = encrypt_a256cbc_hs512(&cek, b"Hello World", b"protected").unwrap();
+
+        let res = decrypt_a256cbc_hs512(&cek, &ciphertext, b"other", &iv, &tag);
+        assert_eq!(IndyErrorKind::UnknownCrypto, res.unwrap_err().kind());
+    }
+
+    #[test]
+    fn concat_kdf_works_for_cc_tag() {
+        let z = vec![1u8; 64];
+
+        let kek = concat_kdf(&z, "ECDH-1PU+A256KW", b"Alice", b"Bob", None).unwrap();
+        assert_eq!(KEKBYTES, kek.len());
+        assert_eq!(kek, concat_kdf(&z, "ECDH-1PU+A256KW", b"Alice", b"Bob", None).unwrap());
+        assert_ne!(kek, concat_kdf(&z, "ECDH-1PU+A256KW", b"Alice", b"Bob", Some(&[2u8; TAGBYTES])).unwrap());
+        assert_ne!(kek, concat_kdf(&z, "ECDH-ES+A256KW", b"Alice", b"Bob", None).unwrap());
+    }
+
+    #[test]
+    fn concat_kdf_works_for_rfc7518_test_vector() {
+        // RFC 7518 appendix C: ECDH-ES key agreement for A128GCM
+        let z = [158u8, 86, 217, 29, 129, 113, 53, 211, 114, 131, 66, 131, 191, 132, 38, 156,
+            251, 49, 110, 163, 218, 128, 106, 72, 246, 218, 167, 121, 140, 254, 144, 196];
+        let expected = [86u8, 170, 141, 234, 248, 35, 109, 32, 92, 34, 40, 205, 113, 167, 16, 26];
+
+        assert_eq!(expected.to_vec(), _concat_kdf(&z, "A128GCM", b"Alice", b"Bob", 16, None).unwrap());
+    }
+
+    #[test]
+    fn concat_kdf_works_for_ecdh_1pu_test_vector() {
+        // draft-madden-jose-ecdh-1pu-04 appendix B: key encryption key of Bob
+        let ze = _from_hex("32810896e0fe4d570ed1acfcedf67117dc194ed5daac21d8ff7af3244694897f");
+        let zs = _from_hex("2157612c9048edfae77cb2e4237140605967c05c7f77a48eeaf2cf29a5737c4a");
+        let cc_tag = _from_hex("1cb6f87d3966f2ca469a28f74723acda02780e91cce21855470745fe119bdd64");
+        let z = [ze, zs].concat();
+
+        let kek = _concat_kdf(&z, "ECDH-1PU+A128KW", b"Alice", b"Bob and Charlie", 16, Some(&cc_tag)).unwrap();
+        assert_eq!(_from_hex("df4c37a0668306a11e3d6b0074b5d8df"), kek);
+    }
+
+    #[test]
+    fn encrypt_decrypt_a256cbc_hs512_works_for_rfc7518_test_vector() {
+        // RFC 7518 appendix B.3: AES_256_CBC_HMAC_SHA_512
+        let cek: Vec<u8> = (0u8..64).collect();
+        let plaintext = b"A cipher system must not be required to be secret, and it must be able to fall into the hands of the enemy without inconvenience";
+        let iv = _from_hex("1af38c2dc2b96ffdd86694092341bc04");
+        let aad = b"The second principle of Auguste Kerckhoffs";
+        let expected_ciphertext = _from_hex("4affaaadb78c31c5da4b1b590d10ffbd3dd8d5d302423526912da037ecbcc7bd\
+                                             822c301dd67c373bccb584ad3e9279c2e6d12a1374b77f077553df829410446b\
+                                             36ebd97066296ae6427ea75c2e0846a11a09ccf5370dc80bfecbad28c73f09b3\
+                                             a3b75e662a2594410ae496b2e2e6609e31e6e02cc837f053d21f37ff4f51950b\
+                                             be2638d09dd7a4930930806d0703b1f6");
+        let expected_tag = _from_hex("4dd3b4c088a7f45c216839645b2012bf2e6269a8c56a816dbc1b267761955bc5");
+
+        let (ciphertext, tag) = _encrypt_a256cbc_hs512(&cek, plaintext, aad, &iv).unwrap();
+        assert_eq!(expected_ciphertext, ciphertext);
+        assert_eq!(expected_tag, tag);
+
+        assert_eq!(plaintext.to_vec(), decrypt_a256cbc_hs512(&cek, &ciphertext, aad, &iv, &tag).unwrap());
+    }
+
+    fn _from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len()).step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+}
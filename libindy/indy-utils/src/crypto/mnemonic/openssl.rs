@@ -0,0 +1,259 @@
+extern crate openssl;
+
+use indy_api_types::errors::prelude::*;
+use self::openssl::hash::MessageDigest;
+use self::openssl::pkcs5::pbkdf2_hmac;
+use self::openssl::pkey::PKey;
+use self::openssl::rand::rand_bytes;
+use self::openssl::sha::sha256;
+use self::openssl::sign::Signer;
+use zeroize::Zeroize;
+
+// BIP-39 English wordlist
+const WORDLIST: &str = include_str!("english.txt");
+const BITS_PER_WORD: usize = 11;
+const PBKDF2_ROUNDS: usize = 2048;
+pub const SEEDBYTES: usize = 64;
+pub const WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+// SLIP-0010 master key generation and hardened child indexes
+const ED25519_CURVE_KEY: &[u8] = b"ed25519 seed";
+const HARDENED_OFFSET: u32 = 0x8000_0000;
+pub const KEYBYTES: usize = 32;
+
+/// Generates BIP-39 mnemonic that encodes random entropy of 128-256 bits depending on word count.
+pub fn generate_mnemonic(word_count: usize) -> Result<String, IndyError> {
+    if !WORD_COUNTS.contains(&word_count) {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Unsupported mnemonic word count: {}. Supported values are {:?}", word_count, WORD_COUNTS)));
+    }
+
+    let mut entropy = vec![0u8; word_count * 4 / 3];
+    rand_bytes(&mut entropy)?;
+
+    let res = _entropy_to_mnemonic(&entropy);
+    entropy.zeroize();
+
+    Ok(res)
+}
+
+/// Validates mnemonic checksum and stretches it with optional passphrase to 64 bytes BIP-39 seed.
+///
+/// Passphrase is used as is, so non-ASCII passphrases are expected to be NFKD normalized by caller.
+/// Caller is expected to zeroize returned seed.
+pub fn mnemonic_to_seed(mnemonic: &str, passphrase: &str) -> Result<Vec<u8>, IndyError> {
+    let mut words = _parse_mnemonic(mnemonic)?;
+    let mut password = words.join(" ");
+    let mut salt = format!("mnemonic{}", passphrase);
+
+    let mut seed = vec![0u8; SEEDBYTES];
+    let res = pbkdf2_hmac(password.as_bytes(), salt.as_bytes(), PBKDF2_ROUNDS, MessageDigest::sha512(), &mut seed);
+
+    for word in words.iter_mut() {
+        word.zeroize();
+    }
+    password.zeroize();
+    salt.zeroize();
+
+    if let Err(err) = res {
+        seed.zeroize();
+        return Err(err.into());
+    }
+
+    Ok(seed)
+}
+
+/// Derives ed25519 private key from BIP-39 seed by SLIP-0010 path like `m/0'/1'`.
+/// Only hardened derivation is defined for ed25519, so all path indexes must be hardened.
+/// Caller is expected to zeroize returned key.
+pub fn derive_ed25519_key(seed: &[u8], path: &str) -> Result<Vec<u8>, IndyError> {
+    let mut indexes = path.split('/');
+
+    if indexes.next() != Some("m") {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Derivation path must start with `m`: {}", path)));
+    }
+
+    let (mut key, mut chain_code) = _hmac_sha512_split(ED25519_CURVE_KEY, &[seed])?;
+
+    for index in indexes {
+        let child = _parse_hardened_index(index)
+            .ok_or_else(|| err_msg(IndyErrorKind::InvalidStructure, format!("Invalid hardened index `{}` in derivation path: {}", index, path)))
+            .and_then(|index| _hmac_sha512_split(&chain_code, &[&[0u8], &key[..], &index.to_be_bytes()]));
+
+        key.zeroize();
+        chain_code.zeroize();
+
+        let (child_key, child_chain_code) = child?;
+        key = child_key;
+        chain_code = child_chain_code;
+    }
+
+    chain_code.zeroize();
+
+    Ok(key)
+}
+
+fn _entropy_to_mnemonic(entropy: &[u8]) -> String {
+    let checksum = sha256(entropy);
+
+    let mut bits: Vec<bool> = _to_bits(entropy);
+    bits.extend(_to_bits(&checksum).into_iter().take(entropy.len() * 8 / 32));
+
+    let words: Vec<&str> = WORDLIST.lines().collect();
+
+    bits.chunks(BITS_PER_WORD)
+        .map(|chunk| words[chunk.iter().fold(0, |index, bit| (index << 1) | *bit as usize)])
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+fn _parse_mnemonic(mnemonic: &str) -> Result<Vec<String>, IndyError> {
+    let words: Vec<String> = mnemonic.split_whitespace().map(str::to_lowercase).collect();
+
+    if !WORD_COUNTS.contains(&words.len()) {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Invalid mnemonic word count: {}", words.len())));
+    }
+
+    let wordlist: Vec<&str> = WORDLIST.lines().collect();
+
+    let mut bits: Vec<bool> = Vec::with_capacity(words.len() * BITS_PER_WORD);
+
+    for word in words.iter() {
+        let index = wordlist.binary_search(&word.as_str())
+            .map_err(|_| err_msg(IndyErrorKind::InvalidStructure, format!("Unknown mnemonic word: {}", word)))?;
+
+        bits.extend((0..BITS_PER_WORD).rev().map(|shift| (index >> shift) & 1 == 1));
+    }
+
+    let (entropy_bits, checksum_bits) = bits.split_at(bits.len() * 32 / 33);
+
+    let mut entropy: Vec<u8> = entropy_bits.chunks(8)
+        .map(|chunk| chunk.iter().fold(0u8, |byte, bit| (byte << 1) | *bit as u8))
+        .collect();
+
+    let checksum = sha256(&entropy);
+    entropy.zeroize();
+
+    if _to_bits(&checksum)[..checksum_bits.len()] != *checksum_bits {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, "Invalid mnemonic checksum"));
+    }
+
+    Ok(words)
+}
+
+fn _parse_hardened_index(index: &str) -> Option<u32> {
+    if !index.ends_with(|c: char| c == '\'' || c == 'h' || c == 'H') {
+        return None;
+    }
+
+    match index[..index.len() - 1].parse::<u32>() {
+        Ok(index) if index < HARDENED_OFFSET => Some(index + HARDENED_OFFSET),
+        _ => None
+    }
+}
+
+fn _hmac_sha512_split(key: &[u8], data: &[&[u8]]) -> Result<(Vec<u8>, Vec<u8>), IndyError> {
+    let key = PKey::hmac(key)?;
+    let mut signer = Signer::new(MessageDigest::sha512(), &key)?;
+
+    for data in data {
+        signer.update(data)?;
+    }
+
+    let mut res = signer.sign_to_vec()?;
+    let (key, chain_code) = (res[..KEYBYTES].to_vec(), res[KEYBYTES..].to_vec());
+    res.zeroize();
+
+    Ok((key, chain_code))
+}
+
+fn _to_bits(bytes: &[u8]) -> Vec<bool> {
+    bytes.iter()
+        .flat_map(|byte| (0..8).rev().map(move |shift| (byte >> shift) & 1 == 1))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ZERO_ENTROPY_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn wordlist_works() {
+        let words: Vec<&str> = WORDLIST.lines().collect();
+        assert_eq!(1 << BITS_PER_WORD, words.len());
+        assert_eq!("abandon", words[0]);
+        assert_eq!("zoo", words[2047]);
+    }
+
+    #[test]
+    fn generate_mnemonic_works() {
+        for word_count in WORD_COUNTS.iter() {
+            let mnemonic = generate_mnemonic(*word_count).unwrap();
+            assert_eq!(*word_count, mnemonic.split(' ').count());
+            assert!(mnemonic_to_seed(&mnemonic, "").is_ok());
+        }
+    }
+
+    #[test]
+    fn generate_mnemonic_not_works_for_unsupported_word_count() {
+        assert_eq!(IndyErrorKind::InvalidStructure, generate_mnemonic(13).unwrap_err().kind());
+    }
+
+    #[test]
+    fn entropy_to_mnemonic_works_for_bip39_test_vectors() {
+        assert_eq!(ZERO_ENTROPY_MNEMONIC, _entropy_to_mnemonic(&[0u8; 16]));
+        assert_eq!("zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong", _entropy_to_mnemonic(&[0xffu8; 16]));
+        assert_eq!("letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic avoid letter always",
+                   _entropy_to_mnemonic(&[0x80u8; 24]));
+    }
+
+    #[test]
+    fn mnemonic_to_seed_works_for_bip39_test_vector() {
+        let seed = mnemonic_to_seed(ZERO_ENTROPY_MNEMONIC, "TREZOR").unwrap();
+        assert_eq!("c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+                   _to_hex(&seed));
+    }
+
+    #[test]
+    fn mnemonic_to_seed_works_for_not_normalized_mnemonic() {
+        let mnemonic = format!("  {}\n", ZERO_ENTROPY_MNEMONIC.to_uppercase().replace(' ', "  "));
+        assert_eq!(mnemonic_to_seed(ZERO_ENTROPY_MNEMONIC, "").unwrap(), mnemonic_to_seed(&mnemonic, "").unwrap());
+    }
+
+    #[test]
+    fn mnemonic_to_seed_not_works_for_invalid_mnemonic() {
+        let invalid_checksum = ZERO_ENTROPY_MNEMONIC.replace("about", "abandon");
+        let unknown_word = ZERO_ENTROPY_MNEMONIC.replace("about", "indy");
+        let short = "abandon abandon abandon";
+
+        for mnemonic in [invalid_checksum.as_str(), unknown_word.as_str(), short].iter() {
+            assert_eq!(IndyErrorKind::InvalidStructure, mnemonic_to_seed(mnemonic, "").unwrap_err().kind());
+        }
+    }
+
+    #[test]
+    fn derive_ed25519_key_works_for_slip10_test_vector() {
+        let seed: Vec<u8> = (0u8..16).collect();
+
+        assert_eq!("2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+                   _to_hex(&derive_ed25519_key(&seed, "m").unwrap()));
+        assert_eq!("68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+                   _to_hex(&derive_ed25519_key(&seed, "m/0'").unwrap()));
+        assert_eq!("b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2",
+                   _to_hex(&derive_ed25519_key(&seed, "m/0H/1H").unwrap()));
+    }
+
+    #[test]
+    fn derive_ed25519_key_not_works_for_invalid_path() {
+        let seed = vec![1u8; SEEDBYTES];
+
+        for path in ["", "0'/1'", "m/0", "m/0'/x'", "m/2147483648'"].iter() {
+            assert_eq!(IndyErrorKind::InvalidStructure, derive_ed25519_key(&seed, path).unwrap_err().kind());
+        }
+    }
+
+    fn _to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
@@ -9,10 +9,22 @@ pub mod base64;
 #[path = "chacha20poly1305_ietf/sodium.rs"]
 pub mod chacha20poly1305_ietf;
 
+#[cfg(feature = "ecdsa_openssl")]
+#[path = "ecdsa/openssl.rs"]
+pub mod ecdsa;
+
 #[cfg(feature = "hash_openssl")]
 #[path = "hash/openssl.rs"]
 pub mod hash;
 
+#[cfg(feature = "jwa_openssl")]
+#[path = "jwa/openssl.rs"]
+pub mod jwa;
+
+#[cfg(feature = "mnemonic_openssl")]
+#[path = "mnemonic/openssl.rs"]
+pub mod mnemonic;
+
 #[cfg(feature = "hmacsha256_sodium")]
 #[path = "hmacsha256/sodium.rs"]
 pub mod hmacsha256;
@@ -218,6 +218,18 @@ macro_rules! check_useful_c_byte_array {
     }
 }
 
+#[macro_export]
+macro_rules! check_useful_opt_c_byte_array {
+    ($ptr:ident, $len:expr, $err1:expr, $err2:expr) => {
+        let $ptr = if $ptr.is_null() {
+            None
+        } else {
+            check_useful_c_byte_array!($ptr, $len, $err1, $err2);
+            Some($ptr)
+        };
+    }
+}
+
 //Returnable pointer is valid only before first vector modification
 pub fn vec_to_pointer(v: &Vec<u8>) -> (*const u8, u32) {
     let len = v.len() as u32;
@@ -41,7 +41,7 @@ pub mod wql;
 
 pub(crate) use indy_api_types::ErrorCode;
 
-use indy_api_types::{CommandHandle, PoolHandle, WalletHandle, SearchHandle};
+use indy_api_types::{CommandHandle, PoolHandle, WalletHandle, SearchHandle, SubscriptionHandle};
 
 pub fn next_wallet_handle() -> WalletHandle { WalletHandle(sequence::get_next_id()) }
 
@@ -57,3 +57,7 @@ pub fn next_search_handle() -> SearchHandle {
     SearchHandle(sequence::get_next_id())
 }
 
+pub fn next_subscription_handle() -> SubscriptionHandle {
+    sequence::get_next_id()
+}
+
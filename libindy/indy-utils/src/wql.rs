@@ -17,6 +17,9 @@ pub enum Query {
     Lt(String, String),
     Lte(String, String),
     Like(String, String),
+    ILike(String, String),
+    Regex(String, String),
+    Exists(String, bool),
     In(String, Vec<String>),
 }
 
@@ -110,6 +113,9 @@ impl Query {
             Query::Lt(ref tag_name, ref tag_value) => json!({tag_name: {"$lt": tag_value}}),
             Query::Lte(ref tag_name, ref tag_value) => json!({tag_name: {"$lte": tag_value}}),
             Query::Like(ref tag_name, ref tag_value) => json!({tag_name: {"$like": tag_value}}),
+            Query::ILike(ref tag_name, ref tag_value) => json!({tag_name: {"$ilike": tag_value}}),
+            Query::Regex(ref tag_name, ref tag_value) => json!({tag_name: {"$regex": tag_value}}),
+            Query::Exists(ref tag_name, exists) => json!({tag_name: {"$exists": exists}}),
             Query::In(ref tag_name, ref tag_values) => json!({tag_name: {"$in": tag_values}}),
             Query::And(ref operators) => {
                 if !operators.is_empty() {
@@ -211,6 +217,12 @@ fn parse_single_operator(operator_name: String, key: String, value: serde_json::
         ("$lte", _) => Err("$lte must be used with string"),
         ("$like", serde_json::Value::String(value_)) => Ok(Query::Like(key, value_)),
         ("$like", _) => Err("$like must be used with string"),
+        ("$ilike", serde_json::Value::String(value_)) => Ok(Query::ILike(key, value_)),
+        ("$ilike", _) => Err("$ilike must be used with string"),
+        ("$regex", serde_json::Value::String(value_)) => Ok(Query::Regex(key, value_)),
+        ("$regex", _) => Err("$regex must be used with string"),
+        ("$exists", serde_json::Value::Bool(value_)) => Ok(Query::Exists(key, value_)),
+        ("$exists", _) => Err("$exists must be used with bool"),
         ("$in", serde_json::Value::Array(values)) => {
             let mut target_values: Vec<String> = Vec::with_capacity(values.len());
 
@@ -382,6 +394,57 @@ mod tests {
         assert_eq!(query, expected);
     }
 
+    #[test]
+    fn test_simple_operator_ilike_parse() {
+        let name1 = _random_string(10);
+        let value1 = _random_string(10);
+
+        let json = format!(r#"{{"{}":{{"$ilike":"{}"}}}}"#, name1, value1);
+
+        let query: Query = ::serde_json::from_str(&json).unwrap();
+
+        let expected = Query::ILike(name1, value1);
+
+        assert_eq!(query, expected);
+    }
+
+    #[test]
+    fn test_simple_operator_regex_parse() {
+        let name1 = _random_string(10);
+
+        let json = format!(r#"{{"{}":{{"$regex":"^a.*z$"}}}}"#, name1);
+
+        let query: Query = ::serde_json::from_str(&json).unwrap();
+
+        let expected = Query::Regex(name1, "^a.*z$".to_string());
+
+        assert_eq!(query, expected);
+    }
+
+    #[test]
+    fn test_simple_operator_exists_parse() {
+        let name1 = _random_string(10);
+
+        let json = format!(r#"{{"{}":{{"$exists":false}}}}"#, name1);
+
+        let query: Query = ::serde_json::from_str(&json).unwrap();
+
+        let expected = Query::Exists(name1, false);
+
+        assert_eq!(query, expected);
+    }
+
+    #[test]
+    fn test_simple_operator_exists_parse_for_string() {
+        let name1 = _random_string(10);
+
+        let json = format!(r#"{{"{}":{{"$exists":"true"}}}}"#, name1);
+
+        let res = ::serde_json::from_str::<Query>(&json);
+
+        assert!(res.is_err());
+    }
+
     #[test]
     fn test_simple_operator_in_plaintext_parse() {
         let name1 = _random_string(10);
@@ -1773,6 +1836,33 @@ mod tests {
         assert_eq!(json, expected);
     }
 
+    #[test]
+    fn test_simple_operator_ilike_to_string() {
+        let name1 = _random_string(10);
+        let value1 = _random_string(10);
+
+        let query = Query::ILike(name1.clone(), value1.clone());
+
+        let json = ::serde_json::to_string(&query).unwrap();
+
+        let expected = format!(r#"{{"{}":{{"$ilike":"{}"}}}}"#, name1, value1);
+
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn test_simple_operator_exists_to_string() {
+        let name1 = _random_string(10);
+
+        let query = Query::Exists(name1.clone(), true);
+
+        let json = ::serde_json::to_string(&query).unwrap();
+
+        let expected = format!(r#"{{"{}":{{"$exists":true}}}}"#, name1);
+
+        assert_eq!(json, expected);
+    }
+
     #[test]
     fn test_simple_operator_in_to_string() {
         let name1 = _random_string(10);
@@ -147,7 +147,11 @@ pub(super) fn decrypt(data: &[u8], key: &chacha20poly1305_ietf::Key, nonce: &cha
 }
 
 pub(super) fn decrypt_merged(joined_data: &[u8], key: &chacha20poly1305_ietf::Key) -> IndyResult<Vec<u8>> {
-    let nonce = chacha20poly1305_ietf::Nonce::from_slice(&joined_data[..chacha20poly1305_ietf::NONCEBYTES]).unwrap(); // We can safety unwrap here
+    if joined_data.len() < chacha20poly1305_ietf::NONCEBYTES {
+        return Err(err_msg(IndyErrorKind::WalletEncryptionError, "Encrypted data is too short"));
+    }
+
+    let nonce = chacha20poly1305_ietf::Nonce::from_slice(&joined_data[..chacha20poly1305_ietf::NONCEBYTES]).unwrap(); // We can safety unwrap here as length is checked
     let data = &joined_data[chacha20poly1305_ietf::NONCEBYTES..];
     let res = decrypt(data, key, &nonce)?;
     Ok(res)
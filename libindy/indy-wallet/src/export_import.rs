@@ -1,21 +1,28 @@
+use std::collections::{HashMap, HashSet};
 use std::io;
+use std::iter;
 use std::io::{BufReader, BufWriter, Read, Write};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use rmp_serde;
 
-use indy_api_types::domain::wallet::Record;
+use indy_api_types::{CommandHandle, ErrorCode};
+use indy_api_types::domain::wallet::{ExportSummary, Record};
 use indy_api_types::domain::wallet::KeyDerivationMethod;
 use indy_api_types::errors::prelude::*;
+use indy_api_types::wallet::{WalletExportWrite, WalletImportRead};
 use crate::encryption::KeyDerivationData;
 use indy_utils::crypto::{chacha20poly1305_ietf, pwhash_argon2i13};
 use indy_utils::crypto::hash::{hash, HASHBYTES};
 
 use super::{Wallet, WalletRecord};
+use super::iterator::WalletIterator;
 
 const CHUNK_SIZE: usize = 1024;
 
+const EXPORT_SEARCH_OPTIONS: &str = r#"{"retrieveType": true, "retrieveValue": true, "retrieveTags": true}"#;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub enum EncryptionMethod {
     // **ChaCha20-Poly1305-IETF** cypher in blocks per chunk_size bytes
@@ -69,7 +76,8 @@ pub struct Header {
 //   "version": ..,
 // }
 
-pub(super) fn export_continue(wallet: &Wallet, writer: &mut dyn Write, version: u32, key: chacha20poly1305_ietf::Key, key_data: &KeyDerivationData) -> IndyResult<()> {
+pub(super) fn export_continue(wallet: &Wallet, writer: &mut dyn Write, version: u32, key: chacha20poly1305_ietf::Key, key_data: &KeyDerivationData,
+                              types: Option<&[String]>, query: Option<&str>) -> IndyResult<()> {
     let nonce = chacha20poly1305_ietf::gen_nonce();
     let chunk_size = CHUNK_SIZE;
 
@@ -112,8 +120,36 @@ pub(super) fn export_continue(wallet: &Wallet, writer: &mut dyn Write, version:
 
     writer.write_all(&hash(&header)?)?;
 
-    let mut records = wallet.get_all()?;
+    match types {
+        Some(types) => {
+            let query = query.unwrap_or("{}");
+            let mut exported_types = HashSet::new();
+
+            for type_ in types {
+                if !exported_types.insert(type_) {
+                    continue;
+                }
+
+                let mut records = wallet.search(type_, query, Some(EXPORT_SEARCH_OPTIONS))?;
+                _write_records(&mut writer, &mut records)?;
+            }
+        }
+        None => {
+            if query.is_some() {
+                return Err(err_msg(IndyErrorKind::InvalidStructure, "Export query requires record types to be specified"));
+            }
+
+            let mut records = wallet.get_all()?;
+            _write_records(&mut writer, &mut records)?;
+        }
+    }
+
+    writer.write_u32::<LittleEndian>(0)?; // END message
+    writer.flush()?;
+    Ok(())
+}
 
+fn _write_records<W>(writer: &mut W, records: &mut WalletIterator) -> IndyResult<()> where W: Write {
     while let Some(WalletRecord { type_, id, value, tags }) = records.next()? {
         let record = Record {
             type_: type_.ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "No type fetched for exported record"))?,
@@ -129,8 +165,6 @@ pub(super) fn export_continue(wallet: &Wallet, writer: &mut dyn Write, version:
         writer.write_all(&record)?;
     }
 
-    writer.write_u32::<LittleEndian>(0)?; // END message
-    writer.flush()?;
     Ok(())
 }
 
@@ -145,6 +179,70 @@ pub(super) fn preparse_file_to_import<T>(reader: T, passphrase: &str) -> IndyRes
     // Reads plain
     let mut reader = BufReader::new(reader);
 
+    let (header, header_bytes) = _read_header(&mut reader)?;
+    let (import_key_derivation_data, nonce, chunk_size) = _parse_encryption_method(header.encryption_method, passphrase)?;
+
+    Ok((reader, import_key_derivation_data, nonce, chunk_size, header_bytes))
+}
+
+pub(super) fn finish_import<T>(wallet: &Wallet, reader: BufReader<T>, key: chacha20poly1305_ietf::Key, nonce: chacha20poly1305_ietf::Nonce, chunk_size: usize, header_bytes: Vec<u8>) -> IndyResult<()> where T: Read {
+    // Reads encrypted
+    let mut reader = chacha20poly1305_ietf::Reader::new(reader, key, nonce, chunk_size);
+
+    _check_header_hash(&mut reader, &header_bytes)?;
+
+    // Records are merged in chunks inside of one storage transaction, so broken or conflicting
+    // export doesn't leave the wallet partially merged
+    let records = iter::from_fn(|| _read_record(&mut reader).transpose());
+    wallet.add_all(records)
+}
+
+pub(super) fn inspect_export<T>(reader: T, passphrase: &str) -> IndyResult<ExportSummary> where T: Read {
+    // Reads plain
+    let mut reader = BufReader::new(CountingReader { inner: reader, count: 0 });
+
+    let (header, header_bytes) = _read_header(&mut reader)?;
+
+    let (encryption_method, key_derivation_method) = match header.encryption_method {
+        EncryptionMethod::ChaCha20Poly1305IETF { .. } => ("ChaCha20Poly1305IETF", KeyDerivationMethod::ARGON2I_MOD),
+        EncryptionMethod::ChaCha20Poly1305IETFInteractive { .. } => ("ChaCha20Poly1305IETFInteractive", KeyDerivationMethod::ARGON2I_INT),
+        EncryptionMethod::ChaCha20Poly1305IETFRaw { .. } => ("ChaCha20Poly1305IETFRaw", KeyDerivationMethod::RAW),
+    };
+
+    let (key_data, nonce, chunk_size) = _parse_encryption_method(header.encryption_method, passphrase)?;
+    let key = key_data.calc_master_key()?;
+
+    // Reads encrypted
+    let mut reader = chacha20poly1305_ietf::Reader::new(reader, key, nonce, chunk_size);
+
+    _check_header_hash(&mut reader, &header_bytes)?;
+
+    let mut types: HashMap<String, usize> = HashMap::new();
+    let mut records_count = 0;
+
+    while let Some(record) = _read_record(&mut reader)? {
+        *types.entry(record.type_).or_insert(0) += 1;
+        records_count += 1;
+    }
+
+    let mut reader = reader.into_inner();
+
+    if reader.read(&mut [0u8; 1]).map_err(_map_io_err)? != 0 {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, "Unexpected data after the end of export"));
+    }
+
+    Ok(ExportSummary {
+        version: header.version,
+        time: header.time,
+        encryption_method: encryption_method.to_string(),
+        key_derivation_method,
+        types,
+        records_count,
+        size: reader.get_ref().count,
+    })
+}
+
+fn _read_header<T>(reader: &mut BufReader<T>) -> IndyResult<(Header, Vec<u8>)> where T: Read {
     let header_len = reader.read_u32::<LittleEndian>().map_err(_map_io_err)? as usize;
 
     if header_len == 0 {
@@ -161,13 +259,17 @@ pub(super) fn preparse_file_to_import<T>(reader: T, passphrase: &str) -> IndyRes
         return Err(err_msg(IndyErrorKind::InvalidStructure, "Unsupported version"));
     }
 
-    let key_derivation_method = match header.encryption_method {
+    Ok((header, header_bytes))
+}
+
+fn _parse_encryption_method(encryption_method: EncryptionMethod, passphrase: &str) -> IndyResult<(KeyDerivationData, chacha20poly1305_ietf::Nonce, usize)> {
+    let key_derivation_method = match encryption_method {
         EncryptionMethod::ChaCha20Poly1305IETF { .. } => KeyDerivationMethod::ARGON2I_MOD,
         EncryptionMethod::ChaCha20Poly1305IETFInteractive { .. } => KeyDerivationMethod::ARGON2I_INT,
         EncryptionMethod::ChaCha20Poly1305IETFRaw { .. } => KeyDerivationMethod::RAW,
     };
 
-    let (import_key_derivation_data, nonce, chunk_size) = match header.encryption_method {
+    let res = match encryption_method {
         EncryptionMethod::ChaCha20Poly1305IETF { salt, nonce, chunk_size } | EncryptionMethod::ChaCha20Poly1305IETFInteractive { salt, nonce, chunk_size } => {
             let salt = pwhash_argon2i13::Salt::from_slice(&salt)
                 .to_indy(IndyErrorKind::InvalidStructure, "Invalid salt")?;
@@ -197,37 +299,104 @@ pub(super) fn preparse_file_to_import<T>(reader: T, passphrase: &str) -> IndyRes
         }
     };
 
-    Ok((reader, import_key_derivation_data, nonce, chunk_size, header_bytes))
+    Ok(res)
 }
 
-pub(super) fn finish_import<T>(wallet: &Wallet, reader: BufReader<T>, key: chacha20poly1305_ietf::Key, nonce: chacha20poly1305_ietf::Nonce, chunk_size: usize, header_bytes: Vec<u8>) -> IndyResult<()> where T: Read {
-    // Reads encrypted
-    let mut reader = chacha20poly1305_ietf::Reader::new(reader, key, nonce, chunk_size);
-
+fn _check_header_hash<T>(reader: &mut chacha20poly1305_ietf::Reader<T>, header_bytes: &[u8]) -> IndyResult<()> where T: Read {
     let mut header_hash = vec![0u8; HASHBYTES];
     reader.read_exact(&mut header_hash).map_err(_map_io_err)?;
 
-    if hash(&header_bytes)? != header_hash {
+    if hash(header_bytes)? != header_hash {
         return Err(err_msg(IndyErrorKind::InvalidStructure, "Invalid header hash"));
     }
 
-    loop {
-        let record_len = reader.read_u32::<LittleEndian>().map_err(_map_io_err)? as usize;
+    Ok(())
+}
+
+fn _read_record<T>(reader: &mut chacha20poly1305_ietf::Reader<T>) -> IndyResult<Option<Record>> where T: Read {
+    let record_len = reader.read_u32::<LittleEndian>().map_err(_map_io_err)? as usize;
+
+    if record_len == 0 {
+        return Ok(None);
+    }
+
+    let mut record = vec![0u8; record_len];
+    reader.read_exact(&mut record).map_err(_map_io_err)?;
+
+    let record: Record = rmp_serde::from_slice(&record)
+        .to_indy(IndyErrorKind::InvalidStructure, "Record is malformed msgpack")?;
+
+    Ok(Some(record))
+}
+
+struct CountingReader<T> where T: Read {
+    inner: T,
+    count: u64,
+}
+
+impl<T> Read for CountingReader<T> where T: Read {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count += read as u64;
+        Ok(read)
+    }
+}
+
+/// Writer that passes exported wallet data to the caller-provided write callback.
+pub struct CallbackWriter {
+    command_handle: CommandHandle,
+    write_cb: WalletExportWrite,
+}
+
+impl CallbackWriter {
+    pub fn new(command_handle: CommandHandle, write_cb: WalletExportWrite) -> CallbackWriter {
+        CallbackWriter { command_handle, write_cb }
+    }
+}
+
+impl Write for CallbackWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
 
-        if record_len == 0 {
-            break;
+        match (self.write_cb)(self.command_handle, buf.as_ptr(), buf.len() as u32) {
+            ErrorCode::Success => Ok(buf.len()),
+            err => Err(io::Error::new(io::ErrorKind::Other, format!("Export write callback failed: {:?}", err)))
         }
+    }
 
-        let mut record = vec![0u8; record_len];
-        reader.read_exact(&mut record).map_err(_map_io_err)?;
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
 
-        let record: Record = rmp_serde::from_slice(&record)
-            .to_indy(IndyErrorKind::InvalidStructure, "Record is malformed msgpack")?;
+/// Reader that takes exported wallet data from the caller-provided read callback.
+pub struct CallbackReader {
+    command_handle: CommandHandle,
+    read_cb: WalletImportRead,
+}
 
-        wallet.add(&record.type_, &record.id, &record.value, &record.tags)?;
+impl CallbackReader {
+    pub fn new(command_handle: CommandHandle, read_cb: WalletImportRead) -> CallbackReader {
+        CallbackReader { command_handle, read_cb }
     }
+}
 
-    Ok(())
+impl Read for CallbackReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let mut read_len: u32 = 0;
+
+        match (self.read_cb)(self.command_handle, buf.as_mut_ptr(), buf.len() as u32, &mut read_len) {
+            ErrorCode::Success if read_len as usize <= buf.len() => Ok(read_len as usize),
+            ErrorCode::Success => Err(io::Error::new(io::ErrorKind::InvalidInput, "Import read callback returned more data than requested")),
+            err => Err(io::Error::new(io::ErrorKind::Other, format!("Import read callback failed: {:?}", err)))
+        }
+    }
 }
 
 fn _map_io_err(e: io::Error) -> IndyError {
@@ -263,7 +432,14 @@ mod tests {
         let key_data = KeyDerivationData::from_passphrase_with_new_salt(passphrase, key_derivation_method);
         let key = key_data.calc_master_key()?;
 
-        export_continue(wallet, writer, version, key, &key_data)
+        export_continue(wallet, writer, version, key, &key_data, None, None)
+    }
+
+    fn export_filtered(wallet: &Wallet, writer: &mut dyn Write, passphrase: &str, types: &[String], query: Option<&str>) -> IndyResult<()> {
+        let key_data = KeyDerivationData::from_passphrase_with_new_salt(passphrase, &KeyDerivationMethod::ARGON2I_INT);
+        let key = key_data.calc_master_key()?;
+
+        export_continue(wallet, writer, _version1(), key, &key_data, Some(types), query)
     }
 
     #[test]
@@ -347,6 +523,136 @@ mod tests {
         _cleanup("export_import_works_for_multiple_items2");
     }
 
+    #[test]
+    fn export_import_works_for_types_filter() {
+        _cleanup("export_import_works_for_types_filter1");
+        _cleanup("export_import_works_for_types_filter2");
+        {
+            let mut output: Vec<u8> = Vec::new();
+            export_filtered(&_add_300_records(_wallet("export_import_works_for_types_filter1")), &mut output, _passphrase(), &[_type(1), _type(1)], None).unwrap();
+
+            let wallet = _wallet("export_import_works_for_types_filter2");
+            import(&wallet, &mut output.as_slice(), _passphrase()).unwrap();
+
+            for i in 0..300 {
+                let res = wallet.get(&_type(i % 3), &_id(i), _options());
+                if i % 3 == 1 {
+                    assert_eq!(res.unwrap().value.unwrap(), _value(i));
+                } else {
+                    assert_eq!(IndyErrorKind::WalletItemNotFound, res.unwrap_err().kind());
+                }
+            }
+        }
+        _cleanup("export_import_works_for_types_filter1");
+        _cleanup("export_import_works_for_types_filter2");
+    }
+
+    #[test]
+    fn export_import_works_for_query_filter() {
+        _cleanup("export_import_works_for_query_filter1");
+        _cleanup("export_import_works_for_query_filter2");
+        {
+            let mut output: Vec<u8> = Vec::new();
+            let query = json!({"~tag_id_1_3": "tag_value_1_3"}).to_string();
+            export_filtered(&_add_2_records(_wallet("export_import_works_for_query_filter1")), &mut output, _passphrase(), &[_type1(), _type2()], Some(&query)).unwrap();
+
+            let wallet = _wallet("export_import_works_for_query_filter2");
+            import(&wallet, &mut output.as_slice(), _passphrase()).unwrap();
+
+            let record = wallet.get(&_type1(), &_id1(), _options()).unwrap();
+            assert_eq!(record.tags.unwrap(), _tags1());
+
+            let res = wallet.get(&_type2(), &_id2(), _options());
+            assert_eq!(IndyErrorKind::WalletItemNotFound, res.unwrap_err().kind());
+        }
+        _cleanup("export_import_works_for_query_filter1");
+        _cleanup("export_import_works_for_query_filter2");
+    }
+
+    #[test]
+    fn export_works_for_query_without_types() {
+        _cleanup("export_works_for_query_without_types");
+
+        let key_data = KeyDerivationData::from_passphrase_with_new_salt(_passphrase(), &KeyDerivationMethod::ARGON2I_INT);
+        let key = key_data.calc_master_key().unwrap();
+
+        let mut output: Vec<u8> = Vec::new();
+        let res = export_continue(&_wallet("export_works_for_query_without_types"), &mut output, _version1(), key, &key_data, None, Some("{}"));
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+        _cleanup("export_works_for_query_without_types");
+    }
+
+    #[test]
+    fn export_import_works_for_non_empty_wallet() {
+        _cleanup("export_import_works_for_non_empty_wallet1");
+        _cleanup("export_import_works_for_non_empty_wallet2");
+        {
+            let mut output: Vec<u8> = Vec::new();
+            export_filtered(&_add_2_records(_wallet("export_import_works_for_non_empty_wallet1")), &mut output, _passphrase(), &[_type1()], None).unwrap();
+
+            let wallet = _wallet("export_import_works_for_non_empty_wallet2");
+            wallet.add(&_type2(), &_id2(), &_value2(), &_tags2()).unwrap();
+
+            import(&wallet, &mut output.as_slice(), _passphrase()).unwrap();
+            _assert_has_2_records(&wallet);
+        }
+        _cleanup("export_import_works_for_non_empty_wallet1");
+        _cleanup("export_import_works_for_non_empty_wallet2");
+    }
+
+    #[test]
+    fn import_works_for_existing_record() {
+        _cleanup("import_works_for_existing_record1");
+        _cleanup("import_works_for_existing_record2");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_add_2_records(_wallet("import_works_for_existing_record1")), &mut output, _passphrase(), _version1(), &KeyDerivationMethod::ARGON2I_INT).unwrap();
+
+        let wallet = _wallet("import_works_for_existing_record2");
+        wallet.add(&_type2(), &_id2(), &_value1(), &_tags1()).unwrap();
+
+        let res = import(&wallet, &mut output.as_slice(), _passphrase());
+        assert_eq!(IndyErrorKind::WalletItemAlreadyExists, res.unwrap_err().kind());
+
+        // Nothing is imported and the existing record is kept as is
+        let res = wallet.get(&_type1(), &_id1(), _options());
+        assert_eq!(IndyErrorKind::WalletItemNotFound, res.unwrap_err().kind());
+
+        let record = wallet.get(&_type2(), &_id2(), _options()).unwrap();
+        assert_eq!(record.value.unwrap(), _value1());
+        assert_eq!(record.tags.unwrap(), _tags1());
+
+        _cleanup("import_works_for_existing_record1");
+        _cleanup("import_works_for_existing_record2");
+    }
+
+    #[test]
+    fn import_works_for_existing_record_in_last_chunk() {
+        _cleanup("import_works_for_existing_record_in_last_chunk1");
+        _cleanup("import_works_for_existing_record_in_last_chunk2");
+        {
+            let mut output: Vec<u8> = Vec::new();
+            export(&_add_300_records(_wallet("import_works_for_existing_record_in_last_chunk1")), &mut output, _passphrase(), _version1(), &KeyDerivationMethod::ARGON2I_MOD).unwrap();
+
+            let wallet = _wallet("import_works_for_existing_record_in_last_chunk2");
+            wallet.add(&_type(299 % 3), &_id(299), &_value1(), &_tags1()).unwrap();
+
+            let res = import(&wallet, &mut output.as_slice(), _passphrase());
+            assert_eq!(IndyErrorKind::WalletItemAlreadyExists, res.unwrap_err().kind());
+
+            // Chunks written before the conflict are rolled back
+            for i in 0..299 {
+                let res = wallet.get(&_type(i % 3), &_id(i), _options());
+                assert_eq!(IndyErrorKind::WalletItemNotFound, res.unwrap_err().kind());
+            }
+
+            let record = wallet.get(&_type(299 % 3), &_id(299), _options()).unwrap();
+            assert_eq!(record.value.unwrap(), _value1());
+        }
+        _cleanup("import_works_for_existing_record_in_last_chunk1");
+        _cleanup("import_works_for_existing_record_in_last_chunk2");
+    }
+
     #[test]
     fn import_works_for_empty() {
         _cleanup("import_works_for_empty");
@@ -460,6 +766,86 @@ mod tests {
         _cleanup("import_works_for_data_extended2");
     }
 
+    #[test]
+    fn inspect_export_works_for_multiple_items() {
+        _cleanup("inspect_export_works_for_multiple_items");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_add_300_records(_wallet("inspect_export_works_for_multiple_items")), &mut output, _passphrase(), _version1(), &KeyDerivationMethod::ARGON2I_MOD).unwrap();
+
+        let summary = inspect_export(&mut output.as_slice(), _passphrase()).unwrap();
+        assert_eq!(_version1(), summary.version);
+        assert_eq!("ChaCha20Poly1305IETF", summary.encryption_method);
+        assert_match!(KeyDerivationMethod::ARGON2I_MOD, summary.key_derivation_method);
+        assert_eq!(300, summary.records_count);
+        assert_eq!(3, summary.types.len());
+        assert_eq!(100, summary.types[&_type(0)]);
+        assert_eq!(100, summary.types[&_type(1)]);
+        assert_eq!(100, summary.types[&_type(2)]);
+        assert_eq!(output.len() as u64, summary.size);
+
+        _cleanup("inspect_export_works_for_multiple_items");
+    }
+
+    #[test]
+    fn inspect_export_works_for_empty_wallet() {
+        _cleanup("inspect_export_works_for_empty_wallet");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_wallet("inspect_export_works_for_empty_wallet"), &mut output, _passphrase(), _version1(), &KeyDerivationMethod::ARGON2I_INT).unwrap();
+
+        let summary = inspect_export(&mut output.as_slice(), _passphrase()).unwrap();
+        assert_match!(KeyDerivationMethod::ARGON2I_INT, summary.key_derivation_method);
+        assert_eq!(0, summary.records_count);
+        assert!(summary.types.is_empty());
+
+        _cleanup("inspect_export_works_for_empty_wallet");
+    }
+
+    #[test]
+    fn inspect_export_works_for_other_passphrase() {
+        _cleanup("inspect_export_works_for_other_passphrase");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_add_2_records(_wallet("inspect_export_works_for_other_passphrase")), &mut output, _passphrase(), _version1(), &KeyDerivationMethod::ARGON2I_MOD).unwrap();
+
+        let res = inspect_export(&mut output.as_slice(), "other_passphrase");
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+
+        _cleanup("inspect_export_works_for_other_passphrase");
+    }
+
+    #[test]
+    fn inspect_export_works_for_changed_record() {
+        _cleanup("inspect_export_works_for_changed_record");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_add_300_records(_wallet("inspect_export_works_for_changed_record")), &mut output, _passphrase(), _version1(), &KeyDerivationMethod::ARGON2I_MOD).unwrap();
+
+        let pos = output.len() / 2;
+        _change_byte(&mut output, pos);
+
+        let res = inspect_export(&mut output.as_slice(), _passphrase());
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+
+        _cleanup("inspect_export_works_for_changed_record");
+    }
+
+    #[test]
+    fn inspect_export_works_for_data_cut() {
+        _cleanup("inspect_export_works_for_data_cut");
+
+        let mut output: Vec<u8> = Vec::new();
+        export(&_add_2_records(_wallet("inspect_export_works_for_data_cut")), &mut output, _passphrase(), _version1(), &KeyDerivationMethod::ARGON2I_MOD).unwrap();
+
+        output.pop().unwrap();
+
+        let res = inspect_export(&mut output.as_slice(), _passphrase());
+        assert_eq!(IndyErrorKind::InvalidStructure, res.unwrap_err().kind());
+
+        _cleanup("inspect_export_works_for_data_cut");
+    }
+
     fn _cleanup(name: &str) {
         test::cleanup_storage(name)
     }
@@ -4,12 +4,31 @@ use indy_api_types::errors::IndyError;
 
 use super::WalletRecord;
 use super::wallet::Keys;
-use super::storage::StorageIterator;
+use super::storage::{StorageIterator, Tag};
 use super::encryption::{decrypt_storage_record};
 
 pub(super) struct WalletIterator {
     storage_iterator: Box<dyn StorageIterator>,
     keys: Rc<Keys>,
+    hidden_tag: Option<&'static str>,
+    expiry_filter: Option<ExpiryFilter>,
+}
+
+// Drops records which plain tag with given encrypted name has value not greater than now.
+// Tags are fetched to check expiry, so they are dropped if caller didn't ask for them
+pub(super) struct ExpiryFilter {
+    pub tag_name: Vec<u8>,
+    pub now: String,
+    pub retrieve_tags: bool,
+}
+
+impl ExpiryFilter {
+    pub fn is_expired(&self, tags: &Option<Vec<Tag>>) -> bool {
+        tags.as_ref().map_or(false, |tags| tags.iter().any(|tag| match *tag {
+            Tag::PlainText(ref name, ref value) => name[..] == self.tag_name[..] && value.as_str() <= self.now.as_str(),
+            _ => false
+        }))
+    }
 }
 
 
@@ -18,15 +37,43 @@ impl WalletIterator {
         WalletIterator {
             storage_iterator: storage_iter,
             keys,
+            hidden_tag: None,
+            expiry_filter: None,
         }
     }
 
+    // Removes reserved tag from tags of returned records
+    pub fn hiding_tag(mut self, tag_name: &'static str) -> Self {
+        self.hidden_tag = Some(tag_name);
+        self
+    }
+
+    // Filters out expired records returned by storage that can't do it by query
+    pub fn filtering_expired(mut self, expiry_filter: ExpiryFilter) -> Self {
+        self.expiry_filter = Some(expiry_filter);
+        self
+    }
+
     pub fn next(&mut self) -> Result<Option<WalletRecord>, IndyError> {
-        let next_storage_entity = self.storage_iterator.next()?;
-        if let Some(next_storage_entity) = next_storage_entity {
-            let record = decrypt_storage_record(&next_storage_entity, &self.keys)?;
-            Ok(Some(record))
-        } else { Ok(None) }
+        while let Some(mut next_storage_entity) = self.storage_iterator.next()? {
+            if let Some(ref expiry_filter) = self.expiry_filter {
+                if expiry_filter.is_expired(&next_storage_entity.tags) {
+                    continue;
+                }
+
+                if !expiry_filter.retrieve_tags {
+                    next_storage_entity.tags = None;
+                }
+            }
+
+            let mut record = decrypt_storage_record(&next_storage_entity, &self.keys)?;
+            if let (Some(tag_name), Some(tags)) = (self.hidden_tag, record.tags.as_mut()) {
+                tags.remove(tag_name);
+            }
+            return Ok(Some(record));
+        }
+
+        Ok(None)
     }
 
     pub fn get_total_count(&self) -> Result<Option<usize>, IndyError> {
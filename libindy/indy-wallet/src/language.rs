@@ -48,7 +48,7 @@ impl From<String> for TargetValue {
 impl string::ToString for TargetValue {
     fn to_string(&self) -> String {
         match *self {
-            TargetValue::Unencrypted(ref s) => format!(r#""{}""#, s),
+            TargetValue::Unencrypted(ref s) => serde_json::Value::String(s.clone()).to_string(),
             TargetValue::Encrypted(ref v) => format!(r#""{}""#, base64::encode(v)),
         }
     }
@@ -66,6 +66,9 @@ pub enum Operator {
     Lt(TagName, TargetValue),
     Lte(TagName, TargetValue),
     Like(TagName, TargetValue),
+    ILike(TagName, TargetValue),
+    Regex(TagName, TargetValue),
+    Exists(TagName, bool),
     In(TagName, Vec<TargetValue>),
 }
 
@@ -79,6 +82,9 @@ impl string::ToString for Operator {
             Operator::Lt(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$lt":{}}}}}"#, tag_name.to_string(), tag_value.to_string()),
             Operator::Lte(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$lte":{}}}}}"#, tag_name.to_string(), tag_value.to_string()),
             Operator::Like(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$like":{}}}}}"#, tag_name.to_string(), tag_value.to_string()),
+            Operator::ILike(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$ilike":{}}}}}"#, tag_name.to_string(), tag_value.to_string()),
+            Operator::Regex(ref tag_name, ref tag_value) => format!(r#"{{{}:{{"$regex":{}}}}}"#, tag_name.to_string(), tag_value.to_string()),
+            Operator::Exists(ref tag_name, exists) => format!(r#"{{{}:{{"$exists":{}}}}}"#, tag_name.to_string(), exists),
             Operator::In(ref tag_name, ref tag_values) => {
                 format!(
                     r#"{{{}:{{"$in":[{}]}}}}"#,
@@ -14,7 +14,7 @@ extern crate serde_derive;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fs;
-use std::io::BufReader;
+use std::io::{BufReader, Read, Write};
 use std::path::PathBuf;
 use std::rc::Rc;
 
@@ -23,18 +23,24 @@ use serde_json::Value as SValue;
 
 use indy_api_types::wallet::*;
 
-use indy_api_types::domain::wallet::{Config, Credentials, ExportConfig, Tags};
+use indy_api_types::domain::wallet::{AddRecordOptions, CheckWalletOptions, Config, Credentials, ExportConfig, ExportSummary, RekeyCredentials, StreamExportConfig, Tags, UpdateRecordOptions, WalletAggregation, WalletAggregationResult, WalletCheckReport, WalletSyncOptions, WalletSyncResult};
 use indy_api_types::errors::prelude::*;
 pub use crate::encryption::KeyDerivationData;
+pub use crate::export_import::{CallbackReader, CallbackWriter};
+pub use crate::storage::migration::{migrate, MigrationOptions, MigrationResult, MigrationStep, MigrationTarget};
+pub use crate::storage::{StorageIssue, StorageIterator, StorageOperation, StorageRecord, Tag, TagName, WalletStorage, WalletStorageType};
+pub use crate::wallet::EncryptedValue;
+pub use crate::subscription::{WalletChange, WalletChangeCallback};
 use indy_utils::crypto::chacha20poly1305_ietf;
 use indy_utils::crypto::chacha20poly1305_ietf::Key as MasterKey;
 
-use self::export_import::{export_continue, finish_import, preparse_file_to_import};
-use self::storage::{WalletStorage, WalletStorageType};
+use self::export_import::{export_continue, finish_import, inspect_export, preparse_file_to_import};
 use self::storage::default::SQLiteStorageType;
+use self::storage::inmem::InmemStorageType;
 use self::storage::plugged::PluggedStorageType;
-use self::wallet::{Keys, Wallet};
-use indy_api_types::{WalletHandle};
+use self::wallet::{Keys, Wallet, EXPIRY_TAG_NAME};
+use self::subscription::Subscription;
+use indy_api_types::{SubscriptionHandle, WalletHandle};
 
 mod storage;
 mod encryption;
@@ -44,12 +50,15 @@ mod iterator;
 pub mod language;
 mod export_import;
 mod wallet;
+mod subscription;
 
 pub struct WalletService {
     storage_types: RefCell<HashMap<String, Box<dyn WalletStorageType>>>,
     wallets: RefCell<HashMap<WalletHandle, Box<Wallet>>>,
     pending_for_open: RefCell<HashMap<WalletHandle, (String /* id */, Box<dyn WalletStorage>, Metadata, Option<KeyDerivationData>)>>,
-    pending_for_import: RefCell<HashMap<WalletHandle, (BufReader<::std::fs::File>, chacha20poly1305_ietf::Nonce, usize, Vec<u8>, KeyDerivationData)>>,
+    pending_for_import: RefCell<HashMap<WalletHandle, (BufReader<Box<dyn Read>>, chacha20poly1305_ietf::Nonce, usize, Vec<u8>, KeyDerivationData)>>,
+    pending_for_import_into: RefCell<HashMap<WalletHandle, (BufReader<Box<dyn Read>>, chacha20poly1305_ietf::Nonce, usize, Vec<u8>)>>,
+    subscriptions: RefCell<HashMap<SubscriptionHandle, Subscription>>,
 }
 
 impl WalletService {
@@ -57,6 +66,7 @@ impl WalletService {
         let storage_types = {
             let mut map: HashMap<String, Box<dyn WalletStorageType>> = HashMap::new();
             map.insert("default".to_string(), Box::new(SQLiteStorageType::new()));
+            map.insert("inmem".to_string(), Box::new(InmemStorageType::new()));
             RefCell::new(map)
         };
 
@@ -65,6 +75,8 @@ impl WalletService {
             wallets: RefCell::new(HashMap::new()),
             pending_for_open: RefCell::new(HashMap::new()),
             pending_for_import: RefCell::new(HashMap::new()),
+            pending_for_import_into: RefCell::new(HashMap::new()),
+            subscriptions: RefCell::new(HashMap::new()),
         }
     }
 
@@ -118,6 +130,58 @@ impl WalletService {
         Ok(())
     }
 
+    pub fn register_native_wallet_storage(&self, type_: &str, storage_type: Box<dyn WalletStorageType>) -> IndyResult<()> {
+        trace!("register_native_wallet_storage >>> type_: {:?}", type_);
+
+        let mut storage_types = self.storage_types.borrow_mut();
+
+        if storage_types.contains_key(type_) {
+            return Err(err_msg(IndyErrorKind::WalletStorageTypeAlreadyRegistered, format!("Wallet storage is already registered for type: {}", type_)));
+        }
+
+        storage_types.insert(type_.to_string(), storage_type);
+
+        trace!("register_native_wallet_storage <<<");
+        Ok(())
+    }
+
+    pub fn register_wallet_storage_transaction_hooks(&self,
+                                                     type_: &str,
+                                                     begin_transaction: WalletBeginTransaction,
+                                                     commit_transaction: WalletCommitTransaction,
+                                                     rollback_transaction: WalletRollbackTransaction) -> IndyResult<()> {
+        trace!("register_wallet_storage_transaction_hooks >>> type_: {:?}", type_);
+
+        let storage_types = self.storage_types.borrow();
+
+        let storage_type = storage_types
+            .get(type_)
+            .ok_or_else(|| err_msg(IndyErrorKind::UnknownWalletStorageType, format!("Unknown wallet storage type: {}", type_)))?;
+
+        storage_type.register_transaction_hooks(begin_transaction, commit_transaction, rollback_transaction)?;
+
+        trace!("register_wallet_storage_transaction_hooks <<<");
+        Ok(())
+    }
+
+    pub fn register_wallet_storage_aggregation(&self,
+                                               type_: &str,
+                                               aggregate_records: WalletAggregateRecords,
+                                               free_aggregation: WalletFreeAggregation) -> IndyResult<()> {
+        trace!("register_wallet_storage_aggregation >>> type_: {:?}", type_);
+
+        let storage_types = self.storage_types.borrow();
+
+        let storage_type = storage_types
+            .get(type_)
+            .ok_or_else(|| err_msg(IndyErrorKind::UnknownWalletStorageType, format!("Unknown wallet storage type: {}", type_)))?;
+
+        storage_type.register_aggregation(aggregate_records, free_aggregation)?;
+
+        trace!("register_wallet_storage_aggregation <<<");
+        Ok(())
+    }
+
     pub fn create_wallet(&self,
                          config: &Config,
                          credentials: &Credentials,
@@ -246,10 +310,46 @@ impl WalletService {
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
         }?;
 
+        self.subscriptions.borrow_mut().retain(|_, subscription| subscription.wallet_handle != handle);
+
         trace!("close_wallet <<<");
         Ok(())
     }
 
+    pub fn rekey_wallet_prepare(&self, wallet_handle: WalletHandle, new_credentials: &RekeyCredentials) -> IndyResult<KeyDerivationData> {
+        trace!("rekey_wallet_prepare >>> wallet_handle: {:?}, new_credentials: {:?}", wallet_handle, secret!(new_credentials));
+
+        if !self.wallets.borrow().contains_key(&wallet_handle) {
+            return Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"));
+        }
+
+        let res = KeyDerivationData::from_passphrase_with_new_salt(&new_credentials.key, &new_credentials.key_derivation_method);
+
+        trace!("rekey_wallet_prepare <<<");
+        Ok(res)
+    }
+
+    // Values of records are re-encrypted before wallet keys are stored with the new master key,
+    // so failure of any step leaves the old key working
+    pub fn rekey_wallet_continue(&self, wallet_handle: WalletHandle, key: (&KeyDerivationData, &MasterKey), progress: &mut dyn FnMut(usize, usize)) -> IndyResult<()> {
+        trace!("rekey_wallet_continue >>> wallet_handle: {:?}", wallet_handle);
+
+        let (key_data, master_key) = key;
+
+        let wallets = self.wallets.borrow();
+        let wallet = wallets
+            .get(&wallet_handle)
+            .ok_or_else(|| err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))?;
+
+        wallet.reencrypt_values(progress)?;
+
+        let metadata = self._prepare_metadata(master_key, key_data, wallet.get_keys())?;
+        wallet.set_storage_metadata(&metadata)?;
+
+        trace!("rekey_wallet_continue <<<");
+        Ok(())
+    }
+
     fn _map_wallet_storage_error(err: IndyError, type_: &str, name: &str) -> IndyError {
         match err.kind() {
             IndyErrorKind::WalletItemAlreadyExists => err_msg(IndyErrorKind::WalletItemAlreadyExists, format!("Wallet item already exists with type: {}, id: {}", type_, name)),
@@ -258,12 +358,62 @@ impl WalletService {
         }
     }
 
+    // Wallet collects changes only of records of subscribed types
+    fn _track_subscribed_types(&self, wallet_handle: WalletHandle) {
+        let types = self.subscriptions.borrow().values()
+            .filter(|subscription| subscription.wallet_handle == wallet_handle)
+            .map(|subscription| subscription.type_.clone())
+            .collect();
+
+        if let Some(wallet) = self.wallets.borrow().get(&wallet_handle) {
+            wallet.track_types(types);
+        }
+    }
+
+    // Called after every operation that can write to wallet, including expiry sweeps started by
+    // reads. Callbacks are collected before calling, so they can't be affected by subscriptions changes
+    fn _notify(&self, wallet_handle: WalletHandle) {
+        let changes = match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => wallet.take_changes(),
+            None => return
+        };
+
+        for change in changes {
+            let callbacks: Vec<_> = self.subscriptions.borrow().iter()
+                .filter_map(|(handle, subscription)| subscription.change_kind(wallet_handle, &change)
+                    .map(|kind| (*handle, kind, Rc::clone(&subscription.callback))))
+                .collect();
+
+            for (handle, kind, callback) in callbacks {
+                callback(handle, kind, &change.id);
+            }
+        }
+    }
+
+    // Expiry is managed by wallet and can't be changed through tags
+    fn _check_tag_names<'a, I>(tag_names: I) -> IndyResult<()> where I: IntoIterator<Item=&'a str> {
+        if tag_names.into_iter().any(|tag_name| tag_name == EXPIRY_TAG_NAME) {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Tag name {} is reserved", EXPIRY_TAG_NAME)));
+        }
+
+        Ok(())
+    }
+
     pub fn add_record(&self, wallet_handle: WalletHandle, type_: &str, name: &str, value: &str, tags: &Tags) -> IndyResult<()> {
-        match self.wallets.borrow_mut().get_mut(&wallet_handle) {
-            Some(wallet) => wallet.add(type_, name, value, tags)
+        self.add_record_with_options(wallet_handle, type_, name, value, tags, &AddRecordOptions::default())
+    }
+
+    pub fn add_record_with_options(&self, wallet_handle: WalletHandle, type_: &str, name: &str, value: &str, tags: &Tags, options: &AddRecordOptions) -> IndyResult<()> {
+        WalletService::_check_tag_names(tags.keys().map(String::as_str))?;
+
+        let res = match self.wallets.borrow_mut().get_mut(&wallet_handle) {
+            Some(wallet) => wallet.add_with_expiry(type_, name, value, tags, options.expires_in)
                 .map_err(|err| WalletService::_map_wallet_storage_error(err, type_, name)),
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }
+        };
+
+        self._notify(wallet_handle);
+        res
     }
 
     pub fn add_indy_record<T>(&self, wallet_handle: WalletHandle, name: &str, value: &str, tags: &Tags)
@@ -281,70 +431,121 @@ impl WalletService {
     }
 
     pub fn update_record_value(&self, wallet_handle: WalletHandle, type_: &str, name: &str, value: &str) -> IndyResult<()> {
-        match self.wallets.borrow().get(&wallet_handle) {
+        self.update_record_value_with_options(wallet_handle, type_, name, value, &UpdateRecordOptions::default())
+    }
+
+    pub fn update_record_value_with_options(&self, wallet_handle: WalletHandle, type_: &str, name: &str, value: &str, options: &UpdateRecordOptions) -> IndyResult<()> {
+        let res = match self.wallets.borrow().get(&wallet_handle) {
             Some(wallet) =>
-                wallet.update(type_, name, value)
+                wallet.update_with_expiry(type_, name, value, options.expires_in)
                     .map_err(|err| WalletService::_map_wallet_storage_error(err, type_, name)),
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }
+        };
+
+        self._notify(wallet_handle);
+        res
     }
 
     pub fn update_indy_object<T>(&self, wallet_handle: WalletHandle, name: &str, object: &T) -> IndyResult<String> where T: ::serde::Serialize + Sized + NamedType {
-        let type_ = T::short_type_name();
-        match self.wallets.borrow().get(&wallet_handle) {
-            Some(wallet) => {
-                let object_json = serde_json::to_string(object)
-                    .to_indy(IndyErrorKind::InvalidState, format!("Cannot serialize {:?}", type_))?;
-                wallet.update(&self.add_prefix(type_), name, &object_json)?;
-                Ok(object_json)
-            }
+        let type_ = self.add_prefix(T::short_type_name());
+        let object_json = serde_json::to_string(object)
+            .to_indy(IndyErrorKind::InvalidState, format!("Cannot serialize {:?}", T::short_type_name()))?;
+
+        let res = match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => wallet.update(&type_, name, &object_json),
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }
+        };
+
+        self._notify(wallet_handle);
+        res.map(|_| object_json)
     }
 
     pub fn add_record_tags(&self, wallet_handle: WalletHandle, type_: &str, name: &str, tags: &Tags) -> IndyResult<()> {
-        match self.wallets.borrow_mut().get_mut(&wallet_handle) {
+        WalletService::_check_tag_names(tags.keys().map(String::as_str))?;
+
+        let res = match self.wallets.borrow_mut().get_mut(&wallet_handle) {
             Some(wallet) => wallet.add_tags(type_, name, tags)
                 .map_err(|err| WalletService::_map_wallet_storage_error(err, type_, name)),
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }
+        };
+
+        self._notify(wallet_handle);
+        res
     }
 
     pub fn update_record_tags(&self, wallet_handle: WalletHandle, type_: &str, name: &str, tags: &Tags) -> IndyResult<()> {
-        match self.wallets.borrow_mut().get_mut(&wallet_handle) {
+        WalletService::_check_tag_names(tags.keys().map(String::as_str))?;
+
+        let res = match self.wallets.borrow_mut().get_mut(&wallet_handle) {
             Some(wallet) => wallet.update_tags(type_, name, tags)
                 .map_err(|err| WalletService::_map_wallet_storage_error(err, type_, name)),
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }
+        };
+
+        self._notify(wallet_handle);
+        res
     }
 
     pub fn delete_record_tags(&self, wallet_handle: WalletHandle, type_: &str, name: &str, tag_names: &[&str]) -> IndyResult<()> {
-        match self.wallets.borrow().get(&wallet_handle) {
+        WalletService::_check_tag_names(tag_names.iter().cloned())?;
+
+        let res = match self.wallets.borrow().get(&wallet_handle) {
             Some(wallet) => wallet.delete_tags(type_, name, tag_names)
                 .map_err(|err| WalletService::_map_wallet_storage_error(err, type_, name)),
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }
+        };
+
+        self._notify(wallet_handle);
+        res
     }
 
     pub fn delete_record(&self, wallet_handle: WalletHandle, type_: &str, name: &str) -> IndyResult<()> {
-        match self.wallets.borrow().get(&wallet_handle) {
+        let res = match self.wallets.borrow().get(&wallet_handle) {
             Some(wallet) => wallet.delete(type_, name)
                 .map_err(|err| WalletService::_map_wallet_storage_error(err, type_, name)),
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        };
+
+        self._notify(wallet_handle);
+        res
+    }
+
+    pub fn batch_execute(&self, wallet_handle: WalletHandle, operations: &[WalletOperation]) -> IndyResult<()> {
+        for operation in operations {
+            match *operation {
+                WalletOperation::Add { ref tags, .. } |
+                WalletOperation::AddTags { ref tags, .. } |
+                WalletOperation::UpdateTags { ref tags, .. } => WalletService::_check_tag_names(tags.keys().map(String::as_str))?,
+                WalletOperation::DeleteTags { ref tag_names, .. } => WalletService::_check_tag_names(tag_names.iter().map(String::as_str))?,
+                WalletOperation::UpdateValue { .. } |
+                WalletOperation::Delete { .. } => {}
+            }
         }
+
+        let res = match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => wallet.batch(operations),
+            None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        };
+
+        self._notify(wallet_handle);
+        res
     }
 
     pub fn delete_indy_record<T>(&self, wallet_handle: WalletHandle, name: &str) -> IndyResult<()> where T: NamedType {
         self.delete_record(wallet_handle, &self.add_prefix(T::short_type_name()), name)
     }
 
+    // Expired record is deleted on read
     pub fn get_record(&self, wallet_handle: WalletHandle, type_: &str, name: &str, options_json: &str) -> IndyResult<WalletRecord> {
-        match self.wallets.borrow().get(&wallet_handle) {
+        let res = match self.wallets.borrow().get(&wallet_handle) {
             Some(wallet) =>
                 wallet.get(type_, name, options_json)
                     .map_err(|err| WalletService::_map_wallet_storage_error(err, type_, name)),
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }
+        };
+
+        self._notify(wallet_handle);
+        res
     }
 
     pub fn get_indy_record<T>(&self, wallet_handle: WalletHandle, name: &str, options_json: &str) -> IndyResult<WalletRecord> where T: NamedType {
@@ -354,10 +555,13 @@ impl WalletService {
     pub fn get_indy_record_value<T>(&self, wallet_handle: WalletHandle, name: &str, options_json: &str) -> IndyResult<String> where T: NamedType {
         let type_ = T::short_type_name();
 
-        let record: WalletRecord = match self.wallets.borrow().get(&wallet_handle) {
+        let record = match self.wallets.borrow().get(&wallet_handle) {
             Some(wallet) => wallet.get(&self.add_prefix(type_), name, options_json),
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }?;
+        };
+
+        self._notify(wallet_handle);
+        let record: WalletRecord = record?;
 
         let record_value = record.get_value()
             .ok_or_else(||err_msg(IndyErrorKind::InvalidState, format!("{} not found for id: {:?}", type_, name)))?.to_string();
@@ -383,23 +587,61 @@ impl WalletService {
     }
 
     pub fn search_records(&self, wallet_handle: WalletHandle, type_: &str, query_json: &str, options_json: &str) -> IndyResult<WalletSearch> {
-        match self.wallets.borrow().get(&wallet_handle) {
-            Some(wallet) => Ok(WalletSearch { iter: wallet.search(type_, query_json, Some(options_json))? }),
+        let res = match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => wallet.search(type_, query_json, Some(options_json)).map(|iter| WalletSearch { iter }),
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }
+        };
+
+        self._notify(wallet_handle);
+        res
     }
 
     pub fn search_indy_records<T>(&self, wallet_handle: WalletHandle, query_json: &str, options_json: &str) -> IndyResult<WalletSearch> where T: NamedType {
         self.search_records(wallet_handle, &self.add_prefix(T::short_type_name()), query_json, options_json)
     }
 
-    #[allow(dead_code)] // TODO: Should we implement getting all records or delete everywhere?
-    pub fn search_all_records(&self, _wallet_handle: WalletHandle) -> IndyResult<WalletSearch> {
-        //        match self.wallets.borrow().get(&wallet_handle) {
-        //            Some(wallet) => wallet.search_all_records(),
-        //            None => Err(IndyError::InvalidHandle(wallet_handle.to_string()))
-        //        }
-        unimplemented!()
+    pub fn search_all_records(&self, wallet_handle: WalletHandle) -> IndyResult<WalletSearch> {
+        match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => Ok(WalletSearch { iter: wallet.get_all()? }),
+            None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        }
+    }
+
+    pub fn aggregate_records(&self, wallet_handle: WalletHandle, type_: &str, query_json: &str, aggregation: &WalletAggregation) -> IndyResult<WalletAggregationResult> {
+        let res = match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => wallet.aggregate(type_, query_json, aggregation),
+            None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        };
+
+        self._notify(wallet_handle);
+        res
+    }
+
+    // Callback is called after every change of matching record made through this service,
+    // including import, sync, check repair and deletion of expired records
+    pub fn subscribe(&self, wallet_handle: WalletHandle, type_: &str, query_json: &str, callback: WalletChangeCallback) -> IndyResult<SubscriptionHandle> {
+        trace!("subscribe >>> wallet_handle: {:?}, type_: {:?}, query_json: {:?}", wallet_handle, type_, query_json);
+
+        let query = match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => wallet.parse_query(query_json)?,
+            None => return Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        };
+
+        let subscription_handle = indy_utils::next_subscription_handle();
+
+        self.subscriptions.borrow_mut().insert(subscription_handle, Subscription::new(wallet_handle, type_, query, callback));
+        self._track_subscribed_types(wallet_handle);
+
+        trace!("subscribe <<< subscription_handle: {:?}", subscription_handle);
+        Ok(subscription_handle)
+    }
+
+    pub fn unsubscribe(&self, subscription_handle: SubscriptionHandle) -> IndyResult<()> {
+        let subscription = self.subscriptions.borrow_mut().remove(&subscription_handle)
+            .ok_or_else(|| err_msg(IndyErrorKind::InvalidStructure, "Unknown wallet subscription handle"))?;
+
+        self._track_subscribed_types(subscription.wallet_handle);
+        Ok(())
     }
 
     pub fn upsert_indy_object<T>(&self, wallet_handle: WalletHandle, name: &str, object: &T) -> IndyResult<String>
@@ -412,7 +654,7 @@ impl WalletService {
     }
 
     pub fn record_exists<T>(&self, wallet_handle: WalletHandle, name: &str) -> IndyResult<bool> where T: NamedType {
-        match self.wallets.borrow().get(&wallet_handle) {
+        let res = match self.wallets.borrow().get(&wallet_handle) {
             Some(wallet) =>
                 match wallet.get(&self.add_prefix(T::short_type_name()), name, &RecordOptions::id()) {
                     Ok(_) => Ok(true),
@@ -420,7 +662,10 @@ impl WalletService {
                     Err(err) => Err(err),
                 }
             None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
-        }
+        };
+
+        self._notify(wallet_handle);
+        res
     }
 
     pub fn check(&self, handle: WalletHandle) -> IndyResult<()> {
@@ -430,6 +675,65 @@ impl WalletService {
         }
     }
 
+    pub fn check_wallet(&self, wallet_handle: WalletHandle, options: &CheckWalletOptions) -> IndyResult<WalletCheckReport> {
+        let res = match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => wallet.check(options.repair),
+            None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        };
+
+        self._notify(wallet_handle);
+        res
+    }
+
+    // Replica gets storage metadata of source wallet, so it is opened with the same credentials
+    // and shares encryption keys with source wallet that is required for sync
+    pub fn create_wallet_replica(&self, wallet_handle: WalletHandle, config: &Config, credentials: &Credentials) -> IndyResult<()> {
+        trace!("create_wallet_replica >>> wallet_handle: {:?}, config: {:?}, credentials: {:?}", wallet_handle, config, secret!(credentials));
+
+        let metadata = match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => wallet.get_storage_metadata()?,
+            None => return Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        };
+
+        let storage_types = self.storage_types.borrow();
+
+        let (storage_type, storage_config, storage_credentials) = WalletService::_get_config_and_cred_for_storage(config, credentials, &storage_types)?;
+
+        storage_type.create_storage(&config.id,
+                                    storage_config
+                                        .as_ref()
+                                        .map(String::as_str),
+                                    storage_credentials
+                                        .as_ref()
+                                        .map(String::as_str),
+                                    &metadata)?;
+
+        trace!("create_wallet_replica <<<");
+        Ok(())
+    }
+
+    pub fn sync_wallet(&self, source_wallet_handle: WalletHandle, target_wallet_handle: WalletHandle, options: &WalletSyncOptions) -> IndyResult<WalletSyncResult> {
+        trace!("sync_wallet >>> source_wallet_handle: {:?}, target_wallet_handle: {:?}, options: {:?}", source_wallet_handle, target_wallet_handle, options);
+
+        let res = {
+            let wallets = self.wallets.borrow();
+
+            let source = wallets.get(&source_wallet_handle)
+                .ok_or_else(|| err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown source wallet handle"))?;
+
+            let target = wallets.get(&target_wallet_handle)
+                .ok_or_else(|| err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown target wallet handle"))?;
+
+            source.sync_to(target, options.since)
+        };
+
+        self._notify(target_wallet_handle);
+        let res = res?;
+
+        trace!("sync_wallet <<< res: {:?}", res);
+        Ok(res)
+    }
+
     pub fn export_wallet(&self, wallet_handle: WalletHandle, export_config: &ExportConfig, version: u32, key: (&KeyDerivationData, &MasterKey)) -> IndyResult<()> {
         trace!("export_wallet >>> wallet_handle: {:?}, export_config: {:?}, version: {:?}", wallet_handle, secret!(export_config), version);
 
@@ -437,8 +741,6 @@ impl WalletService {
             return Err(err_msg(IndyErrorKind::InvalidState, "Unsupported version"));
         }
 
-        let (key_data, key) = key;
-
         let wallets = self.wallets.borrow();
         let wallet = wallets
             .get(&wallet_handle)
@@ -458,13 +760,42 @@ impl WalletService {
                 .create_new(true)
                 .open(export_config.path.clone())?;
 
-        let res = export_continue(wallet, &mut export_file, version, key.clone(), key_data);
+        let res = WalletService::_export_wallet(wallet, &mut export_file, &export_config.types, &export_config.query, version, key);
 
         trace!("export_wallet <<<");
 
         res
     }
 
+    pub fn export_wallet_to_stream(&self, wallet_handle: WalletHandle, writer: &mut dyn Write, export_config: &StreamExportConfig, version: u32, key: (&KeyDerivationData, &MasterKey)) -> IndyResult<()> {
+        trace!("export_wallet_to_stream >>> wallet_handle: {:?}, export_config: {:?}, version: {:?}", wallet_handle, secret!(export_config), version);
+
+        if version != 0 {
+            return Err(err_msg(IndyErrorKind::InvalidState, "Unsupported version"));
+        }
+
+        let wallets = self.wallets.borrow();
+        let wallet = wallets
+            .get(&wallet_handle)
+            .ok_or_else(|| err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))?;
+
+        let res = WalletService::_export_wallet(wallet, writer, &export_config.types, &export_config.query, version, key);
+
+        trace!("export_wallet_to_stream <<<");
+
+        res
+    }
+
+    fn _export_wallet(wallet: &Wallet, writer: &mut dyn Write, types: &Option<Vec<String>>, query: &Option<SValue>, version: u32, key: (&KeyDerivationData, &MasterKey)) -> IndyResult<()> {
+        let (key_data, key) = key;
+
+        let query = query.as_ref().map(SValue::to_string);
+
+        export_continue(wallet, writer, version, key.clone(), key_data,
+                        types.as_ref().map(Vec::as_slice),
+                        query.as_ref().map(String::as_str))
+    }
+
     pub fn import_wallet_prepare(&self,
                                  config: &Config,
                                  credentials: &Credentials,
@@ -476,7 +807,21 @@ impl WalletService {
                 .read(true)
                 .open(&export_config.path)?;
 
-        let (reader, import_key_derivation_data, nonce, chunk_size, header_bytes) = preparse_file_to_import(exported_file_to_import, &export_config.key)?;
+        self._import_wallet_prepare(Box::new(exported_file_to_import), &export_config.key, credentials)
+    }
+
+    pub fn import_wallet_from_stream_prepare(&self,
+                                             config: &Config,
+                                             credentials: &Credentials,
+                                             reader: Box<dyn Read>,
+                                             import_config: &StreamExportConfig) -> IndyResult<(WalletHandle, KeyDerivationData, KeyDerivationData)> {
+        trace!("import_wallet_from_stream_prepare >>> config: {:?}, credentials: {:?}, import_config: {:?}", config, secret!(credentials), secret!(import_config));
+
+        self._import_wallet_prepare(reader, &import_config.key, credentials)
+    }
+
+    fn _import_wallet_prepare(&self, reader: Box<dyn Read>, import_key: &str, credentials: &Credentials) -> IndyResult<(WalletHandle, KeyDerivationData, KeyDerivationData)> {
+        let (reader, import_key_derivation_data, nonce, chunk_size, header_bytes) = preparse_file_to_import(reader, import_key)?;
         let key_data = KeyDerivationData::from_passphrase_with_new_salt(&credentials.key, &credentials.key_derivation_method);
 
         let wallet_handle = indy_utils::next_wallet_handle();
@@ -518,6 +863,65 @@ impl WalletService {
         res
     }
 
+    pub fn import_into_wallet_prepare(&self, wallet_handle: WalletHandle, import_config: &ExportConfig) -> IndyResult<KeyDerivationData> {
+        trace!("import_into_wallet_prepare >>> wallet_handle: {:?}, import_config: {:?}", wallet_handle, secret!(import_config));
+
+        if !self.wallets.borrow().contains_key(&wallet_handle) {
+            return Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"));
+        }
+
+        if self.pending_for_import_into.borrow().contains_key(&wallet_handle) {
+            return Err(err_msg(IndyErrorKind::InvalidState, "Import into this wallet is already in progress"));
+        }
+
+        let exported_file_to_import =
+            fs::OpenOptions::new()
+                .read(true)
+                .open(&import_config.path)?;
+
+        let (reader, import_key_derivation_data, nonce, chunk_size, header_bytes) = preparse_file_to_import(Box::new(exported_file_to_import) as Box<dyn Read>, &import_config.key)?;
+
+        self.pending_for_import_into.borrow_mut().insert(wallet_handle, (reader, nonce, chunk_size, header_bytes));
+
+        trace!("import_into_wallet_prepare <<<");
+        Ok(import_key_derivation_data)
+    }
+
+    pub fn import_into_wallet_continue(&self, wallet_handle: WalletHandle, import_key: IndyResult<MasterKey>) -> IndyResult<()> {
+        trace!("import_into_wallet_continue >>> wallet_handle: {:?}", wallet_handle);
+
+        let (reader, nonce, chunk_size, header_bytes) = self.pending_for_import_into.borrow_mut().remove(&wallet_handle)
+            .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "Import data not found"))?;
+
+        let import_key = import_key?;
+
+        let res = match self.wallets.borrow().get(&wallet_handle) {
+            Some(wallet) => finish_import(wallet, reader, import_key, nonce, chunk_size, header_bytes),
+            None => Err(err_msg(IndyErrorKind::InvalidWalletHandle, "Unknown wallet handle"))
+        };
+
+        self._notify(wallet_handle);
+        res?;
+
+        trace!("import_into_wallet_continue <<<");
+        Ok(())
+    }
+
+    // Doesn't touch opened wallets, so it is safe to call outside of the command thread
+    pub fn inspect_export(export_config: &ExportConfig) -> IndyResult<ExportSummary> {
+        trace!("inspect_export >>> export_config: {:?}", secret!(export_config));
+
+        let exported_file =
+            fs::OpenOptions::new()
+                .read(true)
+                .open(&export_config.path)?;
+
+        let res = inspect_export(exported_file, &export_config.key);
+
+        trace!("inspect_export <<< res: {:?}", res);
+        res
+    }
+
     fn _get_config_and_cred_for_storage<'a>(config: &Config, credentials: &Credentials, storage_types: &'a HashMap<String, Box<dyn WalletStorageType>>) -> IndyResult<(&'a Box<dyn WalletStorageType>, Option<String>, Option<String>)> {
         let storage_type = {
             let storage_type = config.storage_type
@@ -712,16 +1116,115 @@ impl RecordOptions {
 
         serde_json::to_string(&options).unwrap()
     }
-}
 
-impl Default for RecordOptions {
-    fn default() -> RecordOptions {
-        RecordOptions {
+    pub fn id_tags() -> String {
+        let options = RecordOptions {
             retrieve_type: false,
-            retrieve_value: true,
-            retrieve_tags: false,
-        }
-    }
+            retrieve_value: false,
+            retrieve_tags: true,
+        };
+
+        serde_json::to_string(&options).unwrap()
+    }
+
+    pub fn id_value_tags() -> String {
+        let options = RecordOptions {
+            retrieve_type: false,
+            retrieve_value: true,
+            retrieve_tags: true,
+        };
+
+        serde_json::to_string(&options).unwrap()
+    }
+
+    pub fn retrieve_type(&self) -> bool {
+        self.retrieve_type
+    }
+
+    pub fn retrieve_value(&self) -> bool {
+        self.retrieve_value
+    }
+
+    pub fn retrieve_tags(&self) -> bool {
+        self.retrieve_tags
+    }
+}
+
+impl Default for RecordOptions {
+    fn default() -> RecordOptions {
+        RecordOptions {
+            retrieve_type: false,
+            retrieve_value: true,
+            retrieve_tags: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "operation", rename_all = "camelCase")]
+pub enum WalletOperation {
+    Add {
+        #[serde(rename = "type")]
+        type_: String,
+        id: String,
+        value: String,
+        #[serde(default)]
+        tags: Tags,
+    },
+    UpdateValue {
+        #[serde(rename = "type")]
+        type_: String,
+        id: String,
+        value: String,
+    },
+    AddTags {
+        #[serde(rename = "type")]
+        type_: String,
+        id: String,
+        tags: Tags,
+    },
+    UpdateTags {
+        #[serde(rename = "type")]
+        type_: String,
+        id: String,
+        tags: Tags,
+    },
+    DeleteTags {
+        #[serde(rename = "type")]
+        type_: String,
+        id: String,
+        #[serde(rename = "tagNames")]
+        tag_names: Vec<String>,
+    },
+    Delete {
+        #[serde(rename = "type")]
+        type_: String,
+        id: String,
+    },
+}
+
+impl WalletOperation {
+    pub fn get_type(&self) -> &str {
+        match *self {
+            WalletOperation::Add { ref type_, .. } |
+            WalletOperation::UpdateValue { ref type_, .. } |
+            WalletOperation::AddTags { ref type_, .. } |
+            WalletOperation::UpdateTags { ref type_, .. } |
+            WalletOperation::DeleteTags { ref type_, .. } |
+            WalletOperation::Delete { ref type_, .. } => type_
+        }
+    }
+
+    pub fn get_id(&self) -> &str {
+        match *self {
+            WalletOperation::Add { ref id, .. } |
+            WalletOperation::UpdateValue { ref id, .. } |
+            WalletOperation::AddTags { ref id, .. } |
+            WalletOperation::UpdateTags { ref id, .. } |
+            WalletOperation::DeleteTags { ref id, .. } |
+            WalletOperation::Delete { ref id, .. } => id
+        }
+    }
 }
 
 pub struct WalletSearch {
@@ -751,6 +1254,15 @@ pub struct SearchOptions {
     retrieve_value: bool,
     #[serde(default = "default_false")]
     retrieve_tags: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sort_by: Option<Vec<SortBy>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skip: Option<usize>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<usize>,
 }
 
 impl SearchOptions {
@@ -761,10 +1273,46 @@ impl SearchOptions {
             retrieve_type: true,
             retrieve_value: true,
             retrieve_tags: false,
+            sort_by: None,
+            skip: None,
+            limit: None,
         };
 
         serde_json::to_string(&options).unwrap()
     }
+
+    // Accessors for storages implemented outside of this crate that parse options json themselves
+    pub fn retrieve_records(&self) -> bool {
+        self.retrieve_records
+    }
+
+    pub fn retrieve_total_count(&self) -> bool {
+        self.retrieve_total_count
+    }
+
+    pub fn retrieve_type(&self) -> bool {
+        self.retrieve_type
+    }
+
+    pub fn retrieve_value(&self) -> bool {
+        self.retrieve_value
+    }
+
+    pub fn retrieve_tags(&self) -> bool {
+        self.retrieve_tags
+    }
+
+    pub fn sort_by(&self) -> Option<&[SortBy]> {
+        self.sort_by.as_ref().map(Vec::as_slice)
+    }
+
+    pub fn skip(&self) -> Option<usize> {
+        self.skip
+    }
+
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
 }
 
 impl Default for SearchOptions {
@@ -775,10 +1323,45 @@ impl Default for SearchOptions {
             retrieve_type: false,
             retrieve_value: true,
             retrieve_tags: false,
+            sort_by: None,
+            skip: None,
+            limit: None,
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SortBy {
+    tag_name: String,
+    #[serde(default)]
+    order: SortOrder,
+}
+
+impl SortBy {
+    // Tag name as it is given in search options: plain tags are prefixed by "~"
+    pub fn tag_name(&self) -> &str {
+        &self.tag_name
+    }
+
+    pub fn order(&self) -> SortOrder {
+        self.order
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl Default for SortOrder {
+    fn default() -> SortOrder {
+        SortOrder::Asc
+    }
+}
+
 #[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
@@ -792,10 +1375,11 @@ mod tests {
     use std::collections::HashMap;
     use std::fs;
     use std::path::Path;
+    use std::sync::{Arc, Mutex};
 
-    use indy_api_types::INVALID_WALLET_HANDLE;
+    use indy_api_types::{ErrorCode, INVALID_WALLET_HANDLE};
 
-    use indy_api_types::domain::wallet::KeyDerivationMethod;
+    use indy_api_types::domain::wallet::{KeyDerivationMethod, WalletCheckIssueKind};
     use indy_utils::environment;
     use indy_utils::inmem_wallet::InmemWallet;
     use indy_utils::test;
@@ -840,7 +1424,7 @@ mod tests {
                     .read(true)
                     .open(&export_config.path)?;
 
-            let (reader, import_key_derivation_data, nonce, chunk_size, header_bytes) = preparse_file_to_import(exported_file_to_import, &export_config.key)?;
+            let (reader, import_key_derivation_data, nonce, chunk_size, header_bytes) = preparse_file_to_import(Box::new(exported_file_to_import) as Box<dyn Read>, &export_config.key)?;
             let key_data = KeyDerivationData::from_passphrase_with_new_salt(&credentials.key, &credentials.key_derivation_method);
 
             let wallet_handle = next_wallet_handle();
@@ -864,6 +1448,12 @@ mod tests {
 
             self.delete_wallet_continue(config, credentials, &metadata, &master_key)
         }
+
+        fn rekey_wallet(&self, wallet_handle: WalletHandle, new_credentials: &RekeyCredentials) -> IndyResult<()> {
+            let key_data = self.rekey_wallet_prepare(wallet_handle, new_credentials)?;
+            let master_key = key_data.calc_master_key()?;
+            self.rekey_wallet_continue(wallet_handle, (&key_data, &master_key), &mut |_, _| ())
+        }
     }
 
     #[test]
@@ -881,6 +1471,39 @@ mod tests {
         _cleanup("wallet_service_register_type_works");
     }
 
+    #[test]
+    fn wallet_service_register_native_storage_works() {
+        let config = Config {
+            id: "wallet_service_register_native_storage_works".to_string(),
+            storage_type: Some("native_inmem".to_string()),
+            storage_config: None,
+        };
+
+        let wallet_service = WalletService::new();
+        wallet_service.register_native_wallet_storage("native_inmem", Box::new(InmemStorageType::new())).unwrap();
+
+        wallet_service.create_wallet(&config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+        let wallet_handle = wallet_service.open_wallet(&config, &RAW_CREDENTIAL).unwrap();
+
+        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+        let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(false, true, false)).unwrap();
+        assert_eq!(record.get_value().unwrap(), "value1");
+
+        wallet_service.close_wallet(wallet_handle).unwrap();
+        wallet_service.delete_wallet(&config, &RAW_CREDENTIAL).unwrap();
+    }
+
+    #[test]
+    fn wallet_service_register_native_storage_works_for_already_registered() {
+        let wallet_service = WalletService::new();
+
+        let res = wallet_service.register_native_wallet_storage("default", Box::new(InmemStorageType::new()));
+        assert_kind!(IndyErrorKind::WalletStorageTypeAlreadyRegistered, res);
+
+        let res = wallet_service.register_native_wallet_storage("inmem", Box::new(InmemStorageType::new()));
+        assert_kind!(IndyErrorKind::WalletStorageTypeAlreadyRegistered, res);
+    }
+
     #[test]
     fn wallet_service_create_wallet_works() {
         test::cleanup_wallet("wallet_service_create_wallet_works");
@@ -1283,6 +1906,394 @@ mod tests {
         wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
     }
 
+    #[test]
+    fn wallet_service_add_record_works_for_inmem() {
+        let config = _config_builtin_inmem("wallet_service_add_record_works_for_inmem");
+        let wallet_service = WalletService::new();
+
+        wallet_service.create_wallet(&config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+        let wallet_handle = wallet_service.open_wallet(&config, &RAW_CREDENTIAL).unwrap();
+
+        let tags = serde_json::from_str(r#"{"tag_name_1":"tag_value_1", "~tag_name_2":"tag_value_2"}"#).unwrap();
+        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &tags).unwrap();
+        wallet_service.close_wallet(wallet_handle).unwrap();
+
+        let wallet_handle = wallet_service.open_wallet(&config, &RAW_CREDENTIAL).unwrap();
+        let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(false, true, true)).unwrap();
+        assert_eq!(record.get_value().unwrap(), "value1");
+        assert_eq!(record.get_tags().unwrap(), &tags);
+
+        let mut search = wallet_service.search_records(wallet_handle, "type", r#"{"tag_name_1": "tag_value_1", "~tag_name_2": {"$like": "tag%"}}"#, &_fetch_options(false, true, false)).unwrap();
+        assert_eq!(search.fetch_next_record().unwrap().unwrap().get_id(), "key1");
+        assert!(search.fetch_next_record().unwrap().is_none());
+
+        wallet_service.close_wallet(wallet_handle).unwrap();
+        wallet_service.delete_wallet(&config, &RAW_CREDENTIAL).unwrap();
+
+        let res = wallet_service.open_wallet(&config, &RAW_CREDENTIAL);
+        assert_kind!(IndyErrorKind::WalletNotFound, res);
+    }
+
+
+    #[test]
+    fn wallet_service_check_wallet_works() {
+        test::cleanup_wallet("wallet_service_check_wallet_works");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_check_wallet_works"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_check_wallet_works"), &RAW_CREDENTIAL).unwrap();
+
+            let tags = serde_json::from_str(r#"{"tag_name_1":"tag_value_1", "~tag_name_2":"tag_value_2"}"#).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &tags).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new()).unwrap();
+
+            let report = wallet_service.check_wallet(wallet_handle, &CheckWalletOptions::default()).unwrap();
+            assert_eq!(report.records_count, 2);
+            assert!(report.issues.is_empty());
+        }
+        test::cleanup_wallet("wallet_service_check_wallet_works");
+    }
+
+    #[test]
+    fn wallet_service_check_wallet_works_for_corrupted_value() {
+        test::cleanup_wallet("wallet_service_check_wallet_works_for_corrupted_value");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_check_wallet_works_for_corrupted_value"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_check_wallet_works_for_corrupted_value"), &RAW_CREDENTIAL).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+
+            _db_connection("wallet_service_check_wallet_works_for_corrupted_value")
+                .execute("UPDATE items SET value = X'0102'", rusqlite::NO_PARAMS)
+                .unwrap();
+
+            let report = wallet_service.check_wallet(wallet_handle, &CheckWalletOptions::default()).unwrap();
+            assert_eq!(report.records_count, 1);
+            assert_eq!(report.issues.len(), 1);
+
+            let issue = &report.issues[0];
+            assert_eq!(issue.kind, WalletCheckIssueKind::InvalidValue);
+            assert_eq!(issue.type_, Some("type".to_string()));
+            assert_eq!(issue.id, Some("key1".to_string()));
+            assert!(!issue.repaired);
+
+            let report = wallet_service.check_wallet(wallet_handle, &CheckWalletOptions { repair: true }).unwrap();
+            assert_eq!(report.issues.len(), 1);
+            assert!(report.issues[0].repaired);
+
+            let report = wallet_service.check_wallet(wallet_handle, &CheckWalletOptions::default()).unwrap();
+            assert_eq!(report.records_count, 0);
+            assert!(report.issues.is_empty());
+        }
+        test::cleanup_wallet("wallet_service_check_wallet_works_for_corrupted_value");
+    }
+
+    #[test]
+    fn wallet_service_check_wallet_returns_error_if_wrong_handle() {
+        let wallet_service = WalletService::new();
+
+        let res = wallet_service.check_wallet(INVALID_WALLET_HANDLE, &CheckWalletOptions::default());
+        assert_kind!(IndyErrorKind::InvalidWalletHandle, res);
+    }
+
+    #[test]
+    fn wallet_service_sync_wallet_works() {
+        test::cleanup_wallet("wallet_service_sync_wallet_works");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_sync_wallet_works"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_sync_wallet_works"), &RAW_CREDENTIAL).unwrap();
+
+            let tags = serde_json::from_str(r#"{"tag_name_1":"tag_value_1", "~tag_name_2":"tag_value_2"}"#).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &tags).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new()).unwrap();
+
+            let replica_config = _config_builtin_inmem("wallet_service_sync_wallet_works_replica");
+            wallet_service.create_wallet_replica(wallet_handle, &replica_config, &RAW_CREDENTIAL).unwrap();
+            let replica_handle = wallet_service.open_wallet(&replica_config, &RAW_CREDENTIAL).unwrap();
+
+            let res = wallet_service.sync_wallet(wallet_handle, replica_handle, &WalletSyncOptions::default()).unwrap();
+            assert_eq!(res.records_count, 2);
+
+            let record = wallet_service.get_record(replica_handle, "type", "key1", &_fetch_options(false, true, true)).unwrap();
+            assert_eq!(record.get_value().unwrap(), "value1");
+            assert_eq!(record.get_tags().unwrap(), &tags);
+
+            wallet_service.update_record_value(wallet_handle, "type", "key2", "value3").unwrap();
+
+            let res = wallet_service.sync_wallet(wallet_handle, replica_handle, &WalletSyncOptions { since: Some(res.marker) }).unwrap();
+            assert_eq!(res.records_count, 1);
+
+            let record = wallet_service.get_record(replica_handle, "type", "key2", &_fetch_options(false, true, false)).unwrap();
+            assert_eq!(record.get_value().unwrap(), "value3");
+
+            let res = wallet_service.sync_wallet(wallet_handle, replica_handle, &WalletSyncOptions { since: Some(res.marker) }).unwrap();
+            assert_eq!(res.records_count, 0);
+            assert_eq!(res.deleted_count, 0);
+
+            let res = wallet_service.sync_wallet(wallet_handle, replica_handle, &WalletSyncOptions::default()).unwrap();
+            assert_eq!(res.records_count, 0);
+
+            wallet_service.delete_record(wallet_handle, "type", "key1").unwrap();
+
+            let res = wallet_service.sync_wallet(wallet_handle, replica_handle, &WalletSyncOptions { since: Some(res.marker) }).unwrap();
+            assert_eq!(res.records_count, 0);
+            assert_eq!(res.deleted_count, 1);
+
+            let res = wallet_service.get_record(replica_handle, "type", "key1", &_fetch_options(false, true, false));
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        test::cleanup_wallet("wallet_service_sync_wallet_works");
+    }
+
+    #[test]
+    fn wallet_service_sync_wallet_works_for_expired_record() {
+        test::cleanup_wallet("wallet_service_sync_wallet_works_for_expired_record");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_sync_wallet_works_for_expired_record"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_sync_wallet_works_for_expired_record"), &RAW_CREDENTIAL).unwrap();
+
+            let replica_config = _config_builtin_inmem("wallet_service_sync_wallet_works_for_expired_record_replica");
+            wallet_service.create_wallet_replica(wallet_handle, &replica_config, &RAW_CREDENTIAL).unwrap();
+            let replica_handle = wallet_service.open_wallet(&replica_config, &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record_with_options(wallet_handle, "type", "key1", "value1", &HashMap::new(), &AddRecordOptions { expires_in: Some(0) }).unwrap();
+            wallet_service.add_record(replica_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+
+            let res = wallet_service.sync_wallet(wallet_handle, replica_handle, &WalletSyncOptions::default()).unwrap();
+            assert_eq!(res.records_count, 0);
+            assert_eq!(res.deleted_count, 1);
+
+            let res = wallet_service.get_record(replica_handle, "type", "key1", &_fetch_options(false, true, false));
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        test::cleanup_wallet("wallet_service_sync_wallet_works_for_expired_record");
+    }
+
+    #[test]
+    fn wallet_service_subscribe_works() {
+        test::cleanup_wallet("wallet_service_subscribe_works");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_subscribe_works"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_subscribe_works"), &RAW_CREDENTIAL).unwrap();
+
+            let changes = Arc::new(Mutex::new(Vec::new()));
+            let subscription_handle = {
+                let changes = changes.clone();
+                wallet_service.subscribe(wallet_handle, "type", r#"{"~state": "active"}"#,
+                                         Box::new(move |handle, change, id| changes.lock().unwrap().push((handle, change, id.to_string())))).unwrap()
+            };
+
+            let active = serde_json::from_str(r#"{"~state": "active"}"#).unwrap();
+            let revoked = serde_json::from_str(r#"{"~state": "revoked"}"#).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &active).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key2", "value2", &revoked).unwrap();
+            wallet_service.add_record(wallet_handle, "other_type", "key3", "value3", &active).unwrap();
+            wallet_service.update_record_value(wallet_handle, "type", "key1", "value4").unwrap();
+            wallet_service.update_record_tags(wallet_handle, "type", "key2", &active).unwrap();
+            wallet_service.delete_record(wallet_handle, "type", "key1").unwrap();
+
+            assert_eq!(*changes.lock().unwrap(), vec![
+                (subscription_handle, WalletChange::Add, "key1".to_string()),
+                (subscription_handle, WalletChange::Update, "key1".to_string()),
+                (subscription_handle, WalletChange::Update, "key2".to_string()),
+                (subscription_handle, WalletChange::Delete, "key1".to_string()),
+            ]);
+
+            wallet_service.unsubscribe(subscription_handle).unwrap();
+            wallet_service.delete_record(wallet_handle, "type", "key2").unwrap();
+            assert_eq!(changes.lock().unwrap().len(), 4);
+
+            let res = wallet_service.unsubscribe(subscription_handle);
+            assert_kind!(IndyErrorKind::InvalidStructure, res);
+        }
+        test::cleanup_wallet("wallet_service_subscribe_works");
+    }
+
+    #[test]
+    fn wallet_service_subscribe_works_for_batch() {
+        test::cleanup_wallet("wallet_service_subscribe_works_for_batch");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_subscribe_works_for_batch"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_subscribe_works_for_batch"), &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+
+            let changes = Arc::new(Mutex::new(Vec::new()));
+            {
+                let changes = changes.clone();
+                wallet_service.subscribe(wallet_handle, "type", "{}",
+                                         Box::new(move |_, change, id| changes.lock().unwrap().push((change, id.to_string())))).unwrap();
+            }
+
+            let operations: Vec<WalletOperation> = serde_json::from_value(json!([
+                {"operation": "add", "type": "type", "id": "key2", "value": "value2"},
+                {"operation": "updateValue", "type": "type", "id": "key2", "value": "value3"},
+                {"operation": "delete", "type": "type", "id": "key1"},
+            ])).unwrap();
+            wallet_service.batch_execute(wallet_handle, &operations).unwrap();
+
+            assert_eq!(*changes.lock().unwrap(), vec![
+                (WalletChange::Add, "key2".to_string()),
+                (WalletChange::Update, "key2".to_string()),
+                (WalletChange::Delete, "key1".to_string()),
+            ]);
+        }
+        test::cleanup_wallet("wallet_service_subscribe_works_for_batch");
+    }
+
+    #[test]
+    fn wallet_service_subscribe_works_for_record_leaving_filter() {
+        test::cleanup_wallet("wallet_service_subscribe_works_for_record_leaving_filter");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_subscribe_works_for_record_leaving_filter"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_subscribe_works_for_record_leaving_filter"), &RAW_CREDENTIAL).unwrap();
+
+            let changes = Arc::new(Mutex::new(Vec::new()));
+            {
+                let changes = changes.clone();
+                wallet_service.subscribe(wallet_handle, "type", r#"{"~state": "active"}"#,
+                                         Box::new(move |_, change, id| changes.lock().unwrap().push((change, id.to_string())))).unwrap();
+            }
+
+            let active = serde_json::from_str(r#"{"~state": "active"}"#).unwrap();
+            let revoked = serde_json::from_str(r#"{"~state": "revoked"}"#).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &active).unwrap();
+            wallet_service.update_record_tags(wallet_handle, "type", "key1", &revoked).unwrap();
+            wallet_service.update_record_value(wallet_handle, "type", "key1", "value2").unwrap();
+            wallet_service.delete_record(wallet_handle, "type", "key1").unwrap();
+
+            assert_eq!(*changes.lock().unwrap(), vec![
+                (WalletChange::Add, "key1".to_string()),
+                (WalletChange::Remove, "key1".to_string()),
+            ]);
+        }
+        test::cleanup_wallet("wallet_service_subscribe_works_for_record_leaving_filter");
+    }
+
+    #[test]
+    fn wallet_service_subscribe_works_for_expired_record() {
+        test::cleanup_wallet("wallet_service_subscribe_works_for_expired_record");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_subscribe_works_for_expired_record"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_subscribe_works_for_expired_record"), &RAW_CREDENTIAL).unwrap();
+
+            let changes = Arc::new(Mutex::new(Vec::new()));
+            {
+                let changes = changes.clone();
+                wallet_service.subscribe(wallet_handle, "type", "{}",
+                                         Box::new(move |_, change, id| changes.lock().unwrap().push((change, id.to_string())))).unwrap();
+            }
+
+            wallet_service.add_record_with_options(wallet_handle, "type", "key1", "value1", &HashMap::new(), &AddRecordOptions { expires_in: Some(0) }).unwrap();
+            wallet_service.search_records(wallet_handle, "type", "{}", "{}").unwrap();
+
+            assert_eq!(*changes.lock().unwrap(), vec![
+                (WalletChange::Add, "key1".to_string()),
+                (WalletChange::Delete, "key1".to_string()),
+            ]);
+        }
+        test::cleanup_wallet("wallet_service_subscribe_works_for_expired_record");
+    }
+
+    #[test]
+    fn wallet_service_subscribe_works_for_sync() {
+        test::cleanup_wallet("wallet_service_subscribe_works_for_sync");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_subscribe_works_for_sync"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_subscribe_works_for_sync"), &RAW_CREDENTIAL).unwrap();
+
+            let replica_config = _config_builtin_inmem("wallet_service_subscribe_works_for_sync_replica");
+            wallet_service.create_wallet_replica(wallet_handle, &replica_config, &RAW_CREDENTIAL).unwrap();
+            let replica_handle = wallet_service.open_wallet(&replica_config, &RAW_CREDENTIAL).unwrap();
+
+            let changes = Arc::new(Mutex::new(Vec::new()));
+            {
+                let changes = changes.clone();
+                wallet_service.subscribe(replica_handle, "type", "{}",
+                                         Box::new(move |_, change, id| changes.lock().unwrap().push((change, id.to_string())))).unwrap();
+            }
+
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.sync_wallet(wallet_handle, replica_handle, &WalletSyncOptions::default()).unwrap();
+
+            wallet_service.update_record_value(wallet_handle, "type", "key1", "value2").unwrap();
+            wallet_service.sync_wallet(wallet_handle, replica_handle, &WalletSyncOptions::default()).unwrap();
+
+            wallet_service.delete_record(wallet_handle, "type", "key1").unwrap();
+            wallet_service.sync_wallet(wallet_handle, replica_handle, &WalletSyncOptions::default()).unwrap();
+
+            assert_eq!(*changes.lock().unwrap(), vec![
+                (WalletChange::Add, "key1".to_string()),
+                (WalletChange::Update, "key1".to_string()),
+                (WalletChange::Delete, "key1".to_string()),
+            ]);
+        }
+        test::cleanup_wallet("wallet_service_subscribe_works_for_sync");
+    }
+
+    #[test]
+    fn wallet_service_subscribe_works_for_closed_wallet() {
+        test::cleanup_wallet("wallet_service_subscribe_works_for_closed_wallet");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_subscribe_works_for_closed_wallet"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_subscribe_works_for_closed_wallet"), &RAW_CREDENTIAL).unwrap();
+
+            let subscription_handle = wallet_service.subscribe(wallet_handle, "type", "{}", Box::new(|_, _, _| ())).unwrap();
+            wallet_service.close_wallet(wallet_handle).unwrap();
+
+            let res = wallet_service.unsubscribe(subscription_handle);
+            assert_kind!(IndyErrorKind::InvalidStructure, res);
+
+            let res = wallet_service.subscribe(wallet_handle, "type", "{}", Box::new(|_, _, _| ()));
+            assert_kind!(IndyErrorKind::InvalidWalletHandle, res);
+        }
+        test::cleanup_wallet("wallet_service_subscribe_works_for_closed_wallet");
+    }
+
+    #[test]
+    fn wallet_service_sync_wallet_works_for_different_keys() {
+        test::cleanup_wallet("wallet_service_sync_wallet_works_for_different_keys");
+        test::cleanup_wallet("wallet_service_sync_wallet_works_for_different_keys_target");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_sync_wallet_works_for_different_keys"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_sync_wallet_works_for_different_keys"), &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.create_wallet(&_config("wallet_service_sync_wallet_works_for_different_keys_target"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let target_handle = wallet_service.open_wallet(&_config("wallet_service_sync_wallet_works_for_different_keys_target"), &RAW_CREDENTIAL).unwrap();
+
+            let res = wallet_service.sync_wallet(wallet_handle, target_handle, &WalletSyncOptions::default());
+            assert_kind!(IndyErrorKind::InvalidStructure, res);
+        }
+        test::cleanup_wallet("wallet_service_sync_wallet_works_for_different_keys");
+        test::cleanup_wallet("wallet_service_sync_wallet_works_for_different_keys_target");
+    }
+
+    #[test]
+    fn wallet_service_sync_wallet_returns_error_if_wrong_handle() {
+        let wallet_service = WalletService::new();
+
+        let res = wallet_service.sync_wallet(INVALID_WALLET_HANDLE, INVALID_WALLET_HANDLE, &WalletSyncOptions::default());
+        assert_kind!(IndyErrorKind::InvalidWalletHandle, res);
+    }
+
+    #[test]
+    fn wallet_service_create_wallet_replica_returns_error_if_wrong_handle() {
+        let wallet_service = WalletService::new();
+
+        let res = wallet_service.create_wallet_replica(INVALID_WALLET_HANDLE, &_config_builtin_inmem("wallet_service_create_wallet_replica_returns_error_if_wrong_handle"), &RAW_CREDENTIAL);
+        assert_kind!(IndyErrorKind::InvalidWalletHandle, res);
+    }
     #[test]
     fn wallet_service_get_record_works_for_id_only() {
         test::cleanup_wallet("wallet_service_get_record_works_for_id_only");
@@ -1670,71 +2681,395 @@ mod tests {
             wallet_service.create_wallet(&_config("wallet_service_delete_tags"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
             let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_delete_tags"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, type_, name, value, &tags).unwrap();
+            wallet_service.add_record(wallet_handle, type_, name, value, &tags).unwrap();
+
+            let tag_names = vec!["tag_name_1", "~tag_name_3"];
+            wallet_service.delete_record_tags(wallet_handle, type_, name, &tag_names).unwrap();
+
+            let item = wallet_service.get_record(wallet_handle, type_, name, &_fetch_options(true, true, true)).unwrap();
+
+            let expected_tags: Tags = serde_json::from_str(r#"{"tag_name_2":"new_tag_value_2"}"#).unwrap();
+            let retrieved_tags = item.tags.unwrap();
+            assert_eq!(expected_tags, retrieved_tags);
+        }
+        test::cleanup_wallet("wallet_service_delete_tags");
+    }
+
+
+    #[test]
+    fn wallet_service_delete_tags_for_plugged() {
+        _cleanup("wallet_service_delete_tags_for_plugged");
+        {
+            let type_ = "type";
+            let name = "name";
+            let value = "value";
+            let tags = serde_json::from_str(r#"{"tag_name_1":"tag_value_1", "tag_name_2":"new_tag_value_2", "~tag_name_3":"new_tag_value_3"}"#).unwrap();
+
+            let wallet_service = WalletService::new();
+            _register_inmem_wallet(&wallet_service);
+
+            wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, type_, name, value, &tags).unwrap();
+
+            let tag_names = vec!["tag_name_1", "~tag_name_3"];
+            wallet_service.delete_record_tags(wallet_handle, type_, name, &tag_names).unwrap();
+
+            let item = wallet_service.get_record(wallet_handle, type_, name, &_fetch_options(true, true, true)).unwrap();
+
+            let expected_tags: Tags = serde_json::from_str(r#"{"tag_name_2":"new_tag_value_2"}"#).unwrap();
+            let retrieved_tags = item.tags.unwrap();
+            assert_eq!(expected_tags, retrieved_tags);
+        }
+        _cleanup("wallet_service_delete_tags_for_plugged");
+    }
+
+    #[test]
+    fn wallet_service_search_records_works() {
+        test::cleanup_wallet("wallet_service_search_records_works");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_search_records_works"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_search_records_works"), &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type3", "key3", "value3", &HashMap::new()).unwrap();
+
+            let mut search = wallet_service.search_records(wallet_handle, "type3", "{}", &_fetch_options(true, true, true)).unwrap();
+
+            let record = search.fetch_next_record().unwrap().unwrap();
+            assert_eq!("value3", record.get_value().unwrap());
+            assert_eq!(HashMap::new(), record.get_tags().unwrap().clone());
+
+            assert!(search.fetch_next_record().unwrap().is_none());
+        }
+        test::cleanup_wallet("wallet_service_search_records_works");
+    }
+
+    #[test]
+    fn wallet_service_add_record_works_for_expiry() {
+        test::cleanup_wallet("wallet_service_add_record_works_for_expiry");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_add_record_works_for_expiry"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_add_record_works_for_expiry"), &RAW_CREDENTIAL).unwrap();
+
+            let tags: Tags = serde_json::from_value(json!({"~tag1": "value1"})).unwrap();
+
+            wallet_service.add_record_with_options(wallet_handle, "type", "key1", "value1", &tags, &AddRecordOptions { expires_in: Some(3600) }).unwrap();
+            wallet_service.add_record_with_options(wallet_handle, "type", "key2", "value2", &tags, &AddRecordOptions { expires_in: Some(0) }).unwrap();
+            wallet_service.add_record_with_options(wallet_handle, "type", "key3", "value3", &tags, &AddRecordOptions { expires_in: Some(0) }).unwrap();
+
+            let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(false, true, true)).unwrap();
+            assert_eq!(tags, record.get_tags().unwrap().clone());
+
+            let res = wallet_service.get_record(wallet_handle, "type", "key2", "{}");
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let mut search = wallet_service.search_records(wallet_handle, "type", "{}", &_fetch_options(false, true, true)).unwrap();
+            let record = search.fetch_next_record().unwrap().unwrap();
+            assert_eq!("key1", record.get_id());
+            assert_eq!(tags, record.get_tags().unwrap().clone());
+            assert!(search.fetch_next_record().unwrap().is_none());
+
+            let res = wallet_service.add_record(wallet_handle, "type", "key1", "value4", &tags);
+            assert_kind!(IndyErrorKind::WalletItemAlreadyExists, res);
+        }
+        test::cleanup_wallet("wallet_service_add_record_works_for_expiry");
+    }
+
+    #[test]
+    fn wallet_service_add_record_works_for_expired_record() {
+        test::cleanup_wallet("wallet_service_add_record_works_for_expired_record");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_add_record_works_for_expired_record"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_add_record_works_for_expired_record"), &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record_with_options(wallet_handle, "type", "key1", "value1", &HashMap::new(), &AddRecordOptions { expires_in: Some(0) }).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key1", "value2", &HashMap::new()).unwrap();
+
+            let record = wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
+            assert_eq!("value2", record.get_value().unwrap());
+        }
+        test::cleanup_wallet("wallet_service_add_record_works_for_expired_record");
+    }
+
+    #[test]
+    fn wallet_service_update_record_value_works_for_expiry() {
+        test::cleanup_wallet("wallet_service_update_record_value_works_for_expiry");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_update_record_value_works_for_expiry"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_update_record_value_works_for_expiry"), &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record_with_options(wallet_handle, "type", "key2", "value2", &HashMap::new(), &AddRecordOptions { expires_in: Some(3600) }).unwrap();
+
+            wallet_service.update_record_value_with_options(wallet_handle, "type", "key1", "value3", &UpdateRecordOptions { expires_in: Some(Some(0)) }).unwrap();
+            let res = wallet_service.get_record(wallet_handle, "type", "key1", "{}");
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            wallet_service.update_record_value_with_options(wallet_handle, "type", "key2", "value3", &UpdateRecordOptions { expires_in: Some(None) }).unwrap();
+            let mut search = wallet_service.search_all_records(wallet_handle).unwrap();
+            let record = search.fetch_next_record().unwrap().unwrap();
+            assert_eq!("key2", record.get_id());
+            assert_eq!("value3", record.get_value().unwrap());
+            assert_eq!(HashMap::new(), record.get_tags().unwrap().clone());
+        }
+        test::cleanup_wallet("wallet_service_update_record_value_works_for_expiry");
+    }
+
+    #[test]
+    fn wallet_service_update_record_tags_works_for_expiry() {
+        test::cleanup_wallet("wallet_service_update_record_tags_works_for_expiry");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_update_record_tags_works_for_expiry"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_update_record_tags_works_for_expiry"), &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record_with_options(wallet_handle, "type", "key1", "value1", &HashMap::new(), &AddRecordOptions { expires_in: Some(0) }).unwrap();
+
+            let tags: Tags = serde_json::from_value(json!({"tag1": "value1"})).unwrap();
+            wallet_service.update_record_tags(wallet_handle, "type", "key1", &tags).unwrap();
+
+            let res = wallet_service.get_record(wallet_handle, "type", "key1", "{}");
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+            let reserved_tags: Tags = vec![(EXPIRY_TAG_NAME.to_string(), "1".to_string())].into_iter().collect();
+            let res = wallet_service.add_record(wallet_handle, "type", "key2", "value2", &reserved_tags);
+            assert_kind!(IndyErrorKind::InvalidStructure, res);
+
+            let res = wallet_service.delete_record_tags(wallet_handle, "type", "key2", &[EXPIRY_TAG_NAME]);
+            assert_kind!(IndyErrorKind::InvalidStructure, res);
+        }
+        test::cleanup_wallet("wallet_service_update_record_tags_works_for_expiry");
+    }
+
+    #[test]
+    fn wallet_service_batch_execute_works() {
+        test::cleanup_wallet("wallet_service_batch_execute_works");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_batch_execute_works"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_batch_execute_works"), &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+
+            let operations: Vec<WalletOperation> = serde_json::from_value(json!([
+                {"operation": "add", "type": "type", "id": "key2", "value": "value2", "tags": {"tag1": "value1"}},
+                {"operation": "updateValue", "type": "type", "id": "key1", "value": "value3"},
+                {"operation": "addTags", "type": "type", "id": "key1", "tags": {"~tag2": "value2"}},
+                {"operation": "deleteTags", "type": "type", "id": "key2", "tagNames": ["tag1"]},
+                {"operation": "delete", "type": "type", "id": "key2"},
+            ])).unwrap();
+
+            wallet_service.batch_execute(wallet_handle, &operations).unwrap();
+
+            let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(false, true, true)).unwrap();
+            assert_eq!("value3", record.get_value().unwrap());
+            assert_eq!("value2", record.get_tags().unwrap()["~tag2"]);
+
+            let res = wallet_service.get_record(wallet_handle, "type", "key2", "{}");
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        test::cleanup_wallet("wallet_service_batch_execute_works");
+    }
+
+    #[test]
+    fn wallet_service_batch_execute_rolls_back_on_error() {
+        test::cleanup_wallet("wallet_service_batch_execute_rolls_back_on_error");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_batch_execute_rolls_back_on_error"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_batch_execute_rolls_back_on_error"), &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+
+            let operations: Vec<WalletOperation> = serde_json::from_value(json!([
+                {"operation": "add", "type": "type", "id": "key2", "value": "value2"},
+                {"operation": "updateValue", "type": "type", "id": "key1", "value": "value3"},
+                {"operation": "add", "type": "type", "id": "key1", "value": "value4"},
+            ])).unwrap();
+
+            let res = wallet_service.batch_execute(wallet_handle, &operations);
+            assert_kind!(IndyErrorKind::WalletItemAlreadyExists, res);
+
+            let record = wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
+            assert_eq!("value1", record.get_value().unwrap());
+
+            let res = wallet_service.get_record(wallet_handle, "type", "key2", "{}");
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        test::cleanup_wallet("wallet_service_batch_execute_rolls_back_on_error");
+    }
+
+    #[test]
+    fn wallet_service_batch_execute_works_for_plugged_wallet_with_transaction_hooks() {
+        _cleanup("wallet_service_batch_execute_works_for_plugged_wallet_with_transaction_hooks");
+
+        extern fn _transaction_hook(_storage_handle: i32) -> ErrorCode { ErrorCode::Success }
+
+        let wallet_service = WalletService::new();
+        _register_inmem_wallet(&wallet_service);
+        wallet_service.register_wallet_storage_transaction_hooks("plugged_inmem", _transaction_hook, _transaction_hook, _transaction_hook).unwrap();
+
+        wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+        let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
+
+        let operations: Vec<WalletOperation> = serde_json::from_value(json!([
+            {"operation": "add", "type": "type", "id": "key1", "value": "value1"},
+            {"operation": "updateValue", "type": "type", "id": "key1", "value": "value2"},
+        ])).unwrap();
+
+        wallet_service.batch_execute(wallet_handle, &operations).unwrap();
+
+        let record = wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
+        assert_eq!("value2", record.get_value().unwrap());
+    }
+
+    #[test]
+    fn wallet_service_batch_execute_fails_for_plugged_wallet_without_transaction_hooks() {
+        _cleanup("wallet_service_batch_execute_fails_for_plugged_wallet_without_transaction_hooks");
+
+        let wallet_service = WalletService::new();
+        _register_inmem_wallet(&wallet_service);
+
+        wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+        let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
+
+        let operations: Vec<WalletOperation> = serde_json::from_value(json!([
+            {"operation": "add", "type": "type", "id": "key1", "value": "value1"},
+        ])).unwrap();
+
+        let res = wallet_service.batch_execute(wallet_handle, &operations);
+        assert_kind!(IndyErrorKind::WalletStorageError, res);
+    }
+
+    #[test]
+    fn wallet_service_register_transaction_hooks_fails_for_default_storage() {
+        extern fn _transaction_hook(_storage_handle: i32) -> ErrorCode { ErrorCode::Success }
+
+        let wallet_service = WalletService::new();
+        let res = wallet_service.register_wallet_storage_transaction_hooks("default", _transaction_hook, _transaction_hook, _transaction_hook);
+        assert_kind!(IndyErrorKind::InvalidState, res);
+    }
+
+    #[test]
+    fn wallet_service_search_records_works_for_sort_and_pagination() {
+        test::cleanup_wallet("wallet_service_search_records_works_for_sort_and_pagination");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_search_records_works_for_sort_and_pagination"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_search_records_works_for_sort_and_pagination"), &RAW_CREDENTIAL).unwrap();
+
+            for (id, order) in &[("key1", "3"), ("key2", "1"), ("key3", "4"), ("key4", "2")] {
+                let tags: HashMap<String, String> = serde_json::from_value(json!({"~order": order})).unwrap();
+                wallet_service.add_record(wallet_handle, "type", id, "value", &tags).unwrap();
+            }
 
-            let tag_names = vec!["tag_name_1", "~tag_name_3"];
-            wallet_service.delete_record_tags(wallet_handle, type_, name, &tag_names).unwrap();
+            let options = json!({
+                "retrieveTotalCount": true,
+                "sortBy": [{"tagName": "~order", "order": "desc"}],
+                "skip": 1,
+                "limit": 2
+            }).to_string();
 
-            let item = wallet_service.get_record(wallet_handle, type_, name, &_fetch_options(true, true, true)).unwrap();
+            let mut search = wallet_service.search_records(wallet_handle, "type", "{}", &options).unwrap();
 
-            let expected_tags: Tags = serde_json::from_str(r#"{"tag_name_2":"new_tag_value_2"}"#).unwrap();
-            let retrieved_tags = item.tags.unwrap();
-            assert_eq!(expected_tags, retrieved_tags);
+            assert_eq!(Some(4), search.get_total_count().unwrap());
+            assert_eq!("key1", search.fetch_next_record().unwrap().unwrap().get_id());
+            assert_eq!("key4", search.fetch_next_record().unwrap().unwrap().get_id());
+            assert!(search.fetch_next_record().unwrap().is_none());
         }
-        test::cleanup_wallet("wallet_service_delete_tags");
+        test::cleanup_wallet("wallet_service_search_records_works_for_sort_and_pagination");
     }
 
-
     #[test]
-    fn wallet_service_delete_tags_for_plugged() {
-        _cleanup("wallet_service_delete_tags_for_plugged");
-        {
-            let type_ = "type";
-            let name = "name";
-            let value = "value";
-            let tags = serde_json::from_str(r#"{"tag_name_1":"tag_value_1", "tag_name_2":"new_tag_value_2", "~tag_name_3":"new_tag_value_3"}"#).unwrap();
+    fn wallet_service_search_records_works_for_plugged_wallet_with_sort_and_pagination() {
+        _cleanup("wallet_service_search_records_works_for_plugged_wallet_with_sort_and_pagination");
 
-            let wallet_service = WalletService::new();
-            _register_inmem_wallet(&wallet_service);
+        let wallet_service = WalletService::new();
+        _register_inmem_wallet(&wallet_service);
 
-            wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
-            let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
+        wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+        let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, type_, name, value, &tags).unwrap();
+        for (id, order) in &[("key1", "3"), ("key2", "1"), ("key3", "4"), ("key4", "2")] {
+            let tags: HashMap<String, String> = serde_json::from_value(json!({"~order": order})).unwrap();
+            wallet_service.add_record(wallet_handle, "type", id, "value", &tags).unwrap();
+        }
 
-            let tag_names = vec!["tag_name_1", "~tag_name_3"];
-            wallet_service.delete_record_tags(wallet_handle, type_, name, &tag_names).unwrap();
+        let options = json!({
+            "retrieveTotalCount": true,
+            "sortBy": [{"tagName": "~order", "order": "desc"}],
+            "skip": 1,
+            "limit": 2
+        }).to_string();
 
-            let item = wallet_service.get_record(wallet_handle, type_, name, &_fetch_options(true, true, true)).unwrap();
+        let mut search = wallet_service.search_records(wallet_handle, "type", "{}", &options).unwrap();
 
-            let expected_tags: Tags = serde_json::from_str(r#"{"tag_name_2":"new_tag_value_2"}"#).unwrap();
-            let retrieved_tags = item.tags.unwrap();
-            assert_eq!(expected_tags, retrieved_tags);
+        assert_eq!(Some(4), search.get_total_count().unwrap());
+
+        let record = search.fetch_next_record().unwrap().unwrap();
+        assert_eq!("key1", record.get_id());
+        assert!(record.get_tags().is_none());
+        assert_eq!("key4", search.fetch_next_record().unwrap().unwrap().get_id());
+        assert!(search.fetch_next_record().unwrap().is_none());
+    }
+
+    #[test]
+    fn wallet_service_search_records_fails_for_sort_by_encrypted_tag() {
+        test::cleanup_wallet("wallet_service_search_records_fails_for_sort_by_encrypted_tag");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_search_records_fails_for_sort_by_encrypted_tag"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_search_records_fails_for_sort_by_encrypted_tag"), &RAW_CREDENTIAL).unwrap();
+
+            let options = json!({"sortBy": [{"tagName": "order"}]}).to_string();
+
+            let res = wallet_service.search_records(wallet_handle, "type", "{}", &options);
+            assert_kind!(IndyErrorKind::WalletQueryError, res);
         }
-        _cleanup("wallet_service_delete_tags_for_plugged");
+        test::cleanup_wallet("wallet_service_search_records_fails_for_sort_by_encrypted_tag");
     }
 
     #[test]
-    fn wallet_service_search_records_works() {
-        test::cleanup_wallet("wallet_service_search_records_works");
+    fn wallet_service_search_all_records_works() {
+        test::cleanup_wallet("wallet_service_search_all_records_works");
         {
             let wallet_service = WalletService::new();
-            wallet_service.create_wallet(&_config("wallet_service_search_records_works"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
-            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_search_records_works"), &RAW_CREDENTIAL).unwrap();
+            wallet_service.create_wallet(&_config("wallet_service_search_all_records_works"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_search_all_records_works"), &RAW_CREDENTIAL).unwrap();
 
-            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
-            wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new()).unwrap();
-            wallet_service.add_record(wallet_handle, "type3", "key3", "value3", &HashMap::new()).unwrap();
+            let tags: HashMap<String, String> = serde_json::from_value(json!({"tag1": "value1", "~tag2": "value2"})).unwrap();
 
-            let mut search = wallet_service.search_records(wallet_handle, "type3", "{}", &_fetch_options(true, true, true)).unwrap();
+            wallet_service.add_record(wallet_handle, "type1", "key1", "value1", &tags).unwrap();
+            wallet_service.add_record(wallet_handle, "type2", "key2", "value2", &HashMap::new()).unwrap();
 
-            let record = search.fetch_next_record().unwrap().unwrap();
-            assert_eq!("value3", record.get_value().unwrap());
-            assert_eq!(HashMap::new(), record.get_tags().unwrap().clone());
+            let mut search = wallet_service.search_all_records(wallet_handle).unwrap();
 
-            assert!(search.fetch_next_record().unwrap().is_none());
+            let mut records = Vec::new();
+            while let Some(record) = search.fetch_next_record().unwrap() {
+                records.push(record);
+            }
+            records.sort();
+
+            assert_eq!(records, vec![
+                WalletRecord::new("key1".to_string(), Some("type1".to_string()), Some("value1".to_string()), Some(tags)),
+                WalletRecord::new("key2".to_string(), Some("type2".to_string()), Some("value2".to_string()), Some(HashMap::new())),
+            ]);
         }
-        test::cleanup_wallet("wallet_service_search_records_works");
+        test::cleanup_wallet("wallet_service_search_all_records_works");
+    }
+
+    #[test]
+    fn wallet_service_search_all_records_works_for_invalid_handle() {
+        let wallet_service = WalletService::new();
+        let res = wallet_service.search_all_records(INVALID_WALLET_HANDLE);
+        assert_kind!(IndyErrorKind::InvalidWalletHandle, res);
     }
 
     #[test]
@@ -1833,6 +3168,159 @@ mod tests {
         test::cleanup_wallet("wallet_service_key_rotation_for_rekey_interactive_method");
     }
 
+    #[test]
+    fn wallet_service_rekey_wallet_works_for_raw_method() {
+        test::cleanup_wallet("wallet_service_rekey_wallet_works_for_raw_method");
+        {
+            let config: &Config = &_config("wallet_service_rekey_wallet_works_for_raw_method");
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(config, &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+
+            wallet_service.rekey_wallet(wallet_handle, &_rekey_wallet_credentials_raw()).unwrap();
+
+            // Wallet is still usable after rekey
+            wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new()).unwrap();
+            wallet_service.close_wallet(wallet_handle).unwrap();
+
+            // Access failed for old key
+            let res = wallet_service.open_wallet(config, &RAW_CREDENTIAL);
+            assert_kind!(IndyErrorKind::WalletAccessFailed, res);
+
+            // Works ok with new key when reopening
+            let wallet_handle = wallet_service.open_wallet(config, &_credentials_for_new_key_raw()).unwrap();
+            let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(true, true, true)).unwrap();
+            assert_eq!("value1", record.get_value().unwrap());
+            let record = wallet_service.get_record(wallet_handle, "type", "key2", &_fetch_options(true, true, true)).unwrap();
+            assert_eq!("value2", record.get_value().unwrap());
+        }
+        test::cleanup_wallet("wallet_service_rekey_wallet_works_for_raw_method");
+    }
+
+    #[test]
+    fn wallet_service_rekey_wallet_works_for_progress() {
+        test::cleanup_wallet("wallet_service_rekey_wallet_works_for_progress");
+        {
+            let config: &Config = &_config("wallet_service_rekey_wallet_works_for_progress");
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(config, &RAW_CREDENTIAL).unwrap();
+
+            for i in 0..250 {
+                wallet_service.add_record(wallet_handle, "type", &format!("key{}", i), &format!("value{}", i), &HashMap::new()).unwrap();
+            }
+
+            let key_data = wallet_service.rekey_wallet_prepare(wallet_handle, &_rekey_wallet_credentials_raw()).unwrap();
+            let master_key = key_data.calc_master_key().unwrap();
+
+            let mut progress = Vec::new();
+            wallet_service.rekey_wallet_continue(wallet_handle, (&key_data, &master_key), &mut |processed, total| progress.push((processed, total))).unwrap();
+
+            // Progress is reported before the first batch and after each of 3 batches
+            assert_eq!(progress, vec![(0, 250), (100, 250), (200, 250), (250, 250)]);
+            assert!(progress.windows(2).all(|steps| steps[0].0 < steps[1].0));
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+
+            let wallet_handle = wallet_service.open_wallet(config, &_credentials_for_new_key_raw()).unwrap();
+            for i in 0..250 {
+                let record = wallet_service.get_record(wallet_handle, "type", &format!("key{}", i), &_fetch_options(false, true, false)).unwrap();
+                assert_eq!(format!("value{}", i), record.get_value().unwrap());
+            }
+        }
+        test::cleanup_wallet("wallet_service_rekey_wallet_works_for_progress");
+    }
+
+    #[test]
+    fn wallet_service_rekey_wallet_works_for_moderate_method() {
+        test::cleanup_wallet("wallet_service_rekey_wallet_works_for_moderate_method");
+        {
+            let config: &Config = &_config("wallet_service_rekey_wallet_works_for_moderate_method");
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(config, &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+
+            wallet_service.rekey_wallet(wallet_handle, &_rekey_wallet_credentials_moderate()).unwrap();
+
+            // Wallet is still usable after rekey
+            wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new()).unwrap();
+            wallet_service.close_wallet(wallet_handle).unwrap();
+
+            // Access failed for old key
+            let res = wallet_service.open_wallet(config, &RAW_CREDENTIAL);
+            assert_kind!(IndyErrorKind::WalletAccessFailed, res);
+
+            // Works ok with new key when reopening
+            let wallet_handle = wallet_service.open_wallet(config, &_credentials_for_new_key_moderate()).unwrap();
+            let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(true, true, true)).unwrap();
+            assert_eq!("value1", record.get_value().unwrap());
+            let record = wallet_service.get_record(wallet_handle, "type", "key2", &_fetch_options(true, true, true)).unwrap();
+            assert_eq!("value2", record.get_value().unwrap());
+        }
+        test::cleanup_wallet("wallet_service_rekey_wallet_works_for_moderate_method");
+    }
+
+    #[test]
+    fn wallet_service_rekey_wallet_works_for_interactive_method() {
+        test::cleanup_wallet("wallet_service_rekey_wallet_works_for_interactive_method");
+        {
+            let config: &Config = &_config("wallet_service_rekey_wallet_works_for_interactive_method");
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(config, &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+
+            wallet_service.rekey_wallet(wallet_handle, &_rekey_wallet_credentials_interactive()).unwrap();
+
+            // Wallet is still usable after rekey
+            wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new()).unwrap();
+            wallet_service.close_wallet(wallet_handle).unwrap();
+
+            // Access failed for old key
+            let res = wallet_service.open_wallet(config, &RAW_CREDENTIAL);
+            assert_kind!(IndyErrorKind::WalletAccessFailed, res);
+
+            // Works ok with new key when reopening
+            let wallet_handle = wallet_service.open_wallet(config, &_credentials_for_new_key_interactive()).unwrap();
+            let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(true, true, true)).unwrap();
+            assert_eq!("value1", record.get_value().unwrap());
+            let record = wallet_service.get_record(wallet_handle, "type", "key2", &_fetch_options(true, true, true)).unwrap();
+            assert_eq!("value2", record.get_value().unwrap());
+        }
+        test::cleanup_wallet("wallet_service_rekey_wallet_works_for_interactive_method");
+    }
+
+    #[test]
+    fn wallet_service_rekey_wallet_works_for_plugged_wallet() {
+        _cleanup("wallet_service_rekey_wallet_works_for_plugged_wallet");
+
+        let wallet_service = WalletService::new();
+        _register_inmem_wallet(&wallet_service);
+
+        wallet_service.create_wallet(&_config_inmem(), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+        let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &RAW_CREDENTIAL).unwrap();
+        wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+
+        wallet_service.rekey_wallet(wallet_handle, &_rekey_wallet_credentials_raw()).unwrap();
+        wallet_service.close_wallet(wallet_handle).unwrap();
+
+        let wallet_handle = wallet_service.open_wallet(&_config_inmem(), &_credentials_for_new_key_raw()).unwrap();
+        let record = wallet_service.get_record(wallet_handle, "type", "key1", &_fetch_options(true, true, true)).unwrap();
+        assert_eq!("value1", record.get_value().unwrap());
+    }
+
+    #[test]
+    fn wallet_service_rekey_wallet_works_for_invalid_handle() {
+        let wallet_service = WalletService::new();
+        let res = wallet_service.rekey_wallet(INVALID_WALLET_HANDLE, &_rekey_wallet_credentials_raw());
+        assert_kind!(IndyErrorKind::InvalidWalletHandle, res);
+    }
+
     #[test]
     fn wallet_service_key_rotation_for_rekey_raw_method() {
         test::cleanup_wallet("wallet_service_key_rotation_for_rekey_raw_method");
@@ -2186,6 +3674,103 @@ mod tests {
         _cleanup("wallet_service_export_import_returns_error_if_path_missing");
     }
 
+    #[test]
+    fn wallet_service_export_import_wallet_through_stream_works() {
+        test::cleanup_wallet("wallet_service_export_import_wallet_through_stream_works");
+        {
+            let wallet_service = WalletService::new();
+            let config: &Config = &_config("wallet_service_export_import_wallet_through_stream_works");
+            wallet_service.create_wallet(config, &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(config, &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+
+            let stream_config = _stream_export_config_raw();
+            let (kdd, master_key) = _calc_stream_key(&stream_config);
+
+            let mut output: Vec<u8> = Vec::new();
+            wallet_service.export_wallet_to_stream(wallet_handle, &mut output, &stream_config, 0, (&kdd, &master_key)).unwrap();
+            assert!(!output.is_empty());
+
+            wallet_service.close_wallet(wallet_handle).unwrap();
+            wallet_service.delete_wallet(config, &RAW_CREDENTIAL).unwrap();
+
+            let (wallet_handle, key_data, import_key_data) =
+                wallet_service.import_wallet_from_stream_prepare(config, &RAW_CREDENTIAL, Box::new(::std::io::Cursor::new(output)), &stream_config).unwrap();
+
+            let import_key = import_key_data.calc_master_key().unwrap();
+            let master_key = key_data.calc_master_key().unwrap();
+            wallet_service.import_wallet_continue(wallet_handle, config, &RAW_CREDENTIAL, (import_key, master_key)).unwrap();
+
+            let wallet_handle = wallet_service.open_wallet(config, &RAW_CREDENTIAL).unwrap();
+            wallet_service.get_record(wallet_handle, "type", "key1", "{}").unwrap();
+        }
+        test::cleanup_wallet("wallet_service_export_import_wallet_through_stream_works");
+    }
+
+    #[test]
+    fn wallet_service_export_wallet_to_stream_returns_error_if_wrong_handle() {
+        let wallet_service = WalletService::new();
+
+        let stream_config = _stream_export_config_raw();
+        let (kdd, master_key) = _calc_stream_key(&stream_config);
+
+        let mut output: Vec<u8> = Vec::new();
+        let res = wallet_service.export_wallet_to_stream(INVALID_WALLET_HANDLE, &mut output, &stream_config, 0, (&kdd, &master_key));
+        assert_kind!(IndyErrorKind::InvalidWalletHandle, res);
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn wallet_service_import_wallet_from_stream_returns_error_for_truncated_stream() {
+        _cleanup("wallet_service_import_wallet_from_stream_returns_error_for_truncated_stream");
+
+        let wallet_service = WalletService::new();
+        let config: &Config = &_config("wallet_service_import_wallet_from_stream_returns_error_for_truncated_stream");
+
+        let res = wallet_service.import_wallet_from_stream_prepare(config, &RAW_CREDENTIAL, Box::new(::std::io::Cursor::new(vec![1u8, 0])), &_stream_export_config_raw());
+        assert_kind!(IndyErrorKind::InvalidStructure, res);
+
+        _cleanup("wallet_service_import_wallet_from_stream_returns_error_for_truncated_stream");
+    }
+
+    #[test]
+    fn wallet_service_inspect_export_works() {
+        test::cleanup_wallet("wallet_service_inspect_export_works");
+        let export_config = _export_config_raw("wallet_service_inspect_export_works");
+        {
+            let wallet_service = WalletService::new();
+            wallet_service.create_wallet(&_config("wallet_service_inspect_export_works"), &RAW_CREDENTIAL, (&RAW_KDD, &RAW_MASTER_KEY)).unwrap();
+            let wallet_handle = wallet_service.open_wallet(&_config("wallet_service_inspect_export_works"), &RAW_CREDENTIAL).unwrap();
+
+            wallet_service.add_record(wallet_handle, "type", "key1", "value1", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type", "key2", "value2", &HashMap::new()).unwrap();
+            wallet_service.add_record(wallet_handle, "type2", "key1", "value1", &HashMap::new()).unwrap();
+
+            let (kdd, master_key) = _export_key_raw("wallet_service_inspect_export_works");
+            let export_path = remove_exported_wallet(&export_config);
+            wallet_service.export_wallet(wallet_handle, &export_config, 0, (&kdd, &master_key)).unwrap();
+
+            let summary = WalletService::inspect_export(&export_config).unwrap();
+            assert_eq!(3, summary.records_count);
+            assert_eq!(2, summary.types["type"]);
+            assert_eq!(1, summary.types["type2"]);
+            assert_eq!(fs::metadata(&export_path).unwrap().len(), summary.size);
+            assert_match!(KeyDerivationMethod::RAW, summary.key_derivation_method);
+        }
+        let _export_path = remove_exported_wallet(&export_config);
+        test::cleanup_wallet("wallet_service_inspect_export_works");
+    }
+
+    #[test]
+    fn wallet_service_inspect_export_returns_error_if_path_missing() {
+        let export_config = _export_config_raw("wallet_service_inspect_export_returns_error_if_path_missing");
+        let _export_path = remove_exported_wallet(&export_config);
+
+        let res = WalletService::inspect_export(&export_config);
+        assert_kind!(IndyErrorKind::IOError, res);
+    }
+
     fn _fetch_options(type_: bool, value: bool, tags: bool) -> String {
         json!({
           "retrieveType": type_,
@@ -2213,6 +3798,14 @@ mod tests {
     fn _config_inmem() -> Config {
         Config {
             id: "w1".to_string(),
+            storage_type: Some("plugged_inmem".to_string()),
+            storage_config: None,
+        }
+    }
+
+    fn _config_builtin_inmem(name: &str) -> Config {
+        Config {
+            id: name.to_string(),
             storage_type: Some("inmem".to_string()),
             storage_config: None,
         }
@@ -2330,6 +3923,27 @@ mod tests {
         }
     }
 
+    fn _rekey_wallet_credentials_moderate() -> RekeyCredentials {
+        RekeyCredentials {
+            key: "my_new_key".to_string(),
+            key_derivation_method: KeyDerivationMethod::ARGON2I_MOD,
+        }
+    }
+
+    fn _rekey_wallet_credentials_interactive() -> RekeyCredentials {
+        RekeyCredentials {
+            key: "my_new_key".to_string(),
+            key_derivation_method: KeyDerivationMethod::ARGON2I_INT,
+        }
+    }
+
+    fn _rekey_wallet_credentials_raw() -> RekeyCredentials {
+        RekeyCredentials {
+            key: "7nxtSiXFvBd593Y2DCed2dYvRY1PGK9WMtxCBjLzKgbw".to_string(),
+            key_derivation_method: KeyDerivationMethod::RAW,
+        }
+    }
+
     fn _credentials_for_new_key_moderate() -> Credentials {
         Credentials {
             key: "my_new_key".to_string(),
@@ -2371,6 +3985,8 @@ mod tests {
             key: "export_key".to_string(),
             path: _export_file_path(name).to_str().unwrap().to_string(),
             key_derivation_method: KeyDerivationMethod::ARGON2I_MOD,
+            types: None,
+            query: None,
         }
     }
 
@@ -2389,6 +4005,8 @@ mod tests {
             key: "export_key".to_string(),
             path: _export_file_path(name).to_str().unwrap().to_string(),
             key_derivation_method: KeyDerivationMethod::ARGON2I_INT,
+            types: None,
+            query: None,
         }
     }
 
@@ -2401,6 +4019,8 @@ mod tests {
             key: "6nxtSiXFvBd593Y2DCed2dYvRY1PGK9WMtxCBjLzKgbw".to_string(),
             path: _export_file_path(name).to_str().unwrap().to_string(),
             key_derivation_method: KeyDerivationMethod::RAW,
+            types: None,
+            query: None,
         }
     }
 
@@ -2408,15 +4028,37 @@ mod tests {
         _calc_key(&_export_config_raw(name))
     }
 
+    fn _stream_export_config_raw() -> StreamExportConfig {
+        StreamExportConfig {
+            key: "6nxtSiXFvBd593Y2DCed2dYvRY1PGK9WMtxCBjLzKgbw".to_string(),
+            key_derivation_method: KeyDerivationMethod::RAW,
+            types: None,
+            query: None,
+        }
+    }
+
+    fn _calc_stream_key(stream_config: &StreamExportConfig) -> (KeyDerivationData, MasterKey) {
+        let kdd = KeyDerivationData::from_passphrase_with_new_salt(&stream_config.key, &stream_config.key_derivation_method);
+        let master_key = kdd.calc_master_key().unwrap();
+        (kdd, master_key)
+    }
+
     fn _cleanup(name: &str) {
         test::cleanup_storage(name);
         InmemWallet::cleanup();
     }
 
+    fn _db_connection(name: &str) -> rusqlite::Connection {
+        let mut path = environment::wallet_home_path();
+        path.push(name);
+        path.push("sqlite.db");
+        rusqlite::Connection::open(path).unwrap()
+    }
+
     fn _register_inmem_wallet(wallet_service: &WalletService) {
         wallet_service
             .register_wallet_storage(
-                "inmem",
+                "plugged_inmem",
                 InmemWallet::create,
                 InmemWallet::open,
                 InmemWallet::close,
@@ -1,9 +1,12 @@
 use indy_api_types::errors::prelude::*;
 
+use super::SearchOptions;
 use super::wallet::Keys;
 use super::language::{Operator, TargetValue, TagName};
 use super::encryption::encrypt_as_searchable;
+use indy_utils::crypto::base64;
 use indy_utils::wql::Query;
+use regex::Regex;
 
 // Performs encryption of WQL query
 // WQL query is provided as top-level Operator
@@ -11,6 +14,41 @@ pub(super) fn encrypt_query(query: Query, keys: &Keys) -> IndyResult<Operator> {
     transform(query, keys)
 }
 
+// Performs encryption of tag names used for sorting in search options
+// Only plain tags can be used for sorting as order of encrypted values is meaningless
+// Encrypted names are passed to storage in the same form as in WQL query: "~" + base64
+pub(super) fn encrypt_search_options(options: &str, keys: &Keys) -> IndyResult<String> {
+    let mut options: SearchOptions = ::serde_json::from_str(options)
+        .to_indy(IndyErrorKind::InvalidStructure, "Search options is malformed json")?;
+
+    if let Some(ref mut sort_by) = options.sort_by {
+        for sort in sort_by.iter_mut() {
+            match TagName::from(sort.tag_name.clone())? {
+                TagName::PlainTagName(ref name) => {
+                    let encrypted_name = encrypt_as_searchable(&name[..], &keys.tag_name_key, &keys.tags_hmac_key);
+                    sort.tag_name = format!("~{}", base64::encode(&encrypted_name));
+                }
+                TagName::EncryptedTagName(_) =>
+                    return Err(err_msg(IndyErrorKind::WalletQueryError, format!("Sorting is supported only by plaintext tags: {}", sort.tag_name)))
+            }
+        }
+    }
+
+    ::serde_json::to_string(&options)
+        .to_indy(IndyErrorKind::InvalidState, "Cannot serialize search options")
+}
+
+// Performs encryption of plain tag names used for grouping in aggregation
+pub(super) fn encrypt_group_by(group_by: &[String], keys: &Keys) -> IndyResult<Vec<Vec<u8>>> {
+    group_by.iter()
+        .map(|tag_name| match TagName::from(tag_name.clone())? {
+            TagName::PlainTagName(ref name) => Ok(encrypt_as_searchable(&name[..], &keys.tag_name_key, &keys.tags_hmac_key)),
+            TagName::EncryptedTagName(_) =>
+                Err(err_msg(IndyErrorKind::WalletQueryError, format!("Grouping is supported only by plaintext tags: {}", tag_name)))
+        })
+        .collect()
+}
+
 fn transform(query: Query, keys: &Keys) -> IndyResult<Operator> {
     match query {
         Query::Eq(name, value) => {
@@ -41,18 +79,21 @@ fn transform(query: Query, keys: &Keys) -> IndyResult<Operator> {
             let (encrypted_name, encrypted_value) = encrypt_name_value(name, value, keys)?;
             Ok(Operator::Like(encrypted_name, encrypted_value))
         }
+        Query::ILike(name, value) => {
+            check_plain_tag_name(&name, "$ilike")?;
+            let (encrypted_name, encrypted_value) = encrypt_name_value(name, value, keys)?;
+            Ok(Operator::ILike(encrypted_name, encrypted_value))
+        }
+        Query::Regex(name, value) => {
+            check_plain_tag_name(&name, "$regex")?;
+            Regex::new(&value)
+                .to_indy(IndyErrorKind::WalletQueryError, format!("Invalid regular expression in $regex operator: {}", value))?;
+            let (encrypted_name, encrypted_value) = encrypt_name_value(name, value, keys)?;
+            Ok(Operator::Regex(encrypted_name, encrypted_value))
+        }
+        Query::Exists(name, exists) => Ok(Operator::Exists(encrypt_name(name, keys)?, exists)),
         Query::In(name, values) => {
-            let ename = TagName::from(name.clone())?;
-            let ename = match ename {
-                TagName::EncryptedTagName(ref name) => {
-                    let encrypted_name = encrypt_as_searchable(&name[..], &keys.tag_name_key, &keys.tags_hmac_key);
-                    TagName::EncryptedTagName(encrypted_name)
-                }
-                TagName::PlainTagName(ref name) => {
-                    let encrypted_name = encrypt_as_searchable(&name[..], &keys.tag_name_key, &keys.tags_hmac_key);
-                    TagName::PlainTagName(encrypted_name)
-                }
-            };
+            let ename = encrypt_name(name.clone(), keys)?;
             let mut encrypted_values: Vec<TargetValue> = Vec::with_capacity(values.len());
 
             for value in values {
@@ -77,6 +118,29 @@ fn transform_list_operators(operators: Vec<Query>, keys: &Keys) -> IndyResult<Ve
     Ok(transformed)
 }
 
+// Pattern matching is possible only for plaintext values, encrypted values can only be compared for equality
+fn check_plain_tag_name(name: &str, operator: &str) -> IndyResult<()> {
+    match TagName::from(name.to_string())? {
+        TagName::PlainTagName(_) => Ok(()),
+        TagName::EncryptedTagName(_) =>
+            Err(err_msg(IndyErrorKind::WalletQueryError, format!("{} operator can be used only with plaintext tags (name starts with \"~\"): {}", operator, name)))
+    }
+}
+
+// Encrypts a single tag name keeping its kind
+fn encrypt_name(name: String, keys: &Keys) -> IndyResult<TagName> {
+    match TagName::from(name)? {
+        TagName::EncryptedTagName(ref name) => {
+            let encrypted_name = encrypt_as_searchable(&name[..], &keys.tag_name_key, &keys.tags_hmac_key);
+            Ok(TagName::EncryptedTagName(encrypted_name))
+        }
+        TagName::PlainTagName(ref name) => {
+            let encrypted_name = encrypt_as_searchable(&name[..], &keys.tag_name_key, &keys.tags_hmac_key);
+            Ok(TagName::PlainTagName(encrypted_name))
+        }
+    }
+}
+
 // Encrypts a single tag name, tag value pair.
 // If the tag name is EncryptedTagName enum variant, encrypts both the tag name and the tag value
 // If the tag name is PlainTagName enum variant, encrypts only the tag name
@@ -4,6 +4,7 @@ use std;
 use std::fs;
 use std::rc::Rc;
 
+use regex::Regex;
 use rusqlite;
 use serde_json;
 
@@ -11,7 +12,8 @@ use indy_api_types::errors::prelude::*;
 use crate::language;
 use indy_utils::environment;
 
-use super::{EncryptedValue, StorageIterator, StorageRecord, Tag, TagName, WalletStorage, WalletStorageType};
+use super::{EncryptedValue, StorageIterator, StorageOperation, StorageRecord, Tag, TagName, WalletStorage, WalletStorageType};
+use super::migration::{self, MigrationOptions, MigrationStep, MigrationTarget};
 use super::super::{RecordOptions, SearchOptions};
 
 use self::owning_ref::OwningHandle;
@@ -20,6 +22,10 @@ mod query;
 mod transaction;
 
 const _SQLITE_DB: &str = "sqlite.db";
+// Schema version is kept in metadata table in the row with reserved id. The id is greater than id of
+// the keys row, so older libindy versions reading bare "SELECT value FROM metadata" still get the keys.
+// Wallets created before migrations were introduced don't have this row and have version 0
+const _SCHEMA_VERSION_ID: i64 = i64::max_value();
 const _PLAIN_TAGS_QUERY: &str = "SELECT name, value from tags_plaintext where item_id = ?";
 const _ENCRYPTED_TAGS_QUERY: &str = "SELECT name, value from tags_encrypted where item_id = ?";
 const _CREATE_SCHEMA: &str = "
@@ -218,6 +224,8 @@ impl StorageIterator for SQLiteStorageIterator {
 #[derive(Deserialize, Debug)]
 struct Config {
     path: Option<String>,
+    #[serde(default)]
+    migration: MigrationOptions,
 }
 
 #[derive(Debug)]
@@ -235,7 +243,7 @@ impl SQLiteStorageType {
 
     fn _db_path(id: &str, config: Option<&Config>) -> std::path::PathBuf {
         let mut path = match config {
-            Some(Config { path: Some(ref path) }) => std::path::PathBuf::from(path),
+            Some(Config { path: Some(ref path), .. }) => std::path::PathBuf::from(path),
             _ => environment::wallet_home_path()
         };
 
@@ -349,106 +357,32 @@ impl WalletStorage for SQLiteStorage {
     ///
     fn add(&self, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> IndyResult<()> {
         let tx: transaction::Transaction = transaction::Transaction::new(&self.conn, rusqlite::TransactionBehavior::Deferred)?;
-        let res = tx.prepare_cached("INSERT INTO items (type, name, value, key) VALUES (?1, ?2, ?3, ?4)")?
-            .insert(&[&type_.to_vec(), &id.to_vec(), &value.data, &value.key]);
-
-        let id = match res {
-            Ok(entity) => entity,
-            Err(err) => return Err(IndyError::from(err))
-        };
-
-        if !tags.is_empty() {
-            let mut stmt_e = tx.prepare_cached("INSERT INTO tags_encrypted (item_id, name, value) VALUES (?1, ?2, ?3)")?;
-            let mut stmt_p = tx.prepare_cached("INSERT INTO tags_plaintext (item_id, name, value) VALUES (?1, ?2, ?3)")?;
-
-            for tag in tags {
-                match *tag {
-                    Tag::Encrypted(ref tag_name, ref tag_data) => stmt_e.execute(rusqlite::params![&id, tag_name, tag_data])?,
-                    Tag::PlainText(ref tag_name, ref tag_data) => stmt_p.execute(rusqlite::params![&id, tag_name, tag_data])?
-                };
-            }
-        }
-
+        _add(&tx, type_, id, value, tags)?;
         tx.commit()?;
         Ok(())
     }
 
     fn update(&self, type_: &[u8], id: &[u8], value: &EncryptedValue) -> IndyResult<()> {
-        let res = self.conn.prepare_cached("UPDATE items SET value = ?1, key = ?2 WHERE type = ?3 AND name = ?4")?
-            .execute(rusqlite::params![&value.data, &value.key, &type_.to_vec(), &id.to_vec()]);
-
-        match res {
-            Ok(1) => Ok(()),
-            Ok(0) => Err(err_msg(IndyErrorKind::WalletItemNotFound, "Item to update not found")),
-            Ok(_) => Err(err_msg(IndyErrorKind::InvalidState, "More than one row update. Seems wallet structure is inconsistent")),
-            Err(err) => Err(err.into()),
-        }
+        _update(&self.conn, type_, id, value)
     }
 
     fn add_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
         let tx: transaction::Transaction = transaction::Transaction::new(&self.conn, rusqlite::TransactionBehavior::Deferred)?;
-
-        let item_id: i64 = tx.prepare_cached("SELECT id FROM items WHERE type = ?1 AND name = ?2")?
-            .query_row(&[&type_.to_vec(), &id.to_vec()], |row| row.get(0))?;
-
-        if !tags.is_empty() {
-            let mut enc_tag_insert_stmt = tx.prepare_cached("INSERT OR REPLACE INTO tags_encrypted (item_id, name, value) VALUES (?1, ?2, ?3)")?;
-            let mut plain_tag_insert_stmt = tx.prepare_cached("INSERT OR REPLACE INTO tags_plaintext (item_id, name, value) VALUES (?1, ?2, ?3)")?;
-
-            for tag in tags {
-                match *tag {
-                    Tag::Encrypted(ref tag_name, ref tag_data) => enc_tag_insert_stmt.execute(rusqlite::params![&item_id, tag_name, tag_data])?,
-                    Tag::PlainText(ref tag_name, ref tag_data) => plain_tag_insert_stmt.execute(rusqlite::params![&item_id, tag_name, tag_data])?
-                };
-            }
-        }
+        _add_tags(&tx, type_, id, tags)?;
         tx.commit()?;
-
         Ok(())
     }
 
     fn update_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
         let tx: transaction::Transaction = transaction::Transaction::new(&self.conn, rusqlite::TransactionBehavior::Deferred)?;
-
-        let item_id: i64 = tx.prepare_cached("SELECT id FROM items WHERE type = ?1 AND name = ?2")?
-            .query_row(&[&type_.to_vec(), &id.to_vec()], |row| row.get(0))?;
-
-        tx.execute("DELETE FROM tags_encrypted WHERE item_id = ?1", &[&item_id])?;
-        tx.execute("DELETE FROM tags_plaintext WHERE item_id = ?1", &[&item_id])?;
-
-        if !tags.is_empty() {
-            let mut enc_tag_insert_stmt = tx.prepare_cached("INSERT INTO tags_encrypted (item_id, name, value) VALUES (?1, ?2, ?3)")?;
-            let mut plain_tag_insert_stmt = tx.prepare_cached("INSERT INTO tags_plaintext (item_id, name, value) VALUES (?1, ?2, ?3)")?;
-
-            for tag in tags {
-                match *tag {
-                    Tag::Encrypted(ref tag_name, ref tag_data) => enc_tag_insert_stmt.execute(rusqlite::params![&item_id, tag_name, tag_data])?,
-                    Tag::PlainText(ref tag_name, ref tag_data) => plain_tag_insert_stmt.execute(rusqlite::params![&item_id, tag_name, tag_data])?
-                };
-            }
-        }
+        _update_tags(&tx, type_, id, tags)?;
         tx.commit()?;
-
         Ok(())
     }
 
     fn delete_tags(&self, type_: &[u8], id: &[u8], tag_names: &[TagName]) -> IndyResult<()> {
-        let item_id: i64 = self.conn.prepare_cached("SELECT id FROM items WHERE type =?1 AND name = ?2")?
-            .query_row(&[&type_.to_vec(), &id.to_vec()], |row| row.get(0))?;
-
         let tx: transaction::Transaction = transaction::Transaction::new(&self.conn, rusqlite::TransactionBehavior::Deferred)?;
-        {
-            let mut enc_tag_delete_stmt = tx.prepare_cached("DELETE FROM tags_encrypted WHERE item_id = ?1 AND name = ?2")?;
-            let mut plain_tag_delete_stmt = tx.prepare_cached("DELETE FROM tags_plaintext WHERE item_id = ?1 AND name = ?2")?;
-
-            for tag_name in tag_names {
-                match *tag_name {
-                    TagName::OfEncrypted(ref tag_name) => enc_tag_delete_stmt.execute(rusqlite::params![&item_id, tag_name])?,
-                    TagName::OfPlain(ref tag_name) => plain_tag_delete_stmt.execute(rusqlite::params![&item_id, tag_name])?,
-                };
-            }
-        }
-
+        _delete_tags(&tx, type_, id, tag_names)?;
         tx.commit()?;
         Ok(())
     }
@@ -480,28 +414,55 @@ impl WalletStorage for SQLiteStorage {
     ///  * `IOError("IO error during storage operation:...")` - Failed connection or SQL query
     ///
     fn delete(&self, type_: &[u8], id: &[u8]) -> IndyResult<()> {
-        let row_count = self.conn.execute(
-            "DELETE FROM items where type = ?1 AND name = ?2",
-            &[&type_.to_vec(), &id.to_vec()],
-        )?;
+        _delete(&self.conn, type_, id)
+    }
 
-        if row_count == 1 {
-            Ok(())
-        } else {
-            Err(err_msg(IndyErrorKind::WalletItemNotFound, "Item to delete not found"))
+    ///
+    /// applies all operations inside of one SQLite transaction.
+    /// If any operation fails the transaction is rolled back and no changes are visible.
+    ///
+    fn batch(&self, operations: &[StorageOperation]) -> IndyResult<()> {
+        let tx: transaction::Transaction = transaction::Transaction::new(&self.conn, rusqlite::TransactionBehavior::Deferred)?;
+
+        for (index, operation) in operations.iter().enumerate() {
+            _execute(&tx, operation)
+                .map_err(|err| err.extend(format!("Batch operation {} failed", index)))?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    ///
+    /// applies operations of all chunks inside of one SQLite transaction.
+    /// Chunks are fetched while transaction is open, so reads made by `next` see previous chunks.
+    ///
+    fn batch_chunked(&self, next: &mut dyn FnMut() -> IndyResult<Option<Vec<StorageOperation>>>) -> IndyResult<()> {
+        let tx: transaction::Transaction = transaction::Transaction::new(&self.conn, rusqlite::TransactionBehavior::Deferred)?;
+        let mut index = 0;
+
+        while let Some(operations) = next()? {
+            for operation in operations.iter() {
+                _execute(&tx, operation)
+                    .map_err(|err| err.extend(format!("Batch operation {} failed", index)))?;
+                index += 1;
+            }
         }
+
+        tx.commit()?;
+        Ok(())
     }
 
     fn get_storage_metadata(&self) -> IndyResult<Vec<u8>> {
         self.conn.query_row(
-            "SELECT value FROM metadata",
-            rusqlite::NO_PARAMS,
+            "SELECT value FROM metadata WHERE id != ?1",
+            &[&_SCHEMA_VERSION_ID],
             |row| { row.get(0) },
         ).map_err(IndyError::from)
     }
 
     fn set_storage_metadata(&self, metadata: &[u8]) -> IndyResult<()> {
-        self.conn.execute("UPDATE metadata SET value = ?1", &[&metadata.to_vec()])?;
+        self.conn.execute("UPDATE metadata SET value = ?1 WHERE id != ?2", rusqlite::params![&metadata.to_vec(), &_SCHEMA_VERSION_ID])?;
         Ok(())
     }
 
@@ -520,6 +481,53 @@ impl WalletStorage for SQLiteStorage {
         Ok(Box::new(storage_iterator))
     }
 
+    fn count(&self) -> IndyResult<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM items",
+            rusqlite::NO_PARAMS,
+            |row| { row.get(0) },
+        )?;
+
+        Ok(count as usize)
+    }
+
+    fn get_modification_marker(&self) -> IndyResult<u64> {
+        let marker: i64 = self.conn.query_row(
+            "SELECT value FROM modification_marker",
+            rusqlite::NO_PARAMS,
+            |row| { row.get(0) },
+        )?;
+
+        Ok(marker as u64)
+    }
+
+    fn get_modified_since(&self, marker: u64) -> IndyResult<Box<dyn StorageIterator>> {
+        let statement = self._prepare_statement("SELECT id, name, value, key, type FROM items WHERE modified > ?1 ORDER BY modified;")?;
+
+        let fetch_options = RecordOptions {
+            retrieve_type: true,
+            retrieve_value: true,
+            retrieve_tags: true,
+        };
+
+        let tag_retriever = Some(TagRetriever::new_owned(self.conn.clone())?);
+        let storage_iterator = SQLiteStorageIterator::new(Some(statement), &[&(marker as i64)], fetch_options, tag_retriever, None)?;
+
+        Ok(Box::new(storage_iterator))
+    }
+
+    fn get_deleted_since(&self, marker: u64) -> IndyResult<Option<Vec<(Vec<u8>, Vec<u8>)>>> {
+        let mut stmt = self.conn.prepare_cached("SELECT type, name FROM deleted_items WHERE deleted > ?1")?;
+        let mut rows = stmt.query(&[&(marker as i64)])?;
+
+        let mut deleted = Vec::new();
+        while let Some(row) = rows.next()? {
+            deleted.push((row.get(0)?, row.get(1)?));
+        }
+
+        Ok(Some(deleted))
+    }
+
     fn search(&self, type_: &[u8], query: &language::Operator, options: Option<&str>) -> IndyResult<Box<dyn StorageIterator>> {
         let type_ = type_.to_vec(); // FIXME
 
@@ -551,7 +559,7 @@ impl WalletStorage for SQLiteStorage {
                 retrieve_type: search_options.retrieve_type,
             };
 
-            let (query_string, query_arguments) = query::wql_to_sql(&type_, query, options)?;
+            let (query_string, query_arguments) = query::wql_to_sql(&type_, query, &search_options)?;
 
             let statement = self._prepare_statement(&query_string)?;
             let tag_retriever = if fetch_options.retrieve_tags {
@@ -567,9 +575,45 @@ impl WalletStorage for SQLiteStorage {
         }
     }
 
+    fn aggregate(&self, type_: &[u8], query: &language::Operator, group_by: &[Vec<u8>]) -> IndyResult<Vec<(Vec<Option<String>>, usize)>> {
+        let type_ = type_.to_vec();
+        let (query_string, query_arguments) = query::wql_to_sql_aggregate(&type_, query, group_by)?;
+
+        let mut stmt = self.conn.prepare(&query_string)?;
+        let mut rows = stmt.query(&query_arguments)?;
+
+        let mut groups = Vec::new();
+        while let Some(row) = rows.next()? {
+            let mut values = Vec::with_capacity(group_by.len());
+            for index in 0..group_by.len() {
+                values.push(row.get(index)?);
+            }
+            let count: i64 = row.get(group_by.len())?;
+            groups.push((values, count as usize));
+        }
+
+        Ok(groups)
+    }
+
+    fn delete_expired(&self, expiry_tag_name: &[u8], now: &str) -> IndyResult<usize> {
+        let expiry_tag_name = expiry_tag_name.to_vec();
+        let now = now.to_string();
+
+        let count = self.conn.execute(
+            "DELETE FROM items WHERE id IN (SELECT item_id FROM tags_plaintext WHERE name = ?1 AND value <= ?2)",
+            &[&expiry_tag_name as &dyn rusqlite::types::ToSql, &now],
+        )?;
+
+        Ok(count)
+    }
+
     fn close(&mut self) -> IndyResult<()> {
         Ok(())
     }
+
+    fn supports_exists_query(&self) -> bool {
+        true
+    }
 }
 
 impl SQLiteStorage {
@@ -581,6 +625,317 @@ impl SQLiteStorage {
 }
 
 
+fn _add(conn: &rusqlite::Connection, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> IndyResult<()> {
+    let res = conn.prepare_cached("INSERT INTO items (type, name, value, key) VALUES (?1, ?2, ?3, ?4)")?
+        .insert(&[&type_.to_vec(), &id.to_vec(), &value.data, &value.key]);
+
+    let id = match res {
+        Ok(entity) => entity,
+        Err(err) => return Err(IndyError::from(err))
+    };
+
+    if !tags.is_empty() {
+        let mut stmt_e = conn.prepare_cached("INSERT INTO tags_encrypted (item_id, name, value) VALUES (?1, ?2, ?3)")?;
+        let mut stmt_p = conn.prepare_cached("INSERT INTO tags_plaintext (item_id, name, value) VALUES (?1, ?2, ?3)")?;
+
+        for tag in tags {
+            match *tag {
+                Tag::Encrypted(ref tag_name, ref tag_data) => stmt_e.execute(rusqlite::params![&id, tag_name, tag_data])?,
+                Tag::PlainText(ref tag_name, ref tag_data) => stmt_p.execute(rusqlite::params![&id, tag_name, tag_data])?
+            };
+        }
+    }
+
+    Ok(())
+}
+
+fn _update(conn: &rusqlite::Connection, type_: &[u8], id: &[u8], value: &EncryptedValue) -> IndyResult<()> {
+    let res = conn.prepare_cached("UPDATE items SET value = ?1, key = ?2 WHERE type = ?3 AND name = ?4")?
+        .execute(rusqlite::params![&value.data, &value.key, &type_.to_vec(), &id.to_vec()]);
+
+    match res {
+        Ok(1) => Ok(()),
+        Ok(0) => Err(err_msg(IndyErrorKind::WalletItemNotFound, "Item to update not found")),
+        Ok(_) => Err(err_msg(IndyErrorKind::InvalidState, "More than one row update. Seems wallet structure is inconsistent")),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn _add_tags(conn: &rusqlite::Connection, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+    let item_id: i64 = conn.prepare_cached("SELECT id FROM items WHERE type = ?1 AND name = ?2")?
+        .query_row(&[&type_.to_vec(), &id.to_vec()], |row| row.get(0))?;
+
+    if !tags.is_empty() {
+        let mut enc_tag_insert_stmt = conn.prepare_cached("INSERT OR REPLACE INTO tags_encrypted (item_id, name, value) VALUES (?1, ?2, ?3)")?;
+        let mut plain_tag_insert_stmt = conn.prepare_cached("INSERT OR REPLACE INTO tags_plaintext (item_id, name, value) VALUES (?1, ?2, ?3)")?;
+
+        for tag in tags {
+            match *tag {
+                Tag::Encrypted(ref tag_name, ref tag_data) => enc_tag_insert_stmt.execute(rusqlite::params![&item_id, tag_name, tag_data])?,
+                Tag::PlainText(ref tag_name, ref tag_data) => plain_tag_insert_stmt.execute(rusqlite::params![&item_id, tag_name, tag_data])?
+            };
+        }
+    }
+
+    Ok(())
+}
+
+fn _update_tags(conn: &rusqlite::Connection, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+    let item_id: i64 = conn.prepare_cached("SELECT id FROM items WHERE type = ?1 AND name = ?2")?
+        .query_row(&[&type_.to_vec(), &id.to_vec()], |row| row.get(0))?;
+
+    conn.execute("DELETE FROM tags_encrypted WHERE item_id = ?1", &[&item_id])?;
+    conn.execute("DELETE FROM tags_plaintext WHERE item_id = ?1", &[&item_id])?;
+
+    if !tags.is_empty() {
+        let mut enc_tag_insert_stmt = conn.prepare_cached("INSERT INTO tags_encrypted (item_id, name, value) VALUES (?1, ?2, ?3)")?;
+        let mut plain_tag_insert_stmt = conn.prepare_cached("INSERT INTO tags_plaintext (item_id, name, value) VALUES (?1, ?2, ?3)")?;
+
+        for tag in tags {
+            match *tag {
+                Tag::Encrypted(ref tag_name, ref tag_data) => enc_tag_insert_stmt.execute(rusqlite::params![&item_id, tag_name, tag_data])?,
+                Tag::PlainText(ref tag_name, ref tag_data) => plain_tag_insert_stmt.execute(rusqlite::params![&item_id, tag_name, tag_data])?
+            };
+        }
+    }
+
+    Ok(())
+}
+
+fn _delete_tags(conn: &rusqlite::Connection, type_: &[u8], id: &[u8], tag_names: &[TagName]) -> IndyResult<()> {
+    let item_id: i64 = conn.prepare_cached("SELECT id FROM items WHERE type =?1 AND name = ?2")?
+        .query_row(&[&type_.to_vec(), &id.to_vec()], |row| row.get(0))?;
+
+    let mut enc_tag_delete_stmt = conn.prepare_cached("DELETE FROM tags_encrypted WHERE item_id = ?1 AND name = ?2")?;
+    let mut plain_tag_delete_stmt = conn.prepare_cached("DELETE FROM tags_plaintext WHERE item_id = ?1 AND name = ?2")?;
+
+    for tag_name in tag_names {
+        match *tag_name {
+            TagName::OfEncrypted(ref tag_name) => enc_tag_delete_stmt.execute(rusqlite::params![&item_id, tag_name])?,
+            TagName::OfPlain(ref tag_name) => plain_tag_delete_stmt.execute(rusqlite::params![&item_id, tag_name])?,
+        };
+    }
+
+    Ok(())
+}
+
+fn _delete(conn: &rusqlite::Connection, type_: &[u8], id: &[u8]) -> IndyResult<()> {
+    let row_count = conn.execute(
+        "DELETE FROM items where type = ?1 AND name = ?2",
+        &[&type_.to_vec(), &id.to_vec()],
+    )?;
+
+    if row_count == 1 {
+        Ok(())
+    } else {
+        Err(err_msg(IndyErrorKind::WalletItemNotFound, "Item to delete not found"))
+    }
+}
+
+fn _execute(conn: &rusqlite::Connection, operation: &StorageOperation) -> IndyResult<()> {
+    match *operation {
+        StorageOperation::Add(ref type_, ref id, ref value, ref tags) => _add(conn, type_, id, value, tags),
+        StorageOperation::Update(ref type_, ref id, ref value) => _update(conn, type_, id, value),
+        StorageOperation::AddTags(ref type_, ref id, ref tags) => _add_tags(conn, type_, id, tags),
+        StorageOperation::UpdateTags(ref type_, ref id, ref tags) => _update_tags(conn, type_, id, tags),
+        StorageOperation::DeleteTags(ref type_, ref id, ref tag_names) => _delete_tags(conn, type_, id, tag_names),
+        StorageOperation::Delete(ref type_, ref id) => _delete(conn, type_, id),
+    }
+}
+
+
+// SQLite has REGEXP operator syntax but no implementation of it. Query passes the same pattern
+// for all values, so only the last compiled expression is kept
+fn _register_regexp_function(conn: &rusqlite::Connection) -> IndyResult<()> {
+    let mut cached: Option<(String, Regex)> = None;
+
+    conn.create_scalar_function("regexp", 2, true, move |ctx| {
+        let pattern = ctx.get::<String>(0)?;
+
+        let is_cached = cached.as_ref().map(|&(ref cached_pattern, _)| *cached_pattern == pattern).unwrap_or(false);
+
+        if !is_cached {
+            let regex = Regex::new(&pattern)
+                .map_err(|err| rusqlite::Error::UserFunctionError(Box::new(err)))?;
+            cached = Some((pattern, regex));
+        }
+
+        let value = ctx.get::<String>(1)?;
+        Ok(cached.as_ref().map(|&(_, ref regex)| regex.is_match(&value)).unwrap_or(false))
+    })?;
+
+    Ok(())
+}
+
+// Single row counter is incremented on every change of item or its tags and its new value
+// is stored to modified column of the item. Counter never decreases, so records changed
+// after some marker value can be found even if items with bigger markers were deleted
+const _ADD_MODIFICATION_MARKER: &str = "
+    CREATE TABLE modification_marker(
+        id INTEGER NOT NULL CHECK(id = 1),
+        value INTEGER NOT NULL,
+        PRIMARY KEY(id)
+    );
+
+    INSERT INTO modification_marker(id, value) VALUES(1, 0);
+
+    ALTER TABLE items ADD COLUMN modified INTEGER NOT NULL DEFAULT 0;
+
+    CREATE INDEX ix_items_modified ON items(modified);
+
+    CREATE TRIGGER tr_items_insert_marker AFTER INSERT ON items
+    BEGIN
+        UPDATE modification_marker SET value = value + 1;
+        UPDATE items SET modified = (SELECT value FROM modification_marker) WHERE id = NEW.id;
+    END;
+
+    CREATE TRIGGER tr_items_update_marker AFTER UPDATE OF value, key ON items
+    BEGIN
+        UPDATE modification_marker SET value = value + 1;
+        UPDATE items SET modified = (SELECT value FROM modification_marker) WHERE id = NEW.id;
+    END;
+
+    CREATE TRIGGER tr_tags_encrypted_insert_marker AFTER INSERT ON tags_encrypted
+    BEGIN
+        UPDATE modification_marker SET value = value + 1;
+        UPDATE items SET modified = (SELECT value FROM modification_marker) WHERE id = NEW.item_id;
+    END;
+
+    CREATE TRIGGER tr_tags_encrypted_update_marker AFTER UPDATE ON tags_encrypted
+    BEGIN
+        UPDATE modification_marker SET value = value + 1;
+        UPDATE items SET modified = (SELECT value FROM modification_marker) WHERE id = NEW.item_id;
+    END;
+
+    CREATE TRIGGER tr_tags_encrypted_delete_marker AFTER DELETE ON tags_encrypted
+    BEGIN
+        UPDATE modification_marker SET value = value + 1;
+        UPDATE items SET modified = (SELECT value FROM modification_marker) WHERE id = OLD.item_id;
+    END;
+
+    CREATE TRIGGER tr_tags_plaintext_insert_marker AFTER INSERT ON tags_plaintext
+    BEGIN
+        UPDATE modification_marker SET value = value + 1;
+        UPDATE items SET modified = (SELECT value FROM modification_marker) WHERE id = NEW.item_id;
+    END;
+
+    CREATE TRIGGER tr_tags_plaintext_update_marker AFTER UPDATE ON tags_plaintext
+    BEGIN
+        UPDATE modification_marker SET value = value + 1;
+        UPDATE items SET modified = (SELECT value FROM modification_marker) WHERE id = NEW.item_id;
+    END;
+
+    CREATE TRIGGER tr_tags_plaintext_delete_marker AFTER DELETE ON tags_plaintext
+    BEGIN
+        UPDATE modification_marker SET value = value + 1;
+        UPDATE items SET modified = (SELECT value FROM modification_marker) WHERE id = OLD.item_id;
+    END;
+";
+
+// Deleted items are logged with the marker value of deletion, so incremental sync finds them
+// without comparing ids of all records. Log entry is removed when item is added again
+const _ADD_DELETED_ITEMS: &str = "
+    CREATE TABLE deleted_items(
+        type NOT NULL,
+        name NOT NULL,
+        deleted INTEGER NOT NULL,
+        PRIMARY KEY(type, name)
+    );
+
+    CREATE INDEX ix_deleted_items_deleted ON deleted_items(deleted);
+
+    CREATE TRIGGER tr_items_delete_log AFTER DELETE ON items
+    BEGIN
+        UPDATE modification_marker SET value = value + 1;
+        INSERT OR REPLACE INTO deleted_items(type, name, deleted) VALUES(OLD.type, OLD.name, (SELECT value FROM modification_marker));
+    END;
+
+    CREATE TRIGGER tr_items_insert_delete_log AFTER INSERT ON items
+    BEGIN
+        DELETE FROM deleted_items WHERE type = NEW.type AND name = NEW.name;
+    END;
+";
+
+// Ordered schema migration steps. New steps must be appended with the next version,
+// applied steps must never be changed as wallets remember only the last applied version
+fn _migrations() -> Vec<MigrationStep<rusqlite::Connection>> {
+    vec![
+        MigrationStep {
+            version: 1,
+            description: "Baseline schema of wallets created before migrations were introduced",
+            apply: |_conn| Ok(()),
+        },
+        MigrationStep {
+            version: 2,
+            description: "Per-record modification marker for incremental wallet sync",
+            apply: |conn| {
+                conn.execute_batch(_ADD_MODIFICATION_MARKER)?;
+                Ok(())
+            },
+        },
+        MigrationStep {
+            version: 3,
+            description: "Log of deleted items for incremental wallet sync",
+            apply: |conn| {
+                conn.execute_batch(_ADD_DELETED_ITEMS)?;
+                Ok(())
+            },
+        },
+    ]
+}
+
+impl MigrationTarget for rusqlite::Connection {
+    fn get_schema_version(&self) -> IndyResult<u32> {
+        let res: Result<i64, rusqlite::Error> = self.query_row(
+            "SELECT value FROM metadata WHERE id = ?1",
+            &[&_SCHEMA_VERSION_ID],
+            |row| { row.get(0) },
+        );
+
+        match res {
+            Ok(version) => Ok(version as u32),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(0),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn set_schema_version(&self, version: u32) -> IndyResult<()> {
+        self.execute("INSERT OR REPLACE INTO metadata(id, value) VALUES(?1, ?2)", &[&_SCHEMA_VERSION_ID, &(version as i64)])?;
+        Ok(())
+    }
+
+    fn begin(&self) -> IndyResult<()> {
+        self.execute_batch("BEGIN EXCLUSIVE")?;
+        Ok(())
+    }
+
+    fn commit(&self) -> IndyResult<()> {
+        self.execute_batch("COMMIT")?;
+        Ok(())
+    }
+
+    fn rollback(&self) -> IndyResult<()> {
+        self.execute_batch("ROLLBACK")?;
+        Ok(())
+    }
+
+    // Copies database file to "sqlite.db.v<version>.bak" next to it.
+    // WAL content is checkpointed first so the copy is complete
+    fn backup(&self, version: u32) -> IndyResult<()> {
+        let db_path: String = self.query_row("PRAGMA database_list", rusqlite::NO_PARAMS, |row| { row.get(2) })?;
+
+        let busy: i64 = self.query_row("PRAGMA wal_checkpoint(TRUNCATE)", rusqlite::NO_PARAMS, |row| { row.get(0) })?;
+
+        if busy != 0 {
+            return Err(err_msg(IndyErrorKind::WalletStorageError, "Cannot checkpoint wallet database before backup"));
+        }
+
+        let backup_path = format!("{}.v{}.bak", db_path, version);
+        fs::copy(&db_path, &backup_path)?;
+        Ok(())
+    }
+}
+
 impl WalletStorageType for SQLiteStorageType {
     ///
     /// Deletes the SQLite database file with the provided id from the path specified in the
@@ -670,7 +1025,13 @@ impl WalletStorageType for SQLiteStorageType {
 
         match conn.execute_batch(_CREATE_SCHEMA) {
             Ok(_) => match conn.execute("INSERT OR REPLACE INTO metadata(value) VALUES(?1)", &[&metadata.to_vec()]) {
-                Ok(_) => Ok(()),
+                Ok(_) => match migration::migrate(&conn, &_migrations(), &MigrationOptions::default()) {
+                    Ok(_) => Ok(()),
+                    Err(error) => {
+                        std::fs::remove_file(db_path)?;
+                        Err(error)
+                    }
+                },
                 Err(error) => {
                     std::fs::remove_file(db_path)?;
                     Err(error.into())
@@ -737,6 +1098,17 @@ impl WalletStorageType for SQLiteStorageType {
             conn.execute("PRAGMA synchronous = FULL", rusqlite::NO_PARAMS)?;
         }
 
+        _register_regexp_function(&conn)?;
+
+        let migration_options = config.map(|config| config.migration).unwrap_or_default();
+        let migration_result = migration::migrate(&conn, &_migrations(), &migration_options)?;
+
+        if migration_result.dry_run && migration_result.from_version != migration_result.to_version {
+            return Err(err_msg(IndyErrorKind::WalletStorageError,
+                               format!("Wallet storage requires migration of schema from version {} to {}. Dry run succeeded",
+                                       migration_result.from_version, migration_result.to_version)));
+        }
+
         Ok(Box::new(SQLiteStorage { conn: Rc::new(conn) }))
     }
 }
@@ -866,6 +1238,86 @@ mod tests {
         assert_kind!(IndyErrorKind::WalletNotFound, res);
     }
 
+    #[test]
+    fn sqlite_storage_type_create_works_for_latest_schema_version() {
+        _cleanup("sqlite_storage_type_create_works_for_latest_schema_version");
+        {
+            let storage = _storage("sqlite_storage_type_create_works_for_latest_schema_version");
+            assert_eq!(storage.get_storage_metadata().unwrap(), _metadata());
+
+            let conn = _connection("sqlite_storage_type_create_works_for_latest_schema_version");
+            assert_eq!(conn.get_schema_version().unwrap(), _migrations().last().unwrap().version);
+
+            let version: i64 = conn.query_row("SELECT value FROM metadata WHERE id = ?1", &[&_SCHEMA_VERSION_ID], |row| row.get(0)).unwrap();
+            assert_eq!(version as u32, _migrations().last().unwrap().version);
+
+            // Older libindy versions read metadata with bare "SELECT value FROM metadata"
+            let metadata: Vec<u8> = conn.query_row("SELECT value FROM metadata", rusqlite::NO_PARAMS, |row| row.get(0)).unwrap();
+            assert_eq!(metadata, _metadata());
+        }
+        _cleanup("sqlite_storage_type_create_works_for_latest_schema_version");
+    }
+
+    #[test]
+    fn sqlite_storage_type_open_works_for_legacy_wallet() {
+        _cleanup("sqlite_storage_type_open_works_for_legacy_wallet");
+        {
+            _create_legacy_storage("sqlite_storage_type_open_works_for_legacy_wallet");
+
+            let storage_type = SQLiteStorageType::new();
+            let config = json!({"migration": {"backup": true}}).to_string();
+            let storage = storage_type.open_storage("sqlite_storage_type_open_works_for_legacy_wallet", Some(&config), None).unwrap();
+
+            assert_eq!(storage.get_storage_metadata().unwrap(), _metadata());
+            storage.set_storage_metadata(&[1, 2, 3]).unwrap();
+            assert_eq!(storage.get_storage_metadata().unwrap(), vec![1, 2, 3]);
+
+            let db_path = SQLiteStorageType::_db_path("sqlite_storage_type_open_works_for_legacy_wallet", None);
+            assert!(Path::new(&format!("{}.v0.bak", db_path.to_str().unwrap())).exists());
+
+            let conn = _connection("sqlite_storage_type_open_works_for_legacy_wallet");
+            assert_eq!(conn.get_schema_version().unwrap(), _migrations().last().unwrap().version);
+
+            let metadata: Vec<u8> = conn.query_row("SELECT value FROM metadata", rusqlite::NO_PARAMS, |row| row.get(0)).unwrap();
+            assert_eq!(metadata, vec![1, 2, 3]);
+        }
+        _cleanup("sqlite_storage_type_open_works_for_legacy_wallet");
+    }
+
+    #[test]
+    fn sqlite_storage_type_open_works_for_dry_run_migration() {
+        _cleanup("sqlite_storage_type_open_works_for_dry_run_migration");
+        {
+            _create_legacy_storage("sqlite_storage_type_open_works_for_dry_run_migration");
+
+            let storage_type = SQLiteStorageType::new();
+            let config = json!({"migration": {"dry_run": true, "backup": true}}).to_string();
+            let res = storage_type.open_storage("sqlite_storage_type_open_works_for_dry_run_migration", Some(&config), None);
+            assert_kind!(IndyErrorKind::WalletStorageError, res);
+
+            let db_path = SQLiteStorageType::_db_path("sqlite_storage_type_open_works_for_dry_run_migration", None);
+            assert!(!Path::new(&format!("{}.v0.bak", db_path.to_str().unwrap())).exists());
+
+            let conn = _connection("sqlite_storage_type_open_works_for_dry_run_migration");
+            assert_eq!(conn.get_schema_version().unwrap(), 0);
+        }
+        _cleanup("sqlite_storage_type_open_works_for_dry_run_migration");
+    }
+
+    #[test]
+    fn sqlite_storage_type_open_works_for_newer_schema_version() {
+        _cleanup("sqlite_storage_type_open_works_for_newer_schema_version");
+        {
+            _storage("sqlite_storage_type_open_works_for_newer_schema_version");
+            _connection("sqlite_storage_type_open_works_for_newer_schema_version").set_schema_version(1000).unwrap();
+
+            let storage_type = SQLiteStorageType::new();
+            let res = storage_type.open_storage("sqlite_storage_type_open_works_for_newer_schema_version", None, None);
+            assert_kind!(IndyErrorKind::WalletStorageError, res);
+        }
+        _cleanup("sqlite_storage_type_open_works_for_newer_schema_version");
+    }
+
     #[test]
     fn sqlite_storage_add_works_for_is_802() {
         _cleanup("sqlite_storage_add_works_for_is_802");
@@ -1044,6 +1496,125 @@ mod tests {
         _cleanup("sqlite_storage_get_all_works_for_empty");
     }
 
+    #[test]
+    fn sqlite_storage_delete_expired_works() {
+        _cleanup("sqlite_storage_delete_expired_works");
+        {
+            let storage = _storage("sqlite_storage_delete_expired_works");
+            storage.add(&_type1(), &_id(1), &_value(1), &[Tag::PlainText(vec![9], "0000000100".to_string())]).unwrap();
+            storage.add(&_type1(), &_id(2), &_value(2), &[Tag::PlainText(vec![9], "0000000200".to_string())]).unwrap();
+            storage.add(&_type2(), &_id(3), &_value(3), &[Tag::PlainText(vec![2], "0000000100".to_string())]).unwrap();
+
+            assert_eq!(storage.delete_expired(&[9], "0000000100").unwrap(), 1);
+
+            let res = storage.get(&_type1(), &_id(1), r##"{}"##);
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+            storage.get(&_type1(), &_id(2), r##"{}"##).unwrap();
+            storage.get(&_type2(), &_id(3), r##"{}"##).unwrap();
+
+            assert_eq!(storage.delete_expired(&[9], "0000000100").unwrap(), 0);
+        }
+        _cleanup("sqlite_storage_delete_expired_works");
+    }
+
+    #[test]
+    fn sqlite_storage_get_modified_since_works() {
+        _cleanup("sqlite_storage_get_modified_since_works");
+        {
+            let storage = _storage("sqlite_storage_get_modified_since_works");
+            assert_eq!(storage.get_modification_marker().unwrap(), 0);
+
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+            let marker = storage.get_modification_marker().unwrap();
+            assert!(marker > 0);
+
+            let mut storage_iterator = storage.get_modified_since(marker).unwrap();
+            assert!(storage_iterator.next().unwrap().is_none());
+
+            storage.update(&_type1(), &_id1(), &_value2()).unwrap();
+            assert!(storage.get_modification_marker().unwrap() > marker);
+
+            let mut storage_iterator = storage.get_modified_since(marker).unwrap();
+
+            let record = storage_iterator.next().unwrap().unwrap();
+            assert_eq!(record.id, _id1());
+            assert_eq!(record.type_.unwrap(), _type1());
+            assert_eq!(record.value.unwrap(), _value2());
+            assert_eq!(_sort(record.tags.unwrap()), _sort(_tags()));
+
+            assert!(storage_iterator.next().unwrap().is_none());
+        }
+        _cleanup("sqlite_storage_get_modified_since_works");
+    }
+
+    #[test]
+    fn sqlite_storage_get_modified_since_works_for_tags_change() {
+        _cleanup("sqlite_storage_get_modified_since_works_for_tags_change");
+        {
+            let storage = _storage("sqlite_storage_get_modified_since_works_for_tags_change");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+            let marker = storage.get_modification_marker().unwrap();
+            storage.update_tags(&_type2(), &_id2(), &_new_tags()).unwrap();
+
+            let mut storage_iterator = storage.get_modified_since(marker).unwrap();
+
+            let record = storage_iterator.next().unwrap().unwrap();
+            assert_eq!(record.id, _id2());
+            assert_eq!(_sort(record.tags.unwrap()), _sort(_new_tags()));
+
+            assert!(storage_iterator.next().unwrap().is_none());
+        }
+        _cleanup("sqlite_storage_get_modified_since_works_for_tags_change");
+    }
+
+    #[test]
+    fn sqlite_storage_get_modification_marker_works_after_delete() {
+        _cleanup("sqlite_storage_get_modification_marker_works_after_delete");
+        {
+            let storage = _storage("sqlite_storage_get_modification_marker_works_after_delete");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+            let marker = storage.get_modification_marker().unwrap();
+            storage.delete(&_type1(), &_id1()).unwrap();
+            assert!(storage.get_modification_marker().unwrap() >= marker);
+
+            storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+            let mut storage_iterator = storage.get_modified_since(marker).unwrap();
+
+            let record = storage_iterator.next().unwrap().unwrap();
+            assert_eq!(record.id, _id2());
+
+            assert!(storage_iterator.next().unwrap().is_none());
+        }
+        _cleanup("sqlite_storage_get_modification_marker_works_after_delete");
+    }
+
+    #[test]
+    fn sqlite_storage_get_deleted_since_works() {
+        _cleanup("sqlite_storage_get_deleted_since_works");
+        {
+            let storage = _storage("sqlite_storage_get_deleted_since_works");
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+            let marker = storage.get_modification_marker().unwrap();
+            assert_eq!(storage.get_deleted_since(marker).unwrap(), Some(vec![]));
+
+            storage.delete(&_type1(), &_id1()).unwrap();
+            assert!(storage.get_modification_marker().unwrap() > marker);
+            assert_eq!(storage.get_deleted_since(marker).unwrap(), Some(vec![(_type1(), _id1())]));
+
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+            assert_eq!(storage.get_deleted_since(marker).unwrap(), Some(vec![]));
+        }
+        _cleanup("sqlite_storage_get_deleted_since_works");
+    }
+
     #[test]
     fn sqlite_storage_update_works() {
         _cleanup("sqlite_storage_update_works");
@@ -1323,6 +1894,20 @@ mod tests {
         storage_type.open_storage(name, None, None).unwrap()
     }
 
+    fn _connection(name: &str) -> rusqlite::Connection {
+        rusqlite::Connection::open(SQLiteStorageType::_db_path(name, None)).unwrap()
+    }
+
+    // Creates storage the way it was done before schema versioning
+    fn _create_legacy_storage(name: &str) {
+        let db_path = SQLiteStorageType::_db_path(name, None);
+        fs::DirBuilder::new().recursive(true).create(db_path.parent().unwrap()).unwrap();
+
+        let conn = rusqlite::Connection::open(db_path).unwrap();
+        conn.execute_batch(_CREATE_SCHEMA).unwrap();
+        conn.execute("INSERT OR REPLACE INTO metadata(value) VALUES(?1)", &[&_metadata()]).unwrap();
+    }
+
     fn _storage_custom(name: &str) -> Box<dyn WalletStorage> {
         let storage_type = SQLiteStorageType::new();
 
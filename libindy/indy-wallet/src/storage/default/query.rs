@@ -1,26 +1,29 @@
 use indy_api_types::errors::prelude::*;
 use rusqlite::types::ToSql;
 use crate::language::{Operator, TagName, TargetValue};
+use crate::{SearchOptions, SortBy, SortOrder};
+use indy_utils::crypto::base64;
 
 
 // Translates Wallet Query Language to SQL
 // WQL input is provided as a reference to a top level Operator
+// Sorting and pagination are taken from search options
 // Result is a tuple of query string and query arguments
-pub fn wql_to_sql<'a>(class: &'a Vec<u8>, op: &'a Operator, _options: Option<&str>) -> Result<(String, Vec<&'a dyn ToSql>), IndyError> {
+pub fn wql_to_sql<'a>(class: &'a Vec<u8>, op: &'a Operator, options: &SearchOptions) -> Result<(String, Vec<&'a dyn ToSql>), IndyError> {
     let mut arguments: Vec<&dyn ToSql> = Vec::new();
     arguments.push(class);
     let clause_string = operator_to_sql(op, &mut arguments)?;
     const BASE: &str = "SELECT i.id, i.name, i.value, i.key, i.type FROM items as i WHERE i.type = ?";
+    let mut query_string = BASE.to_string();
     if !clause_string.is_empty() {
-        let mut query_string = String::with_capacity(BASE.len() + 5 + clause_string.len());
-        query_string.push_str(BASE);
         query_string.push_str(" AND ");
         query_string.push_str(&clause_string);
-        Ok((query_string, arguments))
-    } else {
-        Ok((BASE.to_string(), arguments))
     }
-
+    if let Some(ref sort_by) = options.sort_by {
+        query_string.push_str(&order_by_to_sql(sort_by)?);
+    }
+    query_string.push_str(&limit_to_sql(options.skip, options.limit));
+    Ok((query_string, arguments))
 }
 
 
@@ -37,6 +40,29 @@ pub fn wql_to_sql_count<'a>(class: &'a Vec<u8>, op: &'a Operator) -> Result<(Str
 }
 
 
+// Values of plain tags used for grouping are selected by subqueries. Tag names are inlined
+// as blob literals as in ORDER BY, so arguments keep the order of WQL clause
+pub fn wql_to_sql_aggregate<'a>(class: &'a Vec<u8>, op: &'a Operator, group_by: &[Vec<u8>]) -> Result<(String, Vec<&'a dyn ToSql>), IndyError> {
+    let mut arguments: Vec<&dyn ToSql> = Vec::new();
+    arguments.push(class);
+    let clause_string = operator_to_sql(op, &mut arguments)?;
+    let mut columns: Vec<String> = group_by.iter()
+        .map(|name| format!("(SELECT value FROM tags_plaintext WHERE item_id = i.id AND name = X'{}')", to_hex(name)))
+        .collect();
+    columns.push("count(*)".to_string());
+    let mut query_string = format!("SELECT {} FROM items as i WHERE i.type = ?", columns.join(", "));
+    if !clause_string.is_empty() {
+        query_string.push_str(" AND ");
+        query_string.push_str(&clause_string);
+    }
+    if !group_by.is_empty() {
+        let positions: Vec<String> = (1..=group_by.len()).map(|position| position.to_string()).collect();
+        query_string.push_str(&format!(" GROUP BY {} ORDER BY {}", positions.join(", "), positions.join(", ")));
+    }
+    Ok((query_string, arguments))
+}
+
+
 fn operator_to_sql<'a>(op: &'a Operator, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
     match *op {
         Operator::Eq(ref tag_name, ref target_value) => eq_to_sql(tag_name, target_value, arguments),
@@ -46,6 +72,9 @@ fn operator_to_sql<'a>(op: &'a Operator, arguments: &mut Vec<&'a dyn ToSql>) ->
         Operator::Lt(ref tag_name, ref target_value) => lt_to_sql(tag_name, target_value, arguments),
         Operator::Lte(ref tag_name, ref target_value) => lte_to_sql(tag_name, target_value, arguments),
         Operator::Like(ref tag_name, ref target_value) => like_to_sql(tag_name, target_value, arguments),
+        Operator::ILike(ref tag_name, ref target_value) => ilike_to_sql(tag_name, target_value, arguments),
+        Operator::Regex(ref tag_name, ref target_value) => regex_to_sql(tag_name, target_value, arguments),
+        Operator::Exists(ref tag_name, exists) => Ok(exists_to_sql(tag_name, exists, arguments)),
         Operator::In(ref tag_name, ref target_values) => in_to_sql(tag_name, target_values, arguments),
         Operator::And(ref suboperators) => and_to_sql(suboperators, arguments),
         Operator::Or(ref suboperators) => or_to_sql(suboperators, arguments),
@@ -148,6 +177,45 @@ fn like_to_sql<'a>(name: &'a TagName, value: &'a TargetValue, arguments: &mut Ve
 }
 
 
+// Both sides are lowered as result of LIKE depends on case_sensitive_like pragma
+fn ilike_to_sql<'a>(name: &'a TagName, value: &'a TargetValue, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    match (name, value) {
+        (&TagName::PlainTagName(ref queried_name), &TargetValue::Unencrypted(ref queried_value)) => {
+            arguments.push(queried_name);
+            arguments.push(queried_value);
+            Ok("(i.id in (SELECT item_id FROM tags_plaintext WHERE name = ? AND lower(value) LIKE lower(?)))".to_string())
+        },
+        _ => Err(err_msg(IndyErrorKind::WalletQueryError, "$ilike operator can be used only with plaintext tags"))
+    }
+}
+
+
+// REGEXP operator calls regexp function registered for wallet connection
+fn regex_to_sql<'a>(name: &'a TagName, value: &'a TargetValue, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
+    match (name, value) {
+        (&TagName::PlainTagName(ref queried_name), &TargetValue::Unencrypted(ref queried_value)) => {
+            arguments.push(queried_name);
+            arguments.push(queried_value);
+            Ok("(i.id in (SELECT item_id FROM tags_plaintext WHERE name = ? AND value REGEXP ?))".to_string())
+        },
+        _ => Err(err_msg(IndyErrorKind::WalletQueryError, "$regex operator can be used only with plaintext tags"))
+    }
+}
+
+
+fn exists_to_sql<'a>(name: &'a TagName, exists: bool, arguments: &mut Vec<&'a dyn ToSql>) -> String {
+    let (queried_name, table) = match *name {
+        TagName::PlainTagName(ref queried_name) => (queried_name, "tags_plaintext"),
+        TagName::EncryptedTagName(ref queried_name) => (queried_name, "tags_encrypted"),
+    };
+
+    arguments.push(queried_name);
+
+    let in_ = if exists { "in" } else { "not in" };
+    format!("(i.id {} (SELECT item_id FROM {} WHERE name = ?))", in_, table)
+}
+
+
 fn in_to_sql<'a>(name: &'a TagName, values: &'a Vec<TargetValue>, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
     let mut in_string = String::new();
     match *name {
@@ -208,6 +276,52 @@ fn not_to_sql<'a>(suboperator: &'a Operator, arguments: &mut Vec<&'a dyn ToSql>)
 }
 
 
+// Tag names are embedded as blob literals. They are always hex encoded so it is safe
+// and allows to avoid keeping of decoded names alive together with query arguments
+fn order_by_to_sql(sort_by: &[SortBy]) -> IndyResult<String> {
+    let mut clauses: Vec<String> = Vec::with_capacity(sort_by.len() + 1);
+
+    for sort in sort_by {
+        let name = decode_plain_tag_name(&sort.tag_name)?;
+        let order = match sort.order {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        };
+        clauses.push(format!("(SELECT value FROM tags_plaintext WHERE item_id = i.id AND name = X'{}') {}", to_hex(&name), order));
+    }
+
+    // makes order stable for records with equal tag values
+    clauses.push("i.id".to_string());
+
+    Ok(format!(" ORDER BY {}", clauses.join(", ")))
+}
+
+
+fn limit_to_sql(skip: Option<usize>, limit: Option<usize>) -> String {
+    match (skip, limit) {
+        (None, None) => String::new(),
+        (None, Some(limit)) => format!(" LIMIT {}", limit),
+        // SQLite requires LIMIT clause for OFFSET, negative value means no limit
+        (Some(skip), None) => format!(" LIMIT -1 OFFSET {}", skip),
+        (Some(skip), Some(limit)) => format!(" LIMIT {} OFFSET {}", limit, skip),
+    }
+}
+
+
+fn decode_plain_tag_name(name: &str) -> IndyResult<Vec<u8>> {
+    if !name.starts_with('~') {
+        return Err(err_msg(IndyErrorKind::WalletQueryError, "Sorting is supported only by plaintext tags"));
+    }
+
+    base64::decode(&name[1..])
+}
+
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+
 fn join_operators<'a>(operators: &'a [Operator], join_str: &str, arguments: &mut Vec<&'a dyn ToSql>) -> IndyResult<String> {
     let mut s = String::new();
     if !operators.is_empty() {
@@ -241,6 +355,95 @@ mod tests {
         ]);
         let query = Operator::Or(vec![condition_1, condition_2]);
         let class = vec![100,100,100];
-        let (_query, _arguments) = wql_to_sql(&class, &query, None).unwrap();
+        let (_query, _arguments) = wql_to_sql(&class, &query, &SearchOptions::default()).unwrap();
+    }
+
+    #[test]
+    fn order_and_limit() {
+        let query = Operator::And(vec![]);
+        let class = vec![100,100,100];
+        let options: SearchOptions = ::serde_json::from_value(json!({
+            "sortBy": [{"tagName": format!("~{}", base64::encode(&[1, 2, 255])), "order": "desc"}],
+            "skip": 10,
+            "limit": 5
+        })).unwrap();
+
+        let (query, arguments) = wql_to_sql(&class, &query, &options).unwrap();
+        assert_eq!(query, "SELECT i.id, i.name, i.value, i.key, i.type FROM items as i WHERE i.type = ? \
+                           ORDER BY (SELECT value FROM tags_plaintext WHERE item_id = i.id AND name = X'0102FF') DESC, i.id \
+                           LIMIT 5 OFFSET 10");
+        assert_eq!(arguments.len(), 1);
+    }
+
+    #[test]
+    fn skip_without_limit() {
+        let query = Operator::And(vec![]);
+        let class = vec![100,100,100];
+        let options: SearchOptions = ::serde_json::from_value(json!({"skip": 3})).unwrap();
+
+        let (query, _arguments) = wql_to_sql(&class, &query, &options).unwrap();
+        assert!(query.ends_with(" LIMIT -1 OFFSET 3"));
+    }
+
+    #[test]
+    fn order_by_encrypted_tag_fails() {
+        let query = Operator::And(vec![]);
+        let class = vec![100,100,100];
+        let options: SearchOptions = ::serde_json::from_value(json!({
+            "sortBy": [{"tagName": base64::encode(&[1, 2, 3])}]
+        })).unwrap();
+
+        let res = wql_to_sql(&class, &query, &options);
+        assert_kind!(IndyErrorKind::WalletQueryError, res);
+    }
+
+    #[test]
+    fn exists_and_pattern_operators() {
+        let query = Operator::And(vec![
+            Operator::Exists(TagName::EncryptedTagName(vec![1, 2, 3]), false),
+            Operator::ILike(TagName::PlainTagName(vec![4, 5, 6]), TargetValue::Unencrypted("Spam%".to_string())),
+            Operator::Regex(TagName::PlainTagName(vec![7, 8, 9]), TargetValue::Unencrypted("^eggs$".to_string())),
+        ]);
+        let class = vec![100,100,100];
+
+        let (query, arguments) = wql_to_sql_count(&class, &query).unwrap();
+        assert_eq!(query, "SELECT count(*) FROM items as i WHERE i.type = ? AND \
+                           ((i.id not in (SELECT item_id FROM tags_encrypted WHERE name = ?)) AND \
+                           (i.id in (SELECT item_id FROM tags_plaintext WHERE name = ? AND lower(value) LIKE lower(?))) AND \
+                           (i.id in (SELECT item_id FROM tags_plaintext WHERE name = ? AND value REGEXP ?)))");
+        assert_eq!(arguments.len(), 6);
+    }
+
+    #[test]
+    fn aggregate_by_plain_tags() {
+        let query = Operator::Eq(TagName::PlainTagName(vec![1, 2, 3]), TargetValue::Unencrypted("spam".to_string()));
+        let class = vec![100,100,100];
+
+        let (query, arguments) = wql_to_sql_aggregate(&class, &query, &[vec![4, 5, 6], vec![7, 8, 255]]).unwrap();
+        assert_eq!(query, "SELECT (SELECT value FROM tags_plaintext WHERE item_id = i.id AND name = X'040506'), \
+                           (SELECT value FROM tags_plaintext WHERE item_id = i.id AND name = X'0708FF'), \
+                           count(*) FROM items as i WHERE i.type = ? AND \
+                           (i.id in (SELECT item_id FROM tags_plaintext WHERE name = ? AND value = ?)) \
+                           GROUP BY 1, 2 ORDER BY 1, 2");
+        assert_eq!(arguments.len(), 3);
+    }
+
+    #[test]
+    fn aggregate_without_grouping() {
+        let query = Operator::And(vec![]);
+        let class = vec![100,100,100];
+
+        let (query, arguments) = wql_to_sql_aggregate(&class, &query, &[]).unwrap();
+        assert_eq!(query, "SELECT count(*) FROM items as i WHERE i.type = ?");
+        assert_eq!(arguments.len(), 1);
+    }
+
+    #[test]
+    fn regex_for_encrypted_tag_fails() {
+        let query = Operator::Regex(TagName::EncryptedTagName(vec![1, 2, 3]), TargetValue::Encrypted(vec![4, 5, 6]));
+        let class = vec![100,100,100];
+
+        let res = wql_to_sql(&class, &query, &SearchOptions::default());
+        assert_kind!(IndyErrorKind::WalletQueryError, res);
     }
 }
\ No newline at end of file
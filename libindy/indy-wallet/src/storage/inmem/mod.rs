@@ -0,0 +1,936 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+use serde_json;
+
+use indy_api_types::errors::prelude::*;
+use regex::Regex;
+use indy_utils::crypto::base64;
+use crate::language::{self, Operator, TargetValue};
+
+use super::{EncryptedValue, StorageIterator, StorageOperation, StorageRecord, Tag, TagName, WalletStorage, WalletStorageType};
+use super::super::{RecordOptions, SearchOptions, SortOrder};
+
+#[derive(Clone, Debug)]
+struct InmemItem {
+    seq: u64,
+    modified: u64,
+    value: EncryptedValue,
+    tags_encrypted: BTreeMap<Vec<u8>, Vec<u8>>,
+    tags_plaintext: BTreeMap<Vec<u8>, String>,
+}
+
+impl InmemItem {
+    fn tags(&self) -> Vec<Tag> {
+        let mut tags: Vec<Tag> = self.tags_encrypted.iter()
+            .map(|(name, value)| Tag::Encrypted(name.clone(), value.clone()))
+            .collect();
+
+        tags.extend(self.tags_plaintext.iter()
+            .map(|(name, value)| Tag::PlainText(name.clone(), value.clone())));
+
+        tags
+    }
+
+    fn add_tags(&mut self, tags: &[Tag]) {
+        for tag in tags {
+            match *tag {
+                Tag::Encrypted(ref name, ref value) => { self.tags_encrypted.insert(name.clone(), value.clone()); }
+                Tag::PlainText(ref name, ref value) => { self.tags_plaintext.insert(name.clone(), value.clone()); }
+            }
+        }
+    }
+}
+
+// Items are keyed by (type, id). Sequence number plays the role of SQLite rowid
+// and keeps search results in insertion order. Marker is incremented on every modification
+// and remembered by modified item or by deleted one until it is added again
+#[derive(Clone, Debug)]
+struct InmemData {
+    metadata: Vec<u8>,
+    items: HashMap<(Vec<u8>, Vec<u8>), InmemItem>,
+    deleted: HashMap<(Vec<u8>, Vec<u8>), u64>,
+    next_seq: u64,
+    marker: u64,
+}
+
+impl InmemData {
+    fn item_mut(&mut self, type_: &[u8], id: &[u8]) -> IndyResult<&mut InmemItem> {
+        self.marker += 1;
+        let marker = self.marker;
+
+        let item = self.items.get_mut(&(type_.to_vec(), id.to_vec()))
+            .ok_or_else(|| err_msg(IndyErrorKind::WalletItemNotFound, "Item not found"))?;
+
+        item.modified = marker;
+        Ok(item)
+    }
+
+    fn add(&mut self, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> IndyResult<()> {
+        let key = (type_.to_vec(), id.to_vec());
+
+        if self.items.contains_key(&key) {
+            return Err(err_msg(IndyErrorKind::WalletItemAlreadyExists, "Item already exists"));
+        }
+
+        let mut item = InmemItem {
+            seq: self.next_seq,
+            modified: self.marker + 1,
+            value: value.clone(),
+            tags_encrypted: BTreeMap::new(),
+            tags_plaintext: BTreeMap::new(),
+        };
+        item.add_tags(tags);
+
+        self.next_seq += 1;
+        self.marker += 1;
+        self.deleted.remove(&key);
+        self.items.insert(key, item);
+        Ok(())
+    }
+
+    fn update(&mut self, type_: &[u8], id: &[u8], value: &EncryptedValue) -> IndyResult<()> {
+        self.item_mut(type_, id)
+            .map_err(|_| err_msg(IndyErrorKind::WalletItemNotFound, "Item to update not found"))?
+            .value = value.clone();
+        Ok(())
+    }
+
+    fn add_tags(&mut self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+        self.item_mut(type_, id)?.add_tags(tags);
+        Ok(())
+    }
+
+    fn update_tags(&mut self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+        let item = self.item_mut(type_, id)?;
+        item.tags_encrypted.clear();
+        item.tags_plaintext.clear();
+        item.add_tags(tags);
+        Ok(())
+    }
+
+    fn delete_tags(&mut self, type_: &[u8], id: &[u8], tag_names: &[TagName]) -> IndyResult<()> {
+        let item = self.item_mut(type_, id)?;
+
+        for tag_name in tag_names {
+            match *tag_name {
+                TagName::OfEncrypted(ref name) => { item.tags_encrypted.remove(name); }
+                TagName::OfPlain(ref name) => { item.tags_plaintext.remove(name); }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn delete(&mut self, type_: &[u8], id: &[u8]) -> IndyResult<()> {
+        let key = (type_.to_vec(), id.to_vec());
+
+        self.items.remove(&key)
+            .ok_or_else(|| err_msg(IndyErrorKind::WalletItemNotFound, "Item to delete not found"))?;
+
+        self.marker += 1;
+        self.deleted.insert(key, self.marker);
+        Ok(())
+    }
+
+    fn apply(&mut self, operation: &StorageOperation) -> IndyResult<()> {
+        match *operation {
+            StorageOperation::Add(ref type_, ref id, ref value, ref tags) => self.add(type_, id, value, tags),
+            StorageOperation::Update(ref type_, ref id, ref value) => self.update(type_, id, value),
+            StorageOperation::AddTags(ref type_, ref id, ref tags) => self.add_tags(type_, id, tags),
+            StorageOperation::UpdateTags(ref type_, ref id, ref tags) => self.update_tags(type_, id, tags),
+            StorageOperation::DeleteTags(ref type_, ref id, ref tag_names) => self.delete_tags(type_, id, tag_names),
+            StorageOperation::Delete(ref type_, ref id) => self.delete(type_, id),
+        }
+    }
+}
+
+struct InmemStorageIterator {
+    records: Option<::std::vec::IntoIter<StorageRecord>>,
+    total_count: Option<usize>,
+}
+
+impl StorageIterator for InmemStorageIterator {
+    fn next(&mut self) -> IndyResult<Option<StorageRecord>> {
+        Ok(self.records.as_mut().and_then(|records| records.next()))
+    }
+
+    fn get_total_count(&self) -> IndyResult<Option<usize>> {
+        Ok(self.total_count)
+    }
+}
+
+struct InmemStorage {
+    data: Rc<RefCell<InmemData>>,
+}
+
+impl WalletStorage for InmemStorage {
+    fn get(&self, type_: &[u8], id: &[u8], options: &str) -> IndyResult<StorageRecord> {
+        let options: RecordOptions = if options == "{}" {
+            RecordOptions::default()
+        } else {
+            serde_json::from_str(options)
+                .to_indy(IndyErrorKind::InvalidStructure, "RecordOptions is malformed json")?
+        };
+
+        let data = self.data.borrow();
+        let item = data.items.get(&(type_.to_vec(), id.to_vec()))
+            .ok_or_else(|| err_msg(IndyErrorKind::WalletItemNotFound, "Item not found"))?;
+
+        Ok(_record(type_, id, item, &options))
+    }
+
+    fn add(&self, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> IndyResult<()> {
+        self.data.borrow_mut().add(type_, id, value, tags)
+    }
+
+    fn update(&self, type_: &[u8], id: &[u8], value: &EncryptedValue) -> IndyResult<()> {
+        self.data.borrow_mut().update(type_, id, value)
+    }
+
+    fn add_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+        self.data.borrow_mut().add_tags(type_, id, tags)
+    }
+
+    fn update_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+        self.data.borrow_mut().update_tags(type_, id, tags)
+    }
+
+    fn delete_tags(&self, type_: &[u8], id: &[u8], tag_names: &[TagName]) -> IndyResult<()> {
+        self.data.borrow_mut().delete_tags(type_, id, tag_names)
+    }
+
+    fn delete(&self, type_: &[u8], id: &[u8]) -> IndyResult<()> {
+        self.data.borrow_mut().delete(type_, id)
+    }
+
+    ///
+    /// applies all operations to a copy of wallet data and replaces the data only if
+    /// all of them succeeded, so partial results of a failed batch are never visible.
+    ///
+    fn batch(&self, operations: &[StorageOperation]) -> IndyResult<()> {
+        let mut data = self.data.borrow().clone();
+
+        for (index, operation) in operations.iter().enumerate() {
+            data.apply(operation)
+                .map_err(|err| err.extend(format!("Batch operation {} failed", index)))?;
+        }
+
+        *self.data.borrow_mut() = data;
+        Ok(())
+    }
+
+    // Chunks are applied to a copy of data, so reads made by `next` don't see previous chunks
+    fn batch_chunked(&self, next: &mut dyn FnMut() -> IndyResult<Option<Vec<StorageOperation>>>) -> IndyResult<()> {
+        let mut data = self.data.borrow().clone();
+        let mut index = 0;
+
+        while let Some(operations) = next()? {
+            for operation in operations.iter() {
+                data.apply(operation)
+                    .map_err(|err| err.extend(format!("Batch operation {} failed", index)))?;
+                index += 1;
+            }
+        }
+
+        *self.data.borrow_mut() = data;
+        Ok(())
+    }
+
+    fn get_storage_metadata(&self) -> IndyResult<Vec<u8>> {
+        Ok(self.data.borrow().metadata.clone())
+    }
+
+    fn set_storage_metadata(&self, metadata: &[u8]) -> IndyResult<()> {
+        self.data.borrow_mut().metadata = metadata.to_vec();
+        Ok(())
+    }
+
+    fn get_all(&self) -> IndyResult<Box<dyn StorageIterator>> {
+        self.get_modified_since(0)
+    }
+
+    fn count(&self) -> IndyResult<usize> {
+        Ok(self.data.borrow().items.len())
+    }
+
+    fn get_modification_marker(&self) -> IndyResult<u64> {
+        Ok(self.data.borrow().marker)
+    }
+
+    fn get_deleted_since(&self, marker: u64) -> IndyResult<Option<Vec<(Vec<u8>, Vec<u8>)>>> {
+        let deleted = self.data.borrow().deleted.iter()
+            .filter(|&(_, &deleted)| deleted > marker)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        Ok(Some(deleted))
+    }
+
+    fn delete_expired(&self, expiry_tag_name: &[u8], now: &str) -> IndyResult<usize> {
+        let mut data = self.data.borrow_mut();
+
+        let expired: Vec<(Vec<u8>, Vec<u8>)> = data.items.iter()
+            .filter(|&(_, item)| item.tags_plaintext.get(expiry_tag_name).map_or(false, |expires_at| expires_at.as_str() <= now))
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for &(ref type_, ref id) in expired.iter() {
+            data.delete(type_, id)?;
+        }
+
+        Ok(expired.len())
+    }
+
+    fn get_modified_since(&self, marker: u64) -> IndyResult<Box<dyn StorageIterator>> {
+        let fetch_options = RecordOptions {
+            retrieve_type: true,
+            retrieve_value: true,
+            retrieve_tags: true,
+        };
+
+        let data = self.data.borrow();
+        let mut items: Vec<(&(Vec<u8>, Vec<u8>), &InmemItem)> = data.items.iter()
+            .filter(|&(_, item)| item.modified > marker)
+            .collect();
+        items.sort_by_key(|&(_, item)| item.seq);
+
+        let records: Vec<StorageRecord> = items.into_iter()
+            .map(|(&(ref type_, ref id), item)| _record(type_, id, item, &fetch_options))
+            .collect();
+
+        Ok(Box::new(InmemStorageIterator { records: Some(records.into_iter()), total_count: None }))
+    }
+
+    fn search(&self, type_: &[u8], query: &language::Operator, options: Option<&str>) -> IndyResult<Box<dyn StorageIterator>> {
+        let search_options = match options {
+            None => SearchOptions::default(),
+            Some(option_str) => serde_json::from_str(option_str)
+                .to_indy(IndyErrorKind::InvalidStructure, "Search options is malformed json")?
+        };
+
+        _check_operator(query)?;
+
+        let sort_by = match search_options.sort_by {
+            Some(ref sort_by) => sort_by.iter()
+                .map(|sort| Ok((_decode_plain_tag_name(&sort.tag_name)?, sort.order)))
+                .collect::<IndyResult<Vec<(Vec<u8>, SortOrder)>>>()?,
+            None => Vec::new(),
+        };
+
+        let data = self.data.borrow();
+
+        let mut items: Vec<(&Vec<u8>, &InmemItem)> = data.items.iter()
+            .filter(|&(&(ref item_type, _), item)| item_type[..] == type_[..] && _matches(query, item))
+            .map(|(&(_, ref id), item)| (id, item))
+            .collect();
+
+        let total_count = if search_options.retrieve_total_count { Some(items.len()) } else { None };
+
+        if !search_options.retrieve_records {
+            return Ok(Box::new(InmemStorageIterator { records: None, total_count }));
+        }
+
+        items.sort_by(|&(_, a), &(_, b)| _compare(a, b, &sort_by));
+
+        let fetch_options = RecordOptions {
+            retrieve_value: search_options.retrieve_value,
+            retrieve_tags: search_options.retrieve_tags,
+            retrieve_type: search_options.retrieve_type,
+        };
+
+        let records: Vec<StorageRecord> = items.into_iter()
+            .skip(search_options.skip.unwrap_or(0))
+            .take(search_options.limit.unwrap_or(::std::usize::MAX))
+            .map(|(id, item)| _record(type_, id, item, &fetch_options))
+            .collect();
+
+        Ok(Box::new(InmemStorageIterator { records: Some(records.into_iter()), total_count }))
+    }
+
+    fn close(&mut self) -> IndyResult<()> {
+        Ok(())
+    }
+
+    fn supports_exists_query(&self) -> bool {
+        true
+    }
+}
+
+fn _record(type_: &[u8], id: &[u8], item: &InmemItem, options: &RecordOptions) -> StorageRecord {
+    let value = if options.retrieve_value { Some(item.value.clone()) } else { None };
+    let type_ = if options.retrieve_type { Some(type_.to_vec()) } else { None };
+    let tags = if options.retrieve_tags { Some(item.tags()) } else { None };
+
+    StorageRecord::new(id.to_vec(), value, type_, tags)
+}
+
+// Missing tag value is ordered first like NULL in SQLite. Ties are resolved by insertion order
+fn _compare(a: &InmemItem, b: &InmemItem, sort_by: &[(Vec<u8>, SortOrder)]) -> ::std::cmp::Ordering {
+    for &(ref name, order) in sort_by {
+        let ordering = a.tags_plaintext.get(name).cmp(&b.tags_plaintext.get(name));
+        let ordering = match order {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        };
+
+        if ordering != ::std::cmp::Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    a.seq.cmp(&b.seq)
+}
+
+fn _decode_plain_tag_name(name: &str) -> IndyResult<Vec<u8>> {
+    if !name.starts_with('~') {
+        return Err(err_msg(IndyErrorKind::WalletQueryError, "Sorting is supported only by plaintext tags"));
+    }
+
+    base64::decode(&name[1..])
+}
+
+// Rejects the same combinations of tag names and values as SQLite query translation does.
+// Validation is done up front as evaluation stops on the first matched or failed suboperator
+fn _check_operator(op: &Operator) -> IndyResult<()> {
+    match *op {
+        Operator::Eq(ref name, ref value) | Operator::Neq(ref name, ref value) => match (name, value) {
+            (&language::TagName::PlainTagName(_), &TargetValue::Unencrypted(_)) |
+            (&language::TagName::EncryptedTagName(_), &TargetValue::Encrypted(_)) => Ok(()),
+            _ => Err(err_msg(IndyErrorKind::WalletQueryError, "Invalid combination of tag name and value for equality operator"))
+        },
+        Operator::Gt(ref name, ref value) | Operator::Gte(ref name, ref value) |
+        Operator::Lt(ref name, ref value) | Operator::Lte(ref name, ref value) |
+        Operator::Like(ref name, ref value) | Operator::ILike(ref name, ref value) => match (name, value) {
+            (&language::TagName::PlainTagName(_), &TargetValue::Unencrypted(_)) => Ok(()),
+            _ => Err(err_msg(IndyErrorKind::WalletQueryError, "Invalid combination of tag name and value for comparison operator"))
+        },
+        Operator::Regex(ref name, ref value) => match (name, value) {
+            (&language::TagName::PlainTagName(_), &TargetValue::Unencrypted(ref pattern)) => Regex::new(pattern)
+                .map(|_| ())
+                .to_indy(IndyErrorKind::WalletQueryError, "Invalid regular expression in $regex operator"),
+            _ => Err(err_msg(IndyErrorKind::WalletQueryError, "$regex operator can be used only with plaintext tags"))
+        },
+        Operator::Exists(_, _) => Ok(()),
+        Operator::In(ref name, ref values) => {
+            for value in values {
+                match (name, value) {
+                    (&language::TagName::PlainTagName(_), &TargetValue::Unencrypted(_)) |
+                    (&language::TagName::EncryptedTagName(_), &TargetValue::Encrypted(_)) => {}
+                    _ => return Err(err_msg(IndyErrorKind::WalletQueryError, "Invalid combination of tag name and value for $in operator"))
+                }
+            }
+            Ok(())
+        }
+        Operator::And(ref suboperators) | Operator::Or(ref suboperators) =>
+            suboperators.iter().map(_check_operator).collect(),
+        Operator::Not(ref suboperator) => _check_operator(suboperator),
+    }
+}
+
+// Empty $and and $or match everything as they are omitted from SQL WHERE clause
+fn _matches(op: &Operator, item: &InmemItem) -> bool {
+    match *op {
+        Operator::Eq(ref name, ref value) => _compare_tag(name, value, item, |ordering| ordering == ::std::cmp::Ordering::Equal),
+        Operator::Neq(ref name, ref value) => _compare_tag(name, value, item, |ordering| ordering != ::std::cmp::Ordering::Equal),
+        Operator::Gt(ref name, ref value) => _compare_tag(name, value, item, |ordering| ordering == ::std::cmp::Ordering::Greater),
+        Operator::Gte(ref name, ref value) => _compare_tag(name, value, item, |ordering| ordering != ::std::cmp::Ordering::Less),
+        Operator::Lt(ref name, ref value) => _compare_tag(name, value, item, |ordering| ordering == ::std::cmp::Ordering::Less),
+        Operator::Lte(ref name, ref value) => _compare_tag(name, value, item, |ordering| ordering != ::std::cmp::Ordering::Greater),
+        Operator::Like(ref name, ref value) => match (name, value) {
+            (&language::TagName::PlainTagName(ref name), &TargetValue::Unencrypted(ref pattern)) =>
+                item.tags_plaintext.get(name).map_or(false, |value| _like(value.as_bytes(), pattern.as_bytes())),
+            _ => false
+        },
+        // _like already ignores case of ASCII letters like SQLite lower() does
+        Operator::ILike(ref name, ref value) => match (name, value) {
+            (&language::TagName::PlainTagName(ref name), &TargetValue::Unencrypted(ref pattern)) =>
+                item.tags_plaintext.get(name).map_or(false, |value| _like(value.as_bytes(), pattern.as_bytes())),
+            _ => false
+        },
+        Operator::Regex(ref name, ref value) => match (name, value) {
+            (&language::TagName::PlainTagName(ref name), &TargetValue::Unencrypted(ref pattern)) =>
+                item.tags_plaintext.get(name).map_or(false, |value| Regex::new(pattern).map(|regex| regex.is_match(value)).unwrap_or(false)),
+            _ => false
+        },
+        Operator::Exists(ref name, exists) => match *name {
+            language::TagName::PlainTagName(ref name) => item.tags_plaintext.contains_key(name) == exists,
+            language::TagName::EncryptedTagName(ref name) => item.tags_encrypted.contains_key(name) == exists,
+        },
+        Operator::In(ref name, ref values) =>
+            values.iter().any(|value| _compare_tag(name, value, item, |ordering| ordering == ::std::cmp::Ordering::Equal)),
+        Operator::And(ref suboperators) => suboperators.iter().all(|op| _matches(op, item)),
+        Operator::Or(ref suboperators) => suboperators.is_empty() || suboperators.iter().any(|op| _matches(op, item)),
+        Operator::Not(ref suboperator) => !_matches(suboperator, item),
+    }
+}
+
+fn _compare_tag<F>(name: &language::TagName, value: &TargetValue, item: &InmemItem, predicate: F) -> bool
+    where F: Fn(::std::cmp::Ordering) -> bool {
+    match (name, value) {
+        (&language::TagName::PlainTagName(ref name), &TargetValue::Unencrypted(ref target)) =>
+            item.tags_plaintext.get(name).map_or(false, |value| predicate(value.as_str().cmp(target.as_str()))),
+        (&language::TagName::EncryptedTagName(ref name), &TargetValue::Encrypted(ref target)) =>
+            item.tags_encrypted.get(name).map_or(false, |value| predicate(value[..].cmp(&target[..]))),
+        _ => false
+    }
+}
+
+// SQLite LIKE semantics: "%" matches any sequence, "_" matches any single character
+// and ASCII letters are compared case-insensitively
+pub(crate) fn _like(value: &[u8], pattern: &[u8]) -> bool {
+    let value = String::from_utf8_lossy(value).chars().collect::<Vec<char>>();
+    let pattern = String::from_utf8_lossy(pattern).chars().collect::<Vec<char>>();
+
+    let (mut v, mut p) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while v < value.len() {
+        if p < pattern.len() && pattern[p] == '%' {
+            backtrack = Some((p, v));
+            p += 1;
+        } else if p < pattern.len() && (pattern[p] == '_' || pattern[p].eq_ignore_ascii_case(&value[v])) {
+            p += 1;
+            v += 1;
+        } else if let Some((star_p, star_v)) = backtrack {
+            p = star_p + 1;
+            v = star_v + 1;
+            backtrack = Some((star_p, star_v + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '%')
+}
+
+///
+/// Keeps wallets in memory of the process. Storages live as long as the storage type
+/// (that is, until libindy is unloaded) and are shared between all handles opened for the same id.
+/// Config and credentials are ignored.
+///
+pub struct InmemStorageType {
+    storages: RefCell<HashMap<String, Rc<RefCell<InmemData>>>>,
+}
+
+impl InmemStorageType {
+    pub fn new() -> InmemStorageType {
+        InmemStorageType {
+            storages: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl WalletStorageType for InmemStorageType {
+    fn create_storage(&self, id: &str, _config: Option<&str>, _credentials: Option<&str>, metadata: &[u8]) -> IndyResult<()> {
+        let mut storages = self.storages.borrow_mut();
+
+        if storages.contains_key(id) {
+            return Err(err_msg(IndyErrorKind::WalletAlreadyExists, format!("Wallet storage already exists: {}", id)));
+        }
+
+        let data = InmemData {
+            metadata: metadata.to_vec(),
+            items: HashMap::new(),
+            deleted: HashMap::new(),
+            next_seq: 0,
+            marker: 0,
+        };
+
+        storages.insert(id.to_string(), Rc::new(RefCell::new(data)));
+        Ok(())
+    }
+
+    fn open_storage(&self, id: &str, _config: Option<&str>, _credentials: Option<&str>) -> IndyResult<Box<dyn WalletStorage>> {
+        let data = self.storages.borrow()
+            .get(id)
+            .cloned()
+            .ok_or_else(|| err_msg(IndyErrorKind::WalletNotFound, format!("Wallet storage isn't found: {}", id)))?;
+
+        Ok(Box::new(InmemStorage { data }))
+    }
+
+    fn delete_storage(&self, id: &str, _config: Option<&str>, _credentials: Option<&str>) -> IndyResult<()> {
+        self.storages.borrow_mut()
+            .remove(id)
+            .map(|_| ())
+            .ok_or_else(|| err_msg(IndyErrorKind::WalletNotFound, format!("Wallet storage isn't found: {}", id)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::TagName as QueryTagName;
+
+    #[test]
+    fn inmem_storage_type_create_works_for_twice() {
+        let storage_type = InmemStorageType::new();
+        storage_type.create_storage("inmem_storage_type_create_works_for_twice", None, None, &_metadata()).unwrap();
+
+        let res = storage_type.create_storage("inmem_storage_type_create_works_for_twice", None, None, &_metadata());
+        assert_kind!(IndyErrorKind::WalletAlreadyExists, res);
+    }
+
+    #[test]
+    fn inmem_storage_type_open_works_for_not_created() {
+        let storage_type = InmemStorageType::new();
+
+        let res = storage_type.open_storage("unknown", None, None);
+        assert_kind!(IndyErrorKind::WalletNotFound, res);
+    }
+
+    #[test]
+    fn inmem_storage_type_delete_works() {
+        let storage_type = InmemStorageType::new();
+        storage_type.create_storage("inmem_storage_type_delete_works", None, None, &_metadata()).unwrap();
+
+        storage_type.delete_storage("inmem_storage_type_delete_works", None, None).unwrap();
+
+        let res = storage_type.open_storage("inmem_storage_type_delete_works", None, None);
+        assert_kind!(IndyErrorKind::WalletNotFound, res);
+
+        let res = storage_type.delete_storage("inmem_storage_type_delete_works", None, None);
+        assert_kind!(IndyErrorKind::WalletNotFound, res);
+    }
+
+    #[test]
+    fn inmem_storage_set_get_works_for_reopen() {
+        let storage_type = InmemStorageType::new();
+        storage_type.create_storage("inmem_storage_set_get_works_for_reopen", None, None, &_metadata()).unwrap();
+
+        {
+            let storage = storage_type.open_storage("inmem_storage_set_get_works_for_reopen", None, None).unwrap();
+            storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+        }
+
+        let storage = storage_type.open_storage("inmem_storage_set_get_works_for_reopen", None, None).unwrap();
+        let record = storage.get(&_type1(), &_id1(), r##"{"retrieveType": false, "retrieveValue": true, "retrieveTags": true}"##).unwrap();
+
+        assert_eq!(record.value.unwrap(), _value1());
+        assert_eq!(_sort(record.tags.unwrap()), _sort(_tags()));
+        assert_eq!(storage.get_storage_metadata().unwrap(), _metadata());
+    }
+
+    #[test]
+    fn inmem_storage_add_works_for_twice() {
+        let storage = _storage();
+        storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+        let res = storage.add(&_type1(), &_id1(), &_value2(), &_tags());
+        assert_kind!(IndyErrorKind::WalletItemAlreadyExists, res);
+    }
+
+    #[test]
+    fn inmem_storage_update_and_delete_works_for_non_existing() {
+        let storage = _storage();
+
+        let res = storage.update(&_type1(), &_id1(), &_value1());
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+        let res = storage.add_tags(&_type1(), &_id1(), &_tags());
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+        let res = storage.delete(&_type1(), &_id1());
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+    }
+
+    #[test]
+    fn inmem_storage_tags_operations_work() {
+        let storage = _storage();
+        storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+        storage.add_tags(&_type1(), &_id1(), &[Tag::PlainText(vec![1, 5, 8, 1], "New value".to_string())]).unwrap();
+        storage.delete_tags(&_type1(), &_id1(), &[TagName::OfEncrypted(vec![1, 5, 8])]).unwrap();
+
+        let record = storage.get(&_type1(), &_id1(), r##"{"retrieveTags": true}"##).unwrap();
+        assert_eq!(record.tags.unwrap(), vec![Tag::PlainText(vec![1, 5, 8, 1], "New value".to_string())]);
+
+        storage.update_tags(&_type1(), &_id1(), &_new_tags()).unwrap();
+
+        let record = storage.get(&_type1(), &_id1(), r##"{"retrieveTags": true}"##).unwrap();
+        assert_eq!(_sort(record.tags.unwrap()), _sort(_new_tags()));
+    }
+
+    #[test]
+    fn inmem_storage_batch_is_atomic() {
+        let storage = _storage();
+        storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+
+        let res = storage.batch(&[
+            StorageOperation::Update(_type1(), _id1(), _value2()),
+            StorageOperation::Delete(_type1(), _id2()),
+        ]);
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+
+        let record = storage.get(&_type1(), &_id1(), "{}").unwrap();
+        assert_eq!(record.value.unwrap(), _value1());
+    }
+
+    #[test]
+    fn inmem_storage_get_all_works() {
+        let storage = _storage();
+        storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+        storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+        let mut storage_iterator = storage.get_all().unwrap();
+
+        let record = storage_iterator.next().unwrap().unwrap();
+        assert_eq!(record.type_.unwrap(), _type1());
+        assert_eq!(record.value.unwrap(), _value1());
+
+        let record = storage_iterator.next().unwrap().unwrap();
+        assert_eq!(record.type_.unwrap(), _type2());
+        assert_eq!(record.id, _id2());
+
+        assert!(storage_iterator.next().unwrap().is_none());
+    }
+
+    #[test]
+    fn inmem_storage_get_modified_since_works() {
+        let storage = _storage();
+        storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+        storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+        let marker = storage.get_modification_marker().unwrap();
+        assert!(storage.get_modified_since(marker).unwrap().next().unwrap().is_none());
+
+        storage.update_tags(&_type1(), &_id1(), &_new_tags()).unwrap();
+        assert!(storage.get_modification_marker().unwrap() > marker);
+
+        let mut storage_iterator = storage.get_modified_since(marker).unwrap();
+
+        let record = storage_iterator.next().unwrap().unwrap();
+        assert_eq!(record.id, _id1());
+        assert_eq!(_sort(record.tags.unwrap()), _sort(_new_tags()));
+
+        assert!(storage_iterator.next().unwrap().is_none());
+    }
+
+    #[test]
+    fn inmem_storage_get_deleted_since_works() {
+        let storage = _storage();
+        storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+        storage.add(&_type2(), &_id2(), &_value2(), &_tags()).unwrap();
+
+        let marker = storage.get_modification_marker().unwrap();
+        assert_eq!(storage.get_deleted_since(marker).unwrap(), Some(vec![]));
+
+        storage.delete(&_type1(), &_id1()).unwrap();
+        assert!(storage.get_modification_marker().unwrap() > marker);
+        assert_eq!(storage.get_deleted_since(marker).unwrap(), Some(vec![(_type1(), _id1())]));
+
+        storage.add(&_type1(), &_id1(), &_value1(), &_tags()).unwrap();
+        assert_eq!(storage.get_deleted_since(marker).unwrap(), Some(vec![]));
+    }
+
+    #[test]
+    fn inmem_storage_search_works() {
+        let storage = _storage();
+        for i in 1..5 {
+            storage.add(&_type1(), &_id(i), &_value(i), &[
+                Tag::Encrypted(vec![1], vec![i % 2]),
+                Tag::PlainText(vec![2], format!("Value{}", i)),
+            ]).unwrap();
+        }
+        storage.add(&_type2(), &_id(5), &_value(5), &[Tag::Encrypted(vec![1], vec![1])]).unwrap();
+
+        let query = Operator::And(vec![
+            Operator::Eq(QueryTagName::EncryptedTagName(vec![1]), TargetValue::Encrypted(vec![1])),
+            Operator::Like(QueryTagName::PlainTagName(vec![2]), TargetValue::Unencrypted("val%".to_string())),
+        ]);
+        let mut storage_iterator = storage.search(&_type1(), &query, Some(r#"{"retrieveTotalCount": true}"#)).unwrap();
+
+        assert_eq!(storage_iterator.get_total_count().unwrap(), Some(2));
+        assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id(1));
+        assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id(3));
+        assert!(storage_iterator.next().unwrap().is_none());
+
+        let query = Operator::Not(Box::new(Operator::Gte(QueryTagName::PlainTagName(vec![2]), TargetValue::Unencrypted("Value3".to_string()))));
+        let mut storage_iterator = storage.search(&_type1(), &query, None).unwrap();
+
+        assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id(1));
+        assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id(2));
+        assert!(storage_iterator.next().unwrap().is_none());
+    }
+
+    #[test]
+    fn inmem_storage_search_works_for_exists_ilike_and_regex() {
+        let storage = _storage();
+        storage.add(&_type1(), &_id(1), &_value(1), &[Tag::PlainText(vec![2], "Value1".to_string())]).unwrap();
+        storage.add(&_type1(), &_id(2), &_value(2), &[Tag::PlainText(vec![2], "value22".to_string())]).unwrap();
+        storage.add(&_type1(), &_id(3), &_value(3), &[Tag::Encrypted(vec![1], vec![1])]).unwrap();
+
+        let query = Operator::Exists(QueryTagName::PlainTagName(vec![2]), false);
+        let mut storage_iterator = storage.search(&_type1(), &query, None).unwrap();
+
+        assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id(3));
+        assert!(storage_iterator.next().unwrap().is_none());
+
+        let query = Operator::ILike(QueryTagName::PlainTagName(vec![2]), TargetValue::Unencrypted("VALUE%".to_string()));
+        let mut storage_iterator = storage.search(&_type1(), &query, None).unwrap();
+
+        assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id(1));
+        assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id(2));
+        assert!(storage_iterator.next().unwrap().is_none());
+
+        let query = Operator::Regex(QueryTagName::PlainTagName(vec![2]), TargetValue::Unencrypted("^value[0-9]{2}$".to_string()));
+        let mut storage_iterator = storage.search(&_type1(), &query, None).unwrap();
+
+        assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id(2));
+        assert!(storage_iterator.next().unwrap().is_none());
+
+        let query = Operator::Regex(QueryTagName::EncryptedTagName(vec![1]), TargetValue::Encrypted(vec![1]));
+        let res = storage.search(&_type1(), &query, None);
+        assert_kind!(IndyErrorKind::WalletQueryError, res);
+    }
+
+    #[test]
+    fn inmem_storage_aggregate_works() {
+        let storage = _storage();
+        storage.add(&_type1(), &_id(1), &_value(1), &[Tag::PlainText(vec![2], "a".to_string()), Tag::PlainText(vec![3], "x".to_string())]).unwrap();
+        storage.add(&_type1(), &_id(2), &_value(2), &[Tag::PlainText(vec![2], "a".to_string())]).unwrap();
+        storage.add(&_type1(), &_id(3), &_value(3), &[Tag::PlainText(vec![2], "b".to_string()), Tag::Encrypted(vec![3], vec![3])]).unwrap();
+        storage.add(&_type2(), &_id(4), &_value(4), &[Tag::PlainText(vec![2], "a".to_string())]).unwrap();
+
+        let groups = storage.aggregate(&_type1(), &Operator::And(vec![]), &[vec![2], vec![3]]).unwrap();
+        assert_eq!(groups, vec![
+            (vec![Some("a".to_string()), None], 1),
+            (vec![Some("a".to_string()), Some("x".to_string())], 1),
+            (vec![Some("b".to_string()), None], 1),
+        ]);
+
+        let query = Operator::Eq(QueryTagName::PlainTagName(vec![2]), TargetValue::Unencrypted("a".to_string()));
+        let groups = storage.aggregate(&_type1(), &query, &[]).unwrap();
+        assert_eq!(groups, vec![(vec![], 2)]);
+    }
+
+    #[test]
+    fn inmem_storage_delete_expired_works() {
+        let storage = _storage();
+        storage.add(&_type1(), &_id(1), &_value(1), &[Tag::PlainText(vec![9], "0000000100".to_string())]).unwrap();
+        storage.add(&_type1(), &_id(2), &_value(2), &[Tag::PlainText(vec![9], "0000000200".to_string())]).unwrap();
+        storage.add(&_type2(), &_id(3), &_value(3), &[Tag::PlainText(vec![2], "0000000100".to_string())]).unwrap();
+
+        assert_eq!(storage.delete_expired(&[9], "0000000100").unwrap(), 1);
+
+        let res = storage.get(&_type1(), &_id(1), r##"{}"##);
+        assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        storage.get(&_type1(), &_id(2), r##"{}"##).unwrap();
+        storage.get(&_type2(), &_id(3), r##"{}"##).unwrap();
+
+        assert_eq!(storage.delete_expired(&[9], "0000000100").unwrap(), 0);
+    }
+
+    #[test]
+    fn inmem_storage_search_works_for_sort_and_pagination() {
+        let storage = _storage();
+        for i in 1..5 {
+            storage.add(&_type1(), &_id(i), &_value(i), &[Tag::PlainText(vec![2], format!("Value{}", i))]).unwrap();
+        }
+
+        let options = json!({
+            "retrieveTotalCount": true,
+            "sortBy": [{"tagName": format!("~{}", base64::encode(&[2])), "order": "desc"}],
+            "skip": 1,
+            "limit": 2
+        }).to_string();
+        let mut storage_iterator = storage.search(&_type1(), &Operator::And(vec![]), Some(&options)).unwrap();
+
+        assert_eq!(storage_iterator.get_total_count().unwrap(), Some(4));
+        assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id(3));
+        assert_eq!(storage_iterator.next().unwrap().unwrap().id, _id(2));
+        assert!(storage_iterator.next().unwrap().is_none());
+    }
+
+    #[test]
+    fn inmem_storage_search_fails_for_invalid_query() {
+        let storage = _storage();
+
+        let query = Operator::Or(vec![
+            Operator::And(vec![]),
+            Operator::Gt(QueryTagName::EncryptedTagName(vec![1]), TargetValue::Encrypted(vec![1])),
+        ]);
+        let res = storage.search(&_type1(), &query, None);
+        assert_kind!(IndyErrorKind::WalletQueryError, res);
+    }
+
+    #[test]
+    fn like_works() {
+        assert!(_like(b"Value", b"value"));
+        assert!(_like(b"Value", b"V%"));
+        assert!(_like(b"Value", b"%a%e"));
+        assert!(_like(b"Value", b"_alu_"));
+        assert!(_like(b"", b"%"));
+        assert!(!_like(b"Value", b"Val"));
+        assert!(!_like(b"Value", b"%x%"));
+        assert!(!_like(b"Value", b"_alue_"));
+    }
+
+    fn _storage() -> Box<dyn WalletStorage> {
+        let storage_type = InmemStorageType::new();
+        storage_type.create_storage("inmem", None, None, &_metadata()).unwrap();
+        storage_type.open_storage("inmem", None, None).unwrap()
+    }
+
+    fn _metadata() -> Vec<u8> {
+        vec![1, 2, 3, 4, 5, 6, 7, 8]
+    }
+
+    fn _type(i: u8) -> Vec<u8> {
+        vec![i, 1 + i, 2 + i]
+    }
+
+    fn _type1() -> Vec<u8> {
+        _type(1)
+    }
+
+    fn _type2() -> Vec<u8> {
+        _type(2)
+    }
+
+    fn _id(i: u8) -> Vec<u8> {
+        vec![3 + i, 4 + i, 5 + i]
+    }
+
+    fn _id1() -> Vec<u8> {
+        _id(1)
+    }
+
+    fn _id2() -> Vec<u8> {
+        _id(2)
+    }
+
+    fn _value(i: u8) -> EncryptedValue {
+        EncryptedValue { data: vec![6 + i, 7 + i, 8 + i], key: vec![9 + i, 10 + i, 11 + i] }
+    }
+
+    fn _value1() -> EncryptedValue {
+        _value(1)
+    }
+
+    fn _value2() -> EncryptedValue {
+        _value(2)
+    }
+
+    fn _tags() -> Vec<Tag> {
+        vec![
+            Tag::Encrypted(vec![1, 5, 8], vec![3, 5, 6]),
+            Tag::PlainText(vec![1, 5, 8, 1], "Plain value".to_string())
+        ]
+    }
+
+    fn _new_tags() -> Vec<Tag> {
+        vec![
+            Tag::Encrypted(vec![1, 1, 1], vec![2, 2, 2]),
+            Tag::PlainText(vec![1, 1, 1], String::from("tag_value_3"))
+        ]
+    }
+
+    fn _sort(mut v: Vec<Tag>) -> Vec<Tag> {
+        v.sort();
+        v
+    }
+}
@@ -0,0 +1,268 @@
+use indy_api_types::errors::prelude::*;
+
+// Versioned schema migration of wallet storages.
+// Storage keeps version of the last applied step. Pending steps are applied in order of
+// their versions inside of one storage transaction together with the version update,
+// so storage is either fully migrated or left untouched.
+//
+// Only storages implemented in Rust can be migrated by this module. Plugged storages own their schema:
+// libindy passes them storage config with the same "migration" options untouched and expects them
+// to migrate their data inside of open_wallet handler.
+
+#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+pub struct MigrationOptions {
+    // Apply pending steps and roll them back to check that migration is possible
+    #[serde(default)]
+    pub dry_run: bool,
+    // Ask storage to make a copy of its data before applying pending steps
+    #[serde(default)]
+    pub backup: bool,
+}
+
+pub struct MigrationStep<T: ?Sized> {
+    pub version: u32,
+    pub description: &'static str,
+    pub apply: fn(&T) -> IndyResult<()>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MigrationResult {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub dry_run: bool,
+}
+
+impl MigrationResult {
+    pub fn is_migrated(&self) -> bool {
+        !self.dry_run && self.from_version != self.to_version
+    }
+}
+
+pub trait MigrationTarget {
+    fn get_schema_version(&self) -> IndyResult<u32>;
+    fn set_schema_version(&self, version: u32) -> IndyResult<()>;
+    fn begin(&self) -> IndyResult<()>;
+    fn commit(&self) -> IndyResult<()>;
+    fn rollback(&self) -> IndyResult<()>;
+    fn backup(&self, _version: u32) -> IndyResult<()> {
+        Err(err_msg(IndyErrorKind::InvalidState, "Storage doesn't support backup before migration"))
+    }
+}
+
+pub fn migrate<T: MigrationTarget + ?Sized>(target: &T, steps: &[MigrationStep<T>], options: &MigrationOptions) -> IndyResult<MigrationResult> {
+    _check_steps(steps)?;
+
+    let current_version = target.get_schema_version()?;
+    let latest_version = steps.last().map(|step| step.version).unwrap_or(0);
+
+    if current_version > latest_version {
+        return Err(err_msg(IndyErrorKind::WalletStorageError,
+                           format!("Wallet storage schema version {} is newer than supported version {}", current_version, latest_version)));
+    }
+
+    let result = MigrationResult {
+        from_version: current_version,
+        to_version: latest_version,
+        dry_run: options.dry_run,
+    };
+
+    let pending_steps: Vec<&MigrationStep<T>> = steps.iter()
+        .filter(|step| step.version > current_version)
+        .collect();
+
+    if pending_steps.is_empty() {
+        return Ok(result);
+    }
+
+    if options.backup && !options.dry_run {
+        target.backup(current_version)
+            .map_err(|err| err.extend("Backup before migration failed"))?;
+    }
+
+    target.begin()?;
+
+    for step in pending_steps {
+        debug!("migrate: applying step {} \"{}\", dry run: {}", step.version, step.description, options.dry_run);
+
+        let res = (step.apply)(target)
+            .and_then(|_| target.set_schema_version(step.version));
+
+        if let Err(err) = res {
+            target.rollback()?;
+            return Err(err.extend(format!("Migration to schema version {} failed", step.version)));
+        }
+    }
+
+    if options.dry_run {
+        target.rollback()?;
+    } else {
+        target.commit()?;
+    }
+
+    Ok(result)
+}
+
+fn _check_steps<T: ?Sized>(steps: &[MigrationStep<T>]) -> IndyResult<()> {
+    let mut previous_version = 0;
+
+    for step in steps {
+        if step.version <= previous_version {
+            return Err(err_msg(IndyErrorKind::InvalidState, "Migration steps must have unique positive versions in ascending order"));
+        }
+        previous_version = step.version;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct MockTarget {
+        version: RefCell<u32>,
+        applied: RefCell<Vec<u32>>,
+        backups: RefCell<Vec<u32>>,
+        snapshot: RefCell<Option<(u32, Vec<u32>)>>,
+    }
+
+    impl MigrationTarget for MockTarget {
+        fn get_schema_version(&self) -> IndyResult<u32> {
+            Ok(*self.version.borrow())
+        }
+
+        fn set_schema_version(&self, version: u32) -> IndyResult<()> {
+            *self.version.borrow_mut() = version;
+            Ok(())
+        }
+
+        fn begin(&self) -> IndyResult<()> {
+            *self.snapshot.borrow_mut() = Some((*self.version.borrow(), self.applied.borrow().clone()));
+            Ok(())
+        }
+
+        fn commit(&self) -> IndyResult<()> {
+            self.snapshot.borrow_mut().take().unwrap();
+            Ok(())
+        }
+
+        fn rollback(&self) -> IndyResult<()> {
+            let (version, applied) = self.snapshot.borrow_mut().take().unwrap();
+            *self.version.borrow_mut() = version;
+            *self.applied.borrow_mut() = applied;
+            Ok(())
+        }
+
+        fn backup(&self, version: u32) -> IndyResult<()> {
+            self.backups.borrow_mut().push(version);
+            Ok(())
+        }
+    }
+
+    fn _apply(target: &MockTarget) -> IndyResult<()> {
+        let version = *target.version.borrow() + 1;
+        target.applied.borrow_mut().push(version);
+        Ok(())
+    }
+
+    fn _fail(_target: &MockTarget) -> IndyResult<()> {
+        Err(err_msg(IndyErrorKind::WalletStorageError, "Step failed"))
+    }
+
+    fn _steps() -> Vec<MigrationStep<MockTarget>> {
+        vec![
+            MigrationStep { version: 1, description: "first", apply: _apply },
+            MigrationStep { version: 2, description: "second", apply: _apply },
+            MigrationStep { version: 3, description: "third", apply: _apply },
+        ]
+    }
+
+    #[test]
+    fn migrate_works() {
+        let target = MockTarget::default();
+
+        let result = migrate(&target, &_steps(), &MigrationOptions::default()).unwrap();
+
+        assert_eq!(result, MigrationResult { from_version: 0, to_version: 3, dry_run: false });
+        assert!(result.is_migrated());
+        assert_eq!(*target.version.borrow(), 3);
+        assert_eq!(*target.applied.borrow(), vec![1, 2, 3]);
+        assert!(target.backups.borrow().is_empty());
+    }
+
+    #[test]
+    fn migrate_works_for_partially_migrated() {
+        let target = MockTarget::default();
+        *target.version.borrow_mut() = 2;
+
+        let result = migrate(&target, &_steps(), &MigrationOptions { dry_run: false, backup: true }).unwrap();
+
+        assert_eq!(result, MigrationResult { from_version: 2, to_version: 3, dry_run: false });
+        assert_eq!(*target.applied.borrow(), vec![3]);
+        assert_eq!(*target.backups.borrow(), vec![2]);
+    }
+
+    #[test]
+    fn migrate_works_for_up_to_date() {
+        let target = MockTarget::default();
+        *target.version.borrow_mut() = 3;
+
+        let result = migrate(&target, &_steps(), &MigrationOptions { dry_run: false, backup: true }).unwrap();
+
+        assert!(!result.is_migrated());
+        assert!(target.applied.borrow().is_empty());
+        assert!(target.backups.borrow().is_empty());
+    }
+
+    #[test]
+    fn migrate_works_for_dry_run() {
+        let target = MockTarget::default();
+
+        let result = migrate(&target, &_steps(), &MigrationOptions { dry_run: true, backup: true }).unwrap();
+
+        assert_eq!(result, MigrationResult { from_version: 0, to_version: 3, dry_run: true });
+        assert!(!result.is_migrated());
+        assert_eq!(*target.version.borrow(), 0);
+        assert!(target.applied.borrow().is_empty());
+        assert!(target.backups.borrow().is_empty());
+    }
+
+    #[test]
+    fn migrate_rolls_back_on_error() {
+        let target = MockTarget::default();
+        let steps = vec![
+            MigrationStep { version: 1, description: "first", apply: _apply },
+            MigrationStep { version: 2, description: "failed", apply: _fail },
+        ];
+
+        let res = migrate(&target, &steps, &MigrationOptions::default());
+        assert_kind!(IndyErrorKind::WalletStorageError, res);
+
+        assert_eq!(*target.version.borrow(), 0);
+        assert!(target.applied.borrow().is_empty());
+    }
+
+    #[test]
+    fn migrate_fails_for_newer_version() {
+        let target = MockTarget::default();
+        *target.version.borrow_mut() = 4;
+
+        let res = migrate(&target, &_steps(), &MigrationOptions::default());
+        assert_kind!(IndyErrorKind::WalletStorageError, res);
+    }
+
+    #[test]
+    fn migrate_fails_for_unordered_steps() {
+        let target = MockTarget::default();
+        let steps = vec![
+            MigrationStep { version: 2, description: "second", apply: _apply },
+            MigrationStep { version: 1, description: "first", apply: _apply },
+        ];
+
+        let res = migrate(&target, &steps, &MigrationOptions::default());
+        assert_kind!(IndyErrorKind::InvalidState, res);
+    }
+}
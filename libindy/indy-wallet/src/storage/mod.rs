@@ -1,8 +1,14 @@
 pub mod default;
+pub mod inmem;
+pub mod migration;
 pub mod plugged;
 
+use std::collections::BTreeMap;
+
 use indy_api_types::errors::prelude::*;
+use indy_api_types::wallet::{WalletAggregateRecords, WalletBeginTransaction, WalletCommitTransaction, WalletFreeAggregation, WalletRollbackTransaction};
 use crate::language;
+use crate::SearchOptions;
 use crate::wallet::EncryptedValue;
 
 #[derive(Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
@@ -11,7 +17,7 @@ pub enum Tag {
     PlainText(Vec<u8>, String)
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum TagName {
     OfEncrypted(Vec<u8>),
     OfPlain(Vec<u8>),
@@ -26,7 +32,7 @@ pub struct StorageRecord {
 }
 
 impl StorageRecord {
-    fn new(id: Vec<u8>, value: Option<EncryptedValue>, type_: Option<Vec<u8>>, tags: Option<Vec<Tag>>) -> Self {
+    pub fn new(id: Vec<u8>, value: Option<EncryptedValue>, type_: Option<Vec<u8>>, tags: Option<Vec<Tag>>) -> Self {
         Self {
             id,
             value,
@@ -36,6 +42,24 @@ impl StorageRecord {
     }
 }
 
+// Single record modification executed as a part of atomic batch
+#[derive(Clone, Debug)]
+pub enum StorageOperation {
+    Add(Vec<u8>, Vec<u8>, EncryptedValue, Vec<Tag>), // type, id, value, tags
+    Update(Vec<u8>, Vec<u8>, EncryptedValue), // type, id, value
+    AddTags(Vec<u8>, Vec<u8>, Vec<Tag>), // type, id, tags
+    UpdateTags(Vec<u8>, Vec<u8>, Vec<Tag>), // type, id, tags
+    DeleteTags(Vec<u8>, Vec<u8>, Vec<TagName>), // type, id, tag names
+    Delete(Vec<u8>, Vec<u8>), // type, id
+}
+
+// Structural problem found by storage consistency check
+#[derive(Clone, Debug, PartialEq)]
+pub enum StorageIssue {
+    OrphanedTags(usize), // number of tags without item
+    DuplicateItem(Vec<u8>, Vec<u8>, usize), // type, id, number of items
+}
+
 pub trait StorageIterator {
     fn next(&mut self) -> Result<Option<StorageRecord>, IndyError>;
     fn get_total_count(&self) -> Result<Option<usize>, IndyError>;
@@ -49,15 +73,121 @@ pub trait WalletStorage {
     fn update_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> Result<(), IndyError>;
     fn delete_tags(&self, type_: &[u8], id: &[u8], tag_names: &[TagName]) -> Result<(), IndyError>;
     fn delete(&self, type_: &[u8], id: &[u8]) -> Result<(), IndyError>;
+    fn batch(&self, operations: &[StorageOperation]) -> Result<(), IndyError>;
+    // Applies chunks of operations returned by `next` until it returns None inside of one transaction,
+    // so failure of any chunk or of `next` leaves storage untouched. Reads made by `next` may not see
+    // operations of previous chunks. Storages that can't keep transaction open collect all chunks into one batch
+    fn batch_chunked(&self, next: &mut dyn FnMut() -> Result<Option<Vec<StorageOperation>>, IndyError>) -> Result<(), IndyError> {
+        let mut operations = Vec::new();
+
+        while let Some(chunk) = next()? {
+            operations.extend(chunk);
+        }
+
+        self.batch(&operations)
+    }
     fn get_storage_metadata(&self) -> Result<Vec<u8>, IndyError>;
     fn set_storage_metadata(&self, metadata: &[u8]) -> Result<(), IndyError>;
     fn get_all(&self) -> Result<Box<dyn StorageIterator>, IndyError>;
     fn search(&self, type_: &[u8], query: &language::Operator, options: Option<&str>) -> Result<Box<dyn StorageIterator>, IndyError>;
     fn close(&mut self) -> Result<(), IndyError>;
+    // Storages that evaluate `$exists` on plain tags get expired records filtered out by query.
+    // Others, like plugged storages implemented before record expiry was added, return expired
+    // records and wallet drops them after fetching
+    fn supports_exists_query(&self) -> bool {
+        false
+    }
+    // Storages without transactions fail on batch. Wallet applies its internal multi-step
+    // updates sequentially for them
+    fn supports_transactions(&self) -> bool {
+        true
+    }
+    // Finds structural problems that can't be detected through records and removes them if repair is set.
+    // Storages that keep records consistent by design have nothing to report
+    fn check(&self, _repair: bool) -> Result<Vec<StorageIssue>, IndyError> {
+        Ok(Vec::new())
+    }
+    // Counts all records. Storages that can't count natively count records returned by get_all
+    fn count(&self) -> Result<usize, IndyError> {
+        let mut iterator = self.get_all()?;
+        let mut count = 0;
+
+        while iterator.next()?.is_some() {
+            count += 1;
+        }
+
+        Ok(count)
+    }
+    // Returns marker that grows on every modification of records. Storages without
+    // modification tracking always return 0 and are synchronized by full copy
+    fn get_modification_marker(&self) -> Result<u64, IndyError> {
+        Ok(0)
+    }
+    // Returns all records added or modified after marker. Deleted records are not returned
+    fn get_modified_since(&self, _marker: u64) -> Result<Box<dyn StorageIterator>, IndyError> {
+        self.get_all()
+    }
+    // Returns type and id of records deleted after marker and not added again since then.
+    // Storages that don't log deletes return None and deleted records are found by comparing ids
+    fn get_deleted_since(&self, _marker: u64) -> Result<Option<Vec<(Vec<u8>, Vec<u8>)>>, IndyError> {
+        Ok(None)
+    }
+    // Deletes records which plain tag with given encrypted name has value not greater than
+    // given one. Used to sweep expired records. Storages that can't do it natively keep
+    // expired records, wallet hides them from get and search anyway
+    fn delete_expired(&self, _expiry_tag_name: &[u8], _now: &str) -> Result<usize, IndyError> {
+        Ok(0)
+    }
+    // Counts records matching query grouped by values of plain tags with given encrypted names.
+    // Records without a tag fall into group with None value. Storages that can't compute it
+    // natively count records returned by search
+    fn aggregate(&self, type_: &[u8], query: &language::Operator, group_by: &[Vec<u8>]) -> Result<Vec<(Vec<Option<String>>, usize)>, IndyError> {
+        aggregate_by_search(self, type_, query, group_by)
+    }
+}
+
+// Counts records returned by search, used by storages without native aggregation
+pub fn aggregate_by_search<S: WalletStorage + ?Sized>(storage: &S, type_: &[u8], query: &language::Operator, group_by: &[Vec<u8>]) -> Result<Vec<(Vec<Option<String>>, usize)>, IndyError> {
+    let options = SearchOptions {
+        retrieve_records: true,
+        retrieve_total_count: false,
+        retrieve_type: false,
+        retrieve_value: false,
+        retrieve_tags: !group_by.is_empty(),
+        sort_by: None,
+        skip: None,
+        limit: None,
+    };
+    let options = serde_json::to_string(&options)
+        .to_indy(IndyErrorKind::InvalidState, "Cannot serialize search options")?;
+
+    let mut groups: BTreeMap<Vec<Option<String>>, usize> = BTreeMap::new();
+    let mut iterator = storage.search(type_, query, Some(&options))?;
+
+    while let Some(record) = iterator.next()? {
+        let tags = record.tags.unwrap_or_default();
+        let values = group_by.iter()
+            .map(|name| tags.iter()
+                .filter_map(|tag| match tag {
+                    Tag::PlainText(ref tag_name, ref value) if tag_name == name => Some(value.clone()),
+                    _ => None
+                })
+                .next())
+            .collect();
+        *groups.entry(values).or_insert(0) += 1;
+    }
+
+    Ok(groups.into_iter().collect())
 }
 
 pub trait WalletStorageType {
     fn create_storage(&self, id: &str, config: Option<&str>, credentials: Option<&str>, metadata: &[u8]) -> Result<(), IndyError>;
     fn open_storage(&self, id: &str, config: Option<&str>, credentials: Option<&str>) -> Result<Box<dyn WalletStorage>, IndyError>;
     fn delete_storage(&self, id: &str, config: Option<&str>, credentials: Option<&str>) -> Result<(), IndyError>;
+    fn register_transaction_hooks(&self, _begin: WalletBeginTransaction, _commit: WalletCommitTransaction, _rollback: WalletRollbackTransaction) -> Result<(), IndyError> {
+        Err(err_msg(IndyErrorKind::InvalidState, "Transaction hooks can be registered only for plugged wallet storage"))
+    }
+    fn register_aggregation(&self, _aggregate: WalletAggregateRecords, _free: WalletFreeAggregation) -> Result<(), IndyError> {
+        Err(err_msg(IndyErrorKind::InvalidState, "Aggregation handlers can be registered only for plugged wallet storage"))
+    }
 }
\ No newline at end of file
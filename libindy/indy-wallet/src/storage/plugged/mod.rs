@@ -1,4 +1,5 @@
 use std::{slice, str};
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ffi::{CStr, CString};
 use std::ptr;
@@ -12,8 +13,8 @@ use indy_api_types::errors::prelude::*;
 use crate::language;
 use indy_utils::crypto::base64;
 
-use super::{EncryptedValue, StorageIterator, StorageRecord, Tag, TagName, WalletStorage, WalletStorageType};
-use super::super::{RecordOptions, SearchOptions};
+use super::{aggregate_by_search, EncryptedValue, StorageIterator, StorageOperation, StorageRecord, Tag, TagName, WalletStorage, WalletStorageType};
+use super::super::{RecordOptions, SearchOptions, SortOrder};
 
 #[derive(Debug, Deserialize)]
 pub struct PluggedWalletJSONValue {
@@ -211,6 +212,35 @@ impl Drop for PluggedStorageIterator {
     }
 }
 
+// Holds records of plugin search sorted and paged by wrapper
+struct PagedStorageIterator {
+    records: ::std::vec::IntoIter<StorageRecord>,
+    total_count: Option<usize>,
+}
+
+impl StorageIterator for PagedStorageIterator {
+    fn next(&mut self) -> IndyResult<Option<StorageRecord>> {
+        Ok(self.records.next())
+    }
+
+    fn get_total_count(&self) -> IndyResult<Option<usize>> {
+        Ok(self.total_count)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct TransactionHooks {
+    begin_handler: WalletBeginTransaction,
+    commit_handler: WalletCommitTransaction,
+    rollback_handler: WalletRollbackTransaction,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct AggregationHandlers {
+    aggregate_handler: WalletAggregateRecords,
+    free_handler: WalletFreeAggregation,
+}
+
 #[derive(PartialEq, Debug)]
 struct PluggedStorage {
     handle: i32,
@@ -235,6 +265,8 @@ struct PluggedStorage {
     fetch_search_next_record_handler: WalletFetchSearchNextRecord,
     free_search_handler: WalletFreeSearch,
     close_handler: WalletClose,
+    transaction_hooks: Option<TransactionHooks>,
+    aggregation_handlers: Option<AggregationHandlers>,
 }
 
 impl PluggedStorage {
@@ -259,7 +291,9 @@ impl PluggedStorage {
            get_search_total_count_handler: WalletGetSearchTotalCount,
            fetch_search_next_record_handler: WalletFetchSearchNextRecord,
            free_search_handler: WalletFreeSearch,
-           close_handler: WalletClose) -> PluggedStorage {
+           close_handler: WalletClose,
+           transaction_hooks: Option<TransactionHooks>,
+           aggregation_handlers: Option<AggregationHandlers>) -> PluggedStorage {
         PluggedStorage {
             handle,
             add_record_handler,
@@ -283,7 +317,63 @@ impl PluggedStorage {
             fetch_search_next_record_handler,
             free_search_handler,
             close_handler,
+            transaction_hooks,
+            aggregation_handlers,
+        }
+    }
+
+    // Runs f between begin and commit hooks, transaction is rolled back if f fails
+    fn _in_transaction<F>(&self, f: F) -> IndyResult<()> where F: FnOnce() -> IndyResult<()> {
+        let hooks = self.transaction_hooks
+            .ok_or_else(|| err_msg(IndyErrorKind::WalletStorageError, "Wallet storage doesn't support transactions. Transaction hooks are not registered for this storage type"))?;
+
+        let err = (hooks.begin_handler)(self.handle);
+
+        if err != ErrorCode::Success {
+            return Err(err.into());
+        }
+
+        if let Err(err) = f() {
+            (hooks.rollback_handler)(self.handle);
+            return Err(err);
+        }
+
+        let err = (hooks.commit_handler)(self.handle);
+
+        if err != ErrorCode::Success {
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+
+    fn _execute(&self, operation: &StorageOperation) -> IndyResult<()> {
+        match *operation {
+            StorageOperation::Add(ref type_, ref id, ref value, ref tags) => self.add(type_, id, value, tags),
+            StorageOperation::Update(ref type_, ref id, ref value) => self.update(type_, id, value),
+            StorageOperation::AddTags(ref type_, ref id, ref tags) => self.add_tags(type_, id, tags),
+            StorageOperation::UpdateTags(ref type_, ref id, ref tags) => self.update_tags(type_, id, tags),
+            StorageOperation::DeleteTags(ref type_, ref id, ref tag_names) => self.delete_tags(type_, id, tag_names),
+            StorageOperation::Delete(ref type_, ref id) => self.delete(type_, id),
+        }
+    }
+
+    fn _search(&self, type_: &CStr, query: &CStr, options: &str, search_options: SearchOptions) -> IndyResult<PluggedStorageIterator> {
+        let options = CString::new(options)?;
+
+        let mut search_handle: SearchHandle = INVALID_SEARCH_HANDLE;
+
+        let err = (self.search_records_handler)(self.handle,
+                                                type_.as_ptr(),
+                                                query.as_ptr(),
+                                                options.as_ptr(),
+                                                &mut search_handle.0);
+
+        if err != ErrorCode::Success {
+            return Err(err.into());
         }
+
+        Ok(PluggedStorageIterator::new(&self, search_handle, search_options))
     }
 }
 
@@ -301,6 +391,40 @@ fn _tags_to_json(tags: &[Tag]) -> IndyResult<String> {
         .to_indy(IndyErrorKind::InvalidState, "Unable to serialize tags as json")
 }
 
+fn _decode_plain_tag_name(name: &str) -> IndyResult<Vec<u8>> {
+    if !name.starts_with('~') {
+        return Err(err_msg(IndyErrorKind::WalletQueryError, "Sorting is supported only by plaintext tags"));
+    }
+
+    base64::decode(&name[1..])
+}
+
+// Missing tag value is ordered first like NULL in SQLite
+fn _compare(a: &StorageRecord, b: &StorageRecord, sort_by: &[(Vec<u8>, SortOrder)]) -> ::std::cmp::Ordering {
+    for &(ref name, order) in sort_by {
+        let ordering = _plain_tag_value(a, name).cmp(&_plain_tag_value(b, name));
+        let ordering = match order {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        };
+
+        if ordering != ::std::cmp::Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    ::std::cmp::Ordering::Equal
+}
+
+fn _plain_tag_value<'a>(record: &'a StorageRecord, name: &[u8]) -> Option<&'a String> {
+    record.tags.as_ref()?.iter()
+        .filter_map(|tag| match *tag {
+            Tag::PlainText(ref tag_name, ref value) if tag_name[..] == name[..] => Some(value),
+            _ => None
+        })
+        .next()
+}
+
 fn _tags_from_json(json: &str) -> IndyResult<Vec<Tag>> {
     let string_tags: HashMap<String, String> = serde_json::from_str(json)
         .to_indy(IndyErrorKind::InvalidState, "Unable to deserialize tags from json")?;
@@ -518,6 +642,33 @@ impl WalletStorage for PluggedStorage {
         Ok(())
     }
 
+    fn batch(&self, operations: &[StorageOperation]) -> IndyResult<()> {
+        self._in_transaction(|| operations
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, operation)|
+                self._execute(operation)
+                    .map_err(|err| err.extend(format!("Batch operation {} failed", index)))))
+    }
+
+    // Chunks are executed between begin and commit hooks, so reads made by `next` see previous
+    // chunks if plugin reads through the same transaction
+    fn batch_chunked(&self, next: &mut dyn FnMut() -> IndyResult<Option<Vec<StorageOperation>>>) -> IndyResult<()> {
+        self._in_transaction(|| {
+            let mut index = 0;
+
+            while let Some(operations) = next()? {
+                for operation in operations.iter() {
+                    self._execute(operation)
+                        .map_err(|err| err.extend(format!("Batch operation {} failed", index)))?;
+                    index += 1;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
     fn get_storage_metadata(&self) -> IndyResult<Vec<u8>> {
         let mut metadata_ptr: *const c_char = ptr::null_mut();
         let mut metadata_handle = -1;
@@ -572,38 +723,116 @@ impl WalletStorage for PluggedStorage {
                     retrieve_type: true,
                     retrieve_value: true,
                     retrieve_tags: true,
+                    sort_by: None,
+                    skip: None,
+                    limit: None,
                 },
             )
         ))
     }
 
+    // Plugins implemented before sorting and pagination were added ignore sortBy, skip and limit,
+    // so they are never passed to plugin. All matching records are fetched then and wrapper
+    // sorts and pages them
     fn search(&self, type_: &[u8], query: &language::Operator, options: Option<&str>) -> IndyResult<Box<dyn StorageIterator>> {
         let type_ = CString::new(base64::encode(type_))?;
         let query = CString::new(query.to_string())?;
-        let options_cstr = CString::new(options.unwrap_or("{}"))?;
 
-        let options: SearchOptions = serde_json::from_str(options.unwrap_or("{}"))
+        let search_options: SearchOptions = serde_json::from_str(options.unwrap_or("{}"))
             .to_indy(IndyErrorKind::InvalidStructure, "Search options is malformed json")?;
 
-        let mut search_handle: SearchHandle = INVALID_SEARCH_HANDLE;
+        let paged = search_options.retrieve_records &&
+            (search_options.sort_by.is_some() || search_options.skip.is_some() || search_options.limit.is_some());
 
-        let err = (self.search_records_handler)(self.handle,
-                                                type_.as_ptr(),
-                                                query.as_ptr(),
-                                                options_cstr.as_ptr(),
-                                                &mut search_handle.0);
+        if !paged {
+            return Ok(Box::new(self._search(&type_, &query, options.unwrap_or("{}"), search_options)?));
+        }
+
+        let sort_by = match search_options.sort_by {
+            Some(ref sort_by) => sort_by.iter()
+                .map(|sort| Ok((_decode_plain_tag_name(&sort.tag_name)?, sort.order)))
+                .collect::<IndyResult<Vec<(Vec<u8>, SortOrder)>>>()?,
+            None => Vec::new(),
+        };
+
+        let plugin_options = SearchOptions {
+            retrieve_records: true,
+            retrieve_total_count: search_options.retrieve_total_count,
+            retrieve_type: search_options.retrieve_type,
+            retrieve_value: search_options.retrieve_value,
+            retrieve_tags: search_options.retrieve_tags || !sort_by.is_empty(),
+            sort_by: None,
+            skip: None,
+            limit: None,
+        };
+        let plugin_options_json = serde_json::to_string(&plugin_options)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize search options")?;
+
+        let mut storage_iterator = self._search(&type_, &query, &plugin_options_json, plugin_options)?;
+
+        let total_count = storage_iterator.get_total_count()?;
+
+        let mut records = Vec::new();
+        while let Some(record) = storage_iterator.next()? {
+            records.push(record);
+        }
+
+        // Sort is stable, so records with equal tag values keep order of plugin
+        records.sort_by(|a, b| _compare(a, b, &sort_by));
+
+        let records: Vec<StorageRecord> = records.into_iter()
+            .skip(search_options.skip.unwrap_or(0))
+            .take(search_options.limit.unwrap_or(::std::usize::MAX))
+            .map(|record| if search_options.retrieve_tags { record } else { StorageRecord { tags: None, ..record } })
+            .collect();
+
+        Ok(Box::new(PagedStorageIterator { records: records.into_iter(), total_count }))
+    }
+
+    fn supports_transactions(&self) -> bool {
+        self.transaction_hooks.is_some()
+    }
+
+    // Storage without registered aggregation handlers counts records returned by search
+    fn aggregate(&self, type_: &[u8], query: &language::Operator, group_by: &[Vec<u8>]) -> IndyResult<Vec<(Vec<Option<String>>, usize)>> {
+        let handlers = match self.aggregation_handlers {
+            Some(handlers) => handlers,
+            None => return aggregate_by_search(self, type_, query, group_by),
+        };
+
+        let type_ = CString::new(base64::encode(type_))?;
+        let query = CString::new(query.to_string())?;
+
+        let group_by: Vec<String> = group_by.iter()
+            .map(|name| format!("~{}", base64::encode(name)))
+            .collect();
+        let group_by = CString::new(serde_json::to_string(&group_by)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize group by tag names")?)?;
+
+        let mut result_ptr: *const c_char = ptr::null_mut();
+        let mut result_handle = -1;
+
+        let err = (handlers.aggregate_handler)(self.handle,
+                                               type_.as_ptr(),
+                                               query.as_ptr(),
+                                               group_by.as_ptr(),
+                                               &mut result_ptr,
+                                               &mut result_handle);
 
         if err != ErrorCode::Success {
             return Err(err.into());
         }
 
-        Ok(Box::new(
-            PluggedStorageIterator::new(
-                &self,
-                search_handle,
-                options,
-            )
-        ))
+        let _result_free_helper = ResourceGuard::new(self.handle, result_handle, handlers.free_handler);
+
+        let result = unsafe {
+            CStr::from_ptr(result_ptr)
+                .to_str()
+                .to_indy(IndyErrorKind::InvalidState, "Aggregation result contains non-utf8 symbol")?
+        };
+
+        serde_json::from_str(result)
+            .to_indy(IndyErrorKind::InvalidState, "Invalid aggregation result json")
     }
 
     fn close(&mut self) -> IndyResult<()> {
@@ -655,6 +884,8 @@ pub struct PluggedStorageType {
     get_search_total_count_handler: WalletGetSearchTotalCount,
     fetch_search_next_record_handler: WalletFetchSearchNextRecord,
     free_search_handler: WalletFreeSearch,
+    transaction_hooks: RefCell<Option<TransactionHooks>>,
+    aggregation_handlers: RefCell<Option<AggregationHandlers>>,
 }
 
 
@@ -708,6 +939,8 @@ impl PluggedStorageType {
             get_search_total_count_handler,
             fetch_search_next_record_handler,
             free_search_handler,
+            transaction_hooks: RefCell::new(None),
+            aggregation_handlers: RefCell::new(None),
         }
     }
 }
@@ -781,7 +1014,9 @@ impl WalletStorageType for PluggedStorageType {
                 self.get_search_total_count_handler,
                 self.fetch_search_next_record_handler,
                 self.free_search_handler,
-                self.close_handler)))
+                self.close_handler,
+                *self.transaction_hooks.borrow(),
+                *self.aggregation_handlers.borrow())))
     }
 
     fn delete_storage(&self, id: &str, config: Option<&str>, credentials: Option<&str>) -> IndyResult<()> {
@@ -805,6 +1040,37 @@ impl WalletStorageType for PluggedStorageType {
 
         Ok(())
     }
+
+    fn register_transaction_hooks(&self, begin: WalletBeginTransaction, commit: WalletCommitTransaction, rollback: WalletRollbackTransaction) -> IndyResult<()> {
+        let mut transaction_hooks = self.transaction_hooks.borrow_mut();
+
+        if transaction_hooks.is_some() {
+            return Err(err_msg(IndyErrorKind::InvalidState, "Transaction hooks are already registered for this storage type"));
+        }
+
+        *transaction_hooks = Some(TransactionHooks {
+            begin_handler: begin,
+            commit_handler: commit,
+            rollback_handler: rollback,
+        });
+
+        Ok(())
+    }
+
+    fn register_aggregation(&self, aggregate: WalletAggregateRecords, free: WalletFreeAggregation) -> IndyResult<()> {
+        let mut aggregation_handlers = self.aggregation_handlers.borrow_mut();
+
+        if aggregation_handlers.is_some() {
+            return Err(err_msg(IndyErrorKind::InvalidState, "Aggregation handlers are already registered for this storage type"));
+        }
+
+        *aggregation_handlers = Some(AggregationHandlers {
+            aggregate_handler: aggregate,
+            free_handler: free,
+        });
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -867,6 +1133,8 @@ mod tests {
         GetSearchTotalCountHandler(i32, i32),
         FetchSearchNextRecordHandler(i32, i32),
         FreeSearchHandler(i32, i32),
+        AggregateRecordsHandler(i32, Option<String>, Option<String>, Option<String>),
+        FreeAggregationHandler(i32, i32),
     }
 
     fn _random_vector(len: usize) -> Vec<u8> {
@@ -913,6 +1181,7 @@ mod tests {
             ).unwrap();
             (str, data)
         });
+        static ref RETURN_AGGREGATION: CString = CString::new(r#"[[["issuer_1"], 2], [[null], 1]]"#).unwrap();
     );
 
     static RETURN_STORAGE_HANDLE: i32 = 1i32;
@@ -920,6 +1189,7 @@ mod tests {
     static RETURN_SEARCH_HANDLE: i32 = 3i32;
     static RETURN_METADATA_HANDLE: i32 = 4i32;
     static RETURN_SEARCH_TOTAL_COUNT: usize = 1024;
+    static RETURN_AGGREGATION_HANDLE: i32 = 5i32;
 
     fn _convert_c_string(str: *const c_char) -> Option<String> {
         if str != ptr::null() {
@@ -1340,6 +1610,41 @@ mod tests {
         ErrorCode::Success
     }
 
+    extern "C" fn _mock_aggregate_records_handler(storage_handle: i32,
+                                                  type_: *const c_char,
+                                                  query_json: *const c_char,
+                                                  group_by_json: *const c_char,
+                                                  result_json_p: *mut *const c_char,
+                                                  result_handle_p: *mut i32) -> ErrorCode {
+        DEBUG_VEC.write().unwrap().push(
+            Call::AggregateRecordsHandler(
+                storage_handle,
+                _convert_c_string(type_),
+                _convert_c_string(query_json),
+                _convert_c_string(group_by_json),
+            )
+        );
+
+        unsafe {
+            *result_json_p = RETURN_AGGREGATION.as_ptr();
+            *result_handle_p = RETURN_AGGREGATION_HANDLE;
+        }
+
+        ErrorCode::Success
+    }
+
+    extern "C" fn _mock_free_aggregation_handler(storage_handle: i32,
+                                                 result_handle: i32) -> ErrorCode {
+        DEBUG_VEC.write().unwrap().push(
+            Call::FreeAggregationHandler(
+                storage_handle,
+                result_handle,
+            )
+        );
+
+        ErrorCode::Success
+    }
+
     fn _create_storage_type() -> PluggedStorageType {
         PluggedStorageType::new(
             _mock_create_handler,
@@ -2185,4 +2490,55 @@ mod tests {
         assert_eq!(&expected_free_record_call, debug.get(6).unwrap());
         assert_eq!(&expected_free_search_call, debug.get(7).unwrap());
     }
+
+    #[test]
+    fn plugged_storage_aggregate_works() {
+        DEBUG_VEC.write().unwrap().clear();
+
+        let storage_type = _create_storage_type();
+        storage_type.register_aggregation(_mock_aggregate_records_handler, _mock_free_aggregation_handler).unwrap();
+        let storage = storage_type.open_storage("wallet1", None, Some("credentials")).unwrap();
+
+        DEBUG_VEC.write().unwrap().clear();
+
+        let type_ = _random_vector(32);
+        let tag_name = _random_vector(32);
+
+        let query = language::Operator::Eq(
+            language::TagName::PlainTagName(tag_name.clone()),
+            language::TargetValue::Unencrypted("value".to_string()),
+        );
+
+        let groups = storage.aggregate(&type_, &query, &[tag_name.clone()]).unwrap();
+        assert_eq!(groups, vec![
+            (vec![Some("issuer_1".to_string())], 2),
+            (vec![None], 1),
+        ]);
+
+        let expected_aggregate_call = Call::AggregateRecordsHandler(
+            RETURN_STORAGE_HANDLE,
+            Some(base64::encode(&type_)),
+            Some(query.to_string()),
+            Some(json!([format!("~{}", base64::encode(&tag_name))]).to_string()),
+        );
+        let expected_free_call = Call::FreeAggregationHandler(
+            RETURN_STORAGE_HANDLE,
+            RETURN_AGGREGATION_HANDLE,
+        );
+
+        let debug = DEBUG_VEC.read().unwrap();
+
+        assert_eq!(debug.len(), 2);
+        assert_eq!(&expected_aggregate_call, debug.get(0).unwrap());
+        assert_eq!(&expected_free_call, debug.get(1).unwrap());
+    }
+
+    #[test]
+    fn plugged_storage_type_register_aggregation_works_for_twice() {
+        let storage_type = _create_storage_type();
+        storage_type.register_aggregation(_mock_aggregate_records_handler, _mock_free_aggregation_handler).unwrap();
+
+        let res = storage_type.register_aggregation(_mock_aggregate_records_handler, _mock_free_aggregation_handler);
+        assert_kind!(IndyErrorKind::InvalidState, res);
+    }
 }
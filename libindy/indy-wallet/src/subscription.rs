@@ -0,0 +1,151 @@
+use std::rc::Rc;
+
+use indy_api_types::{SubscriptionHandle, WalletHandle};
+use indy_api_types::domain::wallet::Tags;
+use indy_utils::wql::Query;
+use regex::Regex;
+
+use crate::storage::inmem::_like;
+
+// Kind of record change reported to subscribers. Remove means that record is kept,
+// but its tags don't match subscription query anymore
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WalletChange {
+    Add,
+    Update,
+    Delete,
+    Remove,
+}
+
+impl WalletChange {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            WalletChange::Add => "add",
+            WalletChange::Update => "update",
+            WalletChange::Delete => "delete",
+            WalletChange::Remove => "remove",
+        }
+    }
+}
+
+// Change of record made by wallet. Tags are None if record is missing before or after the change
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct RecordChange {
+    pub type_: String,
+    pub id: String,
+    pub old_tags: Option<Tags>,
+    pub new_tags: Option<Tags>,
+}
+
+pub type WalletChangeCallback = Box<dyn Fn(SubscriptionHandle, WalletChange, &str) + Send>;
+
+pub(super) struct Subscription {
+    pub wallet_handle: WalletHandle,
+    pub type_: String,
+    pub query: Query,
+    pub callback: Rc<dyn Fn(SubscriptionHandle, WalletChange, &str) + Send>,
+}
+
+impl Subscription {
+    pub fn new(wallet_handle: WalletHandle, type_: &str, query: Query, callback: WalletChangeCallback) -> Subscription {
+        Subscription {
+            wallet_handle,
+            type_: type_.to_string(),
+            query,
+            callback: Rc::from(callback),
+        }
+    }
+
+    // Record that starts matching the query is reported as updated
+    pub fn change_kind(&self, wallet_handle: WalletHandle, change: &RecordChange) -> Option<WalletChange> {
+        if self.wallet_handle != wallet_handle || self.type_ != change.type_ {
+            return None;
+        }
+
+        let matched = change.old_tags.as_ref().map_or(false, |tags| _matches(&self.query, tags));
+        let matches = change.new_tags.as_ref().map_or(false, |tags| _matches(&self.query, tags));
+
+        match (matched, matches) {
+            (_, true) if change.old_tags.is_none() => Some(WalletChange::Add),
+            (_, true) => Some(WalletChange::Update),
+            (true, false) if change.new_tags.is_none() => Some(WalletChange::Delete),
+            (true, false) => Some(WalletChange::Remove),
+            (false, false) => None,
+        }
+    }
+}
+
+// Evaluates WQL query against decrypted tags. Semantics follow the storages:
+// comparison operators are false for records without the tag and values are compared as strings
+fn _matches(query: &Query, tags: &Tags) -> bool {
+    match *query {
+        Query::And(ref queries) => queries.iter().all(|query| _matches(query, tags)),
+        Query::Or(ref queries) => queries.is_empty() || queries.iter().any(|query| _matches(query, tags)),
+        Query::Not(ref query) => !_matches(query, tags),
+        Query::Eq(ref name, ref target) => tags.get(name).map_or(false, |value| value == target),
+        Query::Neq(ref name, ref target) => tags.get(name).map_or(false, |value| value != target),
+        Query::Gt(ref name, ref target) => tags.get(name).map_or(false, |value| value > target),
+        Query::Gte(ref name, ref target) => tags.get(name).map_or(false, |value| value >= target),
+        Query::Lt(ref name, ref target) => tags.get(name).map_or(false, |value| value < target),
+        Query::Lte(ref name, ref target) => tags.get(name).map_or(false, |value| value <= target),
+        Query::Like(ref name, ref pattern) |
+        Query::ILike(ref name, ref pattern) => tags.get(name).map_or(false, |value| _like(value.as_bytes(), pattern.as_bytes())),
+        Query::Regex(ref name, ref pattern) =>
+            tags.get(name).map_or(false, |value| Regex::new(pattern).map(|regex| regex.is_match(value)).unwrap_or(false)),
+        Query::Exists(ref name, exists) => tags.contains_key(name) == exists,
+        Query::In(ref name, ref targets) => tags.get(name).map_or(false, |value| targets.contains(value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn _tags() -> Tags {
+        serde_json::from_value(json!({"~issuer": "issuer_1", "~age": "25", "state": "active"})).unwrap()
+    }
+
+    fn _query(query: serde_json::Value) -> Query {
+        serde_json::from_value(query).unwrap()
+    }
+
+    #[test]
+    fn matches_works() {
+        let tags = _tags();
+
+        assert!(_matches(&_query(json!({})), &tags));
+        assert!(_matches(&_query(json!({"state": "active", "~issuer": {"$like": "ISSUER%"}})), &tags));
+        assert!(_matches(&_query(json!({"~age": {"$gte": "25"}, "~issuer": {"$in": ["issuer_0", "issuer_1"]}})), &tags));
+        assert!(_matches(&_query(json!({"$or": [{"state": "revoked"}, {"~issuer": {"$regex": "_[0-9]$"}}]})), &tags));
+        assert!(_matches(&_query(json!({"$not": {"~missing": {"$exists": true}}})), &tags));
+
+        assert!(!_matches(&_query(json!({"state": "revoked"})), &tags));
+        assert!(!_matches(&_query(json!({"~age": {"$lt": "25"}})), &tags));
+        assert!(!_matches(&_query(json!({"~missing": {"$neq": "value"}})), &tags));
+        assert!(!_matches(&_query(json!({"~issuer": {"$exists": false}})), &tags));
+    }
+
+    #[test]
+    fn change_kind_works() {
+        let subscription = Subscription::new(WalletHandle(1), "type", _query(json!({"state": "active"})), Box::new(|_, _, _| ()));
+
+        let active = Some(_tags());
+        let revoked: Option<Tags> = Some(serde_json::from_value(json!({"state": "revoked"})).unwrap());
+
+        let change = |old_tags: &Option<Tags>, new_tags: &Option<Tags>| RecordChange {
+            type_: "type".to_string(),
+            id: "id".to_string(),
+            old_tags: old_tags.clone(),
+            new_tags: new_tags.clone(),
+        };
+
+        assert_eq!(subscription.change_kind(WalletHandle(1), &change(&None, &active)), Some(WalletChange::Add));
+        assert_eq!(subscription.change_kind(WalletHandle(1), &change(&active, &active)), Some(WalletChange::Update));
+        assert_eq!(subscription.change_kind(WalletHandle(1), &change(&revoked, &active)), Some(WalletChange::Update));
+        assert_eq!(subscription.change_kind(WalletHandle(1), &change(&active, &revoked)), Some(WalletChange::Remove));
+        assert_eq!(subscription.change_kind(WalletHandle(1), &change(&active, &None)), Some(WalletChange::Delete));
+        assert_eq!(subscription.change_kind(WalletHandle(1), &change(&revoked, &None)), None);
+        assert_eq!(subscription.change_kind(WalletHandle(1), &change(&None, &revoked)), None);
+        assert_eq!(subscription.change_kind(WalletHandle(2), &change(&None, &active)), None);
+    }
+}
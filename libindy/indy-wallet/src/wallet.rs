@@ -1,18 +1,23 @@
-use std::collections::HashMap;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use indy_utils::crypto::{hmacsha256, chacha20poly1305_ietf};
 use indy_utils::wql::Query;
 
+use indy_api_types::domain::wallet::{Record, WalletAggregation, WalletAggregationGroup, WalletAggregationResult, WalletCheckIssue, WalletCheckIssueKind, WalletCheckReport, WalletSyncResult};
 use indy_api_types::errors::prelude::*;
 
 use zeroize::Zeroize;
 
 use super::storage;
-use super::iterator::WalletIterator;
+use super::storage::{StorageIssue, StorageOperation, StorageRecord, Tag, TagName};
+use super::iterator::{ExpiryFilter, WalletIterator};
 use super::encryption::*;
-use super::query_encryption::encrypt_query;
-use super::WalletRecord;
+use super::query_encryption::{encrypt_group_by, encrypt_query, encrypt_search_options};
+use super::subscription::RecordChange;
+use super::{RecordOptions, SearchOptions, WalletOperation, WalletRecord};
 
 #[derive(Serialize, Deserialize)]
 pub(super) struct Keys {
@@ -48,6 +53,22 @@ impl Keys {
         Ok(encrypted)
     }
 
+    // Compares serialized keys in constant time, so mismatch doesn't reveal matching prefix
+    pub fn matches(&self, other: &Keys) -> IndyResult<bool> {
+        let mut serialized = rmp_serde::to_vec(self)
+            .to_indy(IndyErrorKind::InvalidState, "Unable to serialize keys")?;
+
+        let mut other_serialized = rmp_serde::to_vec(other)
+            .to_indy(IndyErrorKind::InvalidState, "Unable to serialize keys")?;
+
+        let res = serialized.len() == other_serialized.len() &&
+            serialized.iter().zip(other_serialized.iter()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0;
+
+        serialized.zeroize();
+        other_serialized.zeroize();
+        Ok(res)
+    }
+
     pub fn deserialize_encrypted(bytes: &[u8], master_key: &chacha20poly1305_ietf::Key) -> IndyResult<Keys> {
         let mut decrypted = decrypt_merged(bytes, master_key)?;
 
@@ -112,106 +133,1015 @@ impl EncryptedValue {
     }
 }
 
+// Expiry of record is kept in reserved plain tag as zero-padded unix time, so storages
+// can compare it as a string. The tag is hidden from get and search results
+pub(super) const EXPIRY_TAG_NAME: &str = "~indy_expires_at";
+
+// Minimal number of seconds between sweeps of expired records started by search
+const EXPIRY_SWEEP_INTERVAL: u64 = 60;
+
+// Number of records checked for conflicts and written at once by add_all
+const ADD_ALL_CHUNK_SIZE: usize = 100;
+
+// Number of records re-encrypted at once by reencrypt_values, progress is reported after each batch
+const REENCRYPT_BATCH_SIZE: usize = 100;
+
 pub(super) struct Wallet {
     id: String,
     storage: Box<dyn storage::WalletStorage>,
     keys: Rc<Keys>,
+    last_expiry_sweep: Cell<u64>,
+    tracked_types: RefCell<HashSet<String>>,
+    changes: RefCell<Vec<RecordChange>>,
 }
 
 impl Wallet {
     pub fn new(id: String, storage: Box<dyn storage::WalletStorage>, keys: Rc<Keys>) -> Wallet {
-        Wallet { id, storage, keys }
+        Wallet {
+            id,
+            storage,
+            keys,
+            last_expiry_sweep: Cell::new(0),
+            tracked_types: RefCell::new(HashSet::new()),
+            changes: RefCell::new(Vec::new()),
+        }
+    }
+
+    // Every write collects changes of records of tracked types, so tags are decrypted
+    // only if somebody is subscribed to them
+    pub fn track_types(&self, types: HashSet<String>) {
+        *self.tracked_types.borrow_mut() = types;
+    }
+
+    pub fn take_changes(&self) -> Vec<RecordChange> {
+        self.changes.replace(Vec::new())
     }
 
     pub fn add(&self, type_: &str, name: &str, value: &str, tags: &HashMap<String, String>) -> IndyResult<()> {
+        self.add_with_expiry(type_, name, value, tags, None)
+    }
+
+    // Expired record with the same id can be still kept by storage, it is replaced
+    pub fn add_with_expiry(&self, type_: &str, name: &str, value: &str, tags: &HashMap<String, String>, expires_in: Option<u64>) -> IndyResult<()> {
         let etype = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
         let ename = encrypt_as_searchable(name.as_bytes(), &self.keys.name_key, &self.keys.item_hmac_key);
         let evalue = EncryptedValue::encrypt(value, &self.keys.value_key);
-        let etags = encrypt_tags(tags, &self.keys.tag_name_key, &self.keys.tag_value_key, &self.keys.tags_hmac_key);
-        self.storage.add(&etype, &ename, &evalue, &etags)?;
+        let mut etags = encrypt_tags(tags, &self.keys.tag_name_key, &self.keys.tag_value_key, &self.keys.tags_hmac_key);
+        etags.extend(expires_in.map(|expires_in| self._expiry_tag(_expiry_value(_now().saturating_add(expires_in)))));
+
+        match self.storage.add(&etype, &ename, &evalue, &etags) {
+            Err(ref err) if err.kind() == IndyErrorKind::WalletItemAlreadyExists && self._delete_if_expired(type_, name, &etype, &ename)? =>
+                self.storage.add(&etype, &ename, &evalue, &etags)?,
+            res => res?,
+        }
+
+        if self._tracks(type_) {
+            self._record_change(type_, name, None, Some(tags.clone()));
+        }
+
+        Ok(())
+    }
+
+    // Records are checked for conflicts and written in chunks inside of one storage transaction, so
+    // conflicting or broken input leaves the wallet untouched without keeping all records in memory.
+    // Storages without transactions get all records checked before the first write
+    pub fn add_all<I>(&self, records: I) -> IndyResult<()> where I: Iterator<Item=IndyResult<Record>> {
+        let mut records = records;
+        let mut changes = Vec::new();
+
+        if self.storage.supports_transactions() {
+            self.storage.batch_chunked(&mut || {
+                let chunk = records.by_ref()
+                    .take(ADD_ALL_CHUNK_SIZE)
+                    .collect::<IndyResult<Vec<Record>>>()?;
+
+                if chunk.is_empty() {
+                    return Ok(None);
+                }
+
+                self._add_all_operations(&chunk, &mut changes).map(Some)
+            })?;
+        } else {
+            let records = records.collect::<IndyResult<Vec<Record>>>()?;
+            let operations = self._add_all_operations(&records, &mut changes)?;
+            _apply(&*self.storage, &operations)?;
+        }
+
+        // Replaced expired record is reported as deleted before the new one is added
+        for (type_, id, old_tags, new_tags) in changes {
+            self._record_change(&type_, &id, old_tags, new_tags);
+        }
+
         Ok(())
     }
 
+    // Records repeated inside of one chunk are rejected here, repeated across chunks are rejected
+    // by storage as the first one is already added
+    fn _add_all_operations(&self, records: &[Record], changes: &mut Vec<(String, String, Option<HashMap<String, String>>, Option<HashMap<String, String>>)>) -> IndyResult<Vec<StorageOperation>> {
+        let expiry_tag_name = self._expiry_tag_name();
+        let mut ids: HashSet<(&str, &str)> = HashSet::with_capacity(records.len());
+        let mut operations = Vec::with_capacity(records.len());
+
+        for record in records {
+            if !ids.insert((record.type_.as_str(), record.id.as_str())) {
+                return Err(err_msg(IndyErrorKind::WalletItemAlreadyExists,
+                                   format!("Record of type {} with id {} is added twice", record.type_, record.id)));
+            }
+
+            let etype = self._encrypt_type(&record.type_);
+            let ename = self._encrypt_name(&record.id);
+
+            match self.storage.get(&etype, &ename, &RecordOptions::id_tags()) {
+                Ok(existing) => match _find_expiry(&existing.tags, &expiry_tag_name) {
+                    Some(ref expires_at) if _is_expired(expires_at) => {
+                        if self._tracks(&record.type_) {
+                            changes.push((record.type_.clone(), record.id.clone(), Some(self._decrypt_tags(&existing.tags)?), None));
+                        }
+                        operations.push(StorageOperation::Delete(etype.clone(), ename.clone()))
+                    }
+                    _ => return Err(err_msg(IndyErrorKind::WalletItemAlreadyExists,
+                                            format!("Wallet already contains record of type {} with id {}", record.type_, record.id))),
+                },
+                Err(ref err) if err.kind() == IndyErrorKind::WalletItemNotFound => (),
+                Err(err) => return Err(err),
+            }
+
+            if self._tracks(&record.type_) {
+                changes.push((record.type_.clone(), record.id.clone(), None, Some(record.tags.clone())));
+            }
+
+            operations.push(StorageOperation::Add(etype,
+                                                  ename,
+                                                  EncryptedValue::encrypt(&record.value, &self.keys.value_key),
+                                                  encrypt_tags(&record.tags, &self.keys.tag_name_key, &self.keys.tag_value_key, &self.keys.tags_hmac_key)));
+        }
+
+        Ok(operations)
+    }
+
     pub fn add_tags(&self, type_: &str, name: &str, tags: &HashMap<String, String>) -> IndyResult<()> {
         let encrypted_type = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
         let encrypted_name = encrypt_as_searchable(name.as_bytes(), &self.keys.name_key, &self.keys.item_hmac_key);
         let encrypted_tags = encrypt_tags(tags, &self.keys.tag_name_key, &self.keys.tag_value_key, &self.keys.tags_hmac_key);
-        self.storage.add_tags(&encrypted_type, &encrypted_name, &encrypted_tags)?;
-        Ok(())
+        self._tracked(type_, name, &encrypted_type, &encrypted_name,
+                      || self.storage.add_tags(&encrypted_type, &encrypted_name, &encrypted_tags))
     }
 
     pub fn update_tags(&self, type_: &str, name: &str, tags: &HashMap<String, String>) -> IndyResult<()> {
         let encrypted_type = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
         let encrypted_name = encrypt_as_searchable(name.as_bytes(), &self.keys.name_key, &self.keys.item_hmac_key);
-        let encrypted_tags = encrypt_tags(tags, &self.keys.tag_name_key, &self.keys.tag_value_key, &self.keys.tags_hmac_key);
-        self.storage.update_tags(&encrypted_type, &encrypted_name, &encrypted_tags)?;
-        Ok(())
+        let encrypted_tags = self._encrypt_tags_keeping_expiry(&encrypted_type, &encrypted_name, tags)?;
+        self._tracked(type_, name, &encrypted_type, &encrypted_name,
+                      || self.storage.update_tags(&encrypted_type, &encrypted_name, &encrypted_tags))
     }
 
     pub fn delete_tags(&self, type_: &str, name: &str, tag_names: &[&str]) -> IndyResult<()> {
         let encrypted_type = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
         let encrypted_name = encrypt_as_searchable(name.as_bytes(), &self.keys.name_key, &self.keys.item_hmac_key);
         let encrypted_tag_names = encrypt_tag_names(tag_names, &self.keys.tag_name_key, &self.keys.tags_hmac_key);
-        self.storage.delete_tags(&encrypted_type, &encrypted_name, &encrypted_tag_names[..])?;
-        Ok(())
+        self._tracked(type_, name, &encrypted_type, &encrypted_name,
+                      || self.storage.delete_tags(&encrypted_type, &encrypted_name, &encrypted_tag_names[..]))
     }
 
     pub fn update(&self, type_: &str, name: &str, new_value: &str) -> IndyResult<()> {
+        self.update_with_expiry(type_, name, new_value, None)
+    }
+
+    // Current expiry is kept if expires_in isn't set, Some(None) makes record persistent
+    pub fn update_with_expiry(&self, type_: &str, name: &str, new_value: &str, expires_in: Option<Option<u64>>) -> IndyResult<()> {
         let encrypted_type = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
         let encrypted_name = encrypt_as_searchable(name.as_bytes(), &self.keys.name_key, &self.keys.item_hmac_key);
         let encrypted_value = EncryptedValue::encrypt(new_value, &self.keys.value_key);
-        self.storage.update(&encrypted_type, &encrypted_name, &encrypted_value)?;
-        Ok(())
+
+        let expiry_operation = match expires_in {
+            None => None,
+            Some(Some(expires_in)) =>
+                Some(StorageOperation::AddTags(encrypted_type.clone(), encrypted_name.clone(),
+                                               vec![self._expiry_tag(_expiry_value(_now().saturating_add(expires_in)))])),
+            Some(None) =>
+                Some(StorageOperation::DeleteTags(encrypted_type.clone(), encrypted_name.clone(),
+                                                  vec![TagName::OfPlain(self._expiry_tag_name())])),
+        };
+
+        self._tracked(type_, name, &encrypted_type, &encrypted_name, || match expiry_operation {
+            Some(expiry_operation) =>
+                _apply(&*self.storage, &[StorageOperation::Update(encrypted_type.clone(), encrypted_name.clone(), encrypted_value), expiry_operation]),
+            None => self.storage.update(&encrypted_type, &encrypted_name, &encrypted_value),
+        })
     }
 
+    // Expiry is taken from fetched tags, or fetched separately if caller didn't ask for tags.
+    // Expired record is deleted
     pub fn get(&self, type_: &str, name: &str, options: &str) -> IndyResult<WalletRecord> {
         let etype = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
         let ename = encrypt_as_searchable(name.as_bytes(), &self.keys.name_key, &self.keys.item_hmac_key);
 
+        let retrieve_tags = serde_json::from_str::<RecordOptions>(options)
+            .to_indy(IndyErrorKind::InvalidStructure, "RecordOptions is malformed json")?
+            .retrieve_tags;
+
         let result = self.storage.get(&etype, &ename, options)?;
 
+        let expires_at = if retrieve_tags {
+            _find_expiry(&result.tags, &self._expiry_tag_name())
+        } else {
+            self._get_expiry(&etype, &ename)?
+        };
+
+        if expires_at.map_or(false, |expires_at| _is_expired(&expires_at)) {
+            let expired_tags = if self._tracks(type_) { self._stored_tags(&etype, &ename)? } else { None };
+
+            match self.storage.delete(&etype, &ename) {
+                Ok(()) => self._record_change(type_, name, expired_tags, None),
+                Err(err) => warn!("Unable to delete expired record: {}", err),
+            }
+
+            return Err(err_msg(IndyErrorKind::WalletItemNotFound, "Item not found"));
+        }
+
         let value = match result.value {
             None => None,
             Some(encrypted_value) => Some(encrypted_value.decrypt(&self.keys.value_key)?)
         };
 
-        let tags = decrypt_tags(&result.tags, &self.keys.tag_name_key, &self.keys.tag_value_key)?;
+        let tags = if retrieve_tags {
+            decrypt_tags(&result.tags, &self.keys.tag_name_key, &self.keys.tag_value_key)?
+                .map(_without_expiry)
+        } else {
+            None
+        };
+
+        Ok(WalletRecord::new(String::from(name), result.type_.map(|_| type_.to_string()), value, tags))
+    }
+
+    pub fn delete(&self, type_: &str, name: &str) -> IndyResult<()> {
+        let etype = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
+        let ename = encrypt_as_searchable(name.as_bytes(), &self.keys.name_key, &self.keys.item_hmac_key);
+
+        self._tracked(type_, name, &etype, &ename, || self.storage.delete(&etype, &ename))
+    }
+
+    pub fn batch(&self, operations: &[WalletOperation]) -> IndyResult<()> {
+        let mut storage_operations = Vec::with_capacity(operations.len());
+
+        for operation in operations {
+            let storage_operation = match *operation {
+                WalletOperation::Add { ref type_, ref id, ref value, ref tags } =>
+                    StorageOperation::Add(self._encrypt_type(type_),
+                                          self._encrypt_name(id),
+                                          EncryptedValue::encrypt(value, &self.keys.value_key),
+                                          encrypt_tags(tags, &self.keys.tag_name_key, &self.keys.tag_value_key, &self.keys.tags_hmac_key)),
+                WalletOperation::UpdateValue { ref type_, ref id, ref value } =>
+                    StorageOperation::Update(self._encrypt_type(type_),
+                                             self._encrypt_name(id),
+                                             EncryptedValue::encrypt(value, &self.keys.value_key)),
+                WalletOperation::AddTags { ref type_, ref id, ref tags } =>
+                    StorageOperation::AddTags(self._encrypt_type(type_),
+                                              self._encrypt_name(id),
+                                              encrypt_tags(tags, &self.keys.tag_name_key, &self.keys.tag_value_key, &self.keys.tags_hmac_key)),
+                WalletOperation::UpdateTags { ref type_, ref id, ref tags } => {
+                    let encrypted_type = self._encrypt_type(type_);
+                    let encrypted_name = self._encrypt_name(id);
+                    let encrypted_tags = self._encrypt_tags_keeping_expiry(&encrypted_type, &encrypted_name, tags)?;
+                    StorageOperation::UpdateTags(encrypted_type, encrypted_name, encrypted_tags)
+                }
+                WalletOperation::DeleteTags { ref type_, ref id, ref tag_names } => {
+                    let tag_names: Vec<&str> = tag_names.iter().map(String::as_str).collect();
+                    StorageOperation::DeleteTags(self._encrypt_type(type_),
+                                                 self._encrypt_name(id),
+                                                 encrypt_tag_names(&tag_names, &self.keys.tag_name_key, &self.keys.tags_hmac_key))
+                }
+                WalletOperation::Delete { ref type_, ref id } =>
+                    StorageOperation::Delete(self._encrypt_type(type_),
+                                             self._encrypt_name(id)),
+            };
+
+            storage_operations.push(storage_operation);
+        }
+
+        // Storage applies operations at once, so tags of records are read before the batch
+        // and following states are computed from operations
+        let mut states: HashMap<(&str, &str), Option<HashMap<String, String>>> = HashMap::new();
+
+        for operation in operations {
+            let (type_, id) = (operation.get_type(), operation.get_id());
+
+            if self._tracks(type_) && !states.contains_key(&(type_, id)) {
+                states.insert((type_, id), self._stored_tags(&self._encrypt_type(type_), &self._encrypt_name(id))?);
+            }
+        }
+
+        self.storage.batch(&storage_operations)?;
+
+        for operation in operations {
+            let state = match states.get_mut(&(operation.get_type(), operation.get_id())) {
+                Some(state) => state,
+                None => continue
+            };
+
+            let old_tags = state.clone();
+
+            *state = match *operation {
+                WalletOperation::Add { ref tags, .. } => Some(tags.clone()),
+                WalletOperation::UpdateValue { .. } => old_tags.clone(),
+                WalletOperation::AddTags { ref tags, .. } => old_tags.clone().map(|mut old_tags| {
+                    old_tags.extend(tags.clone());
+                    old_tags
+                }),
+                WalletOperation::UpdateTags { ref tags, .. } => old_tags.as_ref().map(|_| tags.clone()),
+                WalletOperation::DeleteTags { ref tag_names, .. } => old_tags.clone().map(|mut old_tags| {
+                    tag_names.iter().for_each(|tag_name| { old_tags.remove(tag_name); });
+                    old_tags
+                }),
+                WalletOperation::Delete { .. } => None,
+            };
+
+            self._record_change(operation.get_type(), operation.get_id(), old_tags, state.clone());
+        }
+
+        Ok(())
+    }
+
+    fn _tracks(&self, type_: &str) -> bool {
+        self.tracked_types.borrow().contains(type_)
+    }
+
+    fn _tracks_any(&self) -> bool {
+        !self.tracked_types.borrow().is_empty()
+    }
+
+    fn _record_change(&self, type_: &str, id: &str, old_tags: Option<HashMap<String, String>>, new_tags: Option<HashMap<String, String>>) {
+        if old_tags.is_none() && new_tags.is_none() {
+            return;
+        }
+
+        self.changes.borrow_mut().push(RecordChange { type_: type_.to_string(), id: id.to_string(), old_tags, new_tags });
+    }
+
+    // Reads tags before and after the write if record type is tracked
+    fn _tracked<F>(&self, type_: &str, name: &str, encrypted_type: &[u8], encrypted_name: &[u8], write: F) -> IndyResult<()>
+        where F: FnOnce() -> IndyResult<()> {
+        if !self._tracks(type_) {
+            return write();
+        }
+
+        let old_tags = self._stored_tags(encrypted_type, encrypted_name)?;
+        write()?;
+        let new_tags = self._stored_tags(encrypted_type, encrypted_name)?;
+
+        self._record_change(type_, name, old_tags, new_tags);
+        Ok(())
+    }
+
+    // Tags of missing record are None. Expired record is still kept by storage, so it has tags
+    fn _stored_tags(&self, encrypted_type: &[u8], encrypted_name: &[u8]) -> IndyResult<Option<HashMap<String, String>>> {
+        match self.storage.get(encrypted_type, encrypted_name, &RecordOptions::id_tags()) {
+            Ok(record) => Ok(Some(self._decrypt_tags(&record.tags)?)),
+            Err(ref err) if err.kind() == IndyErrorKind::WalletItemNotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn _decrypt_tags(&self, tags: &Option<Vec<Tag>>) -> IndyResult<HashMap<String, String>> {
+        Ok(decrypt_tags(tags, &self.keys.tag_name_key, &self.keys.tag_value_key)?
+            .map(_without_expiry)
+            .unwrap_or_default())
+    }
+
+    // Type, id and tags of storage record are decrypted only if its type is tracked
+    fn _tracked_record(&self, type_: &Option<Vec<u8>>, id: &[u8], tags: &Option<Vec<Tag>>) -> IndyResult<Option<(String, String, HashMap<String, String>)>> {
+        let type_ = match *type_ {
+            Some(ref type_) if self._tracks_any() => self._decrypt_string(type_, &self.keys.type_key)?,
+            _ => return Ok(None)
+        };
+
+        if !self._tracks(&type_) {
+            return Ok(None);
+        }
+
+        let id = self._decrypt_string(id, &self.keys.name_key)?;
+        Ok(Some((type_, id, self._decrypt_tags(tags)?)))
+    }
+
+    fn _encrypt_type(&self, type_: &str) -> Vec<u8> {
+        encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key)
+    }
+
+    fn _encrypt_name(&self, name: &str) -> Vec<u8> {
+        encrypt_as_searchable(name.as_bytes(), &self.keys.name_key, &self.keys.item_hmac_key)
+    }
+
+    fn _expiry_tag_name(&self) -> Vec<u8> {
+        // '~' character on start is skipped as for other plain tags
+        encrypt_as_searchable(&EXPIRY_TAG_NAME.as_bytes()[1..], &self.keys.tag_name_key, &self.keys.tags_hmac_key)
+    }
+
+    fn _expiry_tag(&self, expires_at: String) -> Tag {
+        Tag::PlainText(self._expiry_tag_name(), expires_at)
+    }
+
+    // Expiry of missing record is None
+    fn _get_expiry(&self, encrypted_type: &[u8], encrypted_name: &[u8]) -> IndyResult<Option<String>> {
+        let record = match self.storage.get(encrypted_type, encrypted_name, &RecordOptions::id_tags()) {
+            Err(ref err) if err.kind() == IndyErrorKind::WalletItemNotFound => return Ok(None),
+            res => res?,
+        };
+
+        Ok(_find_expiry(&record.tags, &self._expiry_tag_name()))
+    }
+
+    fn _delete_if_expired(&self, type_: &str, name: &str, encrypted_type: &[u8], encrypted_name: &[u8]) -> IndyResult<bool> {
+        match self._get_expiry(encrypted_type, encrypted_name)? {
+            Some(ref expires_at) if _is_expired(expires_at) => {
+                let expired_tags = if self._tracks(type_) { self._stored_tags(encrypted_type, encrypted_name)? } else { None };
+                self.storage.delete(encrypted_type, encrypted_name)?;
+                self._record_change(type_, name, expired_tags, None);
+                Ok(true)
+            }
+            _ => Ok(false)
+        }
+    }
+
+    // Expiry isn't a user tag, so it survives replacement of record tags
+    fn _encrypt_tags_keeping_expiry(&self, encrypted_type: &[u8], encrypted_name: &[u8], tags: &HashMap<String, String>) -> IndyResult<Vec<Tag>> {
+        let mut encrypted_tags = encrypt_tags(tags, &self.keys.tag_name_key, &self.keys.tag_value_key, &self.keys.tags_hmac_key);
+        encrypted_tags.extend(self._get_expiry(encrypted_type, encrypted_name)?.map(|expires_at| self._expiry_tag(expires_at)));
+        Ok(encrypted_tags)
+    }
+
+    // Storage deletes expired records at most once per interval. Failure only postpones the sweep
+    // as expired records are filtered out anyway
+    fn _sweep_expired(&self) {
+        let now = _now();
+
+        if now < self.last_expiry_sweep.get() + EXPIRY_SWEEP_INTERVAL {
+            return;
+        }
+
+        self.last_expiry_sweep.set(now);
+
+        match self._delete_expired(now) {
+            Ok(count) => trace!("_sweep_expired: {} expired records deleted", count),
+            Err(err) => warn!("Unable to delete expired records: {}", err),
+        }
+    }
+
+    fn _delete_expired(&self, now: u64) -> IndyResult<usize> {
+        let expired = self._tracked_expired(now)?;
+
+        let count = self.storage.delete_expired(&self._expiry_tag_name(), &_expiry_value(now))?;
+
+        for (type_, id, tags) in expired {
+            self._record_change(&type_, &id, Some(tags), None);
+        }
+
+        Ok(count)
+    }
+
+    // Storage deletes expired records without returning them, so records of tracked types
+    // that will be deleted are searched before. Only expired records of tracked types are read
+    fn _tracked_expired(&self, now: u64) -> IndyResult<Vec<(String, String, HashMap<String, String>)>> {
+        let mut expired = Vec::new();
+
+        let types: Vec<String> = self.tracked_types.borrow().iter().cloned().collect();
+
+        if types.is_empty() {
+            return Ok(expired);
+        }
+
+        let query = encrypt_query(Query::Lte(EXPIRY_TAG_NAME.to_string(), _expiry_value(now)), &self.keys)?;
+
+        let options = serde_json::to_string(&SearchOptions {
+            retrieve_records: true,
+            retrieve_total_count: false,
+            retrieve_type: false,
+            retrieve_value: false,
+            retrieve_tags: true,
+            sort_by: None,
+            skip: None,
+            limit: None,
+        }).to_indy(IndyErrorKind::InvalidState, "Cannot serialize search options")?;
+
+        for type_ in types {
+            let encrypted_type = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
+            let mut records = self.storage.search(&encrypted_type, &query, Some(&options))?;
+
+            while let Some(record) = records.next()? {
+                let id = self._decrypt_string(&record.id, &self.keys.name_key)?;
+                expired.push((type_.clone(), id, self._decrypt_tags(&record.tags)?));
+            }
+        }
+
+        Ok(expired)
+    }
+
+    // Storage that can't filter expired records by query returns them with tags and wallet
+    // drops them. Total count and skip/limit include expired records then until they are swept
+    pub fn search<'a>(&'a self, type_: &str, query: &str, options: Option<&str>) -> IndyResult<WalletIterator> {
+        let parsed_query: Query = ::serde_json::from_str(query)
+            .map_err(|err| IndyError::from_msg(IndyErrorKind::WalletQueryError, err))?;
+
+        self._sweep_expired();
+
+        let (query, options, expiry_filter) = if self.storage.supports_exists_query() {
+            (_not_expired(parsed_query), options.map(String::from), None)
+        } else {
+            let (options, expiry_filter) = self._expiry_filter_options(options)?;
+            (parsed_query, Some(options), Some(expiry_filter))
+        };
+
+        let encrypted_query = encrypt_query(query, &self.keys)?;
+        let encrypted_options = match options {
+            Some(options) => Some(encrypt_search_options(&options, &self.keys)?),
+            None => None
+        };
+        let encrypted_type_ = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
+        let storage_iterator = self.storage.search(&encrypted_type_, &encrypted_query, encrypted_options.as_ref().map(String::as_str))?;
+        let mut wallet_iterator = WalletIterator::new(storage_iterator, Rc::clone(&self.keys))
+            .hiding_tag(EXPIRY_TAG_NAME);
+
+        if let Some(expiry_filter) = expiry_filter {
+            wallet_iterator = wallet_iterator.filtering_expired(expiry_filter);
+        }
+
+        Ok(wallet_iterator)
+    }
+
+    fn _expiry_filter(&self, retrieve_tags: bool) -> ExpiryFilter {
+        ExpiryFilter {
+            tag_name: self._expiry_tag_name(),
+            now: _expiry_value(_now()),
+            retrieve_tags,
+        }
+    }
+
+    // Tags are always requested from storage to check expiry
+    fn _expiry_filter_options(&self, options: Option<&str>) -> IndyResult<(String, ExpiryFilter)> {
+        let options: SearchOptions = match options {
+            Some(options) => serde_json::from_str(options)
+                .to_indy(IndyErrorKind::InvalidStructure, "Search options is malformed json")?,
+            None => SearchOptions::default(),
+        };
+
+        let expiry_filter = self._expiry_filter(options.retrieve_tags);
+
+        let options = serde_json::to_string(&SearchOptions { retrieve_tags: true, ..options })
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize search options")?;
+
+        Ok((options, expiry_filter))
+    }
+
+    pub fn close(&mut self) -> IndyResult<()> {
+        self.storage.close()
+            .map_err(IndyError::from)
+    }
+
+    // Expired records are skipped and expiry tag is hidden as in search
+    pub fn get_all(&self) -> IndyResult<WalletIterator> {
+        let all_items = self.storage.get_all()?;
+        Ok(WalletIterator::new(all_items, Rc::clone(&self.keys))
+            .filtering_expired(self._expiry_filter(true))
+            .hiding_tag(EXPIRY_TAG_NAME))
+    }
+
+    pub fn get_id<'a>(&'a self) -> &'a str {
+        &self.id
+    }
+
+    pub fn get_keys(&self) -> &Keys {
+        &self.keys
+    }
+
+    pub fn get_storage_metadata(&self) -> IndyResult<Vec<u8>> {
+        self.storage.get_storage_metadata()
+    }
+
+    pub fn set_storage_metadata(&self, metadata: &[u8]) -> IndyResult<()> {
+        self.storage.set_storage_metadata(metadata)
+    }
+
+    // Re-encrypts values of all records with new random value keys batch by batch and reports
+    // number of processed records out of total. Every record stays readable with wallet keys,
+    // so failed batch leaves the wallet consistent. Record changes are not reported to subscribers
+    pub fn reencrypt_values(&self, progress: &mut dyn FnMut(usize, usize)) -> IndyResult<()> {
+        let total = self.storage.count()?;
+        let mut processed = 0;
+        progress(processed, total);
+
+        let mut records = self.storage.get_all()?;
+        let mut operations = Vec::with_capacity(REENCRYPT_BATCH_SIZE);
+
+        loop {
+            let record = records.next()?;
+            let done = record.is_none();
+
+            if let Some(record) = record {
+                let type_ = record.type_
+                    .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "No type fetched for re-encrypted record"))?;
+                let value = record.value
+                    .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "No value fetched for re-encrypted record"))?
+                    .decrypt(&self.keys.value_key)?;
+
+                operations.push(StorageOperation::Update(type_, record.id, EncryptedValue::encrypt(&value, &self.keys.value_key)));
+            }
+
+            if operations.len() == REENCRYPT_BATCH_SIZE || (done && !operations.is_empty()) {
+                _apply(&*self.storage, &operations)?;
+                processed += operations.len();
+                operations.clear();
+
+                // Records added by other storage users after counting are re-encrypted too
+                progress(processed, total.max(processed));
+            }
+
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    // Parses WQL query and checks that it can be applied to records of this wallet
+    pub fn parse_query(&self, query: &str) -> IndyResult<Query> {
+        let parsed_query: Query = ::serde_json::from_str(query)
+            .map_err(|err| IndyError::from_msg(IndyErrorKind::WalletQueryError, err))?;
+
+        encrypt_query(parsed_query.clone(), &self.keys)?;
+
+        Ok(parsed_query)
+    }
+
+    // Plain tag values are stored unencrypted, so storage groups records without decryption
+    pub fn aggregate(&self, type_: &str, query: &str, aggregation: &WalletAggregation) -> IndyResult<WalletAggregationResult> {
+        let parsed_query: Query = ::serde_json::from_str(query)
+            .map_err(|err| IndyError::from_msg(IndyErrorKind::WalletQueryError, err))?;
+
+        self._sweep_expired();
+
+        let mut encrypted_group_by = encrypt_group_by(&aggregation.group_by, &self.keys)?;
+        let encrypted_type_ = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
+
+        // Storage that can't filter expired records by query groups them by expiry additionally
+        let groups = if self.storage.supports_exists_query() {
+            let encrypted_query = encrypt_query(_not_expired(parsed_query), &self.keys)?;
+            self.storage.aggregate(&encrypted_type_, &encrypted_query, &encrypted_group_by)?
+        } else {
+            let encrypted_query = encrypt_query(parsed_query, &self.keys)?;
+            encrypted_group_by.push(self._expiry_tag_name());
+            _merge_not_expired(self.storage.aggregate(&encrypted_type_, &encrypted_query, &encrypted_group_by)?)
+        };
+
+        let count = groups.iter().map(|&(_, count)| count).sum();
+
+        let groups = if aggregation.group_by.is_empty() {
+            Vec::new()
+        } else {
+            groups.into_iter()
+                .map(|(values, count)| WalletAggregationGroup {
+                    tags: aggregation.group_by.iter().cloned().zip(values).collect(),
+                    count,
+                })
+                .collect()
+        };
+
+        Ok(WalletAggregationResult { count, groups })
+    }
+
+    // Copies encrypted records changed after since marker to target wallet without decryption.
+    // Only records that differ from target ones are written. Marker is read before records,
+    // so records changed during sync are copied again next time. Records deleted after marker
+    // are taken from delete log of storage. Full sync and storages without delete log find target
+    // records missing in source by comparing ids of all records. Expired records are removed from target
+    pub fn sync_to(&self, target: &Wallet, since: Option<u64>) -> IndyResult<WalletSyncResult> {
+        if !self.keys.matches(&target.keys)? {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, "Wallets are encrypted with different keys"));
+        }
+
+        let marker = self.storage.get_modification_marker()?;
+
+        let mut records = match since {
+            Some(since) => self.storage.get_modified_since(since)?,
+            None => self.storage.get_all()?,
+        };
+
+        let expiry_filter = self._expiry_filter(true);
+        let mut records_count = 0;
+        let mut deleted_count = 0;
+
+        while let Some(record) = records.next()? {
+            let type_ = record.type_
+                .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "No type fetched for synchronized record"))?;
+
+            // Target copy could be made before record was changed to expired one
+            if expiry_filter.is_expired(&record.tags) {
+                if target._delete_synced(&type_, &record.id)? {
+                    deleted_count += 1;
+                }
+                continue;
+            }
+
+            let value = record.value
+                .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "No value fetched for synchronized record"))?;
+
+            let mut tags = record.tags.unwrap_or_else(Vec::new);
+            tags.sort();
+
+            // Keys of wallets match, so target decrypts source records
+            let change = if target._tracks_any() {
+                target._tracked_record(&Some(type_.clone()), &record.id, &Some(tags.clone()))?
+            } else {
+                None
+            };
+
+            let (operations, old_tags) = match target.storage.get(&type_, &record.id, &RecordOptions::id_value_tags()) {
+                Err(ref err) if err.kind() == IndyErrorKind::WalletItemNotFound =>
+                    (vec![StorageOperation::Add(type_, record.id, value, tags)], None),
+                Err(err) => return Err(err),
+                Ok(target_record) => {
+                    let mut target_tags = target_record.tags.unwrap_or_else(Vec::new);
+                    target_tags.sort();
+
+                    if target_record.value.as_ref() == Some(&value) && target_tags == tags {
+                        continue;
+                    }
+
+                    let old_tags = match change {
+                        Some(_) => Some(target._decrypt_tags(&Some(target_tags))?),
+                        None => None
+                    };
+
+                    (vec![
+                        StorageOperation::Update(type_.clone(), record.id.clone(), value),
+                        StorageOperation::UpdateTags(type_, record.id, tags),
+                    ], old_tags)
+                }
+            };
+
+            _apply(&*target.storage, &operations)?;
+            records_count += 1;
+
+            if let Some((type_, id, new_tags)) = change {
+                target._record_change(&type_, &id, old_tags, Some(new_tags));
+            }
+        }
+
+        let deleted = match since {
+            Some(since) => self.storage.get_deleted_since(since)?,
+            None => None,
+        };
+
+        let deleted = match deleted {
+            Some(deleted) => deleted,
+            None => self._missing_in(target)?,
+        };
+
+        for (type_, id) in deleted {
+            if target._delete_synced(&type_, &id)? {
+                deleted_count += 1;
+            }
+        }
+
+        // Records that expired without modification are not returned as modified ones
+        deleted_count += target._delete_expired(_now())?;
+
+        Ok(WalletSyncResult { records_count, deleted_count, marker })
+    }
+
+    // Expired source records don't count as present, so their target copies are deleted
+    fn _missing_in(&self, target: &Wallet) -> IndyResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        let expiry_filter = self._expiry_filter(true);
+        let mut ids = HashSet::new();
+        let mut records = self.storage.get_all()?;
+
+        while let Some(record) = records.next()? {
+            if !expiry_filter.is_expired(&record.tags) {
+                ids.insert((record.type_, record.id));
+            }
+        }
+
+        let mut missing = Vec::new();
+        let mut records = target.storage.get_all()?;
+
+        while let Some(record) = records.next()? {
+            let (type_, id) = (record.type_, record.id);
+
+            if !ids.contains(&(type_.clone(), id.clone())) {
+                let type_ = type_
+                    .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "No type fetched for synchronized record"))?;
+                missing.push((type_, id));
+            }
+        }
+
+        Ok(missing)
+    }
+
+    // Returns false if record is already missing in the wallet
+    fn _delete_synced(&self, type_: &[u8], id: &[u8]) -> IndyResult<bool> {
+        let record = match self.storage.get(type_, id, &RecordOptions::id_tags()) {
+            Err(ref err) if err.kind() == IndyErrorKind::WalletItemNotFound => return Ok(false),
+            Err(err) => return Err(err),
+            Ok(record) => record,
+        };
+
+        let change = self._tracked_record(&Some(type_.to_vec()), id, &record.tags)?;
+
+        self.storage.delete(type_, id)?;
+
+        if let Some((type_, id, tags)) = change {
+            self._record_change(&type_, &id, Some(tags), None);
+        }
+
+        Ok(true)
+    }
+
+    // Records that can't be decrypted are unusable, so repair deletes them
+    pub fn check(&self, repair: bool) -> IndyResult<WalletCheckReport> {
+        let mut issues: Vec<WalletCheckIssue> = self.storage.check(repair)?
+            .into_iter()
+            .map(|issue| self._storage_issue(issue, repair))
+            .collect();
+
+        let expiry_filter = self._expiry_filter(true);
+        let mut records_count = 0;
+        // type, id, issues and tracked change of broken records
+        let mut broken = Vec::new();
+
+        {
+            let mut records = self.storage.get_all()?;
+
+            while let Some(record) = records.next()? {
+                if expiry_filter.is_expired(&record.tags) {
+                    continue;
+                }
+
+                records_count += 1;
+
+                let record_issues = self._check_record(&record);
+
+                if !repair || record_issues.is_empty() {
+                    issues.extend(record_issues);
+                    continue;
+                }
+
+                // Only broken value can be reported to subscribers as the rest is needed to match them
+                let change = self._tracked_record(&record.type_, &record.id, &record.tags).unwrap_or(None);
+
+                match record.type_ {
+                    Some(type_) => broken.push((type_, record.id, record_issues, change)),
+                    None => issues.extend(record_issues),
+                }
+            }
+        }
+
+        // Records are deleted once iteration is finished as storage iterator can hold a cursor
+        for (type_, id, mut record_issues, change) in broken {
+            self.storage.delete(&type_, &id)?;
+
+            if let Some((type_, id, tags)) = change {
+                self._record_change(&type_, &id, Some(tags), None);
+            }
+
+            for issue in record_issues.iter_mut() {
+                issue.repaired = true;
+            }
+
+            issues.extend(record_issues);
+        }
+
+        Ok(WalletCheckReport { records_count, issues })
+    }
+
+    fn _storage_issue(&self, issue: StorageIssue, repaired: bool) -> WalletCheckIssue {
+        match issue {
+            StorageIssue::OrphanedTags(count) => WalletCheckIssue {
+                kind: WalletCheckIssueKind::OrphanedTags,
+                type_: None,
+                id: None,
+                details: format!("{} tags reference missing items", count),
+                repaired,
+            },
+            StorageIssue::DuplicateItem(type_, id, count) => WalletCheckIssue {
+                kind: WalletCheckIssueKind::DuplicateItem,
+                type_: self._decrypt_string(&type_, &self.keys.type_key).ok(),
+                id: self._decrypt_string(&id, &self.keys.name_key).ok(),
+                details: format!("{} items have the same type and id", count),
+                repaired,
+            },
+        }
+    }
+
+    fn _check_record(&self, record: &StorageRecord) -> Vec<WalletCheckIssue> {
+        let mut issues = Vec::new();
+
+        let type_ = match record.type_ {
+            Some(ref type_) => self._decrypt_string(type_, &self.keys.type_key)
+                .map_err(|err| issues.push(_record_issue(WalletCheckIssueKind::InvalidType, None, None, err)))
+                .ok(),
+            None => None,
+        };
+
+        let id = self._decrypt_string(&record.id, &self.keys.name_key)
+            .map_err(|err| issues.push(_record_issue(WalletCheckIssueKind::InvalidId, type_.clone(), None, err)))
+            .ok();
+
+        if let Some(ref value) = record.value {
+            if let Err(err) = value.decrypt(&self.keys.value_key) {
+                issues.push(_record_issue(WalletCheckIssueKind::InvalidValue, type_.clone(), id.clone(), err));
+            }
+        }
+
+        if let Err(err) = decrypt_tags(&record.tags, &self.keys.tag_name_key, &self.keys.tag_value_key) {
+            issues.push(_record_issue(WalletCheckIssueKind::InvalidTags, type_, id, err));
+        }
 
-        Ok(WalletRecord::new(String::from(name), result.type_.map(|_| type_.to_string()), value, tags))
+        issues
     }
 
-    pub fn delete(&self, type_: &str, name: &str) -> IndyResult<()> {
-        let etype = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
-        let ename = encrypt_as_searchable(name.as_bytes(), &self.keys.name_key, &self.keys.item_hmac_key);
+    fn _decrypt_string(&self, data: &[u8], key: &chacha20poly1305_ietf::Key) -> IndyResult<String> {
+        String::from_utf8(decrypt_merged(data, key)?)
+            .to_indy(IndyErrorKind::WalletEncryptionError, "Decrypted data is invalid utf8")
+    }
+}
 
-        self.storage.delete(&etype, &ename)?;
-        Ok(())
+fn _record_issue(kind: WalletCheckIssueKind, type_: Option<String>, id: Option<String>, err: IndyError) -> WalletCheckIssue {
+    WalletCheckIssue {
+        kind,
+        type_,
+        id,
+        details: err.to_string(),
+        repaired: false,
     }
+}
 
-    pub fn search<'a>(&'a self, type_: &str, query: &str, options: Option<&str>) -> IndyResult<WalletIterator> {
-        let parsed_query: Query = ::serde_json::from_str(query)
-            .map_err(|err| IndyError::from_msg(IndyErrorKind::WalletQueryError, err))?;
+fn _now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0)
+}
 
-        let encrypted_query = encrypt_query(parsed_query, &self.keys)?;
-        let encrypted_type_ = encrypt_as_searchable(type_.as_bytes(), &self.keys.type_key, &self.keys.item_hmac_key);
-        let storage_iterator = self.storage.search(&encrypted_type_, &encrypted_query, options)?;
-        let wallet_iterator = WalletIterator::new(storage_iterator, Rc::clone(&self.keys));
-        Ok(wallet_iterator)
-    }
+fn _expiry_value(expires_at: u64) -> String {
+    format!("{:020}", expires_at)
+}
 
-    pub fn close(&mut self) -> IndyResult<()> {
-        self.storage.close()
-            .map_err(IndyError::from)
-    }
+fn _is_expired(expires_at: &str) -> bool {
+    expires_at <= _expiry_value(_now()).as_str()
+}
 
-    pub fn get_all(&self) -> IndyResult<WalletIterator> {
-        let all_items = self.storage.get_all()?;
-        Ok(WalletIterator::new(all_items, Rc::clone(&self.keys)))
+fn _find_expiry(tags: &Option<Vec<Tag>>, expiry_tag_name: &[u8]) -> Option<String> {
+    tags.as_ref()?.iter()
+        .filter_map(|tag| match *tag {
+            Tag::PlainText(ref name, ref value) if name[..] == expiry_tag_name[..] => Some(value.clone()),
+            _ => None
+        })
+        .next()
+}
+
+fn _without_expiry(mut tags: HashMap<String, String>) -> HashMap<String, String> {
+    tags.remove(EXPIRY_TAG_NAME);
+    tags
+}
+
+// Last value of each group is expiry. Groups of expired records are dropped, the rest are merged
+fn _merge_not_expired(groups: Vec<(Vec<Option<String>>, usize)>) -> Vec<(Vec<Option<String>>, usize)> {
+    let mut merged: BTreeMap<Vec<Option<String>>, usize> = BTreeMap::new();
+
+    for (mut values, count) in groups {
+        match values.pop() {
+            Some(Some(ref expires_at)) if _is_expired(expires_at) => continue,
+            _ => *merged.entry(values).or_insert(0) += count,
+        }
     }
 
-    pub fn get_id<'a>(&'a self) -> &'a str {
-        &self.id
+    merged.into_iter().collect()
+}
+
+// Applies operations one by one if storage doesn't support transactions.
+// Operations applied before failure are kept then
+fn _apply(storage: &dyn storage::WalletStorage, operations: &[StorageOperation]) -> IndyResult<()> {
+    if storage.supports_transactions() {
+        return storage.batch(operations);
+    }
+
+    for operation in operations {
+        match *operation {
+            StorageOperation::Add(ref type_, ref id, ref value, ref tags) => storage.add(type_, id, value, tags)?,
+            StorageOperation::Update(ref type_, ref id, ref value) => storage.update(type_, id, value)?,
+            StorageOperation::AddTags(ref type_, ref id, ref tags) => storage.add_tags(type_, id, tags)?,
+            StorageOperation::UpdateTags(ref type_, ref id, ref tags) => storage.update_tags(type_, id, tags)?,
+            StorageOperation::DeleteTags(ref type_, ref id, ref tag_names) => storage.delete_tags(type_, id, tag_names)?,
+            StorageOperation::Delete(ref type_, ref id) => storage.delete(type_, id)?,
+        }
     }
+
+    Ok(())
+}
+
+// Records without expiry tag never expire
+fn _not_expired(query: Query) -> Query {
+    Query::And(vec![
+        query,
+        Query::Or(vec![
+            Query::Exists(EXPIRY_TAG_NAME.to_string(), false),
+            Query::Gt(EXPIRY_TAG_NAME.to_string(), _expiry_value(_now())),
+        ]),
+    ])
 }
 
 #[cfg(test)]
@@ -221,6 +1151,7 @@ mod tests {
     use serde_json;
     use std::rc::Rc;
     use std::collections::HashMap;
+    use std::iter;
 
     use crate::{Metadata, MetadataArgon};
     use crate::encryption;
@@ -228,6 +1159,7 @@ mod tests {
     use crate::storage::WalletStorageType;
     use crate::storage::default::SQLiteStorageType;
     use crate::language::*;
+    use indy_api_types::domain::wallet::{WalletAggregation, WalletAggregationGroup};
     use indy_utils::test;
 
     macro_rules! jsonstr {
@@ -1177,171 +2109,537 @@ mod tests {
     }
 
     #[test]
-    fn wallet_search_works_for_lt_encrypted() {
-        test::cleanup_wallet("wallet_search_works_for_lt_encrypted");
+    fn wallet_search_works_for_lt_encrypted() {
+        test::cleanup_wallet("wallet_search_works_for_lt_encrypted");
+        {
+            let mut wallet = _wallet("wallet_search_works_for_lt_encrypted");
+
+            let res = wallet.search(_type1(),
+                                    &jsonstr!({"tag_name": {"$lt": "4"}}),
+                                    Some(&_search_options(true, false, false, true, false)));
+
+            assert_kind!(IndyErrorKind::WalletQueryError, res);
+            wallet.close().unwrap();
+        }
+        test::cleanup_wallet("wallet_search_works_for_lt_encrypted");
+    }
+
+    #[test]
+    fn wallet_search_works_for_lte_plain() {
+        test::cleanup_wallet("wallet_search_works_for_lte_plain");
+        {
+            let mut wallet = _wallet("wallet_search_works_for_lte_plain");
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name":"2"})).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name":"3"})).unwrap();
+            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name":"4"})).unwrap();
+
+            let mut iterator = wallet.search(_type1(),
+                                             &jsonstr!({"~tag_name": {"$lte": "3"}}),
+                                             Some(&_search_options(true, false, false, true, false))).unwrap();
+
+            let expected_records = _sort(vec![
+                WalletRecord {
+                    type_: None,
+                    id: _id1().to_string(),
+                    value: Some(_value1().to_string()),
+                    tags: None,
+                },
+                WalletRecord {
+                    type_: None,
+                    id: _id2().to_string(),
+                    value: Some(_value2().to_string()),
+                    tags: None,
+                },
+            ]);
+
+            assert_eq!(_fetch_all(&mut iterator), expected_records);
+            assert!(iterator.get_total_count().unwrap().is_none());
+
+            // unsuccessful search with no matches
+            let mut iterator = wallet.search(_type1(),
+                                             &jsonstr!({"~tag_name": {"$lte": "1"}}),
+                                             Some(&_search_options(true, false, false, true, false))).unwrap();
+
+            assert!(iterator.next().unwrap().is_none());
+            assert!(iterator.get_total_count().unwrap().is_none());
+
+            // unsuccessful search with nonexisting value
+            let mut iterator = wallet.search(_type1(),
+                                             &jsonstr!({"~tag_name_different": {"$lte": "3"}}),
+                                             Some(&_search_options(true, false, false, true, false))).unwrap();
+
+            assert!(iterator.next().unwrap().is_none());
+            assert!(iterator.get_total_count().unwrap().is_none());
+
+            // unsuccessful search with different type_
+            let mut iterator = wallet.search(_type2(),
+                                             &jsonstr!({"~tag_name": {"$lte": "3"}}),
+                                             Some(&_search_options(true, false, false, true, false))).unwrap();
+
+            assert!(iterator.next().unwrap().is_none());
+            assert!(iterator.get_total_count().unwrap().is_none());
+            wallet.close().unwrap();
+        }
+        test::cleanup_wallet("wallet_search_works_for_lte_plain");
+    }
+
+    #[test]
+    fn wallet_search_works_for_lte_encrypted() {
+        test::cleanup_wallet("wallet_search_works_for_lte_encrypted");
+        {
+            let mut wallet = _wallet("wallet_search_works_for_lte_encrypted");
+
+            let res = wallet.search(_type1(),
+                                    &jsonstr!({"tag_name": {"$lte": "3"}}),
+                                    Some(&_search_options(true, false, false, true, false)));
+
+            assert_kind!(IndyErrorKind::WalletQueryError, res);
+            wallet.close().unwrap();
+        }
+        test::cleanup_wallet("wallet_search_works_for_lte_encrypted");
+    }
+
+    #[test]
+    fn wallet_search_works_for_like_plain() {
+        test::cleanup_wallet("wallet_search_works_for_like_plain");
+        {
+            let mut wallet = _wallet("wallet_search_works_for_like_plain");
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name": "tag_value_1"})).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name": "tag_value_2"})).unwrap();
+            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name": "not_matching"})).unwrap();
+
+            let mut iterator = wallet.search(_type1(),
+                                             &jsonstr!({"~tag_name": {"$like": "tag_value_%"}}),
+                                             Some(&_search_options(true, false, false, true, false))).unwrap();
+
+            let expected_records = _sort(vec![
+                WalletRecord {
+                    type_: None,
+                    id: _id1().to_string(),
+                    value: Some(_value1().to_string()),
+                    tags: None,
+                },
+                WalletRecord {
+                    type_: None,
+                    id: _id2().to_string(),
+                    value: Some(_value2().to_string()),
+                    tags: None,
+                },
+            ]);
+
+            assert_eq!(_fetch_all(&mut iterator), expected_records);
+            assert!(iterator.get_total_count().unwrap().is_none());
+
+            // unsuccessful search with no matches
+            let mut iterator = wallet.search(_type1(),
+                                             &jsonstr!({"~tag_name": {"$like": "tag_value_no_match%"}}),
+                                             Some(&_search_options(true, false, false, true, false))).unwrap();
+
+            assert!(iterator.next().unwrap().is_none());
+            assert!(iterator.get_total_count().unwrap().is_none());
+
+            // unsuccessful search with nonexisting value
+            let mut iterator = wallet.search(_type1(),
+                                             &jsonstr!({"~tag_name_different": {"$like": "tag_value_%"}}),
+                                             Some(&_search_options(true, false, false, true, false))).unwrap();
+
+            assert!(iterator.next().unwrap().is_none());
+            assert!(iterator.get_total_count().unwrap().is_none());
+
+            // unsuccessful search wrong type_
+            let mut iterator = wallet.search(_type2(),
+                                             &jsonstr!({"~tag_name": {"$like": "tag_value_%"}}),
+                                             Some(&_search_options(true, false, false, true, false))).unwrap();
+
+            assert!(iterator.next().unwrap().is_none());
+            assert!(iterator.get_total_count().unwrap().is_none());
+
+            wallet.close().unwrap();
+        }
+        test::cleanup_wallet("wallet_search_works_for_like_plain");
+    }
+
+    #[test]
+    fn wallet_search_works_for_like_encrypted() {
+        test::cleanup_wallet("wallet_search_works_for_like_encrypted");
+        {
+            let mut wallet = _wallet("wallet_search_works_for_like_encrypted");
+
+            let res = wallet.search(_type1(),
+                                    &jsonstr!({"tag_name": {"$like": "1"}}),
+                                    Some(&_search_options(true, false, false, true, false)));
+
+            assert_kind!(IndyErrorKind::WalletQueryError, res);
+
+            wallet.close().unwrap();
+        }
+        test::cleanup_wallet("wallet_search_works_for_like_encrypted");
+    }
+
+    #[test]
+    fn wallet_search_works_for_ilike_plain() {
+        test::cleanup_wallet("wallet_search_works_for_ilike_plain");
+        {
+            let wallet = _wallet("wallet_search_works_for_ilike_plain");
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name": "Tag_Value_1"})).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name": "not_matching"})).unwrap();
+
+            let mut iterator = wallet.search(_type1(),
+                                             &jsonstr!({"~tag_name": {"$ilike": "tag_VALUE_%"}}),
+                                             Some(&_search_options(true, false, false, true, false))).unwrap();
+
+            let expected_records = vec![
+                WalletRecord {
+                    type_: None,
+                    id: _id1().to_string(),
+                    value: Some(_value1().to_string()),
+                    tags: None,
+                },
+            ];
+
+            assert_eq!(_fetch_all(&mut iterator), expected_records);
+        }
+        test::cleanup_wallet("wallet_search_works_for_ilike_plain");
+    }
+
+    #[test]
+    fn wallet_search_works_for_regex_plain() {
+        test::cleanup_wallet("wallet_search_works_for_regex_plain");
+        {
+            let wallet = _wallet("wallet_search_works_for_regex_plain");
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name": "tag_value_1"})).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name": "tag_value_22"})).unwrap();
+            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name": "not_matching"})).unwrap();
+
+            let mut iterator = wallet.search(_type1(),
+                                             &jsonstr!({"~tag_name": {"$regex": "^tag_value_[0-9]$"}}),
+                                             Some(&_search_options(true, false, false, true, false))).unwrap();
+
+            let expected_records = vec![
+                WalletRecord {
+                    type_: None,
+                    id: _id1().to_string(),
+                    value: Some(_value1().to_string()),
+                    tags: None,
+                },
+            ];
+
+            assert_eq!(_fetch_all(&mut iterator), expected_records);
+        }
+        test::cleanup_wallet("wallet_search_works_for_regex_plain");
+    }
+
+    #[test]
+    fn wallet_search_works_for_invalid_regex() {
+        test::cleanup_wallet("wallet_search_works_for_invalid_regex");
+        {
+            let wallet = _wallet("wallet_search_works_for_invalid_regex");
+
+            let res = wallet.search(_type1(),
+                                    &jsonstr!({"~tag_name": {"$regex": "tag_value_(1"}}),
+                                    Some(&_search_options(true, false, false, true, false)));
+
+            assert_kind!(IndyErrorKind::WalletQueryError, res);
+        }
+        test::cleanup_wallet("wallet_search_works_for_invalid_regex");
+    }
+
+    #[test]
+    fn wallet_search_works_for_ilike_and_regex_encrypted() {
+        test::cleanup_wallet("wallet_search_works_for_ilike_and_regex_encrypted");
         {
-            let mut wallet = _wallet("wallet_search_works_for_lt_encrypted");
+            let wallet = _wallet("wallet_search_works_for_ilike_and_regex_encrypted");
 
             let res = wallet.search(_type1(),
-                                    &jsonstr!({"tag_name": {"$lt": "4"}}),
+                                    &jsonstr!({"tag_name": {"$ilike": "1"}}),
                                     Some(&_search_options(true, false, false, true, false)));
+            assert_kind!(IndyErrorKind::WalletQueryError, res);
 
+            let res = wallet.search(_type1(),
+                                    &jsonstr!({"tag_name": {"$regex": "1"}}),
+                                    Some(&_search_options(true, false, false, true, false)));
             assert_kind!(IndyErrorKind::WalletQueryError, res);
-            wallet.close().unwrap();
         }
-        test::cleanup_wallet("wallet_search_works_for_lt_encrypted");
+        test::cleanup_wallet("wallet_search_works_for_ilike_and_regex_encrypted");
     }
 
     #[test]
-    fn wallet_search_works_for_lte_plain() {
-        test::cleanup_wallet("wallet_search_works_for_lte_plain");
+    fn wallet_search_works_for_exists() {
+        test::cleanup_wallet("wallet_search_works_for_exists");
         {
-            let mut wallet = _wallet("wallet_search_works_for_lte_plain");
-            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name":"2"})).unwrap();
-            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name":"3"})).unwrap();
-            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name":"4"})).unwrap();
+            let wallet = _wallet("wallet_search_works_for_exists");
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name": "tag_value_1", "enc_tag_name": "tag_value_1"})).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~other_tag_name": "tag_value_2"})).unwrap();
+
+            let record_1 = WalletRecord {
+                type_: None,
+                id: _id1().to_string(),
+                value: Some(_value1().to_string()),
+                tags: None,
+            };
+
+            let record_2 = WalletRecord {
+                type_: None,
+                id: _id2().to_string(),
+                value: Some(_value2().to_string()),
+                tags: None,
+            };
 
             let mut iterator = wallet.search(_type1(),
-                                             &jsonstr!({"~tag_name": {"$lte": "3"}}),
+                                             &jsonstr!({"~tag_name": {"$exists": true}}),
                                              Some(&_search_options(true, false, false, true, false))).unwrap();
+            assert_eq!(_fetch_all(&mut iterator), vec![record_1.clone()]);
 
-            let expected_records = _sort(vec![
-                WalletRecord {
-                    type_: None,
-                    id: _id1().to_string(),
-                    value: Some(_value1().to_string()),
-                    tags: None,
-                },
-                WalletRecord {
-                    type_: None,
-                    id: _id2().to_string(),
-                    value: Some(_value2().to_string()),
-                    tags: None,
-                },
-            ]);
-
-            assert_eq!(_fetch_all(&mut iterator), expected_records);
-            assert!(iterator.get_total_count().unwrap().is_none());
-
-            // unsuccessful search with no matches
             let mut iterator = wallet.search(_type1(),
-                                             &jsonstr!({"~tag_name": {"$lte": "1"}}),
+                                             &jsonstr!({"enc_tag_name": {"$exists": true}}),
                                              Some(&_search_options(true, false, false, true, false))).unwrap();
+            assert_eq!(_fetch_all(&mut iterator), vec![record_1]);
 
-            assert!(iterator.next().unwrap().is_none());
-            assert!(iterator.get_total_count().unwrap().is_none());
-
-            // unsuccessful search with nonexisting value
             let mut iterator = wallet.search(_type1(),
-                                             &jsonstr!({"~tag_name_different": {"$lte": "3"}}),
+                                             &jsonstr!({"~tag_name": {"$exists": false}}),
                                              Some(&_search_options(true, false, false, true, false))).unwrap();
+            assert_eq!(_fetch_all(&mut iterator), vec![record_2]);
+        }
+        test::cleanup_wallet("wallet_search_works_for_exists");
+    }
 
-            assert!(iterator.next().unwrap().is_none());
-            assert!(iterator.get_total_count().unwrap().is_none());
+    #[test]
+    fn wallet_aggregate_works() {
+        test::cleanup_wallet("wallet_aggregate_works");
+        {
+            let wallet = _wallet("wallet_aggregate_works");
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~issuer": "issuer_1", "~state": "active", "enc_tag_name": "tag_value"})).unwrap();
+            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~issuer": "issuer_1", "~state": "revoked"})).unwrap();
+            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~issuer": "issuer_2", "~state": "active"})).unwrap();
+            wallet.add(_type2(), _id1(), _value1(), &jsonmap!({"~issuer": "issuer_1", "~state": "active"})).unwrap();
+
+            let res = wallet.aggregate(_type1(), "{}", &WalletAggregation { group_by: vec!["~issuer".to_string()] }).unwrap();
+            assert_eq!(res.count, 3);
+            assert_eq!(res.groups, vec![
+                _aggregation_group(json!({"~issuer": "issuer_1"}), 2),
+                _aggregation_group(json!({"~issuer": "issuer_2"}), 1),
+            ]);
 
-            // unsuccessful search with different type_
-            let mut iterator = wallet.search(_type2(),
-                                             &jsonstr!({"~tag_name": {"$lte": "3"}}),
-                                             Some(&_search_options(true, false, false, true, false))).unwrap();
+            let res = wallet.aggregate(_type1(),
+                                       &jsonstr!({"~state": "active"}),
+                                       &WalletAggregation { group_by: vec!["~issuer".to_string(), "~missing".to_string()] }).unwrap();
+            assert_eq!(res.count, 2);
+            assert_eq!(res.groups, vec![
+                _aggregation_group(json!({"~issuer": "issuer_1", "~missing": null}), 1),
+                _aggregation_group(json!({"~issuer": "issuer_2", "~missing": null}), 1),
+            ]);
 
-            assert!(iterator.next().unwrap().is_none());
-            assert!(iterator.get_total_count().unwrap().is_none());
-            wallet.close().unwrap();
+            let res = wallet.aggregate(_type1(), &jsonstr!({"enc_tag_name": "tag_value"}), &WalletAggregation::default()).unwrap();
+            assert_eq!(res.count, 1);
+            assert!(res.groups.is_empty());
         }
-        test::cleanup_wallet("wallet_search_works_for_lte_plain");
+        test::cleanup_wallet("wallet_aggregate_works");
     }
 
     #[test]
-    fn wallet_search_works_for_lte_encrypted() {
-        test::cleanup_wallet("wallet_search_works_for_lte_encrypted");
+    fn wallet_aggregate_works_for_encrypted_tag() {
+        test::cleanup_wallet("wallet_aggregate_works_for_encrypted_tag");
         {
-            let mut wallet = _wallet("wallet_search_works_for_lte_encrypted");
-
-            let res = wallet.search(_type1(),
-                                    &jsonstr!({"tag_name": {"$lte": "3"}}),
-                                    Some(&_search_options(true, false, false, true, false)));
+            let wallet = _wallet("wallet_aggregate_works_for_encrypted_tag");
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"enc_tag_name": "tag_value"})).unwrap();
 
+            let res = wallet.aggregate(_type1(), "{}", &WalletAggregation { group_by: vec!["enc_tag_name".to_string()] });
             assert_kind!(IndyErrorKind::WalletQueryError, res);
-            wallet.close().unwrap();
         }
-        test::cleanup_wallet("wallet_search_works_for_lte_encrypted");
+        test::cleanup_wallet("wallet_aggregate_works_for_encrypted_tag");
     }
 
     #[test]
-    fn wallet_search_works_for_like_plain() {
-        test::cleanup_wallet("wallet_search_works_for_like_plain");
+    fn wallet_get_works_for_expired_record_without_tags() {
+        test::cleanup_wallet("wallet_get_works_for_expired_record_without_tags");
         {
-            let mut wallet = _wallet("wallet_search_works_for_like_plain");
-            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~tag_name": "tag_value_1"})).unwrap();
-            wallet.add(_type1(), _id2(), _value2(), &jsonmap!({"~tag_name": "tag_value_2"})).unwrap();
-            wallet.add(_type1(), _id3(), _value3(), &jsonmap!({"~tag_name": "not_matching"})).unwrap();
+            let wallet = _wallet("wallet_get_works_for_expired_record_without_tags");
+            wallet.add_with_expiry(_type1(), _id1(), _value1(), &_tags(), Some(0)).unwrap();
 
-            let mut iterator = wallet.search(_type1(),
-                                             &jsonstr!({"~tag_name": {"$like": "tag_value_%"}}),
-                                             Some(&_search_options(true, false, false, true, false))).unwrap();
+            let res = wallet.get(_type1(), _id1(), &_fetch_options(false, true, false));
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        test::cleanup_wallet("wallet_get_works_for_expired_record_without_tags");
+    }
 
-            let expected_records = _sort(vec![
+    #[test]
+    fn wallet_get_all_works_for_expired_record() {
+        test::cleanup_wallet("wallet_get_all_works_for_expired_record");
+        {
+            let wallet = _wallet("wallet_get_all_works_for_expired_record");
+            wallet.add_with_expiry(_type1(), _id1(), _value1(), &_tags(), Some(3600)).unwrap();
+            wallet.add_with_expiry(_type1(), _id2(), _value2(), &_tags(), Some(0)).unwrap();
+
+            let mut iterator = wallet.get_all().unwrap();
+
+            assert_eq!(_fetch_all(&mut iterator), vec![
+                WalletRecord {
+                    type_: Some(_type1().to_string()),
+                    id: _id1().to_string(),
+                    value: Some(_value1().to_string()),
+                    tags: Some(_tags()),
+                }
+            ]);
+        }
+        test::cleanup_wallet("wallet_get_all_works_for_expired_record");
+    }
+
+    #[test]
+    fn wallet_search_works_for_expired_record_and_storage_without_exists_query() {
+        test::cleanup_wallet("wallet_search_works_for_expired_record_and_storage_without_exists_query");
+        {
+            let wallet = _legacy_wallet("wallet_search_works_for_expired_record_and_storage_without_exists_query");
+            wallet.add_with_expiry(_type1(), _id1(), _value1(), &_tags(), Some(3600)).unwrap();
+            wallet.add_with_expiry(_type1(), _id2(), _value2(), &_tags(), Some(0)).unwrap();
+
+            let mut iterator = wallet.search(_type1(), "{}", Some(&_search_options(true, false, false, true, false))).unwrap();
+            assert_eq!(_fetch_all(&mut iterator), vec![
                 WalletRecord {
                     type_: None,
                     id: _id1().to_string(),
                     value: Some(_value1().to_string()),
                     tags: None,
-                },
+                }
+            ]);
+
+            let mut iterator = wallet.search(_type1(), "{}", Some(&_search_options(true, false, false, false, true))).unwrap();
+            assert_eq!(_fetch_all(&mut iterator), vec![
                 WalletRecord {
                     type_: None,
-                    id: _id2().to_string(),
-                    value: Some(_value2().to_string()),
-                    tags: None,
-                },
+                    id: _id1().to_string(),
+                    value: None,
+                    tags: Some(_tags()),
+                }
             ]);
+        }
+        test::cleanup_wallet("wallet_search_works_for_expired_record_and_storage_without_exists_query");
+    }
 
-            assert_eq!(_fetch_all(&mut iterator), expected_records);
-            assert!(iterator.get_total_count().unwrap().is_none());
+    #[test]
+    fn wallet_aggregate_works_for_expired_record_and_storage_without_exists_query() {
+        test::cleanup_wallet("wallet_aggregate_works_for_expired_record_and_storage_without_exists_query");
+        {
+            let wallet = _legacy_wallet("wallet_aggregate_works_for_expired_record_and_storage_without_exists_query");
+            wallet.add(_type1(), _id1(), _value1(), &jsonmap!({"~issuer": "issuer_1"})).unwrap();
+            wallet.add_with_expiry(_type1(), _id2(), _value2(), &jsonmap!({"~issuer": "issuer_1"}), Some(3600)).unwrap();
+            wallet.add_with_expiry(_type1(), _id3(), _value3(), &jsonmap!({"~issuer": "issuer_2"}), Some(0)).unwrap();
+
+            let res = wallet.aggregate(_type1(), "{}", &WalletAggregation { group_by: vec!["~issuer".to_string()] }).unwrap();
+            assert_eq!(res.count, 2);
+            assert_eq!(res.groups, vec![
+                _aggregation_group(json!({"~issuer": "issuer_1"}), 2),
+            ]);
+        }
+        test::cleanup_wallet("wallet_aggregate_works_for_expired_record_and_storage_without_exists_query");
+    }
 
-            // unsuccessful search with no matches
-            let mut iterator = wallet.search(_type1(),
-                                             &jsonstr!({"~tag_name": {"$like": "tag_value_no_match%"}}),
-                                             Some(&_search_options(true, false, false, true, false))).unwrap();
+    #[test]
+    fn wallet_update_with_expiry_works_for_storage_without_transactions() {
+        test::cleanup_wallet("wallet_update_with_expiry_works_for_storage_without_transactions");
+        {
+            let wallet = _legacy_wallet("wallet_update_with_expiry_works_for_storage_without_transactions");
+            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
 
-            assert!(iterator.next().unwrap().is_none());
-            assert!(iterator.get_total_count().unwrap().is_none());
+            wallet.update_with_expiry(_type1(), _id1(), _value2(), Some(Some(3600))).unwrap();
+            let record = wallet.get(_type1(), _id1(), &_fetch_options(false, true, true)).unwrap();
+            assert_eq!(record.value, Some(_value2().to_string()));
+            assert_eq!(record.tags, Some(_tags()));
 
-            // unsuccessful search with nonexisting value
-            let mut iterator = wallet.search(_type1(),
-                                             &jsonstr!({"~tag_name_different": {"$like": "tag_value_%"}}),
-                                             Some(&_search_options(true, false, false, true, false))).unwrap();
+            wallet.update_with_expiry(_type1(), _id1(), _value3(), Some(Some(0))).unwrap();
+            let res = wallet.get(_type1(), _id1(), &_fetch_options(false, true, true));
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+        }
+        test::cleanup_wallet("wallet_update_with_expiry_works_for_storage_without_transactions");
+    }
 
-            assert!(iterator.next().unwrap().is_none());
-            assert!(iterator.get_total_count().unwrap().is_none());
+    #[test]
+    fn wallet_take_changes_works() {
+        test::cleanup_wallet("wallet_take_changes_works");
+        {
+            let wallet = _wallet("wallet_take_changes_works");
+            wallet.track_types(vec![_type1().to_string()].into_iter().collect());
 
-            // unsuccessful search wrong type_
-            let mut iterator = wallet.search(_type2(),
-                                             &jsonstr!({"~tag_name": {"$like": "tag_value_%"}}),
-                                             Some(&_search_options(true, false, false, true, false))).unwrap();
+            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
+            wallet.add(_type2(), _id1(), _value1(), &_tags()).unwrap();
+            wallet.update_tags(_type1(), _id1(), &jsonmap!({"tag1": "tag_value_2"})).unwrap();
+            wallet.update(_type1(), _id1(), _value2()).unwrap();
+            wallet.delete(_type1(), _id1()).unwrap();
 
-            assert!(iterator.next().unwrap().is_none());
-            assert!(iterator.get_total_count().unwrap().is_none());
+            assert_eq!(wallet.take_changes(), vec![
+                _change(_type1(), _id1(), None, Some(_tags())),
+                _change(_type1(), _id1(), Some(_tags()), Some(jsonmap!({"tag1": "tag_value_2"}))),
+                _change(_type1(), _id1(), Some(jsonmap!({"tag1": "tag_value_2"})), Some(jsonmap!({"tag1": "tag_value_2"}))),
+                _change(_type1(), _id1(), Some(jsonmap!({"tag1": "tag_value_2"})), None),
+            ]);
+            assert!(wallet.take_changes().is_empty());
+        }
+        test::cleanup_wallet("wallet_take_changes_works");
+    }
 
-            wallet.close().unwrap();
+    #[test]
+    fn wallet_take_changes_works_for_expired_records() {
+        test::cleanup_wallet("wallet_take_changes_works_for_expired_records");
+        {
+            let wallet = _wallet("wallet_take_changes_works_for_expired_records");
+            wallet.add_with_expiry(_type1(), _id1(), _value1(), &_tags(), Some(0)).unwrap();
+            wallet.add_with_expiry(_type1(), _id2(), _value2(), &_tags(), Some(0)).unwrap();
+
+            wallet.track_types(vec![_type1().to_string()].into_iter().collect());
+
+            let res = wallet.get(_type1(), _id1(), &_fetch_options(false, true, false));
+            assert_kind!(IndyErrorKind::WalletItemNotFound, res);
+            assert_eq!(wallet.take_changes(), vec![_change(_type1(), _id1(), Some(_tags()), None)]);
+
+            wallet.search(_type1(), "{}", None).unwrap();
+            assert_eq!(wallet.take_changes(), vec![_change(_type1(), _id2(), Some(_tags()), None)]);
         }
-        test::cleanup_wallet("wallet_search_works_for_like_plain");
+        test::cleanup_wallet("wallet_take_changes_works_for_expired_records");
     }
 
     #[test]
-    fn wallet_search_works_for_like_encrypted() {
-        test::cleanup_wallet("wallet_search_works_for_like_encrypted");
+    fn wallet_take_changes_works_for_expired_records_without_reading_others() {
+        test::cleanup_wallet("wallet_take_changes_works_for_expired_records_without_reading_others");
         {
-            let mut wallet = _wallet("wallet_search_works_for_like_encrypted");
+            let (wallet, fetched) = _scan_counting_wallet("wallet_take_changes_works_for_expired_records_without_reading_others");
+            wallet.add(_type1(), _id1(), _value1(), &_tags()).unwrap();
+            wallet.add_with_expiry(_type1(), _id2(), _value2(), &_tags(), Some(3600)).unwrap();
+            wallet.add_with_expiry(_type1(), _id3(), _value3(), &_tags(), Some(0)).unwrap();
+            wallet.add_with_expiry(_type2(), _id1(), _value1(), &_tags(), Some(0)).unwrap();
 
-            let res = wallet.search(_type1(),
-                                    &jsonstr!({"tag_name": {"$like": "1"}}),
-                                    Some(&_search_options(true, false, false, true, false)));
+            wallet.track_types(vec![_type1().to_string()].into_iter().collect());
+            fetched.set(0);
 
-            assert_kind!(IndyErrorKind::WalletQueryError, res);
+            let expired = wallet._tracked_expired(_now()).unwrap();
+            assert_eq!(expired, vec![(_type1().to_string(), _id3().to_string(), _tags())]);
+            assert_eq!(fetched.get(), 1);
+        }
+        test::cleanup_wallet("wallet_take_changes_works_for_expired_records_without_reading_others");
+    }
 
-            wallet.close().unwrap();
+    #[test]
+    fn wallet_take_changes_works_for_add_all_and_batch() {
+        test::cleanup_wallet("wallet_take_changes_works_for_add_all_and_batch");
+        {
+            let wallet = _wallet("wallet_take_changes_works_for_add_all_and_batch");
+            wallet.track_types(vec![_type1().to_string()].into_iter().collect());
+
+            wallet.add_all(iter::once(Ok(Record { type_: _type1().to_string(), id: _id1().to_string(), value: _value1().to_string(), tags: _tags() }))).unwrap();
+            assert_eq!(wallet.take_changes(), vec![_change(_type1(), _id1(), None, Some(_tags()))]);
+
+            let operations: Vec<WalletOperation> = serde_json::from_value(json!([
+                {"operation": "addTags", "type": _type1(), "id": _id1(), "tags": {"tag2": "tag_value_2"}},
+                {"operation": "deleteTags", "type": _type1(), "id": _id1(), "tagNames": ["tag1"]},
+                {"operation": "delete", "type": _type1(), "id": _id1()},
+            ])).unwrap();
+            wallet.batch(&operations).unwrap();
+
+            assert_eq!(wallet.take_changes(), vec![
+                _change(_type1(), _id1(), Some(_tags()), Some(jsonmap!({"tag1": "tag_value_1", "tag2": "tag_value_2"}))),
+                _change(_type1(), _id1(), Some(jsonmap!({"tag1": "tag_value_1", "tag2": "tag_value_2"})), Some(jsonmap!({"tag2": "tag_value_2"}))),
+                _change(_type1(), _id1(), Some(jsonmap!({"tag2": "tag_value_2"})), None),
+            ]);
         }
-        test::cleanup_wallet("wallet_search_works_for_like_encrypted");
+        test::cleanup_wallet("wallet_take_changes_works_for_add_all_and_batch");
     }
 
     #[test]
@@ -1945,6 +3243,10 @@ mod tests {
         jsonmap!({"tag1": "tag_value_1"})
     }
 
+    fn _change(type_: &str, id: &str, old_tags: Option<HashMap<String, String>>, new_tags: Option<HashMap<String, String>>) -> RecordChange {
+        RecordChange { type_: type_.to_string(), id: id.to_string(), old_tags, new_tags }
+    }
+
     //    fn _wallet_id() -> &'static str {
     //        "w1"
     //    }
@@ -1976,6 +3278,173 @@ mod tests {
         Wallet::new(name.to_string(), storage, Rc::new(keys))
     }
 
+    // Wallet over storage that behaves like plugged one implemented before record expiry:
+    // `$exists` isn't evaluated and transactions aren't supported
+    fn _legacy_wallet(name: &str) -> Wallet {
+        let wallet = _wallet(name);
+        Wallet::new(wallet.id, Box::new(LegacyStorage(wallet.storage)), wallet.keys)
+    }
+
+    struct LegacyStorage(Box<dyn storage::WalletStorage>);
+
+    impl storage::WalletStorage for LegacyStorage {
+        fn get(&self, type_: &[u8], id: &[u8], options: &str) -> IndyResult<StorageRecord> {
+            self.0.get(type_, id, options)
+        }
+
+        fn add(&self, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> IndyResult<()> {
+            self.0.add(type_, id, value, tags)
+        }
+
+        fn update(&self, type_: &[u8], id: &[u8], value: &EncryptedValue) -> IndyResult<()> {
+            self.0.update(type_, id, value)
+        }
+
+        fn add_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+            self.0.add_tags(type_, id, tags)
+        }
+
+        fn update_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+            self.0.update_tags(type_, id, tags)
+        }
+
+        fn delete_tags(&self, type_: &[u8], id: &[u8], tag_names: &[storage::TagName]) -> IndyResult<()> {
+            self.0.delete_tags(type_, id, tag_names)
+        }
+
+        fn delete(&self, type_: &[u8], id: &[u8]) -> IndyResult<()> {
+            self.0.delete(type_, id)
+        }
+
+        fn batch(&self, _operations: &[StorageOperation]) -> IndyResult<()> {
+            Err(err_msg(IndyErrorKind::WalletStorageError, "Transactions are not supported"))
+        }
+
+        fn get_storage_metadata(&self) -> IndyResult<Vec<u8>> {
+            self.0.get_storage_metadata()
+        }
+
+        fn set_storage_metadata(&self, metadata: &[u8]) -> IndyResult<()> {
+            self.0.set_storage_metadata(metadata)
+        }
+
+        fn get_all(&self) -> IndyResult<Box<dyn storage::StorageIterator>> {
+            self.0.get_all()
+        }
+
+        fn search(&self, type_: &[u8], query: &Operator, options: Option<&str>) -> IndyResult<Box<dyn storage::StorageIterator>> {
+            self.0.search(type_, query, options)
+        }
+
+        fn close(&mut self) -> IndyResult<()> {
+            self.0.close()
+        }
+
+        fn supports_transactions(&self) -> bool {
+            false
+        }
+    }
+
+    // Counts records returned by storage iterators
+    fn _scan_counting_wallet(name: &str) -> (Wallet, Rc<Cell<usize>>) {
+        let wallet = _wallet(name);
+        let fetched = Rc::new(Cell::new(0));
+        let storage = ScanCountingStorage { storage: wallet.storage, fetched: fetched.clone() };
+        (Wallet::new(wallet.id, Box::new(storage), wallet.keys), fetched)
+    }
+
+    struct ScanCountingStorage {
+        storage: Box<dyn storage::WalletStorage>,
+        fetched: Rc<Cell<usize>>,
+    }
+
+    struct ScanCountingIterator {
+        iterator: Box<dyn storage::StorageIterator>,
+        fetched: Rc<Cell<usize>>,
+    }
+
+    impl storage::StorageIterator for ScanCountingIterator {
+        fn next(&mut self) -> IndyResult<Option<StorageRecord>> {
+            let record = self.iterator.next()?;
+            if record.is_some() {
+                self.fetched.set(self.fetched.get() + 1);
+            }
+            Ok(record)
+        }
+
+        fn get_total_count(&self) -> IndyResult<Option<usize>> {
+            self.iterator.get_total_count()
+        }
+    }
+
+    impl ScanCountingStorage {
+        fn _counting(&self, iterator: Box<dyn storage::StorageIterator>) -> Box<dyn storage::StorageIterator> {
+            Box::new(ScanCountingIterator { iterator, fetched: self.fetched.clone() })
+        }
+    }
+
+    impl storage::WalletStorage for ScanCountingStorage {
+        fn get(&self, type_: &[u8], id: &[u8], options: &str) -> IndyResult<StorageRecord> {
+            self.storage.get(type_, id, options)
+        }
+
+        fn add(&self, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> IndyResult<()> {
+            self.storage.add(type_, id, value, tags)
+        }
+
+        fn update(&self, type_: &[u8], id: &[u8], value: &EncryptedValue) -> IndyResult<()> {
+            self.storage.update(type_, id, value)
+        }
+
+        fn add_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+            self.storage.add_tags(type_, id, tags)
+        }
+
+        fn update_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+            self.storage.update_tags(type_, id, tags)
+        }
+
+        fn delete_tags(&self, type_: &[u8], id: &[u8], tag_names: &[storage::TagName]) -> IndyResult<()> {
+            self.storage.delete_tags(type_, id, tag_names)
+        }
+
+        fn delete(&self, type_: &[u8], id: &[u8]) -> IndyResult<()> {
+            self.storage.delete(type_, id)
+        }
+
+        fn batch(&self, operations: &[StorageOperation]) -> IndyResult<()> {
+            self.storage.batch(operations)
+        }
+
+        fn get_storage_metadata(&self) -> IndyResult<Vec<u8>> {
+            self.storage.get_storage_metadata()
+        }
+
+        fn set_storage_metadata(&self, metadata: &[u8]) -> IndyResult<()> {
+            self.storage.set_storage_metadata(metadata)
+        }
+
+        fn get_all(&self) -> IndyResult<Box<dyn storage::StorageIterator>> {
+            Ok(self._counting(self.storage.get_all()?))
+        }
+
+        fn search(&self, type_: &[u8], query: &Operator, options: Option<&str>) -> IndyResult<Box<dyn storage::StorageIterator>> {
+            Ok(self._counting(self.storage.search(type_, query, options)?))
+        }
+
+        fn close(&mut self) -> IndyResult<()> {
+            self.storage.close()
+        }
+
+        fn supports_exists_query(&self) -> bool {
+            self.storage.supports_exists_query()
+        }
+
+        fn delete_expired(&self, expiry_tag_name: &[u8], now: &str) -> IndyResult<usize> {
+            self.storage.delete_expired(expiry_tag_name, now)
+        }
+    }
+
     fn _exists_wallet(name: &str) -> Wallet {
         let storage_type = SQLiteStorageType::new();
         let storage = storage_type.open_storage(name, None, None).unwrap();
@@ -2027,6 +3496,13 @@ mod tests {
         }).to_string()
     }
 
+    fn _aggregation_group(tags: serde_json::Value, count: usize) -> WalletAggregationGroup {
+        WalletAggregationGroup {
+            tags: serde_json::from_value(tags).unwrap(),
+            count,
+        }
+    }
+
     fn _fetch_all<'a>(iterator: &mut WalletIterator) -> Vec<WalletRecord> {
         let mut v = Vec::new();
 
@@ -124,6 +124,85 @@ pub extern fn indy_get_schema(command_handle: CommandHandle,
     res
 }
 
+/// Resolves DID to W3C DID Document built from ledger data.
+/// If data is present inside of cache, cached data is returned.
+/// Otherwise data is fetched from the ledger and stored inside of cache for future use.
+///
+/// DID Document contains NYM verkey as Ed25519VerificationKey2018 verification method
+/// and X25519 key agreement key derived from it, services built from `endpoint` ATTRIB
+/// and content of `diddocContent` ATTRIB merged in.
+/// Ledger replies are verified by state proof or consensus of the pool nodes.
+///
+/// EXPERIMENTAL
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// pool_handle: pool handle (created by open_pool_ledger).
+/// wallet_handle: wallet handle (created by open_wallet).
+/// did: DID to resolve.
+/// options_json:
+///  {
+///    noCache: (bool, optional, false by default) Skip usage of cache,
+///    noUpdate: (bool, optional, false by default) Use only cached data, do not try to update.
+///    noStore: (bool, optional, false by default) Skip storing fresh data if updated,
+///    minFresh: (int, optional, 3600 by default) Return cached data if not older than this many seconds. -1 means do not check age.
+///  }
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// did_resolution_result_json:
+///  {
+///    didDocument: DID Document json,
+///    didDocumentMetadata: {
+///        seqNo: (optional) sequence number of NYM transaction,
+///        txnTime: (optional) time of NYM transaction,
+///        role: (optional) role of DID,
+///    },
+///    didResolutionMetadata: {
+///        contentType: "application/did+ld+json",
+///        lastTxnTime: (optional) time of ledger state NYM reply was verified against,
+///    }
+///  }
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Ledger*
+#[no_mangle]
+pub extern fn indy_resolve_did(command_handle: CommandHandle,
+                               pool_handle: PoolHandle,
+                               wallet_handle: WalletHandle,
+                               did: *const c_char,
+                               options_json: *const c_char,
+                               cb: Option<extern fn(command_handle_: CommandHandle,
+                                                    err: ErrorCode,
+                                                    did_resolution_result_json: *const c_char)>) -> ErrorCode {
+    trace!("indy_resolve_did: >>> pool_handle: {:?}, wallet_handle: {:?}, did: {:?}, options_json: {:?}",
+           pool_handle, wallet_handle, did, options_json);
+
+    check_useful_validatable_string!(did, ErrorCode::CommonInvalidParam4, DidValue);
+    check_useful_json!(options_json, ErrorCode::CommonInvalidParam5, GetCacheOptions);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    trace!("indy_resolve_did: entities >>> pool_handle: {:?}, wallet_handle: {:?}, did: {:?}, options_json: {:?}",
+           pool_handle, wallet_handle, did, options_json);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Cache(CacheCommand::ResolveDid(
+            pool_handle,
+            wallet_handle,
+            did,
+            options_json,
+            boxed_callback_string!("indy_resolve_did", cb, command_handle)
+        )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_resolve_did: <<< res: {:?}", res);
+
+    res
+}
+
 /// Purge credential definition cache.
 ///
 /// EXPERIMENTAL
@@ -213,3 +292,48 @@ pub extern fn indy_purge_schema_cache(command_handle: CommandHandle,
 
     res
 }
+
+/// Purge DID Document cache.
+///
+/// EXPERIMENTAL
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// options_json:
+///  {
+///    maxAge: (int, optional, -1 by default) Purge cached data if older than this many seconds. -1 means purge all.
+///  }
+/// cb: Callback that takes command result as parameter.
+#[no_mangle]
+pub extern fn indy_purge_did_doc_cache(command_handle: CommandHandle,
+                                       wallet_handle: WalletHandle,
+                                       options_json: *const c_char,
+                                       cb: Option<extern fn(command_handle_: CommandHandle,
+                                                            err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_purge_did_doc_cache: >>> wallet_handle: {:?}, options_json: {:?}",
+           wallet_handle, options_json);
+
+    check_useful_json!(options_json, ErrorCode::CommonInvalidParam3, PurgeOptions);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    trace!("indy_purge_did_doc_cache: entities >>> wallet_handle: {:?}, options_json: {:?}",
+           wallet_handle, options_json);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Cache(CacheCommand::PurgeDidDocCache(
+            wallet_handle,
+            options_json,
+            Box::new(move |result| {
+                let err = prepare_result!(result);
+                trace!("indy_purge_did_doc_cache:");
+                cb(command_handle, err)
+            })
+        )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_purge_did_doc_cache: <<< res: {:?}", res);
+
+    res
+}
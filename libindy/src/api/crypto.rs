@@ -2,14 +2,56 @@
 use indy_api_types::{ErrorCode, CommandHandle, WalletHandle};
 use crate::commands::{Command, CommandExecutor};
 use crate::commands::crypto::CryptoCommand;
-use crate::domain::crypto::pack::JWE;
-use crate::domain::crypto::key::KeyInfo;
+use crate::domain::crypto::pack::{JWE, PackMessageOptions};
+use crate::domain::crypto::jws::JWSHeader;
+use crate::domain::crypto::key::{KeyInfo, MnemonicConfig};
 use indy_api_types::errors::prelude::*;
 use indy_utils::ctypes;
 
 use serde_json;
 use libc::c_char;
+use zeroize::Zeroize;
 
+pub use crate::services::crypto::{FileKeyCustody, KeyCustody};
+pub use indy_utils::crypto::{ed25519_box, ed25519_sign};
+
+
+/// Register key custody implemented in Rust.
+///
+/// Keys created with "custody" option in key or did info are kept by the custody and wallet stores only
+/// custody specific key reference. Signing and encryption with such keys are delegated to the custody.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// name: Custody name that is referenced from key and did info.
+/// custody: Implementation of KeyCustody trait.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Ok if command is sent
+///
+/// #Errors
+/// InvalidState if custody with the same name is already registered
+pub fn register_key_custody(command_handle: CommandHandle,
+                            name: &str,
+                            custody: Box<dyn KeyCustody + Send>,
+                            cb: Box<dyn Fn(CommandHandle, IndyResult<()>) + Send>) -> IndyResult<()> {
+    trace!("register_key_custody: >>> command_handle: {:?}, name: {:?}", command_handle, name);
+
+    let res = CommandExecutor::instance()
+        .send(Command::Crypto(
+            CryptoCommand::RegisterKeyCustody(
+                name.to_string(),
+                custody,
+                Box::new(move |result| {
+                    trace!("register_key_custody: cb command_handle: {:?}, result: {:?}", command_handle, result);
+                    cb(command_handle, result)
+                })
+            )));
+
+    trace!("register_key_custody: <<< res: {:?}", res);
+    res
+}
 
 /// Creates keys pair and stores in the wallet.
 ///
@@ -20,7 +62,13 @@ use libc::c_char;
 /// {
 ///     "seed": string, (optional) Seed that allows deterministic key creation (if not set random one will be created).
 ///                                Can be UTF-8, base64 or hex string.
-///     "crypto_type": string, // Optional (if not set then ed25519 curve is used); Currently only 'ed25519' value is supported for this field.
+///     "crypto_type": string, // Optional (if not set then ed25519 curve is used); Supported values are 'ed25519', 'secp256k1' and 'p256'. 'secp256k1' and 'p256' keys can be used only for signing.
+///     "custody": string, // Optional name of registered key custody that keeps secret key instead of the wallet.
+///     "mnemonic": { // Optional BIP-39 mnemonic to derive ed25519 key seed from instead of "seed" (see indy_generate_mnemonic).
+///         "phrase": string, // Mnemonic words separated by spaces.
+///         "passphrase": string, // Optional BIP-39 passphrase.
+///         "derivation_path": string, // SLIP-0010 path with hardened indexes only, for example "m/0'/1'".
+///     }
 /// }
 /// cb: Callback that takes command result as parameter.
 ///
@@ -63,6 +111,60 @@ pub extern fn indy_create_key(command_handle: CommandHandle,
     res
 }
 
+/// Generates BIP-39 mnemonic for deterministic creation of keys and DIDs.
+///
+/// Mnemonic is not stored in the wallet. The same keys and DIDs can be created again in any wallet
+/// by passing mnemonic with the same derivation path to indy_create_key or indy_create_and_store_my_did.
+///
+/// #Params
+/// command_handle: Command handle to map callback to caller context.
+/// config_json: Mnemonic config as json. Example:
+/// {
+///     "word_count": int, // Optional number of words (if not set then 24 is used); Supported values are 12, 15, 18, 21 and 24.
+/// }
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Error Code
+/// cb:
+/// - command_handle_: command handle to map callback to caller context.
+/// - err: Error code.
+/// - mnemonic: Mnemonic words of BIP-39 English wordlist separated by spaces.
+///
+/// #Errors
+/// Common*
+#[no_mangle]
+pub extern fn indy_generate_mnemonic(command_handle: CommandHandle,
+                                     config_json: *const c_char,
+                                     cb: Option<extern fn(command_handle_: CommandHandle,
+                                                          err: ErrorCode,
+                                                          mnemonic: *const c_char)>) -> ErrorCode {
+    trace!("indy_generate_mnemonic: >>> config_json: {:?}", config_json);
+
+    check_useful_json!(config_json, ErrorCode::CommonInvalidParam2, MnemonicConfig);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    trace!("indy_generate_mnemonic: entities >>> config_json: {:?}", config_json);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Crypto(CryptoCommand::GenerateMnemonic(
+            config_json,
+            Box::new(move |result| {
+                // mnemonic isn't logged and is wiped once passed to the caller
+                let (err, mnemonic) = prepare_result_1!(result, String::new());
+                let mnemonic = ctypes::string_to_cstring(mnemonic);
+                cb(command_handle, err, mnemonic.as_ptr());
+                mnemonic.into_bytes().zeroize();
+            })
+        )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_generate_mnemonic: <<< res: {:?}", res);
+
+    res
+}
+
 /// Saves/replaces the meta information for the giving key in the wallet.
 ///
 /// #Params
@@ -285,6 +387,141 @@ pub  extern fn indy_crypto_verify(command_handle: CommandHandle,
     res
 }
 
+/// Signs a payload with a key and serializes the result as JWS (RFC 7515).
+///
+/// Algorithm is defined by the key crypto type: EdDSA for ed25519, ES256K for secp256k1 and ES256 for p256 keys.
+///
+/// Note to use DID keys with this function you can call indy_key_for_did to get key id (verkey)
+/// for specific DID.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// signer_vk: id (verkey) of my key. The key must be created by calling indy_create_key or indy_create_and_store_my_did
+/// payload_raw: a pointer to first byte of payload to be signed
+/// payload_len: a payload length
+/// header_json: (optional) additional protected header parameters as json object.
+///              "alg" is set from the key and "kid" defaults to signer_vk.
+///              Example: {"typ": "JWT", "kid": "did:example:123#key-1"}
+/// detached: whether payload must be omitted from the result (RFC 7515 Appendix F)
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// jws_compact: JWS in compact serialization: <protected>.<payload>.<signature> (payload is empty if detached)
+/// jws_json: JWS in flattened JSON serialization:
+///   {
+///     "protected": <b64URLencode(header)>,
+///     "payload": <b64URLencode(payload)>, // omitted if detached
+///     "signature": <b64URLencode(signature)>
+///   }
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Crypto*
+#[no_mangle]
+pub extern fn indy_sign_jws(command_handle: CommandHandle,
+                            wallet_handle: WalletHandle,
+                            signer_vk: *const c_char,
+                            payload_raw: *const u8,
+                            payload_len: u32,
+                            header_json: *const c_char,
+                            detached: bool,
+                            cb: Option<extern fn(command_handle_: CommandHandle,
+                                                 err: ErrorCode,
+                                                 jws_compact: *const c_char,
+                                                 jws_json: *const c_char)>) -> ErrorCode {
+    trace!("indy_sign_jws: >>> wallet_handle: {:?}, signer_vk: {:?}, payload_raw: {:?}, payload_len: {:?}, header_json: {:?}, detached: {:?}",
+           wallet_handle, signer_vk, payload_raw, payload_len, header_json, detached);
+
+    check_useful_c_str!(signer_vk, ErrorCode::CommonInvalidParam3);
+    check_useful_c_byte_array!(payload_raw, payload_len, ErrorCode::CommonInvalidParam4, ErrorCode::CommonInvalidParam5);
+    check_useful_opt_json!(header_json, ErrorCode::CommonInvalidParam6, JWSHeader);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam8);
+
+    trace!("indy_sign_jws: entities >>> wallet_handle: {:?}, signer_vk: {:?}, payload_raw: {:?}, payload_len: {:?}, header_json: {:?}, detached: {:?}",
+           wallet_handle, signer_vk, payload_raw, payload_len, header_json, detached);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Crypto(CryptoCommand::SignJWS(
+            wallet_handle,
+            signer_vk,
+            payload_raw,
+            header_json,
+            detached,
+            Box::new(move |result| {
+                let (err, jws_compact, jws_json) = prepare_result_2!(result, String::new(), String::new());
+                trace!("indy_sign_jws: jws_compact: {:?}, jws_json: {:?}", jws_compact, jws_json);
+                let jws_compact = ctypes::string_to_cstring(jws_compact);
+                let jws_json = ctypes::string_to_cstring(jws_json);
+                cb(command_handle, err, jws_compact.as_ptr(), jws_json.as_ptr())
+            })
+        )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_sign_jws: <<< res: {:?}", res);
+
+    res
+}
+
+/// Verifies a JWS (RFC 7515) created by indy_sign_jws or by any other EdDSA, ES256K or ES256 signer.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// jws: JWS in compact or JSON (flattened or general) serialization.
+/// signer_vk: verkey of the expected signer. "kid" header parameter isn't trusted as it is set by the signer.
+///            For general JSON serialization JWS is valid if any of signatures is made by signer_vk.
+/// detached_payload_raw: (optional) a pointer to first byte of payload for JWS with detached content
+/// detached_payload_len: a detached payload length
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// valid: true - if signature is valid, false - otherwise
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Crypto*
+#[no_mangle]
+pub extern fn indy_verify_jws(command_handle: CommandHandle,
+                              jws: *const c_char,
+                              signer_vk: *const c_char,
+                              detached_payload_raw: *const u8,
+                              detached_payload_len: u32,
+                              cb: Option<extern fn(command_handle_: CommandHandle,
+                                                   err: ErrorCode,
+                                                   valid: bool)>) -> ErrorCode {
+    trace!("indy_verify_jws: >>> jws: {:?}, signer_vk: {:?}, detached_payload_raw: {:?}, detached_payload_len: {:?}",
+           jws, signer_vk, detached_payload_raw, detached_payload_len);
+
+    check_useful_c_str!(jws, ErrorCode::CommonInvalidParam2);
+    check_useful_c_str!(signer_vk, ErrorCode::CommonInvalidParam3);
+    check_useful_opt_c_byte_array!(detached_payload_raw, detached_payload_len, ErrorCode::CommonInvalidParam4, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    trace!("indy_verify_jws: entities >>> jws: {:?}, signer_vk: {:?}, detached_payload_raw: {:?}, detached_payload_len: {:?}",
+           jws, signer_vk, detached_payload_raw, detached_payload_len);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Crypto(CryptoCommand::VerifyJWS(
+            jws,
+            signer_vk,
+            detached_payload_raw,
+            Box::new(move |result| {
+                let (err, valid) = prepare_result_1!(result, false);
+                trace!("indy_verify_jws: valid: {:?}", valid);
+                cb(command_handle, err, valid)
+            })
+        )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_verify_jws: <<< res: {:?}", res);
+
+    res
+}
+
 /// **** THIS FUNCTION WILL BE DEPRECATED USE indy_pack_message() INSTEAD ****
 /// Encrypt a message by authenticated-encryption scheme.
 ///
@@ -658,6 +895,7 @@ pub extern fn indy_pack_message(
         message,
         receiver_list,
         sender,
+        PackMessageOptions::default(),
         wallet_handle,
         Box::new(move |result| {
             let (err, jwe) = prepare_result_1!(result, Vec::new());
@@ -674,9 +912,125 @@ pub extern fn indy_pack_message(
     res
 }
 
+/// Packs a message by encrypting the message and serializes it in the selected envelope format (Experimental)
+///
+/// Note to use DID keys with this function you can call indy_key_for_did to get key id (verkey)
+/// for specific DID.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// message: a pointer to the first byte of the message to be packed
+/// message_len: the length of the message
+/// receivers: a string in the format of a json list which will contain the list of receiver's keys
+///                the message is being encrypted for.
+///                Example:
+///                "[<receiver edge_agent_1 verkey>, <receiver edge_agent_2 verkey>]"
+/// sender: the sender's verkey as a string When null pointer is used in this parameter, anoncrypt is used
+/// options_json: pack options as json:
+///   {
+///     format: (optional, string) envelope format. One of:
+///             "legacy" - JWE-like format produced by indy_pack_message (default)
+///             "didcomm_v2" - standard JWE defined by DIDComm Messaging v2.
+///                            ECDH-1PU+A256KW is used if sender is set and ECDH-ES+A256KW otherwise.
+///                            Key agreement is done over X25519 keys converted from ed25519 verkeys.
+///                            Keys are identified by did:key DID URLs: did:key:<multikey>#<multikey>.
+///   }
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// a JWE in legacy format described for indy_pack_message or a JWE in DIDComm v2 format defined below:
+/// {
+///     "protected": "b64URLencoded({
+///        "typ": "application/didcomm-encrypted+json",
+///        "alg": "ECDH-1PU+A256KW" or "ECDH-ES+A256KW",
+///        "enc": "A256CBC-HS512",
+///        "skid": <sender key id> (authcrypt only),
+///        "apu": b64URLencode(<sender key id>) (authcrypt only),
+///        "apv": b64URLencode(sha256(<sorted recipient key ids joined with '.'>)),
+///        "epk": {"kty": "OKP", "crv": "X25519", "x": b64URLencode(<ephemeral public key>)}
+///     })",
+///     "recipients": [
+///         {
+///             "encrypted_key": b64URLencode(A256KW(kek, cek)),
+///             "header": {
+///                 "kid": <recipient key id>
+///             }
+///         },
+///     ],
+///     "iv": b64URLencode(iv),
+///     "ciphertext": b64URLencode(ciphertext),
+///     "tag": b64URLencode(tag)
+/// }
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Ledger*
+/// Crypto*
+#[no_mangle]
+pub extern fn indy_pack_message_with_options(
+    command_handle: CommandHandle,
+    wallet_handle: WalletHandle,
+    message: *const u8,
+    message_len: u32,
+    receiver_keys: *const c_char,
+    sender: *const c_char,
+    options_json: *const c_char,
+    cb: Option<extern fn(xcommand_handle: CommandHandle, err: ErrorCode, jwe_data: *const u8, jwe_len: u32)>,
+) -> ErrorCode {
+    trace!("indy_pack_message_with_options: >>> wallet_handle: {:?}, message: {:?}, message_len {:?},\
+            receiver_keys: {:?}, sender: {:?}, options_json: {:?}", wallet_handle, message, message_len, receiver_keys, sender, options_json);
+
+    check_useful_c_byte_array!(message, message_len, ErrorCode::CommonInvalidParam2, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(receiver_keys, ErrorCode::CommonInvalidParam4);
+    check_useful_opt_c_str!(sender, ErrorCode::CommonInvalidParam5);
+    check_useful_json!(options_json, ErrorCode::CommonInvalidParam6, PackMessageOptions);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam7);
+
+    trace!("indy_pack_message_with_options: entities >>> wallet_handle: {:?}, message: {:?}, message_len {:?},\
+            receiver_keys: {:?}, sender: {:?}, options_json: {:?}", wallet_handle, message, message_len, receiver_keys, sender, options_json);
+
+    //parse json array of keys
+    let receiver_list = match serde_json::from_str::<Vec<String>>(&receiver_keys) {
+        Ok(x) => x,
+        Err(_) => {
+            return IndyError::from_msg(IndyErrorKind::InvalidParam(4), "Invalid RecipientKeys has been passed").into();
+        },
+    };
+
+    //break early and error out if no receivers keys are provided
+    if receiver_list.is_empty() {
+        return IndyError::from_msg(IndyErrorKind::InvalidParam(4), "Empty RecipientKeys has been passed").into();
+    }
+
+    let result = CommandExecutor::instance().send(Command::Crypto(CryptoCommand::PackMessage(
+        message,
+        receiver_list,
+        sender,
+        options_json,
+        wallet_handle,
+        Box::new(move |result| {
+            let (err, jwe) = prepare_result_1!(result, Vec::new());
+            trace!("indy_pack_message_with_options: jwe: {:?}", jwe);
+            let (jwe_data, jwe_len) = ctypes::vec_to_pointer(&jwe);
+            cb(command_handle, err, jwe_data, jwe_len)
+        }),
+    )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_pack_message_with_options: <<< res: {:?}", res);
+
+    res
+}
+
 
 /// Unpacks a JWE-like formatted message outputted by indy_pack_message (Experimental)
 ///
+/// Envelope format is detected automatically, so DIDComm v2 JWE produced by
+/// indy_pack_message_with_options can be unpacked as well.
+///
 /// #Params
 /// command_handle: command handle to map callback to user context.
 /// wallet_handle: wallet handle (created by open_wallet).
@@ -2,8 +2,9 @@
 use indy_api_types::{ErrorCode, CommandHandle, WalletHandle, SearchHandle, INVALID_SEARCH_HANDLE};
 use crate::commands::{Command, CommandExecutor};
 use crate::commands::non_secrets::NonSecretsCommand;
-use indy_api_types::domain::wallet::Tags;
+use indy_api_types::domain::wallet::{AddRecordOptions, Tags, UpdateRecordOptions, WalletAggregation};
 use indy_api_types::errors::prelude::*;
+use indy_api_types::validation::Validatable;
 use indy_utils::ctypes;
 
 use serde_json;
@@ -54,6 +55,7 @@ pub extern fn indy_add_wallet_record(command_handle: CommandHandle,
                 id,
                 value,
                 tags_json,
+                AddRecordOptions::default(),
                 Box::new(move |result| {
                     let err = prepare_result!(result);
                     trace!("indy_add_wallet_record:");
@@ -68,6 +70,74 @@ pub extern fn indy_add_wallet_record(command_handle: CommandHandle,
     res
 }
 
+/// Create a new non-secret record in the wallet with additional options
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context
+/// wallet_handle: wallet handle (created by open_wallet)
+/// type_: allows to separate different record types collections
+/// id: the id of record
+/// value: the value of record
+/// tags_json: (optional) the record tags used for search and storing meta information as json:
+///   {
+///     "tagName1": <str>, // string tag (will be stored encrypted)
+///     "tagName2": <str>, // string tag (will be stored encrypted)
+///     "~tagName3": <str>, // string tag (will be stored un-encrypted)
+///     "~tagName4": <str>, // string tag (will be stored un-encrypted)
+///   }
+///   Note that null means no tags
+///   If tag name starts with "~" the tag will be stored un-encrypted that will allow
+///   usage of this tag in complex search queries (comparison, predicates)
+///   Encrypted tags can be searched only for exact matching
+/// options_json: record options as json:
+///   {
+///     expiresIn: (optional, int) number of seconds after which the record expires.
+///                Expired records are not returned by get and search calls and are deleted lazily.
+///                Record never expires if not set
+///   }
+#[no_mangle]
+pub extern fn indy_add_wallet_record_with_options(command_handle: CommandHandle,
+                                                  wallet_handle: WalletHandle,
+                                                  type_: *const c_char,
+                                                  id: *const c_char,
+                                                  value: *const c_char,
+                                                  tags_json: *const c_char,
+                                                  options_json: *const c_char,
+                                                  cb: Option<extern fn(command_handle_: CommandHandle, err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_add_wallet_record_with_options: >>> wallet_handle: {:?}, type_: {:?}, id: {:?}, value: {:?}, tags_json: {:?}, options_json: {:?}", wallet_handle, type_, id, value, tags_json, options_json);
+
+    check_useful_c_str!(type_, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(id, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(value, ErrorCode::CommonInvalidParam5);
+    check_useful_opt_json!(tags_json, ErrorCode::CommonInvalidParam6, Tags);
+    check_useful_json!(options_json, ErrorCode::CommonInvalidParam7, AddRecordOptions);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam8);
+
+    trace!("indy_add_wallet_record_with_options: entities >>> wallet_handle: {:?}, type_: {:?}, id: {:?}, value: {:?}, tags_json: {:?}, options_json: {:?}", wallet_handle, type_, id, value, tags_json, options_json);
+
+    let result = CommandExecutor::instance()
+        .send(Command::NonSecrets(
+            NonSecretsCommand::AddRecord(
+                wallet_handle,
+                type_,
+                id,
+                value,
+                tags_json,
+                options_json,
+                Box::new(move |result| {
+                    let err = prepare_result!(result);
+                    trace!("indy_add_wallet_record_with_options:");
+                    cb(command_handle, err)
+                })
+            )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_add_wallet_record_with_options: <<< res: {:?}", res);
+
+    res
+}
+
 /// Update a non-secret wallet record value
 ///
 /// #Params
@@ -99,6 +169,7 @@ pub extern fn indy_update_wallet_record_value(command_handle: CommandHandle,
                 type_,
                 id,
                 value,
+                UpdateRecordOptions::default(),
                 Box::new(move |result| {
                     let err = prepare_result!(result);
                     trace!("indy_update_wallet_record_value:");
@@ -113,6 +184,59 @@ pub extern fn indy_update_wallet_record_value(command_handle: CommandHandle,
     res
 }
 
+/// Update a non-secret wallet record value with additional options
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context
+/// wallet_handle: wallet handle (created by open_wallet)
+/// type_: allows to separate different record types collections
+/// id: the id of record
+/// value: the new value of record
+/// options_json: record options as json:
+///   {
+///     expiresIn: (optional, int or null) number of seconds from now after which the record expires.
+///                Null makes the record never expire. Current expiry is kept if not set
+///   }
+#[no_mangle]
+pub extern fn indy_update_wallet_record_value_with_options(command_handle: CommandHandle,
+                                                           wallet_handle: WalletHandle,
+                                                           type_: *const c_char,
+                                                           id: *const c_char,
+                                                           value: *const c_char,
+                                                           options_json: *const c_char,
+                                                           cb: Option<extern fn(command_handle_: CommandHandle, err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_update_wallet_record_value_with_options: >>> wallet_handle: {:?}, type_: {:?}, id: {:?}, value: {:?}, options_json: {:?}", wallet_handle, type_, id, value, options_json);
+
+    check_useful_c_str!(type_, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(id, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(value, ErrorCode::CommonInvalidParam5);
+    check_useful_json!(options_json, ErrorCode::CommonInvalidParam6, UpdateRecordOptions);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam7);
+
+    trace!("indy_update_wallet_record_value_with_options: entities >>> wallet_handle: {:?}, type_: {:?}, id: {:?}, value: {:?}, options_json: {:?}", wallet_handle, type_, id, value, options_json);
+
+    let result = CommandExecutor::instance()
+        .send(Command::NonSecrets(
+            NonSecretsCommand::UpdateRecordValue(
+                wallet_handle,
+                type_,
+                id,
+                value,
+                options_json,
+                Box::new(move |result| {
+                    let err = prepare_result!(result);
+                    trace!("indy_update_wallet_record_value_with_options:");
+                    cb(command_handle, err)
+                })
+            )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_update_wallet_record_value_with_options: <<< res: {:?}", res);
+
+    res
+}
+
 /// Update a non-secret wallet record tags
 ///
 /// #Params
@@ -310,6 +434,56 @@ pub extern fn indy_delete_wallet_record(command_handle: CommandHandle,
     res
 }
 
+/// Execute a batch of wallet record operations atomically:
+/// either all operations are applied or none of them.
+///
+/// Note that plugged storages support batches only if transaction hooks
+/// were registered for them with indy_register_wallet_storage_transaction_hooks.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context
+/// wallet_handle: wallet handle (created by open_wallet)
+/// operations_json: list of operations to execute in the given order
+///   [
+///     {"operation": "add", "type": <record type>, "id": <record id>, "value": <record value>, "tags": <(optional) tags json>},
+///     {"operation": "updateValue", "type": <record type>, "id": <record id>, "value": <new record value>},
+///     {"operation": "addTags", "type": <record type>, "id": <record id>, "tags": <tags json>},
+///     {"operation": "updateTags", "type": <record type>, "id": <record id>, "tags": <tags json>},
+///     {"operation": "deleteTags", "type": <record type>, "id": <record id>, "tagNames": <list of tag names>},
+///     {"operation": "delete", "type": <record type>, "id": <record id>},
+///   ]
+///   Tags have the same format as in indy_add_wallet_record.
+#[no_mangle]
+pub extern fn indy_wallet_batch_execute(command_handle: CommandHandle,
+                                        wallet_handle: WalletHandle,
+                                        operations_json: *const c_char,
+                                        cb: Option<extern fn(command_handle_: CommandHandle, err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_wallet_batch_execute: >>> wallet_handle: {:?}, operations_json: {:?}", wallet_handle, operations_json);
+
+    check_useful_c_str!(operations_json, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    trace!("indy_wallet_batch_execute: entities >>> wallet_handle: {:?}, operations_json: {:?}", wallet_handle, operations_json);
+
+    let result = CommandExecutor::instance()
+        .send(Command::NonSecrets(
+            NonSecretsCommand::BatchExecute(
+                wallet_handle,
+                operations_json,
+                Box::new(move |result| {
+                    let err = prepare_result!(result);
+                    trace!("indy_wallet_batch_execute:");
+                    cb(command_handle, err)
+                })
+            )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_wallet_batch_execute: <<< res: {:?}", res);
+
+    res
+}
+
 /// Get an wallet record by id
 ///
 /// #Params
@@ -389,6 +563,11 @@ pub  extern fn indy_get_wallet_record(command_handle: CommandHandle,
 ///    retrieveType: (optional, false by default) Retrieve record type,
 ///    retrieveValue: (optional, true by default) Retrieve record value,
 ///    retrieveTags: (optional, false by default) Retrieve record tags,
+///    sortBy: (optional) List of plaintext (prefixed with "~") tag names to sort records by:
+///      [{"tagName": "~tagName", "order": (optional, "asc" by default) "asc" or "desc"}],
+///      records without a tag go first in ascending order,
+///    skip: (optional) Count of records to skip,
+///    limit: (optional) Max count of records to return,
 ///  }
 /// #Returns
 /// search_handle: Wallet search handle that can be used later
@@ -431,13 +610,58 @@ pub  extern fn indy_open_wallet_search(command_handle: CommandHandle,
     res
 }
 
+/// Open a search over all records of the wallet regardless of their type.
+///
+/// Note that unlike indy_open_wallet_search this call also returns records
+/// created by libindy itself (types with "Indy::" prefix) including secrets.
+/// Returned records always contain type, value and tags.
+///
+/// Records can be fetched by small batches with indy_fetch_wallet_search_next_records
+/// and search must be closed by indy_close_wallet_search.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context
+/// wallet_handle: wallet handle (created by open_wallet)
+///
+/// #Returns
+/// search_handle: Wallet search handle that can be used later
+///   to fetch records by small batches (with indy_fetch_wallet_search_next_records)
+#[no_mangle]
+pub  extern fn indy_open_wallet_all_records_search(command_handle: CommandHandle,
+                                                   wallet_handle: WalletHandle,
+                                                   cb: Option<extern fn(command_handle_: CommandHandle, err: ErrorCode,
+                                                                        search_handle: SearchHandle)>) -> ErrorCode {
+    trace!("indy_open_wallet_all_records_search: >>> wallet_handle: {:?}", wallet_handle);
+
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    trace!("indy_open_wallet_all_records_search: entities >>> wallet_handle: {:?}", wallet_handle);
+
+    let result = CommandExecutor::instance()
+        .send(Command::NonSecrets(
+            NonSecretsCommand::OpenAllRecordsSearch(
+                wallet_handle,
+                Box::new(move |result| {
+                    let (err, handle) = prepare_result_1!(result, INVALID_SEARCH_HANDLE);
+                    trace!("indy_open_wallet_all_records_search: handle: {:?}", handle);
+                    cb(command_handle, err, handle)
+                })
+            )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_open_wallet_all_records_search: <<< res: {:?}", res);
+
+    res
+}
+
 /// Fetch next records for wallet search.
 ///
 /// Not if there are no records this call returns WalletNoRecords error.
 ///
 /// #Params
 /// wallet_handle: wallet handle (created by open_wallet)
-/// wallet_search_handle: wallet search handle (created by indy_open_wallet_search)
+/// wallet_search_handle: wallet search handle (created by indy_open_wallet_search or indy_open_wallet_all_records_search)
 /// count: Count of records to fetch
 ///
 /// #Returns
@@ -511,3 +735,66 @@ pub  extern fn indy_close_wallet_search(command_handle: CommandHandle,
 
     res
 }
+
+/// Count wallet records matching the query grouped by values of plaintext tags.
+///
+/// Records are counted by wallet storage without fetching and decrypting them,
+/// so only plaintext (prefixed with "~") tags can be used for grouping.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context
+/// wallet_handle: wallet handle (created by open_wallet)
+/// type_: allows to separate different record types collections
+/// query_json: MongoDB style query to wallet record tags (the same as for indy_open_wallet_search)
+/// aggregation_json:
+///  {
+///    groupBy: (optional) List of plaintext (prefixed with "~") tag names to group records by.
+///      Only total count is calculated if it is omitted or empty
+///  }
+///
+/// #Returns
+/// aggregation result json:
+/// {
+///   count: <int>, // total count of records matching the query
+///   groups: [{ // distinct combinations of grouped tag values ordered by these values
+///       tags: {"~tagName": "tagValue", ...}, // null value if records don't have the tag
+///       count: <int>, // count of records in group
+///   }],
+/// }
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub  extern fn indy_wallet_aggregate(command_handle: CommandHandle,
+                                     wallet_handle: WalletHandle,
+                                     type_: *const c_char,
+                                     query_json: *const c_char,
+                                     aggregation_json: *const c_char,
+                                     cb: Option<extern fn(command_handle_: CommandHandle, err: ErrorCode,
+                                                          aggregation_result_json: *const c_char)>) -> ErrorCode {
+    trace!("indy_wallet_aggregate: >>> wallet_handle: {:?}, type_: {:?}, query_json: {:?}, aggregation_json: {:?}", wallet_handle, type_, query_json, aggregation_json);
+
+    check_useful_c_str!(type_, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(query_json, ErrorCode::CommonInvalidParam4);
+    check_useful_validatable_json!(aggregation_json, ErrorCode::CommonInvalidParam5, WalletAggregation);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    trace!("indy_wallet_aggregate: entities >>> wallet_handle: {:?}, type_: {:?}, query_json: {:?}, aggregation_json: {:?}", wallet_handle, type_, query_json, aggregation_json);
+
+    let result = CommandExecutor::instance()
+        .send(Command::NonSecrets(
+            NonSecretsCommand::Aggregate(
+                wallet_handle,
+                type_,
+                query_json,
+                aggregation_json,
+                boxed_callback_string!("indy_wallet_aggregate", cb, command_handle)
+            )));
+
+    let res = prepare_result!(result);
+
+    trace!("indy_wallet_aggregate: <<< res: {:?}", res);
+
+    res
+}
@@ -1,19 +1,31 @@
 
-use indy_api_types::{ErrorCode, CommandHandle, WalletHandle, INVALID_WALLET_HANDLE};
+use indy_api_types::{ErrorCode, CommandHandle, WalletHandle, INVALID_WALLET_HANDLE, SubscriptionHandle, INVALID_SUBSCRIPTION_HANDLE};
 use crate::commands::{Command, CommandExecutor};
 use crate::commands::wallet::WalletCommand;
-use indy_api_types::domain::wallet::{Config, Credentials, ExportConfig, KeyConfig};
+use indy_api_types::domain::wallet::{CheckWalletOptions, Config, Credentials, ExportConfig, KeyConfig, RekeyCredentials, StreamExportConfig, WalletSyncOptions};
 use indy_api_types::wallet::*;
 use indy_api_types::errors::prelude::*;
 use indy_utils::ctypes;
+use indy_wallet::{CallbackReader, CallbackWriter};
 use indy_api_types::validation::Validatable;
 
 use serde_json;
 use libc::c_char;
+use std::sync::mpsc::channel;
+
+pub use indy_api_types::errors::{err_msg, IndyError, IndyErrorKind, IndyResult};
+pub use indy_wallet::{EncryptedValue, RecordOptions, SearchOptions, SortBy, SortOrder, StorageIssue, StorageIterator, StorageOperation, StorageRecord, Tag, TagName, WalletStorage, WalletStorageType};
+pub use indy_wallet::language;
 
 
 /// Register custom wallet storage implementation.
 ///
+/// Names of built-in storage types ("default" and "inmem") can't be used for custom storages,
+/// registration fails with WalletTypeAlreadyRegisteredError.
+///
+/// libindy doesn't migrate schema of custom storages. Storage config with "migration" options
+/// is passed to open handler untouched and storage is expected to migrate its own data there.
+///
 /// #Params
 /// command_handle: Command handle to map callback to caller context.
 /// type_: Storage type name.
@@ -143,6 +155,146 @@ pub extern fn indy_register_wallet_storage(command_handle: CommandHandle,
     res
 }
 
+/// Register transaction handlers for custom wallet storage implementation.
+///
+/// Registered handlers allow indy_wallet_batch_execute to be used with wallets
+/// of this storage type. Handlers must be registered before the wallet is opened.
+///
+/// #Params
+/// command_handle: Command handle to map callback to caller context.
+/// type_: Storage type name (previously registered with indy_register_wallet_storage).
+/// begin_transaction: WalletType begin transaction operation handler
+/// commit_transaction: WalletType commit transaction operation handler
+/// rollback_transaction: WalletType rollback transaction operation handler
+///
+/// #Returns
+/// Error code
+#[no_mangle]
+pub extern fn indy_register_wallet_storage_transaction_hooks(command_handle: CommandHandle,
+                                                             type_: *const c_char,
+                                                             begin_transaction: Option<WalletBeginTransaction>,
+                                                             commit_transaction: Option<WalletCommitTransaction>,
+                                                             rollback_transaction: Option<WalletRollbackTransaction>,
+                                                             cb: Option<extern fn(command_handle_: CommandHandle,
+                                                                                  err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_register_wallet_storage_transaction_hooks: >>> command_handle: {:?}, type_: {:?}, cb: {:?}",
+           command_handle, type_, cb);
+
+    check_useful_c_str!(type_, ErrorCode::CommonInvalidParam2);
+    check_useful_c_callback!(begin_transaction, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(commit_transaction, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(rollback_transaction, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    trace!("indy_register_wallet_storage_transaction_hooks: params type_: {:?}", type_);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(
+            WalletCommand::RegisterWalletTransactionHooks(
+                type_,
+                begin_transaction,
+                commit_transaction,
+                rollback_transaction,
+                Box::new(move |result| {
+                    let err = prepare_result!(result);
+                    trace!("indy_register_wallet_storage_transaction_hooks: cb command_handle: {:?}, err: {:?}", command_handle, err);
+                    cb(command_handle, err)
+                })
+            )));
+
+    let res = prepare_result!(result);
+    trace!("indy_register_wallet_storage_transaction_hooks: <<< res: {:?}", res);
+    res
+}
+
+/// Register aggregation handlers for custom wallet storage implementation.
+///
+/// Registered handlers allow indy_wallet_aggregate to be computed by the storage natively.
+/// Without them records matching the query are fetched and counted one by one.
+/// Handlers must be registered before the wallet is opened.
+///
+/// #Params
+/// command_handle: Command handle to map callback to caller context.
+/// type_: Storage type name (previously registered with indy_register_wallet_storage).
+/// aggregate_records: WalletType aggregate records operation handler
+/// free_aggregation: WalletType free aggregation result operation handler
+///
+/// #Returns
+/// Error code
+#[no_mangle]
+pub extern fn indy_register_wallet_storage_aggregation(command_handle: CommandHandle,
+                                                       type_: *const c_char,
+                                                       aggregate_records: Option<WalletAggregateRecords>,
+                                                       free_aggregation: Option<WalletFreeAggregation>,
+                                                       cb: Option<extern fn(command_handle_: CommandHandle,
+                                                                            err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_register_wallet_storage_aggregation: >>> command_handle: {:?}, type_: {:?}, cb: {:?}",
+           command_handle, type_, cb);
+
+    check_useful_c_str!(type_, ErrorCode::CommonInvalidParam2);
+    check_useful_c_callback!(aggregate_records, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(free_aggregation, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    trace!("indy_register_wallet_storage_aggregation: params type_: {:?}", type_);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(
+            WalletCommand::RegisterWalletAggregation(
+                type_,
+                aggregate_records,
+                free_aggregation,
+                Box::new(move |result| {
+                    let err = prepare_result!(result);
+                    trace!("indy_register_wallet_storage_aggregation: cb command_handle: {:?}, err: {:?}", command_handle, err);
+                    cb(command_handle, err)
+                })
+            )));
+
+    let res = prepare_result!(result);
+    trace!("indy_register_wallet_storage_aggregation: <<< res: {:?}", res);
+    res
+}
+
+/// Register custom wallet storage implemented in Rust.
+///
+/// Unlike indy_register_wallet_storage the storage is called directly without marshalling
+/// of records through C handlers. Call blocks until storage type is registered.
+///
+/// Options json passed to WalletStorage get and search can be parsed into RecordOptions
+/// and SearchOptions to read requested fields, sorting and paging.
+///
+/// #Params
+/// type_: Storage type name.
+/// storage_type: Implementation of WalletStorageType trait that creates, opens and deletes storages.
+///
+/// #Returns
+/// Ok if storage type is registered
+///
+/// #Errors
+/// WalletStorageTypeAlreadyRegistered if storage type with the same name is already registered
+pub fn register_native_wallet_storage(type_: &str, storage_type: Box<dyn WalletStorageType + Send>) -> IndyResult<()> {
+    trace!("register_native_wallet_storage: >>> type_: {:?}", type_);
+
+    let (sender, receiver) = channel();
+
+    CommandExecutor::instance()
+        .send(Command::Wallet(
+            WalletCommand::RegisterNativeWalletType(
+                type_.to_string(),
+                storage_type,
+                Box::new(move |result| {
+                    sender.send(result).ok();
+                })
+            )))?;
+
+    let res = receiver.recv()
+        .to_indy(IndyErrorKind::InvalidState, "Wallet storage registration is interrupted")?;
+
+    trace!("register_native_wallet_storage: <<< res: {:?}", res);
+    res
+}
+
 /// Create a new secure wallet.
 ///
 /// #Params
@@ -152,6 +304,7 @@ pub extern fn indy_register_wallet_storage(command_handle: CommandHandle,
 ///         Configured storage uses this identifier to lookup exact wallet data placement.
 ///   "storage_type": optional<string>, Type of the wallet storage. Defaults to 'default'.
 ///                  'Default' storage type allows to store wallet data in the local file.
+///                  'Inmem' storage type keeps wallet data in memory of the process until it is deleted.
 ///                  Custom storage types can be registered with indy_register_wallet_storage call.
 ///   "storage_config": optional<object>, Storage configuration json. Storage type defines set of supported keys.
 ///                     Can be optional if storage supports default configuration.
@@ -160,6 +313,13 @@ pub extern fn indy_register_wallet_storage(command_handle: CommandHandle,
 ///     "path": optional<string>, Path to the directory with wallet files.
 ///             Defaults to $HOME/.indy_client/wallet.
 ///             Wallet will be stored in the file {path}/{id}/sqlite.db
+///     "migration": optional<object>, Schema migration options applied on wallet opening:
+///     {
+///       "dry_run": optional<bool>, Apply pending migration steps and roll them back. Opening of wallet
+///                  that requires migration fails. Defaults to false.
+///       "backup": optional<bool>, Copy wallet file to {path}/{id}/sqlite.db.v<version>.bak before
+///                 migration. Defaults to false.
+///     }
 ///   }
 /// }
 /// credentials: Wallet credentials json
@@ -225,6 +385,7 @@ pub extern fn indy_create_wallet(command_handle: CommandHandle,
 ///             Configured storage uses this identifier to lookup exact wallet data placement.
 ///       "storage_type": optional<string>, Type of the wallet storage. Defaults to 'default'.
 ///                       'Default' storage type allows to store wallet data in the local file.
+///                       'Inmem' storage type keeps wallet data in memory of the process until it is deleted.
 ///                       Custom storage types can be registered with indy_register_wallet_storage call.
 ///       "storage_config": optional<object>, Storage configuration json. Storage type defines set of supported keys.
 ///                         Can be optional if storage supports default configuration.
@@ -233,6 +394,13 @@ pub extern fn indy_create_wallet(command_handle: CommandHandle,
 ///              "path": optional<string>, Path to the directory with wallet files.
 ///                      Defaults to $HOME/.indy_client/wallet.
 ///                      Wallet will be stored in the file {path}/{id}/sqlite.db
+///              "migration": optional<object>, Schema migration options applied on wallet opening:
+///              {
+///                "dry_run": optional<bool>, Apply pending migration steps and roll them back. Opening of wallet
+///                           that requires migration fails. Defaults to false.
+///                "backup": optional<bool>, Copy wallet file to {path}/{id}/sqlite.db.v<version>.bak before
+///                          migration. Defaults to false.
+///              }
 ///           }
 ///
 ///   }
@@ -297,6 +465,76 @@ pub extern fn indy_open_wallet(command_handle: CommandHandle,
     res
 }
 
+/// Changes the key of opened wallet.
+///
+/// Wallet keys stored in the wallet metadata are re-encrypted with the master key
+/// derived from new credentials. Values of wallet records are re-encrypted with new random
+/// value keys in batches before that. Wallet stays usable while the new master key is derived
+/// and doesn't need to be reopened.
+/// After the call completes wallet must be opened with the new credentials.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle returned by indy_open_wallet
+/// new_credentials: New wallet credentials json
+///   {
+///     "key": <string>, New key or passphrase used for wallet key derivation.
+///                     Look to key_derivation_method param for information about supported key derivation methods.
+///     "key_derivation_method": optional<string> Algorithm to use for wallet key derivation:
+///                              ARGON2I_MOD - derive secured wallet master key (used by default)
+///                              ARGON2I_INT - derive secured wallet master key (less secured but faster)
+///                              RAW - raw wallet key master provided (skip derivation).
+///                                RAW keys can be generated with indy_generate_wallet_key call
+///   }
+/// progress_cb: (optional) Callback that is called before the first and after each re-encrypted batch
+///   of records with the number of processed records and the total number of records.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Error code
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_rekey_wallet(command_handle: CommandHandle,
+                                wallet_handle: WalletHandle,
+                                new_credentials: *const c_char,
+                                progress_cb: Option<extern fn(command_handle_: CommandHandle,
+                                                              completed_steps: u32,
+                                                              total_steps: u32)>,
+                                cb: Option<extern fn(command_handle_: CommandHandle,
+                                                     err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_rekey_wallet: >>> wallet_handle: {:?}, new_credentials: {:?}", wallet_handle, new_credentials);
+
+    check_useful_json!(new_credentials, ErrorCode::CommonInvalidParam3, RekeyCredentials);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    trace!("indy_rekey_wallet: params wallet_handle: {:?}, new_credentials: {:?}", wallet_handle, secret!(&new_credentials));
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::Rekey(
+            wallet_handle,
+            new_credentials,
+            Box::new(move |completed_steps, total_steps| {
+                trace!("indy_rekey_wallet: progress command_handle: {:?} completed_steps: {:?}, total_steps: {:?}",
+                       command_handle, completed_steps, total_steps);
+                if let Some(progress_cb) = progress_cb {
+                    progress_cb(command_handle, completed_steps, total_steps)
+                }
+            }),
+            Box::new(move |result| {
+                let err = prepare_result!(result);
+                trace!("indy_rekey_wallet: cb command_handle: {:?} err: {:?}", command_handle, err);
+                cb(command_handle, err)
+            })
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_rekey_wallet: <<< res: {:?}", res);
+    res
+}
+
 /// Exports opened wallet
 ///
 /// #Params:
@@ -311,6 +549,9 @@ pub extern fn indy_open_wallet(command_handle: CommandHandle,
 ///                              ARGON2I_INT - derive secured export key (less secured but faster)
 ///                              RAW - raw export key provided (skip derivation).
 ///                                RAW keys can be generated with indy_generate_wallet_key call
+///     "types": optional<array<string>>, Export only records of the given types (all records are exported by default)
+///     "query": optional<object>, Export only records matching the given WQL query (see indy_open_wallet_search).
+///              Requires "types" to be specified. The query is applied to each of the given types.
 ///   }
 ///
 /// #Returns
@@ -327,7 +568,7 @@ pub extern fn indy_export_wallet(command_handle: CommandHandle,
                                                       err: ErrorCode)>) -> ErrorCode {
     trace!("indy_export_wallet: >>> wallet_handle: {:?}, export_config: {:?}", wallet_handle, export_config);
 
-    check_useful_json!(export_config, ErrorCode::CommonInvalidParam3, ExportConfig);
+    check_useful_validatable_json!(export_config, ErrorCode::CommonInvalidParam3, ExportConfig);
     check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
 
     trace!("indy_export_wallet: params wallet_handle: {:?}, export_config: {:?}", wallet_handle, secret!(&export_config));
@@ -348,6 +589,69 @@ pub extern fn indy_export_wallet(command_handle: CommandHandle,
     res
 }
 
+/// Exports opened wallet to the caller-provided stream
+///
+/// Exported data has the same format as indy_export_wallet produces
+/// and is passed to write_cb chunk by chunk instead of being written to a file.
+///
+/// #Params:
+/// command_handle: Command handle to map callbacks to caller context.
+/// wallet_handle: wallet handle returned by indy_open_wallet
+/// export_config: JSON containing settings for input operation.
+///   {
+///     "key": <string>, Key or passphrase used for wallet export key derivation.
+///                     Look to key_derivation_method param for information about supported key derivation methods.
+///     "key_derivation_method": optional<string> Algorithm to use for wallet export key derivation:
+///                              ARGON2I_MOD - derive secured export key (used by default)
+///                              ARGON2I_INT - derive secured export key (less secured but faster)
+///                              RAW - raw export key provided (skip derivation).
+///                                RAW keys can be generated with indy_generate_wallet_key call
+///     "types": optional<array<string>>, Export only records of the given types (all records are exported by default)
+///     "query": optional<object>, Export only records matching the given WQL query (see indy_open_wallet_search).
+///              Requires "types" to be specified. The query is applied to each of the given types.
+///   }
+/// write_cb: handler that consumes next chunk of exported data.
+///           Called with the same command_handle before cb. Must return Success
+///           if the whole chunk was consumed, any other code aborts export.
+///
+/// #Returns
+/// Error code
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_export_wallet_to_stream(command_handle: CommandHandle,
+                                           wallet_handle: WalletHandle,
+                                           export_config: *const c_char,
+                                           write_cb: Option<WalletExportWrite>,
+                                           cb: Option<extern fn(command_handle_: CommandHandle,
+                                                                err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_export_wallet_to_stream: >>> wallet_handle: {:?}, export_config: {:?}", wallet_handle, export_config);
+
+    check_useful_validatable_json!(export_config, ErrorCode::CommonInvalidParam3, StreamExportConfig);
+    check_useful_c_callback!(write_cb, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    trace!("indy_export_wallet_to_stream: params wallet_handle: {:?}, export_config: {:?}", wallet_handle, secret!(&export_config));
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::ExportToStream(
+            wallet_handle,
+            export_config,
+            Box::new(CallbackWriter::new(command_handle, write_cb)),
+            Box::new(move |result| {
+                let err = prepare_result!(result);
+                trace!("indy_export_wallet_to_stream: cb command_handle: {:?} err: {:?}", command_handle, err);
+                cb(command_handle, err)
+            })
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_export_wallet_to_stream: <<< res: {:?}", res);
+    res
+}
+
 
 /// Creates a new secure wallet and then imports its content
 /// according to fields provided in import_config
@@ -360,6 +664,7 @@ pub extern fn indy_export_wallet(command_handle: CommandHandle,
 ///         Configured storage uses this identifier to lookup exact wallet data placement.
 ///   "storage_type": optional<string>, Type of the wallet storage. Defaults to 'default'.
 ///                  'Default' storage type allows to store wallet data in the local file.
+///                  'Inmem' storage type keeps wallet data in memory of the process until it is deleted.
 ///                  Custom storage types can be registered with indy_register_wallet_storage call.
 ///   "storage_config": optional<object>, Storage configuration json. Storage type defines set of supported keys.
 ///                     Can be optional if storage supports default configuration.
@@ -368,6 +673,13 @@ pub extern fn indy_export_wallet(command_handle: CommandHandle,
 ///     "path": optional<string>, Path to the directory with wallet files.
 ///             Defaults to $HOME/.indy_client/wallet.
 ///             Wallet will be stored in the file {path}/{id}/sqlite.db
+///     "migration": optional<object>, Schema migration options applied on wallet opening:
+///     {
+///       "dry_run": optional<bool>, Apply pending migration steps and roll them back. Opening of wallet
+///                  that requires migration fails. Defaults to false.
+///       "backup": optional<bool>, Copy wallet file to {path}/{id}/sqlite.db.v<version>.bak before
+///                 migration. Defaults to false.
+///     }
 ///   }
 /// }
 /// credentials: Wallet credentials json
@@ -430,6 +742,449 @@ pub extern fn indy_import_wallet(command_handle: CommandHandle,
     res
 }
 
+/// Creates a new secure wallet and then imports its content
+/// from the caller-provided stream.
+/// This can be seen as an indy_import_wallet call that reads exported data
+/// through read_cb instead of a file.
+///
+/// #Params
+/// command_handle: Command handle to map callbacks to caller context.
+/// config: Wallet configuration json (see indy_import_wallet).
+/// credentials: Wallet credentials json (see indy_import_wallet).
+/// import_config: Import settings json.
+/// {
+///   "key": <string>, key used for export of the wallet
+/// }
+/// read_cb: handler that fills the given buffer with next chunk of exported data
+///          and stores the number of written bytes to read_len_p (0 means end of stream).
+///          Called with the same command_handle before cb. Any code other than Success aborts import.
+///
+/// #Returns
+/// Error code
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_import_wallet_from_stream(command_handle: CommandHandle,
+                                             config: *const c_char,
+                                             credentials: *const c_char,
+                                             import_config: *const c_char,
+                                             read_cb: Option<WalletImportRead>,
+                                             cb: Option<extern fn(command_handle_: CommandHandle,
+                                                                  err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_import_wallet_from_stream: >>> command_handle: {:?}, config: {:?}, credentials: {:?}, import_config: {:?}, cb: {:?}",
+           command_handle, config, credentials, import_config, cb);
+
+    check_useful_validatable_json!(config, ErrorCode::CommonInvalidParam2, Config);
+    check_useful_json!(credentials, ErrorCode::CommonInvalidParam3, Credentials);
+    check_useful_json!(import_config, ErrorCode::CommonInvalidParam4, StreamExportConfig);
+    check_useful_c_callback!(read_cb, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    trace!("indy_import_wallet_from_stream: params config: {:?}, credentials: {:?}, import_config: {:?}",
+           config, secret!(&credentials), secret!(&import_config));
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::ImportFromStream(
+            config,
+            credentials,
+            import_config,
+            Box::new(CallbackReader::new(command_handle, read_cb)),
+            Box::new(move |result| {
+                let err = prepare_result!(result);
+                trace!("indy_import_wallet_from_stream: cb command_handle: {:?}, err: {:?}", command_handle, err);
+                cb(command_handle, err)
+            })
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_import_wallet_from_stream: <<< res: {:?}", res);
+    res
+}
+
+
+/// Imports content of exported wallet into opened wallet.
+///
+/// Unlike indy_import_wallet this call doesn't create a new wallet and merges
+/// imported records with the records that wallet already contains.
+/// If any imported record has the same type and id as an existing one, import fails
+/// with WalletItemAlreadyExists error and no records are imported.
+///
+/// #Params
+/// wallet_handle: wallet handle returned by indy_open_wallet.
+/// import_config: Import settings json.
+/// {
+///   "path": <string>, path of the file that contains exported wallet content
+///   "key": <string>, key used for export of the wallet
+/// }
+///
+/// #Returns
+/// Error code
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_import_into_wallet(command_handle: CommandHandle,
+                                      wallet_handle: WalletHandle,
+                                      import_config: *const c_char,
+                                      cb: Option<extern fn(command_handle_: CommandHandle,
+                                                           err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_import_into_wallet: >>> command_handle: {:?}, wallet_handle: {:?}, import_config: {:?}, cb: {:?}",
+           command_handle, wallet_handle, import_config, cb);
+
+    check_useful_json!(import_config, ErrorCode::CommonInvalidParam3, ExportConfig);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    trace!("indy_import_into_wallet: params wallet_handle: {:?}, import_config: {:?}",
+           wallet_handle, secret!(&import_config));
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::ImportInto(
+            wallet_handle,
+            import_config,
+            Box::new(move |result| {
+                let err = prepare_result!(result);
+                trace!("indy_import_into_wallet: cb command_handle: {:?}, err: {:?}", command_handle, err);
+                cb(command_handle, err)
+            })
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_import_into_wallet: <<< res: {:?}", res);
+    res
+}
+
+/// Verifies exported wallet file without importing it.
+///
+/// Parses export header, decrypts and checks every encrypted chunk, header hash and
+/// every record, so successful result means that export can be imported with the given key.
+///
+/// #Params
+/// export_config: Export file settings json.
+/// {
+///   "path": <string>, path of the file that contains exported wallet content
+///   "key": <string>, key used for export of the wallet
+/// }
+///
+/// #Returns
+/// Error code
+/// summary_json: Export summary json.
+/// {
+///   "version": <int>, version of export format
+///   "time": <int>, export time in seconds from UNIX Epoch
+///   "encryption_method": <string>, method of records stream encryption
+///   "key_derivation_method": <string>, method of export key derivation (ARGON2I_MOD, ARGON2I_INT or RAW)
+///   "types": <object>, number of exported records per record type {"type": <int>, ...}
+///   "records_count": <int>, total number of exported records
+///   "size": <int>, size of export file in bytes
+/// }
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_inspect_wallet_export(command_handle: CommandHandle,
+                                         export_config: *const c_char,
+                                         cb: Option<extern fn(command_handle_: CommandHandle,
+                                                              err: ErrorCode,
+                                                              summary_json: *const c_char)>) -> ErrorCode {
+    trace!("indy_inspect_wallet_export: >>> command_handle: {:?}, export_config: {:?}, cb: {:?}",
+           command_handle, export_config, cb);
+
+    check_useful_json!(export_config, ErrorCode::CommonInvalidParam2, ExportConfig);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    trace!("indy_inspect_wallet_export: params export_config: {:?}", secret!(&export_config));
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::InspectExport(
+            export_config,
+            boxed_callback_string!("indy_inspect_wallet_export", cb, command_handle)
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_inspect_wallet_export: <<< res: {:?}", res);
+    res
+}
+
+/// Checks internal consistency of opened wallet.
+///
+/// Walks through all wallet records and checks that their types, ids, values and tags can be decrypted.
+/// Storage specific problems like tags without item or duplicated items are reported by storage.
+///
+/// #Params
+/// wallet_handle: wallet handle returned by indy_open_wallet.
+/// options_json: Check options json.
+/// {
+///   "repair": optional<bool>, fix storage specific problems and delete records that cannot be decrypted.
+///             Defaults to false.
+/// }
+///
+/// #Returns
+/// Error code
+/// report_json: Check report json.
+/// {
+///   "records_count": <int>, number of checked records
+///   "issues": [{
+///       "kind": <string>, one of "invalid_type", "invalid_id", "invalid_value", "invalid_tags",
+///               "orphaned_tags", "duplicate_item"
+///       "type": optional<string>, record type if it can be decrypted
+///       "id": optional<string>, record id if it can be decrypted
+///       "details": <string>, description of the problem
+///       "repaired": <bool>, problem was fixed by repair
+///   }, ...]
+/// }
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_check_wallet(command_handle: CommandHandle,
+                                wallet_handle: WalletHandle,
+                                options_json: *const c_char,
+                                cb: Option<extern fn(command_handle_: CommandHandle,
+                                                     err: ErrorCode,
+                                                     report_json: *const c_char)>) -> ErrorCode {
+    trace!("indy_check_wallet: >>> command_handle: {:?}, wallet_handle: {:?}, options_json: {:?}, cb: {:?}",
+           command_handle, wallet_handle, options_json, cb);
+
+    check_useful_json!(options_json, ErrorCode::CommonInvalidParam3, CheckWalletOptions);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    trace!("indy_check_wallet: params wallet_handle: {:?}, options_json: {:?}", wallet_handle, options_json);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::Check(
+            wallet_handle,
+            options_json,
+            boxed_callback_string!("indy_check_wallet", cb, command_handle)
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_check_wallet: <<< res: {:?}", res);
+    res
+}
+
+/// Creates a new wallet that is a replica of opened wallet.
+///
+/// Replica is created empty in its own storage but shares encryption keys with the source wallet,
+/// so records can be copied between them by indy_sync_wallet without decryption.
+/// Replica is opened with the same key as the source wallet.
+///
+/// #Params
+/// wallet_handle: handle of the source wallet returned by indy_open_wallet.
+/// config: Replica configuration json. Same as config of indy_create_wallet.
+/// credentials: Replica credentials json. Same as credentials of indy_create_wallet.
+///              Only "storage_credentials" are used, wallet key is inherited from the source wallet.
+///
+/// #Returns
+/// err: Error code
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_create_wallet_replica(command_handle: CommandHandle,
+                                         wallet_handle: WalletHandle,
+                                         config: *const c_char,
+                                         credentials: *const c_char,
+                                         cb: Option<extern fn(command_handle_: CommandHandle,
+                                                              err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_create_wallet_replica: >>> command_handle: {:?}, wallet_handle: {:?}, config: {:?}, credentials: {:?}, cb: {:?}",
+           command_handle, wallet_handle, config, credentials, cb);
+
+    check_useful_validatable_json!(config, ErrorCode::CommonInvalidParam3, Config);
+    check_useful_json!(credentials, ErrorCode::CommonInvalidParam4, Credentials);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    trace!("indy_create_wallet_replica: params wallet_handle: {:?}, config: {:?}, credentials: {:?}", wallet_handle, config, secret!(&credentials));
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::CreateReplica(
+            wallet_handle,
+            config,
+            credentials,
+            Box::new(move |result| {
+                let err = prepare_result!(result);
+                trace!("indy_create_wallet_replica: cb command_handle: {:?}, err: {:?}", command_handle, err);
+                cb(command_handle, err)
+            })
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_create_wallet_replica: <<< res: {:?}", res);
+    res
+}
+
+/// Copies encrypted records from one opened wallet to another without decryption.
+///
+/// Both wallets must be encrypted with the same keys, so the target wallet must be created by
+/// indy_create_wallet_replica. Existing records of the target wallet are overwritten.
+/// Sync is incremental if marker returned by the previous sync is passed in options:
+/// only records added or modified after it are copied. Records that equal target ones are
+/// not rewritten. Target records missing in the source wallet are deleted.
+///
+/// #Params
+/// source_wallet_handle: handle of the wallet to copy records from.
+/// target_wallet_handle: handle of the wallet to copy records to.
+/// options_json: Sync options json.
+/// {
+///   "since": optional<int>, marker returned by the previous sync. All records are copied if not set.
+/// }
+///
+/// #Returns
+/// Error code
+/// result_json: Sync result json.
+/// {
+///   "records_count": <int>, number of copied records
+///   "deleted_count": <int>, number of target records deleted as missing in the source wallet
+///   "marker": <int>, marker of source wallet to pass to the next sync
+/// }
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_sync_wallet(command_handle: CommandHandle,
+                               source_wallet_handle: WalletHandle,
+                               target_wallet_handle: WalletHandle,
+                               options_json: *const c_char,
+                               cb: Option<extern fn(command_handle_: CommandHandle,
+                                                    err: ErrorCode,
+                                                    result_json: *const c_char)>) -> ErrorCode {
+    trace!("indy_sync_wallet: >>> command_handle: {:?}, source_wallet_handle: {:?}, target_wallet_handle: {:?}, options_json: {:?}, cb: {:?}",
+           command_handle, source_wallet_handle, target_wallet_handle, options_json, cb);
+
+    check_useful_json!(options_json, ErrorCode::CommonInvalidParam4, WalletSyncOptions);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    trace!("indy_sync_wallet: params source_wallet_handle: {:?}, target_wallet_handle: {:?}, options_json: {:?}",
+           source_wallet_handle, target_wallet_handle, options_json);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::Sync(
+            source_wallet_handle,
+            target_wallet_handle,
+            options_json,
+            boxed_callback_string!("indy_sync_wallet", cb, command_handle)
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_sync_wallet: <<< res: {:?}", res);
+    res
+}
+
+/// Subscribes to changes of wallet records of the given type matching WQL query.
+///
+/// notify_cb is called for every record added, updated or deleted through libindy calls
+/// (non_secrets, anoncreds, did, crypto etc.), including wallet import into opened wallet,
+/// indy_sync_wallet, indy_check_wallet repair and deletion of expired records.
+/// Updated record that doesn't match the query anymore is reported as removed.
+/// notify_cb is called from libindy thread before the callback of the changing call,
+/// so it should not block. Subscription is removed when the wallet is closed.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// type_: type of records to watch.
+/// query_json: WQL query to filter watched records (see indy_open_wallet_search).
+///   Only tags are evaluated, "{}" matches all records of the type.
+/// notify_cb: handler that is called on each change of matching record.
+///            Called with the same command_handle as passed to this call.
+///            operation is one of "add", "update", "delete" or "remove".
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// err: Error code
+/// subscription_handle: Handle of subscription to use in indy_wallet_unsubscribe.
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_wallet_subscribe(command_handle: CommandHandle,
+                                    wallet_handle: WalletHandle,
+                                    type_: *const c_char,
+                                    query_json: *const c_char,
+                                    notify_cb: Option<WalletRecordChanged>,
+                                    cb: Option<extern fn(command_handle_: CommandHandle,
+                                                         err: ErrorCode,
+                                                         subscription_handle: SubscriptionHandle)>) -> ErrorCode {
+    trace!("indy_wallet_subscribe: >>> command_handle: {:?}, wallet_handle: {:?}, type_: {:?}, query_json: {:?}",
+           command_handle, wallet_handle, type_, query_json);
+
+    check_useful_c_str!(type_, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(query_json, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(notify_cb, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    trace!("indy_wallet_subscribe: params wallet_handle: {:?}, type_: {:?}, query_json: {:?}",
+           wallet_handle, type_, query_json);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::Subscribe(
+            wallet_handle,
+            type_,
+            query_json,
+            Box::new(move |subscription_handle, operation, id| {
+                let operation = ctypes::str_to_cstring(operation.as_str());
+                let id = ctypes::str_to_cstring(id);
+                notify_cb(command_handle, subscription_handle, operation.as_ptr(), id.as_ptr())
+            }),
+            Box::new(move |result| {
+                let (err, handle) = prepare_result_1!(result, INVALID_SUBSCRIPTION_HANDLE);
+                trace!("indy_wallet_subscribe: cb command_handle: {:?} err: {:?}, handle: {:?}",
+                       command_handle, err, handle);
+                cb(command_handle, err, handle)
+            })
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_wallet_subscribe: <<< res: {:?}", res);
+    res
+}
+
+/// Removes subscription created by indy_wallet_subscribe.
+/// notify_cb of the subscription is not called after this call completes.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// subscription_handle: subscription handle (created by indy_wallet_subscribe).
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Error code
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn indy_wallet_unsubscribe(command_handle: CommandHandle,
+                                      subscription_handle: SubscriptionHandle,
+                                      cb: Option<extern fn(command_handle_: CommandHandle,
+                                                           err: ErrorCode)>) -> ErrorCode {
+    trace!("indy_wallet_unsubscribe: >>> command_handle: {:?}, subscription_handle: {:?}", command_handle, subscription_handle);
+
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    trace!("indy_wallet_unsubscribe: params subscription_handle: {:?}", subscription_handle);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Wallet(WalletCommand::Unsubscribe(
+            subscription_handle,
+            Box::new(move |result| {
+                let err = prepare_result!(result);
+                trace!("indy_wallet_unsubscribe: cb command_handle: {:?}, err: {:?}", command_handle, err);
+                cb(command_handle, err)
+            })
+        )));
+
+    let res = prepare_result!(result);
+    trace!("indy_wallet_unsubscribe: <<< res: {:?}", res);
+    res
+}
 
 /// Closes opened wallet and frees allocated resources.
 ///
@@ -478,6 +1233,7 @@ pub extern fn indy_close_wallet(command_handle: CommandHandle,
 ///         Configured storage uses this identifier to lookup exact wallet data placement.
 ///   "storage_type": optional<string>, Type of the wallet storage. Defaults to 'default'.
 ///                  'Default' storage type allows to store wallet data in the local file.
+///                  'Inmem' storage type keeps wallet data in memory of the process until it is deleted.
 ///                  Custom storage types can be registered with indy_register_wallet_storage call.
 ///   "storage_config": optional<object>, Storage configuration json. Storage type defines set of supported keys.
 ///                     Can be optional if storage supports default configuration.
@@ -486,6 +1242,13 @@ pub extern fn indy_close_wallet(command_handle: CommandHandle,
 ///     "path": optional<string>, Path to the directory with wallet files.
 ///             Defaults to $HOME/.indy_client/wallet.
 ///             Wallet will be stored in the file {path}/{id}/sqlite.db
+///     "migration": optional<object>, Schema migration options applied on wallet opening:
+///     {
+///       "dry_run": optional<bool>, Apply pending migration steps and roll them back. Opening of wallet
+///                  that requires migration fails. Defaults to false.
+///       "backup": optional<bool>, Copy wallet file to {path}/{id}/sqlite.db.v<version>.bak before
+///                 migration. Defaults to false.
+///     }
 ///   }
 /// }
 /// credentials: Wallet credentials json
@@ -18,6 +18,8 @@ use indy_utils::next_command_handle;
 
 const CRED_DEF_CACHE: &str = "cred_def_cache";
 const SCHEMA_CACHE: &str = "schema_cache";
+const DID_DOC_CACHE: &str = "did_doc_cache";
+const DID_DOC_DEFAULT_MIN_FRESH: i32 = 3600;
 
 pub enum CacheCommand {
     GetSchema(PoolHandle,
@@ -44,12 +46,26 @@ pub enum CacheCommand {
         GetCacheOptions,              // options
         CommandHandle,                          // cb_id
     ),
+    ResolveDid(PoolHandle,
+               WalletHandle,
+               DidValue, // did
+               GetCacheOptions, // options
+               Box<dyn Fn(IndyResult<String>) + Send>),
+    ResolveDidContinue(
+        WalletHandle,
+        IndyResult<(String, String)>, // ledger_response
+        GetCacheOptions,              // options
+        CommandHandle,                          // cb_id
+    ),
     PurgeSchemaCache(WalletHandle,
                      PurgeOptions, // options
                      Box<dyn Fn(IndyResult<()>) + Send>),
     PurgeCredDefCache(WalletHandle,
                       PurgeOptions, // options
                       Box<dyn Fn(IndyResult<()>) + Send>),
+    PurgeDidDocCache(WalletHandle,
+                     PurgeOptions, // options
+                     Box<dyn Fn(IndyResult<()>) + Send>),
 }
 
 pub struct CacheCommandExecutor {
@@ -105,6 +121,14 @@ impl CacheCommandExecutor {
                 debug!(target: "non_secrets_command_executor", "GetCredDefContinue command received");
                 self._get_cred_def_continue(wallet_handle, ledger_response, options, cb_id);
             }
+            CacheCommand::ResolveDid(pool_handle, wallet_handle, did, options, cb) => {
+                debug!(target: "non_secrets_command_executor", "ResolveDid command received");
+                self.resolve_did(pool_handle, wallet_handle, &did, options, cb);
+            }
+            CacheCommand::ResolveDidContinue(wallet_handle, ledger_response, options, cb_id) => {
+                debug!(target: "non_secrets_command_executor", "ResolveDidContinue command received");
+                self._resolve_did_continue(wallet_handle, ledger_response, options, cb_id);
+            }
             CacheCommand::PurgeSchemaCache(wallet_handle, options, cb) => {
                 debug!(target: "non_secrets_command_executor", "PurgeSchemaCache command received");
                 cb(self.purge_schema_cache(wallet_handle, options));
@@ -113,6 +137,10 @@ impl CacheCommandExecutor {
                 debug!(target: "non_secrets_command_executor", "PurgeCredDefCache command received");
                 cb(self.purge_cred_def_cache(wallet_handle, options));
             }
+            CacheCommand::PurgeDidDocCache(wallet_handle, options, cb) => {
+                debug!(target: "non_secrets_command_executor", "PurgeDidDocCache command received");
+                cb(self.purge_did_doc_cache(wallet_handle, options));
+            }
         }
     }
 
@@ -268,6 +296,66 @@ impl CacheCommandExecutor {
         }
     }
 
+    fn resolve_did(&self,
+                   pool_handle: PoolHandle,
+                   wallet_handle: WalletHandle,
+                   did: &DidValue,
+                   options: GetCacheOptions,
+                   cb: Box<dyn Fn(IndyResult<String>) + Send>) {
+        trace!("resolve_did >>> pool_handle: {:?}, wallet_handle: {:?}, did: {:?}, options: {:?}",
+               pool_handle, wallet_handle, did, options);
+
+        // DID Documents may change on the ledger, so cached ones expire unless caller asks otherwise
+        let options = GetCacheOptions {
+            min_fresh: options.min_fresh.or(Some(DID_DOC_DEFAULT_MIN_FRESH)),
+            ..options
+        };
+
+        let cache = self.get_record_from_cache(wallet_handle, &did.0, &options, DID_DOC_CACHE);
+        let cache = try_cb!(cache, cb);
+
+        check_cache!(cache, options, cb);
+
+        if options.no_update.unwrap_or(false) {
+            return cb(Err(IndyError::from(IndyErrorKind::LedgerItemNotFound)));
+        }
+
+        let cb_id = next_command_handle();
+        self.pending_callbacks.borrow_mut().insert(cb_id, cb);
+
+        CommandExecutor::instance().send(
+            Command::Ledger(
+                LedgerCommand::GetDidDoc(
+                    pool_handle,
+                    did.clone(),
+                    Box::new(move |ledger_response| {
+                        CommandExecutor::instance().send(
+                            Command::Cache(
+                                CacheCommand::ResolveDidContinue(
+                                    wallet_handle,
+                                    ledger_response,
+                                    options.clone(),
+                                    cb_id,
+                                )
+                            )
+                        ).unwrap();
+                    })
+                )
+            )
+        ).unwrap();
+    }
+
+    fn _resolve_did_continue(&self, wallet_handle: WalletHandle, ledger_response: IndyResult<(String, String)>, options: GetCacheOptions, cb_id: CommandHandle) {
+        let cb = self.pending_callbacks.borrow_mut().remove(&cb_id).expect("FIXME INVALID STATE");
+
+        let (did, did_resolution_result) = try_cb!(ledger_response, cb);
+
+        match self._delete_and_add_record(wallet_handle, options, &did, &did_resolution_result, DID_DOC_CACHE) {
+            Ok(_) => cb(Ok(did_resolution_result)),
+            Err(err) => cb(Err(IndyError::from_msg(IndyErrorKind::InvalidState, format!("resolve_did_continue failed: {:?}", err))))
+        }
+    }
+
     fn get_seconds_since_epoch() -> Result<i32, IndyError> {
         match SystemTime::now().duration_since(UNIX_EPOCH) {
             Ok(ts) => Ok(ts.as_secs() as i32),
@@ -346,4 +434,34 @@ impl CacheCommandExecutor {
 
         Ok(())
     }
+
+    fn purge_did_doc_cache(&self,
+                           wallet_handle: WalletHandle,
+                           options: PurgeOptions) -> IndyResult<()> {
+        trace!("purge_did_doc_cache >>> wallet_handle: {:?}, options: {:?}", wallet_handle, options);
+
+        let max_age = options.max_age.unwrap_or(-1);
+        let query_json = CacheCommandExecutor::build_query_json(max_age)?;
+
+        let options_json = json!({
+            "retrieveType": false,
+            "retrieveValue": false,
+            "retrieveTags": false,
+        }).to_string();
+
+        let mut search = self.wallet_service.search_records(
+            wallet_handle,
+            DID_DOC_CACHE,
+            &query_json,
+            &options_json,
+        )?;
+
+        while let Some(record) = search.fetch_next_record()? {
+            self.wallet_service.delete_record(wallet_handle, DID_DOC_CACHE, record.get_id())?;
+        }
+
+        trace!("purge_did_doc_cache <<< res: ()");
+
+        Ok(())
+    }
 }
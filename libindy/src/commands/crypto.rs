@@ -1,15 +1,20 @@
 use std::collections::HashMap;
 
-use crate::domain::crypto::key::{Key, KeyInfo, KeyMetadata};
+use crate::domain::crypto::key::{Key, KeyInfo, KeyMetadata, MnemonicConfig};
 use crate::domain::crypto::pack::*;
+use crate::domain::crypto::jws::{JWSHeader, JWSJson, JWSSignature};
 use indy_api_types::errors::prelude::*;
-use crate::services::crypto::CryptoService;
+use crate::services::crypto::{CryptoService, KeyCustody};
 use indy_wallet::{RecordOptions, WalletService};
 
 use std::rc::Rc;
 use std::str;
 use indy_utils::crypto::base64;
 use indy_utils::crypto::chacha20poly1305_ietf;
+use indy_utils::crypto::ed25519_box;
+use indy_utils::crypto::hash;
+use indy_utils::crypto::jwa;
+use crate::utils::crypto::did_key;
 use crate::domain::crypto::combo_box::ComboBox;
 use indy_api_types::WalletHandle;
 
@@ -18,12 +23,32 @@ pub const PROTECTED_HEADER_TYP: &str = "JWM/1.0";
 pub const PROTECTED_HEADER_ALG_AUTH: &str = "Authcrypt";
 pub const PROTECTED_HEADER_ALG_ANON: &str = "Anoncrypt";
 
+pub const DIDCOMM_V2_TYP: &str = "application/didcomm-encrypted+json";
+pub const DIDCOMM_V2_ENC: &str = "A256CBC-HS512";
+pub const DIDCOMM_V2_ALG_AUTH: &str = "ECDH-1PU+A256KW";
+pub const DIDCOMM_V2_ALG_ANON: &str = "ECDH-ES+A256KW";
+const EPK_KTY: &str = "OKP";
+const EPK_CRV: &str = "X25519";
+
+const JWS_HEADER_ALG: &str = "alg";
+const JWS_HEADER_KID: &str = "kid";
+const JWS_HEADER_CRIT: &str = "crit";
+
 pub enum CryptoCommand {
+    RegisterKeyCustody(
+        String, // custody name
+        Box<dyn KeyCustody + Send>, // custody
+        Box<dyn Fn(IndyResult<()>) + Send>,
+    ),
     CreateKey(
         WalletHandle,
         KeyInfo, // key info
         Box<dyn Fn(IndyResult<String /*verkey*/>) + Send>,
     ),
+    GenerateMnemonic(
+        MnemonicConfig, // mnemonic config
+        Box<dyn Fn(IndyResult<String /*mnemonic*/>) + Send>,
+    ),
     SetKeyMetadata(
         WalletHandle,
         String, // verkey
@@ -47,6 +72,20 @@ pub enum CryptoCommand {
         Vec<u8>, // signature
         Box<dyn Fn(IndyResult<bool>) + Send>,
     ),
+    SignJWS(
+        WalletHandle,
+        String,  // my vk
+        Vec<u8>, // payload
+        Option<JWSHeader>, // header
+        bool, // detached
+        Box<dyn Fn(IndyResult<(String, String)>) + Send>,
+    ),
+    VerifyJWS(
+        String,  // jws
+        String,  // their vk
+        Option<Vec<u8>>, // detached payload
+        Box<dyn Fn(IndyResult<bool>) + Send>,
+    ),
     AuthenticatedEncrypt(
         WalletHandle,
         String,  // my vk
@@ -75,6 +114,7 @@ pub enum CryptoCommand {
         Vec<u8>, // plaintext message
         Vec<String>,  // list of receiver's keys
         Option<String>,  // senders verkey
+        PackMessageOptions,
         WalletHandle,
         Box<dyn Fn(IndyResult<Vec<u8>>) + Send>,
     ),
@@ -103,10 +143,18 @@ impl CryptoCommandExecutor {
 
     pub fn execute(&self, command: CryptoCommand) {
         match command {
+            CryptoCommand::RegisterKeyCustody(name, custody, cb) => {
+                debug!("RegisterKeyCustody command received");
+                cb(self.register_key_custody(&name, custody));
+            }
             CryptoCommand::CreateKey(wallet_handle, key_info, cb) => {
                 debug!("CreateKey command received");
                 cb(self.create_key(wallet_handle, &key_info));
             }
+            CryptoCommand::GenerateMnemonic(config, cb) => {
+                debug!("GenerateMnemonic command received");
+                cb(self.generate_mnemonic(&config));
+            }
             CryptoCommand::SetKeyMetadata(wallet_handle, verkey, metadata, cb) => {
                 debug!("SetKeyMetadata command received");
                 cb(self.set_key_metadata(wallet_handle, &verkey, &metadata));
@@ -123,6 +171,14 @@ impl CryptoCommandExecutor {
                 debug!("CryptoVerify command received");
                 cb(self.crypto_verify(&their_vk, &msg, &signature));
             }
+            CryptoCommand::SignJWS(wallet_handle, my_vk, payload, header, detached, cb) => {
+                debug!("SignJWS command received");
+                cb(self.sign_jws(wallet_handle, &my_vk, &payload, header, detached));
+            }
+            CryptoCommand::VerifyJWS(jws, their_vk, detached_payload, cb) => {
+                debug!("VerifyJWS command received");
+                cb(self.verify_jws(&jws, &their_vk, detached_payload.as_ref().map(Vec::as_slice)));
+            }
             CryptoCommand::AuthenticatedEncrypt(wallet_handle, my_vk, their_vk, msg, cb) => {
                 debug!("AuthenticatedEncrypt command received");
                 cb(self.authenticated_encrypt(wallet_handle, &my_vk, &their_vk, &msg));
@@ -139,9 +195,9 @@ impl CryptoCommandExecutor {
                 debug!("AnonymousDecrypt command received");
                 cb(self.anonymous_decrypt(wallet_handle, &my_vk, &encrypted_msg));
             }
-            CryptoCommand::PackMessage(message, receivers, sender_vk, wallet_handle, cb) => {
+            CryptoCommand::PackMessage(message, receivers, sender_vk, options, wallet_handle, cb) => {
                 debug!("PackMessage command received");
-                cb(self.pack_msg(message, receivers, sender_vk, wallet_handle));
+                cb(self.pack_msg(message, receivers, sender_vk, options, wallet_handle));
             }
             CryptoCommand::UnpackMessage(jwe_json, wallet_handle, cb) => {
                 debug!("UnpackMessage command received");
@@ -150,6 +206,15 @@ impl CryptoCommandExecutor {
         };
     }
 
+    fn register_key_custody(&self, name: &str, custody: Box<dyn KeyCustody + Send>) -> IndyResult<()> {
+        debug!("register_key_custody >>> name: {:?}", name);
+
+        self.crypto_service.register_key_custody(name, custody)?;
+
+        debug!("register_key_custody <<<");
+        Ok(())
+    }
+
     fn create_key(&self, wallet_handle: WalletHandle, key_info: &KeyInfo) -> IndyResult<String> {
         debug!(
             "create_key >>> wallet_handle: {:?}, key_info: {:?}",
@@ -166,6 +231,15 @@ impl CryptoCommandExecutor {
         Ok(res)
     }
 
+    fn generate_mnemonic(&self, config: &MnemonicConfig) -> IndyResult<String> {
+        debug!("generate_mnemonic >>> config: {:?}", config);
+
+        let res = self.crypto_service.generate_mnemonic(config.word_count)?;
+
+        debug!("generate_mnemonic <<< res: {:?}", secret!(&res));
+        Ok(res)
+    }
+
     fn crypto_sign(&self, wallet_handle: WalletHandle, my_vk: &str, msg: &[u8]) -> IndyResult<Vec<u8>> {
         trace!(
             "crypto_sign >>> wallet_handle: {:?}, sender_vk: {:?}, msg: {:?}",
@@ -205,6 +279,103 @@ impl CryptoCommandExecutor {
         Ok(res)
     }
 
+    fn sign_jws(&self,
+                wallet_handle: WalletHandle,
+                my_vk: &str,
+                payload: &[u8],
+                header: Option<JWSHeader>,
+                detached: bool) -> IndyResult<(String, String)> {
+        trace!("sign_jws >>> wallet_handle: {:?}, my_vk: {:?}, payload: {:?}, header: {:?}, detached: {:?}",
+               wallet_handle, my_vk, payload, header, detached);
+
+        self.crypto_service.validate_key(my_vk)?;
+
+        let key: Key = self.wallet_service.get_indy_object(
+            wallet_handle,
+            &my_vk,
+            &RecordOptions::id_value(),
+        )?;
+
+        let alg = self.crypto_service.jws_alg(my_vk)?;
+
+        let mut header = header.unwrap_or_default();
+
+        if let Some(header_alg) = header.get(JWS_HEADER_ALG) {
+            if header_alg.as_str() != Some(alg) {
+                return Err(err_msg(IndyErrorKind::InvalidStructure,
+                                   format!("JWS header alg {} doesn't match key algorithm {}", header_alg, alg)));
+            }
+        }
+
+        header.insert(JWS_HEADER_ALG.to_string(), serde_json::Value::String(alg.to_string()));
+        header.entry(JWS_HEADER_KID).or_insert_with(|| serde_json::Value::String(my_vk.to_string()));
+
+        let header = serde_json::to_string(&header)
+            .to_indy(IndyErrorKind::InvalidStructure, "Failed to serialize JWS header")?;
+
+        let protected = base64::encode_urlsafe_no_pad(header.as_bytes());
+        let encoded_payload = base64::encode_urlsafe_no_pad(payload);
+
+        let signature = self.crypto_service.sign(&key, _jws_signing_input(&protected, &encoded_payload).as_bytes())?;
+        let signature = base64::encode_urlsafe_no_pad(&signature);
+
+        let attached_payload = if detached { None } else { Some(encoded_payload) };
+
+        let compact = format!("{}.{}.{}", protected, attached_payload.as_ref().map(String::as_str).unwrap_or(""), signature);
+
+        let json = serde_json::to_string(&JWSJson::Flattened { payload: attached_payload, protected, signature })
+            .to_indy(IndyErrorKind::InvalidStructure, "Failed to serialize JWS")?;
+
+        trace!("sign_jws <<< compact: {:?}, json: {:?}", compact, json);
+
+        Ok((compact, json))
+    }
+
+    fn verify_jws(&self,
+                  jws: &str,
+                  their_vk: &str,
+                  detached_payload: Option<&[u8]>) -> IndyResult<bool> {
+        trace!("verify_jws >>> jws: {:?}, their_vk: {:?}, detached_payload: {:?}", jws, their_vk, detached_payload);
+
+        let (payload, signatures) = _parse_jws(jws)?;
+
+        let payload = match (payload, detached_payload) {
+            (Some(payload), None) => payload,
+            (None, Some(detached_payload)) => base64::encode_urlsafe_no_pad(detached_payload),
+            (Some(_), Some(_)) => return Err(err_msg(IndyErrorKind::InvalidStructure, "JWS payload is attached, but detached payload is passed as well")),
+            (None, None) => return Err(err_msg(IndyErrorKind::InvalidStructure, "JWS payload is detached, but detached payload isn't passed")),
+        };
+
+        self.crypto_service.validate_key(their_vk)?;
+
+        let mut res = false;
+
+        for signature in signatures {
+            let header: JWSHeader = serde_json::from_slice(&base64::decode_urlsafe(&signature.protected)?)
+                .to_indy(IndyErrorKind::InvalidStructure, "Failed to deserialize JWS header")?;
+
+            if header.contains_key(JWS_HEADER_CRIT) {
+                return Err(err_msg(IndyErrorKind::InvalidStructure, "JWS critical header parameters aren't supported"));
+            }
+
+            // Algorithm is defined by the key to prevent algorithm substitution
+            if header.get(JWS_HEADER_ALG).and_then(serde_json::Value::as_str) != Some(self.crypto_service.jws_alg(their_vk)?) {
+                continue;
+            }
+
+            let signing_input = _jws_signing_input(&signature.protected, &payload);
+
+            if self.crypto_service.verify(their_vk, signing_input.as_bytes(), &base64::decode_urlsafe(&signature.signature)?)? {
+                res = true;
+                break;
+            }
+        }
+
+        trace!("verify_jws <<< res: {:?}", res);
+
+        Ok(res)
+    }
+
     //TODO begin deprecation process this function. It will be replaced by pack
     fn authenticated_encrypt(
         &self,
@@ -364,6 +535,7 @@ impl CryptoCommandExecutor {
         message: Vec<u8>,
         receiver_list: Vec<String>,
         sender_vk: Option<String>,
+        options: PackMessageOptions,
         wallet_handle: WalletHandle,
     ) -> IndyResult<Vec<u8>> {
 
@@ -372,6 +544,10 @@ impl CryptoCommandExecutor {
             return Err(err_msg(IndyErrorKind::InvalidStructure, "No receiver keys found".to_string()));
         }
 
+        if options.format == PackFormat::DIDCommV2 {
+            return self._pack_msg_didcomm_v2(message, receiver_list, sender_vk, wallet_handle);
+        }
+
         //generate content encryption key that will encrypt `message`
         let cek = chacha20poly1305_ietf::gen_key();
 
@@ -481,6 +657,7 @@ impl CryptoCommandExecutor {
         //serialize pack message and return as vector of bytes
         let jwe_struct = JWE {
             protected: base64_protected.to_string(),
+            recipients: None,
             iv: iv.to_string(),
             ciphertext: ciphertext.to_string(),
             tag: tag.to_string()
@@ -494,6 +671,100 @@ impl CryptoCommandExecutor {
         })
     }
 
+    // Standard JWE in general JSON serialization as defined by DIDComm Messaging v2.
+    // Sender uses ECDH-1PU and recipient uses ECDH-ES key agreement over X25519 keys converted from ed25519 verkeys.
+    // Keys are identified by did:key DID URLs.
+    fn _pack_msg_didcomm_v2(
+        &self,
+        message: Vec<u8>,
+        receiver_list: Vec<String>,
+        sender_vk: Option<String>,
+        wallet_handle: WalletHandle,
+    ) -> IndyResult<Vec<u8>> {
+        let sender_key: Option<Key> = match sender_vk {
+            Some(ref sender_vk) => {
+                self.crypto_service.validate_key(sender_vk)?;
+                Some(self.wallet_service.get_indy_object(wallet_handle, sender_vk, &RecordOptions::id_value())?)
+            }
+            None => None
+        };
+
+        let (epk, esk) = ed25519_box::gen_keypair();
+
+        let skid = match sender_vk {
+            Some(ref sender_vk) => Some(did_key::build_key_id(sender_vk)?),
+            None => None
+        };
+        let kids = receiver_list.iter()
+            .map(|their_vk| did_key::build_key_id(their_vk))
+            .collect::<IndyResult<Vec<String>>>()?;
+
+        //apu identifies sender and apv binds envelope to the whole list of recipients
+        let apu = skid.as_ref().map(|skid| skid.as_bytes().to_vec()).unwrap_or_default();
+        let mut sorted_kids = kids.clone();
+        sorted_kids.sort();
+        let apv = hash::hash(sorted_kids.join(".").as_bytes())?;
+
+        let protected_struct = JWEProtected {
+            typ: DIDCOMM_V2_TYP.to_string(),
+            alg: if sender_key.is_some() { DIDCOMM_V2_ALG_AUTH.to_string() } else { DIDCOMM_V2_ALG_ANON.to_string() },
+            enc: DIDCOMM_V2_ENC.to_string(),
+            apu: skid.as_ref().map(|_| base64::encode_urlsafe_no_pad(&apu)),
+            skid,
+            apv: base64::encode_urlsafe_no_pad(&apv),
+            epk: EphemeralPublicKey {
+                kty: EPK_KTY.to_string(),
+                crv: EPK_CRV.to_string(),
+                x: base64::encode_urlsafe_no_pad(&epk[..]),
+            },
+        };
+
+        let protected_encoded = serde_json::to_string(&protected_struct)
+            .to_indy(IndyErrorKind::InvalidStructure, "Failed to serialize protected field")?;
+        let base64_protected = base64::encode_urlsafe_no_pad(protected_encoded.as_bytes());
+
+        //encrypt content first as ECDH-1PU key wrapping depends on authentication tag
+        let cek = jwa::gen_cek()?;
+        let (ciphertext, iv, tag) = jwa::encrypt_a256cbc_hs512(&cek, &message, base64_protected.as_bytes())?;
+
+        let mut recipients: Vec<JWERecipient> = Vec::with_capacity(receiver_list.len());
+
+        for (their_vk, kid) in receiver_list.into_iter().zip(kids) {
+            let their_pk = self.crypto_service.x25519_public_key(&their_vk)?;
+
+            let mut z = ed25519_box::scalarmult(&esk, &their_pk)?;
+
+            let cc_tag = match sender_key {
+                Some(ref sender_key) => {
+                    z.extend(self.crypto_service.key_agreement(sender_key, &their_pk)?);
+                    Some(tag.as_slice())
+                }
+                None => None
+            };
+
+            let kek = jwa::concat_kdf(&z, &protected_struct.alg, &apu, &apv, cc_tag)?;
+            let encrypted_key = jwa::wrap(&kek, &cek)?;
+
+            recipients.push(JWERecipient {
+                encrypted_key: base64::encode_urlsafe_no_pad(&encrypted_key),
+                header: JWERecipientHeader {
+                    kid
+                },
+            });
+        }
+
+        let jwe_struct = JWE {
+            protected: base64_protected,
+            recipients: Some(recipients),
+            iv: base64::encode_urlsafe_no_pad(&iv),
+            ciphertext: base64::encode_urlsafe_no_pad(&ciphertext),
+            tag: base64::encode_urlsafe_no_pad(&tag),
+        };
+
+        serde_json::to_vec(&jwe_struct)
+            .to_indy(IndyErrorKind::InvalidStructure, "Failed to serialize JWE")
+    }
+
     pub fn unpack_msg(&self, jwe_struct: JWE, wallet_handle: WalletHandle) -> IndyResult<Vec<u8>> {
         //decode protected data
         let protected_decoded_vec = base64::decode_urlsafe(&jwe_struct.protected)?;
@@ -503,6 +774,16 @@ impl CryptoCommandExecutor {
                 err
             ))
         })?;
+
+        //DIDComm v2 envelopes are detected by key management algorithm
+        let is_didcomm_v2 = serde_json::from_str::<serde_json::Value>(&protected_decoded_str).ok()
+            .and_then(|protected| protected["alg"].as_str().map(|alg| alg == DIDCOMM_V2_ALG_AUTH || alg == DIDCOMM_V2_ALG_ANON))
+            .unwrap_or(false);
+
+        if is_didcomm_v2 {
+            return self._unpack_msg_didcomm_v2(jwe_struct, &protected_decoded_str, wallet_handle);
+        }
+
         //convert protected_data_str to struct
         let protected_struct: Protected = serde_json::from_str(&protected_decoded_str).map_err(|err| {
             err_msg(IndyErrorKind::InvalidStructure, format!(
@@ -545,6 +826,94 @@ impl CryptoCommandExecutor {
         })
     }
 
+    fn _unpack_msg_didcomm_v2(&self, jwe_struct: JWE, protected_decoded_str: &str, wallet_handle: WalletHandle) -> IndyResult<Vec<u8>> {
+        let protected_struct: JWEProtected = serde_json::from_str(protected_decoded_str)
+            .to_indy(IndyErrorKind::InvalidStructure, "Failed to deserialize protected data")?;
+
+        if protected_struct.enc != DIDCOMM_V2_ENC {
+            return Err(err_msg(IndyErrorKind::UnknownCrypto, format!("Unsupported content encryption: {}", protected_struct.enc)));
+        }
+
+        if protected_struct.epk.kty != EPK_KTY || protected_struct.epk.crv != EPK_CRV {
+            return Err(err_msg(IndyErrorKind::UnknownCrypto,
+                               format!("Unsupported ephemeral key: {} {}", protected_struct.epk.kty, protected_struct.epk.crv)));
+        }
+
+        //extract recipient that matches a key in the wallet
+        let mut found_recipient: Option<(JWERecipient, String, Key)> = None;
+
+        for recipient in jwe_struct.recipients.unwrap_or_default() {
+            //keys of other DID methods can't be in the wallet
+            let my_vk = match did_key::resolve_key_id(&recipient.header.kid)? {
+                Some(my_vk) => my_vk,
+                None => continue
+            };
+
+            match self.wallet_service.get_indy_object::<Key>(wallet_handle, &my_vk, &RecordOptions::id_value()) {
+                Ok(my_key) => {
+                    found_recipient = Some((recipient, my_vk, my_key));
+                    break;
+                }
+                Err(ref err) if err.kind() == IndyErrorKind::WalletItemNotFound => continue,
+                Err(err) => return Err(err)
+            }
+        }
+
+        let (recipient, my_vk, my_key) = found_recipient
+            .ok_or_else(|| IndyError::from(IndyErrorKind::WalletItemNotFound))?;
+
+        let epk = ed25519_box::PublicKey::from_slice(&base64::decode_urlsafe(&protected_struct.epk.x)?)?;
+        let apu = match protected_struct.apu {
+            Some(ref apu) => base64::decode_urlsafe(apu)?,
+            None => Vec::new()
+        };
+        let apv = base64::decode_urlsafe(&protected_struct.apv)?;
+        let tag = base64::decode_urlsafe(&jwe_struct.tag)?;
+
+        let mut z = self.crypto_service.key_agreement(&my_key, &epk)?;
+
+        let (sender_verkey_option, cc_tag) = if protected_struct.alg == DIDCOMM_V2_ALG_AUTH {
+            let skid = match protected_struct.skid {
+                Some(ref skid) => skid.clone(),
+                None => String::from_utf8(apu.clone())
+                    .to_indy(IndyErrorKind::InvalidStructure, "Failed to utf-8 encode sender key id")?
+            };
+            let sender_vk = did_key::resolve_key_id(&skid)?
+                .ok_or_else(|| err_msg(IndyErrorKind::InvalidStructure, format!("Unsupported sender key id: {}", skid)))?;
+
+            self.crypto_service.validate_key(&sender_vk)?;
+            let sender_pk = self.crypto_service.x25519_public_key(&sender_vk)?;
+            z.extend(self.crypto_service.key_agreement(&my_key, &sender_pk)?);
+
+            (Some(sender_vk), Some(tag.as_slice()))
+        } else {
+            (None, None)
+        };
+
+        //unwrap cek
+        let kek = jwa::concat_kdf(&z, &protected_struct.alg, &apu, &apv, cc_tag)?;
+        let cek = jwa::unwrap(&kek, &base64::decode_urlsafe(&recipient.encrypted_key)?)?;
+
+        //decrypt message
+        let message = jwa::decrypt_a256cbc_hs512(&cek,
+                                                 &base64::decode_urlsafe(&jwe_struct.ciphertext)?,
+                                                 jwe_struct.protected.as_bytes(),
+                                                 &base64::decode_urlsafe(&jwe_struct.iv)?,
+                                                 &tag)?;
+        let message = String::from_utf8(message)
+            .to_indy(IndyErrorKind::InvalidStructure, "Failed to convert message to UTF-8")?;
+
+        //serialize and return decrypted message
+        let res = UnpackMessage {
+            message,
+            sender_verkey: sender_verkey_option,
+            recipient_verkey: my_vk
+        };
+
+        serde_json::to_vec(&res)
+            .to_indy(IndyErrorKind::InvalidStructure, "Failed to serialize message")
+    }
+
     fn _find_correct_recipient(&self, protected_struct: Protected, wallet_handle: WalletHandle) -> IndyResult<(Recipient, bool)>{
         for recipient in protected_struct.recipients {
             let my_key_res = self.wallet_service.get_indy_object::<Key>(
@@ -622,3 +991,32 @@ impl CryptoCommandExecutor {
     }
 
 }
+
+fn _jws_signing_input(protected: &str, payload: &str) -> String {
+    format!("{}.{}", protected, payload)
+}
+
+// Returns encoded payload (None for detached content) and signatures of compact or JSON serialized JWS
+fn _parse_jws(jws: &str) -> IndyResult<(Option<String>, Vec<JWSSignature>)> {
+    let jws = jws.trim();
+
+    if jws.starts_with('{') {
+        let jws: JWSJson = serde_json::from_str(jws)
+            .to_indy(IndyErrorKind::InvalidStructure, "Invalid JWS JSON serialization")?;
+
+        return match jws {
+            JWSJson::General { payload, signatures } => Ok((payload, signatures)),
+            JWSJson::Flattened { payload, protected, signature } => Ok((payload, vec![JWSSignature { protected, signature }])),
+        };
+    }
+
+    let parts: Vec<&str> = jws.split('.').collect();
+
+    if parts.len() != 3 {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, "Invalid JWS compact serialization"));
+    }
+
+    let payload = if parts[1].is_empty() { None } else { Some(parts[1].to_string()) };
+
+    Ok((payload, vec![JWSSignature { protected: parts[0].to_string(), signature: parts[2].to_string() }]))
+}
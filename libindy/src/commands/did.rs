@@ -15,6 +15,7 @@ use crate::domain::pairwise::Pairwise;
 use indy_api_types::errors::prelude::*;
 use crate::services::crypto::CryptoService;
 use crate::services::ledger::LedgerService;
+use crate::utils::crypto::did_key::resolve_verkey;
 use indy_wallet::{RecordOptions, SearchOptions, WalletService};
 use indy_api_types::{WalletHandle, PoolHandle, CommandHandle};
 use indy_utils::next_command_handle;
@@ -232,6 +233,11 @@ impl DidCommandExecutor {
 
         self.crypto_service.validate_did(my_did)?;
 
+        if resolve_verkey(my_did)?.is_some() {
+            return Err(err_msg(IndyErrorKind::InvalidStructure,
+                               format!("Keys of DID \"{}\" can't be replaced as verkey is encoded in the DID itself", my_did.0)));
+        }
+
         let my_did = self._wallet_get_my_did(wallet_handle, my_did)?;
 
         let temporary_key = self.crypto_service.create_key(&key_info)?;
@@ -350,6 +356,12 @@ impl DidCommandExecutor {
 
         try_cb!(self.crypto_service.validate_did(&did), cb);
 
+        // did:key and did:peer encode verkey in the identifier
+        if let Some(verkey) = try_cb!(self._resolve_did_verkey(&did), cb) {
+            debug!("key_for_did <<< res: {:?}", verkey);
+            return cb(Ok(verkey));
+        }
+
         // Look to my did
         match self._wallet_get_my_did(wallet_handle, &did) {
             Ok(my_did) => return cb(Ok(my_did.verkey)),
@@ -383,6 +395,12 @@ impl DidCommandExecutor {
 
         self.crypto_service.validate_did(&did)?;
 
+        // did:key and did:peer encode verkey in the identifier
+        if let Some(verkey) = self._resolve_did_verkey(did)? {
+            info!("key_for_local_did <<< res: {:?}", verkey);
+            return Ok(verkey);
+        }
+
         // Look to my did
         match self._wallet_get_my_did(wallet_handle, did) {
             Ok(my_did) => return Ok(my_did.verkey),
@@ -733,6 +751,16 @@ impl DidCommandExecutor {
             ))).unwrap();
     }
 
+    fn _resolve_did_verkey(&self, did: &DidValue) -> IndyResult<Option<String>> {
+        let verkey = resolve_verkey(did)?;
+
+        if let Some(ref verkey) = verkey {
+            self.crypto_service.validate_key(verkey)?;
+        }
+
+        Ok(verkey)
+    }
+
     fn _wallet_get_my_did(&self, wallet_handle: WalletHandle, my_did: &DidValue) -> IndyResult<Did> {
         self.wallet_service.get_indy_object(wallet_handle, &my_did.0, &RecordOptions::id_value())
     }
@@ -22,7 +22,9 @@ use crate::domain::crypto::did::{Did, DidValue};
 use crate::domain::crypto::key::Key;
 use crate::domain::ledger::auth_rule::{AuthRules, Constraint};
 use crate::domain::ledger::author_agreement::{AcceptanceMechanisms, GetTxnAuthorAgreementData};
+use crate::domain::ledger::did_doc::{DidDocumentMetadata, DidResolutionMetadata, DidResolutionResult, DID_LD_JSON_CONTENT_TYPE, DIDDOC_CONTENT_ATTRIB, ENDPOINT_ATTRIB};
 use crate::domain::ledger::node::NodeOperationData;
+use crate::domain::ledger::nym::NymData;
 use crate::domain::ledger::pool::Schedule;
 use crate::domain::ledger::request::Request;
 use crate::services::crypto::CryptoService;
@@ -32,6 +34,7 @@ use crate::services::pool::{
     PoolService
 };
 use crate::utils::crypto::signature_serializer::serialize_signature;
+use crate::utils::crypto::verkey_builder::build_full_verkey;
 
 pub enum LedgerCommand {
     SignAndSubmitRequest(
@@ -239,6 +242,18 @@ pub enum LedgerCommand {
         IndyResult<String>,
         CommandHandle,
     ),
+    GetDidDoc(
+        PoolHandle,
+        DidValue, // did
+        BoxedCallbackStringStringSend,
+    ),
+    GetDidDocContinue(
+        PoolHandle,
+        DidValue, // did
+        Vec<String>, // received replies: GET_NYM, GET_ATTRIB endpoint, GET_ATTRIB diddocContent
+        IndyResult<String>,
+        CommandHandle,
+    ),
     BuildTxnAuthorAgreementRequest(
         DidValue, // submitter did
         Option<String>, // text
@@ -492,6 +507,14 @@ impl LedgerCommandExecutor {
                 debug!(target: "ledger_command_executor", "GetCredDefContinue command received");
                 self._get_cred_def_continue(id, pool_response, cb_id);
             }
+            LedgerCommand::GetDidDoc(pool_handle, did, cb) => {
+                debug!(target: "ledger_command_executor", "GetDidDoc command received");
+                self.get_did_doc(pool_handle, &did, cb);
+            }
+            LedgerCommand::GetDidDocContinue(pool_handle, did, replies, pool_response, cb_id) => {
+                debug!(target: "ledger_command_executor", "GetDidDocContinue command received");
+                self._get_did_doc_continue(pool_handle, did, replies, pool_response, cb_id);
+            }
             LedgerCommand::BuildTxnAuthorAgreementRequest(submitter_did, text, version, ratification_ts, retirement_ts, cb) => {
                 debug!(target: "ledger_command_executor", "BuildTxnAuthorAgreementRequest command received");
                 cb(self.build_txn_author_agreement_request(&submitter_did, text.as_ref().map(String::as_str), &version, ratification_ts, retirement_ts));
@@ -1308,6 +1331,120 @@ impl LedgerCommandExecutor {
         let pool_response = try_cb!(pool_response, cb);
         cb(self.ledger_service.parse_get_cred_def_response(&pool_response, id.get_method().as_ref().map(String::as_str)))
     }
+
+    fn get_did_doc(&self, pool_handle: PoolHandle, did: &DidValue, cb: BoxedCallbackStringStringSend) {
+        try_cb!(self.crypto_service.validate_did(did), cb);
+
+        let cb_id = next_command_handle();
+        self.pending_callbacks.borrow_mut().insert(cb_id, cb);
+
+        self._get_did_doc_submit(pool_handle, did.clone(), Vec::new(), cb_id);
+    }
+
+    // Ledger data of DID Document is requested one by one as pool replies come in
+    fn _get_did_doc_submit(&self, pool_handle: PoolHandle, did: DidValue, replies: Vec<String>, cb_id: CommandHandle) {
+        let request_json = match replies.len() {
+            0 => self.ledger_service.build_get_nym_request(None, &did),
+            1 => self.ledger_service.build_get_attrib_request(None, &did, Some(ENDPOINT_ATTRIB), None, None),
+            _ => self.ledger_service.build_get_attrib_request(None, &did, Some(DIDDOC_CONTENT_ATTRIB), None, None)
+        };
+
+        let request_json = match request_json {
+            Ok(request_json) => request_json,
+            Err(err) => {
+                let cb = self.pending_callbacks.borrow_mut().remove(&cb_id).expect("FIXME INVALID STATE");
+                return cb(Err(err));
+            }
+        };
+
+        self.submit_request(pool_handle, &request_json, Box::new(move |response| {
+            CommandExecutor::instance().send(
+                Command::Ledger(
+                    LedgerCommand::GetDidDocContinue(
+                        pool_handle,
+                        did.clone(),
+                        replies.clone(),
+                        response,
+                        cb_id
+                    )
+                )
+            ).unwrap();
+        }));
+    }
+
+    fn _get_did_doc_continue(&self, pool_handle: PoolHandle, did: DidValue, mut replies: Vec<String>, pool_response: IndyResult<String>, cb_id: CommandHandle) {
+        let pool_response = pool_response.and_then(|pool_response| {
+            // ATTRIBs of unknown DID aren't requested
+            if replies.is_empty() {
+                self.ledger_service.parse_get_nym_response(&pool_response)?;
+            }
+            Ok(pool_response)
+        });
+
+        match pool_response {
+            Ok(pool_response) => replies.push(pool_response),
+            Err(err) => {
+                let cb = self.pending_callbacks.borrow_mut().remove(&cb_id).expect("FIXME INVALID STATE");
+                return cb(Err(err));
+            }
+        }
+
+        if replies.len() < 3 {
+            return self._get_did_doc_submit(pool_handle, did, replies, cb_id);
+        }
+
+        let cb = self.pending_callbacks.borrow_mut().remove(&cb_id).expect("FIXME INVALID STATE");
+        cb(self._build_did_resolution_result(&did, &replies[0], &replies[1], &replies[2]).map(|res| (did.0.clone(), res)))
+    }
+
+    fn _build_did_resolution_result(&self, did: &DidValue, get_nym_response: &str, get_endpoint_response: &str, get_diddoc_content_response: &str) -> IndyResult<String> {
+        debug!("_build_did_resolution_result >>> did: {:?}, get_nym_response: {:?}, get_endpoint_response: {:?}, get_diddoc_content_response: {:?}",
+               did, get_nym_response, get_endpoint_response, get_diddoc_content_response);
+
+        let nym_data: NymData = serde_json::from_str(&self.ledger_service.parse_get_nym_response(get_nym_response)?)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot deserialize NYM data")?;
+
+        let nym_metadata = parse_response_metadata(get_nym_response)?;
+
+        let verkey = match nym_data.verkey {
+            Some(ref verkey) => Some(build_full_verkey(&did.to_unqualified().0, Some(verkey))?),
+            None => None
+        };
+
+        let x25519_key = match verkey {
+            Some(ref verkey) => Some(self.crypto_service.x25519_public_key(verkey)?[..].to_base58()),
+            None => None
+        };
+
+        let endpoint = self.ledger_service.parse_get_attrib_response(get_endpoint_response, ENDPOINT_ATTRIB)?;
+        let diddoc_content = self.ledger_service.parse_get_attrib_response(get_diddoc_content_response, DIDDOC_CONTENT_ATTRIB)?;
+
+        let did_document = self.ledger_service.build_did_doc(did,
+                                                             verkey.as_ref().map(String::as_str),
+                                                             x25519_key.as_ref().map(String::as_str),
+                                                             endpoint,
+                                                             diddoc_content)?;
+
+        let res = DidResolutionResult {
+            did_document,
+            did_document_metadata: DidDocumentMetadata {
+                seq_no: nym_metadata.seq_no,
+                txn_time: nym_metadata.txn_time,
+                role: nym_data.role,
+            },
+            did_resolution_metadata: DidResolutionMetadata {
+                content_type: DID_LD_JSON_CONTENT_TYPE.to_string(),
+                last_txn_time: nym_metadata.last_txn_time,
+            },
+        };
+
+        let res = serde_json::to_string(&res)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize DID resolution result")?;
+
+        debug!("_build_did_resolution_result <<< res: {:?}", res);
+
+        Ok(res)
+    }
 }
 
 enum SignatureType {
@@ -2,9 +2,9 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 
-use indy_api_types::domain::wallet::Tags;
+use indy_api_types::domain::wallet::{AddRecordOptions, Tags, UpdateRecordOptions, WalletAggregation};
 use indy_api_types::errors::prelude::*;
-use indy_wallet::{RecordOptions, SearchOptions, WalletRecord, WalletSearch, WalletService};
+use indy_wallet::{RecordOptions, SearchOptions, WalletOperation, WalletRecord, WalletSearch, WalletService};
 use indy_utils::next_search_handle;
 use indy_api_types::{WalletHandle, SearchHandle};
 
@@ -15,11 +15,13 @@ pub enum NonSecretsCommand {
               String, // id
               String, // value
               Option<Tags>, //tags
+              AddRecordOptions, // options
               Box<dyn Fn(IndyResult<()>) + Send>),
     UpdateRecordValue(WalletHandle,
                       String, // type
                       String, // id
                       String, // value
+                      UpdateRecordOptions, // options
                       Box<dyn Fn(IndyResult<()>) + Send>),
     UpdateRecordTags(WalletHandle,
                      String, // type
@@ -40,6 +42,9 @@ pub enum NonSecretsCommand {
                  String, // type
                  String, // id
                  Box<dyn Fn(IndyResult<()>) + Send>),
+    BatchExecute(WalletHandle,
+                 String, // operations json
+                 Box<dyn Fn(IndyResult<()>) + Send>),
     GetRecord(WalletHandle,
               String, // type
               String, // id
@@ -50,12 +55,19 @@ pub enum NonSecretsCommand {
                String, // query json
                String, // options json
                Box<dyn Fn(IndyResult<SearchHandle>) + Send>),
+    OpenAllRecordsSearch(WalletHandle,
+                         Box<dyn Fn(IndyResult<SearchHandle>) + Send>),
     FetchSearchNextRecords(WalletHandle,
                            SearchHandle, // wallet search handle
                            usize, // count
                            Box<dyn Fn(IndyResult<String>) + Send>),
     CloseSearch(SearchHandle, // wallet search handle
                 Box<dyn Fn(IndyResult<()>) + Send>),
+    Aggregate(WalletHandle,
+              String, // type
+              String, // query json
+              WalletAggregation, // aggregation
+              Box<dyn Fn(IndyResult<String>) + Send>),
 }
 
 pub struct NonSecretsCommandExecutor {
@@ -73,13 +85,13 @@ impl NonSecretsCommandExecutor {
 
     pub fn execute(&self, command: NonSecretsCommand) {
         match command {
-            NonSecretsCommand::AddRecord(handle, type_, id, value, tags, cb) => {
+            NonSecretsCommand::AddRecord(handle, type_, id, value, tags, options, cb) => {
                 debug!(target: "non_secrets_command_executor", "AddRecord command received");
-                cb(self.add_record(handle, &type_, &id, &value, tags.as_ref()));
+                cb(self.add_record(handle, &type_, &id, &value, tags.as_ref(), &options));
             }
-            NonSecretsCommand::UpdateRecordValue(handle, type_, id, value, cb) => {
+            NonSecretsCommand::UpdateRecordValue(handle, type_, id, value, options, cb) => {
                 debug!(target: "non_secrets_command_executor", "UpdateRecordValue command received");
-                cb(self.update_record_value(handle, &type_, &id, &value));
+                cb(self.update_record_value(handle, &type_, &id, &value, &options));
             }
             NonSecretsCommand::UpdateRecordTags(handle, type_, id, tags, cb) => {
                 debug!(target: "non_secrets_command_executor", "UpdateRecordTags command received");
@@ -97,6 +109,10 @@ impl NonSecretsCommandExecutor {
                 debug!(target: "non_secrets_command_executor", "DeleteRecord command received");
                 cb(self.delete_record(handle, &type_, &id));
             }
+            NonSecretsCommand::BatchExecute(handle, operations_json, cb) => {
+                debug!(target: "non_secrets_command_executor", "BatchExecute command received");
+                cb(self.batch_execute(handle, &operations_json));
+            }
             NonSecretsCommand::GetRecord(handle, type_, id, options_json, cb) => {
                 debug!(target: "non_secrets_command_executor", "GetRecord command received");
                 cb(self.get_record(handle, &type_, &id, &options_json));
@@ -105,6 +121,10 @@ impl NonSecretsCommandExecutor {
                 debug!(target: "non_secrets_command_executor", "OpenSearch command received");
                 cb(self.open_search(handle, &type_, &query_json, &options_json));
             }
+            NonSecretsCommand::OpenAllRecordsSearch(handle, cb) => {
+                debug!(target: "non_secrets_command_executor", "OpenAllRecordsSearch command received");
+                cb(self.open_all_records_search(handle));
+            }
             NonSecretsCommand::FetchSearchNextRecords(wallet_handle, wallet_search_handle, count, cb) => {
                 debug!(target: "non_secrets_command_executor", "SearchNextRecords command received");
                 cb(self.fetch_search_next_records(wallet_handle, wallet_search_handle, count));
@@ -113,6 +133,10 @@ impl NonSecretsCommandExecutor {
                 debug!(target: "non_secrets_command_executor", "CloseSearch command received");
                 cb(self.close_search(wallet_search_handle));
             }
+            NonSecretsCommand::Aggregate(handle, type_, query_json, aggregation, cb) => {
+                debug!(target: "non_secrets_command_executor", "Aggregate command received");
+                cb(self.aggregate(handle, &type_, &query_json, &aggregation));
+            }
         };
     }
 
@@ -121,12 +145,13 @@ impl NonSecretsCommandExecutor {
                   type_: &str,
                   id: &str,
                   value: &str,
-                  tags: Option<&Tags>) -> IndyResult<()> {
-        trace!("add_record >>> wallet_handle: {:?}, type_: {:?}, id: {:?}, value: {:?}, tags: {:?}", wallet_handle, type_, id, value, tags);
+                  tags: Option<&Tags>,
+                  options: &AddRecordOptions) -> IndyResult<()> {
+        trace!("add_record >>> wallet_handle: {:?}, type_: {:?}, id: {:?}, value: {:?}, tags: {:?}, options: {:?}", wallet_handle, type_, id, value, tags, options);
 
         self._check_type(type_)?;
 
-        self.wallet_service.add_record(wallet_handle, type_, id, value, tags.unwrap_or(&Tags::new()))?;
+        self.wallet_service.add_record_with_options(wallet_handle, type_, id, value, tags.unwrap_or(&Tags::new()), options)?;
 
         trace!("add_record <<< res: ()");
 
@@ -137,12 +162,13 @@ impl NonSecretsCommandExecutor {
                            wallet_handle: WalletHandle,
                            type_: &str,
                            id: &str,
-                           value: &str) -> IndyResult<()> {
-        trace!("update_record_value >>> wallet_handle: {:?}, type_: {:?}, id: {:?}, value: {:?}", wallet_handle, type_, id, value);
+                           value: &str,
+                           options: &UpdateRecordOptions) -> IndyResult<()> {
+        trace!("update_record_value >>> wallet_handle: {:?}, type_: {:?}, id: {:?}, value: {:?}, options: {:?}", wallet_handle, type_, id, value, options);
 
         self._check_type(type_)?;
 
-        self.wallet_service.update_record_value(wallet_handle, type_, id, value)?;
+        self.wallet_service.update_record_value_with_options(wallet_handle, type_, id, value, options)?;
 
         trace!("update_record_value <<< res: ()");
 
@@ -215,6 +241,25 @@ impl NonSecretsCommandExecutor {
         Ok(())
     }
 
+    fn batch_execute(&self,
+                     wallet_handle: WalletHandle,
+                     operations_json: &str) -> IndyResult<()> {
+        trace!("batch_execute >>> wallet_handle: {:?}, operations_json: {:?}", wallet_handle, operations_json);
+
+        let operations: Vec<WalletOperation> = serde_json::from_str(operations_json)
+            .to_indy(IndyErrorKind::InvalidStructure, "Cannot deserialize operations")?;
+
+        for operation in operations.iter() {
+            self._check_type(operation.get_type())?;
+        }
+
+        self.wallet_service.batch_execute(wallet_handle, &operations)?;
+
+        trace!("batch_execute <<< res: ()");
+
+        Ok(())
+    }
+
     fn get_record(&self,
                   wallet_handle: WalletHandle,
                   type_: &str,
@@ -260,6 +305,21 @@ impl NonSecretsCommandExecutor {
         Ok(search_handle)
     }
 
+    fn open_all_records_search(&self,
+                               wallet_handle: WalletHandle) -> IndyResult<SearchHandle> {
+        trace!("open_all_records_search >>> wallet_handle: {:?}", wallet_handle);
+
+        let search = self.wallet_service.search_all_records(wallet_handle)?;
+
+        let search_handle = next_search_handle();
+
+        self.searches.borrow_mut().insert(search_handle, Box::new(search));
+
+        trace!("open_all_records_search <<< res: {:?}", search_handle);
+
+        Ok(search_handle)
+    }
+
     fn fetch_search_next_records(&self,
                                  wallet_handle: WalletHandle,
                                  wallet_search_handle: SearchHandle,
@@ -304,6 +364,25 @@ impl NonSecretsCommandExecutor {
         Ok(())
     }
 
+    fn aggregate(&self,
+                 wallet_handle: WalletHandle,
+                 type_: &str,
+                 query_json: &str,
+                 aggregation: &WalletAggregation) -> IndyResult<String> {
+        trace!("aggregate >>> wallet_handle: {:?}, type_: {:?}, query_json: {:?}, aggregation: {:?}", wallet_handle, type_, query_json, aggregation);
+
+        self._check_type(type_)?;
+
+        let result = self.wallet_service.aggregate_records(wallet_handle, type_, query_json, aggregation)?;
+
+        let res = serde_json::to_string(&result)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize WalletAggregationResult")?;
+
+        trace!("aggregate <<< res: {:?}", res);
+
+        Ok(res)
+    }
+
     fn _check_type(&self, type_: &str) -> IndyResult<()> {
         if type_.starts_with(WalletService::PREFIX) {
             return Err(err_msg(IndyErrorKind::WalletAccessFailed, format!("Record of type \"{}\" is not available for fetching", type_)));
@@ -1,16 +1,17 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::io::{Read, Write};
 use std::rc::Rc;
 
 use indy_api_types::wallet::*;
 use crate::commands::{Command, CommandExecutor};
-use indy_api_types::domain::wallet::{Config, Credentials, ExportConfig, KeyConfig};
+use indy_api_types::domain::wallet::{CheckWalletOptions, Config, Credentials, ExportConfig, KeyConfig, RekeyCredentials, StreamExportConfig, WalletSyncOptions};
 use indy_api_types::errors::prelude::*;
 use crate::services::crypto::CryptoService;
-use indy_wallet::{KeyDerivationData, WalletService, Metadata};
+use indy_wallet::{KeyDerivationData, WalletService, WalletStorageType, Metadata, WalletChangeCallback};
 use indy_utils::crypto::{chacha20poly1305_ietf, randombytes};
 use indy_utils::crypto::chacha20poly1305_ietf::Key as MasterKey;
-use indy_api_types::{WalletHandle, CallbackHandle};
+use indy_api_types::{WalletHandle, CallbackHandle, SubscriptionHandle};
 use rust_base58::ToBase58;
 
 type DeriveKeyResult<T> = IndyResult<T>;
@@ -42,6 +43,18 @@ pub enum WalletCommand {
                        WalletFetchSearchNextRecord, // fetch search next record
                        WalletFreeSearch, // free search
                        Box<dyn Fn(IndyResult<()>) + Send>),
+    RegisterNativeWalletType(String, // type_
+                             Box<dyn WalletStorageType + Send>, // storage type
+                             Box<dyn Fn(IndyResult<()>) + Send>),
+    RegisterWalletTransactionHooks(String, // type_
+                                   WalletBeginTransaction, // begin transaction
+                                   WalletCommitTransaction, // commit transaction
+                                   WalletRollbackTransaction, // rollback transaction
+                                   Box<dyn Fn(IndyResult<()>) + Send>),
+    RegisterWalletAggregation(String, // type_
+                              WalletAggregateRecords, // aggregate records
+                              WalletFreeAggregation, // free aggregation
+                              Box<dyn Fn(IndyResult<()>) + Send>),
     Create(Config, // config
            Credentials, // credentials
            Box<dyn Fn(IndyResult<()>) + Send>),
@@ -74,6 +87,15 @@ pub enum WalletCommand {
                    KeyDerivationData,
                    DeriveKeyResult<MasterKey>,
                    CallbackHandle),
+    ExportToStream(WalletHandle,
+                   StreamExportConfig, // export config
+                   Box<dyn Write + Send>, // writer
+                   Box<dyn Fn(IndyResult<()>) + Send>),
+    ExportToStreamContinue(WalletHandle,
+                           StreamExportConfig, // export config
+                           KeyDerivationData,
+                           DeriveKeyResult<MasterKey>,
+                           CallbackHandle),
     Import(Config, // config
            Credentials, // credentials
            ExportConfig, // import config
@@ -84,6 +106,45 @@ pub enum WalletCommand {
                    WalletHandle,
                    CallbackHandle
     ),
+    ImportFromStream(Config, // config
+                     Credentials, // credentials
+                     StreamExportConfig, // import config
+                     Box<dyn Read + Send>, // reader
+                     Box<dyn Fn(IndyResult<()>) + Send>),
+    ImportInto(WalletHandle,
+               ExportConfig, // import config
+               Box<dyn Fn(IndyResult<()>) + Send>),
+    ImportIntoContinue(WalletHandle,
+                       DeriveKeyResult<MasterKey>, // derive_key_result
+                       CallbackHandle),
+    InspectExport(ExportConfig, // export config
+                  Box<dyn Fn(IndyResult<String>) + Send>),
+    Check(WalletHandle,
+          CheckWalletOptions, // options
+          Box<dyn Fn(IndyResult<String>) + Send>),
+    CreateReplica(WalletHandle,
+                  Config, // replica config
+                  Credentials, // replica credentials
+                  Box<dyn Fn(IndyResult<()>) + Send>),
+    Sync(WalletHandle, // source wallet
+         WalletHandle, // target wallet
+         WalletSyncOptions, // options
+         Box<dyn Fn(IndyResult<String>) + Send>),
+    Subscribe(WalletHandle,
+              String, // type
+              String, // query json
+              WalletChangeCallback, // notify
+              Box<dyn Fn(IndyResult<SubscriptionHandle>) + Send>),
+    Unsubscribe(SubscriptionHandle,
+                Box<dyn Fn(IndyResult<()>) + Send>),
+    Rekey(WalletHandle,
+          RekeyCredentials, // new credentials
+          Box<dyn Fn(u32, u32) + Send>, // progress
+          Box<dyn Fn(IndyResult<()>) + Send>),
+    RekeyContinue(WalletHandle,
+                  KeyDerivationData,
+                  DeriveKeyResult<MasterKey>,
+                  CallbackHandle),
     GenerateKey(Option<KeyConfig>, // config
                 Box<dyn Fn(IndyResult<String>) + Send>),
     DeriveKey(KeyDerivationData,
@@ -101,7 +162,9 @@ pub struct WalletCommandExecutor {
     wallet_service: Rc<WalletService>,
     crypto_service: Rc<CryptoService>,
     open_callbacks: RefCell<HashMap<WalletHandle, Box<dyn Fn(IndyResult<WalletHandle>) + Send>>>,
-    pending_callbacks: RefCell<HashMap<CallbackHandle, Box<dyn Fn(IndyResult<()>) + Send>>>
+    pending_callbacks: RefCell<HashMap<CallbackHandle, Box<dyn Fn(IndyResult<()>) + Send>>>,
+    rekey_progress_callbacks: RefCell<HashMap<CallbackHandle, Box<dyn Fn(u32, u32) + Send>>>,
+    pending_export_writers: RefCell<HashMap<CallbackHandle, Box<dyn Write + Send>>>
 }
 
 impl WalletCommandExecutor {
@@ -110,7 +173,9 @@ impl WalletCommandExecutor {
             wallet_service,
             crypto_service,
             open_callbacks: RefCell::new(HashMap::new()),
-            pending_callbacks: RefCell::new(HashMap::new())
+            pending_callbacks: RefCell::new(HashMap::new()),
+            rekey_progress_callbacks: RefCell::new(HashMap::new()),
+            pending_export_writers: RefCell::new(HashMap::new())
         }
     }
 
@@ -130,6 +195,18 @@ impl WalletCommandExecutor {
                                        free_storage_metadata, search_records, search_all_records, get_search_total_count,
                                        fetch_search_next_record, free_search));
             }
+            WalletCommand::RegisterNativeWalletType(type_, storage_type, cb) => {
+                debug!(target: "wallet_command_executor", "RegisterNativeWalletType command received");
+                cb(self._register_native_type(&type_, storage_type));
+            }
+            WalletCommand::RegisterWalletTransactionHooks(type_, begin_transaction, commit_transaction, rollback_transaction, cb) => {
+                debug!(target: "wallet_command_executor", "RegisterWalletTransactionHooks command received");
+                cb(self._register_transaction_hooks(&type_, begin_transaction, commit_transaction, rollback_transaction));
+            }
+            WalletCommand::RegisterWalletAggregation(type_, aggregate_records, free_aggregation, cb) => {
+                debug!(target: "wallet_command_executor", "RegisterWalletAggregation command received");
+                cb(self._register_aggregation(&type_, aggregate_records, free_aggregation));
+            }
             WalletCommand::Create(config, credentials, cb) => {
                 debug!(target: "wallet_command_executor", "Create command received");
                 self._create(&config, &credentials, cb)
@@ -166,6 +243,14 @@ impl WalletCommandExecutor {
                 debug!(target: "wallet_command_executor", "ExportContinue command received");
                 self._export_continue(cb_id, wallet_handle, &export_config, key_data, key_result)
             }
+            WalletCommand::ExportToStream(wallet_handle, export_config, writer, cb) => {
+                debug!(target: "wallet_command_executor", "ExportToStream command received");
+                self._export_to_stream(wallet_handle, &export_config, writer, cb)
+            }
+            WalletCommand::ExportToStreamContinue(wallet_handle, export_config, key_data, key_result, cb_id) => {
+                debug!(target: "wallet_command_executor", "ExportToStreamContinue command received");
+                self._export_to_stream_continue(cb_id, wallet_handle, &export_config, key_data, key_result)
+            }
             WalletCommand::Import(config, credentials, import_config, cb) => {
                 debug!(target: "wallet_command_executor", "Import command received");
                 self._import(&config, &credentials, &import_config, cb);
@@ -174,6 +259,50 @@ impl WalletCommandExecutor {
                 debug!(target: "wallet_command_executor", "ImportContinue command received");
                 self._import_continue(cb_id, wallet_handle, &config, &credential, key_result);
             }
+            WalletCommand::ImportFromStream(config, credentials, import_config, reader, cb) => {
+                debug!(target: "wallet_command_executor", "ImportFromStream command received");
+                self._import_from_stream(&config, &credentials, &import_config, reader, cb);
+            }
+            WalletCommand::ImportInto(wallet_handle, import_config, cb) => {
+                debug!(target: "wallet_command_executor", "ImportInto command received");
+                self._import_into(wallet_handle, &import_config, cb);
+            }
+            WalletCommand::ImportIntoContinue(wallet_handle, key_result, cb_id) => {
+                debug!(target: "wallet_command_executor", "ImportIntoContinue command received");
+                self._import_into_continue(cb_id, wallet_handle, key_result);
+            }
+            WalletCommand::InspectExport(export_config, cb) => {
+                debug!(target: "wallet_command_executor", "InspectExport command received");
+                self._inspect_export(export_config, cb);
+            }
+            WalletCommand::Check(wallet_handle, options, cb) => {
+                debug!(target: "wallet_command_executor", "Check command received");
+                cb(self._check(wallet_handle, &options));
+            }
+            WalletCommand::CreateReplica(wallet_handle, config, credentials, cb) => {
+                debug!(target: "wallet_command_executor", "CreateReplica command received");
+                cb(self._create_replica(wallet_handle, &config, &credentials));
+            }
+            WalletCommand::Sync(source_wallet_handle, target_wallet_handle, options, cb) => {
+                debug!(target: "wallet_command_executor", "Sync command received");
+                cb(self._sync(source_wallet_handle, target_wallet_handle, &options));
+            }
+            WalletCommand::Subscribe(wallet_handle, type_, query_json, notify_cb, cb) => {
+                debug!(target: "wallet_command_executor", "Subscribe command received");
+                cb(self._subscribe(wallet_handle, &type_, &query_json, notify_cb));
+            }
+            WalletCommand::Unsubscribe(subscription_handle, cb) => {
+                debug!(target: "wallet_command_executor", "Unsubscribe command received");
+                cb(self._unsubscribe(subscription_handle));
+            }
+            WalletCommand::Rekey(wallet_handle, new_credentials, progress_cb, cb) => {
+                debug!(target: "wallet_command_executor", "Rekey command received");
+                self._rekey(wallet_handle, &new_credentials, progress_cb, cb);
+            }
+            WalletCommand::RekeyContinue(wallet_handle, key_data, key_result, cb_id) => {
+                debug!(target: "wallet_command_executor", "RekeyContinue command received");
+                self._rekey_continue(cb_id, wallet_handle, key_data, key_result);
+            }
             WalletCommand::GenerateKey(config, cb) => {
                 debug!(target: "wallet_command_executor", "DeriveKey command received");
                 cb(self._generate_key(config.as_ref()));
@@ -226,6 +355,46 @@ impl WalletCommandExecutor {
         Ok(())
     }
 
+    fn _register_native_type(&self, type_: &str, storage_type: Box<dyn WalletStorageType + Send>) -> IndyResult<()> {
+        trace!("_register_native_type >>> type_: {:?}", type_);
+
+        self
+            .wallet_service
+            .register_native_wallet_storage(type_, storage_type)?;
+
+        trace!("_register_native_type <<< res: ()");
+        Ok(())
+    }
+
+    fn _register_transaction_hooks(&self,
+                                   type_: &str,
+                                   begin_transaction: WalletBeginTransaction,
+                                   commit_transaction: WalletCommitTransaction,
+                                   rollback_transaction: WalletRollbackTransaction) -> IndyResult<()> {
+        trace!("_register_transaction_hooks >>> type_: {:?}", type_);
+
+        self
+            .wallet_service
+            .register_wallet_storage_transaction_hooks(type_, begin_transaction, commit_transaction, rollback_transaction)?;
+
+        trace!("_register_transaction_hooks <<< res: ()");
+        Ok(())
+    }
+
+    fn _register_aggregation(&self,
+                             type_: &str,
+                             aggregate_records: WalletAggregateRecords,
+                             free_aggregation: WalletFreeAggregation) -> IndyResult<()> {
+        trace!("_register_aggregation >>> type_: {:?}", type_);
+
+        self
+            .wallet_service
+            .register_wallet_storage_aggregation(type_, aggregate_records, free_aggregation)?;
+
+        trace!("_register_aggregation <<< res: ()");
+        Ok(())
+    }
+
     fn _create(&self,
                config: &Config,
                credentials: &Credentials,
@@ -429,6 +598,228 @@ impl WalletCommandExecutor {
             .and_then(|key| self.wallet_service.export_wallet(wallet_handle, export_config, 0, (&key_data,& key)))) // TODO - later add proper versioning
     }
 
+    fn _export_to_stream(&self,
+                         wallet_handle: WalletHandle,
+                         export_config: &StreamExportConfig,
+                         writer: Box<dyn Write + Send>,
+                         cb: Box<dyn Fn(IndyResult<()>) + Send>) {
+        trace!("_export_to_stream >>> handle: {:?}, export_config: {:?}", wallet_handle, secret!(export_config));
+
+        try_cb!(self.wallet_service.check(wallet_handle), cb);
+
+        let key_data = KeyDerivationData::from_passphrase_with_new_salt(&export_config.key, &export_config.key_derivation_method);
+
+        let cb_id = indy_utils::sequence::get_next_id();
+        self.pending_callbacks.borrow_mut().insert(cb_id, cb);
+        self.pending_export_writers.borrow_mut().insert(cb_id, writer);
+
+        let export_config = export_config.clone();
+
+        CommandExecutor::instance().send(
+            Command::Wallet(WalletCommand::DeriveKey(
+                key_data.clone(),
+                Box::new(move |master_key_res| {
+                    CommandExecutor::instance().send(Command::Wallet(WalletCommand::ExportToStreamContinue(
+                        wallet_handle,
+                        export_config.clone(),
+                        key_data.clone(),
+                        master_key_res,
+                        cb_id,
+                    ))).unwrap();
+                })
+            ))
+        ).unwrap();
+
+        trace!("_export_to_stream <<<");
+    }
+
+    fn _export_to_stream_continue(&self,
+                                  cb_id: CallbackHandle,
+                                  wallet_handle: WalletHandle,
+                                  export_config: &StreamExportConfig,
+                                  key_data: KeyDerivationData,
+                                  key_result: DeriveKeyResult<MasterKey>) {
+        let cb = get_cb!(self, cb_id);
+        let mut writer = match self.pending_export_writers.borrow_mut().remove(&cb_id) {
+            Some(writer) => writer,
+            None => return cb(Err(err_msg(IndyErrorKind::InvalidState, "Export writer not found")))
+        };
+
+        cb(key_result
+            .and_then(|key| self.wallet_service.export_wallet_to_stream(wallet_handle, &mut writer, export_config, 0, (&key_data, &key)))) // TODO - later add proper versioning
+    }
+
+    fn _import_into(&self,
+                    wallet_handle: WalletHandle,
+                    import_config: &ExportConfig,
+                    cb: Box<dyn Fn(IndyResult<()>) + Send>) {
+        trace!("_import_into >>> handle: {:?}, import_config: {:?}", wallet_handle, secret!(import_config));
+
+        let import_key_data = try_cb!(self.wallet_service.import_into_wallet_prepare(wallet_handle, import_config), cb);
+
+        let cb_id: CallbackHandle = indy_utils::sequence::get_next_id();
+        self.pending_callbacks.borrow_mut().insert(cb_id, cb);
+
+        CommandExecutor::instance().send(
+            Command::Wallet(WalletCommand::DeriveKey(
+                import_key_data,
+                Box::new(move |import_key_result| {
+                    CommandExecutor::instance().send(Command::Wallet(WalletCommand::ImportIntoContinue(
+                        wallet_handle,
+                        import_key_result,
+                        cb_id,
+                    ))).unwrap();
+                })
+            ))
+        ).unwrap();
+
+        trace!("_import_into <<<");
+    }
+
+    fn _import_into_continue(&self,
+                             cb_id: CallbackHandle,
+                             wallet_handle: WalletHandle,
+                             key_result: DeriveKeyResult<MasterKey>) {
+        let cb = get_cb!(self, cb_id);
+        cb(self.wallet_service.import_into_wallet_continue(wallet_handle, key_result))
+    }
+
+    fn _inspect_export(&self,
+                       export_config: ExportConfig,
+                       cb: Box<dyn Fn(IndyResult<String>) + Send>) {
+        trace!("_inspect_export >>> export_config: {:?}", secret!(&export_config));
+
+        // Export verification requires key derivation and reading of the whole file, so it is done in thread pool
+        crate::commands::THREADPOOL.lock().unwrap().execute(move || {
+            let res = WalletService::inspect_export(&export_config)
+                .and_then(|summary| serde_json::to_string(&summary)
+                    .to_indy(IndyErrorKind::InvalidState, "Cannot serialize ExportSummary"));
+
+            cb(res)
+        });
+
+        trace!("_inspect_export <<<");
+    }
+
+    fn _check(&self,
+              wallet_handle: WalletHandle,
+              options: &CheckWalletOptions) -> IndyResult<String> {
+        trace!("_check >>> handle: {:?}, options: {:?}", wallet_handle, options);
+
+        let report = self.wallet_service.check_wallet(wallet_handle, options)?;
+
+        let res = serde_json::to_string(&report)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize WalletCheckReport")?;
+
+        trace!("_check <<< res: {:?}", res);
+
+        Ok(res)
+    }
+
+    fn _create_replica(&self,
+                       wallet_handle: WalletHandle,
+                       config: &Config,
+                       credentials: &Credentials) -> IndyResult<()> {
+        trace!("_create_replica >>> handle: {:?}, config: {:?}, credentials: {:?}", wallet_handle, config, secret!(credentials));
+
+        self.wallet_service.create_wallet_replica(wallet_handle, config, credentials)?;
+
+        trace!("_create_replica <<<");
+
+        Ok(())
+    }
+
+    fn _sync(&self,
+             source_wallet_handle: WalletHandle,
+             target_wallet_handle: WalletHandle,
+             options: &WalletSyncOptions) -> IndyResult<String> {
+        trace!("_sync >>> source_handle: {:?}, target_handle: {:?}, options: {:?}", source_wallet_handle, target_wallet_handle, options);
+
+        let result = self.wallet_service.sync_wallet(source_wallet_handle, target_wallet_handle, options)?;
+
+        let res = serde_json::to_string(&result)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize WalletSyncResult")?;
+
+        trace!("_sync <<< res: {:?}", res);
+
+        Ok(res)
+    }
+
+    fn _subscribe(&self,
+                  wallet_handle: WalletHandle,
+                  type_: &str,
+                  query_json: &str,
+                  notify_cb: WalletChangeCallback) -> IndyResult<SubscriptionHandle> {
+        trace!("_subscribe >>> handle: {:?}, type_: {:?}, query_json: {:?}", wallet_handle, type_, query_json);
+
+        let res = self.wallet_service.subscribe(wallet_handle, type_, query_json, notify_cb)?;
+
+        trace!("_subscribe <<< res: {:?}", res);
+
+        Ok(res)
+    }
+
+    fn _unsubscribe(&self,
+                    subscription_handle: SubscriptionHandle) -> IndyResult<()> {
+        trace!("_unsubscribe >>> subscription_handle: {:?}", subscription_handle);
+
+        self.wallet_service.unsubscribe(subscription_handle)?;
+
+        trace!("_unsubscribe <<<");
+
+        Ok(())
+    }
+
+    fn _rekey(&self,
+              wallet_handle: WalletHandle,
+              new_credentials: &RekeyCredentials,
+              progress_cb: Box<dyn Fn(u32, u32) + Send>,
+              cb: Box<dyn Fn(IndyResult<()>) + Send>) {
+        trace!("_rekey >>> handle: {:?}, new_credentials: {:?}", wallet_handle, secret!(new_credentials));
+
+        let key_data = try_cb!(self.wallet_service.rekey_wallet_prepare(wallet_handle, new_credentials), cb);
+
+        let cb_id: CallbackHandle = indy_utils::sequence::get_next_id();
+        self.pending_callbacks.borrow_mut().insert(cb_id, cb);
+        self.rekey_progress_callbacks.borrow_mut().insert(cb_id, progress_cb);
+
+        CommandExecutor::instance().send(
+            Command::Wallet(WalletCommand::DeriveKey(
+                key_data.clone(),
+                Box::new(move |master_key_res| {
+                    CommandExecutor::instance().send(Command::Wallet(WalletCommand::RekeyContinue(
+                        wallet_handle,
+                        key_data.clone(),
+                        master_key_res,
+                        cb_id,
+                    ))).unwrap();
+                })
+            ))
+        ).unwrap();
+
+        trace!("_rekey <<<");
+    }
+
+    fn _rekey_continue(&self,
+                       cb_id: CallbackHandle,
+                       wallet_handle: WalletHandle,
+                       key_data: KeyDerivationData,
+                       key_result: DeriveKeyResult<MasterKey>) {
+        let cb = get_cb!(self, cb_id);
+        let progress_cb = self.rekey_progress_callbacks.borrow_mut().remove(&cb_id);
+
+        let mut report_progress = |processed: usize, total: usize| {
+            if let Some(ref progress_cb) = progress_cb {
+                progress_cb(processed as u32, total as u32)
+            }
+        };
+
+        let res = key_result
+            .and_then(|key| self.wallet_service.rekey_wallet_continue(wallet_handle, (&key_data, &key), &mut report_progress));
+
+        cb(res)
+    }
+
     fn _import(&self,
                config: &Config,
                credentials: &Credentials,
@@ -439,6 +830,34 @@ impl WalletCommandExecutor {
 
         let (wallet_handle, key_data, import_key_data) = try_cb!(self.wallet_service.import_wallet_prepare(&config, &credentials, &import_config), cb);
 
+        self._import_derive_keys(config, credentials, wallet_handle, key_data, import_key_data, cb);
+
+        trace!("_import <<<");
+    }
+
+    fn _import_from_stream(&self,
+                           config: &Config,
+                           credentials: &Credentials,
+                           import_config: &StreamExportConfig,
+                           reader: Box<dyn Read + Send>,
+                           cb: Box<dyn Fn(IndyResult<()>) + Send>) {
+        trace!("_import_from_stream >>> config: {:?}, credentials: {:?}, import_config: {:?}",
+               config, secret!(credentials), secret!(import_config));
+
+        let (wallet_handle, key_data, import_key_data) = try_cb!(self.wallet_service.import_wallet_from_stream_prepare(&config, &credentials, reader, &import_config), cb);
+
+        self._import_derive_keys(config, credentials, wallet_handle, key_data, import_key_data, cb);
+
+        trace!("_import_from_stream <<<");
+    }
+
+    fn _import_derive_keys(&self,
+                           config: &Config,
+                           credentials: &Credentials,
+                           wallet_handle: WalletHandle,
+                           key_data: KeyDerivationData,
+                           import_key_data: KeyDerivationData,
+                           cb: Box<dyn Fn(IndyResult<()>) + Send>) {
         let cb_id : CallbackHandle = indy_utils::sequence::get_next_id();
         self.pending_callbacks.borrow_mut().insert(cb_id, cb);
 
@@ -470,8 +889,6 @@ impl WalletCommandExecutor {
                 }),
             ))
         ).unwrap();
-
-        trace!("_import <<<");
     }
 
     fn _import_continue(&self,
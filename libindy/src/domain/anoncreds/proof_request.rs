@@ -300,6 +300,10 @@ fn _process_operator(restriction_op: &Query, version: &ProofRequestsVersion) ->
         Query::Like(ref tag_name, ref tag_value) => {
             _check_restriction(tag_name, tag_value, version)
         }
+        Query::ILike(_, _) | Query::Regex(_, _) | Query::Exists(_, _) => {
+            Err("Proof Request validation failed: $ilike, $regex and $exists operators are supported by wallet search only \
+                 and can not be used in restrictions.".to_string())
+        }
         Query::In(ref tag_name, ref tag_values) => {
             tag_values
                 .iter()
@@ -370,6 +374,42 @@ mod tests {
         }
     }
 
+    mod restrictions {
+        use super::*;
+
+        fn _proof_request(restrictions: Query) -> ProofRequest {
+            let mut requested_attributes: HashMap<String, AttributeInfo> = HashMap::new();
+            requested_attributes.insert("attr1_referent".to_string(), AttributeInfo {
+                name: Some("name".to_string()),
+                names: None,
+                restrictions: Some(restrictions),
+                non_revoked: None,
+            });
+
+            ProofRequest::ProofRequestV2(ProofRequestPayload {
+                nonce: Nonce::new().unwrap(),
+                name: "proof_request".to_string(),
+                version: "1.0".to_string(),
+                requested_attributes,
+                requested_predicates: HashMap::new(),
+                non_revoked: None,
+            })
+        }
+
+        #[test]
+        fn proof_request_validate_works_for_eq_restriction() {
+            _proof_request(Query::Eq("attr::name::value".to_string(), "Alex".to_string())).validate().unwrap();
+        }
+
+        #[test]
+        fn proof_request_validate_not_works_for_wallet_only_operators() {
+            _proof_request(Query::ILike("attr::name::value".to_string(), "alex".to_string())).validate().unwrap_err();
+            _proof_request(Query::Regex("attr::name::value".to_string(), "^A".to_string())).validate().unwrap_err();
+            _proof_request(Query::Exists("attr::name::value".to_string(), true)).validate().unwrap_err();
+            _proof_request(Query::Not(Box::new(Query::Exists("attr::name::value".to_string(), false)))).validate().unwrap_err();
+        }
+    }
+
     mod to_unqualified {
         use super::*;
 
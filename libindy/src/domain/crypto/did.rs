@@ -4,6 +4,8 @@ use regex::Regex;
 use rust_base58::FromBase58;
 
 use indy_api_types::validation::Validatable;
+use crate::domain::crypto::key::MnemonicInfo;
+use crate::utils::crypto::did_key::PEER_METHOD;
 use crate::utils::qualifier;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
@@ -28,6 +30,9 @@ pub struct MyDidInfo {
     pub crypto_type: Option<String>,
     pub cid: Option<bool>,
     pub method_name: Option<DidMethod>,
+    pub peer_numalgo: Option<u8>,
+    pub custody: Option<String>,
+    pub mnemonic: Option<MnemonicInfo>,
 }
 
 impl Validatable for MyDidInfo {
@@ -38,6 +43,14 @@ impl Validatable for MyDidInfo {
         if let Some(ref name) = self.method_name {
             name.validate()?
         }
+        if let Some(numalgo) = self.peer_numalgo {
+            if self.method_name.as_ref().map(|name| name.0.as_str()) != Some(PEER_METHOD) {
+                return Err(format!("peer_numalgo can be set only for `{}` DID method", PEER_METHOD));
+            }
+            if numalgo != 0 && numalgo != 2 {
+                return Err(format!("Unsupported did:peer numalgo: {}. Only 0 and 2 are supported.", numalgo));
+            }
+        }
         Ok(())
     }
 }
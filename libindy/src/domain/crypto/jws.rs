@@ -0,0 +1,28 @@
+use serde_json::{Map, Value};
+
+pub type JWSHeader = Map<String, Value>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct JWSSignature {
+    pub protected: String,
+    pub signature: String
+}
+
+// JSON serialization of JWS. Payload is omitted for detached content
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum JWSJson {
+    General {
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        payload: Option<String>,
+        signatures: Vec<JWSSignature>
+    },
+    Flattened {
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        payload: Option<String>,
+        protected: String,
+        signature: String
+    },
+}
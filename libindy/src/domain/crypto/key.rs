@@ -15,6 +15,9 @@ pub struct Key {
     pub signkey: String,
     #[cfg(test)]
     pub signkey: String,
+    // Set for keys kept in key custody. Signkey is empty in this case
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custody: Option<KeyCustodyRef>,
 }
 
 impl Key {
@@ -22,8 +25,23 @@ impl Key {
         Key {
             verkey,
             signkey,
+            custody: None,
         }
     }
+
+    pub fn in_custody(verkey: String, custody: KeyCustodyRef) -> Key {
+        Key {
+            verkey,
+            signkey: String::new(),
+            custody: Some(custody),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeyCustodyRef {
+    pub custody: String,
+    pub key_ref: String,
 }
 
 impl Zeroize for Key {
@@ -42,6 +60,21 @@ impl Drop for Key {
 pub struct KeyInfo {
     pub seed: Option<String>,
     pub crypto_type: Option<String>,
+    pub custody: Option<String>,
+    pub mnemonic: Option<MnemonicInfo>,
+}
+
+// BIP-39 mnemonic and SLIP-0010 path that ed25519 key seed is derived by
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MnemonicInfo {
+    pub phrase: String,
+    pub passphrase: Option<String>,
+    pub derivation_path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MnemonicConfig {
+    pub word_count: Option<usize>,
 }
 
 #[derive(Serialize, Deserialize, Debug, NamedType)]
@@ -1,6 +1,10 @@
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct JWE {
     pub protected: String,
+    // Only DIDComm v2 envelopes keep recipients outside of protected header
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipients: Option<Vec<JWERecipient>>,
     pub iv: String,
     pub ciphertext: String,
     pub tag: String
@@ -38,4 +42,57 @@ pub struct UnpackMessage {
     pub recipient_verkey: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sender_verkey: Option<String>
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct JWERecipient {
+    pub encrypted_key: String,
+    pub header: JWERecipientHeader
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct JWERecipientHeader {
+    pub kid: String
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct JWEProtected {
+    pub typ: String,
+    pub alg: String,
+    pub enc: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skid: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apu: Option<String>,
+    pub apv: String,
+    pub epk: EphemeralPublicKey
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct EphemeralPublicKey {
+    pub kty: String,
+    pub crv: String,
+    pub x: String
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PackFormat {
+    #[serde(rename = "legacy")]
+    Legacy,
+    #[serde(rename = "didcomm_v2")]
+    DIDCommV2,
+}
+
+impl Default for PackFormat {
+    fn default() -> Self {
+        PackFormat::Legacy
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct PackMessageOptions {
+    #[serde(default)]
+    pub format: PackFormat
+}
@@ -0,0 +1,93 @@
+use serde_json::Value;
+
+pub const DID_CONTEXT: &str = "https://www.w3.org/ns/did/v1";
+pub const ED25519_2018_CONTEXT: &str = "https://w3id.org/security/suites/ed25519-2018/v1";
+pub const X25519_2019_CONTEXT: &str = "https://w3id.org/security/suites/x25519-2019/v1";
+pub const DID_LD_JSON_CONTENT_TYPE: &str = "application/did+ld+json";
+
+// Method used in DID Document for DIDs that are not fully qualified
+pub const DEFAULT_DID_METHOD: &str = "sov";
+
+// Names of ATTRIB transactions that extend DID Document
+pub const ENDPOINT_ATTRIB: &str = "endpoint";
+pub const DIDDOC_CONTENT_ATTRIB: &str = "diddocContent";
+
+pub const VERKEY_FRAGMENT: &str = "verkey";
+pub const KEY_AGREEMENT_FRAGMENT: &str = "key-agreement";
+pub const DEFAULT_SERVICE_TYPE: &str = "endpoint";
+
+pub const ED25519_2018_TYPE: &str = "Ed25519VerificationKey2018";
+pub const X25519_2019_TYPE: &str = "X25519KeyAgreementKey2019";
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocument {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    pub verification_method: Vec<VerificationMethod>,
+    pub authentication: Vec<String>,
+    pub assertion_method: Vec<String>,
+    pub key_agreement: Vec<String>,
+    pub service: Vec<Service>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMethod {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub controller: String,
+    pub public_key_base58: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Service {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub service_endpoint: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub routing_keys: Vec<String>,
+}
+
+/// Value of `endpoint` ATTRIB. Legacy `ha` form and `endpoint` form with optional service types are supported.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointAttrib {
+    pub ha: Option<String>,
+    pub endpoint: Option<String>,
+    pub types: Option<Vec<String>>,
+    pub routing_keys: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DidResolutionResult {
+    pub did_document: Value,
+    pub did_document_metadata: DidDocumentMetadata,
+    pub did_resolution_metadata: DidResolutionMetadata,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocumentMetadata {
+    // Sequence number and time of NYM transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq_no: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txn_time: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DidResolutionMetadata {
+    pub content_type: String,
+    // Timestamp of ledger state signed by the pool that NYM reply was verified against
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_txn_time: Option<u64>,
+}
@@ -5,6 +5,7 @@ pub mod schema;
 pub mod cred_def;
 pub mod node;
 pub mod ddo;
+pub mod did_doc;
 pub mod txn;
 pub mod pool;
 pub mod rev_reg_def;
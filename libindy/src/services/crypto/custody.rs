@@ -0,0 +1,112 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use indy_api_types::errors::prelude::*;
+use indy_utils::crypto::ed25519_box;
+use indy_utils::crypto::ed25519_sign;
+use indy_utils::crypto::sealedbox;
+use rust_base58::{FromBase58, ToBase58};
+
+/// Custody that keeps ed25519 secret keys outside of the wallet (PKCS#11 token, OS keystore, remote signer).
+///
+/// Wallet stores only custody specific key reference returned by `create_key` and
+/// all operations that need secret key are delegated to the custody by this reference.
+pub trait KeyCustody {
+    /// Generates new key pair inside of custody. Returns verkey and key reference.
+    fn create_key(&self, seed: Option<&ed25519_sign::Seed>) -> IndyResult<(ed25519_sign::PublicKey, String)>;
+    fn sign(&self, key_ref: &str, doc: &[u8]) -> IndyResult<ed25519_sign::Signature>;
+    fn crypto_box(&self, key_ref: &str, their_vk: &ed25519_sign::PublicKey, doc: &[u8], nonce: &ed25519_box::Nonce) -> IndyResult<Vec<u8>>;
+    fn crypto_box_open(&self, key_ref: &str, their_vk: &ed25519_sign::PublicKey, doc: &[u8], nonce: &ed25519_box::Nonce) -> IndyResult<Vec<u8>>;
+    fn crypto_box_seal_open(&self, key_ref: &str, my_vk: &ed25519_sign::PublicKey, doc: &[u8]) -> IndyResult<Vec<u8>>;
+
+    /// Computes X25519 shared secret between custody key and the given X25519 public key.
+    /// Custodies that can't export raw shared secrets keep the default implementation.
+    fn key_agreement(&self, _key_ref: &str, _their_pk: &ed25519_box::PublicKey) -> IndyResult<Vec<u8>> {
+        Err(err_msg(IndyErrorKind::UnknownCrypto, "Key agreement is not supported by this key custody"))
+    }
+}
+
+/// Software reference custody that keeps every secret key in a separate file of the given directory.
+/// Key reference is the file name. Intended for testing of custody integrations only.
+pub struct FileKeyCustody {
+    path: PathBuf,
+}
+
+impl FileKeyCustody {
+    pub fn new(path: PathBuf) -> FileKeyCustody {
+        FileKeyCustody { path }
+    }
+
+    fn _key_path(&self, key_ref: &str) -> IndyResult<PathBuf> {
+        if key_ref.is_empty() || !key_ref.chars().all(char::is_alphanumeric) {
+            return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Invalid key reference: {}", key_ref)));
+        }
+
+        Ok(self.path.join(key_ref))
+    }
+
+    fn _read_key(&self, key_ref: &str) -> IndyResult<ed25519_sign::SecretKey> {
+        let mut sk = String::new();
+
+        fs::File::open(self._key_path(key_ref)?)
+            .and_then(|mut file| file.read_to_string(&mut sk))
+            .to_indy(IndyErrorKind::IOError, format!("Can't read key {} from custody", key_ref))?;
+
+        ed25519_sign::SecretKey::from_slice(&sk.from_base58()?)
+    }
+
+    fn _write_key(&self, key_ref: &str, sk: &ed25519_sign::SecretKey) -> IndyResult<()> {
+        fs::create_dir_all(&self.path)
+            .to_indy(IndyErrorKind::IOError, "Can't create key custody directory")?;
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        options.open(self._key_path(key_ref)?)
+            .and_then(|mut file| file.write_all(sk[..].to_base58().as_bytes()))
+            .to_indy(IndyErrorKind::IOError, format!("Can't write key {} to custody", key_ref))
+    }
+}
+
+impl KeyCustody for FileKeyCustody {
+    fn create_key(&self, seed: Option<&ed25519_sign::Seed>) -> IndyResult<(ed25519_sign::PublicKey, String)> {
+        let (vk, sk) = ed25519_sign::create_key_pair_for_signature(seed)?;
+        let key_ref = vk[..].to_base58();
+
+        if !self._key_path(&key_ref)?.exists() {
+            self._write_key(&key_ref, &sk)?;
+        }
+
+        Ok((vk, key_ref))
+    }
+
+    fn sign(&self, key_ref: &str, doc: &[u8]) -> IndyResult<ed25519_sign::Signature> {
+        ed25519_sign::sign(&self._read_key(key_ref)?, doc)
+    }
+
+    fn crypto_box(&self, key_ref: &str, their_vk: &ed25519_sign::PublicKey, doc: &[u8], nonce: &ed25519_box::Nonce) -> IndyResult<Vec<u8>> {
+        ed25519_box::encrypt(&ed25519_sign::sk_to_curve25519(&self._read_key(key_ref)?)?,
+                             &ed25519_sign::vk_to_curve25519(their_vk)?, doc, nonce)
+    }
+
+    fn crypto_box_open(&self, key_ref: &str, their_vk: &ed25519_sign::PublicKey, doc: &[u8], nonce: &ed25519_box::Nonce) -> IndyResult<Vec<u8>> {
+        ed25519_box::decrypt(&ed25519_sign::sk_to_curve25519(&self._read_key(key_ref)?)?,
+                             &ed25519_sign::vk_to_curve25519(their_vk)?, doc, nonce)
+    }
+
+    fn crypto_box_seal_open(&self, key_ref: &str, my_vk: &ed25519_sign::PublicKey, doc: &[u8]) -> IndyResult<Vec<u8>> {
+        sealedbox::decrypt(&ed25519_sign::vk_to_curve25519(my_vk)?,
+                           &ed25519_sign::sk_to_curve25519(&self._read_key(key_ref)?)?, doc)
+    }
+
+    fn key_agreement(&self, key_ref: &str, their_pk: &ed25519_box::PublicKey) -> IndyResult<Vec<u8>> {
+        ed25519_box::scalarmult(&ed25519_sign::sk_to_curve25519(&self._read_key(key_ref)?)?, their_pk)
+    }
+}
@@ -0,0 +1,83 @@
+use indy_api_types::errors::prelude::*;
+use super::CryptoType;
+use indy_utils::crypto::ecdsa;
+use indy_utils::crypto::ed25519_box;
+use indy_utils::crypto::ed25519_sign;
+
+pub const SECP256K1_CRYPTO_TYPE: &str = "secp256k1";
+pub const P256_CRYPTO_TYPE: &str = "p256";
+
+/// ECDSA over SHA-256 digest. Supports only signing as there is no authenticated encryption defined for these curves.
+pub struct ECDSACryptoType {
+    name: &'static str,
+    curve: ecdsa::Curve,
+}
+
+impl ECDSACryptoType {
+    pub fn secp256k1() -> ECDSACryptoType {
+        ECDSACryptoType { name: SECP256K1_CRYPTO_TYPE, curve: ecdsa::Curve::Secp256k1 }
+    }
+
+    pub fn p256() -> ECDSACryptoType {
+        ECDSACryptoType { name: P256_CRYPTO_TYPE, curve: ecdsa::Curve::P256 }
+    }
+
+    fn _unsupported<T>(&self, operation: &str) -> IndyResult<T> {
+        Err(err_msg(IndyErrorKind::UnknownCrypto,
+                    format!("{} is not supported for {} keys. Only signing is available for this crypto type", operation, self.name)))
+    }
+}
+
+impl CryptoType for ECDSACryptoType {
+    fn crypto_box(&self, _sk: &[u8], _vk: &[u8], _doc: &[u8], _nonce: &ed25519_box::Nonce) -> IndyResult<Vec<u8>> {
+        self._unsupported("Authenticated encryption")
+    }
+
+    fn crypto_box_open(&self, _sk: &[u8], _vk: &[u8], _doc: &[u8], _nonce: &ed25519_box::Nonce) -> IndyResult<Vec<u8>> {
+        self._unsupported("Authenticated decryption")
+    }
+
+    fn gen_nonce(&self) -> ed25519_box::Nonce {
+        ed25519_box::gen_nonce()
+    }
+
+    fn create_key(&self, seed: Option<&ed25519_sign::Seed>) -> IndyResult<(Vec<u8>, Vec<u8>)> {
+        let (vk, sk) = ecdsa::create_key_pair(self.curve, seed.map(|seed| &seed[..]))?;
+        Ok((vk, sk[..].to_vec()))
+    }
+
+    fn validate_key(&self, vk: &[u8]) -> IndyResult<()> {
+        ecdsa::validate_key(self.curve, vk)
+    }
+
+    fn sign(&self, sk: &[u8], doc: &[u8]) -> IndyResult<Vec<u8>> {
+        ecdsa::sign(self.curve, &ecdsa::SecretKey::from_slice(sk)?, doc)
+    }
+
+    fn verify(&self, vk: &[u8], doc: &[u8], signature: &[u8]) -> IndyResult<bool> {
+        ecdsa::verify(self.curve, vk, doc, signature)
+    }
+
+    fn crypto_box_seal(&self, _vk: &[u8], _doc: &[u8]) -> IndyResult<Vec<u8>> {
+        self._unsupported("Anonymous encryption")
+    }
+
+    fn crypto_box_seal_open(&self, _vk: &[u8], _sk: &[u8], _doc: &[u8]) -> IndyResult<Vec<u8>> {
+        self._unsupported("Anonymous decryption")
+    }
+
+    fn x25519_public_key(&self, _vk: &[u8]) -> IndyResult<ed25519_box::PublicKey> {
+        self._unsupported("X25519 key agreement")
+    }
+
+    fn key_agreement(&self, _sk: &[u8], _pk: &ed25519_box::PublicKey) -> IndyResult<Vec<u8>> {
+        self._unsupported("X25519 key agreement")
+    }
+
+    fn jws_alg(&self) -> &'static str {
+        match self.curve {
+            ecdsa::Curve::Secp256k1 => "ES256K",
+            ecdsa::Curve::P256 => "ES256",
+        }
+    }
+}
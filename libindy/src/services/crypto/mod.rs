@@ -1,53 +1,88 @@
 extern crate hex;
 
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::str;
 
 use crate::domain::crypto::combo_box::ComboBox;
 use crate::domain::crypto::did::{Did, DidValue, MyDidInfo, TheirDid, TheirDidInfo};
-use crate::domain::crypto::key::{Key, KeyInfo};
+use crate::domain::crypto::key::{Key, KeyCustodyRef, KeyInfo, MnemonicInfo};
 use indy_api_types::errors::prelude::*;
 use indy_utils::crypto::base64;
 use indy_utils::crypto::ed25519_box;
 use indy_utils::crypto::chacha20poly1305_ietf;
 use indy_utils::crypto::chacha20poly1305_ietf::gen_nonce_and_encrypt_detached;
 use indy_utils::crypto::ed25519_sign;
+use indy_utils::crypto::mnemonic;
+use crate::utils::crypto::did_key::{build_did_key, build_did_peer, resolve_verkey, KEY_METHOD, PEER_METHOD};
 use crate::utils::crypto::verkey_builder::{build_full_verkey, split_verkey, verkey_get_cryptoname};
 
+use self::ecdsa::ECDSACryptoType;
 use self::ed25519::ED25519CryptoType;
 use self::hex::FromHex;
 use rust_base58::{FromBase58, ToBase58};
+use zeroize::Zeroize;
 
+pub use self::custody::{FileKeyCustody, KeyCustody};
+pub use self::ecdsa::{P256_CRYPTO_TYPE, SECP256K1_CRYPTO_TYPE};
+
+mod custody;
+mod ecdsa;
 mod ed25519;
 
 pub const DEFAULT_CRYPTO_TYPE: &str = "ed25519";
+pub const DEFAULT_MNEMONIC_WORD_COUNT: usize = 24;
 
 //TODO fix this crypto trait so it matches the functions below
 //TODO create a second crypto trait for additional functions
+// Keys and signatures are passed as raw bytes in crypto type specific encoding
 trait CryptoType {
-    fn crypto_box(&self, sk: &ed25519_sign::SecretKey, vk: &ed25519_sign::PublicKey, doc: &[u8], nonce: &ed25519_box::Nonce) -> IndyResult<Vec<u8>>;
-    fn crypto_box_open(&self, sk: &ed25519_sign::SecretKey, vk: &ed25519_sign::PublicKey, doc: &[u8], nonce: &ed25519_box::Nonce) -> IndyResult<Vec<u8>>;
+    fn crypto_box(&self, sk: &[u8], vk: &[u8], doc: &[u8], nonce: &ed25519_box::Nonce) -> IndyResult<Vec<u8>>;
+    fn crypto_box_open(&self, sk: &[u8], vk: &[u8], doc: &[u8], nonce: &ed25519_box::Nonce) -> IndyResult<Vec<u8>>;
     fn gen_nonce(&self) -> ed25519_box::Nonce;
-    fn create_key(&self, seed: Option<&ed25519_sign::Seed>) -> IndyResult<(ed25519_sign::PublicKey, ed25519_sign::SecretKey)>;
-    fn validate_key(&self, _vk: &ed25519_sign::PublicKey) -> IndyResult<()>;
-    fn sign(&self, sk: &ed25519_sign::SecretKey, doc: &[u8]) -> IndyResult<ed25519_sign::Signature>;
-    fn verify(&self, vk: &ed25519_sign::PublicKey, doc: &[u8], signature: &ed25519_sign::Signature) -> IndyResult<bool>;
-    fn crypto_box_seal(&self, vk: &ed25519_sign::PublicKey, doc: &[u8]) -> IndyResult<Vec<u8>>;
-    fn crypto_box_seal_open(&self, vk: &ed25519_sign::PublicKey, sk: &ed25519_sign::SecretKey, doc: &[u8]) -> IndyResult<Vec<u8>>;
+    fn create_key(&self, seed: Option<&ed25519_sign::Seed>) -> IndyResult<(Vec<u8>, Vec<u8>)>;
+    fn validate_key(&self, vk: &[u8]) -> IndyResult<()>;
+    fn sign(&self, sk: &[u8], doc: &[u8]) -> IndyResult<Vec<u8>>;
+    fn verify(&self, vk: &[u8], doc: &[u8], signature: &[u8]) -> IndyResult<bool>;
+    fn crypto_box_seal(&self, vk: &[u8], doc: &[u8]) -> IndyResult<Vec<u8>>;
+    fn crypto_box_seal_open(&self, vk: &[u8], sk: &[u8], doc: &[u8]) -> IndyResult<Vec<u8>>;
+    fn x25519_public_key(&self, vk: &[u8]) -> IndyResult<ed25519_box::PublicKey>;
+    fn key_agreement(&self, sk: &[u8], pk: &ed25519_box::PublicKey) -> IndyResult<Vec<u8>>;
+    fn jws_alg(&self) -> &'static str;
 }
 
 pub struct CryptoService {
-    crypto_types: HashMap<&'static str, Box<dyn CryptoType>>
+    crypto_types: HashMap<&'static str, Box<dyn CryptoType>>,
+    custodies: RefCell<HashMap<String, Box<dyn KeyCustody>>>,
 }
 
 impl CryptoService {
     pub fn new() -> CryptoService {
         let mut crypto_types: HashMap<&str, Box<dyn CryptoType>> = HashMap::new();
         crypto_types.insert(DEFAULT_CRYPTO_TYPE, Box::new(ED25519CryptoType::new()));
+        crypto_types.insert(SECP256K1_CRYPTO_TYPE, Box::new(ECDSACryptoType::secp256k1()));
+        crypto_types.insert(P256_CRYPTO_TYPE, Box::new(ECDSACryptoType::p256()));
 
         CryptoService {
-            crypto_types
+            crypto_types,
+            custodies: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn register_key_custody(&self, name: &str, custody: Box<dyn KeyCustody>) -> IndyResult<()> {
+        trace!("register_key_custody >>> name: {:?}", name);
+
+        let mut custodies = self.custodies.borrow_mut();
+
+        if custodies.contains_key(name) {
+            return Err(err_msg(IndyErrorKind::InvalidState, format!("Key custody is already registered: {}", name)));
         }
+
+        custodies.insert(name.to_string(), custody);
+
+        trace!("register_key_custody <<<");
+
+        Ok(())
     }
 
     pub fn create_key(&self, key_info: &KeyInfo) -> IndyResult<Key> {
@@ -62,18 +97,8 @@ impl CryptoService {
             return Err(err_msg(IndyErrorKind::UnknownCrypto, format!("KeyInfo contains unknown crypto: {}", crypto_type_name)));
         }
 
-        let crypto_type = self.crypto_types.get(crypto_type_name).unwrap();
-
-        let seed = self.convert_seed(key_info.seed.as_ref().map(String::as_ref))?;
-        let (vk, sk) = crypto_type.create_key(seed.as_ref())?;
-        let mut vk = vk[..].to_base58();
-        let sk = sk[..].to_base58();
-        if !crypto_type_name.eq(DEFAULT_CRYPTO_TYPE) {
-            // Use suffix with crypto type name to store crypto type inside of vk
-            vk = format!("{}:{}", vk, crypto_type_name);
-        }
-
-        let key = Key::new(vk, sk);
+        let seed = self._get_seed(key_info.seed.as_ref().map(String::as_ref), key_info.mnemonic.as_ref(), crypto_type_name)?;
+        let (_, key) = self._create_key(crypto_type_name, key_info.custody.as_ref().map(String::as_str), seed.as_ref())?;
 
         trace!("create_key <<< key: {:?}", key);
 
@@ -92,27 +117,33 @@ impl CryptoService {
             return Err(err_msg(IndyErrorKind::UnknownCrypto, format!("MyDidInfo contains unknown crypto: {}", crypto_type_name)));
         }
 
-        let crypto_type = self.crypto_types.get(crypto_type_name).unwrap();
+        let method_name = my_did_info.method_name.as_ref().map(|method| method.0.as_str());
+
+        // DIDs derived from verkey bytes and abbreviated verkeys are defined only for ed25519 keys
+        if my_did_info.did.is_none() && method_name != Some(KEY_METHOD) && method_name != Some(PEER_METHOD)
+            && crypto_type_name != DEFAULT_CRYPTO_TYPE {
+            return Err(err_msg(IndyErrorKind::UnknownCrypto,
+                               format!("DID can't be derived from {} key, use did:key or did:peer method or pass DID explicitly", crypto_type_name)));
+        }
+
+        let seed = self._get_seed(my_did_info.seed.as_ref().map(String::as_ref), my_did_info.mnemonic.as_ref(), crypto_type_name)?;
+        let (vk, key) = self._create_key(crypto_type_name, my_did_info.custody.as_ref().map(String::as_str), seed.as_ref())?;
 
-        let seed = self.convert_seed(my_did_info.seed.as_ref().map(String::as_ref))?;
-        let (vk, sk) = crypto_type.create_key(seed.as_ref())?;
         let did = match my_did_info.did {
             Some(ref did) => did.clone(),
+            _ if method_name == Some(KEY_METHOD) =>
+                build_did_key(&key.verkey)?,
+            _ if method_name == Some(PEER_METHOD) && my_did_info.peer_numalgo == Some(2) =>
+                build_did_peer(&key.verkey, Some(&self.x25519_public_key(&key.verkey)?[..]))?,
+            _ if method_name == Some(PEER_METHOD) =>
+                build_did_peer(&key.verkey, None)?,
             _ if my_did_info.cid == Some(true) =>
-                DidValue::new(&vk[..].to_vec().to_base58(), my_did_info.method_name.as_ref().map(|method| method.0.as_str())),
+                DidValue::new(&vk[..].to_vec().to_base58(), method_name),
             _ =>
-                DidValue::new(&vk[0..16].to_vec().to_base58(), my_did_info.method_name.as_ref().map(|method| method.0.as_str()))
+                DidValue::new(&vk[0..16].to_vec().to_base58(), method_name)
         };
 
-        let mut vk = vk[..].to_base58();
-        let sk = sk[..].to_base58();
-
-        if !crypto_type_name.eq(DEFAULT_CRYPTO_TYPE) {
-            // Use suffix with crypto type name to store crypto type inside of vk
-            vk = format!("{}:{}", vk, crypto_type_name);
-        }
-
-        let did = (Did::new(did, vk.clone()), Key::new(vk, sk));
+        let did = (Did::new(did, key.verkey.clone()), key);
 
         trace!("create_my_did <<< did: {:?}", did);
 
@@ -125,8 +156,20 @@ impl CryptoService {
         // Check did is correct Base58
         let _ = self.validate_did(&their_did_info.did)?;
 
-        let verkey = build_full_verkey(&their_did_info.did.to_unqualified().0,
-                                       their_did_info.verkey.as_ref().map(String::as_str))?;
+        // Key of did:key and did:peer DIDs is taken from DID itself, explicit verkey (including abbreviated one)
+        // is accepted only if it's exactly the same
+        let verkey = match (their_did_info.verkey.as_ref(), resolve_verkey(&their_did_info.did)?) {
+            (None, Some(resolved_verkey)) => resolved_verkey,
+            (Some(verkey), Some(resolved_verkey)) => {
+                if *verkey != resolved_verkey {
+                    return Err(err_msg(IndyErrorKind::InvalidStructure,
+                                       format!("Verkey {} doesn't match key of DID {}", verkey, their_did_info.did.0)));
+                }
+                resolved_verkey
+            }
+            (verkey, None) => build_full_verkey(&their_did_info.did.to_unqualified().0,
+                                                verkey.map(String::as_str))?
+        };
 
         self.validate_key(&verkey)?;
 
@@ -148,8 +191,15 @@ impl CryptoService {
 
         let crypto_type = self.crypto_types.get(crypto_type_name).unwrap();
 
-        let my_sk = ed25519_sign::SecretKey::from_slice(&my_key.signkey.as_str().from_base58()?.as_slice())?;
-        let signature = crypto_type.sign(&my_sk, doc)?[..].to_vec();
+        let signature = match my_key.custody {
+            Some(ref custody) => self._with_custody(&custody.custody, |key_custody| key_custody.sign(&custody.key_ref, doc))?[..].to_vec(),
+            None => {
+                let mut sk = my_key.signkey.as_str().from_base58()?;
+                let signature = crypto_type.sign(&sk, doc);
+                sk.zeroize();
+                signature?
+            }
+        };
 
         trace!("sign <<< signature: {:?}", signature);
 
@@ -167,10 +217,7 @@ impl CryptoService {
 
         let crypto_type = self.crypto_types.get(crypto_type_name).unwrap();
 
-        let their_vk = ed25519_sign::PublicKey::from_slice(&their_vk.from_base58()?)?;
-        let signature = ed25519_sign::Signature::from_slice(&signature)?;
-
-        let valid = crypto_type.verify(&their_vk, msg, &signature)?;
+        let valid = crypto_type.verify(&their_vk.from_base58()?, msg, signature)?;
 
         trace!("verify <<< valid: {:?}", valid);
 
@@ -214,11 +261,16 @@ impl CryptoService {
 
         let crypto_type = self.crypto_types.get(&crypto_type_name).unwrap();
 
-        let my_sk = ed25519_sign::SecretKey::from_slice(my_key.signkey.as_str().from_base58()?.as_slice())?;
-        let their_vk = ed25519_sign::PublicKey::from_slice(their_vk.from_base58()?.as_slice())?;
+        let their_vk = their_vk.from_base58()?;
         let nonce = crypto_type.gen_nonce();
 
-        let encrypted_doc = crypto_type.crypto_box(&my_sk, &their_vk, doc, &nonce)?;
+        let encrypted_doc = match my_key.custody {
+            Some(ref custody) => {
+                let their_vk = ed25519_sign::PublicKey::from_slice(&their_vk)?;
+                self._with_custody(&custody.custody, |key_custody| key_custody.crypto_box(&custody.key_ref, &their_vk, doc, &nonce))?
+            }
+            None => crypto_type.crypto_box(&my_key.signkey.as_str().from_base58()?, &their_vk, doc, &nonce)?
+        };
         let nonce = nonce[..].to_vec();
 
         trace!("crypto_box <<< encrypted_doc: {:?}, nonce: {:?}", encrypted_doc, nonce);
@@ -248,11 +300,16 @@ impl CryptoService {
 
         let crypto_type = self.crypto_types.get(crypto_type_name).unwrap();
 
-        let my_sk = ed25519_sign::SecretKey::from_slice(&my_key.signkey.from_base58()?.as_slice())?;
-        let their_vk = ed25519_sign::PublicKey::from_slice(their_vk.from_base58()?.as_slice())?;
+        let their_vk = their_vk.from_base58()?;
         let nonce = ed25519_box::Nonce::from_slice(&nonce)?;
 
-        let decrypted_doc = crypto_type.crypto_box_open(&my_sk, &their_vk, &doc, &nonce)?;
+        let decrypted_doc = match my_key.custody {
+            Some(ref custody) => {
+                let their_vk = ed25519_sign::PublicKey::from_slice(&their_vk)?;
+                self._with_custody(&custody.custody, |key_custody| key_custody.crypto_box_open(&custody.key_ref, &their_vk, &doc, &nonce))?
+            }
+            None => crypto_type.crypto_box_open(&my_key.signkey.from_base58()?, &their_vk, &doc, &nonce)?
+        };
 
         trace!("crypto_box_open <<< decrypted_doc: {:?}", decrypted_doc);
 
@@ -270,9 +327,7 @@ impl CryptoService {
 
         let crypto_type = self.crypto_types.get(crypto_type_name).unwrap();
 
-        let their_vk = ed25519_sign::PublicKey::from_slice(their_vk.from_base58()?.as_slice())?;
-
-        let encrypted_doc = crypto_type.crypto_box_seal(&their_vk, doc)?;
+        let encrypted_doc = crypto_type.crypto_box_seal(&their_vk.from_base58()?, doc)?;
 
         trace!("crypto_box_seal <<< encrypted_doc: {:?}", encrypted_doc);
 
@@ -291,16 +346,111 @@ impl CryptoService {
 
         let crypto_type = self.crypto_types.get(crypto_type_name).unwrap();
 
-        let my_vk = ed25519_sign::PublicKey::from_slice(my_vk.from_base58()?.as_slice())?;
-        let my_sk = ed25519_sign::SecretKey::from_slice(my_key.signkey.as_str().from_base58()?.as_slice())?;
+        let my_vk = my_vk.from_base58()?;
 
-        let decrypted_doc = crypto_type.crypto_box_seal_open(&my_vk, &my_sk, doc)?;
+        let decrypted_doc = match my_key.custody {
+            Some(ref custody) => {
+                let my_vk = ed25519_sign::PublicKey::from_slice(&my_vk)?;
+                self._with_custody(&custody.custody, |key_custody| key_custody.crypto_box_seal_open(&custody.key_ref, &my_vk, doc))?
+            }
+            None => crypto_type.crypto_box_seal_open(&my_vk, &my_key.signkey.as_str().from_base58()?, doc)?
+        };
 
         trace!("crypto_box_seal_open <<< decrypted_doc: {:?}", decrypted_doc);
 
         Ok(decrypted_doc)
     }
 
+    pub fn x25519_public_key(&self, vk: &str) -> IndyResult<ed25519_box::PublicKey> {
+        trace!("x25519_public_key >>> vk: {:?}", vk);
+
+        let (vk, crypto_type_name) = split_verkey(vk);
+
+        if !self.crypto_types.contains_key(&crypto_type_name) {
+            return Err(err_msg(IndyErrorKind::UnknownCrypto,
+                               format!("Trying to convert verkey with unknown crypto: {}", crypto_type_name)));
+        }
+
+        let crypto_type = self.crypto_types.get(crypto_type_name).unwrap();
+
+        let res = crypto_type.x25519_public_key(&vk.from_base58()?)?;
+
+        trace!("x25519_public_key <<< res: {:?}", res);
+
+        Ok(res)
+    }
+
+    pub fn jws_alg(&self, vk: &str) -> IndyResult<&'static str> {
+        let crypto_type_name = verkey_get_cryptoname(vk);
+
+        self.crypto_types.get(crypto_type_name)
+            .map(|crypto_type| crypto_type.jws_alg())
+            .ok_or_else(|| err_msg(IndyErrorKind::UnknownCrypto, format!("Trying to get JWS algorithm for unknown crypto: {}", crypto_type_name)))
+    }
+
+    pub fn key_agreement(&self, my_key: &Key, their_pk: &ed25519_box::PublicKey) -> IndyResult<Vec<u8>> {
+        trace!("key_agreement >>> my_key: {:?}, their_pk: {:?}", my_key, their_pk);
+
+        let crypto_type_name = verkey_get_cryptoname(&my_key.verkey);
+
+        if !self.crypto_types.contains_key(&crypto_type_name) {
+            return Err(err_msg(IndyErrorKind::UnknownCrypto,
+                               format!("Trying to compute shared secret with unknown crypto: {}", crypto_type_name)));
+        }
+
+        let crypto_type = self.crypto_types.get(&crypto_type_name).unwrap();
+
+        let shared_secret = match my_key.custody {
+            Some(ref custody) => self._with_custody(&custody.custody, |key_custody| key_custody.key_agreement(&custody.key_ref, their_pk))?,
+            None => crypto_type.key_agreement(&my_key.signkey.as_str().from_base58()?, their_pk)?
+        };
+
+        trace!("key_agreement <<<");
+
+        Ok(shared_secret)
+    }
+
+    fn _create_key(&self,
+                   crypto_type_name: &str,
+                   custody_name: Option<&str>,
+                   seed: Option<&ed25519_sign::Seed>) -> IndyResult<(Vec<u8>, Key)> {
+        if let Some(custody_name) = custody_name {
+            if !crypto_type_name.eq(DEFAULT_CRYPTO_TYPE) {
+                return Err(err_msg(IndyErrorKind::UnknownCrypto,
+                                   format!("Key custody supports only {} crypto: {}", DEFAULT_CRYPTO_TYPE, crypto_type_name)));
+            }
+
+            let (vk, key_ref) = self._with_custody(custody_name, |key_custody| key_custody.create_key(seed))?;
+            let key = Key::in_custody(vk[..].to_base58(), KeyCustodyRef { custody: custody_name.to_string(), key_ref });
+
+            return Ok((vk[..].to_vec(), key));
+        }
+
+        let crypto_type = self.crypto_types.get(crypto_type_name).unwrap();
+
+        let (vk, mut sk) = crypto_type.create_key(seed)?;
+        let mut verkey = vk.to_base58();
+        let signkey = sk.to_base58();
+        sk.zeroize();
+
+        if !crypto_type_name.eq(DEFAULT_CRYPTO_TYPE) {
+            // Use suffix with crypto type name to store crypto type inside of vk
+            verkey = format!("{}:{}", verkey, crypto_type_name);
+        }
+
+        Ok((vk, Key::new(verkey, signkey)))
+    }
+
+    fn _with_custody<T, F>(&self, custody_name: &str, f: F) -> IndyResult<T>
+        where F: FnOnce(&dyn KeyCustody) -> IndyResult<T> {
+        let custodies = self.custodies.borrow();
+
+        let custody = custodies.get(custody_name)
+            .ok_or_else(|| err_msg(IndyErrorKind::UnknownCrypto, format!("Unknown key custody: {}", custody_name)))?;
+
+        f(custody.as_ref())
+    }
+
     pub fn convert_seed(&self, seed: Option<&str>) -> IndyResult<Option<ed25519_sign::Seed>> {
         trace!("convert_seed >>> seed: {:?}", secret!(seed));
 
@@ -342,6 +492,49 @@ impl CryptoService {
         Ok(Some(res))
     }
 
+    pub fn generate_mnemonic(&self, word_count: Option<usize>) -> IndyResult<String> {
+        trace!("generate_mnemonic >>> word_count: {:?}", word_count);
+
+        let res = mnemonic::generate_mnemonic(word_count.unwrap_or(DEFAULT_MNEMONIC_WORD_COUNT))?;
+
+        trace!("generate_mnemonic <<<");
+
+        Ok(res)
+    }
+
+    pub fn derive_seed(&self, mnemonic_info: &MnemonicInfo) -> IndyResult<ed25519_sign::Seed> {
+        trace!("derive_seed >>> mnemonic_info: {:?}", secret!(mnemonic_info));
+
+        let mut seed = mnemonic::mnemonic_to_seed(&mnemonic_info.phrase,
+                                                  mnemonic_info.passphrase.as_ref().map(String::as_str).unwrap_or(""))?;
+
+        let key = mnemonic::derive_ed25519_key(&seed, &mnemonic_info.derivation_path);
+        seed.zeroize();
+        let mut key = key?;
+
+        let res = ed25519_sign::Seed::from_slice(&key);
+        key.zeroize();
+        let res = res?;
+
+        trace!("derive_seed <<< res: {:?}", secret!(&res));
+
+        Ok(res)
+    }
+
+    fn _get_seed(&self, seed: Option<&str>, mnemonic_info: Option<&MnemonicInfo>, crypto_type_name: &str) -> IndyResult<Option<ed25519_sign::Seed>> {
+        match mnemonic_info {
+            Some(_) if seed.is_some() =>
+                Err(err_msg(IndyErrorKind::InvalidStructure, "Only one of seed and mnemonic can be set")),
+            // SLIP-0010 derivation result is used as is, so it can be ed25519 seed only
+            Some(_) if crypto_type_name != DEFAULT_CRYPTO_TYPE =>
+                Err(err_msg(IndyErrorKind::UnknownCrypto, format!("Mnemonic can't be used for crypto: {}", crypto_type_name))),
+            Some(mnemonic_info) =>
+                self.derive_seed(mnemonic_info).map(Some),
+            None =>
+                self.convert_seed(seed)
+        }
+    }
+
     pub fn validate_key(&self, vk: &str) -> IndyResult<()> {
         trace!("validate_key >>> vk: {:?}", vk);
 
@@ -356,8 +549,7 @@ impl CryptoService {
         if vk.starts_with('~') {
             let _ = vk[1..].from_base58()?; // TODO: proper validate abbreviated verkey
         } else {
-            let vk = ed25519_sign::PublicKey::from_slice(vk.from_base58()?.as_slice())?;
-            crypto_type.validate_key(&vk)?;
+            crypto_type.validate_key(&vk.from_base58()?)?;
         };
 
         trace!("validate_key <<<");
@@ -445,7 +637,7 @@ impl CryptoService {
 
 #[cfg(test)]
 mod tests {
-    use crate::domain::crypto::did::MyDidInfo;
+    use crate::domain::crypto::did::{DidMethod, MyDidInfo};
     use indy_utils::crypto::chacha20poly1305_ietf::gen_key;
 
     use super::*;
@@ -453,7 +645,7 @@ mod tests {
     #[test]
     fn create_my_did_with_works_for_empty_info() {
         let service = CryptoService::new();
-        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None };
+        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
         let my_did = service.create_my_did(&did_info);
         assert!(my_did.is_ok());
     }
@@ -463,7 +655,7 @@ mod tests {
         let service = CryptoService::new();
 
         let did = DidValue("NcYxiDXkpYi6ov5FcYDi1e".to_string());
-        let did_info = MyDidInfo { did: Some(did.clone()), cid: None, seed: None, crypto_type: None, method_name: None };
+        let did_info = MyDidInfo { did: Some(did.clone()), cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
 
         let (my_did, _) = service.create_my_did(&did_info).unwrap();
         assert_eq!(did, my_did.did);
@@ -476,7 +668,7 @@ mod tests {
         let did = DidValue("NcYxiDXkpYi6ov5FcYDi1e".to_string());
         let crypto_type = Some("type".to_string());
 
-        let did_info = MyDidInfo { did: Some(did), cid: None, seed: None, crypto_type, method_name: None };
+        let did_info = MyDidInfo { did: Some(did), cid: None, seed: None, crypto_type, method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
 
         assert!(service.create_my_did(&did_info).is_err());
     }
@@ -488,8 +680,8 @@ mod tests {
         let did = DidValue("NcYxiDXkpYi6ov5FcYDi1e".to_string());
         let seed = Some("00000000000000000000000000000My1".to_string());
 
-        let did_info_with_seed = MyDidInfo { did: Some(did.clone()), cid: None, seed, crypto_type: None, method_name: None };
-        let did_info_without_seed = MyDidInfo { did: Some(did.clone()), cid: None, seed: None, crypto_type: None, method_name: None };
+        let did_info_with_seed = MyDidInfo { did: Some(did.clone()), cid: None, seed, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
+        let did_info_without_seed = MyDidInfo { did: Some(did.clone()), cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
 
         let (did_with_seed, _) = service.create_my_did(&did_info_with_seed).unwrap();
         let (did_without_seed, _) = service.create_my_did(&did_info_without_seed).unwrap();
@@ -497,6 +689,107 @@ mod tests {
         assert_ne!(did_with_seed.verkey, did_without_seed.verkey)
     }
 
+    #[test]
+    fn create_my_did_works_for_mnemonic() {
+        let service = CryptoService::new();
+
+        let mnemonic = |derivation_path: &str| Some(MnemonicInfo {
+            phrase: "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_string(),
+            passphrase: None,
+            derivation_path: derivation_path.to_string(),
+        });
+
+        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: mnemonic("m/0'") };
+        let (my_did, _) = service.create_my_did(&did_info).unwrap();
+        assert_eq!("P2msLB8MPYwECztDyFkjS4", my_did.did.0);
+        assert_eq!("D1Rwdcr6RtByD4izfmxvYfTon6TjaiaCSrt7RiegN5ux", my_did.verkey);
+
+        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: mnemonic("m/1'") };
+        let (my_did, _) = service.create_my_did(&did_info).unwrap();
+        assert_eq!("9TKpydeFXXFbewFmXMtAC2", my_did.did.0);
+    }
+
+    #[test]
+    fn create_my_did_not_works_for_mnemonic_and_seed() {
+        let service = CryptoService::new();
+
+        let mnemonic = Some(MnemonicInfo { phrase: service.generate_mnemonic(None).unwrap(), passphrase: None, derivation_path: "m/0'".to_string() });
+        let seed = Some("00000000000000000000000000000My1".to_string());
+
+        let did_info = MyDidInfo { did: None, cid: None, seed, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic };
+        assert_eq!(IndyErrorKind::InvalidStructure, service.create_my_did(&did_info).unwrap_err().kind());
+    }
+
+    #[test]
+    fn create_key_not_works_for_mnemonic_and_ecdsa_crypto_type() {
+        let service = CryptoService::new();
+
+        let mnemonic = Some(MnemonicInfo { phrase: service.generate_mnemonic(Some(12)).unwrap(), passphrase: None, derivation_path: "m/0'".to_string() });
+
+        let key_info = KeyInfo { seed: None, crypto_type: Some(SECP256K1_CRYPTO_TYPE.to_string()), custody: None, mnemonic };
+        assert_eq!(IndyErrorKind::UnknownCrypto, service.create_key(&key_info).unwrap_err().kind());
+    }
+
+    #[test]
+    fn create_my_did_works_for_key_method() {
+        let service = CryptoService::new();
+        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: Some(DidMethod("key".to_string())), peer_numalgo: None, custody: None, mnemonic: None };
+
+        let (my_did, key) = service.create_my_did(&did_info).unwrap();
+        assert!(my_did.did.0.starts_with("did:key:z6Mk"));
+        assert_eq!(Some(key.verkey), resolve_verkey(&my_did.did).unwrap());
+    }
+
+    #[test]
+    fn create_my_did_works_for_peer_method() {
+        let service = CryptoService::new();
+
+        for (numalgo, prefix) in &[(None, "did:peer:0z6Mk"), (Some(0), "did:peer:0z6Mk"), (Some(2), "did:peer:2.Ez6LS")] {
+            let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: Some(DidMethod("peer".to_string())), peer_numalgo: *numalgo, custody: None, mnemonic: None };
+
+            let (my_did, key) = service.create_my_did(&did_info).unwrap();
+            assert!(my_did.did.0.starts_with(prefix));
+            assert_eq!(Some(key.verkey), resolve_verkey(&my_did.did).unwrap());
+        }
+    }
+
+    #[test]
+    fn create_their_did_works_for_did_key_without_verkey() {
+        let service = CryptoService::new();
+        let verkey = "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW";
+        let did = build_did_key(verkey).unwrap();
+
+        let their_did_info = TheirDidInfo::new(did.clone(), None);
+        let their_did = service.create_their_did(&their_did_info).unwrap();
+
+        assert_eq!(did, their_did.did);
+        assert_eq!(verkey, their_did.verkey);
+    }
+
+    #[test]
+    fn create_their_did_works_for_did_key_and_same_verkey() {
+        let service = CryptoService::new();
+        let verkey = "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW";
+        let did = build_did_key(verkey).unwrap();
+
+        let their_did_info = TheirDidInfo::new(did.clone(), Some(verkey.to_string()));
+        let their_did = service.create_their_did(&their_did_info).unwrap();
+
+        assert_eq!(verkey, their_did.verkey);
+    }
+
+    #[test]
+    fn create_their_did_not_works_for_did_key_and_other_verkey() {
+        let service = CryptoService::new();
+        let did = build_did_key("CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW").unwrap();
+
+        let their_did_info = TheirDidInfo::new(did.clone(), Some("5L2HBnzbu6Auh2pkDRbFt5f4prvgE2LzknkuYLsKkacp".to_string()));
+        assert_eq!(IndyErrorKind::InvalidStructure, service.create_their_did(&their_did_info).unwrap_err().kind());
+
+        let their_did_info = TheirDidInfo::new(did.clone(), Some("~NcYxiDXkpYi6ov5FcYDi1e".to_string()));
+        assert_eq!(IndyErrorKind::InvalidStructure, service.create_their_did(&their_did_info).unwrap_err().kind());
+    }
+
     #[test]
     fn create_their_did_works_without_verkey() {
         let service = CryptoService::new();
@@ -536,7 +829,7 @@ mod tests {
     #[test]
     fn sign_works() {
         let service = CryptoService::new();
-        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None };
+        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
 
         let message = r#"message"#;
         let (_, my_key) = service.create_my_did(&did_info).unwrap();
@@ -555,7 +848,7 @@ mod tests {
     #[test]
     fn sign_verify_works() {
         let service = CryptoService::new();
-        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None };
+        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
         let message = r#"message"#;
         let (my_did, my_key) = service.create_my_did(&did_info).unwrap();
         let signature = service.sign(&my_key, message.as_bytes()).unwrap();
@@ -566,7 +859,7 @@ mod tests {
     #[test]
     fn sign_verify_works_for_verkey_contained_crypto_type() {
         let service = CryptoService::new();
-        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None };
+        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
         let message = r#"message"#;
         let (my_did, my_key) = service.create_my_did(&did_info).unwrap();
         let signature = service.sign(&my_key, message.as_bytes()).unwrap();
@@ -579,7 +872,7 @@ mod tests {
     #[test]
     fn sign_verify_works_for_verkey_contained_invalid_crypto_type() {
         let service = CryptoService::new();
-        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None };
+        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
         let message = r#"message"#;
         let (my_did, my_key) = service.create_my_did(&did_info).unwrap();
         let signature = service.sign(&my_key, message.as_bytes()).unwrap();
@@ -587,10 +880,58 @@ mod tests {
         assert!(service.verify(&verkey, message.as_bytes(), &signature).is_err());
     }
 
+    #[test]
+    fn sign_verify_works_for_ecdsa_crypto_types() {
+        let service = CryptoService::new();
+        let message = r#"message"#;
+
+        for crypto_type in &[SECP256K1_CRYPTO_TYPE, P256_CRYPTO_TYPE] {
+            let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: Some(crypto_type.to_string()), method_name: Some(DidMethod("key".to_string())), peer_numalgo: None, custody: None, mnemonic: None };
+            let (my_did, my_key) = service.create_my_did(&did_info).unwrap();
+            assert!(my_did.verkey.ends_with(&format!(":{}", crypto_type)));
+
+            let signature = service.sign(&my_key, message.as_bytes()).unwrap();
+            assert!(service.verify(&my_did.verkey, message.as_bytes(), &signature).unwrap());
+            service.validate_key(&my_did.verkey).unwrap();
+        }
+    }
+
+    #[test]
+    fn create_my_did_not_works_for_ecdsa_crypto_type_and_unqualified_did() {
+        let service = CryptoService::new();
+
+        for cid in &[None, Some(true)] {
+            let did_info = MyDidInfo { did: None, cid: *cid, seed: None, crypto_type: Some(SECP256K1_CRYPTO_TYPE.to_string()), method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
+            assert_eq!(IndyErrorKind::UnknownCrypto, service.create_my_did(&did_info).unwrap_err().kind());
+        }
+    }
+
+    #[test]
+    fn crypto_box_not_works_for_ecdsa_crypto_type() {
+        let service = CryptoService::new();
+        let key_info = KeyInfo { seed: None, crypto_type: Some(SECP256K1_CRYPTO_TYPE.to_string()), custody: None, mnemonic: None };
+        let key = service.create_key(&key_info).unwrap();
+
+        let res = service.crypto_box(&key, &key.verkey, "some message".as_bytes());
+        assert_eq!(IndyErrorKind::UnknownCrypto, res.unwrap_err().kind());
+
+        let res = service.crypto_box_seal(&key.verkey, "some message".as_bytes());
+        assert_eq!(IndyErrorKind::UnknownCrypto, res.unwrap_err().kind());
+    }
+
+    #[test]
+    fn create_key_not_works_for_custody_and_ecdsa_crypto_type() {
+        let service = _custody_service("create_key_not_works_for_custody_and_ecdsa_crypto_type");
+        let key_info = KeyInfo { seed: None, crypto_type: Some(P256_CRYPTO_TYPE.to_string()), custody: Some("file".to_string()), mnemonic: None };
+
+        let res = service.create_key(&key_info);
+        assert_eq!(IndyErrorKind::UnknownCrypto, res.unwrap_err().kind());
+    }
+
     #[test]
     fn verify_not_works_for_invalid_verkey() {
         let service = CryptoService::new();
-        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None };
+        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
         let message = r#"message"#;
         let (_, my_key) = service.create_my_did(&did_info).unwrap();
         let signature = service.sign(&my_key, message.as_bytes()).unwrap();
@@ -603,7 +944,7 @@ mod tests {
     fn crypto_box_works() {
         let service = CryptoService::new();
         let msg = "some message";
-        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None };
+        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
         let (_, my_key) = service.create_my_did(&did_info).unwrap();
         let (their_did, _) = service.create_my_did(&did_info.clone()).unwrap();
         let their_did = Did::new(their_did.did, their_did.verkey);
@@ -617,7 +958,7 @@ mod tests {
 
         let msg = "some message";
 
-        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None };
+        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
 
         let (my_did, my_key) = service.create_my_did(&did_info).unwrap();
 
@@ -645,7 +986,7 @@ mod tests {
 
         let msg = "some message";
 
-        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None };
+        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
 
         let (my_did, my_key) = service.create_my_did(&did_info).unwrap();
 
@@ -671,7 +1012,7 @@ mod tests {
     fn crypto_box_seal_works() {
         let service = CryptoService::new();
         let msg = "some message";
-        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None };
+        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
         let (did, _) = service.create_my_did(&did_info.clone()).unwrap();
         let did = Did::new(did.did, did.verkey);
         let encrypted_message = service.crypto_box_seal(&did.verkey, msg.as_bytes());
@@ -682,7 +1023,7 @@ mod tests {
     fn crypto_box_seal_and_crypto_box_seal_open_works() {
         let service = CryptoService::new();
         let msg = "some message".as_bytes();
-        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None };
+        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
         let (did, key) = service.create_my_did(&did_info.clone()).unwrap();
         let encrypt_did = Did::new(did.did.clone(), did.verkey.clone());
         let encrypted_message = service.crypto_box_seal(&encrypt_did.verkey, msg).unwrap();
@@ -690,6 +1031,136 @@ mod tests {
         assert_eq!(msg, decrypted_message.as_slice());
     }
 
+    #[test]
+    fn key_agreement_works() {
+        let service = CryptoService::new();
+        let key_info = KeyInfo { seed: None, crypto_type: None, custody: None, mnemonic: None };
+        let alice_key = service.create_key(&key_info).unwrap();
+        let bob_key = service.create_key(&key_info).unwrap();
+
+        let alice_shared = service.key_agreement(&alice_key, &service.x25519_public_key(&bob_key.verkey).unwrap()).unwrap();
+        let bob_shared = service.key_agreement(&bob_key, &service.x25519_public_key(&alice_key.verkey).unwrap()).unwrap();
+        assert_eq!(alice_shared, bob_shared);
+    }
+
+    #[test]
+    fn key_agreement_works_for_custody() {
+        let service = _custody_service("key_agreement_works_for_custody");
+        let alice_key = service.create_key(&KeyInfo { seed: None, crypto_type: None, custody: Some("file".to_string()), mnemonic: None }).unwrap();
+        let bob_key = service.create_key(&KeyInfo { seed: None, crypto_type: None, custody: None, mnemonic: None }).unwrap();
+
+        let alice_shared = service.key_agreement(&alice_key, &service.x25519_public_key(&bob_key.verkey).unwrap()).unwrap();
+        let bob_shared = service.key_agreement(&bob_key, &service.x25519_public_key(&alice_key.verkey).unwrap()).unwrap();
+        assert_eq!(alice_shared, bob_shared);
+    }
+
+    #[test]
+    fn jws_alg_works() {
+        let service = CryptoService::new();
+        assert_eq!("EdDSA", service.jws_alg("AnnxV4t3LUHKZaxVQDWoVaG44NrGmeDYMA4Gz6C2tCZd").unwrap());
+        assert_eq!("ES256K", service.jws_alg("AnnxV4t3LUHKZaxVQDWoVaG44NrGmeDYMA4Gz6C2tCZd:secp256k1").unwrap());
+        assert_eq!("ES256", service.jws_alg("AnnxV4t3LUHKZaxVQDWoVaG44NrGmeDYMA4Gz6C2tCZd:p256").unwrap());
+
+        let res = service.jws_alg("AnnxV4t3LUHKZaxVQDWoVaG44NrGmeDYMA4Gz6C2tCZd:unknown");
+        assert_eq!(IndyErrorKind::UnknownCrypto, res.unwrap_err().kind());
+    }
+
+    #[test]
+    fn x25519_public_key_not_works_for_ecdsa_crypto_type() {
+        let service = CryptoService::new();
+        let key_info = KeyInfo { seed: None, crypto_type: Some(P256_CRYPTO_TYPE.to_string()), custody: None, mnemonic: None };
+        let key = service.create_key(&key_info).unwrap();
+
+        let res = service.x25519_public_key(&key.verkey);
+        assert_eq!(IndyErrorKind::UnknownCrypto, res.unwrap_err().kind());
+    }
+
+    fn _custody_service(name: &str) -> CryptoService {
+        let path = indy_utils::environment::tmp_file_path(name);
+        let _ = ::std::fs::remove_dir_all(&path);
+
+        let service = CryptoService::new();
+        service.register_key_custody("file", Box::new(FileKeyCustody::new(path))).unwrap();
+        service
+    }
+
+    #[test]
+    fn register_key_custody_not_works_for_duplicate() {
+        let service = _custody_service("register_key_custody_not_works_for_duplicate");
+
+        let res = service.register_key_custody("file", Box::new(FileKeyCustody::new(indy_utils::environment::tmp_path())));
+        assert_eq!(IndyErrorKind::InvalidState, res.unwrap_err().kind());
+    }
+
+    #[test]
+    fn create_key_works_for_custody() {
+        let service = _custody_service("create_key_works_for_custody");
+        let key_info = KeyInfo { seed: Some("00000000000000000000000000000My1".to_string()), crypto_type: None, custody: Some("file".to_string()), mnemonic: None };
+
+        let key = service.create_key(&key_info).unwrap();
+        assert!(key.signkey.is_empty());
+
+        let custody = key.custody.as_ref().unwrap();
+        assert_eq!("file", custody.custody);
+        assert_eq!(key.verkey, custody.key_ref);
+
+        let software_key = service.create_key(&KeyInfo { custody: None, ..key_info }).unwrap();
+        assert_eq!(software_key.verkey, key.verkey);
+    }
+
+    #[test]
+    fn create_key_not_works_for_unknown_custody() {
+        let service = CryptoService::new();
+        let key_info = KeyInfo { seed: None, crypto_type: None, custody: Some("unknown".to_string()), mnemonic: None };
+
+        let res = service.create_key(&key_info);
+        assert_eq!(IndyErrorKind::UnknownCrypto, res.unwrap_err().kind());
+    }
+
+    #[test]
+    fn sign_verify_works_for_custody() {
+        let service = _custody_service("sign_verify_works_for_custody");
+        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: Some("file".to_string()), mnemonic: None };
+        let message = r#"message"#;
+
+        let (did, key) = service.create_my_did(&did_info).unwrap();
+        let signature = service.sign(&key, message.as_bytes()).unwrap();
+
+        assert!(service.verify(&did.verkey, message.as_bytes(), &signature).unwrap());
+    }
+
+    #[test]
+    fn crypto_box_and_crypto_box_open_works_for_custody() {
+        let service = _custody_service("crypto_box_and_crypto_box_open_works_for_custody");
+        let msg = "some message".as_bytes();
+
+        let custody_did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: Some("file".to_string()), mnemonic: None };
+        let (_, custody_key) = service.create_my_did(&custody_did_info).unwrap();
+
+        let did_info = MyDidInfo { did: None, cid: None, seed: None, crypto_type: None, method_name: None, peer_numalgo: None, custody: None, mnemonic: None };
+        let (_, key) = service.create_my_did(&did_info).unwrap();
+
+        let (encrypted_message, nonce) = service.crypto_box(&custody_key, &key.verkey, msg).unwrap();
+        let decrypted_message = service.crypto_box_open(&key, &custody_key.verkey, &encrypted_message, &nonce).unwrap();
+        assert_eq!(msg.to_vec(), decrypted_message);
+
+        let (encrypted_message, nonce) = service.crypto_box(&key, &custody_key.verkey, msg).unwrap();
+        let decrypted_message = service.crypto_box_open(&custody_key, &key.verkey, &encrypted_message, &nonce).unwrap();
+        assert_eq!(msg.to_vec(), decrypted_message);
+    }
+
+    #[test]
+    fn crypto_box_seal_open_works_for_custody() {
+        let service = _custody_service("crypto_box_seal_open_works_for_custody");
+        let msg = "some message".as_bytes();
+        let key_info = KeyInfo { seed: None, crypto_type: None, custody: Some("file".to_string()), mnemonic: None };
+
+        let key = service.create_key(&key_info).unwrap();
+        let encrypted_message = service.crypto_box_seal(&key.verkey, msg).unwrap();
+        let decrypted_message = service.crypto_box_seal_open(&key, &encrypted_message).unwrap();
+        assert_eq!(msg, decrypted_message.as_slice());
+    }
+
     #[test]
     pub fn test_encrypt_plaintext_and_decrypt_ciphertext_works() {
         let service: CryptoService = CryptoService::new();
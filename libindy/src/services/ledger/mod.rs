@@ -3,6 +3,7 @@ use ursa::cl::RevocationRegistryDelta as CryproRevocationRegistryDelta;
 use serde::de::DeserializeOwned;
 use serde_json;
 use serde_json::Value;
+use serde_json::map::Entry;
 use log_derive::logfn;
 
 use crate::domain::anoncreds::credential_definition::{CredentialDefinition, CredentialDefinitionV1, CredentialDefinitionId};
@@ -12,9 +13,10 @@ use crate::domain::anoncreds::revocation_registry_delta::{RevocationRegistryDelt
 use crate::domain::anoncreds::schema::{Schema, SchemaV1, SchemaId};
 use crate::domain::crypto::did::DidValue;
 use crate::domain::ledger::attrib::{AttribOperation, GetAttribOperation};
-use crate::domain::ledger::constants::{GET_VALIDATOR_INFO, POOL_RESTART, ROLE_REMOVE, STEWARD, ENDORSER, TRUSTEE, NETWORK_MONITOR, ROLES, txn_name_to_code};
+use crate::domain::ledger::constants::{GET_ATTR, GET_VALIDATOR_INFO, POOL_RESTART, ROLE_REMOVE, STEWARD, ENDORSER, TRUSTEE, NETWORK_MONITOR, ROLES, txn_name_to_code};
 use crate::domain::ledger::cred_def::{CredDefOperation, GetCredDefOperation, GetCredDefReplyResult};
 use crate::domain::ledger::ddo::GetDdoOperation;
+use crate::domain::ledger::did_doc::*;
 use crate::domain::ledger::node::{NodeOperation, NodeOperationData};
 use crate::domain::ledger::nym::{GetNymOperation, GetNymReplyResult, GetNymResultDataV0, NymData, NymOperation};
 use crate::domain::ledger::pool::{PoolConfigOperation, PoolRestartOperation, PoolUpgradeOperation, Schedule};
@@ -29,6 +31,8 @@ use crate::domain::ledger::auth_rule::*;
 use crate::domain::ledger::author_agreement::*;
 use indy_api_types::errors::prelude::*;
 use indy_utils::crypto::hash::hash as openssl_hash;
+use crate::services::crypto::DEFAULT_CRYPTO_TYPE;
+use crate::utils::crypto::verkey_builder::split_verkey;
 
 pub mod merkletree;
 
@@ -120,6 +124,139 @@ impl LedgerService {
         Ok(res)
     }
 
+    #[logfn(Info)]
+    pub fn parse_get_attrib_response(&self, get_attrib_response: &str, name: &str) -> IndyResult<Option<Value>> {
+        let message: Message<Value> = serde_json::from_str(get_attrib_response)
+            .to_indy(IndyErrorKind::InvalidTransaction, "Response is invalid json")?;
+
+        let result = match message {
+            Message::Reject(response) | Message::ReqNACK(response) =>
+                return Err(err_msg(IndyErrorKind::InvalidTransaction, format!("Transaction has been failed: {:?}", response.reason))),
+            Message::Reply(reply) => reply.result()
+        };
+
+        if result["type"] != json!(GET_ATTR) {
+            return Err(err_msg(IndyErrorKind::InvalidTransaction, "Invalid response type"));
+        }
+
+        let raw = match result["data"].as_str() {
+            Some(raw) => raw,
+            None => return Ok(None)
+        };
+
+        let mut raw: Value = serde_json::from_str(raw)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot parse GET_ATTRIB raw data")?;
+
+        Ok(raw.as_object_mut().and_then(|raw| raw.remove(name)))
+    }
+
+    #[logfn(Info)]
+    pub fn build_did_doc(&self, did: &DidValue, verkey: Option<&str>, x25519_key: Option<&str>,
+                         endpoint: Option<Value>, diddoc_content: Option<Value>) -> IndyResult<Value> {
+        let id = if did.is_fully_qualified() { did.0.clone() } else { did.qualify(DEFAULT_DID_METHOD).0 };
+
+        let mut did_doc = DidDocument {
+            context: vec![DID_CONTEXT.to_string()],
+            id: id.clone(),
+            verification_method: Vec::new(),
+            authentication: Vec::new(),
+            assertion_method: Vec::new(),
+            key_agreement: Vec::new(),
+            service: Vec::new(),
+        };
+
+        if let Some(verkey) = verkey {
+            let (verkey, crypto_type_name) = split_verkey(verkey);
+
+            if crypto_type_name != DEFAULT_CRYPTO_TYPE {
+                return Err(err_msg(IndyErrorKind::UnknownCrypto, format!("Unsupported crypto of DID verkey: {}", crypto_type_name)));
+            }
+
+            let key_id = format!("{}#{}", id, VERKEY_FRAGMENT);
+
+            did_doc.context.push(ED25519_2018_CONTEXT.to_string());
+            did_doc.verification_method.push(VerificationMethod {
+                id: key_id.clone(),
+                type_: ED25519_2018_TYPE.to_string(),
+                controller: id.clone(),
+                public_key_base58: verkey.to_string(),
+            });
+            did_doc.authentication.push(key_id.clone());
+            did_doc.assertion_method.push(key_id);
+        }
+
+        if let Some(x25519_key) = x25519_key {
+            let key_id = format!("{}#{}", id, KEY_AGREEMENT_FRAGMENT);
+
+            did_doc.context.push(X25519_2019_CONTEXT.to_string());
+            did_doc.verification_method.push(VerificationMethod {
+                id: key_id.clone(),
+                type_: X25519_2019_TYPE.to_string(),
+                controller: id.clone(),
+                public_key_base58: x25519_key.to_string(),
+            });
+            did_doc.key_agreement.push(key_id);
+        }
+
+        if let Some(endpoint) = endpoint {
+            let endpoint: EndpointAttrib = match endpoint {
+                Value::String(endpoint) => EndpointAttrib { ha: None, endpoint: Some(endpoint), types: None, routing_keys: None },
+                endpoint => serde_json::from_value(endpoint)
+                    .to_indy(IndyErrorKind::InvalidState, "Invalid endpoint ATTRIB")?
+            };
+
+            if let Some(service_endpoint) = endpoint.endpoint.or(endpoint.ha) {
+                let routing_keys = endpoint.routing_keys.unwrap_or_default();
+
+                for type_ in endpoint.types.unwrap_or_else(|| vec![DEFAULT_SERVICE_TYPE.to_string()]) {
+                    did_doc.service.push(Service {
+                        id: format!("{}#{}", id, type_),
+                        type_,
+                        service_endpoint: service_endpoint.clone(),
+                        routing_keys: routing_keys.clone(),
+                    });
+                }
+            }
+        }
+
+        let mut did_doc = serde_json::to_value(&did_doc)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize DID Document")?;
+
+        if let Some(diddoc_content) = diddoc_content {
+            LedgerService::_merge_diddoc_content(&mut did_doc, diddoc_content)?;
+        }
+
+        Ok(did_doc)
+    }
+
+    // Arrays of DID Document are extended with diddocContent ones, other fields can only be added
+    fn _merge_diddoc_content(did_doc: &mut Value, diddoc_content: Value) -> IndyResult<()> {
+        let did_doc = did_doc.as_object_mut()
+            .ok_or_else(|| err_msg(IndyErrorKind::InvalidState, "DID Document isn't json object"))?;
+
+        let diddoc_content = match diddoc_content {
+            Value::Object(diddoc_content) => diddoc_content,
+            _ => return Err(err_msg(IndyErrorKind::InvalidStructure, "diddocContent ATTRIB isn't json object"))
+        };
+
+        for (key, value) in diddoc_content {
+            match did_doc.entry(key.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(value);
+                }
+                Entry::Occupied(mut entry) => match (entry.get_mut(), value) {
+                    (Value::Array(items), Value::Array(values)) => items.extend(values),
+                    (Value::Array(items), value) => items.push(value),
+                    (current, value) => if *current != value {
+                        return Err(err_msg(IndyErrorKind::InvalidStructure, format!("diddocContent ATTRIB conflicts with DID Document field: {}", key)));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     #[logfn(Info)]
     pub fn build_get_ddo_request(&self, identifier: Option<&DidValue>, dest: &DidValue) -> IndyResult<String> {
         build_result!(GetDdoOperation, identifier, dest.to_short())
@@ -1233,6 +1370,94 @@ mod tests {
         }
     }
 
+    mod did_doc {
+        use super::*;
+
+        #[test]
+        fn parse_get_attrib_response_works() {
+            let ledger_service = LedgerService::new();
+
+            let response = json!({
+                "op": "REPLY",
+                "result": {
+                    "type": GET_ATTR,
+                    "dest": DEST,
+                    "raw": "endpoint",
+                    "data": r#"{"endpoint":{"ha":"127.0.0.1:5555"}}"#,
+                    "seqNo": 10,
+                    "txnTime": 1577836800
+                }
+            }).to_string();
+
+            let endpoint = ledger_service.parse_get_attrib_response(&response, "endpoint").unwrap();
+            assert_eq!(Some(json!({"ha": "127.0.0.1:5555"})), endpoint);
+        }
+
+        #[test]
+        fn parse_get_attrib_response_works_for_missed_attrib() {
+            let ledger_service = LedgerService::new();
+
+            let response = json!({
+                "op": "REPLY",
+                "result": {"type": GET_ATTR, "dest": DEST, "raw": "endpoint", "data": null}
+            }).to_string();
+
+            assert_eq!(None, ledger_service.parse_get_attrib_response(&response, "endpoint").unwrap());
+        }
+
+        #[test]
+        fn build_did_doc_works() {
+            let ledger_service = LedgerService::new();
+
+            let did_doc = ledger_service.build_did_doc(&dest(), Some(VERKEY), Some(IDENTIFIER),
+                                                       Some(json!({"ha": "127.0.0.1:5555"})), None).unwrap();
+
+            let did = format!("did:sov:{}", DEST);
+            assert_eq!(json!(did), did_doc["id"]);
+            assert_eq!(json!([format!("{}#verkey", did)]), did_doc["authentication"]);
+            assert_eq!(json!(VERKEY), did_doc["verificationMethod"][0]["publicKeyBase58"]);
+            assert_eq!(json!([format!("{}#key-agreement", did)]), did_doc["keyAgreement"]);
+            assert_eq!(json!("127.0.0.1:5555"), did_doc["service"][0]["serviceEndpoint"]);
+        }
+
+        #[test]
+        fn build_did_doc_works_for_endpoint_types() {
+            let ledger_service = LedgerService::new();
+
+            let endpoint = json!({"endpoint": "https://agent.example.com", "types": ["endpoint", "did-communication"], "routingKeys": [IDENTIFIER]});
+            let did_doc = ledger_service.build_did_doc(&dest(), Some(VERKEY), None, Some(endpoint), None).unwrap();
+
+            assert_eq!(json!("did-communication"), did_doc["service"][1]["type"]);
+            assert_eq!(json!("https://agent.example.com"), did_doc["service"][1]["serviceEndpoint"]);
+            assert_eq!(json!([IDENTIFIER]), did_doc["service"][1]["routingKeys"]);
+        }
+
+        #[test]
+        fn build_did_doc_works_for_diddoc_content() {
+            let ledger_service = LedgerService::new();
+
+            let diddoc_content = json!({
+                "@context": "https://identity.foundation/linked-vp/contexts/v1",
+                "service": [{"id": "#domain", "type": "LinkedDomains", "serviceEndpoint": "https://example.com"}],
+                "alsoKnownAs": ["https://example.com"]
+            });
+
+            let did_doc = ledger_service.build_did_doc(&dest(), Some(VERKEY), None, None, Some(diddoc_content)).unwrap();
+
+            assert_eq!(json!("https://identity.foundation/linked-vp/contexts/v1"), did_doc["@context"][2]);
+            assert_eq!(json!("LinkedDomains"), did_doc["service"][0]["type"]);
+            assert_eq!(json!(["https://example.com"]), did_doc["alsoKnownAs"]);
+        }
+
+        #[test]
+        fn build_did_doc_not_works_for_conflicting_diddoc_content() {
+            let ledger_service = LedgerService::new();
+
+            let res = ledger_service.build_did_doc(&dest(), Some(VERKEY), None, None, Some(json!({"id": "did:sov:other"})));
+            assert_kind!(IndyErrorKind::InvalidStructure, res);
+        }
+    }
+
     #[test]
     fn datetime_to_date() {
         assert_eq!(0, LedgerService::datetime_to_date_timestamp(0));
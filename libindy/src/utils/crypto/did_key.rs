@@ -0,0 +1,203 @@
+use indy_api_types::errors::prelude::*;
+use rust_base58::{FromBase58, ToBase58};
+
+use crate::domain::crypto::did::DidValue;
+use crate::services::crypto::{DEFAULT_CRYPTO_TYPE, P256_CRYPTO_TYPE, SECP256K1_CRYPTO_TYPE};
+use crate::utils::crypto::verkey_builder::split_verkey;
+
+pub const KEY_METHOD: &str = "key";
+pub const PEER_METHOD: &str = "peer";
+
+// Multibase prefix of base58btc encoding
+const MULTIBASE_BASE58BTC: char = 'z';
+
+// Unsigned varint encoded multicodec prefixes of public keys
+const X25519_MULTICODEC: [u8; 2] = [0xec, 0x01];
+const MULTICODECS: [(&str, [u8; 2]); 3] = [
+    (DEFAULT_CRYPTO_TYPE, [0xed, 0x01]),
+    (SECP256K1_CRYPTO_TYPE, [0xe7, 0x01]),
+    (P256_CRYPTO_TYPE, [0x80, 0x24]),
+];
+
+// did:peer numalgo 2 purpose codes
+const PEER_PURPOSE_ENCRYPTION: char = 'E';
+const PEER_PURPOSE_VERIFICATION: char = 'V';
+
+pub fn build_did_key(verkey: &str) -> IndyResult<DidValue> {
+    Ok(DidValue::new(&encode_multikey(verkey)?, Some(KEY_METHOD)))
+}
+
+/// Builds DID URL of the only verification method of did:key, e.g. `did:key:z6Mk...#z6Mk...`.
+pub fn build_key_id(verkey: &str) -> IndyResult<String> {
+    let multikey = encode_multikey(verkey)?;
+    Ok(format!("{}#{}", DidValue::new(&multikey, Some(KEY_METHOD)).0, multikey))
+}
+
+/// Returns verkey referenced by DID URL of did:key or did:peer and None for DIDs of other methods.
+pub fn resolve_key_id(kid: &str) -> IndyResult<Option<String>> {
+    let did = kid.splitn(2, '#').next().unwrap_or_default();
+
+    if !did.starts_with("did:") {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Key id isn't DID URL: {}", kid)));
+    }
+
+    resolve_verkey(&DidValue(did.to_string()))
+}
+
+/// Builds numalgo 2 did:peer if X25519 key agreement key is given and numalgo 0 did:peer otherwise.
+pub fn build_did_peer(verkey: &str, x25519_pk: Option<&[u8]>) -> IndyResult<DidValue> {
+    let multikey = encode_multikey(verkey)?;
+
+    let did = match x25519_pk {
+        Some(x25519_pk) => format!("2.{}{}.{}{}",
+                                   PEER_PURPOSE_ENCRYPTION, _encode_multibase(&X25519_MULTICODEC, x25519_pk),
+                                   PEER_PURPOSE_VERIFICATION, multikey),
+        None => format!("0{}", multikey)
+    };
+
+    Ok(DidValue::new(&did, Some(PEER_METHOD)))
+}
+
+/// Returns verkey encoded in did:key or did:peer identifier and None for DIDs of other methods.
+pub fn resolve_verkey(did: &DidValue) -> IndyResult<Option<String>> {
+    let method = match did.get_method() {
+        Some(method) => method,
+        None => return Ok(None)
+    };
+
+    let id = did.to_unqualified().0;
+
+    match method.as_str() {
+        KEY_METHOD => decode_multikey(&id).map(Some),
+        PEER_METHOD => _resolve_peer_verkey(&id).map(Some),
+        _ => Ok(None)
+    }
+}
+
+pub fn encode_multikey(verkey: &str) -> IndyResult<String> {
+    let (vk, crypto_type_name) = split_verkey(verkey);
+
+    let (_, multicodec) = MULTICODECS.iter()
+        .find(|(name, _)| *name == crypto_type_name)
+        .ok_or_else(|| err_msg(IndyErrorKind::UnknownCrypto, format!("Multicodec isn't defined for crypto: {}", crypto_type_name)))?;
+
+    Ok(_encode_multibase(multicodec, &vk.from_base58()?))
+}
+
+pub fn decode_multikey(multikey: &str) -> IndyResult<String> {
+    if !multikey.starts_with(MULTIBASE_BASE58BTC) {
+        return Err(err_msg(IndyErrorKind::InvalidStructure, format!("Unsupported multibase encoding of key: {}", multikey)));
+    }
+
+    let bytes = multikey[1..].from_base58()
+        .map_err(|_| err_msg(IndyErrorKind::InvalidStructure, format!("Invalid base58 encoding of key: {}", multikey)))?;
+
+    let (crypto_type_name, multicodec) = MULTICODECS.iter()
+        .find(|(_, multicodec)| bytes.starts_with(multicodec))
+        .ok_or_else(|| err_msg(IndyErrorKind::UnknownCrypto, format!("Unsupported multicodec of key: {}", multikey)))?;
+
+    let vk = bytes[multicodec.len()..].to_base58();
+
+    let verkey = if *crypto_type_name == DEFAULT_CRYPTO_TYPE {
+        vk
+    } else {
+        format!("{}:{}", vk, crypto_type_name)
+    };
+
+    Ok(verkey)
+}
+
+fn _resolve_peer_verkey(id: &str) -> IndyResult<String> {
+    if id.starts_with('0') {
+        return decode_multikey(&id[1..]);
+    }
+
+    if id.starts_with("2.") {
+        let element = id[2..].split('.')
+            .find(|element| element.starts_with(PEER_PURPOSE_VERIFICATION))
+            .ok_or_else(|| err_msg(IndyErrorKind::InvalidStructure, format!("did:peer doesn't contain verification key: {}", id)))?;
+
+        return decode_multikey(&element[1..]);
+    }
+
+    Err(err_msg(IndyErrorKind::InvalidStructure, format!("Unsupported did:peer numalgo: {}", id)))
+}
+
+fn _encode_multibase(multicodec: &[u8], key: &[u8]) -> String {
+    let mut bytes = multicodec.to_vec();
+    bytes.extend_from_slice(key);
+    format!("{}{}", MULTIBASE_BASE58BTC, bytes.to_base58())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERKEY: &str = "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW";
+    const SECP256K1_VERKEY: &str = "26NT8yVnsdDdpjrvR8S9myPZpBycK4qf3gDjRcgVXQ9zH:secp256k1";
+
+    #[test]
+    fn build_key_id_works() {
+        let kid = build_key_id(VERKEY).unwrap();
+        let parts: Vec<&str> = kid.splitn(2, '#').collect();
+        assert_eq!(format!("did:key:{}", parts[1]), parts[0]);
+        assert_eq!(Some(VERKEY.to_string()), resolve_key_id(&kid).unwrap());
+    }
+
+    #[test]
+    fn resolve_key_id_works_for_other_method() {
+        assert_eq!(None, resolve_key_id("did:example:alice#key-1").unwrap());
+    }
+
+    #[test]
+    fn resolve_key_id_not_works_for_verkey() {
+        assert_eq!(IndyErrorKind::InvalidStructure, resolve_key_id(VERKEY).unwrap_err().kind());
+    }
+
+    #[test]
+    fn build_did_key_works() {
+        let did = build_did_key(VERKEY).unwrap();
+        assert!(did.0.starts_with("did:key:z6Mk"));
+        assert_eq!(VERKEY, resolve_verkey(&did).unwrap().unwrap());
+    }
+
+    #[test]
+    fn build_did_key_works_for_secp256k1() {
+        let did = build_did_key(SECP256K1_VERKEY).unwrap();
+        assert!(did.0.starts_with("did:key:zQ3s"));
+        assert_eq!(SECP256K1_VERKEY, resolve_verkey(&did).unwrap().unwrap());
+    }
+
+    #[test]
+    fn build_did_peer_works_for_numalgo_0() {
+        let did = build_did_peer(VERKEY, None).unwrap();
+        assert!(did.0.starts_with("did:peer:0z6Mk"));
+        assert_eq!(VERKEY, resolve_verkey(&did).unwrap().unwrap());
+    }
+
+    #[test]
+    fn build_did_peer_works_for_numalgo_2() {
+        let did = build_did_peer(VERKEY, Some(&[1u8; 32])).unwrap();
+        assert!(did.0.starts_with("did:peer:2.Ez6LS"));
+        assert!(did.0.contains(".Vz6Mk"));
+        assert_eq!(VERKEY, resolve_verkey(&did).unwrap().unwrap());
+    }
+
+    #[test]
+    fn resolve_verkey_works_for_other_method() {
+        assert_eq!(None, resolve_verkey(&DidValue("did:sov:NcYxiDXkpYi6ov5FcYDi1e".to_string())).unwrap());
+        assert_eq!(None, resolve_verkey(&DidValue("NcYxiDXkpYi6ov5FcYDi1e".to_string())).unwrap());
+    }
+
+    #[test]
+    fn resolve_verkey_not_works_for_unknown_multicodec() {
+        let did = DidValue(format!("did:key:{}", _encode_multibase(&X25519_MULTICODEC, &[1u8; 32])));
+        assert_eq!(IndyErrorKind::UnknownCrypto, resolve_verkey(&did).unwrap_err().kind());
+    }
+
+    #[test]
+    fn resolve_verkey_not_works_for_unsupported_peer_numalgo() {
+        let did = DidValue(format!("did:peer:1{}", encode_multikey(VERKEY).unwrap()));
+        assert_eq!(IndyErrorKind::InvalidStructure, resolve_verkey(&did).unwrap_err().kind());
+    }
+}
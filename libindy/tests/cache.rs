@@ -426,4 +426,138 @@ mod high_cases {
             purge_cred_def_cache(setup.wallet_handle, &json!({"minFresh": 1000}).to_string()).unwrap();
         }
     }
-}
\ No newline at end of file
+    mod did_doc_cache {
+        use super::*;
+        use crate::utils::constants::*;
+        use crate::utils::{ledger, pool};
+        use crate::utils::types::ResponseType;
+        use serde_json::Value;
+
+        #[test]
+        fn indy_resolve_did_empty_options() {
+            let setup = Setup::wallet_and_pool();
+
+            let res_json = resolve_did(setup.pool_handle, setup.wallet_handle, DID_TRUSTEE, "{}").unwrap();
+            let res: Value = serde_json::from_str(&res_json).unwrap();
+
+            let did = format!("did:sov:{}", DID_TRUSTEE);
+            let did_doc = &res["didDocument"];
+
+            assert_eq!(json!(did), did_doc["id"]);
+            assert_eq!(json!(format!("{}#verkey", did)), did_doc["authentication"][0]);
+            assert_eq!(json!(VERKEY_TRUSTEE), did_doc["verificationMethod"][0]["publicKeyBase58"]);
+            assert_eq!(json!("Ed25519VerificationKey2018"), did_doc["verificationMethod"][0]["type"]);
+            assert_eq!(json!(format!("{}#key-agreement", did)), did_doc["keyAgreement"][0]);
+            assert!(res["didDocumentMetadata"]["seqNo"].is_u64());
+            assert_eq!(json!("application/did+ld+json"), res["didResolutionMetadata"]["contentType"]);
+        }
+
+        #[test]
+        fn indy_resolve_did_works_for_endpoint_and_diddoc_content() {
+            let setup = Setup::new_identity();
+
+            let diddoc_content = json!({
+                "diddocContent": {
+                    "service": [{
+                        "id": format!("did:sov:{}#linked-domain", setup.did),
+                        "type": "LinkedDomains",
+                        "serviceEndpoint": "https://example.com"
+                    }]
+                }
+            }).to_string();
+
+            for raw in &[ATTRIB_RAW_DATA, diddoc_content.as_str()] {
+                let attrib_request = ledger::build_attrib_request(&setup.did, &setup.did, None, Some(raw), None).unwrap();
+                let response = ledger::sign_and_submit_request(setup.pool_handle, setup.wallet_handle, &setup.did, &attrib_request).unwrap();
+                pool::check_response_type(&response, ResponseType::REPLY);
+            }
+
+            let res_json = resolve_did(setup.pool_handle, setup.wallet_handle, &setup.did, "{}").unwrap();
+            let res: Value = serde_json::from_str(&res_json).unwrap();
+
+            let service = res["didDocument"]["service"].as_array().unwrap();
+            assert_eq!(2, service.len());
+            assert_eq!(json!("endpoint"), service[0]["type"]);
+            assert_eq!(json!("127.0.0.1:5555"), service[0]["serviceEndpoint"]);
+            assert_eq!(json!("LinkedDomains"), service[1]["type"]);
+            assert_eq!(json!(setup.verkey), res["didDocument"]["verificationMethod"][0]["publicKeyBase58"]);
+        }
+
+        #[test]
+        fn indy_resolve_did_empty_options_for_unknown_did() {
+            let setup = Setup::wallet_and_pool();
+
+            let res = resolve_did(setup.pool_handle, setup.wallet_handle, DID, "{}");
+            assert_code!(ErrorCode::LedgerNotFound, res);
+        }
+
+        #[test]
+        fn indy_resolve_did_only_cache_no_cached_data() {
+            let setup = Setup::wallet_and_pool();
+
+            let options_json = json!({"noUpdate": true}).to_string();
+
+            let res = resolve_did(setup.pool_handle, setup.wallet_handle, DID_TRUSTEE, &options_json);
+            assert_code!(ErrorCode::LedgerNotFound, res);
+        }
+
+        #[test]
+        fn indy_resolve_did_cache_works() {
+            let setup = Setup::wallet_and_pool();
+
+            let res_json1 = resolve_did(setup.pool_handle, setup.wallet_handle, DID_TRUSTEE, "{}").unwrap();
+
+            // now retrieve it from cache
+            let options_json = json!({"noUpdate": true}).to_string();
+            let res_json2 = resolve_did(setup.pool_handle, setup.wallet_handle, DID_TRUSTEE, &options_json).unwrap();
+
+            assert_eq!(res_json1, res_json2);
+        }
+
+        #[test]
+        fn indy_resolve_did_no_store_works() {
+            let setup = Setup::wallet_and_pool();
+
+            let options_json = json!({"noStore": true}).to_string();
+            resolve_did(setup.pool_handle, setup.wallet_handle, DID_TRUSTEE, &options_json).unwrap();
+
+            // it should not be present inside of cache, because of noStore option in previous request.
+            let options_json = json!({"noUpdate": true}).to_string();
+            let res = resolve_did(setup.pool_handle, setup.wallet_handle, DID_TRUSTEE, &options_json);
+            assert_code!(ErrorCode::LedgerNotFound, res);
+        }
+
+        #[test]
+        fn indy_resolve_did_works_for_invalid_did() {
+            let setup = Setup::wallet_and_pool();
+
+            let res = resolve_did(setup.pool_handle, setup.wallet_handle, INVALID_BASE58_DID, "{}");
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_purge_did_doc_cache_works() {
+            let setup = Setup::wallet_and_pool();
+
+            resolve_did(setup.pool_handle, setup.wallet_handle, DID_TRUSTEE, "{}").unwrap();
+
+            purge_did_doc_cache(setup.wallet_handle, "{}").unwrap();
+
+            let options_json = json!({"noUpdate": true}).to_string();
+            let res = resolve_did(setup.pool_handle, setup.wallet_handle, DID_TRUSTEE, &options_json);
+            assert_code!(ErrorCode::LedgerNotFound, res);
+        }
+
+        #[test]
+        fn indy_purge_did_doc_cache_older_than_1000_seconds() {
+            let setup = Setup::wallet_and_pool();
+
+            resolve_did(setup.pool_handle, setup.wallet_handle, DID_TRUSTEE, "{}").unwrap();
+
+            purge_did_doc_cache(setup.wallet_handle, &json!({"maxAge": 1000}).to_string()).unwrap();
+
+            let options_json = json!({"noUpdate": true}).to_string();
+            resolve_did(setup.pool_handle, setup.wallet_handle, DID_TRUSTEE, &options_json).unwrap();
+        }
+    }
+}
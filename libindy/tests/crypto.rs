@@ -35,6 +35,50 @@ mod high_cases {
             let verkey = crypto::create_key(setup.wallet_handle, None).unwrap();
             assert_eq!(verkey.from_base58().unwrap().len(), 32);
         }
+
+        #[test]
+        fn indy_create_key_works_for_ecdsa_crypto_types() {
+            let setup = Setup::wallet();
+
+            for crypto_type in &["secp256k1", "p256"] {
+                let verkey = crypto::create_key_with_crypto_type(setup.wallet_handle, Some(MY1_SEED), crypto_type).unwrap();
+
+                let (verkey, verkey_crypto_type) = verkey.split_at(verkey.find(':').unwrap());
+                assert_eq!(format!(":{}", crypto_type), verkey_crypto_type);
+                assert_eq!(verkey.from_base58().unwrap().len(), 33);
+            }
+        }
+
+        #[test]
+        fn indy_create_key_works_for_mnemonic() {
+            let setup = Setup::wallet();
+            let verkey = crypto::create_key_with_mnemonic(setup.wallet_handle, MNEMONIC, DERIVATION_PATH).unwrap();
+            assert_eq!(VERKEY_MNEMONIC, verkey);
+        }
+    }
+
+    mod generate_mnemonic {
+        use super::*;
+
+        #[test]
+        fn indy_generate_mnemonic_works() {
+            Setup::empty();
+
+            let mnemonic = crypto::generate_mnemonic(None).unwrap();
+            assert_eq!(24, mnemonic.split(' ').count());
+            assert_ne!(mnemonic, crypto::generate_mnemonic(None).unwrap());
+        }
+
+        #[test]
+        fn indy_generate_mnemonic_works_for_word_count() {
+            let setup = Setup::wallet();
+
+            let mnemonic = crypto::generate_mnemonic(Some(r#"{"word_count": 12}"#)).unwrap();
+            assert_eq!(12, mnemonic.split(' ').count());
+
+            let verkey = crypto::create_key_with_mnemonic(setup.wallet_handle, &mnemonic, "m/0'/1'").unwrap();
+            assert_ne!(verkey, crypto::create_key_with_mnemonic(setup.wallet_handle, &mnemonic, "m/0'/2'").unwrap());
+        }
     }
 
     mod set_key_metadata {
@@ -96,6 +140,21 @@ mod high_cases {
             assert_eq!(SIGNATURE.to_vec(), signature);
         }
 
+        #[test]
+        fn indy_crypto_sign_works_for_ecdsa_crypto_types() {
+            let setup = Setup::wallet();
+
+            for crypto_type in &["secp256k1", "p256"] {
+                let my_vk = crypto::create_key_with_crypto_type(setup.wallet_handle, None, crypto_type).unwrap();
+
+                let signature = crypto::sign(setup.wallet_handle, &my_vk, MESSAGE.as_bytes()).unwrap();
+                assert_eq!(64, signature.len());
+
+                assert!(crypto::verify(&my_vk, MESSAGE.as_bytes(), &signature).unwrap());
+                assert!(!crypto::verify(&my_vk, "other message".as_bytes(), &signature).unwrap());
+            }
+        }
+
         #[test]
         fn indy_crypto_sign_works_for_unknown_signer() {
             let setup = Setup::wallet();
@@ -127,6 +186,107 @@ mod high_cases {
         }
     }
 
+    mod sign_jws {
+        use super::*;
+
+        #[test]
+        fn indy_sign_jws_works() {
+            let setup = Setup::wallet();
+            let my_vk = crypto::create_key(setup.wallet_handle, Some(MY1_SEED)).unwrap();
+
+            let (jws_compact, jws_json) = crypto::sign_jws(setup.wallet_handle, &my_vk, MESSAGE.as_bytes(), None, false).unwrap();
+            assert_eq!(3, jws_compact.split('.').count());
+
+            let jws: serde_json::Value = serde_json::from_str(&jws_json).unwrap();
+            assert_eq!(jws_compact, format!("{}.{}.{}", jws["protected"].as_str().unwrap(), jws["payload"].as_str().unwrap(), jws["signature"].as_str().unwrap()));
+
+            assert!(crypto::verify_jws(&jws_compact, &my_vk, None).unwrap());
+            assert!(crypto::verify_jws(&jws_json, &my_vk, None).unwrap());
+        }
+
+        #[test]
+        fn indy_sign_jws_works_for_detached_payload() {
+            let setup = Setup::key();
+
+            let (jws_compact, jws_json) = crypto::sign_jws(setup.wallet_handle, &setup.verkey, MESSAGE.as_bytes(), None, true).unwrap();
+            assert!(jws_compact.contains(".."));
+
+            let jws: serde_json::Value = serde_json::from_str(&jws_json).unwrap();
+            assert!(jws["payload"].is_null());
+
+            assert!(crypto::verify_jws(&jws_compact, &setup.verkey, Some(MESSAGE.as_bytes())).unwrap());
+            assert!(crypto::verify_jws(&jws_json, &setup.verkey, Some(MESSAGE.as_bytes())).unwrap());
+            assert!(!crypto::verify_jws(&jws_compact, &setup.verkey, Some("other message".as_bytes())).unwrap());
+        }
+
+        #[test]
+        fn indy_sign_jws_works_for_header() {
+            let setup = Setup::key();
+
+            let (jws_compact, _) = crypto::sign_jws(setup.wallet_handle, &setup.verkey, MESSAGE.as_bytes(), Some(r#"{"typ":"JWT","kid":"did:example:123#key-1"}"#), false).unwrap();
+
+            assert!(crypto::verify_jws(&jws_compact, &setup.verkey, None).unwrap());
+        }
+
+        #[test]
+        fn indy_sign_jws_works_for_ecdsa_crypto_types() {
+            let setup = Setup::wallet();
+
+            for crypto_type in &["secp256k1", "p256"] {
+                let my_vk = crypto::create_key_with_crypto_type(setup.wallet_handle, None, crypto_type).unwrap();
+
+                let (jws_compact, _) = crypto::sign_jws(setup.wallet_handle, &my_vk, MESSAGE.as_bytes(), None, false).unwrap();
+                assert!(crypto::verify_jws(&jws_compact, &my_vk, None).unwrap());
+            }
+        }
+
+        #[test]
+        fn indy_sign_jws_works_for_unknown_signer() {
+            let setup = Setup::wallet();
+            let res = crypto::sign_jws(setup.wallet_handle, VERKEY, MESSAGE.as_bytes(), None, false);
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+        }
+    }
+
+    mod verify_jws {
+        use super::*;
+
+        #[test]
+        fn indy_verify_jws_works_for_other_signer() {
+            let setup = Setup::key();
+
+            let (jws_compact, _) = crypto::sign_jws(setup.wallet_handle, &setup.verkey, MESSAGE.as_bytes(), None, false).unwrap();
+
+            assert!(!crypto::verify_jws(&jws_compact, VERKEY_MY2, None).unwrap());
+        }
+
+        #[test]
+        fn indy_verify_jws_works_for_forged_kid() {
+            let setup = Setup::key();
+
+            // Attacker signs JWS with own key and puts this key to kid header
+            let attacker_vk = crypto::create_key(setup.wallet_handle, Some(MY2_SEED)).unwrap();
+            let header = json!({"kid": attacker_vk}).to_string();
+            let (jws_compact, _) = crypto::sign_jws(setup.wallet_handle, &attacker_vk, MESSAGE.as_bytes(), Some(&header), false).unwrap();
+
+            assert!(!crypto::verify_jws(&jws_compact, &setup.verkey, None).unwrap());
+        }
+
+        #[test]
+        fn indy_verify_jws_works_for_general_json_serialization() {
+            let setup = Setup::key();
+
+            let (_, jws_json) = crypto::sign_jws(setup.wallet_handle, &setup.verkey, MESSAGE.as_bytes(), None, false).unwrap();
+            let jws: serde_json::Value = serde_json::from_str(&jws_json).unwrap();
+            let jws_general = json!({
+                "payload": jws["payload"],
+                "signatures": [{"protected": jws["protected"], "signature": jws["signature"]}]
+            }).to_string();
+
+            assert!(crypto::verify_jws(&jws_general, &setup.verkey, None).unwrap());
+        }
+    }
+
     mod auth_crypt {
         use super::*;
 
@@ -326,6 +486,91 @@ mod high_cases {
             assert_code!(ErrorCode::WalletItemNotFound, res);
         }
     }
+
+    mod pack_message_with_options {
+        use super::*;
+
+        #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+        pub struct UnpackMessage {
+            pub message: String,
+            pub sender_verkey: Option<String>,
+            pub recipient_verkey: String,
+        }
+
+        #[test]
+        fn indy_pack_message_with_options_works_for_didcomm_v2_authcrypt() {
+            let sender_setup = Setup::key();
+            let receiver_setup = Setup::key();
+
+            let rec_key_vec = vec![VERKEY_TRUSTEE, &receiver_setup.verkey];
+            let receiver_keys = serde_json::to_string(&rec_key_vec).unwrap();
+            let pack_message = crypto::pack_message_with_options(sender_setup.wallet_handle, AGENT_MESSAGE.as_bytes(), &receiver_keys,
+                                                                 Some(&sender_setup.verkey), PACK_OPTIONS_DIDCOMM_V2).unwrap();
+
+            let jwe: serde_json::Value = serde_json::from_slice(pack_message.as_slice()).unwrap();
+            assert_eq!(2, jwe["recipients"].as_array().unwrap().len());
+            assert!(jwe["recipients"][0]["header"]["kid"].as_str().unwrap().starts_with("did:key:z6Mk"));
+
+            let res = crypto::unpack_message(receiver_setup.wallet_handle, pack_message.as_slice()).unwrap();
+            let res_serialized: UnpackMessage = serde_json::from_slice(res.as_slice()).unwrap();
+
+            assert_eq!(res_serialized.message, AGENT_MESSAGE.to_string());
+            assert_eq!(res_serialized.sender_verkey, Some(sender_setup.verkey));
+            assert_eq!(res_serialized.recipient_verkey, receiver_setup.verkey);
+        }
+
+        #[test]
+        fn indy_pack_message_with_options_works_for_didcomm_v2_anoncrypt() {
+            let sender_setup = Setup::wallet();
+            let receiver_setup = Setup::key();
+
+            let rec_key_vec = vec![VERKEY_TRUSTEE, &receiver_setup.verkey];
+            let receiver_keys = serde_json::to_string(&rec_key_vec).unwrap();
+            let pack_message = crypto::pack_message_with_options(sender_setup.wallet_handle, AGENT_MESSAGE.as_bytes(), &receiver_keys,
+                                                                 None, PACK_OPTIONS_DIDCOMM_V2).unwrap();
+
+            let res = crypto::unpack_message(receiver_setup.wallet_handle, pack_message.as_slice()).unwrap();
+            let res_serialized: UnpackMessage = serde_json::from_slice(res.as_slice()).unwrap();
+
+            assert_eq!(res_serialized.message, AGENT_MESSAGE.to_string());
+            assert_eq!(res_serialized.sender_verkey, None);
+            assert_eq!(res_serialized.recipient_verkey, receiver_setup.verkey);
+        }
+
+        #[test]
+        fn indy_pack_message_with_options_works_for_legacy_format() {
+            let sender_setup = Setup::key();
+            let receiver_setup = Setup::key();
+
+            let rec_key_vec = vec![&receiver_setup.verkey];
+            let receiver_keys = serde_json::to_string(&rec_key_vec).unwrap();
+            let pack_message = crypto::pack_message_with_options(sender_setup.wallet_handle, AGENT_MESSAGE.as_bytes(), &receiver_keys,
+                                                                 Some(&sender_setup.verkey), r#"{"format":"legacy"}"#).unwrap();
+
+            let jwe: serde_json::Value = serde_json::from_slice(pack_message.as_slice()).unwrap();
+            assert!(jwe["recipients"].is_null());
+
+            let res = crypto::unpack_message(receiver_setup.wallet_handle, pack_message.as_slice()).unwrap();
+            let res_serialized: UnpackMessage = serde_json::from_slice(res.as_slice()).unwrap();
+
+            assert_eq!(res_serialized.message, AGENT_MESSAGE.to_string());
+            assert_eq!(res_serialized.sender_verkey, Some(sender_setup.verkey));
+        }
+
+        #[test]
+        fn indy_unpack_message_for_didcomm_v2_fails_no_matching_key() {
+            let sender_setup = Setup::key();
+            let receiver_setup = Setup::key();
+
+            let rec_key_vec = vec![VERKEY_TRUSTEE];
+            let receiver_keys = serde_json::to_string(&rec_key_vec).unwrap();
+            let pack_message = crypto::pack_message_with_options(sender_setup.wallet_handle, AGENT_MESSAGE.as_bytes(), &receiver_keys,
+                                                                 Some(&sender_setup.verkey), PACK_OPTIONS_DIDCOMM_V2).unwrap();
+
+            let res = crypto::unpack_message(receiver_setup.wallet_handle, pack_message.as_slice());
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+        }
+    }
 }
 
 #[cfg(not(feature = "only_high_cases"))]
@@ -343,6 +588,34 @@ mod medium_cases {
             let res = crypto::create_key(INVALID_WALLET_HANDLE, None);
             assert_code!(ErrorCode::WalletInvalidHandle, res);
         }
+
+        #[test]
+        fn indy_create_key_works_for_invalid_mnemonic() {
+            let setup = Setup::wallet();
+
+            let res = crypto::create_key_with_mnemonic(setup.wallet_handle, &MNEMONIC.replace("about", "abandon"), DERIVATION_PATH);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_create_key_works_for_not_hardened_derivation_path() {
+            let setup = Setup::wallet();
+
+            let res = crypto::create_key_with_mnemonic(setup.wallet_handle, MNEMONIC, "m/0");
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+    }
+
+    mod generate_mnemonic {
+        use super::*;
+
+        #[test]
+        fn indy_generate_mnemonic_works_for_unsupported_word_count() {
+            Setup::empty();
+
+            let res = crypto::generate_mnemonic(Some(r#"{"word_count": 13}"#));
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
     }
 
     mod set_key_metadata {
@@ -423,6 +696,43 @@ mod medium_cases {
         }
     }
 
+    mod sign_jws {
+        use super::*;
+
+        #[test]
+        fn indy_sign_jws_fails_for_other_alg_in_header() {
+            let setup = Setup::key();
+            let res = crypto::sign_jws(setup.wallet_handle, &setup.verkey, MESSAGE.as_bytes(), Some(r#"{"alg":"ES256K"}"#), false);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_sign_jws_fails_for_invalid_header() {
+            let setup = Setup::key();
+            let res = crypto::sign_jws(setup.wallet_handle, &setup.verkey, MESSAGE.as_bytes(), Some("[]"), false);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+    }
+
+    mod verify_jws {
+        use super::*;
+
+        #[test]
+        fn indy_verify_jws_fails_for_missed_detached_payload() {
+            let setup = Setup::key();
+            let (jws_compact, _) = crypto::sign_jws(setup.wallet_handle, &setup.verkey, MESSAGE.as_bytes(), None, true).unwrap();
+
+            let res = crypto::verify_jws(&jws_compact, &setup.verkey, None);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_verify_jws_fails_for_invalid_compact_serialization() {
+            let res = crypto::verify_jws("abc.def", VERKEY_MY1, None);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+    }
+
     mod auth_crypt {
         use super::*;
 
@@ -433,6 +743,17 @@ mod medium_cases {
             crypto::auth_crypt(setup.wallet_handle, &verkey, VERKEY_MY2, MESSAGE.as_bytes()).unwrap();
         }
 
+        #[test]
+        fn indy_crypto_auth_crypt_works_for_ecdsa_crypto_type() {
+            let setup = Setup::wallet();
+
+            let my_vk = crypto::create_key_with_crypto_type(setup.wallet_handle, None, "secp256k1").unwrap();
+            let their_vk = crypto::create_key_with_crypto_type(setup.wallet_handle, None, "secp256k1").unwrap();
+
+            let res = crypto::auth_crypt(setup.wallet_handle, &my_vk, &their_vk, MESSAGE.as_bytes());
+            assert_code!(ErrorCode::UnknownCryptoTypeError, res);
+        }
+
         #[test]
         fn indy_crypto_auth_crypt_works_for_invalid_wallet_handle() {
             let setup = Setup::did();
@@ -489,6 +810,16 @@ mod medium_cases {
             let res = crypto::anon_crypt(INVALID_BASE58_VERKEY, &MESSAGE.as_bytes());
             assert_code!(ErrorCode::CommonInvalidStructure, res);
         }
+
+        #[test]
+        fn indy_anon_crypt_works_for_ecdsa_crypto_type() {
+            let setup = Setup::wallet();
+
+            let their_vk = crypto::create_key_with_crypto_type(setup.wallet_handle, None, "p256").unwrap();
+
+            let res = crypto::anon_crypt(&their_vk, &MESSAGE.as_bytes());
+            assert_code!(ErrorCode::UnknownCryptoTypeError, res);
+        }
     }
 
     mod anon_decrypt {
@@ -588,6 +919,48 @@ mod medium_cases {
             assert!(res.is_ok());
         }
     }
+
+    mod pack_message_with_options {
+        use super::*;
+
+        #[test]
+        fn indy_pack_message_with_options_fails_for_unknown_format() {
+            let setup = Setup::key();
+            let rec_key_vec = vec![VERKEY_MY1];
+            let receiver_keys = serde_json::to_string(&rec_key_vec).unwrap();
+            let res = crypto::pack_message_with_options(setup.wallet_handle, AGENT_MESSAGE.as_bytes(), &receiver_keys,
+                                                        Some(&setup.verkey), r#"{"format":"unknown"}"#);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_pack_message_with_options_fails_for_ecdsa_sender_key() {
+            let setup = Setup::wallet();
+            let sender_vk = crypto::create_key_with_crypto_type(setup.wallet_handle, None, "secp256k1").unwrap();
+            let rec_key_vec = vec![VERKEY_MY1];
+            let receiver_keys = serde_json::to_string(&rec_key_vec).unwrap();
+            let res = crypto::pack_message_with_options(setup.wallet_handle, AGENT_MESSAGE.as_bytes(), &receiver_keys,
+                                                        Some(&sender_vk), PACK_OPTIONS_DIDCOMM_V2);
+            assert_code!(ErrorCode::UnknownCryptoTypeError, res);
+        }
+
+        #[test]
+        fn indy_unpack_message_for_didcomm_v2_fails_for_tampered_ciphertext() {
+            let sender_setup = Setup::key();
+            let receiver_setup = Setup::key();
+
+            let rec_key_vec = vec![&receiver_setup.verkey];
+            let receiver_keys = serde_json::to_string(&rec_key_vec).unwrap();
+            let pack_message = crypto::pack_message_with_options(sender_setup.wallet_handle, AGENT_MESSAGE.as_bytes(), &receiver_keys,
+                                                                 Some(&sender_setup.verkey), PACK_OPTIONS_DIDCOMM_V2).unwrap();
+
+            let mut jwe: serde_json::Value = serde_json::from_slice(pack_message.as_slice()).unwrap();
+            jwe["tag"] = serde_json::Value::String("AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA".to_string());
+
+            let res = crypto::unpack_message(receiver_setup.wallet_handle, jwe.to_string().as_bytes());
+            assert_code!(ErrorCode::UnknownCryptoTypeError, res);
+        }
+    }
 }
 
 #[cfg(not(feature = "only_high_cases"))]
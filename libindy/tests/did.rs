@@ -82,6 +82,14 @@ mod high_cases {
             let received_verkey = did::key_for_did(-1, setup.wallet_handle, DID_V1).unwrap();
             assert_eq!(VERKEY, received_verkey);
         }
+
+        #[test]
+        fn indy_key_for_did_works_for_did_key() {
+            let setup = Setup::wallet();
+
+            let received_verkey = did::key_for_did(-1, setup.wallet_handle, DID_KEY).unwrap();
+            assert_eq!(VERKEY, received_verkey);
+        }
     }
 
     mod key_for_local_did {
@@ -120,6 +128,22 @@ mod high_cases {
             let received_verkey = did::key_for_local_did(setup.wallet_handle, &setup.did).unwrap();
             assert_eq!(setup.verkey, received_verkey);
         }
+
+        #[test]
+        fn indy_key_for_local_did_works_for_did_key() {
+            let setup = Setup::wallet();
+
+            let received_verkey = did::key_for_local_did(setup.wallet_handle, DID_KEY).unwrap();
+            assert_eq!(VERKEY, received_verkey);
+        }
+
+        #[test]
+        fn indy_key_for_local_did_works_for_did_peer() {
+            let setup = Setup::wallet();
+
+            let received_verkey = did::key_for_local_did(setup.wallet_handle, &DID_KEY.replace("did:key:", "did:peer:0")).unwrap();
+            assert_eq!(VERKEY, received_verkey);
+        }
     }
 
     mod set_endpoint_for_did {
@@ -424,6 +448,31 @@ mod high_cases {
             assert_eq!(my_verkey_3, did::key_for_local_did(setup.wallet_handle, &my_did_3).unwrap());
         }
 
+        #[test]
+        fn indy_create_my_did_works_for_key_method() {
+            let setup = Setup::wallet();
+
+            let my_did_json = json!({"method_name": "key", "seed": "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"}).to_string();
+            let (my_did, my_verkey) = did::create_my_did(setup.wallet_handle, &my_did_json).unwrap();
+
+            assert_eq!(DID_KEY, my_did);
+            assert_eq!(VERKEY, my_verkey);
+        }
+
+        #[test]
+        fn indy_create_my_did_works_for_peer_method() {
+            let setup = Setup::wallet();
+
+            let my_did_json = json!({"method_name": "peer"}).to_string();
+            let (my_did, _) = did::create_my_did(setup.wallet_handle, &my_did_json).unwrap();
+            assert!(my_did.starts_with("did:peer:0z6Mk"));
+
+            let my_did_json = json!({"method_name": "peer", "peer_numalgo": 2}).to_string();
+            let (my_did, my_verkey) = did::create_my_did(setup.wallet_handle, &my_did_json).unwrap();
+            assert!(my_did.starts_with("did:peer:2.Ez6LS"));
+            assert_eq!(my_verkey, did::key_for_local_did(setup.wallet_handle, &my_did).unwrap());
+        }
+
         #[test]
         fn indy_create_my_did_works_with_seed() {
             let setup = Setup::wallet();
@@ -433,6 +482,20 @@ mod high_cases {
             assert_eq!(my_verkey, VERKEY_MY1);
         }
 
+        #[test]
+        fn indy_create_my_did_works_with_mnemonic() {
+            let setup = Setup::wallet();
+
+            let my_did_json = json!({"mnemonic": {"phrase": MNEMONIC, "derivation_path": DERIVATION_PATH}}).to_string();
+            let (my_did, my_verkey) = did::create_my_did(setup.wallet_handle, &my_did_json).unwrap();
+            assert_eq!(my_did, DID_MNEMONIC);
+            assert_eq!(my_verkey, VERKEY_MNEMONIC);
+
+            let my_did_json = json!({"mnemonic": {"phrase": MNEMONIC, "passphrase": "passphrase", "derivation_path": DERIVATION_PATH}}).to_string();
+            let (my_did, _) = did::create_my_did(setup.wallet_handle, &my_did_json).unwrap();
+            assert_ne!(my_did, DID_MNEMONIC);
+        }
+
         #[test]
         fn indy_create_my_did_works_with_hex_seed() {
             let setup = Setup::wallet();
@@ -861,6 +924,42 @@ mod medium_cases {
             let res = did::create_my_did(INVALID_WALLET_HANDLE, "{}");
             assert_code!(ErrorCode::WalletInvalidHandle, res);
         }
+
+        #[test]
+        fn indy_create_my_did_works_for_peer_numalgo_without_peer_method() {
+            let setup = Setup::wallet();
+
+            let res = did::create_my_did(setup.wallet_handle, r#"{"method_name":"key","peer_numalgo":2}"#);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_create_my_did_works_for_unsupported_peer_numalgo() {
+            let setup = Setup::wallet();
+
+            let res = did::create_my_did(setup.wallet_handle, r#"{"method_name":"peer","peer_numalgo":1}"#);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_create_my_did_works_for_peer_numalgo_2_and_ecdsa_crypto_type() {
+            let setup = Setup::wallet();
+
+            let res = did::create_my_did(setup.wallet_handle, r#"{"method_name":"peer","peer_numalgo":2,"crypto_type":"p256"}"#);
+            assert_code!(ErrorCode::UnknownCryptoTypeError, res);
+        }
+
+        #[test]
+        fn indy_create_my_did_works_for_ecdsa_crypto_type_and_unqualified_did() {
+            let setup = Setup::wallet();
+
+            let res = did::create_my_did(setup.wallet_handle, r#"{"crypto_type":"secp256k1"}"#);
+            assert_code!(ErrorCode::UnknownCryptoTypeError, res);
+
+            let (did, verkey) = did::create_my_did(setup.wallet_handle, r#"{"method_name":"key","crypto_type":"secp256k1"}"#).unwrap();
+            assert!(did.starts_with("did:key:"));
+            assert!(verkey.ends_with(":secp256k1"));
+        }
     }
 
     mod replace_keys_start {
@@ -882,6 +981,16 @@ mod medium_cases {
             assert_eq!(new_verkey, VERKEY);
             assert_ne!(setup.verkey, new_verkey);
         }
+
+        #[test]
+        fn indy_replace_keys_start_works_for_did_key() {
+            let setup = Setup::wallet();
+
+            let (my_did, _) = did::create_my_did(setup.wallet_handle, r#"{"method_name":"key"}"#).unwrap();
+
+            let res = did::replace_keys_start(setup.wallet_handle, &my_did, "{}");
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
     }
 
     mod replace_keys_apply {
@@ -907,6 +1016,24 @@ mod medium_cases {
             did::store_their_did(setup.wallet_handle, &identity_json).unwrap();
         }
 
+        #[test]
+        fn indy_create_my_did_works_for_mnemonic_and_seed() {
+            let setup = Setup::wallet();
+
+            let my_did_json = json!({"seed": MY1_SEED, "mnemonic": {"phrase": MNEMONIC, "derivation_path": DERIVATION_PATH}}).to_string();
+            let res = did::create_my_did(setup.wallet_handle, &my_did_json);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_create_my_did_works_for_mnemonic_and_ecdsa_crypto_type() {
+            let setup = Setup::wallet();
+
+            let my_did_json = json!({"crypto_type": "secp256k1", "mnemonic": {"phrase": MNEMONIC, "derivation_path": DERIVATION_PATH}}).to_string();
+            let res = did::create_my_did(setup.wallet_handle, &my_did_json);
+            assert_code!(ErrorCode::UnknownCryptoTypeError, res);
+        }
+
         #[test]
         fn indy_create_my_did_works_for_invalid_seed() {
             let setup = Setup::wallet();
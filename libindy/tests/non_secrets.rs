@@ -50,6 +50,99 @@ mod high_cases {
         }
     }
 
+    mod add_record_with_options {
+        use super::*;
+
+        #[test]
+        fn indy_add_wallet_record_with_options_works() {
+            let setup = Setup::wallet();
+
+            add_wallet_record_with_options(setup.wallet_handle, TYPE, ID, VALUE, Some(TAGS), r#"{"expiresIn":3600}"#).unwrap();
+            check_record_field(setup.wallet_handle, TYPE, ID, "value", VALUE);
+            check_record_field(setup.wallet_handle, TYPE, ID, "tags", TAGS);
+        }
+
+        #[test]
+        fn indy_add_wallet_record_with_options_works_for_expired_record() {
+            let setup = Setup::wallet();
+
+            add_wallet_record_with_options(setup.wallet_handle, TYPE, ID, VALUE, None, r#"{"expiresIn":0}"#).unwrap();
+
+            let res = get_wallet_record(setup.wallet_handle, TYPE, ID, OPTIONS_EMPTY);
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+
+            let search_handle = open_wallet_search(setup.wallet_handle, TYPE, QUERY_EMPTY, OPTIONS_FULL).unwrap();
+            let search_records = fetch_wallet_search_next_records(setup.wallet_handle, search_handle, 5).unwrap();
+            let search_records: SearchRecords = serde_json::from_str(&search_records).unwrap();
+            assert_eq!(Some(0), search_records.total_count);
+            close_wallet_search(search_handle).unwrap();
+
+            add_wallet_record(setup.wallet_handle, TYPE, ID, VALUE_2, None).unwrap();
+            check_record_field(setup.wallet_handle, TYPE, ID, "value", VALUE_2);
+        }
+
+        #[test]
+        fn indy_add_wallet_record_with_options_works_for_plugged_wallet() {
+            let setup = Setup::plugged_wallet();
+
+            add_wallet_record_with_options(setup.wallet_handle, TYPE, ID, VALUE, None, r#"{"expiresIn":0}"#).unwrap();
+
+            let res = get_wallet_record(setup.wallet_handle, TYPE, ID, OPTIONS_EMPTY);
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+        }
+    }
+
+    mod update_record_value_with_options {
+        use super::*;
+
+        #[test]
+        fn indy_update_record_value_with_options_works_for_expiry() {
+            let setup = Setup::wallet();
+
+            add_wallet_record(setup.wallet_handle, TYPE, ID, VALUE, None).unwrap();
+
+            update_wallet_record_value_with_options(setup.wallet_handle, TYPE, ID, VALUE_2, r#"{"expiresIn":0}"#).unwrap();
+
+            let res = get_wallet_record(setup.wallet_handle, TYPE, ID, OPTIONS_EMPTY);
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+        }
+
+        #[test]
+        fn indy_update_record_value_with_options_works_for_keeping_expiry() {
+            let setup = Setup::wallet();
+
+            add_wallet_record_with_options(setup.wallet_handle, TYPE, ID, VALUE, None, r#"{"expiresIn":3600}"#).unwrap();
+
+            update_wallet_record_value_with_options(setup.wallet_handle, TYPE, ID, VALUE_2, OPTIONS_EMPTY).unwrap();
+            check_record_field(setup.wallet_handle, TYPE, ID, "value", VALUE_2);
+            check_record_field(setup.wallet_handle, TYPE, ID, "tags", TAGS_EMPTY);
+        }
+
+        #[test]
+        fn indy_update_record_value_with_options_works_for_removing_expiry() {
+            let setup = Setup::wallet();
+
+            add_wallet_record_with_options(setup.wallet_handle, TYPE, ID, VALUE, None, r#"{"expiresIn":3600}"#).unwrap();
+
+            update_wallet_record_value_with_options(setup.wallet_handle, TYPE, ID, VALUE_2, r#"{"expiresIn":null}"#).unwrap();
+
+            let search_handle = open_wallet_all_records_search(setup.wallet_handle).unwrap();
+            let search_records = fetch_wallet_search_next_records(setup.wallet_handle, search_handle, 5).unwrap();
+            let search_records: SearchRecords = serde_json::from_str(&search_records).unwrap();
+            assert_eq!(vec![WalletRecord { id: ID.to_string(), type_: Some(TYPE.to_string()), value: Some(VALUE_2.to_string()), tags: Some(HashMap::new()) }],
+                       search_records.records.unwrap());
+            close_wallet_search(search_handle).unwrap();
+        }
+
+        #[test]
+        fn indy_update_record_value_with_options_works_for_not_found_record() {
+            let setup = Setup::wallet();
+
+            let res = update_wallet_record_value_with_options(setup.wallet_handle, TYPE, ID, VALUE, r#"{"expiresIn":3600}"#);
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+        }
+    }
+
     mod update_record_value {
         use super::*;
 
@@ -232,6 +325,76 @@ mod high_cases {
         }
     }
 
+    mod batch_execute {
+        use super::*;
+
+        #[test]
+        fn indy_wallet_batch_execute_works() {
+            let setup = Setup::wallet();
+
+            add_wallet_record(setup.wallet_handle, TYPE, ID, VALUE, Some(TAGS_EMPTY)).unwrap();
+
+            let operations = json!([
+                {"operation": "add", "type": TYPE, "id": ID_2, "value": VALUE_2, "tags": {"tagName1": "str1"}},
+                {"operation": "updateValue", "type": TYPE, "id": ID, "value": VALUE_3},
+                {"operation": "updateTags", "type": TYPE, "id": ID, "tags": {"tagName1": "str2"}},
+                {"operation": "addTags", "type": TYPE, "id": ID_2, "tags": {"~tagName2": "str3"}},
+                {"operation": "deleteTags", "type": TYPE, "id": ID_2, "tagNames": ["tagName1"]},
+            ]).to_string();
+
+            wallet_batch_execute(setup.wallet_handle, &operations).unwrap();
+
+            let record = get_wallet_record(setup.wallet_handle, TYPE, ID, OPTIONS_FULL).unwrap();
+            let record: WalletRecord = serde_json::from_str(&record).unwrap();
+            let expected_tags: HashMap<String, String> = serde_json::from_str(r#"{"tagName1": "str2"}"#).unwrap();
+            assert_eq!(WalletRecord { id: ID.to_string(), type_: Some(TYPE.to_string()), value: Some(VALUE_3.to_string()), tags: Some(expected_tags) }, record);
+
+            let record = get_wallet_record(setup.wallet_handle, TYPE, ID_2, OPTIONS_FULL).unwrap();
+            let record: WalletRecord = serde_json::from_str(&record).unwrap();
+            let expected_tags: HashMap<String, String> = serde_json::from_str(r#"{"~tagName2": "str3"}"#).unwrap();
+            assert_eq!(WalletRecord { id: ID_2.to_string(), type_: Some(TYPE.to_string()), value: Some(VALUE_2.to_string()), tags: Some(expected_tags) }, record);
+        }
+
+        #[test]
+        fn indy_wallet_batch_execute_works_for_delete() {
+            let setup = Setup::wallet();
+
+            add_wallet_record(setup.wallet_handle, TYPE, ID, VALUE, None).unwrap();
+
+            let operations = json!([
+                {"operation": "delete", "type": TYPE, "id": ID},
+            ]).to_string();
+
+            wallet_batch_execute(setup.wallet_handle, &operations).unwrap();
+
+            let res = get_wallet_record(setup.wallet_handle, TYPE, ID, OPTIONS_EMPTY);
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+        }
+
+        #[test]
+        fn indy_wallet_batch_execute_works_for_rollback_on_error() {
+            let setup = Setup::wallet();
+
+            add_wallet_record(setup.wallet_handle, TYPE, ID, VALUE, None).unwrap();
+
+            let operations = json!([
+                {"operation": "add", "type": TYPE, "id": ID_2, "value": VALUE_2},
+                {"operation": "updateValue", "type": TYPE, "id": ID, "value": VALUE_3},
+                {"operation": "updateValue", "type": TYPE, "id": ID_3, "value": VALUE_3},
+            ]).to_string();
+
+            let res = wallet_batch_execute(setup.wallet_handle, &operations);
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+
+            let record = get_wallet_record(setup.wallet_handle, TYPE, ID, OPTIONS_EMPTY).unwrap();
+            let record: WalletRecord = serde_json::from_str(&record).unwrap();
+            assert_eq!(Some(VALUE.to_string()), record.value);
+
+            let res = get_wallet_record(setup.wallet_handle, TYPE, ID_2, OPTIONS_EMPTY);
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+        }
+    }
+
     mod get_record {
         use super::*;
 
@@ -823,6 +986,117 @@ mod high_cases {
             }
         }
 
+        mod sorting {
+            use super::*;
+
+            fn check_search_records_order(search_records: &str, expected_records: Vec<WalletRecord>) {
+                let search_records: SearchRecords = serde_json::from_str(&search_records).unwrap();
+                assert_eq!(search_records.records.unwrap(), expected_records);
+            }
+
+            #[test]
+            fn indy_wallet_search_for_sort_by_plain_tag() {
+                const SEARCH_WALLET_CONFIG: &str = r#"{"id":"indy_wallet_search_for_sort_by_plain_tag"}"#;
+                let wallet_handle = setup("indy_wallet_search_for_sort_by_plain_tag", SEARCH_WALLET_CONFIG);
+
+                let options = r#"{"retrieveType":true, "retrieveTags":true, "sortBy":[{"tagName":"~tagName3", "order":"desc"}]}"#;
+
+                let search_handle = open_wallet_search(wallet_handle, TYPE, QUERY_EMPTY, options).unwrap();
+
+                let search_records = fetch_wallet_search_next_records(wallet_handle, search_handle, 5).unwrap();
+
+                check_search_records_order(&search_records, vec![record_1(),
+                                                                 record_5(),
+                                                                 record_4(),
+                                                                 record_2(),
+                                                                 record_3()]);
+
+                tear_down(wallet_handle, search_handle);
+                cleanup_wallet("indy_wallet_search_for_sort_by_plain_tag");
+            }
+
+            #[test]
+            fn indy_wallet_search_for_skip_and_limit() {
+                const SEARCH_WALLET_CONFIG: &str = r#"{"id":"indy_wallet_search_for_skip_and_limit"}"#;
+                let wallet_handle = setup("indy_wallet_search_for_skip_and_limit", SEARCH_WALLET_CONFIG);
+
+                let options = r#"{"retrieveType":true, "retrieveTags":true, "retrieveTotalCount":true, "sortBy":[{"tagName":"~tagName3"}], "skip":1, "limit":3}"#;
+
+                let search_handle = open_wallet_search(wallet_handle, TYPE, QUERY_EMPTY, options).unwrap();
+
+                let search_records = fetch_wallet_search_next_records(wallet_handle, search_handle, 5).unwrap();
+
+                let search_records_parsed: SearchRecords = serde_json::from_str(&search_records).unwrap();
+                assert_eq!(Some(5), search_records_parsed.total_count);
+
+                check_search_records_order(&search_records, vec![record_2(),
+                                                                 record_4(),
+                                                                 record_5()]);
+
+                tear_down(wallet_handle, search_handle);
+                cleanup_wallet("indy_wallet_search_for_skip_and_limit");
+            }
+
+            #[test]
+            fn indy_wallet_search_for_sort_by_encrypted_tag() {
+                const SEARCH_WALLET_CONFIG: &str = r#"{"id":"indy_wallet_search_for_sort_by_encrypted_tag"}"#;
+                let wallet_handle = setup("indy_wallet_search_for_sort_by_encrypted_tag", SEARCH_WALLET_CONFIG);
+
+                let options = r#"{"sortBy":[{"tagName":"tagName1"}]}"#;
+
+                let res = open_wallet_search(wallet_handle, TYPE, QUERY_EMPTY, options);
+                assert_code!(ErrorCode::WalletQueryError, res);
+
+                wallet::close_wallet(wallet_handle).unwrap();
+                cleanup_wallet("indy_wallet_search_for_sort_by_encrypted_tag");
+            }
+        }
+
+        mod all_records {
+            use super::*;
+
+            #[test]
+            fn indy_wallet_all_records_search_works() {
+                const SEARCH_WALLET_CONFIG: &str = r#"{"id":"indy_wallet_all_records_search_works"}"#;
+                let wallet_handle = setup("indy_wallet_all_records_search_works", SEARCH_WALLET_CONFIG);
+
+                add_wallet_record(wallet_handle, TYPE_2, ID, VALUE, Some(TAGS)).unwrap();
+
+                let search_handle = open_wallet_all_records_search(wallet_handle).unwrap();
+
+                let search_records = fetch_wallet_search_next_records(wallet_handle, search_handle, 10).unwrap();
+
+                let record_of_type_2 = WalletRecord { id: ID.to_string(), type_: Some(TYPE_2.to_string()), value: Some(VALUE.to_string()), tags: Some(tags_1()) };
+
+                let search_records: SearchRecords = serde_json::from_str(&search_records).unwrap();
+                let mut records = search_records.records.unwrap();
+                records.sort_by_key(|record| (record.id.to_string(), record.type_.clone()));
+
+                assert_eq!(records, vec![record_1(),
+                                         record_of_type_2,
+                                         record_2(),
+                                         record_3(),
+                                         record_4(),
+                                         record_5()]);
+
+                tear_down(wallet_handle, search_handle);
+                cleanup_wallet("indy_wallet_all_records_search_works");
+            }
+
+            #[test]
+            fn indy_wallet_all_records_search_works_for_empty_wallet() {
+                let setup = Setup::wallet();
+
+                let search_handle = open_wallet_all_records_search(setup.wallet_handle).unwrap();
+
+                let search_records = fetch_wallet_search_next_records(setup.wallet_handle, search_handle, 10).unwrap();
+                let search_records: SearchRecords = serde_json::from_str(&search_records).unwrap();
+                assert!(search_records.records.is_none());
+
+                close_wallet_search(search_handle).unwrap();
+            }
+        }
+
         mod close {
             use super::*;
 
@@ -855,6 +1129,72 @@ mod high_cases {
             }
         }
     }
+
+    mod aggregate {
+        use super::*;
+
+        fn setup(name: &str, wallet_config: &str) -> WalletHandle {
+            init_non_secret_test_wallet(name, wallet_config);
+            wallet::open_wallet(wallet_config, WALLET_CREDENTIALS).unwrap()
+        }
+
+        #[test]
+        fn indy_wallet_aggregate_works() {
+            const AGGREGATE_WALLET_CONFIG: &str = r#"{"id":"indy_wallet_aggregate_works"}"#;
+            let wallet_handle = setup("indy_wallet_aggregate_works", AGGREGATE_WALLET_CONFIG);
+
+            let result = wallet_aggregate(wallet_handle, TYPE, QUERY_EMPTY, r#"{"groupBy":["~tagName3"]}"#).unwrap();
+            let result: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+            assert_eq!(json!({
+                "count": 5,
+                "groups": [
+                    {"tags": {"~tagName3": null}, "count": 1},
+                    {"tags": {"~tagName3": "2"}, "count": 1},
+                    {"tags": {"~tagName3": "5"}, "count": 1},
+                    {"tags": {"~tagName3": "6"}, "count": 1},
+                    {"tags": {"~tagName3": "8"}, "count": 1},
+                ]
+            }), result);
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            cleanup_wallet("indy_wallet_aggregate_works");
+        }
+
+        #[test]
+        fn indy_wallet_aggregate_works_for_query() {
+            const AGGREGATE_WALLET_CONFIG: &str = r#"{"id":"indy_wallet_aggregate_works_for_query"}"#;
+            let wallet_handle = setup("indy_wallet_aggregate_works_for_query", AGGREGATE_WALLET_CONFIG);
+
+            let result = wallet_aggregate(wallet_handle, TYPE, r#"{"tagName1":"str1"}"#, r#"{"groupBy":["~tagName2"]}"#).unwrap();
+            let result: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+            assert_eq!(json!({
+                "count": 2,
+                "groups": [
+                    {"tags": {"~tagName2": null}, "count": 1},
+                    {"tags": {"~tagName2": "5"}, "count": 1},
+                ]
+            }), result);
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            cleanup_wallet("indy_wallet_aggregate_works_for_query");
+        }
+
+        #[test]
+        fn indy_wallet_aggregate_works_for_total_count() {
+            const AGGREGATE_WALLET_CONFIG: &str = r#"{"id":"indy_wallet_aggregate_works_for_total_count"}"#;
+            let wallet_handle = setup("indy_wallet_aggregate_works_for_total_count", AGGREGATE_WALLET_CONFIG);
+
+            let result = wallet_aggregate(wallet_handle, TYPE, QUERY_EMPTY, "{}").unwrap();
+            let result: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+            assert_eq!(json!({"count": 5, "groups": []}), result);
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            cleanup_wallet("indy_wallet_aggregate_works_for_total_count");
+        }
+    }
 }
 
 #[cfg(not(feature = "only_high_cases"))]
@@ -928,6 +1268,56 @@ mod medium_cases {
         }
     }
 
+    mod add_record_with_options {
+        use super::*;
+
+        #[test]
+        fn indy_add_wallet_record_with_options_works_for_invalid_options() {
+            let setup = Setup::wallet();
+
+            let res = add_wallet_record_with_options(setup.wallet_handle, TYPE, ID, VALUE, None, r#"{"expiresIn":"soon"}"#);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_add_wallet_record_with_options_works_for_reserved_tag() {
+            let setup = Setup::wallet();
+
+            let res = add_wallet_record_with_options(setup.wallet_handle, TYPE, ID, VALUE, Some(r#"{"~indy_expires_at":"1"}"#), OPTIONS_EMPTY);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_add_wallet_record_with_options_works_for_invalid_handle() {
+            Setup::empty();
+
+            let res = add_wallet_record_with_options(INVALID_WALLET_HANDLE, TYPE, ID, VALUE, None, OPTIONS_EMPTY);
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+    }
+
+    mod update_record_value_with_options {
+        use super::*;
+
+        #[test]
+        fn indy_update_record_value_with_options_works_for_invalid_options() {
+            let setup = Setup::wallet();
+
+            add_wallet_record(setup.wallet_handle, TYPE, ID, VALUE, None).unwrap();
+
+            let res = update_wallet_record_value_with_options(setup.wallet_handle, TYPE, ID, VALUE_2, r#"{"expiresIn":-1}"#);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_update_record_value_with_options_works_for_invalid_type() {
+            let setup = Setup::wallet();
+
+            let res = update_wallet_record_value_with_options(setup.wallet_handle, FORBIDDEN_TYPE, ID, VALUE, OPTIONS_EMPTY);
+            assert_code!(ErrorCode::WalletAccessFailed, res);
+        }
+    }
+
     mod update_record_value {
         use super::*;
 
@@ -1121,6 +1511,42 @@ mod medium_cases {
         }
     }
 
+    mod batch_execute {
+        use super::*;
+
+        #[test]
+        fn indy_wallet_batch_execute_works_for_invalid_handle() {
+            Setup::empty();
+
+            let res = wallet_batch_execute(INVALID_WALLET_HANDLE, "[]");
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+
+        #[test]
+        fn indy_wallet_batch_execute_works_for_invalid_operations() {
+            let setup = Setup::wallet();
+
+            let res = wallet_batch_execute(setup.wallet_handle, r#"[{"operation": "unknown", "type": "TestType", "id": "RecordId"}]"#);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_wallet_batch_execute_works_for_invalid_type() {
+            let setup = Setup::wallet();
+
+            let operations = json!([
+                {"operation": "add", "type": TYPE, "id": ID, "value": VALUE},
+                {"operation": "add", "type": FORBIDDEN_TYPE, "id": ID, "value": VALUE},
+            ]).to_string();
+
+            let res = wallet_batch_execute(setup.wallet_handle, &operations);
+            assert_code!(ErrorCode::WalletAccessFailed, res);
+
+            let res = get_wallet_record(setup.wallet_handle, TYPE, ID, OPTIONS_EMPTY);
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+        }
+    }
+
     mod get_record {
         use super::*;
 
@@ -1222,6 +1648,12 @@ mod medium_cases {
             cleanup_wallet("indy_wallet_search_for_invalid_search_handle");
         }
 
+        #[test]
+        fn indy_wallet_all_records_search_for_invalid_wallet_handle() {
+            let res = open_wallet_all_records_search(INVALID_WALLET_HANDLE);
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+
         #[test]
         fn indy_wallet_search_for_invalid_type() {
             const SEARCH_WALLET_CONFIG: &str = r#"{"id":"indy_wallet_search_for_invalid_type"}"#;
@@ -1250,6 +1682,34 @@ mod medium_cases {
         }
     }
 
+    mod aggregate {
+        use super::*;
+
+        #[test]
+        fn indy_wallet_aggregate_works_for_encrypted_tag() {
+            let setup = Setup::wallet();
+
+            let res = wallet_aggregate(setup.wallet_handle, TYPE, QUERY_EMPTY, r#"{"groupBy":["tagName1"]}"#);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_wallet_aggregate_works_for_invalid_wallet_handle() {
+            Setup::empty();
+
+            let res = wallet_aggregate(INVALID_WALLET_HANDLE, TYPE, QUERY_EMPTY, r#"{"groupBy":["~tagName3"]}"#);
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+
+        #[test]
+        fn indy_wallet_aggregate_works_for_not_allowed_type() {
+            let setup = Setup::wallet();
+
+            let res = wallet_aggregate(setup.wallet_handle, FORBIDDEN_TYPE, QUERY_EMPTY, "{}");
+            assert_code!(ErrorCode::WalletAccessFailed, res);
+        }
+    }
+
     mod rusqlite_transaction_fix {
         use super::*;
 
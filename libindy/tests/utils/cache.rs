@@ -15,10 +15,18 @@ pub fn get_cred_def_cache(pool_handle: PoolHandle, wallet_handle: WalletHandle,
     cache::get_cred_def(pool_handle, wallet_handle, submitter_did, id, options_json).wait()
 }
 
+pub fn resolve_did(pool_handle: PoolHandle, wallet_handle: WalletHandle, did: &str, options_json: &str) -> Result<String, IndyError> {
+    cache::resolve_did(pool_handle, wallet_handle, did, options_json).wait()
+}
+
 pub fn purge_schema_cache(wallet_handle: WalletHandle, options_json: &str) -> Result<(), IndyError> {
     cache::purge_schema_cache(wallet_handle, options_json).wait()
 }
 
 pub fn purge_cred_def_cache(wallet_handle: WalletHandle, options_json: &str) -> Result<(), IndyError> {
     cache::purge_cred_def_cache(wallet_handle, options_json).wait()
-}
\ No newline at end of file
+}
+
+pub fn purge_did_doc_cache(wallet_handle: WalletHandle, options_json: &str) -> Result<(), IndyError> {
+    cache::purge_did_doc_cache(wallet_handle, options_json).wait()
+}
@@ -1,21 +1,25 @@
 pub const SEQ_NO: i32 = 1;
 pub const PROTOCOL_VERSION: usize = 2;
 pub const TYPE: &'static str = "default";
-pub const INMEM_TYPE: &'static str = "inmem";
+pub const INMEM_TYPE: &'static str = "plugged_inmem";
 pub const SIGNATURE_TYPE: &'static str = "CL";
 pub const TRUSTEE_SEED: &'static str = "000000000000000000000000Trustee1";
 pub const STEWARD_SEED: &'static str = "000000000000000000000000Steward1";
 pub const MY1_SEED: &'static str = "00000000000000000000000000000My1";
 pub const MY2_SEED: &'static str = "00000000000000000000000000000My2";
+pub const MNEMONIC: &'static str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+pub const DERIVATION_PATH: &'static str = "m/0'";
 pub const ISSUER_DID: &'static str = "NcYxiDXkpYi6ov5FcYDi1e";
 pub const ISSUER_DID_SUB: &'static str = "NcYxiDXkpYi6ov5FcYDi1i";
 pub const ISSUER_DID_V1: &'static str = "did:sov:NcYxiDXkpYi6ov5FcYDi1e";
 pub const ISSUER_DID_2: &'static str = "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW";
 pub const DID: &'static str = "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW";
 pub const DID_V1: &'static str = "did:sov:CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW";
+pub const DID_KEY: &'static str = "did:key:z6MkrEVGLPYHhLGBQ25Bh5DXSakRiDpdoj7fdsNZSqYPcUGt";
 pub const DID_MY1: &'static str = "VsKV7grR1BUE29mG2Fm2kX";
 pub const DID_MY1_V1: &'static str = "did:sov:VsKV7grR1BUE29mG2Fm2kX";
 pub const DID_MY2: &'static str = "2PRyVHmkXQnQzJQKxHxnXC";
+pub const DID_MNEMONIC: &'static str = "P2msLB8MPYwECztDyFkjS4";
 pub const DID_TRUSTEE: &'static str = "V4SGRU86Z58d6TV7PBUe6f";
 pub const INVALID_BASE58_DID: &'static str = "invalid_base58string";
 pub const IDENTIFIER: &'static str = "Th7MpTaRZVRYnPiabds81Y";
@@ -39,6 +43,7 @@ pub const INVALID_VERKEY_LENGTH: &'static str = "invalidVerkeyLength";
 pub const INVALID_BASE58_VERKEY: &'static str = "CnEDk___MnmiHXEV1WFgbV___eYnPqs___TdcZaNhFVW";
 pub const NONCE: &'static [u8; 24] = &[242, 246, 53, 153, 106, 37, 185, 65, 212, 14, 109, 131, 200, 169, 94, 110, 51, 47, 101, 89, 0, 171, 105, 183];
 pub const VERKEY_MY2: &'static str = "kqa2HyagzfMAq42H5f9u3UMwnSBPQx2QfrSyXbUPxMn";
+pub const VERKEY_MNEMONIC: &'static str = "D1Rwdcr6RtByD4izfmxvYfTon6TjaiaCSrt7RiegN5ux";
 pub const VERKEY_TRUSTEE: &'static str = "GJ1SzoWzavQYfNL9XkaJdrQejfztN4XqdsiV4ct3LXKL";
 pub const METADATA: &'static str = "some_metadata";
 pub const MESSAGE: &'static str = r#"{"reqId":1496822211362017764}"#;
@@ -57,8 +62,9 @@ pub const WALLET_CREDENTIALS_ARGON2I_MOD: &'static str = r#"{"key":"key", "key_d
 pub const WALLET_CREDENTIALS_ARGON2I_INT: &'static str = r#"{"key":"key", "key_derivation_method":"ARGON2I_INT"}"#;
 pub const WALLET_CREDENTIALS_RAW: &'static str = r#"{"key":"8dvfYSt5d1taSd6yJdpjq4emkwsPDDLYxkNFysFD2cZY", "key_derivation_method":"RAW"}"#;
 pub const DEFAULT_WALLET_CONFIG: &'static str = r#"{"id":"default_wallet_1","storage_type":"default"}"#;  // FIXME never use global names
-pub const INMEM_WALLET_CONFIG: &'static str = r#"{"id":"inmem_wallet_1","storage_type":"inmem"}"#;  // FIXME never use global names
+pub const INMEM_WALLET_CONFIG: &'static str = r#"{"id":"inmem_wallet_1","storage_type":"plugged_inmem"}"#;  // FIXME never use global names
 pub const UNKNOWN_WALLET_CONFIG: &'static str = r#"{"id":"unknown_wallet_1","storage_type":"unknown"}"#;  // FIXME never use global names
 pub const AGENT_MESSAGE: &'static str = r#"{ "@id": "123456780","@type":"did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/basicmessage/1.0/message","sent_time": "2019-01-15 18:42:01Z","content": "Your hovercraft is full of eels."}"#;
+pub const PACK_OPTIONS_DIDCOMM_V2: &'static str = r#"{"format":"didcomm_v2"}"#;
 pub const DEFAULT_METHOD_NAME: &'static str = "sov";
 pub const DEFAULT_PREFIX: &'static str = "did:sov:";
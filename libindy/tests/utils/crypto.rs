@@ -11,6 +11,20 @@ pub fn create_key(wallet_handle: WalletHandle, seed: Option<&str>) -> Result<Str
     crypto::create_key(wallet_handle, Some(&key_json)).wait()
 }
 
+pub fn create_key_with_crypto_type(wallet_handle: WalletHandle, seed: Option<&str>, crypto_type: &str) -> Result<String, IndyError> {
+    let key_json = json!({"seed": seed, "crypto_type": crypto_type}).to_string();
+    crypto::create_key(wallet_handle, Some(&key_json)).wait()
+}
+
+pub fn create_key_with_mnemonic(wallet_handle: WalletHandle, mnemonic: &str, derivation_path: &str) -> Result<String, IndyError> {
+    let key_json = json!({"mnemonic": {"phrase": mnemonic, "derivation_path": derivation_path}}).to_string();
+    crypto::create_key(wallet_handle, Some(&key_json)).wait()
+}
+
+pub fn generate_mnemonic(config_json: Option<&str>) -> Result<String, IndyError> {
+    crypto::generate_mnemonic(config_json).wait()
+}
+
 pub fn set_key_metadata(wallet_handle: WalletHandle, verkey: &str, metadata: &str) -> Result<(), IndyError> {
     crypto::set_key_metadata(wallet_handle, verkey, metadata).wait()
 }
@@ -27,6 +41,14 @@ pub fn verify(their_vk: &str, msg: &[u8], signature: &[u8]) -> Result<bool, Indy
     crypto::verify(their_vk, msg, signature).wait()
 }
 
+pub fn sign_jws(wallet_handle: WalletHandle, my_vk: &str, payload: &[u8], header_json: Option<&str>, detached: bool) -> Result<(String, String), IndyError> {
+    crypto::sign_jws(wallet_handle, my_vk, payload, header_json, detached).wait()
+}
+
+pub fn verify_jws(jws: &str, their_vk: &str, detached_payload: Option<&[u8]>) -> Result<bool, IndyError> {
+    crypto::verify_jws(jws, their_vk, detached_payload).wait()
+}
+
 pub fn auth_crypt(wallet_handle: WalletHandle, my_vk: &str, their_vk: &str, msg: &[u8]) -> Result<Vec<u8>, IndyError> {
     crypto::auth_crypt(wallet_handle, my_vk, their_vk, msg).wait()
 }
@@ -47,6 +69,10 @@ pub fn pack_message(wallet_handle: WalletHandle, message: &[u8], receiver_keys:
     crypto::pack_message(wallet_handle, message, receiver_keys, sender).wait()
 }
 
+pub fn pack_message_with_options(wallet_handle: WalletHandle, message: &[u8], receiver_keys: &str, sender: Option<&str>, options_json: &str) -> Result<Vec<u8>, IndyError> {
+    crypto::pack_message_with_options(wallet_handle, message, receiver_keys, sender, options_json).wait()
+}
+
 pub fn unpack_message(wallet_handle: WalletHandle, jwe: &[u8]) -> Result<Vec<u8>, IndyError> {
     crypto::unpack_message(wallet_handle, jwe).wait()
 }
\ No newline at end of file
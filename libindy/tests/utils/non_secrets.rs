@@ -42,10 +42,18 @@ pub fn add_wallet_record(wallet_handle: WalletHandle, type_: &str, id: &str, val
     wallet::add_wallet_record(wallet_handle, type_, id, value, tags_json).wait()
 }
 
+pub fn add_wallet_record_with_options(wallet_handle: WalletHandle, type_: &str, id: &str, value: &str, tags_json: Option<&str>, options_json: &str) -> Result<(), IndyError> {
+    wallet::add_wallet_record_with_options(wallet_handle, type_, id, value, tags_json, options_json).wait()
+}
+
 pub fn update_wallet_record_value(wallet_handle: WalletHandle, type_: &str, id: &str, value: &str) -> Result<(), IndyError> {
     wallet::update_wallet_record_value(wallet_handle, type_, id, value).wait()
 }
 
+pub fn update_wallet_record_value_with_options(wallet_handle: WalletHandle, type_: &str, id: &str, value: &str, options_json: &str) -> Result<(), IndyError> {
+    wallet::update_wallet_record_value_with_options(wallet_handle, type_, id, value, options_json).wait()
+}
+
 pub fn update_wallet_record_tags(wallet_handle: WalletHandle, type_: &str, id: &str, tags_json: &str) -> Result<(), IndyError> {
     wallet::update_wallet_record_tags(wallet_handle, type_, id, tags_json).wait()
 }
@@ -62,6 +70,10 @@ pub fn delete_wallet_record(wallet_handle: WalletHandle, type_: &str, id: &str)
     wallet::delete_wallet_record(wallet_handle, type_, id).wait()
 }
 
+pub fn wallet_batch_execute(wallet_handle: WalletHandle, operations_json: &str) -> Result<(), IndyError> {
+    wallet::wallet_batch_execute(wallet_handle, operations_json).wait()
+}
+
 pub fn get_wallet_record(wallet_handle: WalletHandle, type_: &str, id: &str, options_json: &str) -> Result<String, IndyError> {
     wallet::get_wallet_record(wallet_handle, type_, id, options_json).wait()
 }
@@ -70,6 +82,10 @@ pub fn open_wallet_search(wallet_handle: WalletHandle, type_: &str, query_json:
     wallet::open_wallet_search(wallet_handle, type_, query_json, options_json).wait()
 }
 
+pub fn open_wallet_all_records_search(wallet_handle: WalletHandle) -> Result<i32, IndyError> {
+    wallet::open_wallet_all_records_search(wallet_handle).wait()
+}
+
 pub fn fetch_wallet_search_next_records(wallet_handle: WalletHandle, wallet_search_handle: i32, count: usize) -> Result<String, IndyError> {
     wallet::fetch_wallet_search_next_records(wallet_handle, wallet_search_handle, count).wait()
 }
@@ -78,6 +94,10 @@ pub fn close_wallet_search(wallet_search_handle: i32) -> Result<(), IndyError> {
     wallet::close_wallet_search(wallet_search_handle).wait()
 }
 
+pub fn wallet_aggregate(wallet_handle: WalletHandle, type_: &str, query_json: &str, aggregation_json: &str) -> Result<String, IndyError> {
+    wallet::wallet_aggregate(wallet_handle, type_, query_json, aggregation_json).wait()
+}
+
 pub fn tags_1() -> HashMap<String, String> {
     serde_json::from_str(TAGS).unwrap()
 }
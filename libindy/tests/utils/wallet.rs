@@ -11,14 +11,15 @@ use crate::utils::{callback, sequence, environment};
 use crate::utils::inmem_wallet::InmemWallet;
 
 use std::collections::HashSet;
-use std::sync::Mutex;
+use std::io::{Cursor, Write};
+use std::sync::{Arc, Mutex};
 use std::ffi::CString;
 use super::libc::c_char;
 
 use crate::utils::constants::{TYPE, INMEM_TYPE, WALLET_CREDENTIALS};
 
 use std::path::{Path, PathBuf};
-use indy::{WalletHandle, CommandHandle};
+use indy::{WalletHandle, CommandHandle, SubscriptionHandle};
 
 pub fn register_wallet_storage(xtype: &str, force_create: bool) -> Result<(), ErrorCode> {
     lazy_static! {
@@ -98,7 +99,7 @@ pub fn create_and_open_plugged_wallet() -> Result<(WalletHandle, String), IndyEr
             "storage_type": INMEM_TYPE
         }).to_string();
 
-    register_wallet_storage("inmem", false).unwrap();
+    register_wallet_storage(INMEM_TYPE, false).unwrap();
     create_wallet(&config, WALLET_CREDENTIALS)?;
     let wallet_handle = open_wallet(&config, WALLET_CREDENTIALS).unwrap();
     Ok((wallet_handle, config))
@@ -117,6 +118,10 @@ pub fn close_and_delete_wallet(wallet_handle: WalletHandle, wallet_config: &str)
     delete_wallet(wallet_config, WALLET_CREDENTIALS)
 }
 
+pub fn rekey_wallet(wallet_handle: WalletHandle, new_credentials: &str) -> Result<(), IndyError> {
+    wallet::rekey_wallet(wallet_handle, new_credentials).wait()
+}
+
 pub fn export_wallet(wallet_handle: WalletHandle, export_config_json: &str) -> Result<(), IndyError> {
     wallet::export_wallet(wallet_handle, export_config_json).wait()
 }
@@ -125,6 +130,63 @@ pub fn import_wallet(config: &str, credentials: &str, import_config: &str) -> Re
     wallet::import_wallet(config, credentials, import_config).wait()
 }
 
+pub fn export_wallet_to_stream(wallet_handle: WalletHandle, export_config_json: &str) -> Result<Vec<u8>, IndyError> {
+    let buffer = ExportBuffer::default();
+    wallet::export_wallet_to_stream(wallet_handle, export_config_json, Box::new(buffer.clone())).wait()?;
+    let data = buffer.0.lock().unwrap().clone();
+    Ok(data)
+}
+
+pub fn import_wallet_from_stream(config: &str, credentials: &str, import_config: &str, data: Vec<u8>) -> Result<(), IndyError> {
+    wallet::import_wallet_from_stream(config, credentials, import_config, Box::new(Cursor::new(data))).wait()
+}
+
+#[derive(Clone, Default)]
+struct ExportBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for ExportBuffer {
+    fn write(&mut self, buf: &[u8]) -> ::std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> ::std::io::Result<()> {
+        Ok(())
+    }
+}
+
+pub fn import_into_wallet(wallet_handle: WalletHandle, import_config: &str) -> Result<(), IndyError> {
+    wallet::import_into_wallet(wallet_handle, import_config).wait()
+}
+
+pub fn inspect_wallet_export(export_config_json: &str) -> Result<String, IndyError> {
+    wallet::inspect_wallet_export(export_config_json).wait()
+}
+
+pub fn check_wallet(wallet_handle: WalletHandle, options_json: &str) -> Result<String, IndyError> {
+    wallet::check_wallet(wallet_handle, options_json).wait()
+}
+
+pub fn create_wallet_replica(wallet_handle: WalletHandle, config: &str, credentials: &str) -> Result<(), IndyError> {
+    wallet::create_wallet_replica(wallet_handle, config, credentials).wait()
+}
+
+pub fn sync_wallet(source_wallet_handle: WalletHandle, target_wallet_handle: WalletHandle, options_json: &str) -> Result<String, IndyError> {
+    wallet::sync_wallet(source_wallet_handle, target_wallet_handle, options_json).wait()
+}
+
+pub fn subscribe_wallet(wallet_handle: WalletHandle, type_: &str, query_json: &str) -> Result<(SubscriptionHandle, Arc<Mutex<Vec<(String, String)>>>), IndyError> {
+    let changes: Arc<Mutex<Vec<(String, String)>>> = Default::default();
+    let changes_ = changes.clone();
+    let subscription_handle = wallet::subscribe_wallet(wallet_handle, type_, query_json, Box::new(move |operation, id| {
+        changes_.lock().unwrap().push((operation.to_string(), id.to_string()));
+    })).wait()?;
+    Ok((subscription_handle, changes))
+}
+
+pub fn unsubscribe_wallet(subscription_handle: SubscriptionHandle) -> Result<(), IndyError> {
+    wallet::unsubscribe_wallet(subscription_handle).wait()
+}
+
 pub fn export_wallet_path(name: &str) -> PathBuf {
     environment::tmp_file_path(name)
 }
@@ -137,6 +199,12 @@ pub fn prepare_export_wallet_config(path: &Path) -> String {
     serde_json::to_string(&json).unwrap()
 }
 
+pub fn prepare_export_stream_config() -> String {
+    json!({
+        "key": "export_key",
+    }).to_string()
+}
+
 pub fn generate_wallet_key(config: Option<&str>) -> Result<String, IndyError> {
     wallet::generate_wallet_key(config).wait()
 }
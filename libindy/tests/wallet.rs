@@ -7,7 +7,7 @@ extern crate indyrs as indy;
 extern crate indyrs as api;
 
 use crate::utils::inmem_wallet::InmemWallet;
-use crate::utils::{environment, wallet, test, did};
+use crate::utils::{environment, wallet, test, did, non_secrets};
 use crate::utils::constants::*;
 use crate::utils::Setup;
 
@@ -42,6 +42,34 @@ mod high_cases {
 
             InmemWallet::cleanup();
         }
+
+        #[test]
+        fn indy_register_native_wallet_storage_works() {
+            let setup = Setup::empty();
+
+            native_storage::register(NATIVE_INMEM_TYPE).unwrap();
+
+            let config = json!({
+                "id": &setup.name,
+                "storage_type": NATIVE_INMEM_TYPE,
+            }).to_string();
+
+            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+
+            non_secrets::add_wallet_record(wallet_handle, non_secrets::TYPE, non_secrets::ID, non_secrets::VALUE, Some(non_secrets::TAGS)).unwrap();
+
+            let record = non_secrets::get_wallet_record(wallet_handle, non_secrets::TYPE, non_secrets::ID, r#"{"retrieveTags":true}"#).unwrap();
+            let record: serde_json::Value = serde_json::from_str(&record).unwrap();
+            assert_eq!(non_secrets::VALUE, record["value"].as_str().unwrap());
+            assert_eq!(serde_json::from_str::<serde_json::Value>(non_secrets::TAGS).unwrap(), record["tags"]);
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            assert!(native_storage::exists(&setup.name));
+
+            wallet::delete_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            assert!(!native_storage::exists(&setup.name));
+        }
     }
 
     mod create_wallet {
@@ -80,6 +108,31 @@ mod high_cases {
             InmemWallet::cleanup();
         }
 
+        #[test]
+        fn indy_create_wallet_works_for_inmem() {
+            let setup = Setup::empty();
+
+            let config = json!({
+                "id": &setup.name,
+                "storage_type": "inmem",
+            }).to_string();
+
+            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+
+            let (my_did, my_verkey) = did::create_and_store_my_did(wallet_handle, None).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            assert_eq!(did::key_for_local_did(wallet_handle, &my_did).unwrap(), my_verkey);
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            wallet::delete_wallet(&config, WALLET_CREDENTIALS).unwrap();
+
+            let res = wallet::open_wallet(&config, WALLET_CREDENTIALS);
+            assert_code!(ErrorCode::WalletNotFoundError, res);
+        }
+
         #[test]
         fn indy_create_wallet_works_for_unknown_type() {
             Setup::empty();
@@ -224,6 +277,69 @@ mod high_cases {
         }
     }
 
+    mod rekey_wallet {
+        use super::*;
+
+        #[test]
+        fn indy_rekey_wallet_works() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            wallet::create_wallet(&config, r#"{"key":"key"}"#).unwrap();
+            let wallet_handle = wallet::open_wallet(&config, r#"{"key":"key"}"#).unwrap();
+
+            let (did, _) = did::create_my_did(wallet_handle, "{}").unwrap();
+
+            wallet::rekey_wallet(wallet_handle, r#"{"key":"other_key"}"#).unwrap();
+
+            did::key_for_local_did(wallet_handle, &did).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            let res = wallet::open_wallet(&config, r#"{"key":"key"}"#);
+            assert_code!(ErrorCode::WalletAccessFailed, res);
+
+            let wallet_handle = wallet::open_wallet(&config, r#"{"key":"other_key"}"#).unwrap();
+            did::key_for_local_did(wallet_handle, &did).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+        }
+
+        #[test]
+        fn indy_rekey_wallet_works_for_raw_key() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+
+            let key = wallet::generate_wallet_key(None).unwrap();
+            let credentials = json!({"key": key, "key_derivation_method": "RAW"}).to_string();
+
+            wallet::rekey_wallet(wallet_handle, &credentials).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            let wallet_handle = wallet::open_wallet(&config, &credentials).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+        }
+
+        #[test]
+        fn indy_rekey_wallet_works_for_plugged() {
+            Setup::empty();
+            InmemWallet::cleanup();
+
+            wallet::register_wallet_storage(INMEM_TYPE, false).unwrap();
+            wallet::create_wallet(INMEM_WALLET_CONFIG, WALLET_CREDENTIALS).unwrap();
+            let wallet_handle = wallet::open_wallet(INMEM_WALLET_CONFIG, WALLET_CREDENTIALS).unwrap();
+
+            wallet::rekey_wallet(wallet_handle, r#"{"key":"other_key", "key_derivation_method":"ARGON2I_INT"}"#).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            let wallet_handle = wallet::open_wallet(INMEM_WALLET_CONFIG, r#"{"key":"other_key", "key_derivation_method":"ARGON2I_INT"}"#).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+
+            InmemWallet::cleanup();
+        }
+    }
+
     mod export_wallet {
         use super::*;
 
@@ -244,6 +360,41 @@ mod high_cases {
 
             test::cleanup_files(&path, &setup.name);
         }
+
+        #[test]
+        fn indy_export_wallet_works_for_types_and_query() {
+            let setup = Setup::wallet();
+            let config = config(&format!("{}_import", setup.name));
+
+            let path = wallet::export_wallet_path(&setup.name);
+            let config_json = json!({
+                "path": path.to_str().unwrap(),
+                "key": "export_key",
+                "types": ["TestType"],
+                "query": {"~tagName": "1"},
+            }).to_string();
+
+            non_secrets::add_wallet_record(setup.wallet_handle, "TestType", "RecordId", "RecordValue", Some(r#"{"~tagName":"1"}"#)).unwrap();
+            non_secrets::add_wallet_record(setup.wallet_handle, "TestType", "RecordId2", "RecordValue2", Some(r#"{"~tagName":"2"}"#)).unwrap();
+            non_secrets::add_wallet_record(setup.wallet_handle, "TestType2", "RecordId3", "RecordValue3", Some(r#"{"~tagName":"1"}"#)).unwrap();
+
+            cleanup_file(&path);
+            wallet::export_wallet(setup.wallet_handle, &config_json).unwrap();
+
+            wallet::import_wallet(&config, WALLET_CREDENTIALS, &config_json).unwrap();
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+
+            non_secrets::get_wallet_record(wallet_handle, "TestType", "RecordId", "{}").unwrap();
+
+            let res = non_secrets::get_wallet_record(wallet_handle, "TestType", "RecordId2", "{}");
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+
+            let res = non_secrets::get_wallet_record(wallet_handle, "TestType2", "RecordId3", "{}");
+            assert_code!(ErrorCode::WalletItemNotFound, res);
+
+            wallet::close_and_delete_wallet(wallet_handle, &config).unwrap();
+            test::cleanup_files(&path, &setup.name);
+        }
     }
 
     mod import_wallet {
@@ -283,151 +434,391 @@ mod high_cases {
         }
     }
 
-    mod generate_wallet_key {
+    mod export_wallet_to_stream {
         use super::*;
-        use rust_base58::FromBase58;
 
         #[test]
-        fn indy_generate_wallet_key_works() {
+        fn indy_export_wallet_to_stream_works() {
             let setup = Setup::empty();
             let config = config(&setup.name);
+            let stream_config = wallet::prepare_export_stream_config();
 
-            let key = wallet::generate_wallet_key(None).unwrap();
+            let (wallet_handle, wallet_config) = wallet::create_and_open_default_wallet(&setup.name).unwrap();
 
-            let credentials = json!({"key": key, "key_derivation_method": "RAW"}).to_string();
-            wallet::create_wallet(&config, &credentials).unwrap();
+            let (did, _) = did::create_my_did(wallet_handle, "{}").unwrap();
+            did::set_did_metadata(wallet_handle, &did, METADATA).unwrap();
+            let did_with_meta = did::get_my_did_with_metadata(wallet_handle, &did).unwrap();
+
+            let data = wallet::export_wallet_to_stream(wallet_handle, &stream_config).unwrap();
+            assert!(!data.is_empty());
 
-            let wallet_handle = wallet::open_wallet(&config, &credentials).unwrap();
             wallet::close_wallet(wallet_handle).unwrap();
-            wallet::delete_wallet(&config, &credentials).unwrap();
+            wallet::delete_wallet(&wallet_config, WALLET_CREDENTIALS).unwrap();
+
+            wallet::import_wallet_from_stream(&config, WALLET_CREDENTIALS, &stream_config, data).unwrap();
+
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+
+            let did_with_meta_after_import = did::get_my_did_with_metadata(wallet_handle, &did).unwrap();
+            assert_eq!(did_with_meta, did_with_meta_after_import);
+
+            wallet::close_and_delete_wallet(wallet_handle, &config).unwrap();
         }
 
         #[test]
-        fn indy_generate_wallet_key_works_for_seed() {
+        fn indy_export_wallet_to_stream_works_for_file_import() {
             let setup = Setup::empty();
-            let wallet_config = config(&setup.name);
+            let config = config(&setup.name);
 
-            let config = json!({"seed": MY1_SEED}).to_string();
-            let key = wallet::generate_wallet_key(Some(config.as_str())).unwrap();
-            assert_eq!(key.from_base58().unwrap(), vec![177, 92, 220, 199, 104, 203, 161, 4, 218, 78, 105, 13, 7, 50, 66, 107, 154, 155, 108, 133, 1, 30, 87, 149, 233, 76, 39, 156, 178, 46, 230, 124]);
+            let path = wallet::export_wallet_path(&setup.name);
+            let config_json = wallet::prepare_export_wallet_config(&path);
 
-            let credentials = json!({"key": key, "key_derivation_method": "RAW"}).to_string();
-            wallet::create_wallet(&wallet_config, &credentials).unwrap();
+            let (wallet_handle, wallet_config) = wallet::create_and_open_default_wallet(&setup.name).unwrap();
+
+            let (did, _) = did::create_my_did(wallet_handle, "{}").unwrap();
+
+            let data = wallet::export_wallet_to_stream(wallet_handle, &wallet::prepare_export_stream_config()).unwrap();
 
-            let wallet_handle = wallet::open_wallet(&wallet_config, &credentials).unwrap();
             wallet::close_wallet(wallet_handle).unwrap();
-            wallet::delete_wallet(&wallet_config, &credentials).unwrap();
-        }
-    }
-}
+            wallet::delete_wallet(&wallet_config, WALLET_CREDENTIALS).unwrap();
 
-#[cfg(not(feature="only_high_cases"))]
-mod medium_cases {
-    extern crate libc;
+            cleanup_file(&path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, data).unwrap();
 
-    use super::*;
-    use std::ffi::CString;
+            wallet::import_wallet(&config, WALLET_CREDENTIALS, &config_json).unwrap();
 
-    use crate::api::INVALID_WALLET_HANDLE;
-    use crate::utils::test::cleanup_wallet;
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            did::key_for_local_did(wallet_handle, &did).unwrap();
 
-    mod register_wallet_type {
+            wallet::close_and_delete_wallet(wallet_handle, &config).unwrap();
+            cleanup_file(&path);
+        }
+    }
+
+    mod inspect_wallet_export {
         use super::*;
 
         #[test]
-        fn indy_register_wallet_storage_does_not_work_twice_with_same_name() {
-            Setup::empty();
-            InmemWallet::cleanup();
+        fn indy_inspect_wallet_export_works() {
+            let setup = Setup::wallet();
 
-            wallet::register_wallet_storage(INMEM_TYPE, false).unwrap();
-            let res = wallet::register_wallet_storage(INMEM_TYPE, true).unwrap_err();
-            assert_eq!(ErrorCode::WalletTypeAlreadyRegisteredError, res);
+            let path = wallet::export_wallet_path(&setup.name);
+            let config_json = wallet::prepare_export_wallet_config(&path);
 
-            InmemWallet::cleanup();
-        }
+            did::create_my_did(setup.wallet_handle, "{}").unwrap();
+            did::create_my_did(setup.wallet_handle, "{}").unwrap();
 
-        #[test]
-        fn indy_register_wallet_storage_does_not_work_with_null_params() {
-            Setup::empty();
-            InmemWallet::cleanup();
+            cleanup_file(&path);
+            wallet::export_wallet(setup.wallet_handle, &config_json).unwrap();
 
-            let xtype = CString::new(INMEM_TYPE).unwrap();
-            let res = unsafe {
-                wallet::indy_register_wallet_storage(1, xtype.as_ptr(), None, None, None, None, None,
-                                                     None, None, None, None, None,
-                                                     None, None, None, None, None, None,
-                                                     None, None, None, None,
-                                                     None, None, None, None, None)
-            };
-            assert_eq!(ErrorCode::CommonInvalidParam3, res);
+            let summary_json = wallet::inspect_wallet_export(&config_json).unwrap();
+            let summary: serde_json::Value = serde_json::from_str(&summary_json).unwrap();
 
-            InmemWallet::cleanup();
+            assert_eq!(0, summary["version"].as_u64().unwrap());
+            assert_eq!("ARGON2I_MOD", summary["key_derivation_method"].as_str().unwrap());
+            assert_eq!(2, summary["types"]["Indy::Did"].as_u64().unwrap());
+            assert!(summary["records_count"].as_u64().unwrap() >= 2);
+            assert_eq!(fs::metadata(&path).unwrap().len(), summary["size"].as_u64().unwrap());
+
+            cleanup_file(&path);
         }
     }
 
-    mod create_wallet {
+    mod check_wallet {
         use super::*;
 
         #[test]
-        fn indy_create_wallet_works_for_empty_type() {
-            let setup = Setup::empty();
-            let config = config(&setup.name);
+        fn indy_check_wallet_works() {
+            let setup = Setup::wallet();
 
-            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
-        }
+            did::create_my_did(setup.wallet_handle, "{}").unwrap();
 
-        #[test]
-        fn indy_create_wallet_works_for_duplicate_name() {
-            let setup = Setup::empty();
-            let config = config(&setup.name);
+            let report_json = wallet::check_wallet(setup.wallet_handle, "{}").unwrap();
+            let report: serde_json::Value = serde_json::from_str(&report_json).unwrap();
 
-            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
-            let res = wallet::create_wallet(&config, WALLET_CREDENTIALS);
-            assert_code!(ErrorCode::WalletAlreadyExistsError, res);
+            assert!(report["records_count"].as_u64().unwrap() >= 1);
+            assert!(report["issues"].as_array().unwrap().is_empty());
         }
 
         #[test]
-        fn indy_create_wallet_works_for_missed_key() {
-            let setup = Setup::empty();
-            let config = config(&setup.name);
+        fn indy_check_wallet_works_for_repair() {
+            let setup = Setup::wallet();
 
-            let res = wallet::create_wallet(&config, r#"{}"#);
-            assert_code!(ErrorCode::CommonInvalidStructure, res);
-        }
+            did::create_my_did(setup.wallet_handle, "{}").unwrap();
 
-        #[test]
-        fn indy_create_wallet_works_for_empty_name() {
-            Setup::empty();
-            let config = config("");
+            let report_json = wallet::check_wallet(setup.wallet_handle, r#"{"repair": true}"#).unwrap();
+            let report: serde_json::Value = serde_json::from_str(&report_json).unwrap();
 
-            let res = wallet::create_wallet(&config, WALLET_CREDENTIALS);
-            assert_code!(ErrorCode::CommonInvalidStructure, res);
+            assert!(report["issues"].as_array().unwrap().is_empty());
         }
+    }
+
+    mod sync_wallet {
+        use super::*;
 
         #[test]
-        fn indy_create_wallet_works_for_raw_key_invalid_length() {
-            let setup = Setup::empty();
-            let config = config(&setup.name);
+        fn indy_sync_wallet_works() {
+            let setup = Setup::wallet();
 
-            let credentials = json!({"key": "key", "key_derivation_method": "RAW"}).to_string();
-            let res = wallet::create_wallet(&config, &credentials);
-            assert_code!(ErrorCode::CommonInvalidStructure, res);
+            let (did, verkey) = did::create_and_store_my_did(setup.wallet_handle, None).unwrap();
+
+            let replica_config = json!({
+                "id": format!("{}_replica", setup.name),
+                "storage_type": "inmem",
+            }).to_string();
+
+            wallet::create_wallet_replica(setup.wallet_handle, &replica_config, WALLET_CREDENTIALS).unwrap();
+            let replica_handle = wallet::open_wallet(&replica_config, WALLET_CREDENTIALS).unwrap();
+
+            let result_json = wallet::sync_wallet(setup.wallet_handle, replica_handle, "{}").unwrap();
+            let result: serde_json::Value = serde_json::from_str(&result_json).unwrap();
+            assert!(result["records_count"].as_u64().unwrap() >= 1);
+
+            assert_eq!(did::key_for_local_did(replica_handle, &did).unwrap(), verkey);
+
+            let options = json!({"since": result["marker"]}).to_string();
+            let result_json = wallet::sync_wallet(setup.wallet_handle, replica_handle, &options).unwrap();
+            let result: serde_json::Value = serde_json::from_str(&result_json).unwrap();
+            assert_eq!(result["records_count"].as_u64().unwrap(), 0);
+            assert_eq!(result["deleted_count"].as_u64().unwrap(), 0);
+
+            wallet::close_wallet(replica_handle).unwrap();
+            wallet::delete_wallet(&replica_config, WALLET_CREDENTIALS).unwrap();
         }
     }
 
-    mod delete_wallet {
+    mod subscribe_wallet {
         use super::*;
 
         #[test]
-        fn indy_delete_wallet_works_for_closed() {
-            let setup = Setup::empty();
-            let config = config(&setup.name);
+        fn indy_wallet_subscribe_works() {
+            let setup = Setup::wallet();
+
+            let (subscription_handle, changes) = wallet::subscribe_wallet(setup.wallet_handle, non_secrets::TYPE, r#"{"tagName1": "str1"}"#).unwrap();
+
+            non_secrets::add_wallet_record(setup.wallet_handle, non_secrets::TYPE, non_secrets::ID, non_secrets::VALUE, Some(non_secrets::TAGS)).unwrap();
+            non_secrets::add_wallet_record(setup.wallet_handle, non_secrets::TYPE, non_secrets::ID_2, non_secrets::VALUE, Some(non_secrets::TAGS_2)).unwrap();
+            non_secrets::update_wallet_record_value(setup.wallet_handle, non_secrets::TYPE, non_secrets::ID, non_secrets::VALUE_2).unwrap();
+            non_secrets::delete_wallet_record(setup.wallet_handle, non_secrets::TYPE, non_secrets::ID).unwrap();
+
+            assert_eq!(vec![
+                ("add".to_string(), non_secrets::ID.to_string()),
+                ("update".to_string(), non_secrets::ID.to_string()),
+                ("delete".to_string(), non_secrets::ID.to_string()),
+            ], *changes.lock().unwrap());
+
+            wallet::unsubscribe_wallet(subscription_handle).unwrap();
+
+            non_secrets::add_wallet_record(setup.wallet_handle, non_secrets::TYPE, non_secrets::ID, non_secrets::VALUE, Some(non_secrets::TAGS)).unwrap();
+            assert_eq!(3, changes.lock().unwrap().len());
+        }
+
+        #[test]
+        fn indy_wallet_subscribe_works_for_record_leaving_filter() {
+            let setup = Setup::wallet();
+
+            let (_, changes) = wallet::subscribe_wallet(setup.wallet_handle, non_secrets::TYPE, r#"{"tagName1": "str1"}"#).unwrap();
+
+            non_secrets::add_wallet_record(setup.wallet_handle, non_secrets::TYPE, non_secrets::ID, non_secrets::VALUE, Some(non_secrets::TAGS)).unwrap();
+            non_secrets::update_wallet_record_tags(setup.wallet_handle, non_secrets::TYPE, non_secrets::ID, non_secrets::TAGS_2).unwrap();
+            non_secrets::delete_wallet_record(setup.wallet_handle, non_secrets::TYPE, non_secrets::ID).unwrap();
+
+            assert_eq!(vec![
+                ("add".to_string(), non_secrets::ID.to_string()),
+                ("remove".to_string(), non_secrets::ID.to_string()),
+            ], *changes.lock().unwrap());
+        }
+    }
+
+    mod import_into_wallet {
+        use super::*;
+
+        #[test]
+        fn indy_import_into_wallet_works() {
+            let setup = Setup::wallet();
+
+            let path = wallet::export_wallet_path(&setup.name);
+            let config_json = wallet::prepare_export_wallet_config(&path);
+
+            let (wallet_handle, wallet_config) = wallet::create_and_open_default_wallet(&setup.name).unwrap();
+
+            let (did, _) = did::create_my_did(wallet_handle, "{}").unwrap();
+            did::set_did_metadata(wallet_handle, &did, METADATA).unwrap();
+            let did_with_meta = did::get_my_did_with_metadata(wallet_handle, &did).unwrap();
+
+            cleanup_file(&path);
+            wallet::export_wallet(wallet_handle, &config_json).unwrap();
+            wallet::close_and_delete_wallet(wallet_handle, &wallet_config).unwrap();
+
+            let (did_2, _) = did::create_my_did(setup.wallet_handle, "{}").unwrap();
+
+            wallet::import_into_wallet(setup.wallet_handle, &config_json).unwrap();
+
+            let did_with_meta_after_import = did::get_my_did_with_metadata(setup.wallet_handle, &did).unwrap();
+            assert_eq!(did_with_meta, did_with_meta_after_import);
+
+            did::key_for_local_did(setup.wallet_handle, &did_2).unwrap();
+
+            cleanup_file(&path);
+        }
+    }
+
+    mod generate_wallet_key {
+        use super::*;
+        use rust_base58::FromBase58;
+
+        #[test]
+        fn indy_generate_wallet_key_works() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            let key = wallet::generate_wallet_key(None).unwrap();
+
+            let credentials = json!({"key": key, "key_derivation_method": "RAW"}).to_string();
+            wallet::create_wallet(&config, &credentials).unwrap();
+
+            let wallet_handle = wallet::open_wallet(&config, &credentials).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+            wallet::delete_wallet(&config, &credentials).unwrap();
+        }
+
+        #[test]
+        fn indy_generate_wallet_key_works_for_seed() {
+            let setup = Setup::empty();
+            let wallet_config = config(&setup.name);
+
+            let config = json!({"seed": MY1_SEED}).to_string();
+            let key = wallet::generate_wallet_key(Some(config.as_str())).unwrap();
+            assert_eq!(key.from_base58().unwrap(), vec![177, 92, 220, 199, 104, 203, 161, 4, 218, 78, 105, 13, 7, 50, 66, 107, 154, 155, 108, 133, 1, 30, 87, 149, 233, 76, 39, 156, 178, 46, 230, 124]);
+
+            let credentials = json!({"key": key, "key_derivation_method": "RAW"}).to_string();
+            wallet::create_wallet(&wallet_config, &credentials).unwrap();
+
+            let wallet_handle = wallet::open_wallet(&wallet_config, &credentials).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+            wallet::delete_wallet(&wallet_config, &credentials).unwrap();
+        }
+    }
+}
+
+#[cfg(not(feature="only_high_cases"))]
+mod medium_cases {
+    extern crate libc;
+
+    use super::*;
+    use std::ffi::CString;
+
+    use crate::api::INVALID_WALLET_HANDLE;
+    use crate::utils::test::cleanup_wallet;
+
+    mod register_wallet_type {
+        use super::*;
+
+        #[test]
+        fn indy_register_wallet_storage_does_not_work_twice_with_same_name() {
+            Setup::empty();
+            InmemWallet::cleanup();
+
+            wallet::register_wallet_storage(INMEM_TYPE, false).unwrap();
+            let res = wallet::register_wallet_storage(INMEM_TYPE, true).unwrap_err();
+            assert_eq!(ErrorCode::WalletTypeAlreadyRegisteredError, res);
+
+            InmemWallet::cleanup();
+        }
+
+        #[test]
+        fn indy_register_wallet_storage_does_not_work_for_builtin_type_name() {
+            Setup::empty();
+
+            for type_ in &["default", "inmem"] {
+                let res = wallet::register_wallet_storage(type_, true).unwrap_err();
+                assert_eq!(ErrorCode::WalletTypeAlreadyRegisteredError, res);
+            }
+        }
+
+        #[test]
+        fn indy_register_wallet_storage_does_not_work_with_null_params() {
+            Setup::empty();
+            InmemWallet::cleanup();
+
+            let xtype = CString::new(INMEM_TYPE).unwrap();
+            let res = unsafe {
+                wallet::indy_register_wallet_storage(1, xtype.as_ptr(), None, None, None, None, None,
+                                                     None, None, None, None, None,
+                                                     None, None, None, None, None, None,
+                                                     None, None, None, None,
+                                                     None, None, None, None, None)
+            };
+            assert_eq!(ErrorCode::CommonInvalidParam3, res);
+
+            InmemWallet::cleanup();
+        }
+    }
+
+    mod create_wallet {
+        use super::*;
+
+        #[test]
+        fn indy_create_wallet_works_for_empty_type() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
+        }
+
+        #[test]
+        fn indy_create_wallet_works_for_duplicate_name() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            let res = wallet::create_wallet(&config, WALLET_CREDENTIALS);
+            assert_code!(ErrorCode::WalletAlreadyExistsError, res);
+        }
+
+        #[test]
+        fn indy_create_wallet_works_for_missed_key() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            let res = wallet::create_wallet(&config, r#"{}"#);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_create_wallet_works_for_empty_name() {
+            Setup::empty();
+            let config = config("");
+
+            let res = wallet::create_wallet(&config, WALLET_CREDENTIALS);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
+        #[test]
+        fn indy_create_wallet_works_for_raw_key_invalid_length() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            let credentials = json!({"key": "key", "key_derivation_method": "RAW"}).to_string();
+            let res = wallet::create_wallet(&config, &credentials);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+    }
+
+    mod delete_wallet {
+        use super::*;
+
+        #[test]
+        fn indy_delete_wallet_works_for_closed() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
 
             wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
-            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
-            wallet::close_wallet(wallet_handle).unwrap();
-            wallet::delete_wallet(&config, WALLET_CREDENTIALS).unwrap();
-            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            wallet::close_wallet(wallet_handle).unwrap();
+            wallet::delete_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
         }
 
         #[test]
@@ -594,6 +985,26 @@ mod medium_cases {
         }
     }
 
+    mod rekey_wallet {
+        use super::*;
+
+        #[test]
+        fn indy_rekey_wallet_works_for_invalid_handle() {
+            Setup::empty();
+
+            let res = wallet::rekey_wallet(INVALID_WALLET_HANDLE, r#"{"key":"other_key"}"#);
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+
+        #[test]
+        fn indy_rekey_wallet_works_for_invalid_credentials() {
+            let setup = Setup::wallet();
+
+            let res = wallet::rekey_wallet(setup.wallet_handle, r#"{"field":"value"}"#);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+    }
+
     mod export_wallet {
         use super::*;
         use std::fs;
@@ -623,6 +1034,21 @@ mod medium_cases {
             assert_code!(ErrorCode::CommonInvalidStructure, res);
         }
 
+        #[test]
+        fn indy_export_wallet_returns_error_if_query_without_types() {
+            let setup= Setup::wallet();
+
+            let path = wallet::export_wallet_path(&setup.name);
+            let config_json = json!({
+                "path": path.to_str().unwrap(),
+                "key": "export_key",
+                "query": {"~tagName": "1"},
+            }).to_string();
+
+            let res = wallet::export_wallet(setup.wallet_handle, &config_json);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+
         #[test]
         fn indy_export_wallet_returns_error_if_invalid_handle() {
             let setup= Setup::empty();
@@ -635,6 +1061,247 @@ mod medium_cases {
         }
     }
 
+    mod import_into_wallet {
+        use super::*;
+
+        #[test]
+        fn indy_import_into_wallet_works_for_existing_record() {
+            let setup = Setup::wallet();
+
+            let path = wallet::export_wallet_path(&setup.name);
+            let config_json = wallet::prepare_export_wallet_config(&path);
+
+            non_secrets::add_wallet_record(setup.wallet_handle, "TestType", "RecordId", "RecordValue", None).unwrap();
+
+            cleanup_file(&path);
+            wallet::export_wallet(setup.wallet_handle, &config_json).unwrap();
+
+            let res = wallet::import_into_wallet(setup.wallet_handle, &config_json);
+            assert_code!(ErrorCode::WalletItemAlreadyExists, res);
+
+            cleanup_file(&path);
+        }
+
+        #[test]
+        fn indy_import_into_wallet_works_for_invalid_handle() {
+            let setup = Setup::empty();
+
+            let path = wallet::export_wallet_path(&setup.name);
+            let config_json = wallet::prepare_export_wallet_config(&path);
+
+            let res = wallet::import_into_wallet(INVALID_WALLET_HANDLE, &config_json);
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+
+        #[test]
+        fn indy_import_into_wallet_returns_error_if_path_doesnt_exist() {
+            let setup = Setup::wallet();
+
+            let path = wallet::export_wallet_path(&setup.name);
+            let config_json = wallet::prepare_export_wallet_config(&path);
+
+            cleanup_file(&path);
+            let res = wallet::import_into_wallet(setup.wallet_handle, &config_json);
+            assert_code!(ErrorCode::CommonIOError, res);
+        }
+    }
+
+    mod export_wallet_to_stream {
+        use super::*;
+
+        #[test]
+        fn indy_export_wallet_to_stream_returns_error_for_invalid_handle() {
+            Setup::empty();
+
+            let res = wallet::export_wallet_to_stream(INVALID_WALLET_HANDLE, &wallet::prepare_export_stream_config());
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+
+        #[test]
+        fn indy_export_wallet_to_stream_returns_error_for_query_without_types() {
+            let setup = Setup::wallet();
+
+            let stream_config = json!({
+                "key": "export_key",
+                "query": {"tagName1": "str1"},
+            }).to_string();
+
+            let res = wallet::export_wallet_to_stream(setup.wallet_handle, &stream_config);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+    }
+
+    mod inspect_wallet_export {
+        use super::*;
+
+        #[test]
+        fn indy_inspect_wallet_export_works_for_other_key() {
+            let setup = Setup::wallet();
+
+            let path = wallet::export_wallet_path(&setup.name);
+            let config_json = wallet::prepare_export_wallet_config(&path);
+
+            did::create_my_did(setup.wallet_handle, "{}").unwrap();
+
+            cleanup_file(&path);
+            wallet::export_wallet(setup.wallet_handle, &config_json).unwrap();
+
+            let config_json = json!({
+                "path": path.to_str().unwrap(),
+                "key": "other_key",
+            }).to_string();
+
+            let res = wallet::inspect_wallet_export(&config_json);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+
+            cleanup_file(&path);
+        }
+
+        #[test]
+        fn indy_inspect_wallet_export_returns_error_if_path_doesnt_exist() {
+            let setup = Setup::empty();
+
+            let path = wallet::export_wallet_path(&setup.name);
+            let config_json = wallet::prepare_export_wallet_config(&path);
+
+            cleanup_file(&path);
+            let res = wallet::inspect_wallet_export(&config_json);
+            assert_code!(ErrorCode::CommonIOError, res);
+        }
+
+        #[test]
+        fn indy_inspect_wallet_export_returns_error_for_invalid_config() {
+            Setup::empty();
+
+            let res = wallet::inspect_wallet_export("{}");
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+    }
+
+    mod check_wallet {
+        use super::*;
+
+        #[test]
+        fn indy_check_wallet_returns_error_for_invalid_handle() {
+            Setup::empty();
+
+            let res = wallet::check_wallet(INVALID_WALLET_HANDLE, "{}");
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+
+        #[test]
+        fn indy_check_wallet_returns_error_for_invalid_options() {
+            let setup = Setup::wallet();
+
+            let res = wallet::check_wallet(setup.wallet_handle, r#"{"repair": "yes"}"#);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+    }
+
+    mod sync_wallet {
+        use super::*;
+
+        #[test]
+        fn indy_sync_wallet_returns_error_for_invalid_handle() {
+            let setup = Setup::wallet();
+
+            let res = wallet::sync_wallet(setup.wallet_handle, INVALID_WALLET_HANDLE, "{}");
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+
+        #[test]
+        fn indy_sync_wallet_returns_error_for_different_keys() {
+            let setup = Setup::wallet();
+
+            let config = json!({
+                "id": format!("{}_other", setup.name),
+                "storage_type": "inmem",
+            }).to_string();
+
+            wallet::create_wallet(&config, WALLET_CREDENTIALS).unwrap();
+            let wallet_handle = wallet::open_wallet(&config, WALLET_CREDENTIALS).unwrap();
+
+            let res = wallet::sync_wallet(setup.wallet_handle, wallet_handle, "{}");
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+
+            wallet::close_wallet(wallet_handle).unwrap();
+            wallet::delete_wallet(&config, WALLET_CREDENTIALS).unwrap();
+        }
+
+        #[test]
+        fn indy_create_wallet_replica_returns_error_for_invalid_handle() {
+            let setup = Setup::empty();
+
+            let res = wallet::create_wallet_replica(INVALID_WALLET_HANDLE, &config(&setup.name), WALLET_CREDENTIALS);
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+    }
+
+    mod subscribe_wallet {
+        use super::*;
+
+        #[test]
+        fn indy_wallet_subscribe_returns_error_for_invalid_handle() {
+            Setup::empty();
+
+            let res = wallet::subscribe_wallet(INVALID_WALLET_HANDLE, non_secrets::TYPE, non_secrets::QUERY_EMPTY);
+            assert_code!(ErrorCode::WalletInvalidHandle, res);
+        }
+
+        #[test]
+        fn indy_wallet_subscribe_returns_error_for_invalid_query() {
+            let setup = Setup::wallet();
+
+            let res = wallet::subscribe_wallet(setup.wallet_handle, non_secrets::TYPE, r#"{"tagName1": {"$unknown": "str1"}}"#);
+            assert_code!(ErrorCode::WalletQueryError, res);
+        }
+
+        #[test]
+        fn indy_wallet_unsubscribe_returns_error_for_unknown_handle() {
+            let setup = Setup::wallet();
+
+            let (subscription_handle, _) = wallet::subscribe_wallet(setup.wallet_handle, non_secrets::TYPE, non_secrets::QUERY_EMPTY).unwrap();
+            wallet::unsubscribe_wallet(subscription_handle).unwrap();
+
+            let res = wallet::unsubscribe_wallet(subscription_handle);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+    }
+
+    mod import_wallet_from_stream {
+        use super::*;
+
+        #[test]
+        fn indy_import_wallet_from_stream_returns_error_for_invalid_data() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            let res = wallet::import_wallet_from_stream(&config, WALLET_CREDENTIALS, &wallet::prepare_export_stream_config(), vec![1, 2, 3]);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+
+            let res = wallet::open_wallet(&config, WALLET_CREDENTIALS);
+            assert_code!(ErrorCode::WalletNotFoundError, res);
+        }
+
+        #[test]
+        fn indy_import_wallet_from_stream_returns_error_for_other_key() {
+            let setup = Setup::empty();
+            let config = config(&setup.name);
+
+            let (wallet_handle, wallet_config) = wallet::create_and_open_default_wallet(&setup.name).unwrap();
+            did::create_my_did(wallet_handle, "{}").unwrap();
+
+            let data = wallet::export_wallet_to_stream(wallet_handle, &wallet::prepare_export_stream_config()).unwrap();
+
+            wallet::close_and_delete_wallet(wallet_handle, &wallet_config).unwrap();
+
+            let import_config = json!({"key": "other_key"}).to_string();
+
+            let res = wallet::import_wallet_from_stream(&config, WALLET_CREDENTIALS, &import_config, data);
+            assert_code!(ErrorCode::CommonInvalidStructure, res);
+        }
+    }
+
     mod import_wallet {
         use super::*;
 
@@ -726,3 +1393,226 @@ fn _custom_path(name: &str) -> String {
     path.to_str().unwrap().to_owned()
 }
 
+const NATIVE_INMEM_TYPE: &str = "native_inmem";
+
+// Minimal in-memory storage registered through Rust API. Unlike plugged storage it is called
+// by libindy directly, so it has to be Send only at type level and keeps data in global map
+mod native_storage {
+    extern crate indy as libindy;
+
+    use self::libindy::api::wallet::{err_msg, language, register_native_wallet_storage, EncryptedValue, IndyErrorKind, IndyResult, RecordOptions, SearchOptions, StorageIterator, StorageOperation, StorageRecord, Tag, TagName, WalletStorage, WalletStorageType};
+
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use std::vec;
+
+    struct StorageData {
+        metadata: Vec<u8>,
+        records: HashMap<(Vec<u8>, Vec<u8>), (EncryptedValue, Vec<Tag>)>,
+    }
+
+    lazy_static! {
+        static ref STORAGES: Mutex<HashMap<String, Arc<Mutex<StorageData>>>> = Default::default();
+    }
+
+    pub fn register(type_: &str) -> IndyResult<()> {
+        register_native_wallet_storage(type_, Box::new(NativeInmemStorageType {}))
+    }
+
+    pub fn exists(id: &str) -> bool {
+        STORAGES.lock().unwrap().contains_key(id)
+    }
+
+    struct NativeInmemStorageType {}
+
+    impl WalletStorageType for NativeInmemStorageType {
+        fn create_storage(&self, id: &str, _config: Option<&str>, _credentials: Option<&str>, metadata: &[u8]) -> IndyResult<()> {
+            let mut storages = STORAGES.lock().unwrap();
+
+            if storages.contains_key(id) {
+                return Err(err_msg(IndyErrorKind::WalletAlreadyExists, "Wallet already exists"));
+            }
+
+            let data = StorageData { metadata: metadata.to_vec(), records: HashMap::new() };
+            storages.insert(id.to_string(), Arc::new(Mutex::new(data)));
+            Ok(())
+        }
+
+        fn open_storage(&self, id: &str, _config: Option<&str>, _credentials: Option<&str>) -> IndyResult<Box<dyn WalletStorage>> {
+            let data = STORAGES.lock().unwrap().get(id).cloned()
+                .ok_or_else(|| err_msg(IndyErrorKind::WalletNotFound, "Wallet not found"))?;
+
+            Ok(Box::new(NativeInmemStorage { data }))
+        }
+
+        fn delete_storage(&self, id: &str, _config: Option<&str>, _credentials: Option<&str>) -> IndyResult<()> {
+            STORAGES.lock().unwrap().remove(id)
+                .map(|_| ())
+                .ok_or_else(|| err_msg(IndyErrorKind::WalletNotFound, "Wallet not found"))
+        }
+    }
+
+    struct NativeInmemStorage {
+        data: Arc<Mutex<StorageData>>,
+    }
+
+    fn _tag_name(tag: &Tag) -> &[u8] {
+        match *tag {
+            Tag::Encrypted(ref name, _) | Tag::PlainText(ref name, _) => name,
+        }
+    }
+
+    fn _record(type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag], retrieve_type: bool, retrieve_value: bool, retrieve_tags: bool) -> StorageRecord {
+        StorageRecord::new(id.to_vec(),
+                           if retrieve_value { Some(value.clone()) } else { None },
+                           if retrieve_type { Some(type_.to_vec()) } else { None },
+                           if retrieve_tags { Some(tags.to_vec()) } else { None })
+    }
+
+    impl NativeInmemStorage {
+        fn _with_record<F>(&self, type_: &[u8], id: &[u8], f: F) -> IndyResult<()> where F: FnOnce(&mut (EncryptedValue, Vec<Tag>)) {
+            let mut data = self.data.lock().unwrap();
+            let record = data.records.get_mut(&(type_.to_vec(), id.to_vec()))
+                .ok_or_else(|| err_msg(IndyErrorKind::WalletItemNotFound, "Item not found"))?;
+            f(record);
+            Ok(())
+        }
+    }
+
+    impl WalletStorage for NativeInmemStorage {
+        fn get(&self, type_: &[u8], id: &[u8], options: &str) -> IndyResult<StorageRecord> {
+            let options: RecordOptions = serde_json::from_str(options)
+                .map_err(|_| err_msg(IndyErrorKind::InvalidStructure, "RecordOptions is malformed json"))?;
+
+            let data = self.data.lock().unwrap();
+            let (value, tags) = data.records.get(&(type_.to_vec(), id.to_vec()))
+                .ok_or_else(|| err_msg(IndyErrorKind::WalletItemNotFound, "Item not found"))?;
+
+            Ok(_record(type_, id, value, tags, options.retrieve_type(), options.retrieve_value(), options.retrieve_tags()))
+        }
+
+        fn add(&self, type_: &[u8], id: &[u8], value: &EncryptedValue, tags: &[Tag]) -> IndyResult<()> {
+            let mut data = self.data.lock().unwrap();
+            let key = (type_.to_vec(), id.to_vec());
+
+            if data.records.contains_key(&key) {
+                return Err(err_msg(IndyErrorKind::WalletItemAlreadyExists, "Wallet item already exists"));
+            }
+
+            data.records.insert(key, (value.clone(), tags.to_vec()));
+            Ok(())
+        }
+
+        fn update(&self, type_: &[u8], id: &[u8], value: &EncryptedValue) -> IndyResult<()> {
+            self._with_record(type_, id, |record| record.0 = value.clone())
+        }
+
+        fn add_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+            self._with_record(type_, id, |record| {
+                record.1.retain(|tag| !tags.iter().any(|new_tag| _tag_name(new_tag) == _tag_name(tag)));
+                record.1.extend_from_slice(tags);
+            })
+        }
+
+        fn update_tags(&self, type_: &[u8], id: &[u8], tags: &[Tag]) -> IndyResult<()> {
+            self._with_record(type_, id, |record| record.1 = tags.to_vec())
+        }
+
+        fn delete_tags(&self, type_: &[u8], id: &[u8], tag_names: &[TagName]) -> IndyResult<()> {
+            self._with_record(type_, id, |record| {
+                record.1.retain(|tag| !tag_names.iter().any(|tag_name| match (tag_name, tag) {
+                    (TagName::OfEncrypted(name), Tag::Encrypted(stored_name, _)) |
+                    (TagName::OfPlain(name), Tag::PlainText(stored_name, _)) => name == stored_name,
+                    _ => false
+                }))
+            })
+        }
+
+        fn delete(&self, type_: &[u8], id: &[u8]) -> IndyResult<()> {
+            self.data.lock().unwrap().records.remove(&(type_.to_vec(), id.to_vec()))
+                .map(|_| ())
+                .ok_or_else(|| err_msg(IndyErrorKind::WalletItemNotFound, "Item not found"))
+        }
+
+        fn batch(&self, _operations: &[StorageOperation]) -> IndyResult<()> {
+            Err(err_msg(IndyErrorKind::InvalidState, "Transactions are not supported"))
+        }
+
+        fn get_storage_metadata(&self) -> IndyResult<Vec<u8>> {
+            Ok(self.data.lock().unwrap().metadata.clone())
+        }
+
+        fn set_storage_metadata(&self, metadata: &[u8]) -> IndyResult<()> {
+            self.data.lock().unwrap().metadata = metadata.to_vec();
+            Ok(())
+        }
+
+        fn get_all(&self) -> IndyResult<Box<dyn StorageIterator>> {
+            let records = self.data.lock().unwrap().records.iter()
+                .map(|((type_, id), (value, tags))| _record(type_, id, value, tags, true, true, true))
+                .collect::<Vec<StorageRecord>>();
+
+            Ok(Box::new(NativeInmemIterator { records: records.into_iter(), total_count: None }))
+        }
+
+        // Only searches of all records of a type are supported, options are applied as given
+        fn search(&self, type_: &[u8], query: &language::Operator, options: Option<&str>) -> IndyResult<Box<dyn StorageIterator>> {
+            match *query {
+                language::Operator::And(ref operators) if operators.is_empty() => {}
+                _ => return Err(err_msg(IndyErrorKind::WalletQueryError, "Only empty query is supported"))
+            }
+
+            let options: SearchOptions = match options {
+                Some(options) => serde_json::from_str(options)
+                    .map_err(|_| err_msg(IndyErrorKind::InvalidStructure, "SearchOptions is malformed json"))?,
+                None => SearchOptions::default()
+            };
+
+            if options.sort_by().is_some() {
+                return Err(err_msg(IndyErrorKind::WalletQueryError, "Sorting is not supported"));
+            }
+
+            let data = self.data.lock().unwrap();
+            let matched = data.records.iter()
+                .filter(|((record_type, _), _)| record_type.as_slice() == type_)
+                .collect::<Vec<_>>();
+
+            let total_count = if options.retrieve_total_count() { Some(matched.len()) } else { None };
+
+            let records = if options.retrieve_records() {
+                matched.into_iter()
+                    .skip(options.skip().unwrap_or(0))
+                    .take(options.limit().unwrap_or(usize::max_value()))
+                    .map(|((type_, id), (value, tags))| _record(type_, id, value, tags, options.retrieve_type(), options.retrieve_value(), options.retrieve_tags()))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            Ok(Box::new(NativeInmemIterator { records: records.into_iter(), total_count }))
+        }
+
+        fn close(&mut self) -> IndyResult<()> {
+            Ok(())
+        }
+
+        fn supports_transactions(&self) -> bool {
+            false
+        }
+    }
+
+    struct NativeInmemIterator {
+        records: vec::IntoIter<StorageRecord>,
+        total_count: Option<usize>,
+    }
+
+    impl StorageIterator for NativeInmemIterator {
+        fn next(&mut self) -> IndyResult<Option<StorageRecord>> {
+            Ok(self.records.next())
+        }
+
+        fn get_total_count(&self) -> IndyResult<Option<usize>> {
+            Ok(self.total_count)
+        }
+    }
+}
@@ -22,6 +22,14 @@ extern {
                              options_json: CString,
                              cb: Option<ResponseStringCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_resolve_did(command_handle: CommandHandle,
+                            pool_handle: PoolHandle,
+                            wallet_handle: WalletHandle,
+                            did: CString,
+                            options_json: CString,
+                            cb: Option<ResponseStringCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_purge_schema_cache(command_handle: CommandHandle,
                                    wallet_handle: WalletHandle,
@@ -33,4 +41,10 @@ extern {
                                      wallet_handle: WalletHandle,
                                      options_json: CString,
                                      cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_purge_did_doc_cache(command_handle: CommandHandle,
+                                    wallet_handle: WalletHandle,
+                                    options_json: CString,
+                                    cb: Option<ResponseEmptyCB>) -> Error;
 }
@@ -10,6 +10,11 @@ extern {
                            key_json: CString,
                            cb: Option<ResponseStringCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_generate_mnemonic(command_handle: CommandHandle,
+                                  config_json: CString,
+                                  cb: Option<ResponseStringCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_set_key_metadata(command_handle: CommandHandle,
                                  wallet_handle: WalletHandle,
@@ -40,6 +45,24 @@ extern {
                               signature_len: u32,
                               cb: Option<ResponseBoolCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_sign_jws(command_handle: CommandHandle,
+                         wallet_handle: WalletHandle,
+                         signer_vk: CString,
+                         payload_raw: BString,
+                         payload_len: u32,
+                         header_json: CString,
+                         detached: bool,
+                         cb: Option<ResponseStringStringCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_verify_jws(command_handle: CommandHandle,
+                           jws: CString,
+                           signer_vk: CString,
+                           detached_payload_raw: BString,
+                           detached_payload_len: u32,
+                           cb: Option<ResponseBoolCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_crypto_auth_crypt(command_handle: CommandHandle,
                                   wallet_handle: WalletHandle,
@@ -81,6 +104,16 @@ extern {
                              sender: CString,
                              cb: Option<ResponseSliceCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_pack_message_with_options(command_handle: CommandHandle,
+                                          wallet_handle: WalletHandle,
+                                          message: BString,
+                                          message_len: u32,
+                                          receiver_keys: CString,
+                                          sender: CString,
+                                          options_json: CString,
+                                          cb: Option<ResponseSliceCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_unpack_message(command_handle: CommandHandle,
                                wallet_handle: WalletHandle,
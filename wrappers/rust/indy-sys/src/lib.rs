@@ -46,6 +46,7 @@ pub type BlobStorageReaderCfgHandle = i32;
 pub type MetadataHandle = i32;
 pub type Timeout = i32;
 pub type TailsWriterHandle = i32;
+pub type SubscriptionHandle = i32;
 
 pub type Error = i32;
 
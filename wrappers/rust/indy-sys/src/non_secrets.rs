@@ -13,6 +13,16 @@ extern {
                                   tags_json: CString,
                                   cb: Option<ResponseEmptyCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_add_wallet_record_with_options(command_handle: CommandHandle,
+                                               wallet_handle: WalletHandle,
+                                               type_: CString,
+                                               id: CString,
+                                               value: CString,
+                                               tags_json: CString,
+                                               options_json: CString,
+                                               cb: Option<ResponseEmptyCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_update_wallet_record_value(command_handle: CommandHandle,
                                            wallet_handle: WalletHandle,
@@ -21,6 +31,21 @@ extern {
                                            value: CString,
                                            cb: Option<ResponseEmptyCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_update_wallet_record_value_with_options(command_handle: CommandHandle,
+                                                        wallet_handle: WalletHandle,
+                                                        type_: CString,
+                                                        id: CString,
+                                                        value: CString,
+                                                        options_json: CString,
+                                                        cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_wallet_batch_execute(command_handle: CommandHandle,
+                                     wallet_handle: WalletHandle,
+                                     operations_json: CString,
+                                     cb: Option<ResponseEmptyCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_update_wallet_record_tags(command_handle: CommandHandle,
                                           wallet_handle: WalletHandle,
@@ -68,6 +93,11 @@ extern {
                                    options_json: CString,
                                    cb: Option<ResponseI32CB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_open_wallet_all_records_search(command_handle: CommandHandle,
+                                               wallet_handle: WalletHandle,
+                                               cb: Option<ResponseI32CB>) -> Error;
+
     #[no_mangle]
     pub fn indy_fetch_wallet_search_next_records(command_handle: CommandHandle,
                                                  wallet_handle: WalletHandle,
@@ -79,4 +109,12 @@ extern {
     pub fn indy_close_wallet_search(command_handle: CommandHandle,
                                     wallet_search_handle: SearchHandle,
                                     cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_wallet_aggregate(command_handle: CommandHandle,
+                                 wallet_handle: WalletHandle,
+                                 type_: CString,
+                                 query_json: CString,
+                                 aggregation_json: CString,
+                                 cb: Option<ResponseStringCB>) -> Error;
 }
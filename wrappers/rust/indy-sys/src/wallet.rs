@@ -1,6 +1,6 @@
 use super::*;
 
-use {BString, CString, Error, CommandHandle, StorageHandle};
+use {BString, CString, Error, CommandHandle, StorageHandle, SubscriptionHandle};
 
 extern {
 
@@ -33,6 +33,21 @@ extern {
                                         free_search: Option<WalletFreeSearch>,
                                         cb: Option<ResponseEmptyCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_register_wallet_storage_transaction_hooks(command_handle: CommandHandle,
+                                                          type_: CString,
+                                                          begin_transaction: Option<WalletBeginTransaction>,
+                                                          commit_transaction: Option<WalletCommitTransaction>,
+                                                          rollback_transaction: Option<WalletRollbackTransaction>,
+                                                          cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_register_wallet_storage_aggregation(command_handle: CommandHandle,
+                                                    type_: CString,
+                                                    aggregate_records: Option<WalletAggregateRecords>,
+                                                    free_aggregation: Option<WalletFreeAggregation>,
+                                                    cb: Option<ResponseEmptyCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_create_wallet(command_handle: CommandHandle,
                               config: CString,
@@ -51,6 +66,20 @@ extern {
                               export_config: CString,
                               cb: Option<ResponseEmptyCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_export_wallet_to_stream(command_handle: CommandHandle,
+                                        wallet_handle: WalletHandle,
+                                        export_config: CString,
+                                        write_cb: Option<WalletExportWrite>,
+                                        cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_rekey_wallet(command_handle: CommandHandle,
+                             wallet_handle: WalletHandle,
+                             new_credentials: CString,
+                             progress_cb: Option<extern fn(command_handle: CommandHandle, completed_steps: u32, total_steps: u32)>,
+                             cb: Option<ResponseEmptyCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_import_wallet(command_handle: CommandHandle,
                               config: CString,
@@ -58,6 +87,58 @@ extern {
                               import_config: CString,
                               cb: Option<ResponseEmptyCB>) -> Error;
 
+    #[no_mangle]
+    pub fn indy_import_wallet_from_stream(command_handle: CommandHandle,
+                                          config: CString,
+                                          credentials: CString,
+                                          import_config: CString,
+                                          read_cb: Option<WalletImportRead>,
+                                          cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_import_into_wallet(command_handle: CommandHandle,
+                                   wallet_handle: WalletHandle,
+                                   import_config: CString,
+                                   cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_inspect_wallet_export(command_handle: CommandHandle,
+                                      export_config: CString,
+                                      cb: Option<ResponseStringCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_check_wallet(command_handle: CommandHandle,
+                             wallet_handle: WalletHandle,
+                             options_json: CString,
+                             cb: Option<ResponseStringCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_create_wallet_replica(command_handle: CommandHandle,
+                                      wallet_handle: WalletHandle,
+                                      config: CString,
+                                      credentials: CString,
+                                      cb: Option<ResponseEmptyCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_sync_wallet(command_handle: CommandHandle,
+                            source_wallet_handle: WalletHandle,
+                            target_wallet_handle: WalletHandle,
+                            options_json: CString,
+                            cb: Option<ResponseStringCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_wallet_subscribe(command_handle: CommandHandle,
+                                 wallet_handle: WalletHandle,
+                                 type_: CString,
+                                 query_json: CString,
+                                 notify_cb: Option<WalletRecordChanged>,
+                                 cb: Option<ResponseI32CB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_wallet_unsubscribe(command_handle: CommandHandle,
+                                   subscription_handle: SubscriptionHandle,
+                                   cb: Option<ResponseEmptyCB>) -> Error;
+
     #[no_mangle]
     pub fn indy_close_wallet(command_handle: CommandHandle,
                              wallet_handle: WalletHandle,
@@ -155,3 +236,25 @@ pub type WalletFetchSearchNextRecord = extern fn(storage_handle: StorageHandle,
                                                  record_handle_p: *mut RecordHandle) -> Error;
 pub type WalletFreeSearch = extern fn(storage_handle: StorageHandle,
                                       search_handle: SearchHandle) -> Error;
+pub type WalletBeginTransaction = extern fn(storage_handle: StorageHandle) -> Error;
+pub type WalletCommitTransaction = extern fn(storage_handle: StorageHandle) -> Error;
+pub type WalletRollbackTransaction = extern fn(storage_handle: StorageHandle) -> Error;
+pub type WalletAggregateRecords = extern fn(storage_handle: StorageHandle,
+                                            type_: CString,
+                                            query_json: CString,
+                                            group_by_json: CString,
+                                            result_json_p: *mut CString,
+                                            result_handle_p: *mut IndyHandle) -> Error;
+pub type WalletFreeAggregation = extern fn(storage_handle: StorageHandle,
+                                           result_handle: IndyHandle) -> Error;
+pub type WalletExportWrite = extern fn(command_handle: CommandHandle,
+                                       data: *const u8,
+                                       data_len: u32) -> Error;
+pub type WalletImportRead = extern fn(command_handle: CommandHandle,
+                                      buffer: *mut u8,
+                                      buffer_len: u32,
+                                      read_len_p: *mut u32) -> Error;
+pub type WalletRecordChanged = extern fn(command_handle: CommandHandle,
+                                        subscription_handle: SubscriptionHandle,
+                                        operation: CString,
+                                        id: CString);
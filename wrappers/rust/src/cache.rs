@@ -128,6 +128,68 @@ pub fn _get_cred_def(command_handle: CommandHandle,
     )
 }
 
+/// Resolve DID to W3C DID Document built from ledger data.
+/// If data is present inside of cache, cached data is returned.
+/// Otherwise data is fetched from the ledger and stored inside of cache for future use.
+///
+/// DID Document contains NYM verkey as Ed25519VerificationKey2018 verification method
+/// and X25519 key agreement key derived from it, services built from `endpoint` ATTRIB
+/// and content of `diddocContent` ATTRIB merged in.
+///
+/// EXPERIMENTAL
+///
+/// # Arguments
+/// * `pool_handle` - pool handle (created by open_pool_ledger).
+/// * `wallet_handle` - wallet handle (created by open_wallet).
+/// * `did` - DID to resolve.
+/// * `options_json` -
+///  {
+///    noCache: (bool, optional, false by default) Skip usage of cache,
+///    noUpdate: (bool, optional, false by default) Use only cached data, do not try to update.
+///    noStore: (bool, optional, false by default) Skip storing fresh data if updated,
+///    minFresh: (int, optional, 3600 by default) Return cached data if not older than this many seconds. -1 means do not check age.
+///  }
+/// # Returns
+/// DID resolution result json.
+/// {
+///     didDocument: DID Document json,
+///     didDocumentMetadata: {
+///         seqNo: (optional) sequence number of NYM transaction,
+///         txnTime: (optional) time of NYM transaction,
+///         role: (optional) role of DID,
+///     },
+///     didResolutionMetadata: {
+///         contentType: "application/did+ld+json",
+///         lastTxnTime: (optional) time of ledger state NYM reply was verified against,
+///     }
+/// }
+pub fn resolve_did(pool_handle: PoolHandle,
+                   wallet_handle: WalletHandle,
+                   did: &str,
+                   options_json: &str) -> Box<dyn Future<Item=String, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+    let err = _resolve_did(command_handle, pool_handle, wallet_handle, did, options_json, cb);
+
+    ResultHandler::str(command_handle, err, receiver)
+}
+
+pub fn _resolve_did(command_handle: CommandHandle,
+                    pool_handle: PoolHandle,
+                    wallet_handle: WalletHandle,
+                    did: &str,
+                    options_json: &str,
+                    cb: Option<ResponseStringCB>) -> ErrorCode {
+    let did = c_str!(did);
+    let options_json = c_str!(options_json);
+
+    ErrorCode::from(
+        unsafe {
+            cache::indy_resolve_did(command_handle, pool_handle, wallet_handle, did.as_ptr(), options_json.as_ptr(), cb)
+        }
+    )
+}
+
 /// Purge schema cache.
 ///
 /// EXPERIMENTAL
@@ -176,4 +238,27 @@ fn _purge_cred_def_cache(command_handle: CommandHandle, wallet_handle: WalletHan
     let options_json = c_str!(options_json);
 
     ErrorCode::from(unsafe { cache::indy_purge_cred_def_cache(command_handle, wallet_handle, options_json.as_ptr(), cb) })
-}
\ No newline at end of file
+}
+/// Purge DID Document cache.
+///
+/// EXPERIMENTAL
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle (created by open_wallet).
+/// * `options_json` -
+///  {
+///    maxAge: (int, optional, -1 by default) Purge cached data if older than this many seconds. -1 means purge all.
+///  }
+pub fn purge_did_doc_cache(wallet_handle: WalletHandle, options_json: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _purge_did_doc_cache(command_handle, wallet_handle, options_json, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _purge_did_doc_cache(command_handle: CommandHandle, wallet_handle: WalletHandle, options_json: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let options_json = c_str!(options_json);
+
+    ErrorCode::from(unsafe { cache::indy_purge_did_doc_cache(command_handle, wallet_handle, options_json.as_ptr(), cb) })
+}
@@ -1,6 +1,7 @@
 use ffi::crypto;
 use ffi::{ResponseEmptyCB,
           ResponseStringCB,
+          ResponseStringStringCB,
           ResponseSliceCB,
           ResponseBoolCB,
           ResponseStringSliceCB};
@@ -24,7 +25,13 @@ use {WalletHandle, CommandHandle};
 /// {
 ///     "seed": string, (optional) Seed that allows deterministic key creation (if not set random one will be created).
 ///                                Can be UTF-8, base64 or hex string.
-///     "crypto_type": string, // Optional (if not set then ed25519 curve is used); Currently only 'ed25519' value is supported for this field.
+///     "crypto_type": string, // Optional (if not set then ed25519 curve is used); Supported values are 'ed25519', 'secp256k1' and 'p256'. 'secp256k1' and 'p256' keys can be used only for signing.
+///     "custody": string, // Optional name of registered key custody that keeps secret key instead of the wallet.
+///     "mnemonic": { // Optional BIP-39 mnemonic to derive ed25519 key seed from instead of "seed" (see crypto::generate_mnemonic).
+///         "phrase": string, // Mnemonic words separated by spaces.
+///         "passphrase": string, // Optional BIP-39 passphrase.
+///         "derivation_path": string, // SLIP-0010 path with hardened indexes only, for example "m/0'/1'".
+///     }
 /// }
 /// # Returns
 /// verkey of generated key pair, also used as key identifier
@@ -42,6 +49,31 @@ fn _create_key(command_handle: CommandHandle, wallet_handle: WalletHandle, my_ke
     ErrorCode::from(unsafe { crypto::indy_create_key(command_handle, wallet_handle, my_key_json.as_ptr(), cb) })
 }
 
+/// Generates BIP-39 mnemonic for deterministic creation of keys and DIDs.
+///
+/// Mnemonic isn't stored in the wallet. The same keys and DIDs can be created again in any wallet
+/// by passing mnemonic with the same derivation path to `create_key` or `did::create_and_store_my_did`.
+/// # Arguments
+/// * `config_json` - Mnemonic config as json. Example:
+/// {
+///     "word_count": int, // Optional number of words (if not set then 24 is used); Supported values are 12, 15, 18, 21 and 24.
+/// }
+/// # Returns
+/// mnemonic words of BIP-39 English wordlist separated by spaces
+pub fn generate_mnemonic(config_json: Option<&str>) -> Box<dyn Future<Item=String, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+    let err = _generate_mnemonic(command_handle, config_json, cb);
+
+    ResultHandler::str(command_handle, err, receiver)
+}
+
+fn _generate_mnemonic(command_handle: CommandHandle, config_json: Option<&str>, cb: Option<ResponseStringCB>) -> ErrorCode {
+    let config_json = opt_c_str_json!(config_json);
+
+    ErrorCode::from(unsafe { crypto::indy_generate_mnemonic(command_handle, config_json.as_ptr(), cb) })
+}
+
 /// Saves/replaces the metadata for the `verkey` in the wallet
 /// # Arguments
 /// * `wallet_handle` - wallet handle (created by Wallet::open)
@@ -133,6 +165,68 @@ fn _verify(command_handle: CommandHandle, signer_vk: &str, message: &[u8], signa
     })
 }
 
+/// Signs a payload with a key and serializes the result as JWS (RFC 7515)
+///
+/// Algorithm is defined by the key crypto type: EdDSA for ed25519, ES256K for secp256k1 and ES256 for p256 keys.
+/// # Arguments
+/// * `wallet_handle` - wallet handle (created by Wallet::open)
+/// * `signer_vk` - key id or verkey of my key. The key must be created by calling create_key or Did::new
+/// * `payload` - the data to be signed
+/// * `header_json` - (optional) additional protected header parameters as json object. "kid" defaults to signer_vk
+/// * `detached` - whether payload must be omitted from the result
+/// # Returns
+/// JWS in compact and in flattened JSON serializations
+pub fn sign_jws(wallet_handle: WalletHandle, signer_vk: &str, payload: &[u8], header_json: Option<&str>, detached: bool) -> Box<dyn Future<Item=(String, String), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string_string();
+
+    let err = _sign_jws(command_handle, wallet_handle, signer_vk, payload, header_json, detached, cb);
+
+    ResultHandler::str_str(command_handle, err, receiver)
+}
+
+fn _sign_jws(command_handle: CommandHandle, wallet_handle: WalletHandle, signer_vk: &str, payload: &[u8], header_json: Option<&str>, detached: bool, cb: Option<ResponseStringStringCB>) -> ErrorCode {
+    let signer_vk = c_str!(signer_vk);
+    let header_json_str = opt_c_str!(header_json);
+
+    ErrorCode::from(unsafe {
+        crypto::indy_sign_jws(command_handle, wallet_handle, signer_vk.as_ptr(),
+                              payload.as_ptr() as *const u8, payload.len() as u32,
+                              opt_c_ptr!(header_json, header_json_str),
+                              detached,
+                              cb)
+    })
+}
+
+/// Verifies a JWS (RFC 7515) in compact or JSON serialization
+/// # Arguments
+/// * `jws` - JWS to verify
+/// * `signer_vk` - verkey of the expected signer. "kid" header parameter isn't trusted as it is set by the signer
+/// * `detached_payload` - (optional) payload of JWS with detached content
+/// # Returns
+/// true if signature is valid, false otherwise
+pub fn verify_jws(jws: &str, signer_vk: &str, detached_payload: Option<&[u8]>) -> Box<dyn Future<Item=bool, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_bool();
+
+    let err = _verify_jws(command_handle, jws, signer_vk, detached_payload, cb);
+
+    ResultHandler::bool(command_handle, err, receiver)
+}
+
+fn _verify_jws(command_handle: CommandHandle, jws: &str, signer_vk: &str, detached_payload: Option<&[u8]>, cb: Option<ResponseBoolCB>) -> ErrorCode {
+    let jws = c_str!(jws);
+    let signer_vk = c_str!(signer_vk);
+    let (detached_payload_raw, detached_payload_len) = detached_payload
+        .map(|payload| (payload.as_ptr() as *const u8, payload.len() as u32))
+        .unwrap_or((null(), 0));
+
+    ErrorCode::from(unsafe {
+        crypto::indy_verify_jws(command_handle, jws.as_ptr(),
+                                signer_vk.as_ptr(),
+                                detached_payload_raw, detached_payload_len,
+                                cb)
+    })
+}
+
 /// **** THIS FUNCTION WILL BE DEPRECATED USE pack_message INSTEAD ****
 /// Encrypt a message by authenticated-encryption scheme.
 ///
@@ -317,8 +411,50 @@ fn _pack_message(command_handle: CommandHandle, wallet_handle: WalletHandle, mes
 
 }
 
+/// Packs a message in the selected envelope format (Experimental)
+///
+///
+/// # Arguments
+/// * `wallet_handle`: wallet handle (created by Wallet::open).
+/// * `message`: a pointer to the first byte of the message to be encrypted
+/// * `receiver_keys`: a JSON array as a string containing a list of the receivers verkey's
+/// * `sender` : a string of the sender's verkey When None is used in this parameter, anoncrypt is used
+/// * `options_json`: pack options as json:
+///   {
+///     format: (optional, string) envelope format. One of:
+///             "legacy" - JWE-like format produced by pack_message (default)
+///             "didcomm_v2" - standard JWE defined by DIDComm Messaging v2.
+///                            ECDH-1PU+A256KW is used if sender is set and ECDH-ES+A256KW otherwise.
+///   }
+/// # Returns
+/// a json structure in the form of a JWE that contains the encrypted message and associated metadata
+pub fn pack_message_with_options(wallet_handle: WalletHandle, message: &[u8], receiver_keys: &str, sender: Option<&str>, options_json: &str) -> Box<dyn Future<Item=Vec<u8>, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_slice();
+
+    let err = _pack_message_with_options(command_handle, wallet_handle, message, receiver_keys, sender, options_json, cb);
+
+    ResultHandler::slice(command_handle, err, receiver)
+}
+
+fn _pack_message_with_options(command_handle: CommandHandle, wallet_handle: WalletHandle, message: &[u8], receiver_keys: &str, sender: Option<&str>, options_json: &str, cb: Option<ResponseSliceCB>) -> ErrorCode {
+    let receiver_keys = c_str!(receiver_keys);
+    let sender_str = opt_c_str!(sender);
+    let options_json = c_str!(options_json);
+
+    ErrorCode::from(unsafe {
+        crypto::indy_pack_message_with_options(command_handle,
+                                               wallet_handle,
+                                               message.as_ptr() as *const u8,
+                                               message.len() as u32,
+                                               receiver_keys.as_ptr(),
+                                               opt_c_ptr!(sender, sender_str),
+                                               options_json.as_ptr(),
+                                               cb)
+    })
+}
+
 /// Unpacks a message packed using indy_pack_message which follows the wire message format HIPE
-/// (Experimental)
+/// or a DIDComm v2 JWE produced by pack_message_with_options (Experimental)
 ///
 ///
 /// # Arguments
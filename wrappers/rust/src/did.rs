@@ -32,9 +32,20 @@ use {CommandHandle, WalletHandle, PoolHandle};
 ///     "seed": string, (optional) Seed that allows deterministic key creation (if not set random one will be created).
 ///                                Can be UTF-8, base64 or hex string.
 ///     "crypto_type": string, (optional; if not set then ed25519 curve is used;
-///               currently only 'ed25519' value is supported for this field)
+///               'ed25519', 'secp256k1' and 'p256' values are supported for this field;
+///               'secp256k1' and 'p256' keys can be used only for signing and only with "key" or "peer"
+///               method_name or explicitly provided did)
+///     "custody": string, (optional) name of registered key custody that keeps secret key instead of the wallet.
+///     "mnemonic": json, (optional) BIP-39 mnemonic to derive ed25519 key seed from instead of "seed" (see crypto::generate_mnemonic):
+///         {"phrase": string, "passphrase": string (optional), "derivation_path": string}
+///         derivation_path is SLIP-0010 path with hardened indexes only, for example "m/0'/1'".
 ///     "cid": bool, (optional; if not set then false is used;)
 ///     "method_name": string, (optional) method name to create fully qualified did.
+///               'key' and 'peer' methods create did:key and did:peer DIDs that encode the verkey itself
+///               (Example: `did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK`)
+///     "peer_numalgo": int, (optional; if not set then 0 is used; can be set only for 'peer' method)
+///               0 - did:peer encodes the verkey only;
+///               2 - did:peer encodes the verkey and X25519 key agreement key (ed25519 keys only)
 /// }
 ///
 /// # Returns
@@ -67,7 +78,12 @@ fn _create_and_store_my_did(command_handle: CommandHandle, wallet_handle: Wallet
 ///     "seed": string, (optional) Seed that allows deterministic key creation (if not set random one will be created).
 ///                                Can be UTF-8, base64 or hex string.
 ///     "crypto_type": string, (optional; if not set then ed25519 curve is used;
-///               currently only 'ed25519' value is supported for this field)
+///               'ed25519', 'secp256k1' and 'p256' values are supported for this field;
+///               'secp256k1' and 'p256' keys can be used only for signing)
+///     "custody": string, (optional) name of registered key custody that keeps secret key instead of the wallet.
+///     "mnemonic": json, (optional) BIP-39 mnemonic to derive ed25519 key seed from instead of "seed" (see crypto::generate_mnemonic):
+///         {"phrase": string, "passphrase": string (optional), "derivation_path": string}
+///         derivation_path is SLIP-0010 path with hardened indexes only, for example "m/0'/1'".
 /// }
 ///
 /// # Returns
@@ -148,6 +164,9 @@ fn _store_their_did(command_handle: CommandHandle, wallet_handle: WalletHandle,
 /// Note that "new" makes similar wallet record as "Key::create_key".
 /// As result we can use returned ver key in all generic crypto and messaging functions.
 ///
+/// Note that did:key and did:peer DIDs encode ver key in the DID itself so it is returned
+/// without wallet and ledger lookup.
+///
 /// # Arguments
 /// * `pool_handle` - Pool handle (created by Pool::open).
 /// * `wallet_handle` - Wallet handle (created by Wallet::open).
@@ -180,6 +199,9 @@ fn _key_for_did(command_handle: CommandHandle, pool_handle: PoolHandle, wallet_h
 /// Note that "new" makes similar wallet record as "Key::create_key".
 /// As result we can use returned ver key in all generic crypto and messaging functions.
 ///
+/// Note that did:key and did:peer DIDs encode ver key in the DID itself so it is returned
+/// without wallet and ledger lookup.
+///
 /// # Arguments
 /// * `wallet_handle` - Wallet handle (created by Wallet::open).
 /// * `did` - The DID to resolve key.
@@ -54,6 +54,7 @@ pub use ffi::{
     PoolHandle,
     SearchHandle,
     StorageHandle,
+    SubscriptionHandle,
     INVALID_WALLET_HANDLE,
     INVALID_POOL_HANDLE,
     INVALID_COMMAND_HANDLE
@@ -1,9 +1,15 @@
 use futures::Future;
 
+use libc::c_char;
+
 use {ErrorCode, IndyError};
 
-use std::ffi::CString;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::io::{Read, Write};
 use std::ptr::null;
+use std::slice;
+use std::sync::{Arc, Mutex};
 
 use utils::callbacks::{ClosureHandler, ResultHandler};
 
@@ -12,7 +18,14 @@ use ffi::{ResponseEmptyCB,
           ResponseStringCB,
           ResponseI32CB,
           ResponseWalletHandleCB};
-use {CommandHandle, WalletHandle, SearchHandle};
+use {CommandHandle, WalletHandle, SearchHandle, SubscriptionHandle};
+
+lazy_static! {
+    static ref EXPORT_WRITERS: Mutex<HashMap<CommandHandle, Box<dyn Write + Send>>> = Default::default();
+    static ref IMPORT_READERS: Mutex<HashMap<CommandHandle, Box<dyn Read + Send>>> = Default::default();
+    static ref SUBSCRIPTION_CALLBACKS: Mutex<HashMap<CommandHandle, Arc<dyn Fn(&str, &str) + Send + Sync>>> = Default::default();
+    static ref SUBSCRIPTIONS: Mutex<HashMap<SubscriptionHandle, CommandHandle>> = Default::default();
+}
 
 /// Registers custom wallet implementation.
 ///
@@ -148,6 +161,72 @@ fn _register_storage(command_handle: CommandHandle,
     })
 }
 
+/// Registers transaction handlers for custom wallet storage implementation.
+///
+/// Registered handlers allow `wallet_batch_execute` to be used with wallets
+/// of this storage type. Handlers must be registered before the wallet is opened.
+///
+/// # Arguments
+/// * `xtype` - Wallet type name (previously registered with `register_wallet_storage`).
+/// * `begin_transaction` - WalletType begin transaction operation handler
+/// * `commit_transaction` - WalletType commit transaction operation handler
+/// * `rollback_transaction` - WalletType rollback transaction operation handler
+pub fn register_wallet_storage_transaction_hooks(xtype: &str,
+                                                 begin_transaction: Option<wallet::WalletBeginTransaction>,
+                                                 commit_transaction: Option<wallet::WalletCommitTransaction>,
+                                                 rollback_transaction: Option<wallet::WalletRollbackTransaction>) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _register_storage_transaction_hooks(command_handle, xtype, begin_transaction, commit_transaction, rollback_transaction, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _register_storage_transaction_hooks(command_handle: CommandHandle,
+                                       xtype: &str,
+                                       begin_transaction: Option<wallet::WalletBeginTransaction>,
+                                       commit_transaction: Option<wallet::WalletCommitTransaction>,
+                                       rollback_transaction: Option<wallet::WalletRollbackTransaction>,
+                                       cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let xtype = c_str!(xtype);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_register_wallet_storage_transaction_hooks(command_handle, xtype.as_ptr(), begin_transaction, commit_transaction, rollback_transaction, cb)
+    })
+}
+
+/// Registers aggregation handlers for custom wallet storage implementation.
+///
+/// Registered handlers allow `wallet_aggregate` to be computed by the storage natively.
+/// Without them records matching the query are fetched and counted one by one.
+/// Handlers must be registered before the wallet is opened.
+///
+/// # Arguments
+/// * `xtype` - Wallet type name (previously registered with `register_wallet_storage`).
+/// * `aggregate_records` - WalletType aggregate records operation handler
+/// * `free_aggregation` - WalletType free aggregation result operation handler
+pub fn register_wallet_storage_aggregation(xtype: &str,
+                                           aggregate_records: Option<wallet::WalletAggregateRecords>,
+                                           free_aggregation: Option<wallet::WalletFreeAggregation>) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _register_storage_aggregation(command_handle, xtype, aggregate_records, free_aggregation, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _register_storage_aggregation(command_handle: CommandHandle,
+                                 xtype: &str,
+                                 aggregate_records: Option<wallet::WalletAggregateRecords>,
+                                 free_aggregation: Option<wallet::WalletFreeAggregation>,
+                                 cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let xtype = c_str!(xtype);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_register_wallet_storage_aggregation(command_handle, xtype.as_ptr(), aggregate_records, free_aggregation, cb)
+    })
+}
+
 /// Creates a new secure wallet with the given unique name.
 ///
 /// # Arguments
@@ -205,6 +284,38 @@ fn _open_wallet(command_handle: CommandHandle, config: &str, credentials: &str,
     })
 }
 
+/// Changes the key of opened wallet.
+///
+/// Wallet keys stored in the wallet metadata are re-encrypted with the master key
+/// derived from new credentials. Wallet doesn't need to be reopened, but next opening
+/// must use the new credentials.
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle returned by open_wallet
+/// * `new_credentials` - New wallet credentials json
+///   {
+///     "key": string, New key or passphrase used for wallet key derivation.
+///     "key_derivation_method": optional<string> Algorithm to use for wallet key derivation:
+///                              ARGON2I_MOD - derive secured wallet master key (used by default)
+///                              ARGON2I_INT - derive secured wallet master key (less secured but faster)
+///                              RAW - raw wallet key master provided (skip derivation).
+///   }
+pub fn rekey_wallet(wallet_handle: WalletHandle, new_credentials: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _rekey_wallet(command_handle, wallet_handle, new_credentials, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _rekey_wallet(command_handle: CommandHandle, wallet_handle: WalletHandle, new_credentials: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let new_credentials = c_str!(new_credentials);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_rekey_wallet(command_handle, wallet_handle, new_credentials.as_ptr(), None, cb)
+    })
+}
+
 /// Exports opened wallet
 ///
 /// Note this endpoint is EXPERIMENTAL. Function signature and behaviour may change
@@ -216,6 +327,8 @@ fn _open_wallet(command_handle: CommandHandle, config: &str, credentials: &str,
 ///   {
 ///     "path": path of the file that contains exported wallet content
 ///     "key": passphrase used to derive export key
+///     "types": optional<array<string>> export only records of the given types
+///     "query": optional<object> export only records matching the given WQL query (requires "types")
 ///   }
 pub fn export_wallet(wallet_handle: WalletHandle, export_config: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
     let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
@@ -233,6 +346,61 @@ fn _export_wallet(command_handle: CommandHandle, wallet_handle: WalletHandle, ex
     })
 }
 
+/// Exports opened wallet to the given writer
+///
+/// Exported data has the same format as export_wallet produces.
+///
+/// # Arguments:
+/// * `wallet_handle` - wallet handle returned by indy_open_wallet
+/// * `export_config` - JSON containing settings for input operation.
+///   {
+///     "key": passphrase used to derive export key
+///     "types": optional<array<string>> export only records of the given types
+///     "query": optional<object> export only records matching the given WQL query (requires "types")
+///   }
+/// * `writer` - destination of exported data
+pub fn export_wallet_to_stream(wallet_handle: WalletHandle, export_config: &str, writer: Box<dyn Write + Send>) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    EXPORT_WRITERS.lock().unwrap().insert(command_handle, writer);
+
+    let err = _export_wallet_to_stream(command_handle, wallet_handle, export_config, cb);
+
+    Box::new(ResultHandler::empty(command_handle, err, receiver)
+        .then(move |res| {
+            let writer = EXPORT_WRITERS.lock().unwrap().remove(&command_handle);
+
+            match (res, writer) {
+                (Ok(()), Some(mut writer)) => writer.flush().map_err(|_| IndyError::new(ErrorCode::CommonIOError)),
+                (res, _) => res
+            }
+        }))
+}
+
+fn _export_wallet_to_stream(command_handle: CommandHandle, wallet_handle: WalletHandle, export_config: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let export_config = c_str!(export_config);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_export_wallet_to_stream(command_handle, wallet_handle, export_config.as_ptr(), Some(_export_write), cb)
+    })
+}
+
+extern fn _export_write(command_handle: CommandHandle, data: *const u8, data_len: u32) -> i32 {
+    let mut writers = EXPORT_WRITERS.lock().unwrap();
+
+    let writer = match writers.get_mut(&command_handle) {
+        Some(writer) => writer,
+        None => return ErrorCode::CommonInvalidState as i32
+    };
+
+    let data = unsafe { slice::from_raw_parts(data, data_len as usize) };
+
+    match writer.write_all(data) {
+        Ok(()) => ErrorCode::Success as i32,
+        Err(_) => ErrorCode::CommonIOError as i32
+    }
+}
+
 /// Creates a new secure wallet with the given unique name and then imports its content
 /// according to fields provided in import_config
 /// This can be seen as an create call with additional content import
@@ -274,6 +442,270 @@ fn _import_wallet(command_handle: CommandHandle, config: &str, credentials: &str
     })
 }
 
+/// Creates a new secure wallet with the given unique name and then imports its content
+/// from the given reader
+///
+/// # Arguments
+/// * `config` - Wallet configuration json (see import_wallet).
+/// * `credentials` - Wallet credentials json (see import_wallet).
+/// * `import_config` - Import settings json.
+///   {
+///       "key": key used for export of the wallet
+///   }
+/// * `reader` - source of data produced by export_wallet or export_wallet_to_stream
+pub fn import_wallet_from_stream(config: &str, credentials: &str, import_config: &str, reader: Box<dyn Read + Send>) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    IMPORT_READERS.lock().unwrap().insert(command_handle, reader);
+
+    let err = _import_wallet_from_stream(command_handle, config, credentials, import_config, cb);
+
+    Box::new(ResultHandler::empty(command_handle, err, receiver)
+        .then(move |res| {
+            IMPORT_READERS.lock().unwrap().remove(&command_handle);
+            res
+        }))
+}
+
+fn _import_wallet_from_stream(command_handle: CommandHandle, config: &str, credentials: &str, import_config: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let config = c_str!(config);
+    let credentials = c_str!(credentials);
+    let import_config = c_str!(import_config);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_import_wallet_from_stream(command_handle, config.as_ptr(), credentials.as_ptr(), import_config.as_ptr(), Some(_import_read), cb)
+    })
+}
+
+extern fn _import_read(command_handle: CommandHandle, buffer: *mut u8, buffer_len: u32, read_len_p: *mut u32) -> i32 {
+    let mut readers = IMPORT_READERS.lock().unwrap();
+
+    let reader = match readers.get_mut(&command_handle) {
+        Some(reader) => reader,
+        None => return ErrorCode::CommonInvalidState as i32
+    };
+
+    let buffer = unsafe { slice::from_raw_parts_mut(buffer, buffer_len as usize) };
+
+    match reader.read(buffer) {
+        Ok(read_len) => {
+            unsafe { *read_len_p = read_len as u32; }
+            ErrorCode::Success as i32
+        }
+        Err(_) => ErrorCode::CommonIOError as i32
+    }
+}
+
+/// Imports content of exported wallet into opened wallet.
+///
+/// Imported records are merged with the records that wallet already contains.
+/// Import fails with WalletItemAlreadyExists error without importing anything if any record conflicts.
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle returned by open_wallet
+/// * `import_config` - JSON containing settings for input operation.
+///   {
+///     "path": path of the file that contains exported wallet content
+///     "key": passphrase used to derive export key
+///   }
+pub fn import_into_wallet(wallet_handle: WalletHandle, import_config: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _import_into_wallet(command_handle, wallet_handle, import_config, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _import_into_wallet(command_handle: CommandHandle, wallet_handle: WalletHandle, import_config: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let import_config = c_str!(import_config);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_import_into_wallet(command_handle, wallet_handle, import_config.as_ptr(), cb)
+    })
+}
+
+/// Verifies exported wallet file without importing it.
+///
+/// # Arguments
+/// * `export_config` - JSON containing settings of export file.
+///   {
+///     "path": path of the file that contains exported wallet content
+///     "key": passphrase used to derive export key
+///   }
+///
+/// # Returns
+/// Export summary json with export version, time, encryption and key derivation methods,
+/// number of records per type, total number of records and size of export file in bytes
+pub fn inspect_wallet_export(export_config: &str) -> Box<dyn Future<Item=String, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+    let err = _inspect_wallet_export(command_handle, export_config, cb);
+
+    ResultHandler::str(command_handle, err, receiver)
+}
+
+fn _inspect_wallet_export(command_handle: CommandHandle, export_config: &str, cb: Option<ResponseStringCB>) -> ErrorCode {
+    let export_config = c_str!(export_config);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_inspect_wallet_export(command_handle, export_config.as_ptr(), cb)
+    })
+}
+
+/// Checks internal consistency of opened wallet.
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle returned by open_wallet
+/// * `options_json` - JSON containing check options.
+///   {
+///     "repair": optional<bool>, fix storage specific problems and delete records that cannot be decrypted
+///   }
+///
+/// # Returns
+/// Check report json with number of checked records and list of found issues
+pub fn check_wallet(wallet_handle: WalletHandle, options_json: &str) -> Box<dyn Future<Item=String, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+    let err = _check_wallet(command_handle, wallet_handle, options_json, cb);
+
+    ResultHandler::str(command_handle, err, receiver)
+}
+
+fn _check_wallet(command_handle: CommandHandle, wallet_handle: WalletHandle, options_json: &str, cb: Option<ResponseStringCB>) -> ErrorCode {
+    let options_json = c_str!(options_json);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_check_wallet(command_handle, wallet_handle, options_json.as_ptr(), cb)
+    })
+}
+
+/// Creates a new wallet that shares encryption keys with opened wallet.
+///
+/// # Arguments
+/// * `wallet_handle` - handle of the source wallet returned by open_wallet
+/// * `config` - replica configuration json, same as for create_wallet
+/// * `credentials` - replica credentials json, same as for create_wallet.
+///   Only storage credentials are used, wallet key is inherited from the source wallet
+pub fn create_wallet_replica(wallet_handle: WalletHandle, config: &str, credentials: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _create_wallet_replica(command_handle, wallet_handle, config, credentials, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _create_wallet_replica(command_handle: CommandHandle, wallet_handle: WalletHandle, config: &str, credentials: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let config = c_str!(config);
+    let credentials = c_str!(credentials);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_create_wallet_replica(command_handle, wallet_handle, config.as_ptr(), credentials.as_ptr(), cb)
+    })
+}
+
+/// Copies encrypted records from one opened wallet to its replica without decryption.
+///
+/// # Arguments
+/// * `source_wallet_handle` - handle of the wallet to copy records from
+/// * `target_wallet_handle` - handle of the wallet to copy records to
+/// * `options_json` - JSON containing sync options.
+///   {
+///     "since": optional<int>, marker returned by the previous sync. All records are copied if not set
+///   }
+///
+/// # Returns
+/// Sync result json with number of copied and deleted records and marker to pass to the next sync
+pub fn sync_wallet(source_wallet_handle: WalletHandle, target_wallet_handle: WalletHandle, options_json: &str) -> Box<dyn Future<Item=String, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+    let err = _sync_wallet(command_handle, source_wallet_handle, target_wallet_handle, options_json, cb);
+
+    ResultHandler::str(command_handle, err, receiver)
+}
+
+fn _sync_wallet(command_handle: CommandHandle, source_wallet_handle: WalletHandle, target_wallet_handle: WalletHandle, options_json: &str, cb: Option<ResponseStringCB>) -> ErrorCode {
+    let options_json = c_str!(options_json);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_sync_wallet(command_handle, source_wallet_handle, target_wallet_handle, options_json.as_ptr(), cb)
+    })
+}
+
+/// Subscribes to changes of wallet records of the given type matching WQL query.
+///
+/// Records written by wallet import, sync_wallet, check_wallet repair and deleted on expiry are reported too.
+/// Updated record that doesn't match the query anymore is reported as removed.
+/// `callback` is called from libindy thread before the changing call completes,
+/// so it should not block. Subscription is removed when the wallet is closed.
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle (created by open_wallet)
+/// * `xtype` - type of records to watch
+/// * `query_json` - WQL query to filter watched records (see open_wallet_search).
+///   Only tags are evaluated, "{}" matches all records of the type.
+/// * `callback` - handler that takes operation ("add", "update", "delete" or "remove") and id of changed record
+///
+/// # Returns
+/// * `subscription_handle` - handle of subscription to use in unsubscribe_wallet
+pub fn subscribe_wallet(wallet_handle: WalletHandle, xtype: &str, query_json: &str, callback: Box<dyn Fn(&str, &str) + Send + Sync>) -> Box<dyn Future<Item=SubscriptionHandle, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_handle();
+
+    SUBSCRIPTION_CALLBACKS.lock().unwrap().insert(command_handle, Arc::from(callback));
+
+    let err = _subscribe_wallet(command_handle, wallet_handle, xtype, query_json, cb);
+
+    Box::new(ResultHandler::handle(command_handle, err, receiver)
+        .then(move |res| {
+            match res {
+                Ok(subscription_handle) => { SUBSCRIPTIONS.lock().unwrap().insert(subscription_handle, command_handle); }
+                Err(_) => { SUBSCRIPTION_CALLBACKS.lock().unwrap().remove(&command_handle); }
+            };
+            res
+        }))
+}
+
+fn _subscribe_wallet(command_handle: CommandHandle, wallet_handle: WalletHandle, xtype: &str, query_json: &str, cb: Option<ResponseI32CB>) -> ErrorCode {
+    let xtype = c_str!(xtype);
+    let query_json = c_str!(query_json);
+
+    ErrorCode::from(unsafe {
+      wallet::indy_wallet_subscribe(command_handle, wallet_handle, xtype.as_ptr(), query_json.as_ptr(), Some(_wallet_record_changed), cb)
+    })
+}
+
+extern fn _wallet_record_changed(command_handle: CommandHandle, _subscription_handle: SubscriptionHandle, operation: *const c_char, id: *const c_char) {
+    let callback = match SUBSCRIPTION_CALLBACKS.lock().unwrap().get(&command_handle) {
+        Some(callback) => callback.clone(),
+        None => return
+    };
+
+    callback(&rust_str!(operation), &rust_str!(id))
+}
+
+/// Removes subscription created by subscribe_wallet.
+///
+/// # Arguments
+/// * `subscription_handle` - subscription handle (created by subscribe_wallet)
+pub fn unsubscribe_wallet(subscription_handle: SubscriptionHandle) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _unsubscribe_wallet(command_handle, subscription_handle, cb);
+
+    Box::new(ResultHandler::empty(command_handle, err, receiver)
+        .then(move |res| {
+            if let Some(subscribe_command_handle) = SUBSCRIPTIONS.lock().unwrap().remove(&subscription_handle) {
+                SUBSCRIPTION_CALLBACKS.lock().unwrap().remove(&subscribe_command_handle);
+            }
+            res
+        }))
+}
+
+fn _unsubscribe_wallet(command_handle: CommandHandle, subscription_handle: SubscriptionHandle, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    ErrorCode::from(unsafe {
+      wallet::indy_wallet_unsubscribe(command_handle, subscription_handle, cb)
+    })
+}
+
 /// Deletes created wallet.
 pub fn delete_wallet(config: &str, credentials: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
     let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
@@ -350,6 +782,48 @@ fn _add_wallet_record(command_handle: CommandHandle, wallet_handle: WalletHandle
     })
 }
 
+/// Create a new non-secret record in the wallet with additional options
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle (created by open_wallet)
+/// * `xtype` - allows to separate different record types collections
+/// * `id` - the id of record
+/// * `value` - the value of record
+/// * `tags_json` - the record tags used for search and storing meta information as json.
+///   Tags have the same format as in `add_wallet_record`
+/// * `options_json` - record options as json:
+///   {
+///     expiresIn: (optional, int) number of seconds after which the record expires.
+///                Expired records are not returned by get and search calls and are deleted lazily.
+///                Record never expires if not set
+///   }
+pub fn add_wallet_record_with_options(wallet_handle: WalletHandle, xtype: &str, id: &str, value: &str, tags_json: Option<&str>, options_json: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _add_wallet_record_with_options(command_handle, wallet_handle, xtype, id, value, tags_json, options_json, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _add_wallet_record_with_options(command_handle: CommandHandle, wallet_handle: WalletHandle, xtype: &str, id: &str, value: &str, tags_json: Option<&str>, options_json: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let xtype = c_str!(xtype);
+    let id = c_str!(id);
+    let value = c_str!(value);
+    let tags_json_str = opt_c_str!(tags_json);
+    let options_json = c_str!(options_json);
+
+    ErrorCode::from(unsafe {
+        non_secrets::indy_add_wallet_record_with_options(command_handle,
+                                                         wallet_handle,
+                                                         xtype.as_ptr(),
+                                                         id.as_ptr(),
+                                                         value.as_ptr(),
+                                                         opt_c_ptr!(tags_json, tags_json_str),
+                                                         options_json.as_ptr(),
+                                                         cb)
+    })
+}
+
 /// Update a non-secret wallet record value
 ///
 /// # Arguments
@@ -380,6 +854,43 @@ fn _update_wallet_record_value(command_handle: CommandHandle, wallet_handle: Wal
     })
 }
 
+/// Update a non-secret wallet record value with additional options
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle (created by open_wallet)
+/// * `xtype` - allows to separate different record types collections
+/// * `id` - the id of record
+/// * `value` - the new value of record
+/// * `options_json` - record options as json:
+///   {
+///     expiresIn: (optional, int or null) number of seconds from now after which the record expires.
+///                Null makes the record never expire. Current expiry is kept if not set
+///   }
+pub fn update_wallet_record_value_with_options(wallet_handle: WalletHandle, xtype: &str, id: &str, value: &str, options_json: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _update_wallet_record_value_with_options(command_handle, wallet_handle, xtype, id, value, options_json, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _update_wallet_record_value_with_options(command_handle: CommandHandle, wallet_handle: WalletHandle, xtype: &str, id: &str, value: &str, options_json: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let xtype = c_str!(xtype);
+    let id = c_str!(id);
+    let value = c_str!(value);
+    let options_json = c_str!(options_json);
+
+    ErrorCode::from(unsafe {
+        non_secrets::indy_update_wallet_record_value_with_options(command_handle,
+                                                                  wallet_handle,
+                                                                  xtype.as_ptr(),
+                                                                  id.as_ptr(),
+                                                                  value.as_ptr(),
+                                                                  options_json.as_ptr(),
+                                                                  cb)
+    })
+}
+
 /// Update a non-secret wallet record tags
 ///
 /// # Arguments
@@ -499,6 +1010,36 @@ fn _delete_wallet_record(command_handle: CommandHandle, wallet_handle: WalletHan
     })
 }
 
+/// Execute a batch of wallet record operations atomically:
+/// either all operations are applied or none of them.
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle (created by open_wallet)
+/// * `operations_json` - list of operations to execute in the given order
+///   [
+///     {"operation": "add", "type": <record type>, "id": <record id>, "value": <record value>, "tags": <(optional) tags json>},
+///     {"operation": "updateValue", "type": <record type>, "id": <record id>, "value": <new record value>},
+///     {"operation": "addTags", "type": <record type>, "id": <record id>, "tags": <tags json>},
+///     {"operation": "updateTags", "type": <record type>, "id": <record id>, "tags": <tags json>},
+///     {"operation": "deleteTags", "type": <record type>, "id": <record id>, "tagNames": <list of tag names>},
+///     {"operation": "delete", "type": <record type>, "id": <record id>},
+///   ]
+pub fn wallet_batch_execute(wallet_handle: WalletHandle, operations_json: &str) -> Box<dyn Future<Item=(), Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+    let err = _wallet_batch_execute(command_handle, wallet_handle, operations_json, cb);
+
+    ResultHandler::empty(command_handle, err, receiver)
+}
+
+fn _wallet_batch_execute(command_handle: CommandHandle, wallet_handle: WalletHandle, operations_json: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+    let operations_json = c_str!(operations_json);
+
+    ErrorCode::from(unsafe {
+      non_secrets::indy_wallet_batch_execute(command_handle, wallet_handle, operations_json.as_ptr(), cb)
+    })
+}
+
 /// Get an wallet record by id
 ///
 /// # Arguments
@@ -561,6 +1102,11 @@ fn _get_wallet_record(command_handle: CommandHandle, wallet_handle: WalletHandle
 ///    retrieveType: (optional, false by default) Retrieve record type,
 ///    retrieveValue: (optional, true by default) Retrieve record value,
 ///    retrieveTags: (optional, false by default) Retrieve record tags,
+///    sortBy: (optional) List of plaintext (prefixed with "~") tag names to sort records by:
+///      [{"tagName": "~tagName", "order": (optional, "asc" by default) "asc" or "desc"}],
+///      records without a tag go first in ascending order,
+///    skip: (optional) Count of records to skip,
+///    limit: (optional) Max count of records to return,
 ///  }
 /// # Returns
 /// * `search_handle` - Wallet search handle that can be used later
@@ -583,13 +1129,42 @@ fn _open_wallet_search(command_handle: CommandHandle, wallet_handle: WalletHandl
     })
 }
 
+/// Open a search over all records of the wallet regardless of their type.
+///
+/// Note that unlike `open_wallet_search` this call also returns records
+/// created by libindy itself (types with "Indy::" prefix) including secrets.
+/// Returned records always contain type, value and tags.
+///
+/// Records can be fetched by small batches with `fetch_wallet_search_next_records`
+/// and search must be closed by `close_wallet_search`.
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle (created by open_wallet)
+///
+/// # Returns
+/// * `search_handle` - Wallet search handle that can be used later
+///   to fetch records by small batches (with fetch_wallet_search_next_records)
+pub fn open_wallet_all_records_search(wallet_handle: WalletHandle) -> Box<dyn Future<Item=SearchHandle, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_handle();
+
+    let err = _open_wallet_all_records_search(command_handle, wallet_handle, cb);
+
+    ResultHandler::handle(command_handle, err, receiver)
+}
+
+fn _open_wallet_all_records_search(command_handle: CommandHandle, wallet_handle: WalletHandle, cb: Option<ResponseI32CB>) -> ErrorCode {
+    ErrorCode::from(unsafe {
+      non_secrets::indy_open_wallet_all_records_search(command_handle, wallet_handle, cb)
+    })
+}
+
 /// Fetch next records for wallet search.
 ///
 /// Not if there are no records this call returns WalletNoRecords error.
 ///
 /// # Arguments
 /// * `wallet_handle` - wallet handle (created by open_wallet)
-/// * `wallet_search_handle` - wallet search handle (created by indy_open_wallet_search)
+/// * `wallet_search_handle` - wallet search handle (created by indy_open_wallet_search or indy_open_wallet_all_records_search)
 /// * `count` - Count of records to fetch
 ///
 /// # Returns
@@ -635,6 +1210,47 @@ fn _close_wallet_search(command_handle: CommandHandle, wallet_search_handle: Sea
     })
 }
 
+/// Count wallet records matching the query grouped by values of plaintext tags.
+///
+/// Records are counted by wallet storage without fetching and decrypting them,
+/// so only plaintext (prefixed with "~") tags can be used for grouping.
+///
+/// # Arguments
+/// * `wallet_handle` - wallet handle (created by open_wallet)
+/// * `xtype` - allows to separate different record types collections
+/// * `query_json` - MongoDB style query to wallet record tags (the same as for open_wallet_search)
+/// * `aggregation_json` -
+///  {
+///    groupBy: (optional) List of plaintext (prefixed with "~") tag names to group records by.
+///      Only total count is calculated if it is omitted or empty
+///  }
+/// # Returns
+/// * `aggregation result json`
+/// {
+///   count: <int>, // total count of records matching the query
+///   groups: [{ // distinct combinations of grouped tag values ordered by these values
+///       tags: {"~tagName": "tagValue", ...}, // null value if records don't have the tag
+///       count: <int>, // count of records in group
+///   }],
+/// }
+pub fn wallet_aggregate(wallet_handle: WalletHandle, xtype: &str, query_json: &str, aggregation_json: &str) -> Box<dyn Future<Item=String, Error=IndyError>> {
+    let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+    let err = _wallet_aggregate(command_handle, wallet_handle, xtype, query_json, aggregation_json, cb);
+
+    ResultHandler::str(command_handle, err, receiver)
+}
+
+fn _wallet_aggregate(command_handle: CommandHandle, wallet_handle: WalletHandle, xtype: &str, query_json: &str, aggregation_json: &str, cb: Option<ResponseStringCB>) -> ErrorCode {
+    let xtype = c_str!(xtype);
+    let query_json = c_str!(query_json);
+    let aggregation_json = c_str!(aggregation_json);
+
+    ErrorCode::from(unsafe {
+      non_secrets::indy_wallet_aggregate(command_handle, wallet_handle, xtype.as_ptr(), query_json.as_ptr(), aggregation_json.as_ptr(), cb)
+    })
+}
+
 fn _default_credentials(credentials: Option<&str>) -> CString {
     match credentials {
         Some(s) => c_str!(s),